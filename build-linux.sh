@@ -10,91 +10,18 @@ if [[ "$OSTYPE" != "linux-gnu"* ]]; then
     echo ""
 fi
 
-# Create a temporary Linux-specific Cargo.toml
+# Create a temporary Linux-specific Cargo.toml from the real one so its dependencies and
+# features stay current; only the GUI binary and the tray dependencies differ. src/lib.rs
+# already leaves out the macOS-only modules on Linux.
 echo "📦 Creating Linux-specific build configuration..."
 
-cat > Cargo.linux.tmp.toml << 'EOF'
-[package]
-name = "port-kill"
-version = "0.5.35"
-edition = "2021"
-authors = ["Treadie <info@treadie.com>"]
-description = "A CLI tool to help you find and free ports blocking your dev work, plus manage development caches"
-license = "FSL-1.1-MIT"
-repository = "https://github.com/treadiehq/port-kill"
-build = "build.rs"
-
-[lib]
-name = "port_kill"
-path = "src/lib.rs"
-
-[[bin]]
-name = "port-kill"
-path = "src/main_linux.rs"
-
-[[bin]]
-name = "port-kill-console"
-path = "src/main_console.rs"
-
-[dependencies]
-# Platform-agnostic dependencies (used by both GUI and console)
-crossbeam-channel = "0.5"
-tokio = { version = "1.0", features = ["full"] }
-serde = { version = "1.0", features = ["derive"] }
-serde_json = "1.0"
-serde_yaml = "0.9"
-anyhow = "1.0"
-thiserror = "1.0"
-log = "0.4"
-env_logger = "0.10"
-clap = { version = "4.0", features = ["derive"] }
-regex = "1.0"
-sysinfo = "0.30"
-chrono = { version = "0.4", features = ["serde"] }
-reqwest = { version = "0.11", features = ["json", "blocking"] }
-walkdir = "2"
-
-# Unix-specific dependencies (for process management)
-nix = { version = "0.27", features = ["signal", "process", "fs"] }
+sed 's#path = "src/main.rs"#path = "src/main_linux.rs"#' Cargo.toml > Cargo.linux.tmp.toml
+cat >> Cargo.linux.tmp.toml << 'EOF'
 
 # Linux-specific tray support
+[target.'cfg(target_os = "linux")'.dependencies]
 libappindicator = "0.7"
 gtk = "0.15"
-
-[build-dependencies]
-embed-resource = "1.8"
-
-[features]
-default = []
-embed_icon = []
-EOF
-
-# Create a temporary lib.rs that excludes macOS-specific modules
-echo "📦 Creating Linux-specific lib.rs..."
-
-cat > src/lib.linux.tmp.rs << 'EOF'
-pub mod cache;
-pub mod cli;
-pub mod command_line;
-pub mod console_app;
-pub mod endpoint_monitor;
-pub mod file_monitor;
-pub mod orchestrator;
-pub mod port_guard;
-pub mod preset_manager;
-pub mod process_monitor;
-pub mod restart_manager;
-pub mod scripting;
-pub mod security_audit;
-pub mod service_detector;
-pub mod smart_filter;
-pub mod system_monitor;
-pub mod types;
-pub mod update_check;
-
-// Exclude macOS-specific modules for Linux build
-// pub mod app;
-// pub mod tray_menu;
 EOF
 
 # Backup current files
@@ -103,14 +30,8 @@ if [ -f "Cargo.toml" ]; then
     echo "📦 Backed up macOS Cargo.toml"
 fi
 
-if [ -f "src/lib.rs" ]; then
-    cp src/lib.rs src/lib.macos.rs.backup
-    echo "📦 Backed up macOS lib.rs"
-fi
-
 # Switch to Linux configuration
 cp Cargo.linux.tmp.toml Cargo.toml
-cp src/lib.linux.tmp.rs src/lib.rs
 echo "📦 Using Linux configuration"
 
 # Check for required Linux packages
@@ -148,18 +69,13 @@ if [ $? -eq 0 ]; then
     echo "   System tray mode requires GTK development packages"
     
     # Clean up temporary files
-    rm Cargo.linux.tmp.toml src/lib.linux.tmp.rs
+    rm Cargo.linux.tmp.toml
     
     # Restore macOS configuration
     if [ -f "Cargo.macos.toml.backup" ]; then
         cp Cargo.macos.toml.backup Cargo.toml
         echo "📦 Restored macOS Cargo.toml"
     fi
-    
-    if [ -f "src/lib.macos.rs.backup" ]; then
-        cp src/lib.macos.rs.backup src/lib.rs
-        echo "📦 Restored macOS lib.rs"
-    fi
 else
     echo "❌ Build failed!"
     echo ""
@@ -168,7 +84,7 @@ else
     echo "   2. Try console mode: ./target/release/port-kill-console --console --ports 3000,8000"
     
     # Clean up temporary files
-    rm Cargo.linux.tmp.toml src/lib.linux.tmp.rs
+    rm Cargo.linux.tmp.toml
     
     # Restore macOS configuration
     if [ -f "Cargo.macos.toml.backup" ]; then
@@ -176,10 +92,5 @@ else
         echo "📦 Restored macOS Cargo.toml"
     fi
     
-    if [ -f "src/lib.macos.rs.backup" ]; then
-        cp src/lib.macos.rs.backup src/lib.rs
-        echo "📦 Restored macOS lib.rs"
-    fi
-    
     exit 1
 fi
@@ -3,6 +3,29 @@ fn main() {
     {
         embed_icon();
     }
+
+    #[cfg(feature = "ffi")]
+    generate_header();
+}
+
+/// Write the C header for src/ffi.rs to include/port_kill.h
+#[cfg(feature = "ffi")]
+fn generate_header() {
+    println!("cargo:rerun-if-changed=src/ffi.rs");
+    println!("cargo:rerun-if-changed=cbindgen.toml");
+    let crate_dir = std::env::var("CARGO_MANIFEST_DIR").unwrap();
+    let config = cbindgen::Config::from_file(format!("{}/cbindgen.toml", crate_dir))
+        .expect("Failed to read cbindgen.toml");
+    match cbindgen::Builder::new()
+        .with_crate(&crate_dir)
+        .with_config(config)
+        .generate()
+    {
+        Ok(bindings) => {
+            bindings.write_to_file(format!("{}/include/port_kill.h", crate_dir));
+        }
+        Err(e) => println!("cargo:warning=Failed to generate include/port_kill.h: {}", e),
+    }
 }
 
 #[cfg(all(target_os = "windows", feature = "embed_icon"))]
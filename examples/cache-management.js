@@ -1,18 +0,0 @@
-// Cache Management Example
-// This script demonstrates cache management capabilities
-
-log("Starting cache management example...")
-
-// List all detected caches
-log("Listing all development caches...")
-listCaches()
-
-// Run system diagnostics
-log("Running cache diagnostics...")
-cacheDoctor()
-
-// Clean caches with safe backup
-log("Cleaning caches with safe backup...")
-cleanCaches()
-
-log("Cache management example complete!")
@@ -0,0 +1,41 @@
+#ifndef PORT_KILL_H
+#define PORT_KILL_H
+
+/* Generated by cbindgen from src/ffi.rs when building with the `ffi` feature. Do not edit. */
+
+#include <stdint.h>
+
+/**
+ * Scan ports `start_port` to `end_port` (inclusive) and return the processes
+ * listening on them as a JSON array, or NULL on failure.
+ */
+char *port_kill_scan_json(uint16_t start_port, uint16_t end_port);
+
+/**
+ * Kill the process listening on `port`. Returns 1 when a process was killed, 0 when
+ * the port was free and -1 on failure.
+ */
+int32_t port_kill_kill_port(uint16_t port);
+
+/**
+ * The error of the last failed call on this thread, or NULL. The string belongs
+ * to port-kill and stays valid until the next call on this thread.
+ */
+const char *port_kill_last_error(void);
+
+/**
+ * The port-kill version, e.g. "0.5.41". The string is static and must not be freed.
+ */
+const char *port_kill_version(void);
+
+/**
+ * Release a string returned by port-kill. NULL is ignored.
+ *
+ * # Safety
+ *
+ * `value` must be NULL or a string returned by a port-kill function that has not
+ * been freed yet.
+ */
+void port_kill_string_free(char *value);
+
+#endif /* PORT_KILL_H */
@@ -0,0 +1,20 @@
+import { EventEmitter } from 'events'
+import { Process, PortEventObject, PortWatcher, ScanOptions } from './binding'
+
+export { Process, PortEventObject, PortWatcher, ScanOptions }
+
+export function listProcesses(options?: ScanOptions | undefined | null): Promise<Array<Process>>
+export function killPort(port: number): Promise<Process | null>
+export function watchPorts(
+  options: ScanOptions | undefined | null,
+  callback: (event: PortEventObject) => void,
+): PortWatcher
+
+export declare class PortEvents extends EventEmitter {
+  constructor(options?: ScanOptions)
+  on(event: 'occupied' | 'freed', listener: (process: Process) => void): this
+  on(event: 'event', listener: (event: PortEventObject) => void): this
+  stop(): void
+}
+
+export function watch(options?: ScanOptions): PortEvents
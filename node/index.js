@@ -0,0 +1,32 @@
+const { EventEmitter } = require('events')
+const binding = require('./binding.js')
+
+/**
+ * Watch ports and emit `occupied` and `freed` with the process, and `event` with
+ * `{ kind, process }` for both. Call `stop()` to end it.
+ */
+class PortEvents extends EventEmitter {
+  constructor(options) {
+    super()
+    this._watcher = binding.watchPorts(options, (event) => {
+      this.emit(event.kind, event.process)
+      this.emit('event', event)
+    })
+  }
+
+  stop() {
+    this._watcher.stop()
+  }
+}
+
+function watch(options) {
+  return new PortEvents(options)
+}
+
+module.exports = {
+  listProcesses: binding.listProcesses,
+  killPort: binding.killPort,
+  watchPorts: binding.watchPorts,
+  watch,
+  PortEvents,
+}
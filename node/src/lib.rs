@@ -0,0 +1,154 @@
+//! Node.js bindings for port-kill, built on the `PortKill` API of the main crate.
+//! `index.js` wraps `watchPorts` in an `EventEmitter`.
+
+use napi::bindgen_prelude::*;
+use napi::threadsafe_function::{ErrorStrategy, ThreadsafeFunction, ThreadsafeFunctionCallMode};
+use napi_derive::napi;
+use port_kill::types::ProcessInfo;
+use port_kill::{PortEvent, PortKill};
+use std::time::Duration;
+
+/// A process listening on a port
+#[napi(object)]
+pub struct Process {
+    pub pid: i32,
+    pub port: u32,
+    pub name: String,
+    pub command: String,
+    pub command_line: Option<String>,
+    pub working_directory: Option<String>,
+    pub container_id: Option<String>,
+    pub container_name: Option<String>,
+    pub process_group: Option<String>,
+    pub project_name: Option<String>,
+}
+
+impl From<ProcessInfo> for Process {
+    fn from(process: ProcessInfo) -> Self {
+        Self {
+            pid: process.pid,
+            port: process.port as u32,
+            name: process.name,
+            command: process.command,
+            command_line: process.command_line,
+            working_directory: process.working_directory,
+            container_id: process.container_id,
+            container_name: process.container_name,
+            process_group: process.process_group,
+            project_name: process.project_name,
+        }
+    }
+}
+
+/// Which ports to look at. Without `ports` or a range, 2000-9000 like the CLI.
+#[napi(object)]
+#[derive(Default)]
+pub struct ScanOptions {
+    pub ports: Option<Vec<u32>>,
+    pub start_port: Option<u32>,
+    pub end_port: Option<u32>,
+    pub ignore_ports: Option<Vec<u32>>,
+    pub ignore_processes: Option<Vec<String>>,
+    pub docker: Option<bool>,
+    /// Look up each process's command line and working directory
+    pub verbose: Option<bool>,
+    /// How often `watchPorts` rescans, in milliseconds (2000 by default)
+    pub interval_ms: Option<u32>,
+}
+
+/// `"occupied"` when a process took a port, `"freed"` when it let go of it
+#[napi(object)]
+pub struct PortEventObject {
+    pub kind: String,
+    pub process: Process,
+}
+
+fn port(value: u32) -> Result<u16> {
+    u16::try_from(value).map_err(|_| Error::from_reason(format!("{} is not a valid port", value)))
+}
+
+fn ports(values: Option<Vec<u32>>) -> Result<Vec<u16>> {
+    values.unwrap_or_default().into_iter().map(port).collect()
+}
+
+fn build(options: Option<ScanOptions>) -> Result<PortKill> {
+    let options = options.unwrap_or_default();
+    let mut builder = PortKill::builder()
+        .ports(ports(options.ports)?)
+        .ignore_ports(ports(options.ignore_ports)?)
+        .ignore_processes(options.ignore_processes.unwrap_or_default())
+        .docker(options.docker.unwrap_or(false))
+        .verbose(options.verbose.unwrap_or(false));
+    if options.start_port.is_some() || options.end_port.is_some() {
+        let start = port(options.start_port.unwrap_or(2000))?;
+        let end = port(options.end_port.unwrap_or(9000))?;
+        builder = builder.ports(start..=end);
+    }
+    if let Some(interval) = options.interval_ms {
+        builder = builder.scan_interval(Duration::from_millis(interval as u64));
+    }
+    builder.build().map_err(reason)
+}
+
+fn reason(e: anyhow::Error) -> Error {
+    Error::from_reason(e.to_string())
+}
+
+/// The processes listening on the given ports, sorted by port
+#[napi]
+pub async fn list_processes(options: Option<ScanOptions>) -> Result<Vec<Process>> {
+    let processes = build(options)?.scan().await.map_err(reason)?;
+    Ok(processes.into_iter().map(Process::from).collect())
+}
+
+/// Kill whatever listens on `port`; resolves to the killed process, or null when the
+/// port was free
+#[napi]
+pub async fn kill_port(port_number: u32) -> Result<Option<Process>> {
+    let port_number = port(port_number)?;
+    let port_kill = PortKill::builder().port(port_number).build().map_err(reason)?;
+    let killed = port_kill.kill_port(port_number).await.map_err(reason)?;
+    Ok(killed.map(Process::from))
+}
+
+/// A running `watchPorts`, stopped with `stop()`
+#[napi]
+pub struct PortWatcher {
+    task: Option<tokio::task::JoinHandle<()>>,
+}
+
+#[napi]
+impl PortWatcher {
+    #[napi]
+    pub fn stop(&mut self) {
+        if let Some(task) = self.task.take() {
+            task.abort();
+        }
+    }
+}
+
+/// Call `callback` with each port event until the watcher is stopped. Ports already
+/// taken are reported as occupied first.
+#[napi(ts_args_type = "options: ScanOptions | undefined | null, callback: (event: PortEventObject) => void")]
+pub fn watch_ports(options: Option<ScanOptions>, callback: JsFunction) -> Result<PortWatcher> {
+    let port_kill = build(options)?;
+    let callback: ThreadsafeFunction<PortEventObject, ErrorStrategy::Fatal> =
+        callback.create_threadsafe_function(0, |ctx| Ok(vec![ctx.value]))?;
+    let task = spawn(async move {
+        let mut events = port_kill.events();
+        while let Some(event) = events.recv().await {
+            let (kind, process) = match event {
+                PortEvent::Occupied(process) => ("occupied", process),
+                PortEvent::Freed(process) => ("freed", process),
+            };
+            callback.call(
+                PortEventObject {
+                    kind: kind.to_string(),
+                    process: process.into(),
+                },
+                ThreadsafeFunctionCallMode::NonBlocking,
+            );
+        }
+    });
+    Ok(PortWatcher { task: Some(task) })
+}
@@ -0,0 +1,311 @@
+use crate::history_store::HistoryStore;
+use crate::port_activity::{ActivityKind, PortActivityEvent};
+use crate::types::{HistoryFilter, ProcessHistoryEntry};
+use anyhow::Result;
+use chrono::{DateTime, Duration, Local, Timelike, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+
+/// Recent period compared against the baseline before it
+const RECENT_HOURS: i64 = 24;
+/// Days before the recent period that make up the baseline
+const BASELINE_DAYS: i64 = 14;
+/// Fewer recent kills than this on a port are never a spike
+const SPIKE_MIN_KILLS: usize = 5;
+/// How many times the baseline's daily average the recent kills must be
+const SPIKE_FACTOR: f64 = 3.0;
+/// Window in which a port being taken over and over counts as respawning
+const RESPAWN_WINDOW_MINUTES: i64 = 10;
+/// Starts inside the window needed to call it respawning
+const RESPAWN_MIN_STARTS: usize = 4;
+/// Longest typical gap between starts that still counts as respawning
+const RESPAWN_MAX_GAP_SECS: i64 = 30;
+/// Baseline occupations of a port needed before an hour can look unusual
+const UNUSUAL_MIN_OCCUPATIONS: usize = 20;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AnomalyKind {
+    /// Far more kills on a port than it usually sees
+    KillSpike,
+    /// A process that comes back every few seconds
+    Respawning,
+    /// A listener at an hour the port is never used
+    UnusualHour,
+}
+
+/// Something in the kill history or port activity that breaks the usual pattern
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Anomaly {
+    pub kind: AnomalyKind,
+    pub port: u16,
+    pub process: Option<String>,
+    /// Latest evidence of it
+    pub at: DateTime<Utc>,
+    pub description: String,
+}
+
+/// The anomalies in `kills` and port `activity` as of `now`
+pub fn detect_anomalies(
+    kills: &[ProcessHistoryEntry],
+    activity: &[PortActivityEvent],
+    now: DateTime<Utc>,
+) -> Vec<Anomaly> {
+    let mut anomalies = kill_spikes(kills, now);
+    anomalies.extend(respawning(activity, now));
+    anomalies.extend(unusual_hours(activity, now));
+    anomalies.sort_by_key(|anomaly| std::cmp::Reverse(anomaly.at));
+    anomalies
+}
+
+/// The anomalies in the history database and the saved port activity
+pub fn load_anomalies(activity: &[PortActivityEvent]) -> Result<Vec<Anomaly>> {
+    let now = Utc::now();
+    let filter = HistoryFilter {
+        since: Some(now - Duration::hours(RECENT_HOURS) - Duration::days(BASELINE_DAYS)),
+        ..Default::default()
+    };
+    let kills = HistoryStore::open_default()?.kills(&filter)?;
+    Ok(detect_anomalies(
+        kills.get_recent_entries(usize::MAX),
+        activity,
+        now,
+    ))
+}
+
+fn kill_spikes(kills: &[ProcessHistoryEntry], now: DateTime<Utc>) -> Vec<Anomaly> {
+    let recent_since = now - Duration::hours(RECENT_HOURS);
+    let baseline_since = recent_since - Duration::days(BASELINE_DAYS);
+    let mut recent: HashMap<u16, Vec<&ProcessHistoryEntry>> = HashMap::new();
+    let mut baseline: HashMap<u16, usize> = HashMap::new();
+    for kill in kills {
+        if kill.killed_at >= recent_since && kill.killed_at <= now {
+            recent.entry(kill.port).or_default().push(kill);
+        } else if kill.killed_at >= baseline_since && kill.killed_at < recent_since {
+            *baseline.entry(kill.port).or_default() += 1;
+        }
+    }
+
+    recent
+        .into_iter()
+        .filter_map(|(port, kills)| {
+            let usual = baseline.get(&port).copied().unwrap_or(0) as f64 / BASELINE_DAYS as f64;
+            if kills.len() < SPIKE_MIN_KILLS || (kills.len() as f64) < SPIKE_FACTOR * usual.max(1.0) {
+                return None;
+            }
+            let last = kills.iter().max_by_key(|kill| kill.killed_at)?;
+            Some(Anomaly {
+                kind: AnomalyKind::KillSpike,
+                port,
+                process: Some(last.process_name.clone()),
+                at: last.killed_at,
+                description: format!(
+                    "{} kills on port {} in the last {}h, against {:.1} a day over the previous {} days",
+                    kills.len(),
+                    port,
+                    RECENT_HOURS,
+                    usual,
+                    BASELINE_DAYS
+                ),
+            })
+        })
+        .collect()
+}
+
+fn respawning(activity: &[PortActivityEvent], now: DateTime<Utc>) -> Vec<Anomaly> {
+    let since = now - Duration::minutes(RESPAWN_WINDOW_MINUTES);
+    let mut starts: HashMap<u16, Vec<&PortActivityEvent>> = HashMap::new();
+    for event in activity {
+        if event.kind == ActivityKind::Occupied && event.at >= since && event.at <= now {
+            starts.entry(event.port).or_default().push(event);
+        }
+    }
+
+    starts
+        .into_iter()
+        .filter_map(|(port, mut starts)| {
+            if starts.len() < RESPAWN_MIN_STARTS {
+                return None;
+            }
+            starts.sort_by_key(|event| event.at);
+            let mut gaps: Vec<i64> = starts
+                .windows(2)
+                .map(|pair| (pair[1].at - pair[0].at).num_seconds())
+                .collect();
+            gaps.sort_unstable();
+            let typical_gap = gaps[gaps.len() / 2];
+            if typical_gap > RESPAWN_MAX_GAP_SECS {
+                return None;
+            }
+            let last = starts.last()?;
+            Some(Anomaly {
+                kind: AnomalyKind::Respawning,
+                port,
+                process: Some(last.process_name.clone()),
+                at: last.at,
+                description: format!(
+                    "{} restarted {} times on port {} in {} minutes, about every {}s",
+                    last.process_name,
+                    starts.len(),
+                    port,
+                    RESPAWN_WINDOW_MINUTES,
+                    typical_gap.max(1)
+                ),
+            })
+        })
+        .collect()
+}
+
+/// Ports taken in the recent period at an hour (give or take one) they were never
+/// taken at in the baseline. Hours are compared in UTC, which keeps them consistent
+/// across the log; the description shows local time.
+fn unusual_hours(activity: &[PortActivityEvent], now: DateTime<Utc>) -> Vec<Anomaly> {
+    let recent_since = now - Duration::hours(RECENT_HOURS);
+    let mut usual_hours: HashMap<u16, (usize, HashSet<u32>)> = HashMap::new();
+    let mut recent: HashMap<u16, Vec<&PortActivityEvent>> = HashMap::new();
+    for event in activity.iter().filter(|e| e.kind == ActivityKind::Occupied) {
+        if event.at >= recent_since && event.at <= now {
+            recent.entry(event.port).or_default().push(event);
+        } else if event.at < recent_since {
+            let (count, hours) = usual_hours.entry(event.port).or_default();
+            *count += 1;
+            hours.insert(event.at.hour());
+        }
+    }
+
+    recent
+        .into_iter()
+        .filter_map(|(port, events)| {
+            let (count, hours) = usual_hours.get(&port)?;
+            if *count < UNUSUAL_MIN_OCCUPATIONS {
+                return None;
+            }
+            let usual = |hour: u32| (0..3).any(|offset| hours.contains(&((hour + 23 + offset) % 24)));
+            let event = events
+                .into_iter()
+                .filter(|event| !usual(event.at.hour()))
+                .max_by_key(|event| event.at)?;
+            Some(Anomaly {
+                kind: AnomalyKind::UnusualHour,
+                port,
+                process: Some(event.process_name.clone()),
+                at: event.at,
+                description: format!(
+                    "{} (PID {}) started listening on port {} at {}, an hour the port is not normally used",
+                    event.process_name,
+                    event.pid,
+                    port,
+                    event.at.with_timezone(&Local).format("%H:%M")
+                ),
+            })
+        })
+        .collect()
+}
+
+/// Remembers the anomalies already announced, so each is notified once while it lasts
+#[derive(Debug, Default)]
+pub struct AnomalyNotifier {
+    announced: HashSet<(AnomalyKind, u16)>,
+}
+
+impl AnomalyNotifier {
+    /// The anomalies in `current` that were not announced yet. Ones that went away
+    /// are forgotten, so they are announced again if they come back.
+    pub fn new_anomalies<'a>(&mut self, current: &'a [Anomaly]) -> Vec<&'a Anomaly> {
+        let keys: HashSet<(AnomalyKind, u16)> =
+            current.iter().map(|anomaly| (anomaly.kind, anomaly.port)).collect();
+        self.announced.retain(|key| keys.contains(key));
+        current
+            .iter()
+            .filter(|anomaly| self.announced.insert((anomaly.kind, anomaly.port)))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn kill(port: u16, at: DateTime<Utc>) -> ProcessHistoryEntry {
+        ProcessHistoryEntry {
+            pid: 42,
+            port,
+            process_name: "node".to_string(),
+            process_group: None,
+            project_name: None,
+            killed_at: at,
+            killed_by: "user".to_string(),
+            command_line: None,
+            working_directory: None,
+        }
+    }
+
+    fn started(port: u16, pid: i32, at: DateTime<Utc>) -> PortActivityEvent {
+        PortActivityEvent {
+            at,
+            port,
+            kind: ActivityKind::Occupied,
+            pid,
+            process_name: "worker".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_kill_spike() {
+        let now: DateTime<Utc> = "2026-03-15T12:00:00Z".parse().unwrap();
+        // 3000 is killed about once a day, then 6 times today; 8080 always sees 6 a day
+        let mut kills: Vec<ProcessHistoryEntry> = (2..=15)
+            .map(|day| kill(3000, now - Duration::days(day)))
+            .collect();
+        for day in 1..=14 {
+            for hour in 0..6 {
+                kills.push(kill(8080, now - Duration::days(day) - Duration::hours(hour)));
+            }
+        }
+        for hour in 0..6 {
+            kills.push(kill(3000, now - Duration::hours(hour)));
+            kills.push(kill(8080, now - Duration::hours(hour)));
+        }
+
+        let anomalies = detect_anomalies(&kills, &[], now);
+        assert_eq!(anomalies.len(), 1);
+        assert_eq!(anomalies[0].kind, AnomalyKind::KillSpike);
+        assert_eq!(anomalies[0].port, 3000);
+        assert!(anomalies[0].description.starts_with("6 kills on port 3000"));
+    }
+
+    #[test]
+    fn test_respawning_and_unusual_hour() {
+        let now: DateTime<Utc> = "2026-03-15T03:00:00Z".parse().unwrap();
+        let mut activity = Vec::new();
+        // 5000 is taken during working hours for weeks, then at 3am
+        for day in 2..=22 {
+            activity.push(started(5000, day as i32, now - Duration::days(day) + Duration::hours(7)));
+        }
+        activity.push(started(5000, 100, now - Duration::minutes(30)));
+        // A worker on 9000 comes back every 5 seconds
+        for i in 0..6 {
+            activity.push(started(9000, 200 + i, now - Duration::seconds(5 * i as i64)));
+        }
+        // One restart a minute ago is nothing
+        activity.push(started(4000, 300, now - Duration::minutes(1)));
+
+        let anomalies = detect_anomalies(&[], &activity, now);
+        let kinds: Vec<(AnomalyKind, u16)> =
+            anomalies.iter().map(|anomaly| (anomaly.kind, anomaly.port)).collect();
+        assert_eq!(
+            kinds,
+            vec![
+                (AnomalyKind::Respawning, 9000),
+                (AnomalyKind::UnusualHour, 5000)
+            ]
+        );
+        assert!(anomalies[0].description.contains("about every 5s"));
+
+        let mut notifier = AnomalyNotifier::default();
+        assert_eq!(notifier.new_anomalies(&anomalies).len(), 2);
+        assert!(notifier.new_anomalies(&anomalies).is_empty());
+        assert!(notifier.new_anomalies(&anomalies[1..]).is_empty());
+        assert_eq!(notifier.new_anomalies(&anomalies).len(), 1);
+    }
+}
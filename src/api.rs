@@ -0,0 +1,367 @@
+//! Programmatic API for embedding port-kill in other Rust tools, without going
+//! through the command line `Args`.
+//!
+//! ```no_run
+//! # async fn example() -> anyhow::Result<()> {
+//! use port_kill::PortKill;
+//!
+//! let port_kill = PortKill::builder().ports(3000..=4000).build()?;
+//! for process in port_kill.scan().await? {
+//!     println!("{} is on port {}", process.name, process.port);
+//! }
+//! port_kill.kill_port(3000).await?;
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::port_guard::PortGuardDaemon;
+use crate::process_monitor::ProcessMonitor;
+use crate::smart_filter::SmartFilter;
+use crate::types::{GuardStatus, ProcessInfo};
+use anyhow::{anyhow, Result};
+use crossbeam_channel::bounded;
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::{mpsc, Mutex};
+use tokio::task::JoinHandle;
+
+/// Ports scanned when the builder is given none, the same as the CLI's defaults
+const DEFAULT_PORTS: std::ops::RangeInclusive<u16> = 2000..=9000;
+const DEFAULT_SCAN_INTERVAL: Duration = Duration::from_secs(2);
+const DEFAULT_RESERVATION_FILE: &str = "~/.port-kill/reservations.json";
+
+/// A change seen by [`PortKill::events`]
+#[derive(Debug, Clone, PartialEq)]
+pub enum PortEvent {
+    /// A process started listening on a port, or a different one took it over
+    Occupied(ProcessInfo),
+    /// The process that held a port is gone
+    Freed(ProcessInfo),
+}
+
+/// Settings for a [`PortKill`], from [`PortKill::builder`]
+#[derive(Debug, Clone)]
+pub struct PortKillBuilder {
+    ports: Vec<u16>,
+    ignore_ports: HashSet<u16>,
+    ignore_processes: HashSet<String>,
+    docker: bool,
+    verbose: bool,
+    friendly_names: bool,
+    scan_interval: Duration,
+    reservation_file: String,
+}
+
+impl Default for PortKillBuilder {
+    fn default() -> Self {
+        Self {
+            ports: Vec::new(),
+            ignore_ports: HashSet::new(),
+            ignore_processes: HashSet::new(),
+            docker: false,
+            verbose: false,
+            friendly_names: false,
+            scan_interval: DEFAULT_SCAN_INTERVAL,
+            reservation_file: DEFAULT_RESERVATION_FILE.to_string(),
+        }
+    }
+}
+
+impl PortKillBuilder {
+    /// Add ports to watch, e.g. `3000..=4000` or `[3000, 8080]`
+    pub fn ports(mut self, ports: impl IntoIterator<Item = u16>) -> Self {
+        self.ports.extend(ports);
+        self
+    }
+
+    pub fn port(mut self, port: u16) -> Self {
+        self.ports.push(port);
+        self
+    }
+
+    /// Leave these ports out of scans and events
+    pub fn ignore_ports(mut self, ports: impl IntoIterator<Item = u16>) -> Self {
+        self.ignore_ports.extend(ports);
+        self
+    }
+
+    /// Leave processes with these names out of scans and events
+    pub fn ignore_processes<S: Into<String>>(mut self, names: impl IntoIterator<Item = S>) -> Self {
+        self.ignore_processes.extend(names.into_iter().map(Into::into));
+        self
+    }
+
+    /// Also report Docker containers publishing the ports
+    pub fn docker(mut self, enabled: bool) -> Self {
+        self.docker = enabled;
+        self
+    }
+
+    /// Look up each process's command line and working directory
+    pub fn verbose(mut self, enabled: bool) -> Self {
+        self.verbose = enabled;
+        self
+    }
+
+    /// Name generic runtimes after their project, like `--friendly-names`
+    pub fn friendly_names(mut self, enabled: bool) -> Self {
+        self.friendly_names = enabled;
+        self
+    }
+
+    /// How often [`PortKill::events`] rescans the ports
+    pub fn scan_interval(mut self, interval: Duration) -> Self {
+        self.scan_interval = interval;
+        self
+    }
+
+    /// Reservation file used by [`PortKill::guard`]; `~/` is expanded
+    pub fn reservation_file(mut self, path: impl Into<String>) -> Self {
+        self.reservation_file = path.into();
+        self
+    }
+
+    pub fn build(self) -> Result<PortKill> {
+        let mut ports = if self.ports.is_empty() {
+            DEFAULT_PORTS.collect()
+        } else {
+            self.ports
+        };
+        ports.sort_unstable();
+        ports.dedup();
+        if self.scan_interval.is_zero() {
+            return Err(anyhow!("The scan interval must be greater than zero"));
+        }
+
+        let smart_filter = if self.ignore_ports.is_empty() && self.ignore_processes.is_empty() {
+            None
+        } else {
+            Some(SmartFilter::new(
+                self.ignore_ports,
+                self.ignore_processes,
+                None,
+                HashSet::new(),
+                None,
+            )?)
+        };
+        // Updates are only used by `start_monitoring`; scans return their results directly
+        let (update_sender, _update_receiver) = bounded(1);
+        let mut monitor = ProcessMonitor::new_with_performance(
+            update_sender,
+            ports.clone(),
+            self.docker,
+            self.verbose,
+            smart_filter,
+            false,
+        )?;
+        monitor.set_friendly_names(self.friendly_names);
+
+        let reservation_file = match self.reservation_file.strip_prefix("~/") {
+            Some(rest) => std::env::var("HOME")
+                .map(|home| format!("{}/{}", home, rest))
+                .unwrap_or(self.reservation_file),
+            None => self.reservation_file,
+        };
+
+        Ok(PortKill {
+            monitor: Arc::new(Mutex::new(monitor)),
+            ports,
+            scan_interval: self.scan_interval,
+            reservation_file,
+        })
+    }
+}
+
+/// Scans, kills and guards a set of ports. Cheap to clone; clones share the same
+/// monitor.
+#[derive(Clone)]
+pub struct PortKill {
+    monitor: Arc<Mutex<ProcessMonitor>>,
+    ports: Vec<u16>,
+    scan_interval: Duration,
+    reservation_file: String,
+}
+
+impl PortKill {
+    pub fn builder() -> PortKillBuilder {
+        PortKillBuilder::default()
+    }
+
+    /// The watched ports, sorted
+    pub fn ports(&self) -> &[u16] {
+        &self.ports
+    }
+
+    /// The processes listening on the watched ports, sorted by port
+    pub async fn scan(&self) -> Result<Vec<ProcessInfo>> {
+        let processes = self.monitor.lock().await.scan_processes().await?;
+        Ok(sorted(processes))
+    }
+
+    /// Kill whatever listens on `port`, returning the process that was killed, or
+    /// `None` when the port was free. Kills are recorded in the history like the
+    /// CLI's.
+    pub async fn kill_port(&self, port: u16) -> Result<Option<ProcessInfo>> {
+        let mut monitor = self.monitor.lock().await;
+        let processes = monitor.scan_processes().await?;
+        let Some(process) = processes.get(&port).cloned() else {
+            return Ok(None);
+        };
+        monitor.kill_process(process.pid).await?;
+        Ok(Some(process))
+    }
+
+    /// Start Port Guard on the watched ports in the background. With `auto_resolve`
+    /// it kills processes that take a port reserved for someone else.
+    pub async fn guard(&self, auto_resolve: bool) -> Result<Guard> {
+        let daemon = Arc::new(PortGuardDaemon::new(
+            self.ports.clone(),
+            self.reservation_file.clone(),
+            auto_resolve,
+            self.monitor.clone(),
+        ));
+        let running = daemon.clone();
+        let task = tokio::spawn(async move { running.start().await });
+        Ok(Guard { daemon, task })
+    }
+
+    /// A stream of ports being taken and freed, from a rescan every scan interval.
+    /// Ports already taken when it starts are reported as occupied first. Scanning
+    /// stops once the receiver is dropped.
+    pub fn events(&self) -> mpsc::UnboundedReceiver<PortEvent> {
+        let (sender, receiver) = mpsc::unbounded_channel();
+        let monitor = self.monitor.clone();
+        let interval = self.scan_interval;
+        tokio::spawn(async move {
+            let mut previous = HashMap::new();
+            while !sender.is_closed() {
+                let scanned = monitor.lock().await.scan_processes().await;
+                match scanned {
+                    Ok(current) => {
+                        for event in diff(&previous, &current) {
+                            if sender.send(event).is_err() {
+                                return;
+                            }
+                        }
+                        previous = current;
+                    }
+                    Err(e) => log::warn!("Port scan failed: {}", e),
+                }
+                tokio::time::sleep(interval).await;
+            }
+        });
+        receiver
+    }
+}
+
+/// Handle to the Port Guard started by [`PortKill::guard`]
+pub struct Guard {
+    daemon: Arc<PortGuardDaemon>,
+    task: JoinHandle<Result<()>>,
+}
+
+impl Guard {
+    pub async fn status(&self) -> GuardStatus {
+        self.daemon.get_status().await
+    }
+
+    /// Reserve `port` for a project's process
+    pub async fn reserve_port(&self, port: u16, project: &str, process: &str) -> Result<()> {
+        self.daemon
+            .reserve_port(port, project.to_string(), process.to_string())
+            .await
+    }
+
+    pub async fn release_port(&self, port: u16) -> Result<()> {
+        self.daemon.release_port(port).await
+    }
+
+    /// Stop the guard, saving its reservations
+    pub async fn stop(self) -> Result<()> {
+        self.daemon.stop().await?;
+        // The loop may still be between scans, or not have started yet
+        self.task.abort();
+        match self.task.await {
+            Ok(result) => result,
+            Err(e) if e.is_cancelled() => Ok(()),
+            Err(e) => Err(e.into()),
+        }
+    }
+}
+
+fn sorted(processes: HashMap<u16, ProcessInfo>) -> Vec<ProcessInfo> {
+    let mut processes: Vec<ProcessInfo> = processes.into_values().collect();
+    processes.sort_by_key(|process| process.port);
+    processes
+}
+
+fn diff(previous: &HashMap<u16, ProcessInfo>, current: &HashMap<u16, ProcessInfo>) -> Vec<PortEvent> {
+    let mut events = Vec::new();
+    for (port, before) in previous {
+        if current.get(port).map(|now| now.pid) != Some(before.pid) {
+            events.push(PortEvent::Freed(before.clone()));
+        }
+    }
+    for (port, now) in current {
+        if previous.get(port).map(|before| before.pid) != Some(now.pid) {
+            events.push(PortEvent::Occupied(now.clone()));
+        }
+    }
+    events.sort_by_key(|event| match event {
+        PortEvent::Freed(process) => (process.port, 0),
+        PortEvent::Occupied(process) => (process.port, 1),
+    });
+    events
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn process(port: u16, pid: i32) -> ProcessInfo {
+        ProcessInfo {
+            pid,
+            port,
+            command: "node".to_string(),
+            name: "node".to_string(),
+            container_id: None,
+            container_name: None,
+            command_line: None,
+            working_directory: None,
+            process_group: None,
+            project_name: None,
+            cpu_usage: None,
+            memory_usage: None,
+            memory_percentage: None,
+            protocol: crate::types::Protocol::Tcp,
+            bind_address: None,
+            address_family: None,
+        }
+    }
+
+    #[test]
+    fn test_builder_and_events_diff() {
+        let port_kill = PortKill::builder()
+            .ports(3010..=3012)
+            .port(3000)
+            .port(3011)
+            .build()
+            .unwrap();
+        assert_eq!(port_kill.ports(), &[3000, 3010, 3011, 3012]);
+        assert_eq!(PortKill::builder().build().unwrap().ports().len(), 7001);
+        assert!(PortKill::builder().scan_interval(Duration::ZERO).build().is_err());
+
+        let previous = HashMap::from([(3000, process(3000, 1)), (3010, process(3010, 2))]);
+        let current = HashMap::from([(3000, process(3000, 1)), (3010, process(3010, 3)), (3011, process(3011, 4))]);
+        assert_eq!(
+            diff(&previous, &current),
+            vec![
+                PortEvent::Freed(process(3010, 2)),
+                PortEvent::Occupied(process(3010, 3)),
+                PortEvent::Occupied(process(3011, 4)),
+            ]
+        );
+        assert!(diff(&current, &current).is_empty());
+    }
+}
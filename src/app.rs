@@ -1,13 +1,40 @@
-use crate::{cli::Args, tray_menu::TrayMenu, types::{ProcessInfo, StatusBarInfo}};
+use crate::{
+    anomaly::AnomalyNotifier,
+    cli::Args,
+    config_reload::spawn_tray_reloader,
+    docker_containers::{ContainerAction, DockerContainer},
+    login_item,
+    notify::{send_actionable_notification, send_desktop_notification, NotificationAction},
+    port_activity::PortActivityLog,
+    port_guard::{read_reservation_file, release_port_in_file, reserve_port_in_file},
+    process_monitor::notify_anomalies,
+    quick_actions::{console_binary, HotkeyAction, QuickAction, QUICK_ACTION_PREFIX},
+    restart_manager::RestartManager,
+    system_monitor::SystemMonitor,
+    tray_icon_image,
+    tray_menu::{
+        RestartMenuItems, TrayMenu, HEALTH_MENU_ID, KILL_PROJECT_PREFIX, LOGIN_ITEM_ID,
+        PIN_GUARD_PREFIX, PIN_PREFIX, UNPIN_PREFIX,
+    },
+    tray_status::{pin_notifications, HealthSummary, IconLevel, PinnedStatus, TrayAlerts},
+    types::{ProcessInfo, StatusBarInfo},
+    user_config::UserConfig,
+};
 use anyhow::Result;
 use crossbeam_channel::{bounded, Receiver};
+#[cfg(target_os = "macos")]
+use global_hotkey::{hotkey::HotKey, GlobalHotKeyEvent, GlobalHotKeyManager, HotKeyState};
 use log::{error, info, warn};
 use std::collections::HashMap;
+use std::path::Path;
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
 use std::sync::Mutex as StdMutex;
 #[cfg(target_os = "macos")]
-use tray_icon::{menu::MenuEvent, TrayIcon, TrayIconBuilder};
+use tray_icon::{
+    menu::{MenuEvent, MenuItem},
+    TrayIcon, TrayIconBuilder,
+};
 #[cfg(target_os = "macos")]
 use winit::event_loop::EventLoop;
 
@@ -28,7 +55,9 @@ impl PortKillApp {
         let (menu_sender, menu_event_receiver) = bounded(100);
 
         // Create tray menu
-        let tray_menu = TrayMenu::new(menu_sender)?;
+        let mut tray_menu = TrayMenu::new(menu_sender)?;
+        tray_menu.set_quick_actions(args.get_tray_quick_actions());
+        tray_menu.set_layout(args.tray_menu_layout.clone());
 
         Ok(Self {
             tray_icon: Arc::new(StdMutex::new(None)),
@@ -49,10 +78,13 @@ impl PortKillApp {
         // Now create the tray icon after the event loop is created
         info!("Creating tray icon...");
         let initial_menu = self.tray_menu.get_current_menu()?;
+        let initial_image =
+            tray_icon_image::render(IconLevel::Idle, self.args.tray_icon_rules.style, None);
         let tray_icon = TrayIconBuilder::new()
             .with_tooltip("Port Kill - Development Port Monitor (Click or press Cmd+Shift+P)")
             .with_menu(Box::new(initial_menu))
-            .with_icon(self.tray_menu.icon.clone())
+            .with_icon(TrayMenu::create_level_icon(&initial_image)?)
+            .with_icon_as_template(initial_image.template)
             .build()?;
 
         info!("Tray icon created successfully!");
@@ -62,12 +94,34 @@ impl PortKillApp {
             *tray_icon_guard = Some(tray_icon);
         }
 
+        // Global shortcuts from `[tray.hotkeys]`; the manager must outlive the event loop
+        let hotkey_manager = match GlobalHotKeyManager::new() {
+            Ok(manager) => Some(manager),
+            Err(e) => {
+                warn!("Global hotkeys are unavailable: {}", e);
+                None
+            }
+        };
+        let mut hotkeys =
+            Self::register_hotkeys(hotkey_manager.as_ref(), &[], &self.args.tray_hotkeys);
+
         // For now, let's manually check for processes every 5 seconds in the event loop
         let tray_icon = self.tray_icon.clone();
         let mut last_check = std::time::Instant::now();
         let mut last_process_count = 0;
+        let mut activity = PortActivityLog::load();
+        let mut anomalies = AnomalyNotifier::default();
+        // Processes of the previous check, to notify about pinned ports changing hands
+        let mut last_scan: Option<HashMap<u16, crate::types::ProcessInfo>> = None;
         let mut last_menu_update = std::time::Instant::now();
         let is_killing_processes = Arc::new(AtomicBool::new(false));
+        // Ports killed by the last tray action, offered as "Undo (restart)"
+        let last_killed: Arc<StdMutex<Vec<u16>>> = Arc::new(StdMutex::new(Vec::new()));
+        // Alerts already announced, so each one is notified once
+        let mut last_alerts = TrayAlerts::default();
+        // Source of the menu's health header, which is updated on every check
+        let mut system_monitor = SystemMonitor::new();
+        let mut health_item: Option<MenuItem> = None;
         // Track when menu was last interacted with to avoid updating during interaction
         let mut last_menu_interaction = std::time::Instant::now() - std::time::Duration::from_secs(10);
 
@@ -83,10 +137,47 @@ impl PortKillApp {
         let menu_event_receiver = self.menu_event_receiver.clone();
         let current_processes = self.current_processes.clone();
         let menu_id_to_port = self.menu_id_to_port.clone();
-        let args = self.args.clone();
+        let mut args = self.args.clone();
+
+        // Config, preset and reservation edits arrive here while the tray is running
+        let reloaded_args: Arc<StdMutex<Option<Args>>> = Arc::new(StdMutex::new(None));
+        spawn_tray_reloader(self.args.clone(), reloaded_args.clone());
 
         // Run the event loop
         event_loop.run(move |_event, _elwt| {
+            if let Some(updated) = reloaded_args.lock().ok().and_then(|mut slot| slot.take()) {
+                if updated.tray_hotkeys != args.tray_hotkeys {
+                    hotkeys = Self::register_hotkeys(hotkey_manager.as_ref(), &hotkeys, &updated.tray_hotkeys);
+                }
+                if updated.tray_menu_layout != args.tray_menu_layout || updated.tray_pins != args.tray_pins {
+                    // Rebuild the menu on the next check even if the process count is the same
+                    last_process_count = usize::MAX;
+                }
+                args = updated;
+                // Rescan right away so the menu reflects the new ports and filters
+                last_check = std::time::Instant::now() - std::time::Duration::from_secs(10);
+            }
+
+            // Handle global hotkeys; they work without opening the menu, so they are not
+            // held back by menu interaction
+            if let Ok(event) = GlobalHotKeyEvent::receiver().try_recv() {
+                if event.state == HotKeyState::Pressed {
+                    if let Some((_, action)) = hotkeys.iter().find(|(hotkey, _)| hotkey.id() == event.id) {
+                        info!("Hotkey pressed: {}", action);
+                        let action = action.clone();
+                        let processes = current_processes
+                            .lock()
+                            .map(|guard| guard.clone())
+                            .unwrap_or_default();
+                        let args_clone = args.clone();
+                        let last_killed_clone = last_killed.clone();
+                        std::thread::spawn(move || {
+                            Self::run_hotkey_action(&action, &processes, &args_clone, &last_killed_clone);
+                        });
+                    }
+                }
+            }
+
             // Handle menu events with crash-safe approach
             if let Ok(event) = menu_event_receiver.try_recv() {
                 info!("Menu event received: {:?}", event);
@@ -104,6 +195,7 @@ impl PortKillApp {
                     let is_killing_clone = is_killing_processes.clone();
                     let args_clone = args.clone();
                     let menu_id_to_port_clone = menu_id_to_port.clone();
+                    let last_killed_clone = last_killed.clone();
                     
                     std::thread::spawn(move || {
                         // Add a delay to ensure the menu system is stable
@@ -123,8 +215,106 @@ impl PortKillApp {
                             
                             if menu_id == "kill_all" {
                                 info!("Kill All Processes clicked, killing all processes...");
+                                let saved = Self::save_for_restart(processes.values());
                                 let ports_to_kill = args_clone.get_ports_to_monitor();
-                                Self::kill_all_processes(&ports_to_kill, &args_clone)
+                                let result = Self::kill_all_processes(&ports_to_kill, &args_clone);
+                                if result.is_ok() {
+                                    if let Ok(mut last_killed_guard) = last_killed_clone.lock() {
+                                        *last_killed_guard = saved;
+                                    }
+                                }
+                                result
+                            } else if menu_id == "undo" {
+                                let ports = last_killed_clone
+                                    .lock()
+                                    .map(|mut guard| std::mem::take(&mut *guard))
+                                    .unwrap_or_default();
+                                info!("Undo clicked, restarting ports {:?}...", ports);
+                                Self::restart_ports(&ports)
+                            } else if let Some(port) = menu_id
+                                .strip_prefix("restart:")
+                                .and_then(|p| p.parse::<u16>().ok())
+                            {
+                                info!("Restart clicked for port {}", port);
+                                Self::restart_ports(&[port])
+                            } else if let Some(project) = menu_id.strip_prefix(KILL_PROJECT_PREFIX) {
+                                info!("Kill All in {} clicked", project);
+                                let project_processes: Vec<&ProcessInfo> = processes
+                                    .values()
+                                    .filter(|p| p.get_workspace_name().as_deref() == Some(project))
+                                    .collect();
+                                let saved = Self::save_for_restart(project_processes.iter().copied());
+                                let result = project_processes
+                                    .iter()
+                                    .try_for_each(|p| Self::kill_single_process(p, &args_clone));
+                                if result.is_ok() {
+                                    if let Ok(mut last_killed_guard) = last_killed_clone.lock() {
+                                        *last_killed_guard = saved;
+                                    }
+                                }
+                                result
+                            } else if let Some(spec) = menu_id.strip_prefix(QUICK_ACTION_PREFIX) {
+                                info!("Quick action '{}' clicked", spec);
+                                match spec.parse::<QuickAction>().and_then(|action| action.run()) {
+                                    Ok(summary) => send_desktop_notification("Port Kill", &summary),
+                                    Err(e) => {
+                                        error!("Quick action '{}' failed: {}", spec, e);
+                                        send_desktop_notification("Port Kill", &e.to_string());
+                                    }
+                                }
+                                Ok(())
+                            } else if let Some((action, container_id)) = ContainerAction::parse_menu_id(&menu_id) {
+                                info!("Docker {} clicked for container {}", action.label(), container_id);
+                                match action.run(&container_id) {
+                                    Ok(summary) => send_desktop_notification("Port Kill", &summary),
+                                    Err(e) => {
+                                        error!("{}", e);
+                                        send_desktop_notification("Port Kill", &e.to_string());
+                                    }
+                                }
+                                Ok(())
+                            } else if let Some(port) = menu_id
+                                .strip_prefix(PIN_PREFIX)
+                                .and_then(|p| p.parse::<u16>().ok())
+                            {
+                                info!("Watch clicked for port {}", port);
+                                Self::update_pins(UserConfig::pin_port(&UserConfig::default_path(), port), port, "pinned");
+                                Ok(())
+                            } else if let Some(port) = menu_id
+                                .strip_prefix(UNPIN_PREFIX)
+                                .and_then(|p| p.parse::<u16>().ok())
+                            {
+                                info!("Unwatch clicked for port {}", port);
+                                Self::update_pins(UserConfig::unpin_port(&UserConfig::default_path(), port), port, "unpinned");
+                                Ok(())
+                            } else if let Some(port) = menu_id
+                                .strip_prefix(PIN_GUARD_PREFIX)
+                                .and_then(|p| p.parse::<u16>().ok())
+                            {
+                                info!("Guard clicked for pinned port {}", port);
+                                match Self::toggle_guard(port, processes, &args_clone) {
+                                    Ok(summary) => send_desktop_notification("Port Kill", &summary),
+                                    Err(e) => {
+                                        error!("Failed to update the guard for port {}: {}", port, e);
+                                        send_desktop_notification("Port Kill", &e.to_string());
+                                    }
+                                }
+                                Ok(())
+                            } else if menu_id == LOGIN_ITEM_ID {
+                                let result = if login_item::is_installed() {
+                                    login_item::remove().map(|_| "Port Kill will no longer start at login".to_string())
+                                } else {
+                                    login_item::install(&login_item::current_arguments())
+                                        .map(|_| "Port Kill will start at login".to_string())
+                                };
+                                match result {
+                                    Ok(summary) => send_desktop_notification("Port Kill", &summary),
+                                    Err(e) => {
+                                        error!("Failed to update the login item: {}", e);
+                                        send_desktop_notification("Port Kill", &e.to_string());
+                                    }
+                                }
+                                Ok(())
                             } else if menu_id == "quit" {
                                 info!("Quit clicked, exiting gracefully...");
                                 std::process::exit(0);
@@ -136,7 +326,15 @@ impl PortKillApp {
                                     if let Some(&port) = menu_id_guard.get(&menu_id) {
                                         if let Some(process_info) = processes.get(&port) {
                                             info!("Killing specific process on port {} with PID {}", port, process_info.pid);
-                                            Self::kill_single_process(process_info, &args_clone)
+                                            let saved = Self::save_for_restart(std::iter::once(process_info));
+                                            let result = Self::kill_single_process(process_info, &args_clone);
+                                            if result.is_ok() {
+                                                Self::notify_killed(process_info, &saved);
+                                                if let Ok(mut last_killed_guard) = last_killed_clone.lock() {
+                                                    *last_killed_guard = saved;
+                                                }
+                                            }
+                                            result
                                         } else {
                                             error!("Process not found for port {}", port);
                                             Ok(())
@@ -154,6 +352,7 @@ impl PortKillApp {
                             error!("Failed to access current processes");
                             Ok(())
                         };
+                        Self::notify_daemon();
                         
                         match result {
                             Ok(_) => {
@@ -177,11 +376,16 @@ impl PortKillApp {
             if last_check.elapsed() >= std::time::Duration::from_secs(5) {
                 last_check = std::time::Instant::now();
                 
-                // Get detailed process information with crash-safe approach
-                let (process_count, processes) = if args.verbose {
+                // Get detailed process information with crash-safe approach; a running
+                // daemon has already scanned the ports
+                let (process_count, processes) = if let Some(processes) =
+                    Self::get_processes_from_daemon(&Self::tray_ports(&args), &args)
+                {
+                    (processes.len(), processes)
+                } else if args.verbose {
                     // Use ProcessMonitor for verbose information
                     match std::panic::catch_unwind(|| {
-                        Self::get_processes_on_ports_verbose(&args.get_ports_to_monitor(), &args)
+                        Self::get_processes_on_ports_verbose(&Self::tray_ports(&args), &args)
                     }) {
                         Ok(result) => result,
                         Err(e) => {
@@ -192,7 +396,7 @@ impl PortKillApp {
                 } else {
                     // Use simple process detection for non-verbose mode
                     match std::panic::catch_unwind(|| {
-                        Self::get_processes_on_ports(&args.get_ports_to_monitor(), &args)
+                        Self::get_processes_on_ports(&Self::tray_ports(&args), &args)
                     }) {
                         Ok(result) => result,
                         Err(e) => {
@@ -202,7 +406,46 @@ impl PortKillApp {
                     }
                 };
                 
-                let status_info = StatusBarInfo::from_process_count(process_count);
+                if activity.record_scan(&Self::tray_ports(&args), &processes, chrono::Utc::now()) {
+                    if let Err(e) = activity.save() {
+                        warn!("Failed to save port activity: {}", e);
+                    }
+                    notify_anomalies(&mut anomalies, &activity);
+                }
+                if let Some(ref before) = last_scan {
+                    for message in pin_notifications(&args.tray_pins, before, &processes) {
+                        send_desktop_notification("Port Kill", &message);
+                    }
+                }
+                last_scan = Some(processes.clone());
+
+                let mut status_info = StatusBarInfo::from_process_count(process_count);
+                let alerts = TrayAlerts::detect(&processes, &args);
+                for (port, description) in alerts.new_since(&last_alerts, &args.tray_icon_rules.alert_on) {
+                    if let Some(process_info) = processes.get(&port) {
+                        Self::notify_with_actions(
+                            format!(
+                                "{}: {} (PID {}) on port {}",
+                                description, process_info.name, process_info.pid, port
+                            ),
+                            process_info.clone(),
+                            vec![NotificationAction::IgnoreProcess, NotificationAction::OpenDetails],
+                        );
+                    }
+                }
+                last_alerts = alerts.clone();
+                system_monitor.refresh();
+                let health = HealthSummary::collect(
+                    &system_monitor,
+                    &processes,
+                    args.get_ports_to_monitor().len(),
+                );
+                for alert in alerts.describe() {
+                    status_info.tooltip.push_str(&format!("\n⚠️ {}", alert));
+                }
+                let icon_level = args.tray_icon_rules.level(process_count, &alerts);
+                let badge = args.tray_icon_rules.badge(process_count, &alerts);
+                let icon_image = tray_icon_image::render(icon_level, args.tray_icon_rules.style, badge);
                 println!("🔄 Port Status: {} - {}", status_info.text, status_info.tooltip);
                 
                 // Update current processes
@@ -267,11 +510,15 @@ impl PortKillApp {
                             error!("Failed to update tooltip: {}", e);
                         }
                         
-                        // Update icon with new status
-                        if let Ok(new_icon) = TrayMenu::create_icon(&status_info.text) {
+                        // Update icon with new status; the badge is drawn into the image
+                        if let Ok(new_icon) = TrayMenu::create_level_icon(&icon_image) {
                             if let Err(e) = icon.set_icon(Some(new_icon)) {
                                 error!("Failed to update icon: {}", e);
                             }
+                            icon.set_icon_as_template(icon_image.template);
+                        }
+                        if let Some(ref item) = health_item {
+                            item.set_text(health.header());
                         }
                         
                         // Only update menu if process count changed significantly and we're not killing processes
@@ -299,13 +546,41 @@ impl PortKillApp {
                                       process_count, valid_process_count);
                             }
                             
-                            // Only proceed if we have valid processes
-                            if !valid_processes.is_empty() {
+                            let last_killed_ports = last_killed
+                                .lock()
+                                .map(|guard| guard.clone())
+                                .unwrap_or_default();
+                            let restart_items = Self::restart_menu_items(&valid_processes, &last_killed_ports);
+                            let containers = if args.docker {
+                                Self::monitored_containers(&args)
+                            } else {
+                                Vec::new()
+                            };
+                            let pinned = Self::pinned_statuses(&args, &valid_processes, &activity);
+
+                            // Only proceed if we have valid processes, pinned ports or something to restart
+                            if !valid_processes.is_empty() || !pinned.is_empty() || !restart_items.is_empty() {
                                 // Use a try-catch approach to prevent crashes
                                 match std::panic::catch_unwind(|| {
-                                    TrayMenu::create_menu_with_verbose(&valid_processes, args.show_pid, args.verbose)
+                                    TrayMenu::create_menu_with_verbose(
+                                        &valid_processes,
+                                        args.show_pid,
+                                        args.verbose,
+                                        &restart_items,
+                                        &args.get_tray_quick_actions(),
+                                        Some(&health),
+                                        &args.tray_menu_layout,
+                                        &containers,
+                                        &pinned,
+                                    )
                                 }) {
                                     Ok(Ok((new_menu, new_menu_id_to_port))) => {
+                                        health_item = new_menu
+                                            .items()
+                                            .into_iter()
+                                            .find(|item| item.id().0 == HEALTH_MENU_ID)
+                                            .and_then(|item| item.as_menuitem().cloned());
+
                                         // SAFETY FIX: Clear the old menu first and wait for macOS to finish cleanup
                                         // This prevents a use-after-free crash when macOS tries to unregister
                                         // key equivalents from menu items that are being deallocated.
@@ -353,6 +628,27 @@ impl PortKillApp {
         Ok(())
     }
 
+    /// Processes on `ports` from a running `port-kill daemon`, so the tray doesn't scan
+    /// on its own while one runs. None when no daemon answers.
+    fn get_processes_from_daemon(ports: &[u16], args: &Args) -> Option<HashMap<u16, ProcessInfo>> {
+        if args.no_daemon {
+            return None;
+        }
+        let rt = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .ok()?;
+        rt.block_on(crate::daemon::list_from_daemon(ports, args.udp))
+            .map(|(processes, _)| processes)
+    }
+
+    /// Let a running daemon know that something the tray did changed its ports
+    fn notify_daemon() {
+        if let Ok(rt) = tokio::runtime::Builder::new_current_thread().enable_all().build() {
+            rt.block_on(crate::daemon::rescan_daemon());
+        }
+    }
+
     pub fn get_processes_on_ports_verbose(
         ports: &[u16],
         args: &Args,
@@ -371,6 +667,8 @@ impl PortKillApp {
             None,
             true,
         ) {
+            process_monitor.set_friendly_names(args.friendly_names);
+            process_monitor.set_udp(args.udp);
             // Use tokio runtime to run the async scan_processes method
             let rt = tokio::runtime::Runtime::new().unwrap();
             match rt.block_on(process_monitor.scan_processes()) {
@@ -391,14 +689,15 @@ impl PortKillApp {
         }
 
         #[cfg(target_os = "windows")]
-        {
-            return Self::get_processes_on_ports_windows(ports, args);
-        }
+        let (count, mut processes) = Self::get_processes_on_ports_windows(ports, args);
 
         #[cfg(not(target_os = "windows"))]
-        {
-            return Self::get_processes_on_ports_unix(ports, args);
+        let (count, mut processes) = Self::get_processes_on_ports_unix(ports, args);
+
+        if args.friendly_names {
+            crate::process_monitor::apply_friendly_names(&mut processes);
         }
+        (count, processes)
     }
 
     #[cfg(not(target_os = "windows"))]
@@ -613,6 +912,9 @@ impl PortKillApp {
                 cpu_usage: None,
                 memory_usage: None,
                 memory_percentage: None,
+                protocol: crate::types::Protocol::Tcp,
+                bind_address: None,
+                address_family: None,
             };
 
             process_info.process_group = process_info.determine_process_group();
@@ -715,6 +1017,9 @@ impl PortKillApp {
                 cpu_usage: None,
                 memory_usage: None,
                 memory_percentage: None,
+                protocol: crate::types::Protocol::Tcp,
+                bind_address: None,
+                address_family: None,
             };
 
             process_info.process_group = process_info.determine_process_group();
@@ -1088,6 +1393,321 @@ impl PortKillApp {
         Ok(())
     }
 
+    /// Register the `[tray.hotkeys]` shortcuts, replacing `previous`. Shortcuts that do
+    /// not parse or are taken by another app are skipped with a warning.
+    fn register_hotkeys(
+        manager: Option<&GlobalHotKeyManager>,
+        previous: &[(HotKey, HotkeyAction)],
+        bindings: &[(String, HotkeyAction)],
+    ) -> Vec<(HotKey, HotkeyAction)> {
+        let Some(manager) = manager else {
+            return Vec::new();
+        };
+        let previous: Vec<HotKey> = previous.iter().map(|(hotkey, _)| *hotkey).collect();
+        if let Err(e) = manager.unregister_all(&previous) {
+            warn!("Failed to unregister hotkeys: {}", e);
+        }
+
+        let mut registered = Vec::new();
+        for (shortcut, action) in bindings {
+            let hotkey = match shortcut.parse::<HotKey>() {
+                Ok(hotkey) => hotkey,
+                Err(e) => {
+                    warn!("Ignoring hotkey '{}': {}", shortcut, e);
+                    continue;
+                }
+            };
+            match manager.register(hotkey) {
+                Ok(()) => {
+                    info!("Registered hotkey {} → {}", shortcut, action);
+                    registered.push((hotkey, action.clone()));
+                }
+                Err(e) => warn!("Failed to register hotkey '{}': {}", shortcut, e),
+            }
+        }
+        registered
+    }
+
+    /// Perform a hotkey's action and report the outcome in a notification
+    fn run_hotkey_action(
+        action: &HotkeyAction,
+        processes: &HashMap<u16, ProcessInfo>,
+        args: &Args,
+        last_killed: &StdMutex<Vec<u16>>,
+    ) {
+        let message = match action {
+            HotkeyAction::Kill(port) => match processes.get(port) {
+                Some(process_info) => {
+                    let saved = Self::save_for_restart(std::iter::once(process_info));
+                    match Self::kill_single_process(process_info, args) {
+                        Ok(()) => {
+                            Self::notify_killed(process_info, &saved);
+                            if let Ok(mut last_killed_guard) = last_killed.lock() {
+                                *last_killed_guard = saved;
+                            }
+                            return;
+                        }
+                        Err(e) => {
+                            error!("Failed to kill process on port {}: {}", port, e);
+                            format!(
+                                "Failed to kill {} on port {}: {}",
+                                process_info.name, port, e
+                            )
+                        }
+                    }
+                }
+                None => format!("Nothing is running on port {}", port),
+            },
+            HotkeyAction::List => {
+                let mut ports: Vec<&u16> = processes.keys().collect();
+                ports.sort_unstable();
+                if ports.is_empty() {
+                    "No processes on the monitored ports".to_string()
+                } else {
+                    ports
+                        .into_iter()
+                        .map(|port| format!(":{} {}", port, processes[port].name))
+                        .collect::<Vec<_>>()
+                        .join("\n")
+                }
+            }
+            HotkeyAction::Quick(quick_action) => match quick_action.run() {
+                Ok(summary) => summary,
+                Err(e) => {
+                    error!("Quick action '{}' failed: {}", quick_action, e);
+                    e.to_string()
+                }
+            },
+        };
+        send_desktop_notification("Port Kill", &message);
+    }
+
+    /// Announce a process killed from the tray, offering to restart it when its command
+    /// was saved, or to ignore it from now on
+    fn notify_killed(process_info: &ProcessInfo, saved: &[u16]) {
+        let mut actions = Vec::new();
+        if saved.contains(&process_info.port) {
+            actions.push(NotificationAction::Restart);
+        }
+        actions.push(NotificationAction::IgnoreProcess);
+        Self::notify_with_actions(
+            format!("Killed {} on port {}", process_info.name, process_info.port),
+            process_info.clone(),
+            actions,
+        );
+    }
+
+    /// Post a notification with action buttons about `process_info` and carry out the
+    /// chosen action. Runs on its own thread since the notification waits for an answer.
+    fn notify_with_actions(
+        message: String,
+        process_info: ProcessInfo,
+        actions: Vec<NotificationAction>,
+    ) {
+        std::thread::spawn(move || {
+            match send_actionable_notification("Port Kill", &message, &actions) {
+                Some(NotificationAction::Restart) => {
+                    if let Err(e) = Self::restart_ports(&[process_info.port]) {
+                        error!("Failed to restart port {}: {}", process_info.port, e);
+                    }
+                }
+                Some(NotificationAction::IgnoreProcess) => {
+                    // The config watcher applies the edit to the running tray
+                    let config = UserConfig::default_path();
+                    match UserConfig::add_ignored_process(&config, &process_info.name) {
+                        Ok(true) => info!(
+                            "Added {} to ignore_processes in {}",
+                            process_info.name,
+                            config.display()
+                        ),
+                        Ok(false) => info!("{} is already ignored", process_info.name),
+                        Err(e) => error!("Failed to ignore {}: {}", process_info.name, e),
+                    }
+                }
+                Some(NotificationAction::OpenDetails) => Self::open_details(process_info.port),
+                None => {}
+            }
+        });
+    }
+
+    /// Open Terminal with the verbose listing for `port`
+    fn open_details(port: u16) {
+        let command = format!(
+            "'{}' --list --ports {} --verbose --show-context",
+            console_binary()
+                .display()
+                .to_string()
+                .replace('\'', "'\\''"),
+            port
+        );
+        let script = format!(
+            "tell application \"Terminal\"\nactivate\ndo script \"{}\"\nend tell",
+            command.replace('\\', "\\\\").replace('"', "\\\"")
+        );
+        if let Err(e) = std::process::Command::new("osascript")
+            .arg("-e")
+            .arg(script)
+            .spawn()
+        {
+            error!("Failed to open details for port {}: {}", port, e);
+        }
+    }
+
+    /// Save restart commands for processes about to be killed from the tray.
+    /// Returns the ports that can be restarted afterwards.
+    fn save_for_restart<'a>(processes: impl Iterator<Item = &'a ProcessInfo>) -> Vec<u16> {
+        let mut restart_manager = match RestartManager::new() {
+            Ok(manager) => manager,
+            Err(e) => {
+                warn!("Failed to open restart history: {}", e);
+                return Vec::new();
+            }
+        };
+
+        let mut saved = Vec::new();
+        for process_info in processes {
+            match restart_manager.save_process_info(process_info) {
+                Ok(true) => saved.push(process_info.port),
+                Ok(false) => {}
+                Err(e) => warn!(
+                    "Failed to save restart info for port {}: {}",
+                    process_info.port, e
+                ),
+            }
+        }
+        saved.sort_unstable();
+        saved
+    }
+
+    fn restart_ports(ports: &[u16]) -> Result<()> {
+        let mut restart_manager = RestartManager::new()?;
+        for &port in ports {
+            match restart_manager.restart_port(port) {
+                Ok(pid) => info!("Restarted port {} with PID {}", port, pid),
+                Err(e) => error!("Failed to restart port {}: {}", port, e),
+            }
+        }
+        restart_manager.wait_for_hooks();
+        Ok(())
+    }
+
+    /// Running containers that publish a monitored port, for the menu's Docker section
+    fn monitored_containers(args: &Args) -> Vec<DockerContainer> {
+        let ports = args.get_ports_to_monitor();
+        match DockerContainer::list() {
+            Ok(containers) => containers
+                .into_iter()
+                .filter(|container| container.publishes_any(&ports))
+                .collect(),
+            Err(e) => {
+                warn!("Docker containers are unavailable: {}", e);
+                Vec::new()
+            }
+        }
+    }
+
+    /// Monitored ports plus pinned ones, which are checked even outside the monitored range
+    fn tray_ports(args: &Args) -> Vec<u16> {
+        let mut ports = args.get_ports_to_monitor();
+        for pin in &args.tray_pins {
+            if !ports.contains(&pin.port) {
+                ports.push(pin.port);
+            }
+        }
+        ports
+    }
+
+    /// Pinned ports with their holder and guard state, for the menu's pinned section
+    fn pinned_statuses(
+        args: &Args,
+        processes: &HashMap<u16, ProcessInfo>,
+        activity: &PortActivityLog,
+    ) -> Vec<PinnedStatus> {
+        let reserved: Vec<u16> =
+            read_reservation_file(Path::new(&args.get_reservation_file_path()))
+                .map(|loaded| loaded.data.into_keys().collect())
+                .unwrap_or_default();
+        PinnedStatus::collect(&args.tray_pins, processes, &reserved, |port| {
+            activity.last_holder(port).map(str::to_string)
+        })
+    }
+
+    /// Report a pin or unpin from the menu; the config reload then rebuilds the menu
+    fn update_pins(result: Result<bool>, port: u16, done: &str) {
+        match result {
+            Ok(true) => send_desktop_notification("Port Kill", &format!(":{} {}", port, done)),
+            Ok(false) => info!("Port {} was already {}", port, done),
+            Err(e) => {
+                error!("Failed to update pinned ports: {}", e);
+                send_desktop_notification("Port Kill", &e.to_string());
+            }
+        }
+    }
+
+    /// Reserve a pinned port for its holder (or its last holder), or drop the reservation
+    fn toggle_guard(
+        port: u16,
+        processes: &HashMap<u16, ProcessInfo>,
+        args: &Args,
+    ) -> Result<String> {
+        let path = args.get_reservation_file_path();
+        let path = Path::new(&path);
+        if release_port_in_file(path, port)? {
+            return Ok(format!("Stopped guarding :{}", port));
+        }
+        let (process_name, project_name) = match processes.get(&port) {
+            Some(process) => (
+                process.name.clone(),
+                process
+                    .get_workspace_name()
+                    .unwrap_or_else(|| process.name.clone()),
+            ),
+            None => {
+                let name = PortActivityLog::load()
+                    .last_holder(port)
+                    .map(str::to_string)
+                    .ok_or_else(|| {
+                        anyhow::anyhow!(
+                            "Nothing has held :{} yet, so there is nothing to guard it for",
+                            port
+                        )
+                    })?;
+                (name.clone(), name)
+            }
+        };
+        reserve_port_in_file(path, port, &project_name, &process_name)?;
+        Ok(format!(":{} is now reserved for {}", port, process_name))
+    }
+
+    /// Restart actions for ports that are free again and have a saved command
+    fn restart_menu_items(
+        processes: &HashMap<u16, ProcessInfo>,
+        last_killed: &[u16],
+    ) -> RestartMenuItems {
+        const MAX_RESTART_ITEMS: usize = 5;
+
+        let restart_manager = match RestartManager::new() {
+            Ok(manager) => manager,
+            Err(_) => return RestartMenuItems::default(),
+        };
+        let restartable = restart_manager
+            .recent_launchable(MAX_RESTART_ITEMS)
+            .into_iter()
+            .filter(|info| !processes.contains_key(&info.port) && !last_killed.contains(&info.port))
+            .map(|info| (info.port, info.display_name()))
+            .collect();
+        let undo_ports = last_killed
+            .iter()
+            .copied()
+            .filter(|port| !processes.contains_key(port))
+            .collect();
+
+        RestartMenuItems {
+            restartable,
+            undo_ports,
+        }
+    }
+
     pub fn kill_single_process(process_info: &ProcessInfo, args: &Args) -> Result<()> {
         info!("Killing single process PID: {}", process_info.pid);
 
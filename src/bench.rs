@@ -0,0 +1,328 @@
+//! `port-kill bench`: scan latency and allocations per scanner backend, port range
+//! size and scan interval, to check performance work and catch regressions.
+
+use crate::cache::output::print_or_json;
+use crate::cli::{Args, BenchArgs, BenchBackend};
+use crate::types::{ProcessInfo, Protocol};
+use anyhow::{anyhow, Result};
+use serde::Serialize;
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+
+static ALLOCATIONS: AtomicU64 = AtomicU64::new(0);
+static ALLOCATED_BYTES: AtomicU64 = AtomicU64::new(0);
+
+/// The system allocator, counting allocations for `port-kill bench`. The binaries
+/// install it as their global allocator when built with the `bench` feature;
+/// otherwise bench reports no allocation counts.
+pub struct CountingAllocator;
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        count(layout.size());
+        System.alloc(layout)
+    }
+
+    unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        count(layout.size());
+        System.alloc_zeroed(layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        count(new_size);
+        System.realloc(ptr, layout, new_size)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
+
+fn count(size: usize) {
+    ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+    ALLOCATED_BYTES.fetch_add(size as u64, Ordering::Relaxed);
+}
+
+fn allocation_counts() -> (u64, u64) {
+    (
+        ALLOCATIONS.load(Ordering::Relaxed),
+        ALLOCATED_BYTES.load(Ordering::Relaxed),
+    )
+}
+
+/// Whether `CountingAllocator` is the global allocator of this process
+fn counting_allocations() -> bool {
+    let (before, _) = allocation_counts();
+    drop(std::hint::black_box(Box::new(0u64)));
+    allocation_counts().0 > before
+}
+
+/// Timings of one backend, range size and interval
+#[derive(Debug, Serialize)]
+pub struct BenchResult {
+    pub backend: &'static str,
+    pub ports: usize,
+    pub interval_ms: u64,
+    pub iterations: usize,
+    /// Listeners found by the last scan
+    pub found: usize,
+    pub p50_ms: f64,
+    pub p95_ms: f64,
+    pub max_ms: f64,
+    /// Mean per scan; absent when allocations aren't being counted
+    pub allocations: Option<u64>,
+    pub allocated_bytes: Option<u64>,
+}
+
+pub async fn run_bench(args: &Args, bench: &BenchArgs) -> Result<()> {
+    if bench.iterations == 0 {
+        return Err(anyhow!("--iterations must be at least 1"));
+    }
+    let counting = counting_allocations();
+    let mut results = Vec::new();
+
+    for &backend in &bench.backends {
+        if let Err(e) = scan(backend, &[args.start_port], args) {
+            eprintln!("⚠️  Skipping the {} backend: {}", backend.name(), e);
+            continue;
+        }
+        for &size in &bench.sizes {
+            let ports = port_range(args.start_port, size);
+            for &interval_ms in &bench.intervals {
+                if !bench.json {
+                    eprintln!(
+                        "⏱️  {} on {} ports every {}ms...",
+                        backend.name(),
+                        ports.len(),
+                        interval_ms
+                    );
+                }
+                let result = measure(backend, &ports, interval_ms, bench.iterations, counting, args)?;
+                results.push(result);
+            }
+        }
+    }
+
+    if bench.json {
+        print_or_json(&results, true);
+        return Ok(());
+    }
+    println!(
+        "{:<8} {:>6} {:>9} {:>6} {:>10} {:>10} {:>10} {:>12} {:>12}",
+        "BACKEND", "PORTS", "INTERVAL", "FOUND", "P50", "P95", "MAX", "ALLOCS/SCAN", "BYTES/SCAN"
+    );
+    for result in &results {
+        let optional = |value: Option<u64>| value.map_or("-".to_string(), |value| value.to_string());
+        println!(
+            "{:<8} {:>6} {:>9} {:>6} {:>10} {:>10} {:>10} {:>12} {:>12}",
+            result.backend,
+            result.ports,
+            format!("{}ms", result.interval_ms),
+            result.found,
+            format!("{:.2}ms", result.p50_ms),
+            format!("{:.2}ms", result.p95_ms),
+            format!("{:.2}ms", result.max_ms),
+            optional(result.allocations),
+            optional(result.allocated_bytes)
+        );
+    }
+    if !counting {
+        println!("(allocations are only counted in builds with --features bench)");
+    }
+    Ok(())
+}
+
+fn port_range(start: u16, size: u16) -> Vec<u16> {
+    let end = start.saturating_add(size.saturating_sub(1));
+    (start..=end).collect()
+}
+
+fn measure(
+    backend: BenchBackend,
+    ports: &[u16],
+    interval_ms: u64,
+    iterations: usize,
+    counting: bool,
+    args: &Args,
+) -> Result<BenchResult> {
+    let interval = Duration::from_millis(interval_ms);
+    let mut timings = Vec::with_capacity(iterations);
+    let (mut allocations, mut allocated_bytes) = (0, 0);
+    let mut found = 0;
+
+    for iteration in 0..iterations {
+        if iteration > 0 && !interval.is_zero() {
+            std::thread::sleep(interval);
+        }
+        let (allocations_before, bytes_before) = allocation_counts();
+        let started = Instant::now();
+        let processes = scan(backend, ports, args)?;
+        timings.push(started.elapsed());
+        let (allocations_after, bytes_after) = allocation_counts();
+        allocations += allocations_after - allocations_before;
+        allocated_bytes += bytes_after - bytes_before;
+        found = processes.len();
+    }
+
+    timings.sort_unstable();
+    let per_scan = |total: u64| counting.then_some(total / iterations as u64);
+    Ok(BenchResult {
+        backend: backend.name(),
+        ports: ports.len(),
+        interval_ms,
+        iterations,
+        found,
+        p50_ms: millis(percentile(&timings, 50)),
+        p95_ms: millis(percentile(&timings, 95)),
+        max_ms: millis(timings.last().copied().unwrap_or_default()),
+        allocations: per_scan(allocations),
+        allocated_bytes: per_scan(allocated_bytes),
+    })
+}
+
+/// Nearest-rank percentile of sorted `timings`
+fn percentile(timings: &[Duration], percent: usize) -> Duration {
+    if timings.is_empty() {
+        return Duration::ZERO;
+    }
+    let rank = (percent * timings.len()).div_ceil(100).max(1);
+    timings[rank - 1]
+}
+
+fn millis(duration: Duration) -> f64 {
+    duration.as_secs_f64() * 1000.0
+}
+
+fn scan(backend: BenchBackend, ports: &[u16], args: &Args) -> Result<HashMap<u16, ProcessInfo>> {
+    match backend {
+        BenchBackend::Lsof => {
+            let mut args = args.clone();
+            args.verbose = false;
+            args.friendly_names = false;
+            Ok(crate::process_monitor::get_processes_on_ports(ports, &args).1)
+        }
+        BenchBackend::Native => native_scan(ports),
+    }
+}
+
+/// Prototype scanner that reads the kernel's socket tables instead of running lsof:
+/// listening sockets from /proc/net/tcp{,6}, their owners from /proc/<pid>/fd.
+#[cfg(target_os = "linux")]
+fn native_scan(ports: &[u16]) -> Result<HashMap<u16, ProcessInfo>> {
+    use std::collections::HashSet;
+    use std::fs;
+
+    let wanted: HashSet<u16> = ports.iter().copied().collect();
+    let mut sockets = HashMap::new();
+    for table in ["/proc/net/tcp", "/proc/net/tcp6"] {
+        if let Ok(contents) = fs::read_to_string(table) {
+            sockets.extend(
+                parse_listening_sockets(&contents)
+                    .into_iter()
+                    .filter(|(_, port)| wanted.contains(port)),
+            );
+        }
+    }
+    if sockets.is_empty() && !std::path::Path::new("/proc/net/tcp").exists() {
+        return Err(anyhow!("/proc/net/tcp is not readable"));
+    }
+
+    let mut processes = HashMap::new();
+    for entry in fs::read_dir("/proc")?.flatten() {
+        if sockets.is_empty() {
+            break;
+        }
+        let Some(pid) = entry.file_name().to_str().and_then(|name| name.parse::<i32>().ok()) else {
+            continue;
+        };
+        let Ok(fds) = fs::read_dir(entry.path().join("fd")) else {
+            continue;
+        };
+        for fd in fds.flatten() {
+            let Ok(target) = fs::read_link(fd.path()) else {
+                continue;
+            };
+            let inode = target
+                .to_str()
+                .and_then(|target| target.strip_prefix("socket:["))
+                .and_then(|target| target.strip_suffix(']'))
+                .and_then(|inode| inode.parse::<u64>().ok());
+            let Some(port) = inode.and_then(|inode| sockets.remove(&inode)) else {
+                continue;
+            };
+            let name = fs::read_to_string(entry.path().join("comm"))
+                .map(|comm| comm.trim().to_string())
+                .unwrap_or_default();
+            processes.insert(
+                port,
+                ProcessInfo {
+                    pid,
+                    port,
+                    command: name.clone(),
+                    name,
+                    container_id: None,
+                    container_name: None,
+                    command_line: None,
+                    working_directory: None,
+                    process_group: None,
+                    project_name: None,
+                    cpu_usage: None,
+                    memory_usage: None,
+                    memory_percentage: None,
+                    protocol: Protocol::Tcp,
+                    bind_address: None,
+                    address_family: None,
+                },
+            );
+        }
+    }
+    Ok(processes)
+}
+
+#[cfg(not(target_os = "linux"))]
+fn native_scan(_ports: &[u16]) -> Result<HashMap<u16, ProcessInfo>> {
+    Err(anyhow!("the native scanner is only available on Linux"))
+}
+
+/// Socket inode to local port for the listening sockets of a /proc/net/tcp table
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+fn parse_listening_sockets(table: &str) -> HashMap<u64, u16> {
+    const TCP_LISTEN: &str = "0A";
+    table
+        .lines()
+        .skip(1)
+        .filter_map(|line| {
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            if fields.len() < 10 || fields[3] != TCP_LISTEN {
+                return None;
+            }
+            let port = u16::from_str_radix(fields[1].rsplit(':').next()?, 16).ok()?;
+            let inode = fields[9].parse().ok()?;
+            Some((inode, port))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_percentiles_and_socket_table() {
+        let timings: Vec<Duration> = (1..=20).map(Duration::from_millis).collect();
+        assert_eq!(percentile(&timings, 50), Duration::from_millis(10));
+        assert_eq!(percentile(&timings, 95), Duration::from_millis(19));
+        assert_eq!(percentile(&timings[..1], 95), Duration::from_millis(1));
+        assert_eq!(percentile(&[], 50), Duration::ZERO);
+        assert_eq!(port_range(3000, 3), vec![3000, 3001, 3002]);
+        assert_eq!(port_range(65534, 10), vec![65534, 65535]);
+
+        let table = "  sl  local_address rem_address   st tx_queue rx_queue tr tm->when retrnsmt   uid  timeout inode
+   0: 00000000:0F1D 00000000:0000 0A 00000000:00000000 00:00000000 00000000  1000        0 51234 1 0000000000000000 100 0 0 10 0
+   1: 0100007F:1F90 0100007F:C350 01 00000000:00000000 00:00000000 00000000  1000        0 51299 1 0000000000000000 20 4 30 10 -1
+";
+        assert_eq!(parse_listening_sockets(table), HashMap::from([(51234, 3869)]));
+    }
+}
@@ -0,0 +1,366 @@
+use crate::cli::Args;
+use crate::port_guard::{read_reservation_file, release_port_in_file, reserve_port_in_file};
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// First port handed out to a branch; later branches get the next free one
+pub const BRANCH_PORT_BASE: u16 = 3000;
+
+/// Marks the lines port-kill added to a hook, so installing twice does nothing
+const HOOK_MARKER: &str = "# port-kill: branch port reservations";
+
+/// Runs after `git checkout`/`git switch` and after `git worktree add`. The third
+/// argument is 1 for branch checkouts (0 for checking out files).
+const POST_CHECKOUT_HOOK: &str = r#"if [ "$3" = 1 ] && command -v port-kill >/dev/null 2>&1; then
+    port-kill --reserve-branch-port || true
+fi
+"#;
+
+/// Sees every ref update; a branch updated to the all-zero id was deleted
+const REFERENCE_TRANSACTION_HOOK: &str = r#"if [ "$1" = committed ] && command -v port-kill >/dev/null 2>&1; then
+    while read -r old new ref; do
+        case "$new" in
+            *[!0]*) ;;
+            *) case "$ref" in
+                refs/heads/*) port-kill --release-branch-port "${ref#refs/heads/}" || true ;;
+            esac ;;
+        esac
+    done
+fi
+"#;
+
+/// A port kept for one branch of one repository
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BranchPortReservation {
+    /// The repository's common git directory, shared by all its worktrees
+    pub repo: String,
+    pub branch: String,
+    pub port: u16,
+    /// Worktree the branch was last checked out in
+    pub worktree: String,
+    pub reserved_at: DateTime<Utc>,
+}
+
+/// Ports reserved per branch, so parallel worktrees each get their own dev server port
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct BranchPorts {
+    reservations: Vec<BranchPortReservation>,
+}
+
+impl BranchPorts {
+    pub fn file_path() -> PathBuf {
+        let home_dir = std::env::var("HOME")
+            .or_else(|_| std::env::var("USERPROFILE"))
+            .unwrap_or_else(|_| ".".to_string());
+        PathBuf::from(home_dir).join(".port-kill-branch-ports.json")
+    }
+
+    /// The saved reservations, or none when there is no file yet or it cannot be read
+    pub fn load() -> Self {
+        fs::read_to_string(Self::file_path())
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self) -> Result<()> {
+        let path = Self::file_path();
+        let content = serde_json::to_string_pretty(self)?;
+        fs::write(&path, content).with_context(|| format!("Failed to write {}", path.display()))
+    }
+
+    pub fn reservations(&self) -> &[BranchPortReservation] {
+        &self.reservations
+    }
+
+    pub fn find(&self, repo: &str, branch: &str) -> Option<&BranchPortReservation> {
+        self.reservations
+            .iter()
+            .find(|reservation| reservation.repo == repo && reservation.branch == branch)
+    }
+
+    /// The branch's port, reserving the lowest port from `base` that no other branch
+    /// of any repository holds, and that is not in `taken`, when it has none yet
+    pub fn reserve(
+        &mut self,
+        repo: &str,
+        branch: &str,
+        worktree: &str,
+        base: u16,
+        taken: &HashSet<u16>,
+    ) -> Result<u16> {
+        if let Some(reservation) = self
+            .reservations
+            .iter_mut()
+            .find(|reservation| reservation.repo == repo && reservation.branch == branch)
+        {
+            reservation.worktree = worktree.to_string();
+            return Ok(reservation.port);
+        }
+
+        let port = (base..=u16::MAX)
+            .find(|port| {
+                !taken.contains(port)
+                    && self.reservations.iter().all(|reservation| reservation.port != *port)
+            })
+            .ok_or_else(|| anyhow::anyhow!("No port left to reserve from {}", base))?;
+        self.reservations.push(BranchPortReservation {
+            repo: repo.to_string(),
+            branch: branch.to_string(),
+            port,
+            worktree: worktree.to_string(),
+            reserved_at: Utc::now(),
+        });
+        Ok(port)
+    }
+
+    /// Give up the branch's port. Returns the port it held.
+    pub fn release(&mut self, repo: &str, branch: &str) -> Option<u16> {
+        let index = self
+            .reservations
+            .iter()
+            .position(|reservation| reservation.repo == repo && reservation.branch == branch)?;
+        Some(self.reservations.remove(index).port)
+    }
+}
+
+/// The repository, branch and worktree of the current directory
+#[derive(Debug, Clone)]
+pub struct GitCheckout {
+    pub repo: String,
+    /// `None` on a detached HEAD
+    pub branch: Option<String>,
+    pub worktree: String,
+}
+
+impl GitCheckout {
+    pub fn current() -> Result<Self> {
+        let cwd = std::env::current_dir()?;
+        let common_dir = git_output(&cwd, &["rev-parse", "--git-common-dir"])?;
+        // Relative to the current directory in the main worktree (".git")
+        let common_dir = cwd.join(common_dir);
+        let repo = common_dir.canonicalize().unwrap_or(common_dir);
+        let worktree = git_output(&cwd, &["rev-parse", "--show-toplevel"])?;
+        let branch = git_output(&cwd, &["symbolic-ref", "--quiet", "--short", "HEAD"]).ok();
+
+        Ok(Self {
+            repo: repo.to_string_lossy().to_string(),
+            branch,
+            worktree,
+        })
+    }
+}
+
+/// The port reserved for the branch checked out in the current directory, if any
+pub fn current_branch_port() -> Option<u16> {
+    let checkout = GitCheckout::current().ok()?;
+    let branch = checkout.branch?;
+    BranchPorts::load()
+        .find(&checkout.repo, &branch)
+        .map(|reservation| reservation.port)
+}
+
+fn git_output(dir: &Path, args: &[&str]) -> Result<String> {
+    let output = Command::new("git")
+        .args(args)
+        .current_dir(dir)
+        .output()
+        .context("Failed to run git")?;
+    if !output.status.success() {
+        return Err(anyhow::anyhow!(
+            "git {} failed: {}",
+            args.join(" "),
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+/// `existing` with port-kill's `body` added: a new script when there is no hook yet,
+/// appended to a hook of the user's own, unchanged when port-kill is already in it
+fn hook_script(existing: Option<&str>, body: &str) -> Option<String> {
+    match existing {
+        Some(existing) if existing.contains(HOOK_MARKER) => None,
+        Some(existing) if !existing.trim().is_empty() => Some(format!(
+            "{}\n\n{}\n{}",
+            existing.trim_end(),
+            HOOK_MARKER,
+            body
+        )),
+        _ => Some(format!("#!/bin/sh\n{}\n{}", HOOK_MARKER, body)),
+    }
+}
+
+/// Add the post-checkout and reference-transaction hooks to the current repository.
+/// Returns the hook files that were written.
+pub fn install_git_hooks() -> Result<Vec<PathBuf>> {
+    let cwd = std::env::current_dir()?;
+    // Honors core.hooksPath and is shared by all worktrees
+    let hooks_dir = cwd.join(
+        git_output(&cwd, &["rev-parse", "--git-path", "hooks"])
+            .context("Not inside a git repository")?,
+    );
+    fs::create_dir_all(&hooks_dir)
+        .with_context(|| format!("Failed to create {}", hooks_dir.display()))?;
+
+    let mut written = Vec::new();
+    for (name, body) in [
+        ("post-checkout", POST_CHECKOUT_HOOK),
+        ("reference-transaction", REFERENCE_TRANSACTION_HOOK),
+    ] {
+        let path = hooks_dir.join(name);
+        let existing = fs::read_to_string(&path).ok();
+        let Some(script) = hook_script(existing.as_deref(), body) else {
+            continue;
+        };
+        fs::write(&path, script).with_context(|| format!("Failed to write {}", path.display()))?;
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            fs::set_permissions(&path, fs::Permissions::from_mode(0o755))?;
+        }
+        written.push(path);
+    }
+    Ok(written)
+}
+
+/// Guard reservations belong to a project named after its directory, the way
+/// `port-kill run` names the project it runs in
+fn worktree_project(worktree: &str) -> String {
+    Path::new(worktree)
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_else(|| worktree.to_string())
+}
+
+/// `--install-git-hooks`: install the hooks and reserve a port for the current branch
+pub fn run_install_git_hooks(args: &Args) -> Result<()> {
+    let written = install_git_hooks()?;
+    if written.is_empty() {
+        println!("ℹ️  The port-kill git hooks are already installed");
+    }
+    for path in &written {
+        println!("✅ Installed {}", path.display());
+    }
+    run_reserve_branch_port(args)
+}
+
+/// `--reserve-branch-port`: reserve (or look up) the current branch's port and print it.
+/// The port also goes into the guard's reservation file, so guard mode and `port-kill run`
+/// keep other worktrees off it.
+pub fn run_reserve_branch_port(args: &Args) -> Result<()> {
+    let checkout = GitCheckout::current()?;
+    let Some(branch) = checkout.branch else {
+        println!("ℹ️  HEAD is detached; no port reserved");
+        return Ok(());
+    };
+
+    let reservation_file = PathBuf::from(args.get_reservation_file_path());
+    let taken: HashSet<u16> = read_reservation_file(&reservation_file)
+        .map(|versioned| versioned.data.into_keys().collect())
+        .unwrap_or_default();
+
+    let mut ports = BranchPorts::load();
+    let port = ports.reserve(
+        &checkout.repo,
+        &branch,
+        &checkout.worktree,
+        BRANCH_PORT_BASE,
+        &taken,
+    )?;
+    ports.save()?;
+    reserve_port_in_file(
+        &reservation_file,
+        port,
+        &worktree_project(&checkout.worktree),
+        &format!("branch {}", branch),
+    )?;
+    println!("🔌 Branch '{}' uses port {}", branch, port);
+    Ok(())
+}
+
+/// `--release-branch-port <branch>`: give up a branch's port in the current repository
+pub fn run_release_branch_port(args: &Args, branch: &str) -> Result<()> {
+    let checkout = GitCheckout::current()?;
+    let mut ports = BranchPorts::load();
+    match ports.release(&checkout.repo, branch) {
+        Some(port) => {
+            ports.save()?;
+            release_port_in_file(Path::new(&args.get_reservation_file_path()), port)?;
+            println!("🔌 Released port {} of branch '{}'", port, branch);
+        }
+        // git reports a deleted branch to the hook once per ref store, so only say so
+        // when run by hand (the hook feeds refs on stdin)
+        None if std::io::IsTerminal::is_terminal(&std::io::stdin()) => {
+            println!("ℹ️  Branch '{}' has no reserved port", branch)
+        }
+        None => {}
+    }
+    Ok(())
+}
+
+/// `--branch-ports`: list every reservation
+pub fn print_branch_ports(json: bool) {
+    let ports = BranchPorts::load();
+    if json {
+        crate::cache::output::print_or_json(&ports.reservations, true);
+        return;
+    }
+    if ports.reservations.is_empty() {
+        println!("No branch ports reserved. Set them up in a repository with: port-kill --install-git-hooks");
+        return;
+    }
+    println!("🔌 Branch port reservations:");
+    for reservation in &ports.reservations {
+        println!(
+            "   {:<6} {:<30} {}",
+            reservation.port, reservation.branch, reservation.worktree
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reserve_and_release() {
+        let mut ports = BranchPorts::default();
+        let none = HashSet::new();
+        assert_eq!(ports.reserve("/repo/.git", "main", "/repo", 3000, &none).unwrap(), 3000);
+        assert_eq!(ports.reserve("/repo/.git", "feature", "/wt/feature", 3000, &none).unwrap(), 3001);
+        // Another repository does not get a port in use elsewhere
+        assert_eq!(ports.reserve("/other/.git", "main", "/other", 3000, &none).unwrap(), 3002);
+
+        // Checking the branch out again, even in another worktree, keeps its port
+        assert_eq!(ports.reserve("/repo/.git", "feature", "/repo", 3000, &none).unwrap(), 3001);
+        assert_eq!(ports.find("/repo/.git", "feature").unwrap().worktree, "/repo");
+
+        assert_eq!(ports.release("/repo/.git", "main"), Some(3000));
+        assert_eq!(ports.release("/repo/.git", "main"), None);
+        // The freed port goes to the next new branch
+        assert_eq!(ports.reserve("/repo/.git", "fix", "/wt/fix", 3000, &none).unwrap(), 3000);
+
+        // Ports reserved outside the branch store are skipped
+        let taken = HashSet::from([3003, 3004]);
+        assert_eq!(ports.reserve("/repo/.git", "docs", "/wt/docs", 3000, &taken).unwrap(), 3005);
+    }
+
+    #[test]
+    fn test_hook_script() {
+        let new = hook_script(None, POST_CHECKOUT_HOOK).unwrap();
+        assert!(new.starts_with("#!/bin/sh\n# port-kill"));
+        assert!(new.contains("port-kill --reserve-branch-port"));
+
+        // The user's own hook is kept
+        let appended = hook_script(Some("#!/bin/sh\nnpm install\n"), POST_CHECKOUT_HOOK).unwrap();
+        assert!(appended.starts_with("#!/bin/sh\nnpm install\n\n# port-kill"));
+
+        assert!(hook_script(Some(&appended), POST_CHECKOUT_HOOK).is_none());
+    }
+}
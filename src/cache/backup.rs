@@ -1,14 +1,41 @@
-use super::types::CacheEntry;
-use chrono::{DateTime, Utc};
+use super::types::{CacheEntry, PruneResponse};
+use chrono::{DateTime, Duration, Utc};
 use serde::{Deserialize, Serialize};
 use std::fs;
 use std::path::{Path, PathBuf};
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct BackupManifest {
     pub timestamp: DateTime<Utc>,
     pub entries: Vec<CacheEntry>,
     pub backup_dir: String,
+    /// Entries are stored as `<name>.tar.zst` archives instead of plain copies
+    #[serde(default)]
+    pub compressed: bool,
+}
+
+const ARCHIVE_EXTENSION: &str = "tar.zst";
+
+/// Limits applied when pruning old safe-delete backups. Unset limits are ignored.
+#[derive(Debug, Clone, Default)]
+pub struct RetentionPolicy {
+    pub keep_last: Option<usize>,
+    pub max_total_bytes: Option<u64>,
+    pub max_age_days: Option<u32>,
+}
+
+impl RetentionPolicy {
+    pub fn is_empty(&self) -> bool {
+        self.keep_last.is_none() && self.max_total_bytes.is_none() && self.max_age_days.is_none()
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct BackupInfo {
+    pub path: PathBuf,
+    pub timestamp: DateTime<Utc>,
+    pub size_bytes: u64,
+    pub entry_count: usize,
 }
 
 pub fn get_backup_dir() -> PathBuf {
@@ -30,14 +57,50 @@ pub fn get_timestamped_dir() -> PathBuf {
     backup_dir.join(timestamp.to_string())
 }
 
+/// Unique name for an entry inside a backup directory, derived from the entry ID to avoid
+/// filename collisions (e.g., multiple __pycache__ directories from different paths)
+fn backup_name(entry: &CacheEntry) -> String {
+    format!(
+        "{}-{}",
+        entry.id.replace([':', '/', '\\'], "-"),
+        Path::new(&entry.path)
+            .file_name()
+            .unwrap_or_default()
+            .to_string_lossy()
+    )
+}
+
+fn archive_name(entry: &CacheEntry) -> String {
+    format!("{}.{}", backup_name(entry), ARCHIVE_EXTENSION)
+}
+
+/// Move a file or directory to the OS trash / recycle bin
+pub fn move_to_trash(path: &Path) -> Result<(), std::io::Error> {
+    trash::delete(path).map_err(|e| std::io::Error::other(e.to_string()))
+}
+
+/// Delete entries. `trash` moves them to the OS trash and takes precedence over `safe_delete`,
+/// which copies them into a timestamped backup directory first.
 pub async fn safe_delete_entries(
     entries: &[CacheEntry],
     safe_delete: bool,
+    compress: bool,
+    trash: bool,
 ) -> Result<(Vec<CacheEntry>, Option<String>), std::io::Error> {
     let mut deleted = Vec::new();
     let mut backup_path = None;
 
-    if safe_delete {
+    if trash {
+        for entry in entries {
+            let path = Path::new(&entry.path);
+            if path.exists() {
+                match move_to_trash(path) {
+                    Ok(()) => deleted.push(entry.clone()),
+                    Err(e) => eprintln!("Warning: Failed to move {} to trash: {}", entry.path, e),
+                }
+            }
+        }
+    } else if safe_delete {
         let timestamped_dir = get_timestamped_dir();
         fs::create_dir_all(&timestamped_dir)?;
         backup_path = Some(timestamped_dir.to_string_lossy().to_string());
@@ -46,15 +109,16 @@ pub async fn safe_delete_entries(
         for entry in entries {
             let src = Path::new(&entry.path);
             if src.exists() {
-                // Generate unique destination name using entry ID to avoid filename collisions
-                // (e.g., multiple __pycache__ directories from different paths)
-                let unique_name = format!(
-                    "{}-{}",
-                    entry.id.replace([':', '/', '\\'], "-"),
-                    src.file_name().unwrap_or_default().to_string_lossy()
-                );
-                let dst = timestamped_dir.join(&unique_name);
-                match copy_dir_or_file(src, &dst) {
+                let (dst, result) = if compress {
+                    let dst = timestamped_dir.join(archive_name(entry));
+                    let result = create_archive(src, &dst);
+                    (dst, result)
+                } else {
+                    let dst = timestamped_dir.join(backup_name(entry));
+                    let result = copy_dir_or_file(src, &dst);
+                    (dst, result)
+                };
+                match result {
                     Ok(()) => match remove_dir_or_file(src) {
                         Ok(()) => deleted.push(entry.clone()),
                         Err(e) => {
@@ -76,6 +140,7 @@ pub async fn safe_delete_entries(
             timestamp: Utc::now(),
             entries: deleted.clone(),
             backup_dir: timestamped_dir.to_string_lossy().to_string(),
+            compressed: compress,
         };
 
         let manifest_path = timestamped_dir.join("manifest.json");
@@ -122,22 +187,178 @@ pub fn find_latest_backup() -> Result<Option<PathBuf>, std::io::Error> {
     Ok(entries.last().map(|e| e.path()))
 }
 
-pub async fn restore_from_backup(backup_path: &Path) -> Result<usize, std::io::Error> {
-    let manifest_path = backup_path.join("manifest.json");
-    let manifest_content = fs::read_to_string(&manifest_path)?;
-    let manifest: BackupManifest = serde_json::from_str(&manifest_content)?;
+/// Find a backup by its timestamped directory name. A prefix such as `2025-01-31`
+/// matches the most recent backup taken that day.
+pub fn find_backup(timestamp: &str) -> Result<Option<PathBuf>, std::io::Error> {
+    let backup_dir = get_backup_dir();
+    if !backup_dir.exists() {
+        return Ok(None);
+    }
+
+    let mut entries: Vec<_> = fs::read_dir(&backup_dir)?
+        .filter_map(|e| e.ok())
+        .filter(|e| e.path().is_dir() && e.path().join("manifest.json").exists())
+        .filter(|e| e.file_name().to_string_lossy().starts_with(timestamp))
+        .collect();
+
+    entries.sort_by_key(|e| e.file_name());
+
+    Ok(entries.last().map(|e| e.path()))
+}
+
+pub fn read_manifest(backup_path: &Path) -> Result<BackupManifest, std::io::Error> {
+    let manifest_content = fs::read_to_string(backup_path.join("manifest.json"))?;
+    Ok(serde_json::from_str(&manifest_content)?)
+}
+
+/// List all backups that have a readable manifest, newest first.
+pub fn list_backups() -> Result<Vec<BackupInfo>, std::io::Error> {
+    let backup_dir = get_backup_dir();
+    if !backup_dir.exists() {
+        return Ok(Vec::new());
+    }
+
+    let mut backups = Vec::new();
+    for entry in fs::read_dir(&backup_dir)?.flatten() {
+        let path = entry.path();
+        let manifest_path = path.join("manifest.json");
+        if !path.is_dir() || !manifest_path.exists() {
+            continue;
+        }
+        let manifest: BackupManifest = match fs::read_to_string(&manifest_path)
+            .ok()
+            .and_then(|c| serde_json::from_str(&c).ok())
+        {
+            Some(m) => m,
+            None => continue,
+        };
+        backups.push(BackupInfo {
+            size_bytes: path_size(&path),
+            path,
+            timestamp: manifest.timestamp,
+            entry_count: manifest.entries.len(),
+        });
+    }
+
+    backups.sort_by_key(|b| std::cmp::Reverse(b.timestamp));
+    Ok(backups)
+}
+
+/// Pick the backups that fall outside the retention policy.
+/// `backups` must be sorted newest first, as returned by `list_backups`.
+pub fn select_backups_to_prune<'a>(
+    backups: &'a [BackupInfo],
+    policy: &RetentionPolicy,
+    now: DateTime<Utc>,
+) -> Vec<&'a BackupInfo> {
+    let mut pruned = Vec::new();
+    let mut kept_bytes = 0u64;
+    let mut kept_count = 0usize;
+
+    for backup in backups {
+        let over_count = policy.keep_last.is_some_and(|n| kept_count >= n);
+        let over_age = policy
+            .max_age_days
+            .is_some_and(|days| now - backup.timestamp > Duration::days(days as i64));
+        let over_size = policy
+            .max_total_bytes
+            .is_some_and(|max| kept_bytes.saturating_add(backup.size_bytes) > max);
+
+        if over_count || over_age || over_size {
+            pruned.push(backup);
+        } else {
+            kept_bytes = kept_bytes.saturating_add(backup.size_bytes);
+            kept_count += 1;
+        }
+    }
+
+    pruned
+}
+
+pub fn prune_backups(policy: &RetentionPolicy) -> PruneResponse {
+    if policy.is_empty() {
+        return PruneResponse {
+            pruned: vec![],
+            freed_bytes: 0,
+            kept_count: 0,
+            error: Some(
+                "No retention limits given (use --keep-backups, --max-backup-size or --max-backup-age)"
+                    .to_string(),
+            ),
+        };
+    }
+
+    let backups = match list_backups() {
+        Ok(b) => b,
+        Err(e) => {
+            return PruneResponse {
+                pruned: vec![],
+                freed_bytes: 0,
+                kept_count: 0,
+                error: Some(format!("Failed to list backups: {}", e)),
+            }
+        }
+    };
+
+    let mut pruned = Vec::new();
+    let mut freed_bytes = 0u64;
+    for backup in select_backups_to_prune(&backups, policy, Utc::now()) {
+        match fs::remove_dir_all(&backup.path) {
+            Ok(()) => {
+                freed_bytes = freed_bytes.saturating_add(backup.size_bytes);
+                pruned.push(backup.path.to_string_lossy().to_string());
+            }
+            Err(e) => eprintln!(
+                "Warning: Failed to prune backup {}: {}",
+                backup.path.display(),
+                e
+            ),
+        }
+    }
+
+    PruneResponse {
+        kept_count: backups.len() - pruned.len(),
+        pruned,
+        freed_bytes,
+        error: None,
+    }
+}
+
+fn path_size(path: &Path) -> u64 {
+    walkdir::WalkDir::new(path)
+        .into_iter()
+        .flatten()
+        .filter_map(|e| e.metadata().ok())
+        .filter(|m| m.is_file())
+        .map(|m| m.len())
+        .sum()
+}
+
+/// Restore entries from a backup. When `only_ids` is given, just those entries are restored
+/// and the rest stay in the backup for a later restore.
+pub async fn restore_from_backup(
+    backup_path: &Path,
+    only_ids: Option<&[String]>,
+) -> Result<usize, std::io::Error> {
+    let mut manifest = read_manifest(backup_path)?;
 
     let mut restored_count = 0;
+    let mut remaining = Vec::new();
 
     for entry in &manifest.entries {
+        if let Some(ids) = only_ids {
+            if !ids.contains(&entry.id) {
+                remaining.push(entry.clone());
+                continue;
+            }
+        }
+
         let original_path = Path::new(&entry.path);
-        // Use the same unique naming scheme as in safe_delete_entries
-        let unique_name = format!(
-            "{}-{}",
-            entry.id.replace([':', '/', '\\'], "-"),
-            original_path.file_name().unwrap_or_default().to_string_lossy()
-        );
-        let backup_file = backup_path.join(&unique_name);
+        let backup_file = if manifest.compressed {
+            backup_path.join(archive_name(entry))
+        } else {
+            backup_path.join(backup_name(entry))
+        };
 
         if backup_file.exists() {
             // Ensure parent directory exists
@@ -145,30 +366,84 @@ pub async fn restore_from_backup(backup_path: &Path) -> Result<usize, std::io::E
                 fs::create_dir_all(parent)?;
             }
 
-            match copy_dir_or_file(&backup_file, original_path) {
+            let result = if manifest.compressed {
+                extract_archive(&backup_file, original_path)
+            } else {
+                copy_dir_or_file(&backup_file, original_path)
+            };
+            match result {
                 Ok(()) => match remove_dir_or_file(&backup_file) {
                     Ok(()) => restored_count += 1,
-                    Err(e) => eprintln!(
-                        "Warning: Failed to remove backup after restore {}: {}",
-                        entry.path, e
-                    ),
+                    Err(e) => {
+                        eprintln!(
+                            "Warning: Failed to remove backup after restore {}: {}",
+                            entry.path, e
+                        );
+                        remaining.push(entry.clone());
+                    }
                 },
-                Err(e) => eprintln!("Warning: Failed to restore {}: {}", entry.path, e),
+                Err(e) => {
+                    eprintln!("Warning: Failed to restore {}: {}", entry.path, e);
+                    remaining.push(entry.clone());
+                }
             }
         } else {
             eprintln!("Warning: Backup file not found for {}", entry.path);
+            remaining.push(entry.clone());
         }
     }
 
     // Clean up backup directory only after ALL files are successfully restored
-    // If any files failed to restore, keep the backup so users can retry
-    if restored_count > 0 && restored_count == manifest.entries.len() {
-        let _ = fs::remove_dir_all(backup_path);
+    // If any files failed to restore (or were not selected), keep them so users can retry
+    if remaining.is_empty() {
+        if restored_count > 0 {
+            let _ = fs::remove_dir_all(backup_path);
+        }
+    } else if restored_count > 0 {
+        manifest.entries = remaining;
+        let manifest_json = serde_json::to_string_pretty(&manifest)?;
+        fs::write(backup_path.join("manifest.json"), manifest_json)?;
     }
 
     Ok(restored_count)
 }
 
+/// Pack `src` into a zstd-compressed tarball at `dst`. The archive holds a single
+/// top-level item named after `src`, so it can be unpacked straight into the original parent.
+fn create_archive(src: &Path, dst: &Path) -> Result<(), std::io::Error> {
+    let name = src.file_name().ok_or_else(|| {
+        std::io::Error::new(std::io::ErrorKind::InvalidInput, "Path has no file name")
+    })?;
+    let file = fs::File::create(dst)?;
+    let encoder = zstd::Encoder::new(file, 0)?;
+    let mut builder = tar::Builder::new(encoder);
+    builder.follow_symlinks(false);
+
+    let meta = fs::symlink_metadata(src)?;
+    if meta.is_dir() {
+        builder.append_dir_all(name, src)?;
+    } else {
+        builder.append_path_with_name(src, name)?;
+    }
+    builder.into_inner()?.finish()?;
+    Ok(())
+}
+
+/// Unpack an archive created by `create_archive` back to `dst`.
+fn extract_archive(archive: &Path, dst: &Path) -> Result<(), std::io::Error> {
+    if dst.is_dir() {
+        prepare_directory_destination(dst)?;
+    } else {
+        prepare_file_destination(dst)?;
+    }
+    let parent = dst.parent().unwrap_or_else(|| Path::new("."));
+    let decoder = zstd::Decoder::new(fs::File::open(archive)?)?;
+    let mut tar = tar::Archive::new(decoder);
+    tar.set_preserve_permissions(true);
+    tar.set_preserve_mtime(true);
+    tar.unpack(parent)
+}
+
 fn copy_dir_or_file(src: &Path, dst: &Path) -> Result<(), std::io::Error> {
     let meta = fs::symlink_metadata(src)?;
     if meta.is_symlink() {
@@ -268,3 +543,72 @@ fn remove_dir_or_file(path: &Path) -> Result<(), std::io::Error> {
         fs::remove_file(path)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn backup(days_old: i64, size_bytes: u64, now: DateTime<Utc>) -> BackupInfo {
+        BackupInfo {
+            path: PathBuf::from(format!("/backups/{}", days_old)),
+            timestamp: now - Duration::days(days_old),
+            size_bytes,
+            entry_count: 1,
+        }
+    }
+
+    #[test]
+    fn test_prune_keep_last() {
+        let now = Utc::now();
+        let backups = vec![backup(0, 10, now), backup(1, 10, now), backup(2, 10, now)];
+        let policy = RetentionPolicy {
+            keep_last: Some(2),
+            ..Default::default()
+        };
+
+        let pruned = select_backups_to_prune(&backups, &policy, now);
+        assert_eq!(pruned.len(), 1);
+        assert_eq!(pruned[0].path, PathBuf::from("/backups/2"));
+    }
+
+    #[test]
+    fn test_prune_max_age_and_size() {
+        let now = Utc::now();
+        let backups = vec![backup(0, 60, now), backup(1, 60, now), backup(40, 1, now)];
+        let policy = RetentionPolicy {
+            max_total_bytes: Some(100),
+            max_age_days: Some(30),
+            ..Default::default()
+        };
+
+        let pruned = select_backups_to_prune(&backups, &policy, now);
+        let paths: Vec<_> = pruned.iter().map(|b| b.path.clone()).collect();
+        assert_eq!(
+            paths,
+            vec![PathBuf::from("/backups/1"), PathBuf::from("/backups/40")]
+        );
+    }
+
+    #[test]
+    fn test_empty_policy_prunes_nothing() {
+        let now = Utc::now();
+        let backups = vec![backup(0, 10, now), backup(400, 10, now)];
+        assert!(select_backups_to_prune(&backups, &RetentionPolicy::default(), now).is_empty());
+    }
+
+    #[test]
+    fn test_archive_round_trip() {
+        let root = std::env::temp_dir().join(format!("port-kill-archive-{}", std::process::id()));
+        let src = root.join("cache-dir");
+        fs::create_dir_all(src.join("nested")).unwrap();
+        fs::write(src.join("nested/file.txt"), b"cached").unwrap();
+        let archive = root.join("cache-dir.tar.zst");
+
+        create_archive(&src, &archive).unwrap();
+        remove_dir_or_file(&src).unwrap();
+        extract_archive(&archive, &src).unwrap();
+
+        assert_eq!(fs::read(src.join("nested/file.txt")).unwrap(), b"cached");
+        let _ = fs::remove_dir_all(&root);
+    }
+}
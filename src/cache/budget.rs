@@ -0,0 +1,125 @@
+use super::output::human_size;
+use super::types::{BudgetProvider, BudgetReport, ListResponse};
+use std::collections::BTreeMap;
+
+/// Number of largest entries included in a budget report
+const LARGEST_ENTRIES: usize = 10;
+
+/// Compare the tracked caches in a listing against a size budget
+pub fn check_budget(listing: &ListResponse, budget_bytes: u64) -> BudgetReport {
+    let total: u64 = listing.entries.iter().map(|e| e.size_bytes).sum();
+
+    let mut by_provider: BTreeMap<&str, (u64, usize)> = BTreeMap::new();
+    for e in &listing.entries {
+        let slot = by_provider.entry(e.kind.as_str()).or_default();
+        slot.0 += e.size_bytes;
+        slot.1 += 1;
+    }
+    let mut providers: Vec<BudgetProvider> = by_provider
+        .into_iter()
+        .map(|(provider, (size_bytes, count))| BudgetProvider {
+            provider: provider.to_string(),
+            size_bytes,
+            count,
+        })
+        .collect();
+    providers.sort_by_key(|p| std::cmp::Reverse(p.size_bytes));
+
+    let mut largest = listing.entries.clone();
+    largest.sort_by_key(|e| std::cmp::Reverse(e.size_bytes));
+    largest.truncate(LARGEST_ENTRIES);
+
+    BudgetReport {
+        budget_bytes,
+        total_size_bytes: total,
+        over_budget: total > budget_bytes,
+        over_by_bytes: total.saturating_sub(budget_bytes),
+        providers,
+        largest,
+    }
+}
+
+pub fn print_budget_report(report: &BudgetReport) {
+    if report.over_budget {
+        println!(
+            "❌ Caches use {} — {} over the {} budget",
+            human_size(report.total_size_bytes),
+            human_size(report.over_by_bytes),
+            human_size(report.budget_bytes)
+        );
+    } else {
+        println!(
+            "✅ Caches use {} of the {} budget",
+            human_size(report.total_size_bytes),
+            human_size(report.budget_bytes)
+        );
+    }
+    println!();
+    for p in &report.providers {
+        println!(
+            "  {:<12} {:>12}  ({} entries)",
+            p.provider,
+            human_size(p.size_bytes),
+            p.count
+        );
+    }
+    if report.over_budget {
+        println!();
+        println!("Largest entries:");
+        for e in &report.largest {
+            println!("  {:>12}  {}", human_size(e.size_bytes), e.path);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cache::types::{CacheEntry, ListSummary};
+    use serde_json::json;
+
+    fn entry(id: &str, kind: &str, size_bytes: u64) -> CacheEntry {
+        CacheEntry {
+            id: id.to_string(),
+            kind: kind.to_string(),
+            name: id.to_string(),
+            path: format!("/tmp/{}", id),
+            size_bytes,
+            last_used_at: None,
+            stale: false,
+            details: json!({}),
+        }
+    }
+
+    fn listing(entries: Vec<CacheEntry>) -> ListResponse {
+        ListResponse {
+            summary: ListSummary {
+                total_size_bytes: entries.iter().map(|e| e.size_bytes).sum(),
+                count: entries.len(),
+                stale_count: 0,
+            },
+            entries,
+        }
+    }
+
+    #[test]
+    fn test_check_budget() {
+        let resp = listing(vec![
+            entry("rust:target", "rust", 600),
+            entry("js:.next", "js", 300),
+            entry("js:.vite", "js", 200),
+        ]);
+
+        let report = check_budget(&resp, 1000);
+        assert!(report.over_budget);
+        assert_eq!(report.over_by_bytes, 100);
+        assert_eq!(report.providers[0].provider, "rust");
+        assert_eq!(report.providers[1].size_bytes, 500);
+        assert_eq!(report.providers[1].count, 2);
+        assert_eq!(report.largest[0].id, "rust:target");
+
+        let report = check_budget(&resp, 2000);
+        assert!(!report.over_budget);
+        assert_eq!(report.over_by_bytes, 0);
+    }
+}
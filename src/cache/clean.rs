@@ -1,10 +1,11 @@
-use super::backup::safe_delete_entries;
+use super::backup::{prune_backups, safe_delete_entries, RetentionPolicy};
 use super::detect::{
-    detect_cloudflare_caches, detect_hf_caches, detect_java_caches, detect_js_caches,
+    detect_cloudflare_caches, detect_hf_clean_targets, detect_java_caches, detect_js_caches,
     detect_js_pm_caches, detect_npx_caches, detect_python_caches, detect_rust_caches,
     detect_torch_caches, detect_vercel_caches,
 };
 use super::types::{CleanResponse, CleanSummary};
+use chrono::Duration;
 use std::path::Path;
 
 pub async fn clean_caches(
@@ -12,12 +13,15 @@ pub async fn clean_caches(
     include_npx: bool,
     include_js_pm: bool,
     safe_delete: bool,
+    compress: bool,
+    trash: bool,
     _force: bool,
     include_hf: bool,
     include_torch: bool,
     include_vercel: bool,
     include_cloudflare: bool,
-    stale_days: Option<u32>,
+    max_age: Option<Duration>,
+    retention: &RetentionPolicy,
 ) -> CleanResponse {
     let cwd = std::env::current_dir().unwrap_or_else(|_| std::path::PathBuf::from("."));
 
@@ -31,56 +35,73 @@ pub async fn clean_caches(
 
     // Specialized integrations
     if include_npx {
-        entries.extend(detect_npx_caches(stale_days));
+        entries.extend(detect_npx_caches(max_age));
     }
     if include_js_pm {
-        entries.extend(detect_js_pm_caches(stale_days));
+        entries.extend(detect_js_pm_caches(max_age));
     }
     if include_hf {
-        entries.extend(detect_hf_caches(stale_days));
+        entries.extend(detect_hf_clean_targets(max_age));
     }
     if include_torch {
-        entries.extend(detect_torch_caches(stale_days));
+        entries.extend(detect_torch_caches(max_age));
     }
     if include_vercel {
-        entries.extend(detect_vercel_caches(stale_days));
+        entries.extend(detect_vercel_caches(max_age));
     }
     if include_cloudflare {
-        entries.extend(detect_cloudflare_caches(stale_days));
+        entries.extend(detect_cloudflare_caches(max_age));
     }
 
     // Language-based detection (always runs unless only specialized flags were given)
     if !has_specialized_flags || lang != "auto" {
         if lang == "auto" || lang == "rust" {
-            entries.extend(detect_rust_caches(Path::new(&cwd), stale_days));
+            entries.extend(detect_rust_caches(Path::new(&cwd), max_age));
         }
         if lang == "auto" || lang == "js" {
-            entries.extend(detect_js_caches(Path::new(&cwd), stale_days));
+            entries.extend(detect_js_caches(Path::new(&cwd), max_age));
         }
         if lang == "auto" || lang == "py" {
-            entries.extend(detect_python_caches(stale_days));
+            entries.extend(detect_python_caches(max_age));
         }
         if lang == "auto" || lang == "java" {
-            entries.extend(detect_java_caches(stale_days));
+            entries.extend(detect_java_caches(max_age));
         }
     }
 
-    // Only delete stale entries when stale_days filtering is requested
-    if stale_days.is_some() {
+    entries.retain(|e| !e.is_pinned());
+
+    // Only delete stale entries when age filtering is requested
+    if max_age.is_some() {
         entries.retain(|e| e.stale);
     }
 
-    match safe_delete_entries(&entries, safe_delete).await {
+    match safe_delete_entries(&entries, safe_delete, compress, trash).await {
         Ok((deleted, backup_path)) => {
             let freed_bytes: u64 = deleted.iter().map(|e| e.size_bytes).sum();
             let deleted_count = deleted.len();
+            if deleted_count > 0 {
+                let recorded = crate::history_store::HistoryStore::open_default()
+                    .and_then(|store| store.record_cache_clean(freed_bytes, deleted_count));
+                if let Err(e) = recorded {
+                    log::warn!("Failed to save cache cleanup to history: {}", e);
+                }
+            }
+            // Apply the retention policy once the new backup is in place
+            let pruned_backups = if backup_path.is_some() && !retention.is_empty() {
+                Some(prune_backups(retention))
+            } else {
+                None
+            };
             CleanResponse {
                 deleted,
                 backed_up_to: backup_path,
+                moved_to_trash: trash,
                 summary: CleanSummary {
                     freed_bytes: freed_bytes,
                     deleted_count: deleted_count,
                 },
+                pruned_backups,
             }
         }
         Err(e) => {
@@ -88,10 +109,12 @@ pub async fn clean_caches(
             CleanResponse {
                 deleted: vec![],
                 backed_up_to: None,
+                moved_to_trash: false,
                 summary: CleanSummary {
                     freed_bytes: 0,
                     deleted_count: 0,
                 },
+                pruned_backups: None,
             }
         }
     }
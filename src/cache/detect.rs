@@ -1,35 +1,37 @@
+use super::hf::{get_hub_dir, linked_blobs, parse_repo_dir, HfPins};
 use super::types::CacheEntry;
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, Duration, Utc};
 use serde_json::json;
 use std::fs;
 use std::path::{Path, PathBuf};
 
-/// Returns true if the entry should be considered stale for --stale-days filtering.
-/// When stale_days is None, returns false (no filtering). When stale_days is Some(threshold),
-/// returns true if mtime is older than threshold days, or if mtime is unknown.
-fn is_stale(mtime: Option<DateTime<Utc>>, stale_days: Option<u32>) -> bool {
-    match (mtime, stale_days) {
+/// Returns true if the entry should be considered stale for --older-than/--stale-days filtering.
+/// When max_age is None, returns false (no filtering). When max_age is Some(threshold),
+/// returns true if the entry was last used longer ago than threshold, or if that is unknown.
+fn is_stale(last_used: Option<DateTime<Utc>>, max_age: Option<Duration>) -> bool {
+    match (last_used, max_age) {
         (_, None) => false,
         (None, Some(_)) => true,
-        (Some(last_used), Some(threshold)) => {
-            let now = Utc::now();
-            let days_old = (now - last_used).num_days();
-            days_old > threshold as i64
-        }
+        (Some(last_used), Some(threshold)) => Utc::now() - last_used > threshold,
     }
 }
 
+/// Total size of a path plus the most recent time anything inside it was modified or read.
+/// Access times only count for files: walking a directory updates its own access time.
 fn dir_size_and_mtime(path: &Path) -> (u64, Option<DateTime<Utc>>) {
     let mut total: u64 = 0;
     let mut newest: Option<DateTime<Utc>> = None;
     let _ = walkdir::WalkDir::new(path).into_iter().for_each(|e| {
         if let Ok(entry) = e {
             if let Ok(md) = entry.metadata() {
-                if md.is_file() {
+                let accessed = if md.is_file() {
                     total = total.saturating_add(md.len());
-                }
-                if let Ok(modified) = md.modified() {
-                    let dt: DateTime<Utc> = modified.into();
+                    md.accessed().ok()
+                } else {
+                    None
+                };
+                for time in [md.modified().ok(), accessed].into_iter().flatten() {
+                    let dt: DateTime<Utc> = time.into();
                     newest = Some(match newest {
                         Some(n) => n.max(dt),
                         None => dt,
@@ -41,7 +43,7 @@ fn dir_size_and_mtime(path: &Path) -> (u64, Option<DateTime<Utc>>) {
     (total, newest)
 }
 
-pub fn detect_rust_caches(root: &Path, stale_days: Option<u32>) -> Vec<CacheEntry> {
+pub fn detect_rust_caches(root: &Path, max_age: Option<Duration>) -> Vec<CacheEntry> {
     let mut entries = Vec::new();
 
     // project target/
@@ -55,33 +57,36 @@ pub fn detect_rust_caches(root: &Path, stale_days: Option<u32>) -> Vec<CacheEntr
             path: target.to_string_lossy().to_string(),
             size_bytes: size,
             last_used_at: mtime,
-            stale: is_stale(mtime, stale_days),
+            stale: is_stale(mtime, max_age),
             details: json!({}),
         });
     }
 
-    // ~/.cargo/registry (downloaded crate sources and indices — safe to delete)
-    if let Ok(home) = std::env::var("HOME") {
-        let cargo_registry = PathBuf::from(home).join(".cargo/registry");
-        if cargo_registry.exists() {
-            let (size, mtime) = dir_size_and_mtime(&cargo_registry);
-            entries.push(CacheEntry {
-                id: "rust:cargo-registry".to_string(),
-                kind: "rust".to_string(),
-                name: "Cargo registry cache".to_string(),
-                path: cargo_registry.to_string_lossy().to_string(),
-                size_bytes: size,
-                last_used_at: mtime,
-                stale: is_stale(mtime, stale_days),
-                details: json!({}),
-            });
-        }
-    }
-
+    entries.extend(detect_cargo_registry(max_age));
     entries
 }
 
-pub fn detect_js_caches(root: &Path, stale_days: Option<u32>) -> Vec<CacheEntry> {
+/// ~/.cargo/registry (downloaded crate sources and indices — safe to delete)
+pub fn detect_cargo_registry(max_age: Option<Duration>) -> Option<CacheEntry> {
+    let home = std::env::var("HOME").ok()?;
+    let cargo_registry = PathBuf::from(home).join(".cargo/registry");
+    if !cargo_registry.exists() {
+        return None;
+    }
+    let (size, mtime) = dir_size_and_mtime(&cargo_registry);
+    Some(CacheEntry {
+        id: "rust:cargo-registry".to_string(),
+        kind: "rust".to_string(),
+        name: "Cargo registry cache".to_string(),
+        path: cargo_registry.to_string_lossy().to_string(),
+        size_bytes: size,
+        last_used_at: mtime,
+        stale: is_stale(mtime, max_age),
+        details: json!({}),
+    })
+}
+
+pub fn detect_js_caches(root: &Path, max_age: Option<Duration>) -> Vec<CacheEntry> {
     let mut entries = Vec::new();
 
     let js_dirs = [
@@ -107,7 +112,7 @@ pub fn detect_js_caches(root: &Path, stale_days: Option<u32>) -> Vec<CacheEntry>
                 path: dir_path.to_string_lossy().to_string(),
                 size_bytes: size,
                 last_used_at: mtime,
-                stale: is_stale(mtime, stale_days),
+                stale: is_stale(mtime, max_age),
                 details: json!({
                     "framework": dir_name,
                     "type": "build_cache"
@@ -119,7 +124,7 @@ pub fn detect_js_caches(root: &Path, stale_days: Option<u32>) -> Vec<CacheEntry>
     entries
 }
 
-pub fn detect_npx_caches(stale_days: Option<u32>) -> Vec<CacheEntry> {
+pub fn detect_npx_caches(max_age: Option<Duration>) -> Vec<CacheEntry> {
     let mut entries = Vec::new();
 
     // NPX cache location
@@ -133,7 +138,7 @@ pub fn detect_npx_caches(stale_days: Option<u32>) -> Vec<CacheEntry> {
                         let package_name = entry.file_name().to_string_lossy().to_string();
                         let package_path = entry.path();
                         let (size, mtime) = dir_size_and_mtime(&package_path);
-                        let stale = is_stale(mtime, stale_days);
+                        let stale = is_stale(mtime, max_age);
 
                         // Try to extract package name and version from package.json
                         let (actual_name, version) = extract_package_info(&package_path);
@@ -229,7 +234,7 @@ fn extract_package_info(package_path: &Path) -> (String, Option<String>) {
     ("unknown".to_string(), None)
 }
 
-pub fn detect_js_pm_caches(stale_days: Option<u32>) -> Vec<CacheEntry> {
+pub fn detect_js_pm_caches(max_age: Option<Duration>) -> Vec<CacheEntry> {
     let mut entries = Vec::new();
 
     if let Ok(home) = std::env::var("HOME") {
@@ -246,7 +251,7 @@ pub fn detect_js_pm_caches(stale_days: Option<u32>) -> Vec<CacheEntry> {
                 path: npm_cache.to_string_lossy().to_string(),
                 size_bytes: size,
                 last_used_at: mtime,
-                stale: is_stale(mtime, stale_days),
+                stale: is_stale(mtime, max_age),
                 details: json!({"manager": "npm"}),
             });
         }
@@ -262,7 +267,7 @@ pub fn detect_js_pm_caches(stale_days: Option<u32>) -> Vec<CacheEntry> {
                 path: pnpm_store.to_string_lossy().to_string(),
                 size_bytes: size,
                 last_used_at: mtime,
-                stale: is_stale(mtime, stale_days),
+                stale: is_stale(mtime, max_age),
                 details: json!({"manager": "pnpm"}),
             });
         }
@@ -278,7 +283,7 @@ pub fn detect_js_pm_caches(stale_days: Option<u32>) -> Vec<CacheEntry> {
                 path: yarn_cache.to_string_lossy().to_string(),
                 size_bytes: size,
                 last_used_at: mtime,
-                stale: is_stale(mtime, stale_days),
+                stale: is_stale(mtime, max_age),
                 details: json!({"manager": "yarn"}),
             });
         }
@@ -287,7 +292,7 @@ pub fn detect_js_pm_caches(stale_days: Option<u32>) -> Vec<CacheEntry> {
     entries
 }
 
-pub fn detect_python_caches(stale_days: Option<u32>) -> Vec<CacheEntry> {
+pub fn detect_python_caches(max_age: Option<Duration>) -> Vec<CacheEntry> {
     let mut entries = Vec::new();
     let cwd = std::env::current_dir().unwrap_or_else(|_| std::path::PathBuf::from("."));
 
@@ -320,7 +325,7 @@ pub fn detect_python_caches(stale_days: Option<u32>) -> Vec<CacheEntry> {
             path: entry.path().to_string_lossy().to_string(),
             size_bytes: size,
             last_used_at: mtime,
-            stale: is_stale(mtime, stale_days),
+            stale: is_stale(mtime, max_age),
             details: json!({ "type": cache_type }),
         });
         it.skip_current_dir();
@@ -329,7 +334,7 @@ pub fn detect_python_caches(stale_days: Option<u32>) -> Vec<CacheEntry> {
     entries
 }
 
-pub fn detect_java_caches(stale_days: Option<u32>) -> Vec<CacheEntry> {
+pub fn detect_java_caches(max_age: Option<Duration>) -> Vec<CacheEntry> {
     let mut entries = Vec::new();
     let cwd = std::env::current_dir().unwrap_or_else(|_| std::path::PathBuf::from("."));
 
@@ -354,7 +359,7 @@ pub fn detect_java_caches(stale_days: Option<u32>) -> Vec<CacheEntry> {
                 path: entry.path().to_string_lossy().to_string(),
                 size_bytes: size,
                 last_used_at: mtime,
-                stale: is_stale(mtime, stale_days),
+                stale: is_stale(mtime, max_age),
                 details: json!({ "type": "gradle_cache" }),
             });
             it.skip_current_dir();
@@ -380,7 +385,7 @@ pub fn detect_java_caches(stale_days: Option<u32>) -> Vec<CacheEntry> {
                     path: entry.path().to_string_lossy().to_string(),
                     size_bytes: size,
                     last_used_at: mtime,
-                    stale: is_stale(mtime, stale_days),
+                    stale: is_stale(mtime, max_age),
                     details: json!({ "type": "build_cache" }),
                 });
                 it.skip_current_dir();
@@ -388,20 +393,116 @@ pub fn detect_java_caches(stale_days: Option<u32>) -> Vec<CacheEntry> {
         }
     }
 
-    // Maven local repository (~/.m2/repository) — downloaded artifacts, safe to delete
-    if let Ok(home) = std::env::var("HOME") {
-        let maven_repo = PathBuf::from(home).join(".m2/repository");
-        if maven_repo.exists() {
-            let (size, mtime) = dir_size_and_mtime(&maven_repo);
+    entries.extend(detect_maven_repository(max_age));
+    entries
+}
+
+/// Maven local repository (~/.m2/repository) — downloaded artifacts, safe to delete
+pub fn detect_maven_repository(max_age: Option<Duration>) -> Option<CacheEntry> {
+    let home = std::env::var("HOME").ok()?;
+    let maven_repo = PathBuf::from(home).join(".m2/repository");
+    if !maven_repo.exists() {
+        return None;
+    }
+    let (size, mtime) = dir_size_and_mtime(&maven_repo);
+    Some(CacheEntry {
+        id: "maven:repository".to_string(),
+        kind: "java".to_string(),
+        name: "Maven repository cache".to_string(),
+        path: maven_repo.to_string_lossy().to_string(),
+        size_bytes: size,
+        last_used_at: mtime,
+        stale: is_stale(mtime, max_age),
+        details: json!({ "type": "maven_repository" }),
+    })
+}
+
+/// Hugging Face caches: one entry per model/dataset/space in the hub cache (with its
+/// revisions in `details`), plus the processed `datasets` cache. Pinned repos are flagged
+/// with `details.pinned` so clean skips them.
+pub fn detect_hf_caches(max_age: Option<Duration>) -> Vec<CacheEntry> {
+    let mut entries = Vec::new();
+    let pins = HfPins::load();
+
+    if let Some(hub) = get_hub_dir() {
+        if let Ok(repos) = fs::read_dir(&hub) {
+            for repo in repos.flatten() {
+                let dir_name = repo.file_name().to_string_lossy().to_string();
+                let Some((repo_type, repo_id)) = parse_repo_dir(&dir_name) else {
+                    continue;
+                };
+                let repo_path = repo.path();
+                let (size, mtime) = dir_size_and_mtime(&repo_path);
+                let pinned = pins.is_pinned(&repo_id);
+                entries.push(CacheEntry {
+                    id: format!("hf:{}:{}", repo_type, repo_id),
+                    kind: "hf".to_string(),
+                    name: repo_id.clone(),
+                    path: repo_path.to_string_lossy().to_string(),
+                    size_bytes: size,
+                    last_used_at: mtime,
+                    stale: !pinned && is_stale(mtime, max_age),
+                    details: json!({
+                        "type": format!("hf_{}", repo_type),
+                        "repo": repo_id,
+                        "revisions": hf_revisions(&repo_path),
+                        "pinned": pinned,
+                    }),
+                });
+            }
+        }
+    }
+
+    if let Some((hf_cache, rest)) = hf_cache_outside_hub() {
+        let mut size = 0;
+        let mut newest = None;
+        for path in &rest {
+            let (bytes, mtime) = dir_size_and_mtime(path);
+            size += bytes;
+            newest = newest.max(mtime);
+        }
+        entries.push(CacheEntry {
+            id: "hf:cache".to_string(),
+            kind: "hf".to_string(),
+            name: "Hugging Face cache".to_string(),
+            path: hf_cache.to_string_lossy().to_string(),
+            size_bytes: size,
+            last_used_at: newest,
+            stale: is_stale(newest, max_age),
+            details: json!({ "type": "hf_cache" }),
+        });
+    }
+
+    entries
+}
+
+/// What `cache --hf --clean` removes. Hub repos are cleaned per revision: every snapshot
+/// (only the stale ones when `max_age` is set), then the blobs no remaining snapshot links
+/// to. Pinned repos are skipped. Everything else in `~/.cache/huggingface` except the
+/// login token is removed as before.
+pub fn detect_hf_clean_targets(max_age: Option<Duration>) -> Vec<CacheEntry> {
+    let mut entries = Vec::new();
+    if let Some(hub) = get_hub_dir() {
+        entries.extend(hub_clean_targets(&hub, &HfPins::load(), max_age));
+    }
+
+    if let Some((_, rest)) = hf_cache_outside_hub() {
+        for path in rest {
+            let name = path
+                .file_name()
+                .unwrap_or_default()
+                .to_string_lossy()
+                .to_string();
+            let (size, mtime) = dir_size_and_mtime(&path);
             entries.push(CacheEntry {
-                id: "maven:repository".to_string(),
-                kind: "java".to_string(),
-                name: "Maven repository cache".to_string(),
-                path: maven_repo.to_string_lossy().to_string(),
+                id: format!("hf:cache:{}", name),
+                kind: "hf".to_string(),
+                name: format!("Hugging Face {}", name),
+                path: path.to_string_lossy().to_string(),
                 size_bytes: size,
                 last_used_at: mtime,
-                stale: is_stale(mtime, stale_days),
-                details: json!({ "type": "maven_repository" }),
+                stale: is_stale(mtime, max_age),
+                details: json!({ "type": "hf_cache" }),
             });
         }
     }
@@ -409,32 +510,139 @@ pub fn detect_java_caches(stale_days: Option<u32>) -> Vec<CacheEntry> {
     entries
 }
 
-pub fn detect_hf_caches(stale_days: Option<u32>) -> Vec<CacheEntry> {
+/// Revisions and blobs to clean in the hub cache at `hub`, leaving pinned repos alone
+fn hub_clean_targets(hub: &Path, pins: &HfPins, max_age: Option<Duration>) -> Vec<CacheEntry> {
     let mut entries = Vec::new();
+    if let Ok(repos) = fs::read_dir(hub) {
+        for repo in repos.flatten() {
+            let dir_name = repo.file_name().to_string_lossy().to_string();
+            let Some((repo_type, repo_id)) = parse_repo_dir(&dir_name) else {
+                continue;
+            };
+            if !pins.is_pinned(&repo_id) {
+                entries.extend(hf_repo_clean_targets(
+                    &repo.path(),
+                    repo_type,
+                    &repo_id,
+                    max_age,
+                ));
+            }
+        }
+    }
+    entries
+}
 
-    if let Ok(home) = std::env::var("HOME") {
-        let home_path = PathBuf::from(home);
+/// Revisions of one hub repo (the stale ones when `max_age` is set, including the one a
+/// ref points at) and the blobs that only they link to
+fn hf_repo_clean_targets(
+    repo_path: &Path,
+    repo_type: &str,
+    repo_id: &str,
+    max_age: Option<Duration>,
+) -> Vec<CacheEntry> {
+    let mut removed = Vec::new();
+    let mut kept_blobs = std::collections::HashSet::new();
+    if let Ok(snapshots) = fs::read_dir(repo_path.join("snapshots")) {
+        for snapshot in snapshots.flatten() {
+            let revision = snapshot.file_name().to_string_lossy().to_string();
+            let (size, mtime) = dir_size_and_mtime(&snapshot.path());
+            if max_age.is_some() && !is_stale(mtime, max_age) {
+                kept_blobs.extend(linked_blobs(&snapshot.path()));
+                continue;
+            }
+            removed.push(CacheEntry {
+                id: format!("hf:{}:{}@{}", repo_type, repo_id, revision),
+                kind: "hf".to_string(),
+                name: format!("{}@{}", repo_id, revision),
+                path: snapshot.path().to_string_lossy().to_string(),
+                size_bytes: size,
+                last_used_at: mtime,
+                stale: true,
+                details: json!({
+                    "type": "hf_revision",
+                    "repo": repo_id,
+                    "revision": revision,
+                }),
+            });
+        }
+    }
 
-        let hf_cache = home_path.join(".cache/huggingface");
-        if hf_cache.exists() {
-            let (size, mtime) = dir_size_and_mtime(&hf_cache);
-            entries.push(CacheEntry {
-                id: "hf:cache".to_string(),
+    // Blobs still being downloaded end in `.incomplete` and belong to no snapshot yet
+    if let Ok(blobs) = fs::read_dir(repo_path.join("blobs")) {
+        for blob in blobs.flatten() {
+            let name = blob.file_name().to_string_lossy().to_string();
+            if kept_blobs.contains(&name) || name.ends_with(".incomplete") {
+                continue;
+            }
+            let (size, mtime) = dir_size_and_mtime(&blob.path());
+            removed.push(CacheEntry {
+                id: format!("hf:{}:{}:blobs/{}", repo_type, repo_id, name),
                 kind: "hf".to_string(),
-                name: "Hugging Face cache".to_string(),
-                path: hf_cache.to_string_lossy().to_string(),
+                name: format!("{} blob {}", repo_id, name),
+                path: blob.path().to_string_lossy().to_string(),
                 size_bytes: size,
                 last_used_at: mtime,
-                stale: is_stale(mtime, stale_days),
-                details: json!({ "type": "hf_cache" }),
+                stale: true,
+                details: json!({ "type": "hf_blob", "repo": repo_id }),
             });
         }
     }
+    removed
+}
 
-    entries
+/// `~/.cache/huggingface` and its contents other than hub caches and the login token
+fn hf_cache_outside_hub() -> Option<(PathBuf, Vec<PathBuf>)> {
+    let home = std::env::var("HOME").ok()?;
+    let hf_cache = PathBuf::from(home).join(".cache/huggingface");
+    let hub = get_hub_dir();
+    let rest = fs::read_dir(&hf_cache)
+        .ok()?
+        .flatten()
+        .map(|e| e.path())
+        .filter(|p| Some(p) != hub.as_ref())
+        .filter(|p| {
+            let name = p.file_name().unwrap_or_default();
+            name != "hub" && name != "token" && name != "stored_tokens"
+        })
+        .collect();
+    Some((hf_cache, rest))
+}
+
+/// Revisions checked out in a hub repo, with the refs (e.g. `main`) pointing at each
+fn hf_revisions(repo_path: &Path) -> Vec<serde_json::Value> {
+    let mut refs: Vec<(String, String)> = Vec::new();
+    if let Ok(ref_files) = fs::read_dir(repo_path.join("refs")) {
+        for r in ref_files.flatten() {
+            if let Ok(commit) = fs::read_to_string(r.path()) {
+                refs.push((
+                    commit.trim().to_string(),
+                    r.file_name().to_string_lossy().to_string(),
+                ));
+            }
+        }
+    }
+
+    let mut revisions = Vec::new();
+    if let Ok(snapshots) = fs::read_dir(repo_path.join("snapshots")) {
+        for snapshot in snapshots.flatten() {
+            let revision = snapshot.file_name().to_string_lossy().to_string();
+            let (_, mtime) = dir_size_and_mtime(&snapshot.path());
+            let names: Vec<&String> = refs
+                .iter()
+                .filter(|(commit, _)| *commit == revision)
+                .map(|(_, name)| name)
+                .collect();
+            revisions.push(json!({
+                "revision": revision,
+                "refs": names,
+                "lastUsedAt": mtime,
+            }));
+        }
+    }
+    revisions
 }
 
-pub fn detect_torch_caches(stale_days: Option<u32>) -> Vec<CacheEntry> {
+pub fn detect_torch_caches(max_age: Option<Duration>) -> Vec<CacheEntry> {
     let mut entries = Vec::new();
 
     if let Ok(home) = std::env::var("HOME") {
@@ -450,7 +658,7 @@ pub fn detect_torch_caches(stale_days: Option<u32>) -> Vec<CacheEntry> {
                 path: torch_cache.to_string_lossy().to_string(),
                 size_bytes: size,
                 last_used_at: mtime,
-                stale: is_stale(mtime, stale_days),
+                stale: is_stale(mtime, max_age),
                 details: json!({ "type": "torch_cache" }),
             });
         }
@@ -459,7 +667,7 @@ pub fn detect_torch_caches(stale_days: Option<u32>) -> Vec<CacheEntry> {
     entries
 }
 
-pub fn detect_vercel_caches(stale_days: Option<u32>) -> Vec<CacheEntry> {
+pub fn detect_vercel_caches(max_age: Option<Duration>) -> Vec<CacheEntry> {
     let mut entries = Vec::new();
 
     if let Ok(home) = std::env::var("HOME") {
@@ -476,7 +684,7 @@ pub fn detect_vercel_caches(stale_days: Option<u32>) -> Vec<CacheEntry> {
                 path: vercel_cache.to_string_lossy().to_string(),
                 size_bytes: size,
                 last_used_at: mtime,
-                stale: is_stale(mtime, stale_days),
+                stale: is_stale(mtime, max_age),
                 details: json!({
                     "type": "vercel_cache"
                 }),
@@ -487,7 +695,7 @@ pub fn detect_vercel_caches(stale_days: Option<u32>) -> Vec<CacheEntry> {
     entries
 }
 
-pub fn detect_cloudflare_caches(stale_days: Option<u32>) -> Vec<CacheEntry> {
+pub fn detect_cloudflare_caches(max_age: Option<Duration>) -> Vec<CacheEntry> {
     let mut entries = Vec::new();
 
     if let Ok(home) = std::env::var("HOME") {
@@ -504,7 +712,7 @@ pub fn detect_cloudflare_caches(stale_days: Option<u32>) -> Vec<CacheEntry> {
                 path: cf_cache.to_string_lossy().to_string(),
                 size_bytes: size,
                 last_used_at: mtime,
-                stale: is_stale(mtime, stale_days),
+                stale: is_stale(mtime, max_age),
                 details: json!({
                     "type": "cloudflare_cache"
                 }),
@@ -514,3 +722,44 @@ pub fn detect_cloudflare_caches(stale_days: Option<u32>) -> Vec<CacheEntry> {
 
     entries
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(unix)]
+    #[test]
+    fn test_hub_clean_targets_skip_only_pinned_repos() {
+        let hub = std::env::temp_dir().join(format!("port-kill-hub-{}", std::process::id()));
+        for repo in ["models--org--free", "models--org--kept"] {
+            let repo = hub.join(repo);
+            fs::create_dir_all(repo.join("refs")).unwrap();
+            fs::create_dir_all(repo.join("snapshots/abc")).unwrap();
+            fs::create_dir_all(repo.join("blobs")).unwrap();
+            fs::write(repo.join("refs/main"), "abc").unwrap();
+            fs::write(repo.join("blobs/1234"), "{}").unwrap();
+            std::os::unix::fs::symlink("../../blobs/1234", repo.join("snapshots/abc/config.json"))
+                .unwrap();
+        }
+        let pins = HfPins {
+            pinned: vec!["org/kept".to_string()],
+        };
+
+        let mut ids: Vec<String> = hub_clean_targets(&hub, &pins, None)
+            .into_iter()
+            .map(|entry| entry.id)
+            .collect();
+        ids.sort();
+        // The revision `main` points at goes too, along with its blobs
+        assert_eq!(
+            ids,
+            vec!["hf:model:org/free:blobs/1234", "hf:model:org/free@abc"]
+        );
+
+        // Recently used revisions survive an age filter
+        let recent = hub_clean_targets(&hub, &pins, Some(Duration::days(30)));
+        assert!(recent.is_empty());
+
+        let _ = fs::remove_dir_all(&hub);
+    }
+}
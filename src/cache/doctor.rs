@@ -1,6 +1,10 @@
+use super::backup::move_to_trash;
+use crate::file_monitor::FileMonitor;
 use serde::Serialize;
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::time::{Duration, SystemTime};
 
 #[derive(Debug, Serialize)]
 pub struct DoctorReport {
@@ -8,9 +12,38 @@ pub struct DoctorReport {
     pub notes: Vec<String>,
     pub warnings: Vec<String>,
     pub errors: Vec<String>,
+    pub issues: Vec<DoctorIssue>,
 }
 
-pub async fn doctor() -> DoctorReport {
+/// A concrete problem found while verifying caches, with the fix that was (or can be) applied
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DoctorIssue {
+    pub kind: String,
+    pub path: String,
+    pub message: String,
+    pub suggested_fix: String,
+    pub fixed: bool,
+}
+
+/// Lock files, temporary writes and npx installs younger than this may belong to an operation
+/// that is still running
+const STALE_LOCK_MIN_AGE: Duration = Duration::from_secs(10 * 60);
+
+/// Dependency lockfiles share the `.lock` extension but are not locks and must never be removed
+const DEPENDENCY_LOCKFILES: [&str; 9] = [
+    "Cargo.lock",
+    "yarn.lock",
+    "flake.lock",
+    "Gemfile.lock",
+    "poetry.lock",
+    "composer.lock",
+    "Pipfile.lock",
+    "bun.lock",
+    "uv.lock",
+];
+
+pub async fn doctor(fix: bool, trash: bool) -> DoctorReport {
     let mut notes = Vec::new();
     let mut warnings = Vec::new();
     let mut errors = Vec::new();
@@ -115,13 +148,339 @@ pub async fn doctor() -> DoctorReport {
         }
     }
 
+    let mut issues = Vec::new();
+    let fix_mode = match (fix, trash) {
+        (false, _) => Fix::ReportOnly,
+        (true, false) => Fix::Remove,
+        (true, true) => Fix::Trash,
+    };
+    if let Ok(home) = std::env::var("HOME") {
+        let home = PathBuf::from(home);
+        check_npm_cacache(&home.join(".npm/_cacache"), fix_mode, &mut issues);
+        check_npx_installs(&home.join(".npm/_npx"), fix_mode, &mut issues);
+        check_stale_locks(&home.join(".npm/_locks"), 1, fix_mode, &mut issues);
+    }
+    check_stale_locks(&cwd.join(".git"), 1, fix_mode, &mut issues);
+    check_stale_locks(&cwd.join("node_modules/.cache"), 3, fix_mode, &mut issues);
+    check_lockfiles(&cwd, &mut issues);
+
+    let unfixed = issues.iter().filter(|i| !i.fixed).count();
+    if unfixed > 0 {
+        warnings.push(format!(
+            "{} cache issue(s) found{}",
+            unfixed,
+            if fix {
+                ""
+            } else {
+                " - run with --fix to repair"
+            }
+        ));
+    }
+
     let ok = errors.is_empty();
     DoctorReport {
         ok,
         notes,
         warnings,
         errors,
+        issues,
+    }
+}
+
+fn record_issue(
+    issues: &mut Vec<DoctorIssue>,
+    kind: &str,
+    path: &Path,
+    message: String,
+    suggested_fix: &str,
+    fixed: bool,
+) {
+    issues.push(DoctorIssue {
+        kind: kind.to_string(),
+        path: path.to_string_lossy().to_string(),
+        message,
+        suggested_fix: suggested_fix.to_string(),
+        fixed,
+    });
+}
+
+/// What to do with a broken file or directory once it has been reported
+#[derive(Debug, Clone, Copy)]
+enum Fix {
+    ReportOnly,
+    Remove,
+    Trash,
+}
+
+impl Fix {
+    /// Apply the fix to `path`, returning whether it was repaired
+    fn apply(self, path: &Path) -> bool {
+        match self {
+            Fix::ReportOnly => false,
+            Fix::Remove => remove_path(path, false),
+            Fix::Trash => remove_path(path, true),
+        }
+    }
+}
+
+fn remove_path(path: &Path, trash: bool) -> bool {
+    let result = if trash {
+        move_to_trash(path)
+    } else if path.is_dir() {
+        fs::remove_dir_all(path)
+    } else {
+        fs::remove_file(path)
+    };
+    match result {
+        Ok(()) => true,
+        Err(e) => {
+            eprintln!("Warning: Failed to remove {}: {}", path.display(), e);
+            false
+        }
+    }
+}
+
+/// npm writes cache content to `_cacache/tmp` first and moves it into place when done, so
+/// anything left there (and empty content blobs) comes from an interrupted write
+fn check_npm_cacache(cacache: &Path, fix: Fix, issues: &mut Vec<DoctorIssue>) {
+    if !cacache.exists() {
+        return;
+    }
+
+    if let Ok(entries) = fs::read_dir(cacache.join("tmp")) {
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if !is_stale(&path) {
+                continue;
+            }
+            let fixed = fix.apply(&path);
+            record_issue(
+                issues,
+                "npm-partial-write",
+                &path,
+                "Half-written npm cache entry".to_string(),
+                "Remove the temporary file (or run `npm cache verify`)",
+                fixed,
+            );
+        }
+    }
+
+    for entry in walkdir::WalkDir::new(cacache.join("content-v2"))
+        .into_iter()
+        .flatten()
+    {
+        let empty = entry
+            .metadata()
+            .map(|m| m.is_file() && m.len() == 0)
+            .unwrap_or(false);
+        if empty && is_stale(entry.path()) {
+            let fixed = fix.apply(entry.path());
+            record_issue(
+                issues,
+                "npm-corrupt-content",
+                entry.path(),
+                "Empty npm cache content blob".to_string(),
+                "Remove the blob so npm refetches it (or run `npm cache verify`)",
+                fixed,
+            );
+        }
+    }
+}
+
+/// An npx install without an installed package is left behind when `npx` is interrupted
+fn check_npx_installs(npx_dir: &Path, fix: Fix, issues: &mut Vec<DoctorIssue>) {
+    let Ok(entries) = fs::read_dir(npx_dir) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if !path.is_dir() {
+            continue;
+        }
+        let installed = fs::read_dir(path.join("node_modules"))
+            .map(|mut d| d.next().is_some())
+            .unwrap_or(false);
+        if (!path.join("package.json").exists() || !installed) && is_stale(&path) {
+            let fixed = fix.apply(&path);
+            record_issue(
+                issues,
+                "npx-orphan",
+                &path,
+                "npx install without an installed package".to_string(),
+                "Remove the directory; npx will reinstall on next use",
+                fixed,
+            );
+        }
+    }
+}
+
+/// Report `*.lock` files that no running process holds. When the lock records a PID, that PID
+/// must also be dead before the lock is considered stale.
+fn check_stale_locks(dir: &Path, max_depth: usize, fix: Fix, issues: &mut Vec<DoctorIssue>) {
+    if !dir.exists() {
+        return;
+    }
+    let file_monitor = FileMonitor::new();
+
+    for entry in walkdir::WalkDir::new(dir)
+        .max_depth(max_depth)
+        .into_iter()
+        .flatten()
+    {
+        let path = entry.path();
+        let name = entry.file_name().to_string_lossy();
+        if !name.ends_with(".lock")
+            || !entry.file_type().is_file()
+            || DEPENDENCY_LOCKFILES.contains(&&*name)
+        {
+            continue;
+        }
+
+        if !is_stale(path) {
+            continue;
+        }
+
+        let recorded_pid = fs::read_to_string(path)
+            .ok()
+            .and_then(|c| c.trim().parse::<u32>().ok());
+        if recorded_pid.map(is_pid_alive).unwrap_or(false) {
+            continue;
+        }
+        let held = file_monitor
+            .find_processes_with_file(&path.to_string_lossy())
+            .map(|p| !p.is_empty())
+            .unwrap_or(false);
+        if held {
+            continue;
+        }
+
+        let message = match recorded_pid {
+            Some(pid) => format!("Lock file held by dead process {}", pid),
+            None => "Lock file not held by any process".to_string(),
+        };
+        let fixed = fix.apply(path);
+        record_issue(
+            issues,
+            "stale-lock",
+            path,
+            message,
+            "Remove the lock file",
+            fixed,
+        );
+    }
+}
+
+/// Whether `path` was last modified at least [`STALE_LOCK_MIN_AGE`] ago
+fn is_stale(path: &Path) -> bool {
+    fs::symlink_metadata(path)
+        .ok()
+        .and_then(|m| m.modified().ok())
+        .and_then(|t| SystemTime::now().duration_since(t).ok())
+        .map(|age| age >= STALE_LOCK_MIN_AGE)
+        .unwrap_or(false)
+}
+
+fn is_pid_alive(pid: u32) -> bool {
+    let mut system = sysinfo::System::new();
+    system.refresh_process(sysinfo::Pid::from_u32(pid))
+}
+
+/// Lockfile and engine mismatches can't be repaired automatically, so only suggest fixes
+fn check_lockfiles(project: &Path, issues: &mut Vec<DoctorIssue>) {
+    let package_json = project.join("package.json");
+    let Some(manifest) = fs::read_to_string(&package_json)
+        .ok()
+        .and_then(|c| serde_json::from_str::<serde_json::Value>(&c).ok())
+    else {
+        return;
+    };
+
+    let lockfiles: Vec<(&str, &str)> = [
+        ("package-lock.json", "npm"),
+        ("yarn.lock", "yarn"),
+        ("pnpm-lock.yaml", "pnpm"),
+        ("bun.lockb", "bun"),
+    ]
+    .into_iter()
+    .filter(|(file, _)| project.join(file).exists())
+    .collect();
+
+    if lockfiles.len() > 1 {
+        let names: Vec<&str> = lockfiles.iter().map(|(f, _)| *f).collect();
+        record_issue(
+            issues,
+            "lockfile-conflict",
+            project,
+            format!("Multiple lockfiles present: {}", names.join(", ")),
+            "Keep only the lockfile of the package manager the project uses",
+            false,
+        );
+    }
+
+    if let Some(declared) = manifest.get("packageManager").and_then(|v| v.as_str()) {
+        let declared = declared.split('@').next().unwrap_or(declared);
+        for (file, manager) in &lockfiles {
+            if *manager != declared {
+                record_issue(
+                    issues,
+                    "lockfile-mismatch",
+                    &project.join(file),
+                    format!("{} found but packageManager is {}", file, declared),
+                    "Remove the stray lockfile and reinstall with the declared package manager",
+                    false,
+                );
+            }
+        }
     }
+
+    let required = manifest
+        .get("engines")
+        .and_then(|e| e.get("node"))
+        .and_then(|v| v.as_str());
+    if let (Some(required), Some(installed)) = (required, installed_node_major()) {
+        if let Some(minimum) = minimum_major(required) {
+            if installed < minimum {
+                record_issue(
+                    issues,
+                    "engine-mismatch",
+                    &package_json,
+                    format!(
+                        "package.json requires node {} but node {} is installed",
+                        required, installed
+                    ),
+                    "Switch Node.js versions (e.g., with nvm or mise) before reinstalling",
+                    false,
+                );
+            }
+        }
+    }
+}
+
+fn installed_node_major() -> Option<u64> {
+    let output = Command::new("node").arg("--version").output().ok()?;
+    let version = String::from_utf8_lossy(&output.stdout);
+    version
+        .trim()
+        .trim_start_matches('v')
+        .split('.')
+        .next()?
+        .parse()
+        .ok()
+}
+
+/// Lowest major version allowed by a simple engines range such as `>=18`, `^20.1` or `18.x`
+fn minimum_major(range: &str) -> Option<u64> {
+    range
+        .split("||")
+        .filter_map(|part| {
+            part.trim()
+                .trim_start_matches(['>', '=', '^', '~', 'v', ' '])
+                .split(['.', ' '])
+                .next()?
+                .parse::<u64>()
+                .ok()
+        })
+        .min()
 }
 
 fn get_dir_size(path: &std::path::Path) -> Result<u64, std::io::Error> {
@@ -137,3 +496,33 @@ fn get_dir_size(path: &std::path::Path) -> Result<u64, std::io::Error> {
     }
     Ok(total)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_minimum_major() {
+        assert_eq!(minimum_major(">=18"), Some(18));
+        assert_eq!(minimum_major("^20.1.0"), Some(20));
+        assert_eq!(minimum_major("16.x || >=18"), Some(16));
+        assert_eq!(minimum_major("*"), None);
+    }
+
+    #[test]
+    fn test_fresh_partial_writes_are_left_alone() {
+        let dir = std::env::temp_dir().join(format!("port-kill-doctor-{}", std::process::id()));
+        let cacache = dir.join("_cacache");
+        fs::create_dir_all(cacache.join("tmp")).unwrap();
+        fs::write(cacache.join("tmp").join("in-flight"), b"x").unwrap();
+        fs::create_dir_all(dir.join("_npx").join("abc")).unwrap();
+
+        let mut issues = Vec::new();
+        check_npm_cacache(&cacache, Fix::Remove, &mut issues);
+        check_npx_installs(&dir.join("_npx"), Fix::Remove, &mut issues);
+        assert!(issues.is_empty());
+        assert!(cacache.join("tmp").join("in-flight").exists());
+        assert!(dir.join("_npx").join("abc").exists());
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
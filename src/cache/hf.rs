@@ -0,0 +1,160 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Hugging Face repos that `cache --clean` must never remove
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct HfPins {
+    pub pinned: Vec<String>,
+}
+
+pub fn get_pins_file() -> PathBuf {
+    let home = std::env::var("HOME")
+        .or_else(|_| std::env::var("USERPROFILE"))
+        .unwrap_or_else(|_| "/tmp".to_string());
+    PathBuf::from(home).join(".port-kill").join("hf-pins.json")
+}
+
+/// Location of the Hugging Face hub cache, honouring HF_HUB_CACHE and HF_HOME
+pub fn get_hub_dir() -> Option<PathBuf> {
+    if let Ok(dir) = std::env::var("HF_HUB_CACHE") {
+        return Some(PathBuf::from(dir));
+    }
+    if let Ok(dir) = std::env::var("HF_HOME") {
+        return Some(PathBuf::from(dir).join("hub"));
+    }
+    let home = std::env::var("HOME").ok()?;
+    Some(PathBuf::from(home).join(".cache/huggingface/hub"))
+}
+
+/// Split a hub directory name such as `models--org--name` into `("model", "org/name")`
+pub fn parse_repo_dir(dir_name: &str) -> Option<(&'static str, String)> {
+    let (repo_type, rest) = dir_name.split_once("--")?;
+    let repo_type = match repo_type {
+        "models" => "model",
+        "datasets" => "dataset",
+        "spaces" => "space",
+        _ => return None,
+    };
+    Some((repo_type, rest.replace("--", "/")))
+}
+
+/// Commits that a ref of a hub repo (`refs/main`, `refs/pr/1`, ...) points at
+pub fn referenced_commits(repo_path: &Path) -> HashSet<String> {
+    walkdir::WalkDir::new(repo_path.join("refs"))
+        .into_iter()
+        .flatten()
+        .filter(|e| e.file_type().is_file())
+        .filter_map(|e| fs::read_to_string(e.path()).ok())
+        .map(|commit| commit.trim().to_string())
+        .collect()
+}
+
+/// Names of the blobs that the files of a snapshot link to
+pub fn linked_blobs(snapshot: &Path) -> HashSet<String> {
+    walkdir::WalkDir::new(snapshot)
+        .into_iter()
+        .flatten()
+        .filter(|e| e.path_is_symlink())
+        .filter_map(|e| fs::read_link(e.path()).ok())
+        .filter_map(|target| target.file_name().map(|n| n.to_string_lossy().to_string()))
+        .collect()
+}
+
+impl HfPins {
+    pub fn load() -> Self {
+        fs::read_to_string(get_pins_file())
+            .ok()
+            .and_then(|c| serde_json::from_str(&c).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self) -> Result<(), std::io::Error> {
+        let path = get_pins_file();
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(path, serde_json::to_string_pretty(self)?)
+    }
+
+    pub fn is_pinned(&self, repo: &str) -> bool {
+        self.pinned.iter().any(|p| p == repo)
+    }
+
+    pub fn pin(&mut self, repo: &str) {
+        if !self.is_pinned(repo) {
+            self.pinned.push(repo.to_string());
+            self.pinned.sort();
+        }
+    }
+
+    pub fn unpin(&mut self, repo: &str) -> bool {
+        let before = self.pinned.len();
+        self.pinned.retain(|p| p != repo);
+        self.pinned.len() != before
+    }
+}
+
+/// Apply `--pin`/`--unpin` and persist the result
+pub fn update_pins(pin: &[String], unpin: &[String]) -> Result<HfPins, std::io::Error> {
+    let mut pins = HfPins::load();
+    for repo in pin {
+        pins.pin(repo);
+    }
+    for repo in unpin {
+        pins.unpin(repo);
+    }
+    pins.save()?;
+    Ok(pins)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_repo_dir() {
+        assert_eq!(
+            parse_repo_dir("models--meta-llama--Llama-2-7b"),
+            Some(("model", "meta-llama/Llama-2-7b".to_string()))
+        );
+        assert_eq!(
+            parse_repo_dir("datasets--squad"),
+            Some(("dataset", "squad".to_string()))
+        );
+        assert_eq!(parse_repo_dir(".locks"), None);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_refs_and_blob_links() {
+        let repo = std::env::temp_dir().join(format!("port-kill-hf-{}", std::process::id()));
+        fs::create_dir_all(repo.join("refs/pr")).unwrap();
+        fs::create_dir_all(repo.join("snapshots/abc")).unwrap();
+        fs::write(repo.join("refs/main"), "abc\n").unwrap();
+        fs::write(repo.join("refs/pr/1"), "def").unwrap();
+        std::os::unix::fs::symlink("../../blobs/1234", repo.join("snapshots/abc/config.json"))
+            .unwrap();
+
+        let commits = referenced_commits(&repo);
+        assert!(commits.contains("abc") && commits.contains("def"));
+        assert_eq!(
+            linked_blobs(&repo.join("snapshots/abc")),
+            HashSet::from(["1234".to_string()])
+        );
+        let _ = fs::remove_dir_all(&repo);
+    }
+
+    #[test]
+    fn test_pin_unpin() {
+        let mut pins = HfPins::default();
+        pins.pin("org/b");
+        pins.pin("org/a");
+        pins.pin("org/a");
+        assert_eq!(pins.pinned, vec!["org/a", "org/b"]);
+        assert!(pins.unpin("org/b"));
+        assert!(!pins.unpin("org/b"));
+        assert!(pins.is_pinned("org/a"));
+    }
+}
@@ -5,6 +5,7 @@ use super::detect::{
 };
 use super::output::{human_since, human_size, print_cache_summary, print_table};
 use super::types::{ListResponse, ListSummary};
+use chrono::Duration;
 use std::path::Path;
 
 pub async fn list_caches(
@@ -15,7 +16,7 @@ pub async fn list_caches(
     include_torch: bool,
     include_vercel: bool,
     include_cloudflare: bool,
-    stale_days: Option<u32>,
+    max_age: Option<Duration>,
 ) -> ListResponse {
     let cwd = std::env::current_dir().unwrap_or_else(|_| std::path::PathBuf::from("."));
 
@@ -29,37 +30,37 @@ pub async fn list_caches(
 
     // Specialized integrations
     if include_npx {
-        entries.extend(detect_npx_caches(stale_days));
+        entries.extend(detect_npx_caches(max_age));
     }
     if include_js_pm {
-        entries.extend(detect_js_pm_caches(stale_days));
+        entries.extend(detect_js_pm_caches(max_age));
     }
     if include_hf {
-        entries.extend(detect_hf_caches(stale_days));
+        entries.extend(detect_hf_caches(max_age));
     }
     if include_torch {
-        entries.extend(detect_torch_caches(stale_days));
+        entries.extend(detect_torch_caches(max_age));
     }
     if include_vercel {
-        entries.extend(detect_vercel_caches(stale_days));
+        entries.extend(detect_vercel_caches(max_age));
     }
     if include_cloudflare {
-        entries.extend(detect_cloudflare_caches(stale_days));
+        entries.extend(detect_cloudflare_caches(max_age));
     }
 
     // Language-based detection (always runs unless only specialized flags were given)
     if !has_specialized_flags || lang != "auto" {
         if lang == "auto" || lang == "rust" {
-            entries.extend(detect_rust_caches(Path::new(&cwd), stale_days));
+            entries.extend(detect_rust_caches(Path::new(&cwd), max_age));
         }
         if lang == "auto" || lang == "js" {
-            entries.extend(detect_js_caches(Path::new(&cwd), stale_days));
+            entries.extend(detect_js_caches(Path::new(&cwd), max_age));
         }
         if lang == "auto" || lang == "py" {
-            entries.extend(detect_python_caches(stale_days));
+            entries.extend(detect_python_caches(max_age));
         }
         if lang == "auto" || lang == "java" {
-            entries.extend(detect_java_caches(stale_days));
+            entries.extend(detect_java_caches(max_age));
         }
     }
 
@@ -97,6 +98,15 @@ pub fn print_list_table(resp: &ListResponse) {
                     .and_then(|v| v.as_str())
                     .unwrap_or("unknown");
                 format!("{}:{}", e.name, version)
+            } else if e.kind == "hf" && e.details.get("repo").is_some() {
+                // For Hugging Face repos, show the repo id, revision count and pin state
+                let revisions = e
+                    .details
+                    .get("revisions")
+                    .and_then(|r| r.as_array())
+                    .map_or(0, |r| r.len());
+                let pinned = if e.is_pinned() { " [pinned]" } else { "" };
+                format!("{} ({} rev){}", e.name, revisions, pinned)
             } else {
                 e.path.clone()
             };
@@ -1,10 +1,16 @@
 pub mod backup;
+pub mod budget;
 pub mod clean;
 pub mod detect;
 pub mod doctor;
+pub mod hf;
 pub mod js_pm;
 pub mod list;
 pub mod npx;
 pub mod output;
+pub mod plan;
+pub mod remote;
 pub mod restore;
+pub mod trends;
 pub mod types;
+pub mod watch;
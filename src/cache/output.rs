@@ -29,6 +29,50 @@ pub fn human_size(bytes: u64) -> String {
     format!("{:.2} {}", size, UNITS[unit_index])
 }
 
+/// Parse a human-readable size such as `500MB`, `10GB` or `1024` (bytes).
+/// Units are binary (1KB = 1024 bytes) to match `human_size`.
+pub fn parse_size(input: &str) -> Result<u64, String> {
+    let trimmed = input.trim();
+    let split = trimmed
+        .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+        .unwrap_or(trimmed.len());
+    let (number, unit) = trimmed.split_at(split);
+    let value: f64 = number
+        .parse()
+        .map_err(|_| format!("Invalid size: '{}'", input))?;
+    let multiplier: u64 = match unit.trim().to_ascii_uppercase().as_str() {
+        "" | "B" => 1,
+        "K" | "KB" => 1 << 10,
+        "M" | "MB" => 1 << 20,
+        "G" | "GB" => 1 << 30,
+        "T" | "TB" => 1 << 40,
+        _ => return Err(format!("Invalid size unit in '{}'", input)),
+    };
+    Ok((value * multiplier as f64) as u64)
+}
+
+/// Parse an age such as `30d`, `12h`, `2w` or `90m`. A bare number means days.
+pub fn parse_age(input: &str) -> Result<chrono::Duration, String> {
+    let trimmed = input.trim();
+    let split = trimmed
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(trimmed.len());
+    let (number, unit) = trimmed.split_at(split);
+    let value: i64 = number
+        .parse()
+        .map_err(|_| format!("Invalid age: '{}'", input))?;
+    match unit.trim().to_ascii_lowercase().as_str() {
+        "m" | "min" => Ok(chrono::Duration::minutes(value)),
+        "h" => Ok(chrono::Duration::hours(value)),
+        "" | "d" => Ok(chrono::Duration::days(value)),
+        "w" => Ok(chrono::Duration::weeks(value)),
+        _ => Err(format!(
+            "Invalid age unit in '{}' (use m, h, d or w)",
+            input
+        )),
+    }
+}
+
 pub fn human_since(ts: Option<DateTime<Utc>>) -> String {
     match ts {
         None => "-".to_string(),
@@ -106,3 +150,25 @@ pub fn print_cache_summary(resp: &super::types::ListResponse) {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_size() {
+        assert_eq!(parse_size("1024").unwrap(), 1024);
+        assert_eq!(parse_size("10GB").unwrap(), 10 * (1 << 30));
+        assert_eq!(parse_size("1.5 kb").unwrap(), 1536);
+        assert!(parse_size("10XB").is_err());
+    }
+
+    #[test]
+    fn test_parse_age() {
+        assert_eq!(parse_age("30d").unwrap(), chrono::Duration::days(30));
+        assert_eq!(parse_age("12h").unwrap(), chrono::Duration::hours(12));
+        assert_eq!(parse_age("2w").unwrap(), chrono::Duration::weeks(2));
+        assert_eq!(parse_age("7").unwrap(), chrono::Duration::days(7));
+        assert!(parse_age("3y").is_err());
+    }
+}
@@ -0,0 +1,194 @@
+use super::detect::detect_hf_clean_targets;
+use super::list::list_caches;
+use super::output::{human_since, human_size};
+use super::types::{CacheEntry, CleanPlanEntry, CleanPlanResponse, CleanPlanSummary};
+use chrono::{Duration, Utc};
+
+/// What a real clean would do with an entry
+pub fn planned_action(
+    entry: &CacheEntry,
+    filtering: bool,
+    safe_delete: bool,
+    trash: bool,
+) -> &'static str {
+    if entry.is_pinned() || (filtering && !entry.stale) {
+        "keep"
+    } else if trash {
+        "trash"
+    } else if safe_delete {
+        "backup"
+    } else {
+        "delete"
+    }
+}
+
+/// How costly it is to lose an entry:
+/// - `rebuildable`: build output regenerated locally by the next build
+/// - `redownloadable`: package/model caches that are fetched again over the network
+/// - `environment`: installed dependencies that need a reinstall before the project runs
+/// - `sensitive`: may hold credentials or project links; review before deleting
+pub fn safety_class(entry: &CacheEntry) -> &'static str {
+    let detail_type = entry
+        .details
+        .get("type")
+        .and_then(|t| t.as_str())
+        .unwrap_or("");
+    match (entry.kind.as_str(), entry.id.as_str(), detail_type) {
+        ("vercel", _, _) | ("cloudflare", _, _) => "sensitive",
+        (_, "js:node_modules", _) | (_, _, "venv") => "environment",
+        ("rust", "rust:cargo-registry", _)
+        | ("npx", _, _)
+        | ("js-pm", _, _)
+        | ("hf", _, _)
+        | ("torch", _, _)
+        | (_, _, "maven_repository")
+        | (_, _, "gradle_cache") => "redownloadable",
+        _ => "rebuildable",
+    }
+}
+
+/// Build the plan a clean with the same flags would execute, without touching anything
+#[allow(clippy::too_many_arguments)]
+pub async fn plan_clean(
+    lang: &str,
+    include_npx: bool,
+    include_js_pm: bool,
+    include_hf: bool,
+    include_torch: bool,
+    include_vercel: bool,
+    include_cloudflare: bool,
+    max_age: Option<Duration>,
+    safe_delete: bool,
+    trash: bool,
+) -> CleanPlanResponse {
+    let mut listing = list_caches(
+        lang,
+        include_npx,
+        include_js_pm,
+        include_hf,
+        include_torch,
+        include_vercel,
+        include_cloudflare,
+        max_age,
+    )
+    .await;
+    // Hub repos are cleaned per revision, so plan what clean will actually remove
+    if include_hf {
+        listing.entries.retain(|e| e.kind != "hf");
+        listing.entries.extend(detect_hf_clean_targets(max_age));
+    }
+
+    let now = Utc::now();
+    let filtering = max_age.is_some();
+    let entries: Vec<CleanPlanEntry> = listing
+        .entries
+        .iter()
+        .map(|e| CleanPlanEntry {
+            provider: e.kind.clone(),
+            id: e.id.clone(),
+            path: e.path.clone(),
+            bytes: e.size_bytes,
+            last_used_at: e.last_used_at,
+            age_days: e.last_used_at.map(|t| (now - t).num_days()),
+            action: planned_action(e, filtering, safe_delete, trash).to_string(),
+            safety: safety_class(e).to_string(),
+        })
+        .collect();
+
+    let affected: Vec<&CleanPlanEntry> = entries.iter().filter(|e| e.action != "keep").collect();
+    CleanPlanResponse {
+        summary: CleanPlanSummary {
+            affected_count: affected.len(),
+            affected_bytes: affected.iter().map(|e| e.bytes).sum(),
+            kept_count: entries.len() - affected.len(),
+        },
+        entries,
+    }
+}
+
+pub fn print_plan_table(plan: &CleanPlanResponse) {
+    println!(
+        "{:<8} | {:<14} | {:<12} | {:<10} | {:<14} | PATH",
+        "ACTION", "SAFETY", "SIZE", "LAST USED", "PROVIDER"
+    );
+    println!("{}", "-".repeat(100));
+    for e in &plan.entries {
+        println!(
+            "{:<8} | {:<14} | {:<12} | {:<10} | {:<14} | {}",
+            e.action,
+            e.safety,
+            human_size(e.bytes),
+            human_since(e.last_used_at),
+            e.provider,
+            e.path
+        );
+    }
+    println!();
+    println!(
+        "Dry run: {} entries ({}) would be cleaned, {} kept",
+        plan.summary.affected_count,
+        human_size(plan.summary.affected_bytes),
+        plan.summary.kept_count
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn entry(id: &str, kind: &str, details: serde_json::Value, stale: bool) -> CacheEntry {
+        CacheEntry {
+            id: id.to_string(),
+            kind: kind.to_string(),
+            name: id.to_string(),
+            path: format!("/tmp/{}", id),
+            size_bytes: 1,
+            last_used_at: None,
+            stale,
+            details,
+        }
+    }
+
+    #[test]
+    fn test_safety_class() {
+        assert_eq!(
+            safety_class(&entry("rust:project-target", "rust", json!({}), false)),
+            "rebuildable"
+        );
+        assert_eq!(
+            safety_class(&entry("rust:cargo-registry", "rust", json!({}), false)),
+            "redownloadable"
+        );
+        assert_eq!(
+            safety_class(&entry("js:node_modules", "js", json!({}), false)),
+            "environment"
+        );
+        assert_eq!(
+            safety_class(&entry(
+                "python:venv:/x/.venv",
+                "python",
+                json!({"type": "venv"}),
+                false
+            )),
+            "environment"
+        );
+        assert_eq!(
+            safety_class(&entry("vercel:cache", "vercel", json!({}), false)),
+            "sensitive"
+        );
+    }
+
+    #[test]
+    fn test_planned_action() {
+        let fresh = entry("js:.next", "js", json!({}), false);
+        let stale = entry("js:.vite", "js", json!({}), true);
+        assert_eq!(planned_action(&fresh, true, true, false), "keep");
+        assert_eq!(planned_action(&stale, true, true, false), "backup");
+        assert_eq!(planned_action(&fresh, false, true, true), "trash");
+        assert_eq!(planned_action(&fresh, false, false, false), "delete");
+
+        let pinned = entry("hf:model:org/name", "hf", json!({"pinned": true}), false);
+        assert_eq!(planned_action(&pinned, false, false, false), "keep");
+    }
+}
@@ -0,0 +1,208 @@
+use super::budget::print_budget_report;
+use super::list::print_list_table;
+use super::plan::print_plan_table;
+use super::restore::print_backup_list;
+use super::types::{BackupListResponse, BudgetReport, CleanPlanResponse, ListResponse};
+use crate::cli::CacheArgs;
+use std::process::Command;
+
+/// Rebuild the `cache` invocation to run on the remote host. JSON output is always
+/// requested so the results come back with the same schema as a local run.
+pub fn remote_cache_args(c: &CacheArgs) -> Result<Vec<String>, String> {
+    if c.watch_disk {
+        return Err("--watch-disk is not supported with --remote".to_string());
+    }
+    if c.select {
+        return Err("--select is not supported with --remote; use --only instead".to_string());
+    }
+
+    let mut args = vec!["cache".to_string()];
+    let modes = [
+        (c.list, "--list"),
+        (c.check, "--check"),
+        (c.clean, "--clean"),
+        (c.dry_run, "--dry-run"),
+        (c.restore, "--restore"),
+        (c.restore_last, "--restore-last"),
+        (c.list_backups, "--list-backups"),
+        (c.doctor, "--doctor"),
+        (c.prune_backups, "--prune-backups"),
+        (c.trends, "--trends"),
+        (c.npx, "--npx"),
+        (c.js_pm, "--js-pm"),
+        (c.hf, "--hf"),
+        (c.torch, "--torch"),
+        (c.vercel, "--vercel"),
+        (c.cloudflare, "--cloudflare"),
+        (c.force, "--force"),
+        (c.fix, "--fix"),
+        (c.trash, "--trash"),
+        (c.compress, "--compress"),
+    ];
+    for (enabled, flag) in modes {
+        if enabled {
+            args.push(flag.to_string());
+        }
+    }
+    args.push("--json".to_string());
+    args.push(format!("--lang={}", c.lang));
+
+    if let Some(max_age) = c.max_age() {
+        args.push(format!("--older-than={}m", max_age.num_minutes()));
+    }
+    if let Some(backup) = &c.backup {
+        args.push(format!("--backup={}", backup));
+    }
+    if let Some(only) = &c.only {
+        args.push(format!("--only={}", only.join(",")));
+    }
+    if !c.pin.is_empty() {
+        args.push(format!("--pin={}", c.pin.join(",")));
+    }
+    if !c.unpin.is_empty() {
+        args.push(format!("--unpin={}", c.unpin.join(",")));
+    }
+    if let Some(bytes) = c.budget {
+        args.push(format!("--budget={}B", bytes));
+    }
+    if let Some(n) = c.keep_backups {
+        args.push(format!("--keep-backups={}", n));
+    }
+    if let Some(bytes) = c.max_backup_size {
+        args.push(format!("--max-backup-size={}B", bytes));
+    }
+    if let Some(days) = c.max_backup_age {
+        args.push(format!("--max-backup-age={}", days));
+    }
+    Ok(args)
+}
+
+/// Quote an argument for the remote shell
+fn shell_quote(arg: &str) -> String {
+    format!("'{}'", arg.replace('\'', r"'\''"))
+}
+
+/// The command line for the remote shell. A leading `~/` in `binary` stays unquoted so
+/// the remote shell expands it to the remote home.
+fn remote_command(binary: &str, args: &[String]) -> String {
+    let binary = match binary.strip_prefix("~/") {
+        Some(rest) => format!("~/{}", shell_quote(rest)),
+        None => shell_quote(binary),
+    };
+    std::iter::once(binary)
+        .chain(args.iter().map(|a| shell_quote(a)))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Run a cache operation on `host` over SSH with its `binary` (`--remote-bin`) and render
+/// the results locally. Returns the remote exit code so callers can propagate failures.
+pub fn run_remote_cache(host: &str, binary: &str, c: &CacheArgs) -> Result<i32, String> {
+    let remote_command = remote_command(binary, &remote_cache_args(c)?);
+
+    if !c.json {
+        eprintln!("🌐 Running cache operation on {}", host);
+    }
+
+    let output = Command::new("ssh")
+        .arg(host)
+        .arg(&remote_command)
+        .output()
+        .map_err(|e| format!("Failed to run ssh: {}", e))?;
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    if !stderr.trim().is_empty() {
+        eprint!("{}", stderr);
+    }
+    let code = output.status.code().unwrap_or(1);
+    if stdout.trim().is_empty() && code != 0 {
+        return Err(format!(
+            "Remote command failed on {} (exit code {})",
+            host, code
+        ));
+    }
+
+    if c.json {
+        print!("{}", stdout);
+    } else if c.list {
+        let resp: ListResponse = parse_remote(&stdout)?;
+        print_list_table(&resp);
+    } else if c.check {
+        let report: BudgetReport = parse_remote(&stdout)?;
+        print_budget_report(&report);
+    } else if c.dry_run {
+        let plan: CleanPlanResponse = parse_remote(&stdout)?;
+        print_plan_table(&plan);
+    } else if c.list_backups {
+        let resp: BackupListResponse = parse_remote(&stdout)?;
+        print_backup_list(&resp);
+    } else {
+        print!("{}", stdout);
+    }
+    Ok(code)
+}
+
+fn parse_remote<T: serde::de::DeserializeOwned>(stdout: &str) -> Result<T, String> {
+    serde_json::from_str(stdout).map_err(|e| format!("Unexpected output from remote host: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cli::{self, Args};
+    use clap::Parser;
+
+    fn cache_args(argv: &[&str]) -> CacheArgs {
+        let args = Args::parse_from(argv);
+        match args.command.unwrap() {
+            cli::Command::Op(c) => c,
+            other => panic!("expected a cache operation, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_remote_cache_args() {
+        let c = cache_args(&[
+            "port-kill",
+            "--remote",
+            "build-box",
+            "cache",
+            "--clean",
+            "--hf",
+            "--older-than",
+            "2d",
+        ]);
+        let args = remote_cache_args(&c).unwrap();
+        assert_eq!(args[0], "cache");
+        assert!(args.contains(&"--clean".to_string()));
+        assert!(args.contains(&"--hf".to_string()));
+        assert!(args.contains(&"--json".to_string()));
+        assert!(args.contains(&"--older-than=2880m".to_string()));
+    }
+
+    #[test]
+    fn test_remote_rejects_watch_disk() {
+        let c = cache_args(&["port-kill", "cache", "--watch-disk"]);
+        assert!(remote_cache_args(&c).is_err());
+    }
+
+    #[test]
+    fn test_shell_quote() {
+        assert_eq!(shell_quote("a b"), "'a b'");
+        assert_eq!(shell_quote("it's"), r"'it'\''s'");
+    }
+
+    #[test]
+    fn test_remote_command() {
+        let args = vec!["cache".to_string(), "--list".to_string()];
+        assert_eq!(
+            remote_command("port-kill-console", &args),
+            "'port-kill-console' 'cache' '--list'"
+        );
+        assert_eq!(
+            remote_command("~/.cargo/bin/port-kill-console", &args),
+            "~/'.cargo/bin/port-kill-console' 'cache' '--list'"
+        );
+    }
+}
@@ -1,41 +1,102 @@
-use super::backup::{find_latest_backup, restore_from_backup};
-use super::types::RestoreResponse;
+use super::backup::{
+    find_backup, find_latest_backup, list_backups, read_manifest, restore_from_backup,
+};
+use super::output::{human_since, human_size};
+use super::types::{BackupListResponse, BackupSummary, RestoreResponse};
+use std::io::{IsTerminal, Write};
+use std::path::Path;
 
 pub async fn restore_last_backup() -> RestoreResponse {
-    match find_latest_backup() {
-        Ok(Some(backup_path)) => match restore_from_backup(&backup_path).await {
-            Ok(count) => {
-                let restored_from = backup_path.to_string_lossy().to_string();
-                if count == 0 {
-                    eprintln!("No entries were restored from backup (destinations may be non-empty or backup files missing)");
-                    RestoreResponse {
-                        restored_from,
-                        restored_count: 0,
-                        error: Some("No entries were restored from backup".to_string()),
+    restore_backup(None, None, false, false).await
+}
+
+/// Restore from the backup matching `timestamp` (or the latest one). Entries can be narrowed
+/// with `only_ids`, or picked interactively from a numbered list when `select` is set. The
+/// list and prompt go to stderr, and there is no prompt with `json` or when stdin is not a
+/// terminal: `only_ids` is used then, and nothing is restored without it.
+pub async fn restore_backup(
+    timestamp: Option<&str>,
+    only_ids: Option<&[String]>,
+    select: bool,
+    json: bool,
+) -> RestoreResponse {
+    let found = match timestamp {
+        Some(ts) => find_backup(ts),
+        None => find_latest_backup(),
+    };
+
+    match found {
+        Ok(Some(backup_path)) => {
+            let restored_from = backup_path.to_string_lossy().to_string();
+            let interactive = !json && std::io::stdin().is_terminal();
+            if select && !interactive && only_ids.is_none() {
+                let message = "--select needs an interactive terminal; pick entries with --only instead";
+                eprintln!("{}", message);
+                return RestoreResponse {
+                    restored_from,
+                    restored_count: 0,
+                    error: Some(message.to_string()),
+                };
+            }
+            let selected = if select && interactive {
+                match prompt_for_entries(&backup_path) {
+                    Ok(ids) if ids.is_empty() => {
+                        return RestoreResponse {
+                            restored_from,
+                            restored_count: 0,
+                            error: Some("No entries selected".to_string()),
+                        }
+                    }
+                    Ok(ids) => Some(ids),
+                    Err(e) => {
+                        return RestoreResponse {
+                            restored_from,
+                            restored_count: 0,
+                            error: Some(format!("Failed to select entries: {}", e)),
+                        }
+                    }
+                }
+            } else {
+                only_ids.map(|ids| ids.to_vec())
+            };
+
+            match restore_from_backup(&backup_path, selected.as_deref()).await {
+                Ok(count) => {
+                    if count == 0 {
+                        eprintln!("No entries were restored from backup (destinations may be non-empty or backup files missing)");
+                        RestoreResponse {
+                            restored_from,
+                            restored_count: 0,
+                            error: Some("No entries were restored from backup".to_string()),
+                        }
+                    } else {
+                        RestoreResponse {
+                            restored_from,
+                            restored_count: count,
+                            error: None,
+                        }
                     }
-                } else {
+                }
+                Err(e) => {
+                    eprintln!("Error restoring backup: {}", e);
                     RestoreResponse {
                         restored_from,
-                        restored_count: count,
-                        error: None,
+                        restored_count: 0,
+                        error: Some(format!("Failed to restore from backup: {}", e)),
                     }
                 }
             }
-            Err(e) => {
-                eprintln!("Error restoring backup: {}", e);
-                RestoreResponse {
-                    restored_from: backup_path.to_string_lossy().to_string(),
-                    restored_count: 0,
-                    error: Some(format!("Failed to restore from backup: {}", e)),
-                }
-            }
-        },
+        }
         Ok(None) => {
-            eprintln!("No backup found to restore");
+            let message = match timestamp {
+                Some(ts) => format!("No backup found matching '{}'", ts),
+                None => "No backup found to restore".to_string(),
+            };
+            eprintln!("{}", message);
             RestoreResponse {
                 restored_from: String::new(),
                 restored_count: 0,
-                error: Some("No backup found to restore".to_string()),
+                error: Some(message),
             }
         }
         Err(e) => {
@@ -48,3 +109,136 @@ pub async fn restore_last_backup() -> RestoreResponse {
         }
     }
 }
+
+/// All backups with their entries, newest first
+pub fn list_backup_entries() -> BackupListResponse {
+    let backups = list_backups()
+        .unwrap_or_default()
+        .into_iter()
+        .filter_map(|info| {
+            let manifest = read_manifest(&info.path).ok()?;
+            Some(BackupSummary {
+                name: backup_label(&info.path),
+                path: info.path.to_string_lossy().to_string(),
+                timestamp: info.timestamp,
+                size_bytes: info.size_bytes,
+                compressed: manifest.compressed,
+                entries: manifest.entries,
+            })
+        })
+        .collect();
+    BackupListResponse { backups }
+}
+
+pub fn print_backup_list(resp: &BackupListResponse) {
+    if resp.backups.is_empty() {
+        println!("No backups found.");
+        return;
+    }
+    for backup in &resp.backups {
+        println!(
+            "📦 {} ({}, {} entries, {})",
+            backup.name,
+            human_since(Some(backup.timestamp)),
+            backup.entries.len(),
+            human_size(backup.size_bytes)
+        );
+        for entry in &backup.entries {
+            println!(
+                "   {:<40} {:<10} {:>12}  {}",
+                entry.id,
+                entry.kind,
+                human_size(entry.size_bytes),
+                entry.path
+            );
+        }
+    }
+}
+
+fn backup_label(path: &Path) -> String {
+    path.file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_default()
+}
+
+/// Show the entries of a backup as a numbered list on stderr and read the user's choice
+/// from stdin
+fn prompt_for_entries(backup_path: &Path) -> Result<Vec<String>, std::io::Error> {
+    let manifest = read_manifest(backup_path)?;
+    eprintln!(
+        "Backup {} ({})",
+        backup_label(backup_path),
+        human_since(Some(manifest.timestamp))
+    );
+    for (i, entry) in manifest.entries.iter().enumerate() {
+        eprintln!(
+            "  [{}] {:<40} {:>12}  {}",
+            i + 1,
+            entry.name,
+            human_size(entry.size_bytes),
+            entry.path
+        );
+    }
+    eprint!("Select entries to restore (e.g. 1,3-4 or 'all', empty to cancel): ");
+    std::io::stderr().flush()?;
+
+    let mut input = String::new();
+    std::io::stdin().read_line(&mut input)?;
+    let indices = parse_selection(&input, manifest.entries.len())
+        .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidInput, e))?;
+    Ok(indices
+        .into_iter()
+        .map(|i| manifest.entries[i].id.clone())
+        .collect())
+}
+
+/// Parse a selection like `1,3-4` or `all` into zero-based indices below `count`
+fn parse_selection(input: &str, count: usize) -> Result<Vec<usize>, String> {
+    let input = input.trim();
+    if input.eq_ignore_ascii_case("all") {
+        return Ok((0..count).collect());
+    }
+
+    let mut indices = Vec::new();
+    for part in input.split(',').map(str::trim).filter(|p| !p.is_empty()) {
+        let (start, end) = match part.split_once('-') {
+            Some((a, b)) => (a.trim(), b.trim()),
+            None => (part, part),
+        };
+        let start: usize = start
+            .parse()
+            .map_err(|_| format!("Invalid selection: '{}'", part))?;
+        let end: usize = end
+            .parse()
+            .map_err(|_| format!("Invalid selection: '{}'", part))?;
+        if start == 0 || end > count || start > end {
+            return Err(format!("Selection out of range: '{}'", part));
+        }
+        for i in start - 1..end {
+            if !indices.contains(&i) {
+                indices.push(i);
+            }
+        }
+    }
+    Ok(indices)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_selection() {
+        assert_eq!(parse_selection("1,3-4", 5).unwrap(), vec![0, 2, 3]);
+        assert_eq!(parse_selection("all", 3).unwrap(), vec![0, 1, 2]);
+        assert_eq!(parse_selection("2, 2", 3).unwrap(), vec![1]);
+        assert!(parse_selection("", 3).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_parse_selection_out_of_range() {
+        assert!(parse_selection("0", 3).is_err());
+        assert!(parse_selection("2-5", 3).is_err());
+        assert!(parse_selection("x", 3).is_err());
+    }
+}
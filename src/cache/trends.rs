@@ -0,0 +1,257 @@
+use super::detect::{
+    detect_cargo_registry, detect_cloudflare_caches, detect_hf_caches, detect_js_pm_caches,
+    detect_maven_repository, detect_npx_caches, detect_torch_caches, detect_vercel_caches,
+};
+use super::output::human_size;
+use super::types::CacheEntry;
+use crate::system_monitor::sparkline;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::PathBuf;
+
+/// Oldest snapshots are dropped once the history grows past this many entries
+const MAX_SNAPSHOTS: usize = 500;
+
+/// `--list` records at most one snapshot per this many minutes, so listing again soon
+/// after doesn't walk every user-wide cache a second time
+const SNAPSHOT_INTERVAL_MINUTES: i64 = 60;
+
+/// Providers every snapshot records, 0 when they have nothing. Only their user-wide caches
+/// count: project caches (`target/`, `node_modules`, `__pycache__`, ...) depend on where
+/// `--list` runs, which would make one snapshot incomparable with the next.
+const TRACKED_KINDS: [&str; 8] = [
+    "rust",
+    "java",
+    "npx",
+    "js-pm",
+    "hf",
+    "torch",
+    "vercel",
+    "cloudflare",
+];
+
+/// Total cache size per provider kind at one point in time
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CacheSnapshot {
+    pub timestamp: DateTime<Utc>,
+    pub sizes: BTreeMap<String, u64>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProviderTrend {
+    pub kind: String,
+    pub first_seen: DateTime<Utc>,
+    pub latest_bytes: u64,
+    pub change_bytes: i64,
+    pub growth_per_day_bytes: i64,
+    pub samples: Vec<(DateTime<Utc>, u64)>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TrendsResponse {
+    pub snapshots: usize,
+    pub providers: Vec<ProviderTrend>,
+}
+
+pub fn get_history_file() -> PathBuf {
+    let home = std::env::var("HOME")
+        .or_else(|_| std::env::var("USERPROFILE"))
+        .unwrap_or_else(|_| "/tmp".to_string());
+    PathBuf::from(home)
+        .join(".port-kill")
+        .join("cache-history.json")
+}
+
+pub fn load_snapshots() -> Vec<CacheSnapshot> {
+    fs::read_to_string(get_history_file())
+        .ok()
+        .and_then(|c| serde_json::from_str(&c).ok())
+        .unwrap_or_default()
+}
+
+/// Total size per tracked provider; other kinds are left out
+fn tracked_sizes(entries: impl IntoIterator<Item = CacheEntry>) -> BTreeMap<String, u64> {
+    let mut sizes: BTreeMap<String, u64> = TRACKED_KINDS
+        .iter()
+        .map(|kind| (kind.to_string(), 0))
+        .collect();
+    for entry in entries {
+        if let Some(size) = sizes.get_mut(&entry.kind) {
+            *size += entry.size_bytes;
+        }
+    }
+    sizes
+}
+
+/// Whether the latest snapshot is old enough for another one to be recorded at `now`
+fn snapshot_due(snapshots: &[CacheSnapshot], now: DateTime<Utc>) -> bool {
+    snapshots.last().is_none_or(|last| {
+        now - last.timestamp >= chrono::Duration::minutes(SNAPSHOT_INTERVAL_MINUTES)
+    })
+}
+
+/// Append the size of every tracked provider's user-wide caches to the usage history,
+/// unless the latest snapshot is less than `SNAPSHOT_INTERVAL_MINUTES` old. They are
+/// measured here rather than taken from a listing, so the `--list` flags and the current
+/// directory don't change what gets recorded.
+pub fn record_snapshot() -> Result<(), std::io::Error> {
+    let mut snapshots = load_snapshots();
+    let now = Utc::now();
+    if !snapshot_due(&snapshots, now) {
+        return Ok(());
+    }
+
+    let entries = detect_cargo_registry(None)
+        .into_iter()
+        .chain(detect_maven_repository(None))
+        .chain(detect_npx_caches(None))
+        .chain(detect_js_pm_caches(None))
+        .chain(detect_hf_caches(None))
+        .chain(detect_torch_caches(None))
+        .chain(detect_vercel_caches(None))
+        .chain(detect_cloudflare_caches(None));
+    let sizes = tracked_sizes(entries);
+
+    snapshots.push(CacheSnapshot {
+        timestamp: now,
+        sizes,
+    });
+    if snapshots.len() > MAX_SNAPSHOTS {
+        snapshots.drain(..snapshots.len() - MAX_SNAPSHOTS);
+    }
+
+    let path = get_history_file();
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(path, serde_json::to_string(&snapshots)?)
+}
+
+/// Growth per provider across all recorded snapshots, fastest growing first
+pub fn compute_trends(snapshots: &[CacheSnapshot]) -> TrendsResponse {
+    let mut samples: BTreeMap<String, Vec<(DateTime<Utc>, u64)>> = BTreeMap::new();
+    for snapshot in snapshots {
+        for (kind, size) in &snapshot.sizes {
+            samples
+                .entry(kind.clone())
+                .or_default()
+                .push((snapshot.timestamp, *size));
+        }
+    }
+
+    let mut providers: Vec<ProviderTrend> = samples
+        .into_iter()
+        .map(|(kind, samples)| {
+            let (first_seen, first_bytes) = samples[0];
+            let (last_seen, latest_bytes) = samples[samples.len() - 1];
+            let change_bytes = latest_bytes as i64 - first_bytes as i64;
+            // Spread the change over at least one day so a single session doesn't look explosive
+            let days = ((last_seen - first_seen).num_seconds() as f64 / 86_400.0).max(1.0);
+            ProviderTrend {
+                kind,
+                first_seen,
+                latest_bytes,
+                change_bytes,
+                growth_per_day_bytes: (change_bytes as f64 / days) as i64,
+                samples,
+            }
+        })
+        .collect();
+    providers.sort_by_key(|p| std::cmp::Reverse(p.growth_per_day_bytes));
+
+    TrendsResponse {
+        snapshots: snapshots.len(),
+        providers,
+    }
+}
+
+pub fn print_trends(resp: &TrendsResponse) {
+    if resp.providers.is_empty() {
+        println!("No cache history yet. Run `port-kill cache --list` to record a snapshot.");
+        return;
+    }
+
+    println!(
+        "{:<12} | {:<12} | {:<13} | {:<13} | TREND",
+        "KIND", "SIZE", "CHANGE", "PER DAY"
+    );
+    println!("{}", "-".repeat(80));
+    for p in &resp.providers {
+        // Keep the sparkline to the most recent 30 samples so it fits on one line
+        let recent: Vec<u64> = p.samples.iter().rev().take(30).rev().map(|s| s.1).collect();
+        // Scale between the smallest and largest sample so growth shows as a ramp
+        let low = recent.iter().copied().min().unwrap_or(0);
+        let high = recent.iter().copied().max().unwrap_or(0);
+        println!(
+            "{:<12} | {:<12} | {:<13} | {:<13} | {}",
+            p.kind,
+            human_size(p.latest_bytes),
+            signed_size(p.change_bytes),
+            signed_size(p.growth_per_day_bytes),
+            sparkline(recent.iter().map(|&v| (v - low) as f64), (high - low) as f64)
+        );
+    }
+    println!();
+    println!("Based on {} snapshot(s)", resp.snapshots);
+}
+
+fn signed_size(bytes: i64) -> String {
+    let sign = if bytes < 0 { "-" } else { "+" };
+    format!("{}{}", sign, human_size(bytes.unsigned_abs()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+
+    fn snapshot(days_ago: i64, sizes: &[(&str, u64)]) -> CacheSnapshot {
+        CacheSnapshot {
+            timestamp: Utc::now() - Duration::days(days_ago),
+            sizes: sizes.iter().map(|(k, v)| (k.to_string(), *v)).collect(),
+        }
+    }
+
+    #[test]
+    fn test_compute_trends_orders_by_growth() {
+        let snapshots = vec![
+            snapshot(10, &[("js", 1000), ("rust", 1000)]),
+            snapshot(0, &[("js", 1100), ("rust", 6000)]),
+        ];
+        let trends = compute_trends(&snapshots);
+        assert_eq!(trends.providers[0].kind, "rust");
+        assert_eq!(trends.providers[0].change_bytes, 5000);
+        assert_eq!(trends.providers[0].growth_per_day_bytes, 500);
+        assert_eq!(trends.providers[1].change_bytes, 100);
+    }
+
+    #[test]
+    fn test_snapshot_due_once_per_interval() {
+        let now = Utc::now();
+        assert!(snapshot_due(&[], now));
+        assert!(!snapshot_due(&[snapshot(0, &[])], now + Duration::minutes(59)));
+        assert!(snapshot_due(&[snapshot(0, &[])], now + Duration::minutes(61)));
+    }
+
+    #[test]
+    fn test_tracked_sizes_record_every_provider() {
+        let entry = |kind: &str, size_bytes| CacheEntry {
+            id: format!("{}:test", kind),
+            kind: kind.to_string(),
+            name: "test".to_string(),
+            path: "/tmp/test".to_string(),
+            size_bytes,
+            last_used_at: None,
+            stale: false,
+            details: serde_json::json!({}),
+        };
+        let sizes = tracked_sizes(vec![entry("rust", 10), entry("rust", 5), entry("js", 100)]);
+        assert_eq!(sizes.len(), TRACKED_KINDS.len());
+        assert_eq!(sizes["rust"], 15);
+        assert_eq!(sizes["hf"], 0);
+        assert!(!sizes.contains_key("js"));
+    }
+}
@@ -14,6 +14,16 @@ pub struct CacheEntry {
     pub details: serde_json::Value,
 }
 
+impl CacheEntry {
+    /// Pinned entries (currently Hugging Face repos) are never cleaned
+    pub fn is_pinned(&self) -> bool {
+        self.details
+            .get("pinned")
+            .and_then(|p| p.as_bool())
+            .unwrap_or(false)
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct ListSummary {
@@ -40,7 +50,50 @@ pub struct CleanSummary {
 pub struct CleanResponse {
     pub deleted: Vec<CacheEntry>,
     pub backed_up_to: Option<String>,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub moved_to_trash: bool,
     pub summary: CleanSummary,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pruned_backups: Option<PruneResponse>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PruneResponse {
+    pub pruned: Vec<String>,
+    pub freed_bytes: u64,
+    pub kept_count: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CleanPlanEntry {
+    pub provider: String,
+    pub id: String,
+    pub path: String,
+    pub bytes: u64,
+    pub last_used_at: Option<DateTime<Utc>>,
+    pub age_days: Option<i64>,
+    /// One of: keep, backup, trash, delete
+    pub action: String,
+    /// One of: rebuildable, redownloadable, environment, sensitive
+    pub safety: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CleanPlanSummary {
+    pub affected_count: usize,
+    pub affected_bytes: u64,
+    pub kept_count: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CleanPlanResponse {
+    pub entries: Vec<CleanPlanEntry>,
+    pub summary: CleanPlanSummary,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -51,3 +104,38 @@ pub struct RestoreResponse {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub error: Option<String>,
 }
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BackupSummary {
+    pub name: String,
+    pub path: String,
+    pub timestamp: DateTime<Utc>,
+    pub size_bytes: u64,
+    pub compressed: bool,
+    pub entries: Vec<CacheEntry>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BackupListResponse {
+    pub backups: Vec<BackupSummary>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BudgetProvider {
+    pub provider: String,
+    pub size_bytes: u64,
+    pub count: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BudgetReport {
+    pub budget_bytes: u64,
+    pub total_size_bytes: u64,
+    pub over_budget: bool,
+    pub over_by_bytes: u64,
+    pub providers: Vec<BudgetProvider>,
+    pub largest: Vec<CacheEntry>,
+}
@@ -0,0 +1,269 @@
+use super::backup::RetentionPolicy;
+use super::clean::clean_caches;
+use super::list::list_caches;
+use super::output::human_size;
+use super::types::{CacheEntry, CleanResponse};
+use crate::notify::send_desktop_notification;
+use crate::system_monitor::{DiskUsage, SystemMonitor};
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::path::PathBuf;
+use std::time::Duration;
+
+/// Providers accepted by `--auto-clean`
+pub const PROVIDERS: [&str; 10] = [
+    "rust",
+    "js",
+    "py",
+    "java",
+    "npx",
+    "js-pm",
+    "hf",
+    "torch",
+    "vercel",
+    "cloudflare",
+];
+
+/// How many of the largest cache entries to include in a cleanup plan
+const PLAN_SIZE: usize = 10;
+
+/// Free-space level below which the watcher raises an alert
+#[derive(Debug, Clone, Copy)]
+pub enum DiskThreshold {
+    FreeBytes(u64),
+    FreePercent(f64),
+}
+
+impl DiskThreshold {
+    pub fn is_low(&self, usage: &DiskUsage) -> bool {
+        match self {
+            DiskThreshold::FreeBytes(min) => usage.available_bytes < *min,
+            DiskThreshold::FreePercent(min) => usage.free_percentage() < *min,
+        }
+    }
+}
+
+/// Remembers whether the disk was low on the last check, so an alert fires when free
+/// space crosses the threshold and not again on every tick while it stays low
+#[derive(Debug, Default)]
+struct Crossing {
+    was_low: bool,
+}
+
+impl Crossing {
+    /// Record this check; true when it is the first one below the threshold
+    fn crossed(&mut self, low: bool) -> bool {
+        let crossed = low && !self.was_low;
+        self.was_low = low;
+        crossed
+    }
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DiskAlert {
+    pub timestamp: DateTime<Utc>,
+    pub disk: DiskUsage,
+    pub plan: Vec<CacheEntry>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub auto_cleaned: Vec<CleanResponse>,
+}
+
+/// Watch free space on the disk holding the home directory. Each time it drops below the
+/// threshold, print a ranked cleanup plan, send a desktop notification and clean the
+/// pre-approved `auto_clean` providers. Runs until interrupted.
+pub async fn watch_disk(
+    threshold: DiskThreshold,
+    interval: Duration,
+    auto_clean: &[String],
+    json: bool,
+) {
+    let home = std::env::var("HOME")
+        .or_else(|_| std::env::var("USERPROFILE"))
+        .map(PathBuf::from)
+        .unwrap_or_else(|_| PathBuf::from("/"));
+    let monitor = SystemMonitor::new();
+    let mut crossing = Crossing::default();
+
+    if !json {
+        println!(
+            "💾 Watching free disk space for {} (Ctrl+C to stop)",
+            home.display()
+        );
+    }
+
+    loop {
+        match monitor.get_disk_usage(&home) {
+            Some(usage) => {
+                if crossing.crossed(threshold.is_low(&usage)) {
+                    let alert = build_alert(usage, auto_clean).await;
+                    report_alert(&alert, json);
+                }
+            }
+            None => eprintln!(
+                "Warning: Could not determine disk usage for {}",
+                home.display()
+            ),
+        }
+        tokio::time::sleep(interval).await;
+    }
+}
+
+async fn build_alert(disk: DiskUsage, auto_clean: &[String]) -> DiskAlert {
+    let mut auto_cleaned = Vec::new();
+    for provider in auto_clean {
+        if let Some(resp) = clean_provider(provider).await {
+            auto_cleaned.push(resp);
+        }
+    }
+
+    // Language caches are only detected when no specialized provider is requested,
+    // so gather both groups separately
+    let mut plan = list_caches("auto", false, false, false, false, false, false, None)
+        .await
+        .entries;
+    plan.extend(
+        list_caches("auto", true, true, true, true, true, true, None)
+            .await
+            .entries,
+    );
+    plan.sort_by_key(|e| std::cmp::Reverse(e.size_bytes));
+    plan.truncate(PLAN_SIZE);
+
+    DiskAlert {
+        timestamp: Utc::now(),
+        disk,
+        plan,
+        auto_cleaned,
+    }
+}
+
+/// The `--lang` an `--auto-clean` provider cleans with, None for unknown providers
+fn provider_lang(provider: &str) -> Option<&str> {
+    match provider {
+        "rust" | "js" | "py" | "java" => Some(provider),
+        _ if PROVIDERS.contains(&provider) => Some("auto"),
+        _ => None,
+    }
+}
+
+async fn clean_provider(provider: &str) -> Option<CleanResponse> {
+    let Some(lang) = provider_lang(provider) else {
+        eprintln!("Warning: Unknown cache provider '{}'", provider);
+        return None;
+    };
+    Some(
+        clean_caches(
+            lang,
+            provider == "npx",
+            provider == "js-pm",
+            // No safe-delete backup: copying the caches first needs the space that is missing
+            false,
+            false,
+            false,
+            false,
+            provider == "hf",
+            provider == "torch",
+            provider == "vercel",
+            provider == "cloudflare",
+            None,
+            &RetentionPolicy::default(),
+        )
+        .await,
+    )
+}
+
+fn report_alert(alert: &DiskAlert, json: bool) {
+    let freed: u64 = alert
+        .auto_cleaned
+        .iter()
+        .map(|r| r.summary.freed_bytes)
+        .sum();
+    let mut body = format!(
+        "Only {} free on {} ({:.1}%)",
+        human_size(alert.disk.available_bytes),
+        alert.disk.mount_point,
+        alert.disk.free_percentage()
+    );
+    if freed > 0 {
+        body.push_str(&format!(", auto-cleaned {}", human_size(freed)));
+    }
+    send_desktop_notification("Low disk space", &body);
+
+    if json {
+        // One JSON document per line so wrappers can stream alerts
+        match serde_json::to_string(alert) {
+            Ok(s) => println!("{}", s),
+            Err(e) => eprintln!("Failed to serialize JSON: {}", e),
+        }
+        return;
+    }
+
+    println!();
+    println!("⚠️  Low disk space: {}", body);
+    if alert.plan.is_empty() {
+        println!("No caches found to clean.");
+        return;
+    }
+    println!("Suggested cleanup (largest first):");
+    for (i, entry) in alert.plan.iter().enumerate() {
+        println!(
+            "  {:>2}. {:<10} {:>12}  {}",
+            i + 1,
+            entry.kind,
+            human_size(entry.size_bytes),
+            entry.path
+        );
+    }
+    println!("Run `port-kill cache --clean` with the matching provider flags to reclaim space.");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn usage(available_bytes: u64) -> DiskUsage {
+        DiskUsage {
+            mount_point: "/".to_string(),
+            total_bytes: 100 * 1024,
+            available_bytes,
+        }
+    }
+
+    #[test]
+    fn test_disk_threshold() {
+        let bytes = DiskThreshold::FreeBytes(10 * 1024);
+        assert!(bytes.is_low(&usage(10 * 1024 - 1)));
+        assert!(!bytes.is_low(&usage(10 * 1024)));
+
+        let percent = DiskThreshold::FreePercent(10.0);
+        assert!(percent.is_low(&usage(5 * 1024)));
+        assert!(!percent.is_low(&usage(10 * 1024)));
+        // An unknown disk size reads as 0% free
+        let unknown = DiskUsage {
+            total_bytes: 0,
+            ..usage(0)
+        };
+        assert!(percent.is_low(&unknown));
+    }
+
+    #[test]
+    fn test_alerts_once_per_crossing() {
+        let mut crossing = Crossing::default();
+        let alerts: Vec<bool> = [false, true, true, true, false, true, false]
+            .into_iter()
+            .map(|low| crossing.crossed(low))
+            .collect();
+        assert_eq!(alerts, [false, true, false, false, false, true, false]);
+    }
+
+    #[test]
+    fn test_provider_lang() {
+        assert_eq!(provider_lang("rust"), Some("rust"));
+        assert_eq!(provider_lang("java"), Some("java"));
+        assert_eq!(provider_lang("hf"), Some("auto"));
+        assert_eq!(provider_lang("js-pm"), Some("auto"));
+        assert_eq!(provider_lang("go"), None);
+        assert!(PROVIDERS.iter().all(|p| provider_lang(p).is_some()));
+    }
+}
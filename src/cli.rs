@@ -1,7 +1,27 @@
-use crate::preset_manager::{PortPreset, PresetManager};
-use clap::Parser;
+use crate::cache::backup::RetentionPolicy;
+use crate::cache::watch::DiskThreshold;
+use crate::preset_manager::{
+    fetch_preset_source, parse_shared_presets, PortPreset, PresetManager,
+};
+use crate::project_dir::resolve_upward;
+use crate::quick_actions::{HotkeyAction, QuickAction};
+use crate::restart_manager::RestartOverrides;
+use crate::service_detector::{detect_project_preset, devcontainer_ports, ServiceDetector};
+use crate::smart_filter::SmartFilterRules;
+use crate::tray_status::{PinnedPort, TrayIconRules, TrayMenuLayout};
+use crate::types::BindFilter;
+use crate::user_config::UserConfig;
+use clap::builder::BoolishValueParser;
+use clap::{CommandFactory, FromArgMatches, Parser};
 use clap::{Args as ClapArgs, Subcommand};
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+
+/// Ports watched by guard mode unless --guard-ports is given
+pub const DEFAULT_GUARD_PORTS: &str = "3000,3001,3002,8000,8080,9000";
+
+/// Ports the security audit flags unless --suspicious-ports is given
+pub const DEFAULT_SUSPICIOUS_PORTS: &str = "8444,4444,9999,14444,5555,6666,7777";
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
 pub enum LogLevel {
@@ -15,7 +35,82 @@ pub enum LogLevel {
     None,
 }
 
+/// How log lines are written, to stderr and to --log-file
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, clap::ValueEnum)]
+pub enum LogFormat {
+    /// Human-readable lines
+    #[default]
+    Text,
+    /// One JSON object per line, with the event's fields
+    Json,
+}
+
+/// When --log-file starts a new file
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, clap::ValueEnum)]
+pub enum LogRotation {
+    Hourly,
+    #[default]
+    Daily,
+    /// Keep writing to the same file
+    Never,
+}
+
+/// Which releases update checks and `--self-update` follow
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, clap::ValueEnum)]
+pub enum UpdateChannel {
+    /// Full releases only
+    #[default]
+    Stable,
+    /// Pre-releases as well as full releases
+    Beta,
+}
+
+impl UpdateChannel {
+    pub fn name(&self) -> &'static str {
+        match self {
+            UpdateChannel::Stable => "stable",
+            UpdateChannel::Beta => "beta",
+        }
+    }
+}
+
+/// Time span a `--report` covers, ending now
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum ReportPeriod {
+    Daily,
+    Weekly,
+    Monthly,
+}
+
+impl ReportPeriod {
+    pub fn duration(self) -> chrono::Duration {
+        match self {
+            ReportPeriod::Daily => chrono::Duration::days(1),
+            ReportPeriod::Weekly => chrono::Duration::weeks(1),
+            ReportPeriod::Monthly => chrono::Duration::days(30),
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            ReportPeriod::Daily => "daily",
+            ReportPeriod::Weekly => "weekly",
+            ReportPeriod::Monthly => "monthly",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum ReportFormat {
+    /// Plain text for the terminal
+    Terminal,
+    Markdown,
+    /// A standalone HTML page
+    Html,
+}
+
 #[derive(Parser, Debug, Clone)]
+#[command(group(clap::ArgGroup::new("tmux_target").multiple(true).args(["restart", "start", "up", "restart_service"])))]
 #[command(
     name = "port-kill",
     about = "A lightweight macOS status bar app that monitors and manages development processes",
@@ -24,62 +119,91 @@ pub enum LogLevel {
 )]
 pub struct Args {
     /// Starting port for range scanning (inclusive)
-    #[arg(short, long, default_value = "2000")]
+    #[arg(short, long, default_value = "2000", env = "PORT_KILL_START_PORT")]
     pub start_port: u16,
 
     /// Ending port for range scanning (inclusive)
-    #[arg(short, long, default_value = "9000")]
+    #[arg(short, long, default_value = "9000", env = "PORT_KILL_END_PORT")]
     pub end_port: u16,
 
     /// Specific ports to monitor (comma-separated, supports ranges like 3000-3010, overrides start/end port range)
-    #[arg(short, long, value_delimiter = ',')]
+    #[arg(short, long, value_delimiter = ',', env = "PORT_KILL_PORTS")]
     pub ports: Option<Vec<String>>,
 
     /// Ports to ignore (comma-separated, e.g., 5353,5000,7000 for Chromecast/AirDrop)
-    #[arg(long, value_delimiter = ',')]
+    #[arg(long, value_delimiter = ',', env = "PORT_KILL_IGNORE_PORTS")]
     pub ignore_ports: Option<Vec<u16>>,
 
     /// Process names to ignore (comma-separated, e.g., Chrome,ControlCe)
-    #[arg(long, value_delimiter = ',')]
+    #[arg(long, value_delimiter = ',', env = "PORT_KILL_IGNORE_PROCESSES")]
     pub ignore_processes: Option<Vec<String>>,
 
     /// Process name patterns to ignore (supports wildcards: *, ?)
-    #[arg(long, value_delimiter = ',')]
+    #[arg(long, value_delimiter = ',', env = "PORT_KILL_IGNORE_PATTERNS")]
     pub ignore_patterns: Option<Vec<String>>,
 
     /// Process groups to ignore (e.g., Database,Web Server)
-    #[arg(long, value_delimiter = ',')]
+    #[arg(long, value_delimiter = ',', env = "PORT_KILL_IGNORE_GROUPS")]
     pub ignore_groups: Option<Vec<String>>,
 
     /// Enable smart filtering (auto-detect and ignore system processes)
-    #[arg(long)]
+    #[arg(long, env = "PORT_KILL_SMART_FILTER", value_parser = BoolishValueParser::new())]
     pub smart_filter: bool,
 
     /// Only show processes from specific groups (e.g., Node.js,Python)
-    #[arg(long, value_delimiter = ',')]
+    #[arg(long, value_delimiter = ',', env = "PORT_KILL_ONLY_GROUPS")]
     pub only_groups: Option<Vec<String>>,
 
     /// Run in console mode instead of status bar mode
     #[arg(short, long)]
     pub console: bool,
 
+    /// Full-screen table of the monitored ports (console mode): sort, filter, kill, restart and guard with single keys
+    #[arg(long)]
+    pub tui: bool,
+
     /// Enable verbose logging
-    #[arg(short, long)]
+    #[arg(short, long, env = "PORT_KILL_VERBOSE", value_parser = BoolishValueParser::new())]
     pub verbose: bool,
 
     /// Enable Docker container monitoring (includes containers in process detection)
-    #[arg(short, long)]
+    #[arg(short, long, env = "PORT_KILL_DOCKER", value_parser = BoolishValueParser::new())]
     pub docker: bool,
 
+    /// Also detect processes bound to UDP ports (mDNS responders, game servers, QUIC), not just TCP listeners
+    #[arg(long, env = "PORT_KILL_UDP", value_parser = BoolishValueParser::new())]
+    pub udp: bool,
+
+    /// Only show processes bound to these addresses: localhost, exposed (all interfaces or a LAN address) or an IP like 0.0.0.0 or ::1
+    #[arg(long, value_delimiter = ',', value_name = "ADDRS", env = "PORT_KILL_BIND_ADDR")]
+    pub bind_addr: Vec<BindFilter>,
+
     /// Show process IDs (PIDs) in the display output
-    #[arg(short = 'P', long)]
+    #[arg(
+        short = 'P',
+        long,
+        env = "PORT_KILL_SHOW_PID",
+        value_parser = BoolishValueParser::new()
+    )]
     pub show_pid: bool,
 
     /// Log level (info, warn, error, none)
-    #[arg(long, default_value = "info", value_enum)]
+    #[arg(long, default_value = "info", value_enum, env = "PORT_KILL_LOG_LEVEL")]
     pub log_level: LogLevel,
 
-    /// Show process kill history
+    /// Also write logs to this file (default when given without a path: ~/.port-kill/port-kill.log)
+    #[arg(long, value_name = "PATH", num_args = 0..=1, default_missing_value = "~/.port-kill/port-kill.log", env = "PORT_KILL_LOG_FILE")]
+    pub log_file: Option<String>,
+
+    /// Log line format (text, json)
+    #[arg(long, value_enum, default_value = "text", env = "PORT_KILL_LOG_FORMAT")]
+    pub log_format: LogFormat,
+
+    /// How often --log-file rotates (hourly, daily, never); the last 7 files are kept
+    #[arg(long, value_enum, default_value = "daily", requires = "log_file")]
+    pub log_rotation: LogRotation,
+
+    /// Show the last 20 process kills (query the full history with `port-kill history`)
     #[arg(long)]
     pub show_history: bool,
 
@@ -92,13 +216,17 @@ pub struct Args {
     pub show_filters: bool,
 
     /// Enable performance metrics (CPU and memory usage)
-    #[arg(long)]
+    #[arg(long, env = "PORT_KILL_PERFORMANCE", value_parser = BoolishValueParser::new())]
     pub performance: bool,
 
     /// Show project context for each process
-    #[arg(long)]
+    #[arg(long, env = "PORT_KILL_SHOW_CONTEXT", value_parser = BoolishValueParser::new())]
     pub show_context: bool,
 
+    /// Name generic runtimes after their project, e.g. "node (webshop-frontend)" from package.json or the directory
+    #[arg(long, env = "PORT_KILL_FRIENDLY_NAMES", value_parser = BoolishValueParser::new())]
+    pub friendly_names: bool,
+
     /// Kill all processes on the specified ports
     #[arg(long)]
     pub kill_all: bool,
@@ -115,6 +243,68 @@ pub struct Args {
     #[arg(long)]
     pub restart: Option<u16>,
     
+    /// Choose among the commands previously saved for the port before restarting
+    #[arg(long, requires = "restart")]
+    pub pick: bool,
+
+    /// Keep the process started by --restart running: relaunch it with exponential backoff whenever it exits
+    #[arg(long, requires = "restart")]
+    pub keep_alive: bool,
+
+    /// Kill and relaunch the process started by --restart whenever files under these paths change
+    #[arg(long, value_delimiter = ',', value_name = "PATHS", requires = "restart", conflicts_with = "keep_alive")]
+    pub watch: Vec<std::path::PathBuf>,
+
+    /// Shell command to run before --restart launches the saved command (overrides the saved hook)
+    #[arg(long, value_name = "COMMAND", requires = "restart")]
+    pub pre_restart: Option<String>,
+
+    /// Shell command to run once the restarted process listens on the port (overrides the saved hook)
+    #[arg(long, value_name = "COMMAND", requires = "restart")]
+    pub post_restart: Option<String>,
+
+    /// Run in tmux: --restart opens a new window of the session the process originally ran
+    /// in; --start, --up and --restart-service open a window per service in the --tmux-session session
+    #[arg(long, requires = "tmux_target", conflicts_with_all = ["keep_alive", "watch", "no_tmux"])]
+    pub tmux: bool,
+
+    /// tmux session for --tmux with --start, --up or --restart-service, created when missing (default: the current directory's name)
+    #[arg(long, value_name = "NAME", requires = "tmux")]
+    pub tmux_session: Option<String>,
+
+    /// Restart detached even when the process originally ran inside tmux (skips the prompt)
+    #[arg(long, requires = "restart")]
+    pub no_tmux: bool,
+
+    /// Override an environment variable for --restart (KEY=VALUE, repeatable)
+    #[arg(long, value_name = "KEY=VALUE", value_parser = parse_env_assignment)]
+    pub restart_env: Vec<(String, String)>,
+
+    /// Load environment variables for --restart from a dotenv-style file (--restart-env still wins)
+    #[arg(long, value_name = "FILE", requires = "restart")]
+    pub env_file: Option<std::path::PathBuf>,
+
+    /// Restart with a minimal environment (PATH, HOME, ...) instead of the captured one
+    #[arg(long, requires = "restart")]
+    pub clean_env: bool,
+
+    /// Restart the process as another user with sudo (not supported on Windows)
+    #[arg(long, value_name = "USER", requires = "restart")]
+    pub as_user: Option<String>,
+
+    /// Restart every port in the restart history that has a saved command
+    #[arg(long)]
+    pub restart_all: bool,
+
+    /// Seconds a restart waits for the old process to release its port before giving up
+    #[arg(
+        long,
+        value_name = "SECONDS",
+        default_value = "10",
+        env = "PORT_KILL_RESTART_TIMEOUT"
+    )]
+    pub restart_timeout: u64,
+
     /// Show restart history (list ports that can be restarted)
     #[arg(long)]
     pub show_restart_history: bool,
@@ -123,12 +313,24 @@ pub struct Args {
     #[arg(long)]
     pub clear_restart: Option<u16>,
 
+    /// Remove restart entries whose working directory or program no longer exists
+    #[arg(long)]
+    pub prune_restarts: bool,
+
+    /// Write saved restart commands to a shareable recipes file
+    #[arg(long, value_name = "FILE")]
+    pub export_restarts: Option<std::path::PathBuf>,
+
+    /// Add restart commands from a recipes file written by --export-restarts
+    #[arg(long, value_name = "FILE")]
+    pub import_restarts: Option<std::path::PathBuf>,
+
     /// Show process tree (parent-child relationships)
     #[arg(long)]
     pub show_tree: bool,
 
     /// Output processes in JSON format (for API integration)
-    #[arg(long)]
+    #[arg(long, env = "PORT_KILL_JSON", value_parser = BoolishValueParser::new())]
     pub json: bool,
 
     /// Reset common development ports (3000, 5000, 8000, 5432, 3306, 6379, 27017, 8080, 9000)
@@ -143,6 +345,23 @@ pub struct Args {
     #[arg(long)]
     pub show_patterns: bool,
 
+    /// Only count kills on this port in the history views (--show-history, --show-offenders, --show-patterns, --show-stats)
+    #[arg(long, value_name = "PORT")]
+    pub history_port: Option<u16>,
+
+    /// Only count kills on or after this day (YYYY-MM-DD, UTC) in the history views
+    #[arg(long, value_name = "DATE")]
+    pub since: Option<chrono::NaiveDate>,
+
+    /// Only count kills on or before this day (YYYY-MM-DD, UTC) in the history views
+    #[arg(long, value_name = "DATE")]
+    pub until: Option<chrono::NaiveDate>,
+
+    /// Open the TUI's timeline of when each monitored port was taken and freed over the
+    /// last HOURS (default 24); with --json, print it instead
+    #[arg(long, value_name = "HOURS", num_args = 0..=1, default_missing_value = crate::port_activity::DEFAULT_TIMELINE_HOURS, value_parser = clap::value_parser!(u32).range(1..))]
+    pub timeline: Option<u32>,
+
     /// Show auto-suggestions for ignore lists
     #[arg(long)]
     pub show_suggestions: bool,
@@ -155,12 +374,25 @@ pub struct Args {
     #[arg(long)]
     pub show_root_cause: bool,
 
+    /// Print a digest of the last day, week or month: most-killed processes, busiest
+    /// ports, guard violations and cache space reclaimed
+    #[arg(long, value_enum, value_name = "PERIOD")]
+    pub report: Option<ReportPeriod>,
+
+    /// How --report is written
+    #[arg(long, value_enum, default_value = "terminal", requires = "report")]
+    pub report_format: ReportFormat,
+
+    /// Also send the --report as a desktop notification and to the [notifications] endpoint
+    #[arg(long, requires = "report")]
+    pub report_send: bool,
+
     /// Enable Port Guard Mode - proactive port conflict prevention
     #[arg(long)]
     pub guard_mode: bool,
 
     /// Ports to watch in guard mode (comma-separated)
-    #[arg(long, default_value = "3000,3001,3002,8000,8080,9000")]
+    #[arg(long, default_value = DEFAULT_GUARD_PORTS)]
     pub guard_ports: String,
 
     /// Auto-resolve conflicts by killing conflicting processes
@@ -179,11 +411,11 @@ pub struct Args {
     #[arg(long)]
     pub reserve_port: Option<u16>,
 
-    /// Project name for port reservation
+    /// Project name for port reservation (defaults to the detected project expecting the port)
     #[arg(long)]
     pub project_name: Option<String>,
 
-    /// Process name for port reservation
+    /// Process name for port reservation (defaults to the detected service expecting the port)
     #[arg(long)]
     pub process_name: Option<String>,
 
@@ -196,7 +428,7 @@ pub struct Args {
     pub security_mode: bool,
 
     /// Suspicious ports to flag (comma-separated)
-    #[arg(long, default_value = "8444,4444,9999,14444,5555,6666,7777")]
+    #[arg(long, default_value = DEFAULT_SUSPICIOUS_PORTS)]
     pub suspicious_ports: String,
 
     /// Baseline file for approved ports comparison
@@ -208,23 +440,31 @@ pub struct Args {
     pub suspicious_only: bool,
 
     /// Remote mode: connect to remote host via SSH
-    #[arg(long)]
+    #[arg(long, env = "PORT_KILL_REMOTE")]
     pub remote: Option<String>,
 
+    /// port-kill-console on the --remote host: a name looked up on its PATH, or a path
+    #[arg(long, value_name = "PATH", default_value = "port-kill-console", env = "PORT_KILL_REMOTE_BIN")]
+    pub remote_bin: String,
+
     /// Endpoint monitoring: send data to external endpoint for monitoring/alerting
-    #[arg(long)]
+    #[arg(long, env = "PORT_KILL_ENDPOINT")]
     pub monitor_endpoint: Option<String>,
 
     /// Interval for sending data to endpoint (seconds, default: 30)
-    #[arg(long, default_value = "30")]
+    #[arg(long, default_value = "30", env = "PORT_KILL_SEND_INTERVAL")]
     pub send_interval: u64,
 
-    /// Interval for scanning processes (seconds, default: 2)
-    #[arg(long, default_value = "2")]
+    /// Interval for scanning processes; monitoring starts at this rate and returns to it when ports change (seconds, default: 2)
+    #[arg(long, default_value = "2", env = "PORT_KILL_SCAN_INTERVAL")]
     pub scan_interval: u64,
 
+    /// Longest interval monitoring backs off to while nothing changes; set it to --scan-interval to keep a fixed rate (seconds, default: 30)
+    #[arg(long, default_value = "30", env = "PORT_KILL_MAX_SCAN_INTERVAL")]
+    pub max_scan_interval: u64,
+
     /// Authentication for endpoint (e.g., "Bearer token123" or "Basic user:pass")
-    #[arg(long)]
+    #[arg(long, env = "PORT_KILL_ENDPOINT_AUTH", hide_env_values = true)]
     pub endpoint_auth: Option<String>,
 
     /// Custom fields to include in endpoint payload (comma-separated key=value pairs)
@@ -236,13 +476,21 @@ pub struct Args {
     pub endpoint_include_audit: bool,
 
     /// Retry failed endpoint requests (number of retries, default: 3)
-    #[arg(long, default_value = "3")]
+    #[arg(long, default_value = "3", env = "PORT_KILL_ENDPOINT_RETRIES")]
     pub endpoint_retries: u32,
 
     /// Timeout for endpoint requests (seconds, default: 10)
-    #[arg(long, default_value = "10")]
+    #[arg(long, default_value = "10", env = "PORT_KILL_ENDPOINT_TIMEOUT")]
     pub endpoint_timeout: u64,
 
+    /// Expose Prometheus metrics (processes per port, CPU/memory, guard violations, kill counts) at http://ADDR:PORT/metrics
+    #[arg(long, value_name = "PORT", env = "PORT_KILL_PROMETHEUS_PORT")]
+    pub prometheus_port: Option<u16>,
+
+    /// Address to serve --prometheus-port on; use 0.0.0.0 to let other machines scrape it
+    #[arg(long, value_name = "ADDR", default_value = "127.0.0.1", env = "PORT_KILL_PROMETHEUS_ADDR")]
+    pub prometheus_addr: std::net::IpAddr,
+
     /// Execute inline script
     #[arg(long)]
     pub script: Option<String>,
@@ -288,6 +536,15 @@ pub struct Args {
     #[arg(long)]
     pub list: bool,
 
+    /// Scan for --list, the TUI and the tray even when a port-kill daemon is running,
+    /// instead of asking it
+    #[arg(long)]
+    pub no_daemon: bool,
+
+    /// One-shot: print a JSON report of the monitored ports and exit with status 1 if any is in use (for CI)
+    #[arg(long)]
+    pub fail_if_found: bool,
+
     /// Ask for confirmation before killing
     #[arg(long)]
     pub safe: bool,
@@ -296,10 +553,23 @@ pub struct Args {
     #[arg(value_parser, value_name = "PORTS")]
     pub positional_ports: Vec<u16>,
 
+    /// Extra arguments appended to the saved command for --restart, or passed on to the
+    /// --start service (e.g., -- --port 3001)
+    #[arg(last = true, value_name = "ARGS")]
+    pub restart_args: Vec<String>,
+
     /// Use a preset configuration (e.g., 'dev', 'system', 'database', 'web', 'react', 'node', 'python', 'full', 'minimal')
-    #[arg(long, value_name = "PRESET")]
+    #[arg(long, value_name = "PRESET", env = "PORT_KILL_PRESET")]
     pub preset: Option<String>,
 
+    /// Don't pick a preset from the project type (package.json, Cargo.toml, ...)
+    #[arg(long, env = "PORT_KILL_NO_AUTO_PRESET", value_parser = BoolishValueParser::new())]
+    pub no_auto_preset: bool,
+
+    /// Print the effective configuration (after presets, config file and environment) and exit
+    #[arg(long)]
+    pub explain: bool,
+
     /// List available presets and exit
     #[arg(long)]
     pub list_presets: bool,
@@ -312,29 +582,99 @@ pub struct Args {
     #[arg(long, value_name = "TEXT")]
     pub preset_desc: Option<String>,
 
+    /// With --save-preset, scan current listeners and walk through the preset choices
+    #[arg(long, requires = "save_preset")]
+    pub interactive: bool,
+
     /// Delete a user-defined preset by name
     #[arg(long, value_name = "NAME")]
     pub delete_preset: Option<String>,
 
+    /// Import presets from a URL or file (JSON or YAML), previewing changes before saving
+    #[arg(long, value_name = "URL|FILE")]
+    pub import_preset: Option<String>,
+
+    /// Print a preset as JSON for sharing (e.g., --export-preset dev > dev.json)
+    #[arg(long, value_name = "NAME")]
+    pub export_preset: Option<String>,
+
+    /// Skip the confirmation prompt when importing presets
+    #[arg(long, short = 'y')]
+    pub yes: bool,
+
     /// Check for updates and show notification if available
     #[arg(long)]
     pub check_updates: bool,
 
+    /// Check the environment (lsof, permissions, Docker, config and data files, tray support) and suggest fixes
+    #[arg(long)]
+    pub doctor: bool,
+
+    /// Serve a live web dashboard of ports, processes, services, guard events and kill history
+    #[arg(long, value_name = "ADDR", num_args = 0..=1, default_missing_value = crate::dashboard::DEFAULT_DASHBOARD_ADDR)]
+    pub dashboard: Option<String>,
+
+    /// Serve a REST API (GET /ports, /processes, /guards, /status; POST /kill/{pid}, /clear/{port}) for dashboards and editor plugins
+    #[arg(long, value_name = "ADDR", num_args = 0..=1, default_missing_value = crate::rest_api::DEFAULT_SERVE_ADDR)]
+    pub serve: Option<String>,
+
+    /// Speak newline-delimited JSON-RPC 2.0 on stdin/stdout (list, kill, guard.*, subscribe) for editor plugins and wrappers
+    #[arg(long)]
+    pub machine: bool,
+
+    /// Start the tray app at login with the current arguments (a LaunchAgent on macOS)
+    #[arg(long, conflicts_with = "remove_login_item")]
+    pub install_login_item: bool,
+
+    /// Stop starting the tray app at login
+    #[arg(long)]
+    pub remove_login_item: bool,
+
+    /// Install git hooks that reserve a distinct port per branch/worktree and release it when the branch is deleted
+    #[arg(long)]
+    pub install_git_hooks: bool,
+
+    /// Reserve a port for the current git branch (run by the post-checkout hook)
+    #[arg(long)]
+    pub reserve_branch_port: bool,
+
+    /// Release the port reserved for a branch of the current repository
+    #[arg(long, value_name = "BRANCH")]
+    pub release_branch_port: Option<String>,
+
+    /// List the ports reserved per branch
+    #[arg(long)]
+    pub branch_ports: bool,
+
     /// Automatically update to the latest version
     #[arg(long)]
     pub self_update: bool,
 
-    /// Cache management subcommand
+    /// Release channel for update checks and --self-update (beta includes pre-releases)
+    #[arg(long, value_enum, default_value = "stable", env = "PORT_KILL_UPDATE_CHANNEL")]
+    pub update_channel: UpdateChannel,
+
+    /// Subcommand (cache, run, history, daemon, ...)
     #[command(subcommand)]
-    pub cache: Option<CacheSubcommand>,
+    pub command: Option<Command>,
     
     /// Detect available services (npm scripts, docker-compose, etc.)
     #[arg(long)]
     pub detect: bool,
     
-    /// Start a detected service by name (e.g., "npm:dev", "docker:web")
-    #[arg(long)]
+    /// Start a detected service by name (e.g., "npm:dev", "docker:web"); without a
+    /// name, or with one that matches several services, pick one interactively
+    #[arg(long, num_args = 0..=1, default_missing_value = "")]
     pub start: Option<String>,
+
+    /// Set an environment variable for the --start service (KEY=VALUE, repeatable)
+    #[arg(long, value_name = "KEY=VALUE", value_parser = parse_env_assignment, requires = "start")]
+    pub start_env: Vec<(String, String)>,
+
+    /// Load the project's direnv (.envrc) and mise environment into services started
+    /// by --start, --up and --restart-service
+    #[arg(long)]
+    pub load_env: bool,
     
     /// Auto-restart in guard mode (restart allowed process if it dies)
     #[arg(long)]
@@ -343,6 +683,14 @@ pub struct Args {
     /// Start all services from config file (.port-kill.yaml)
     #[arg(long)]
     pub up: bool,
+
+    /// With --up, keep running and apply changes to the config file as they are saved
+    #[arg(long, requires = "up")]
+    pub watch_config: bool,
+
+    /// With --up, keep running in the TUI's services tab to start, stop and restart services
+    #[arg(long, requires = "up")]
+    pub manage: bool,
     
     /// Stop all running services from config
     #[arg(long)]
@@ -363,39 +711,476 @@ pub struct Args {
     /// Create a sample .port-kill.yaml configuration file
     #[arg(long)]
     pub init_config: bool,
+
+    /// Ignore the defaults in ~/.port-kill/config.toml
+    #[arg(long, env = "PORT_KILL_NO_CONFIG", value_parser = BoolishValueParser::new())]
+    pub no_config: bool,
+
+    /// Don't apply config, preset or reservation file changes while running
+    #[arg(long)]
+    pub no_reload: bool,
+
+    /// Extra ignores and exceptions for --smart-filter, from the `[smart_filter]` config section
+    #[arg(skip)]
+    pub smart_filter_rules: SmartFilterRules,
+
+    /// Tray quick actions from the `[tray]` config section; None shows the defaults
+    #[arg(skip)]
+    pub tray_quick_actions: Option<Vec<QuickAction>>,
+
+    /// When the tray icon turns orange/red and what its badge shows, from `[tray]`
+    #[arg(skip)]
+    pub tray_icon_rules: TrayIconRules,
+
+    /// Sections, order and process limit of the tray menu, from `[tray]`
+    #[arg(skip)]
+    pub tray_menu_layout: TrayMenuLayout,
+
+    /// Global shortcuts for the tray app (`[tray.hotkeys]`), as (shortcut, action)
+    #[arg(skip)]
+    pub tray_hotkeys: Vec<(String, HotkeyAction)>,
+
+    /// Ports pinned in the tray menu and their notification rules (`[tray.pins]`)
+    #[arg(skip)]
+    pub tray_pins: Vec<PinnedPort>,
+
+    /// Settings given on the command line or in the environment (argument ids), which
+    /// an auto-selected preset leaves alone
+    #[arg(skip)]
+    pub explicit_settings: Vec<String>,
+
+    /// Why the config file could not be applied. Only kept for --doctor, which reports it
+    /// instead of exiting like every other mode does.
+    #[arg(skip)]
+    pub config_error: Option<String>,
 }
 
 #[derive(Subcommand, Debug, Clone)]
-pub enum CacheSubcommand {
+#[allow(clippy::large_enum_variant)]
+pub enum Command {
     /// Cache operations: list, clean, dry-run, restore, doctor, NPX, JS PM
     #[command(name = "cache", visible_alias = "c")]
     Op(CacheArgs),
+    /// Print a shell completion script (e.g., port-kill completions zsh > ~/.zfunc/_port-kill)
+    Completions(CompletionsArgs),
+    /// Clear the ports a command is about to use, then run it (e.g., port-kill run -- npm run dev)
+    Run(RunArgs),
+    /// Query the kill and restart history (e.g., port-kill history --port 3000 --since 7d)
+    History(HistoryArgs),
+    /// Run one long-lived port-kill that scans, guards and runs services, controlled with `port-kill ctl`
+    Daemon(DaemonArgs),
+    /// Talk to the running daemon (e.g., port-kill ctl list, port-kill ctl kill 3000)
+    Ctl(CtlArgs),
+    /// Measure scan latency per backend and port range (e.g., port-kill bench --sizes 100,7001)
+    Bench(BenchArgs),
+    /// Block until ports are open or free (e.g., port-kill wait --port 5432 --timeout 60s)
+    Wait(WaitArgs),
+    /// Forward a port to another one without killing its occupant (e.g., port-kill proxy 3000 --to 3001)
+    Proxy(ProxyArgs),
+}
+
+#[derive(ClapArgs, Debug, Clone)]
+#[command(args_conflicts_with_subcommands = true)]
+pub struct HistoryArgs {
+    #[command(subcommand)]
+    pub action: Option<HistoryAction>,
+
+    /// Only kills on this port
+    #[arg(long)]
+    pub port: Option<u16>,
+
+    /// Only kills at or after this time: a day (YYYY-MM-DD, UTC) or an age such as 7d, 12h or 2w
+    #[arg(long, value_name = "WHEN", value_parser = HistoryTime::parse)]
+    pub since: Option<HistoryTime>,
+
+    /// Only kills up to this time: a day (YYYY-MM-DD, UTC, inclusive) or an age such as 1d
+    #[arg(long, value_name = "WHEN", value_parser = HistoryTime::parse)]
+    pub until: Option<HistoryTime>,
+
+    /// Only kills of this process (case-insensitive)
+    #[arg(long, value_name = "NAME")]
+    pub process: Option<String>,
+
+    /// Only kills of processes in this project
+    #[arg(long, value_name = "NAME")]
+    pub project: Option<String>,
+
+    /// Show the restarts port-kill ran instead of kills (filtered by port and time only)
+    #[arg(long, conflicts_with_all = ["process", "project"])]
+    pub restarts: bool,
+
+    /// Show only the most recent N
+    #[arg(long, value_name = "N")]
+    pub limit: Option<usize>,
+
+    /// JSON output
+    #[arg(long)]
+    pub json: bool,
+}
+
+#[derive(Subcommand, Debug, Clone)]
+pub enum HistoryAction {
+    /// Export port occupancy and kill counts as time series (e.g., for Grafana)
+    Export(HistoryExportArgs),
+}
+
+#[derive(ClapArgs, Debug, Clone)]
+pub struct HistoryExportArgs {
+    /// Output format
+    #[arg(long, value_enum)]
+    pub format: ExportFormat,
+
+    /// Start of the export: a day (YYYY-MM-DD, UTC) or an age such as 30d
+    #[arg(long, value_name = "WHEN", value_parser = HistoryTime::parse, default_value = "30d")]
+    pub since: HistoryTime,
+
+    /// End of the export: a day (YYYY-MM-DD, UTC, inclusive) or an age (default: now)
+    #[arg(long, value_name = "WHEN", value_parser = HistoryTime::parse)]
+    pub until: Option<HistoryTime>,
+
+    /// Only this port
+    #[arg(long)]
+    pub port: Option<u16>,
+
+    /// Length of each data point, such as 5m, 1h or 1d
+    #[arg(long, value_name = "DURATION", value_parser = parse_export_interval, default_value = "1h")]
+    pub interval: chrono::Duration,
+
+    /// POST the export to this URL instead of printing it (required for prometheus-remote-write)
+    #[arg(long, required_if_eq("format", "prometheus-remote-write"))]
+    pub url: Option<String>,
+
+    /// Authorization header for --url (e.g., "Bearer token123")
+    #[arg(long, requires = "url")]
+    pub auth: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum ExportFormat {
+    /// InfluxDB line protocol
+    Influx,
+    /// Prometheus remote-write (snappy-compressed protobuf, POSTed to --url)
+    PrometheusRemoteWrite,
+    /// timestamp,metric,port,process,value rows
+    Csv,
+}
+
+fn parse_export_interval(input: &str) -> Result<chrono::Duration, String> {
+    let interval = crate::cache::output::parse_age(input)?;
+    if interval <= chrono::Duration::zero() {
+        return Err(format!("Interval must be positive: '{}'", input));
+    }
+    Ok(interval)
+}
+
+/// A `--since`/`--until` bound of `port-kill history`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HistoryTime {
+    /// A whole day, UTC
+    Day(chrono::NaiveDate),
+    /// This long before now
+    Ago(chrono::Duration),
+}
+
+impl HistoryTime {
+    pub fn parse(input: &str) -> Result<Self, String> {
+        if let Ok(day) = input.trim().parse() {
+            return Ok(HistoryTime::Day(day));
+        }
+        crate::cache::output::parse_age(input)
+            .map(HistoryTime::Ago)
+            .map_err(|_| format!("Invalid time '{}' (use YYYY-MM-DD or an age such as 7d or 12h)", input))
+    }
+
+    /// The first moment this bound covers
+    pub fn start(&self, now: chrono::DateTime<chrono::Utc>) -> chrono::DateTime<chrono::Utc> {
+        match self {
+            HistoryTime::Day(day) => day.and_hms_opt(0, 0, 0).unwrap_or_default().and_utc(),
+            HistoryTime::Ago(age) => now - *age,
+        }
+    }
+
+    /// The moment just after this bound: the next midnight for a day
+    pub fn end(&self, now: chrono::DateTime<chrono::Utc>) -> chrono::DateTime<chrono::Utc> {
+        match self {
+            HistoryTime::Day(day) => HistoryTime::Day(*day + chrono::Duration::days(1)).start(now),
+            HistoryTime::Ago(_) => self.start(now),
+        }
+    }
+}
+
+impl HistoryArgs {
+    pub fn filter(&self) -> Result<crate::types::HistoryFilter, String> {
+        let now = chrono::Utc::now();
+        let filter = crate::types::HistoryFilter {
+            port: self.port,
+            since: self.since.map(|since| since.start(now)),
+            until: self.until.map(|until| until.end(now)),
+            process: self.process.clone(),
+            project: self.project.clone(),
+        };
+        if let (Some(since), Some(until)) = (filter.since, filter.until) {
+            if since >= until {
+                return Err("--since is not before --until".to_string());
+            }
+        }
+        Ok(filter)
+    }
+}
+
+#[derive(ClapArgs, Debug, Clone)]
+pub struct DaemonArgs {
+    /// Socket to listen on (default: ~/.port-kill/daemon.sock, or a named pipe on Windows)
+    #[arg(long, env = "PORT_KILL_SOCKET")]
+    pub socket: Option<String>,
+
+    /// Start the services of the config file (like --up) when the daemon starts
+    #[arg(long)]
+    pub up: bool,
+}
+
+#[derive(ClapArgs, Debug, Clone)]
+pub struct ProxyArgs {
+    /// Port to accept connections on
+    pub port: u16,
+
+    /// Where to send them: a port on this machine, or host:port
+    #[arg(long, value_name = "TARGET")]
+    pub to: String,
+
+    /// Addresses to listen on; ones the occupant already holds are skipped
+    #[arg(long, value_delimiter = ',', value_name = "ADDRS", default_value = "127.0.0.1,::1")]
+    pub bind: Vec<std::net::IpAddr>,
+
+    /// Stop forwarding after this long, e.g. 30m or 2h (default: until Ctrl+C)
+    #[arg(long, value_parser = crate::port_wait::parse_duration)]
+    pub duration: Option<std::time::Duration>,
+}
+
+#[derive(ClapArgs, Debug, Clone)]
+pub struct WaitArgs {
+    /// Ports to wait for; all of them have to reach the state
+    #[arg(short, long, value_delimiter = ',', required = true)]
+    pub port: Vec<u16>,
+
+    /// Wait for something to listen (open) or for nothing to listen (free)
+    #[arg(long, value_enum, default_value = "open")]
+    pub state: WaitState,
+
+    /// Host to connect to
+    #[arg(long, default_value = "localhost")]
+    pub host: String,
+
+    /// Give up after this long, e.g. 500ms, 60s or 2m; 0 waits forever
+    #[arg(long, default_value = "30s", value_parser = crate::port_wait::parse_duration)]
+    pub timeout: std::time::Duration,
+
+    /// How often to check
+    #[arg(long, default_value = "250ms", value_parser = crate::port_wait::parse_duration)]
+    pub interval: std::time::Duration,
+
+    /// Print nothing; only the exit status tells
+    #[arg(short, long)]
+    pub quiet: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum WaitState {
+    /// Something accepts connections on the port
+    Open,
+    /// Nothing listens on the port
+    Free,
+}
+
+#[derive(ClapArgs, Debug, Clone)]
+pub struct BenchArgs {
+    /// Scanners to compare
+    #[arg(long, value_enum, value_delimiter = ',', default_value = "lsof,native")]
+    pub backends: Vec<BenchBackend>,
+
+    /// Port range sizes to scan, each range starting at --start-port
+    #[arg(long, value_delimiter = ',', value_name = "N", default_value = "10,100,1000,7001")]
+    pub sizes: Vec<u16>,
+
+    /// Pauses between scans in milliseconds; 0 scans back to back
+    #[arg(long, value_delimiter = ',', value_name = "MS", default_value = "0")]
+    pub intervals: Vec<u64>,
+
+    /// Scans per backend, size and interval
+    #[arg(short = 'n', long, default_value = "20")]
+    pub iterations: usize,
+
+    /// JSON output
+    #[arg(long)]
+    pub json: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum BenchBackend {
+    /// The scan port-kill uses today: lsof, or netstat on Windows
+    Lsof,
+    /// Reads the kernel's socket tables directly (Linux only)
+    Native,
+}
+
+impl BenchBackend {
+    pub fn name(&self) -> &'static str {
+        match self {
+            BenchBackend::Lsof => "lsof",
+            BenchBackend::Native => "native",
+        }
+    }
+}
+
+#[derive(ClapArgs, Debug, Clone)]
+pub struct CtlArgs {
+    #[command(subcommand)]
+    pub command: CtlCommand,
+
+    /// Socket of the daemon (default: ~/.port-kill/daemon.sock, or a named pipe on Windows)
+    #[arg(long, env = "PORT_KILL_SOCKET", global = true)]
+    pub socket: Option<String>,
+
+    /// Print the daemon's JSON replies
+    #[arg(long, global = true)]
+    pub json: bool,
+}
+
+#[derive(Subcommand, Debug, Clone)]
+pub enum CtlCommand {
+    /// Whether the daemon is running, with its uptime, guard and services
+    Status,
+    /// Processes on the daemon's ports (or the given ones)
+    List {
+        #[arg(long, value_delimiter = ',')]
+        ports: Vec<u16>,
+    },
+    /// Kill whatever listens on a port
+    Kill { port: u16 },
+    /// Reserve a port for a project in the guard
+    Reserve {
+        port: u16,
+        project: String,
+        /// Process allowed on the port (default: the project name)
+        #[arg(long)]
+        process: Option<String>,
+    },
+    /// Release a reserved port
+    Release { port: u16 },
+    /// Start the services of the config file, or just one of them, in the daemon
+    Up { service: Option<String> },
+    /// Stop the daemon's services, or just one of them
+    Down { service: Option<String> },
+    /// Restart one of the daemon's services, or the process on a port (killed and
+    /// relaunched with its saved command, like --restart)
+    Restart {
+        /// Service name or port
+        target: String,
+    },
+    /// Show the daemon's services
+    Services,
+    /// Print port, guard and conflict events until interrupted
+    Watch,
+    /// Stop the daemon and the services it started
+    Stop,
+    /// Send any JSON-RPC method (e.g., port-kill ctl call port.check '{"port":3000}')
+    Call {
+        method: String,
+        /// Params as JSON
+        params: Option<String>,
+    },
+}
+
+#[derive(ClapArgs, Debug, Clone)]
+pub struct RunArgs {
+    /// Port(s) the command will use, instead of predicting them from the command
+    #[arg(long, value_delimiter = ',')]
+    pub port: Vec<u16>,
+
+    /// Show the predicted ports and what is on them without clearing or running anything
+    #[arg(long)]
+    pub dry_run: bool,
+
+    /// The command to run
+    #[arg(
+        required = true,
+        trailing_var_arg = true,
+        allow_hyphen_values = true,
+        value_name = "COMMAND"
+    )]
+    pub command: Vec<String>,
+}
+
+#[derive(ClapArgs, Debug, Clone)]
+#[command(group(clap::ArgGroup::new("what").required(true).args(["shell", "values"])))]
+pub struct CompletionsArgs {
+    /// Shell to generate completions for
+    #[arg(value_enum)]
+    pub shell: Option<clap_complete::Shell>,
+
+    /// Print the current candidates for a dynamic value (used by the completion scripts)
+    #[arg(long, value_enum, hide = true)]
+    pub values: Option<CompletionValues>,
+}
+
+/// Values completed from the current machine rather than the static CLI definition
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum CompletionValues {
+    /// Built-in, user and project preset names
+    Presets,
+    /// Services configured in .port-kill.yaml
+    Services,
+    /// Services detected in the current directory (for --start)
+    Detected,
+    /// Ports with a listening process
+    Ports,
 }
 
 // For backward compatibility - keep CacheCommand as an alias
-pub type CacheCommand = CacheSubcommand;
+pub type CacheCommand = Command;
+pub type CacheSubcommand = Command;
 
-impl CacheSubcommand {
-    pub fn args(&self) -> &CacheArgs {
+impl Command {
+    pub fn args(&self) -> Option<&CacheArgs> {
         match self {
-            CacheSubcommand::Op(args) => args,
+            Command::Op(args) => Some(args),
+            Command::Completions(_)
+            | Command::Run(_)
+            | Command::History(_)
+            | Command::Daemon(_)
+            | Command::Ctl(_)
+            | Command::Bench(_)
+            | Command::Wait(_)
+            | Command::Proxy(_) => None,
         }
     }
 }
 
 #[derive(ClapArgs, Debug, Clone)]
 pub struct CacheArgs {
-    /// Operation mode: list, clean, dry-run, restore-last, doctor
+    /// Operation mode: list, check, clean, dry-run, restore, restore-last, list-backups, doctor, prune-backups, watch-disk, trends, pin/unpin
     #[arg(long)]
     pub list: bool,
+    #[arg(long, requires = "budget")]
+    pub check: bool,
     #[arg(long)]
     pub clean: bool,
     #[arg(long)]
     pub dry_run: bool,
     #[arg(long)]
+    pub restore: bool,
+    #[arg(long)]
     pub restore_last: bool,
     #[arg(long)]
+    pub list_backups: bool,
+    #[arg(long)]
     pub doctor: bool,
+    #[arg(long)]
+    pub prune_backups: bool,
+    #[arg(long)]
+    pub watch_disk: bool,
+    #[arg(long)]
+    pub trends: bool,
 
     /// JSON output
     #[arg(long)]
@@ -423,18 +1208,138 @@ pub struct CacheArgs {
     #[arg(long)]
     pub cloudflare: bool,
 
+    /// Pin Hugging Face repos (e.g., meta-llama/Llama-2-7b) so clean never removes them
+    #[arg(long, value_delimiter = ',', value_name = "REPOS")]
+    pub pin: Vec<String>,
+
+    /// Remove Hugging Face repos from the pin list
+    #[arg(long, value_delimiter = ',', value_name = "REPOS")]
+    pub unpin: Vec<String>,
+
     /// Safety and force flags for clean
     #[arg(long, default_value = "true")]
     pub safe_delete: bool,
     #[arg(long)]
     pub force: bool,
 
-    /// NPX stale days
+    /// Let --doctor repair the issues it finds (partial writes, orphan installs, stale locks)
+    #[arg(long)]
+    pub fix: bool,
+
+    /// Move cleaned entries (and files removed by --doctor --fix) to the OS trash instead of deleting them
+    #[arg(long)]
+    pub trash: bool,
+
+    /// Store safe-delete backups as compressed tar.zst archives instead of plain copies
+    #[arg(long)]
+    pub compress: bool,
+
+    /// Only treat entries not used within this many days as stale (shorthand for --older-than <N>d)
     #[arg(long)]
     pub stale_days: Option<u32>,
+
+    /// Only treat entries not modified or accessed within this window as stale, for every provider (e.g., 30d, 12h, 2w)
+    #[arg(long, value_name = "AGE", value_parser = crate::cache::output::parse_age)]
+    pub older_than: Option<chrono::Duration>,
+
+    /// Restore from the backup with this timestamp (directory name or prefix, e.g. 2025-01-31)
+    #[arg(long, value_name = "TIMESTAMP")]
+    pub backup: Option<String>,
+
+    /// Restore only these entry IDs (comma-separated, see --list-backups)
+    #[arg(long, value_delimiter = ',', value_name = "IDS")]
+    pub only: Option<Vec<String>>,
+
+    /// Pick entries to restore interactively (needs a terminal; ignored with --json)
+    #[arg(long)]
+    pub select: bool,
+
+    /// Disk watch: alert when free space drops below this size (e.g., 20GB)
+    #[arg(long, value_name = "SIZE", value_parser = crate::cache::output::parse_size)]
+    pub min_free: Option<u64>,
+
+    /// Disk watch: alert when free space drops below this percentage (used when --min-free is not set)
+    #[arg(long, value_name = "PERCENT", default_value = "10")]
+    pub min_free_percent: f64,
+
+    /// Disk watch: seconds between disk space checks
+    #[arg(long, value_name = "SECONDS", default_value = "60")]
+    pub watch_interval: u64,
+
+    /// Disk watch: providers to clean automatically when space runs low (e.g., npx,js-pm), deleted without a backup
+    #[arg(long, value_delimiter = ',', value_parser = clap::builder::PossibleValuesParser::new(crate::cache::watch::PROVIDERS))]
+    pub auto_clean: Vec<String>,
+
+    /// Budget for --check: exit nonzero when tracked caches exceed this size (e.g., 10GB)
+    #[arg(long, value_name = "SIZE", value_parser = crate::cache::output::parse_size)]
+    pub budget: Option<u64>,
+
+    /// Backup retention: keep only the N most recent backups
+    #[arg(long, value_name = "N")]
+    pub keep_backups: Option<usize>,
+
+    /// Backup retention: maximum total size of all backups (e.g., 5GB)
+    #[arg(long, value_name = "SIZE", value_parser = crate::cache::output::parse_size)]
+    pub max_backup_size: Option<u64>,
+
+    /// Backup retention: delete backups older than this many days
+    #[arg(long, value_name = "DAYS")]
+    pub max_backup_age: Option<u32>,
+}
+
+impl CacheArgs {
+    /// Backup retention policy built from the retention flags
+    pub fn retention_policy(&self) -> RetentionPolicy {
+        RetentionPolicy {
+            keep_last: self.keep_backups,
+            max_total_bytes: self.max_backup_size,
+            max_age_days: self.max_backup_age,
+        }
+    }
+
+    /// Age filter from --older-than, falling back to --stale-days
+    pub fn max_age(&self) -> Option<chrono::Duration> {
+        self.older_than
+            .or_else(|| self.stale_days.map(|d| chrono::Duration::days(d as i64)))
+    }
+
+    /// Free-space threshold for --watch-disk
+    pub fn disk_threshold(&self) -> DiskThreshold {
+        match self.min_free {
+            Some(bytes) => DiskThreshold::FreeBytes(bytes),
+            None => DiskThreshold::FreePercent(self.min_free_percent),
+        }
+    }
+}
+
+/// Parse a `KEY=VALUE` environment assignment
+fn parse_env_assignment(input: &str) -> Result<(String, String), String> {
+    match input.split_once('=') {
+        Some((key, value)) if !key.is_empty() => Ok((key.to_string(), value.to_string())),
+        _ => Err(format!("Invalid environment assignment '{}' (expected KEY=VALUE)", input)),
+    }
 }
 
 impl Args {
+    /// One-off overrides for --restart from --restart-env, --env-file and trailing arguments
+    pub fn restart_overrides(&self) -> anyhow::Result<RestartOverrides> {
+        let mut env = match &self.env_file {
+            Some(path) => crate::restart_manager::parse_env_file(path)?,
+            None => HashMap::new(),
+        };
+        env.extend(self.restart_env.iter().cloned());
+
+        Ok(RestartOverrides {
+            extra_args: self.restart_args.clone(),
+            env,
+            pre: self.pre_restart.clone(),
+            post: self.post_restart.clone(),
+            in_tmux: self.tmux,
+            clean_env: self.clean_env,
+            as_user: self.as_user.clone(),
+        })
+    }
+
     /// Get the list of ports to monitor
     pub fn get_ports_to_monitor(&self) -> Vec<u16> {
         if let Some(ref specific_ports) = self.ports {
@@ -479,6 +1384,11 @@ impl Args {
         self.get_ports_to_monitor().into_iter().collect()
     }
 
+    /// Whether a socket bound to `bind_address` passes `--bind-addr`
+    pub fn bind_address_allowed(&self, bind_address: Option<std::net::IpAddr>) -> bool {
+        self.bind_addr.is_empty() || self.bind_addr.iter().any(|filter| filter.matches(bind_address))
+    }
+
     /// Get a HashSet of ports to ignore for efficient lookup
     pub fn get_ignore_ports_set(&self) -> HashSet<u16> {
         self.ignore_ports
@@ -520,7 +1430,7 @@ impl Args {
         }
 
         // Smart port ignores (common system ports)
-        let smart_ignore_ports: HashSet<u16> = [
+        let mut smart_ignore_ports: HashSet<u16> = [
             22,    // SSH
             25,    // SMTP
             53,    // DNS
@@ -544,7 +1454,7 @@ impl Args {
         .collect();
 
         // Smart process ignores (common system processes)
-        let smart_ignore_processes: HashSet<String> = [
+        let mut smart_ignore_processes: HashSet<String> = [
             "Chrome",
             "Safari",
             "Firefox",
@@ -575,7 +1485,7 @@ impl Args {
         .collect();
 
         // Smart group ignores (system service groups)
-        let smart_ignore_groups: HashSet<String> = [
+        let mut smart_ignore_groups: HashSet<String> = [
             "Web Server", // System web servers
             "Database",   // System databases
         ]
@@ -583,6 +1493,13 @@ impl Args {
         .map(|s| s.to_string())
         .collect();
 
+        // User rules from the config file
+        self.smart_filter_rules.apply(
+            &mut smart_ignore_ports,
+            &mut smart_ignore_processes,
+            &mut smart_ignore_groups,
+        );
+
         (
             smart_ignore_ports,
             smart_ignore_processes,
@@ -590,6 +1507,19 @@ impl Args {
         )
     }
 
+    /// Get the process name patterns to ignore, including smart filter patterns
+    pub fn get_ignore_patterns(&self) -> Option<Vec<String>> {
+        let mut patterns = self.ignore_patterns.clone().unwrap_or_default();
+        if self.smart_filter {
+            patterns.extend(self.smart_filter_rules.ignore_patterns.iter().cloned());
+        }
+        if patterns.is_empty() {
+            None
+        } else {
+            Some(patterns)
+        }
+    }
+
     /// Get a description of the port configuration
     pub fn get_port_description(&self) -> String {
         let mut description = if let Some(ref specific_ports) = self.ports {
@@ -637,6 +1567,131 @@ impl Args {
         description
     }
 
+    /// Describe the fully resolved configuration for `--explain`
+    pub fn explain(&self) -> String {
+        fn list(values: &Option<Vec<String>>) -> String {
+            match values {
+                Some(values) if !values.is_empty() => values.join(", "),
+                _ => "none".to_string(),
+            }
+        }
+        fn sorted<T: Ord + ToString>(values: HashSet<T>) -> String {
+            let mut values: Vec<T> = values.into_iter().collect();
+            values.sort();
+            values
+                .iter()
+                .map(|v| v.to_string())
+                .collect::<Vec<_>>()
+                .join(", ")
+        }
+        let on_off = |enabled: bool| if enabled { "on" } else { "off" };
+
+        let mut output = match self.preset {
+            Some(ref name) => format!("🔍 Effective configuration for preset '{}'\n\n", name),
+            None => "🔍 Effective configuration\n\n".to_string(),
+        };
+
+        let ports = match self.ports {
+            Some(ref ports) => ports.join(", "),
+            None => format!("{}-{}", self.start_port, self.end_port),
+        };
+        output.push_str(&format!(
+            "Ports: {} ({} ports)\n",
+            ports,
+            self.get_ports_to_monitor().len()
+        ));
+        let ignore_ports = self
+            .ignore_ports
+            .as_ref()
+            .map(|ports| ports.iter().map(|p| p.to_string()).collect());
+        output.push_str(&format!("Ignored ports: {}\n", list(&ignore_ports)));
+        output.push_str(&format!(
+            "Ignored processes: {}\n",
+            list(&self.ignore_processes)
+        ));
+        output.push_str(&format!(
+            "Ignored patterns: {}\n",
+            list(&self.get_ignore_patterns())
+        ));
+        output.push_str(&format!("Ignored groups: {}\n", list(&self.ignore_groups)));
+        output.push_str(&format!("Only groups: {}\n", list(&self.only_groups)));
+
+        output.push_str(&format!("Smart filter: {}\n", on_off(self.smart_filter)));
+        if self.smart_filter {
+            let (ports, processes, groups) = self.get_smart_filter_defaults();
+            output.push_str(&format!("  also ignores ports: {}\n", sorted(ports)));
+            output.push_str(&format!("  also ignores processes: {}\n", sorted(processes)));
+            output.push_str(&format!("  also ignores groups: {}\n", sorted(groups)));
+        }
+        output.push_str(&format!("Docker: {}\n", on_off(self.docker)));
+        output.push_str(&format!("Show PID: {}\n", on_off(self.show_pid)));
+        output.push_str(&format!("Show context: {}\n", on_off(self.show_context)));
+        output.push_str(&format!("Performance: {}\n", on_off(self.performance)));
+
+        output.push_str(&format!("Guard mode: {}\n", on_off(self.guard_mode)));
+        if self.guard_mode {
+            output.push_str(&format!("  guarded ports: {}\n", self.guard_ports));
+            output.push_str(&format!("  auto-resolve: {}\n", on_off(self.auto_resolve)));
+            output.push_str(&format!(
+                "  reservations: {}\n",
+                self.get_reservation_file_path()
+            ));
+        }
+        output.push_str(&format!("Suspicious ports: {}\n", self.suspicious_ports));
+        output.push_str(&format!(
+            "Audit baseline: {}\n",
+            self.get_baseline_file_path()
+                .unwrap_or_else(|| "none".to_string())
+        ));
+
+        output
+    }
+
+    /// Parse the command line with defaults from ~/.port-kill/config.toml layered
+    /// underneath it (CLI > environment > config file)
+    pub fn parse_with_config() -> Self {
+        let matches = Self::command().get_matches();
+        let mut args = Self::from_arg_matches(&matches).unwrap_or_else(|e| e.exit());
+        args.explicit_settings = explicit_settings(&matches);
+        if !args.no_config {
+            let result = UserConfig::load(&UserConfig::default_path())
+                .and_then(|config| config.apply_to(&mut args, &matches));
+            if let Err(e) = result {
+                if !args.doctor {
+                    eprintln!("Error: {}", e);
+                    std::process::exit(1);
+                }
+                args.config_error = Some(e.to_string());
+            }
+        }
+        args
+    }
+
+    /// Build the arguments again from this process's command line, the config file and
+    /// presets as they are now, the way startup does. Used to pick up edits while running.
+    pub fn reload() -> Result<Self, String> {
+        let matches = Self::command()
+            .try_get_matches_from(std::env::args_os())
+            .map_err(|e| e.to_string())?;
+        let mut args = Self::from_arg_matches(&matches).map_err(|e| e.to_string())?;
+        args.explicit_settings = explicit_settings(&matches);
+        if !args.no_config {
+            UserConfig::load(&UserConfig::default_path())
+                .and_then(|config| config.apply_to(&mut args, &matches))
+                .map_err(|e| e.to_string())?;
+        }
+        if let Some(preset_name) = args.preset.clone() {
+            args.load_preset(&preset_name)?;
+        } else {
+            args.auto_select_preset();
+        }
+        if args.guard_mode {
+            args.add_detected_guard_ports();
+        }
+        args.validate()?;
+        Ok(args)
+    }
+
     /// Validate the arguments
     pub fn validate(&self) -> Result<(), String> {
         // Validate port range
@@ -663,6 +1718,12 @@ impl Args {
             }
         }
 
+        if let (Some(since), Some(until)) = (self.since, self.until) {
+            if since > until {
+                return Err(format!("--since {} is after --until {}", since, until));
+            }
+        }
+
         // Validate ignore ports if provided
         if let Some(ref ignore_ports) = self.ignore_ports {
             for &port in ignore_ports {
@@ -699,6 +1760,13 @@ impl Args {
         ]
     }
 
+    /// Quick actions to offer in the tray menu
+    pub fn get_tray_quick_actions(&self) -> Vec<QuickAction> {
+        self.tray_quick_actions
+            .clone()
+            .unwrap_or_else(QuickAction::defaults)
+    }
+
     /// Get the list of ports to watch in guard mode
     pub fn get_guard_ports(&self) -> Vec<u16> {
         self.guard_ports
@@ -707,6 +1775,60 @@ impl Args {
             .collect()
     }
 
+    /// Also guard the ports of services detected in the current directory when the
+    /// guard ports are still the defaults. Returns the ports that were added.
+    pub fn add_detected_guard_ports(&mut self) -> Vec<u16> {
+        if self.guard_ports != DEFAULT_GUARD_PORTS {
+            return Vec::new();
+        }
+        let port_map = ServiceDetector::new().port_map().unwrap_or_default();
+
+        let mut guarded = self.get_guard_ports();
+        let mut added = Vec::new();
+        for port in port_map.ports() {
+            if !guarded.contains(&port) {
+                guarded.push(port);
+                added.push(port);
+            }
+        }
+        self.guard_ports = join_ports(&guarded);
+        added
+    }
+
+    /// Also monitor the ports the dev container in `dir` forwards. Returns the ports
+    /// that were not monitored yet.
+    pub fn add_devcontainer_ports(&mut self, dir: &Path) -> Vec<u16> {
+        let monitored = self.get_ports_set();
+        let added: Vec<u16> = devcontainer_ports(dir)
+            .into_iter()
+            .filter(|port| !monitored.contains(port))
+            .collect();
+        if added.is_empty() {
+            return added;
+        }
+
+        // The range keeps applying alongside the extra ports
+        let mut ports = self
+            .ports
+            .clone()
+            .unwrap_or_else(|| vec![format!("{}-{}", self.start_port, self.end_port)]);
+        ports.extend(added.iter().map(|port| port.to_string()));
+        self.ports = Some(ports);
+        added
+    }
+
+    /// The `--history-port`, `--since` and `--until` filter for the history views
+    pub fn history_filter(&self) -> crate::types::HistoryFilter {
+        crate::types::HistoryFilter::days(self.history_port, self.since, self.until)
+    }
+
+    /// tmux session `--tmux` opens service windows in
+    pub fn tmux_session_name(&self) -> String {
+        self.tmux_session
+            .clone()
+            .unwrap_or_else(crate::tmux::default_session_name)
+    }
+
     /// Get the expanded reservation file path
     pub fn get_reservation_file_path(&self) -> String {
         if self.reservation_file.starts_with("~/") {
@@ -736,11 +1858,18 @@ impl Args {
                     .unwrap_or_else(|_| "/tmp".to_string());
                 path.replace("~/", &format!("{}/", home))
             } else {
-                path.clone()
+                // A relative baseline may live at the repository root
+                resolve_upward(path).to_string_lossy().to_string()
             }
         })
     }
 
+    /// Get the orchestration config path, looking in parent directories up to the
+    /// git root when it is not in the current directory
+    pub fn get_config_file_path(&self) -> std::path::PathBuf {
+        resolve_upward(&self.config_file)
+    }
+
     /// Get the remote host for SSH connection
     pub fn get_remote_host(&self) -> Option<String> {
         self.remote.clone()
@@ -778,6 +1907,21 @@ impl Args {
         self.show_pid = preset.show_pid;
         self.performance = preset.performance;
         self.show_context = preset.show_context;
+
+        // Guard and security posture
+        if let Some(ref guard_ports) = preset.guard_ports {
+            self.guard_mode = true;
+            self.guard_ports = join_ports(guard_ports);
+        }
+        if preset.auto_resolve {
+            self.auto_resolve = true;
+        }
+        if let Some(ref suspicious_ports) = preset.suspicious_ports {
+            self.suspicious_ports = join_ports(suspicious_ports);
+        }
+        if let Some(ref baseline_file) = preset.baseline_file {
+            self.baseline_file = Some(baseline_file.clone());
+        }
     }
 
     /// Load and apply preset by name
@@ -798,6 +1942,86 @@ impl Args {
         }
     }
 
+    /// Apply a preset matching the project in the current directory when no preset or
+    /// ports were chosen explicitly, and also monitor the ports its dev container
+    /// forwards. Returns the preset name and why it was picked.
+    pub fn auto_select_preset(&mut self) -> Option<(String, String)> {
+        let explicit_ports = self.ports.is_some()
+            || !self.positional_ports.is_empty()
+            || self.start_port != 2000
+            || self.end_port != 9000;
+        if self.preset.is_some() || self.no_auto_preset || explicit_ports {
+            return None;
+        }
+
+        let cwd = std::env::current_dir().ok()?;
+        let selected = self.apply_project_preset(&cwd);
+        let added = self.add_devcontainer_ports(&cwd);
+        if !added.is_empty() {
+            log::info!("Also monitoring dev container ports {}", join_ports(&added));
+        }
+        selected
+    }
+
+    fn apply_project_preset(&mut self, cwd: &Path) -> Option<(String, String)> {
+        let detected = detect_project_preset(cwd)?;
+        let mut manager = PresetManager::new();
+        manager.load_presets().ok()?;
+        let preset = manager.get_preset(detected.preset)?;
+        self.apply_auto_preset(preset, detected.preset);
+        Some((detected.preset.to_string(), detected.reason))
+    }
+
+    /// Apply a preset picked from the project rather than named with `--preset`. The
+    /// project's `.port-kill-presets.yaml` can replace the built-in one, so it must not
+    /// start guarding or killing anything, nor change what was passed explicitly.
+    fn apply_auto_preset(&mut self, preset: &PortPreset, name: &str) {
+        let before = self.clone();
+        self.apply_preset(preset);
+
+        self.guard_mode = before.guard_mode;
+        self.guard_ports = before.guard_ports.clone();
+        self.auto_resolve = before.auto_resolve;
+        if preset.guard_ports.is_some() || preset.auto_resolve {
+            log::warn!(
+                "Preset '{}' turns on guard mode or auto-resolve, which auto-selected presets don't do; pass --preset {} to use them",
+                name,
+                name
+            );
+        }
+
+        let explicit = |id: &str| before.explicit_settings.iter().any(|setting| setting == id);
+        // Flags keep the value passed on the command line or in the environment, and
+        // stay on when the config file turns them on
+        self.smart_filter = before.smart_filter || (self.smart_filter && !explicit("smart_filter"));
+        self.docker = before.docker || (self.docker && !explicit("docker"));
+        self.show_pid = before.show_pid || (self.show_pid && !explicit("show_pid"));
+        self.performance = before.performance || (self.performance && !explicit("performance"));
+        self.show_context = before.show_context || (self.show_context && !explicit("show_context"));
+        // So do lists and files
+        if explicit("ignore_ports") {
+            self.ignore_ports = before.ignore_ports.clone();
+        }
+        if explicit("ignore_processes") {
+            self.ignore_processes = before.ignore_processes.clone();
+        }
+        if explicit("ignore_patterns") {
+            self.ignore_patterns = before.ignore_patterns.clone();
+        }
+        if explicit("ignore_groups") {
+            self.ignore_groups = before.ignore_groups.clone();
+        }
+        if explicit("only_groups") {
+            self.only_groups = before.only_groups.clone();
+        }
+        if explicit("suspicious_ports") {
+            self.suspicious_ports = before.suspicious_ports.clone();
+        }
+        if explicit("baseline_file") {
+            self.baseline_file = before.baseline_file.clone();
+        }
+    }
+
     /// List available presets
     pub fn list_available_presets() -> Result<String, String> {
         let mut manager = PresetManager::new();
@@ -807,6 +2031,84 @@ impl Args {
         Ok(manager.list_presets())
     }
 
+    /// Export a preset as shareable JSON
+    pub fn export_preset_json(name: &str) -> Result<String, String> {
+        let mut manager = PresetManager::new();
+        manager
+            .load_presets()
+            .map_err(|e| format!("Failed to load presets: {}", e))?;
+        manager
+            .export_preset(name)
+            .map_err(|e| format!("{}. Use --list-presets to see available presets.", e))
+    }
+
+    /// Fetch presets from a URL or file, show what they change, and save them after
+    /// confirmation. Returns the names of the imported presets.
+    pub async fn import_presets(source: &str, assume_yes: bool) -> Result<Vec<String>, String> {
+        use std::io::{IsTerminal, Write};
+
+        let content = fetch_preset_source(source).await.map_err(|e| e.to_string())?;
+        let incoming = parse_shared_presets(&content).map_err(|e| e.to_string())?;
+        if incoming.is_empty() {
+            return Err(format!("No presets found in {}", source));
+        }
+
+        let mut manager = PresetManager::new();
+        manager
+            .load_presets()
+            .map_err(|e| format!("Failed to load presets: {}", e))?;
+
+        println!("📥 Presets from {}:", source);
+        let mut changed = Vec::new();
+        for preset in incoming {
+            let changes = manager.describe_import(&preset);
+            match manager.preset_source(&preset.name) {
+                None => println!("  ➕ {} (new)", preset.name),
+                Some(_) if changes.is_empty() => {
+                    println!("  ✔ {} (unchanged)", preset.name);
+                    continue;
+                }
+                Some(origin) => {
+                    println!("  ✏️  {} (replaces {} preset)", preset.name, origin)
+                }
+            }
+            for change in &changes {
+                println!("      {}", change);
+            }
+            changed.push(preset);
+        }
+
+        if changed.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        if !assume_yes {
+            if !std::io::stdin().is_terminal() {
+                return Err(
+                    "Refusing to import without confirmation; re-run with --yes".to_string(),
+                );
+            }
+            print!("Save {} preset(s)? [y/N] ", changed.len());
+            std::io::stdout().flush().map_err(|e| e.to_string())?;
+            let mut input = String::new();
+            std::io::stdin()
+                .read_line(&mut input)
+                .map_err(|e| e.to_string())?;
+            if !matches!(input.trim(), "y" | "Y" | "yes" | "YES") {
+                return Err("Import cancelled".to_string());
+            }
+        }
+
+        let names = changed.iter().map(|p| p.name.clone()).collect();
+        for preset in changed {
+            manager.add_preset(preset);
+        }
+        manager
+            .save_presets()
+            .map_err(|e| format!("Failed to save presets: {}", e))?;
+        Ok(names)
+    }
+
     /// Build a PortPreset from current arguments
     pub fn build_preset_from_args(&self, name: String, description: String) -> PortPreset {
         PortPreset {
@@ -823,10 +2125,38 @@ impl Args {
             show_pid: self.show_pid,
             performance: self.performance,
             show_context: self.show_context,
+            guard_ports: self.guard_mode.then(|| self.get_guard_ports()),
+            auto_resolve: self.guard_mode && self.auto_resolve,
+            suspicious_ports: (self.suspicious_ports != DEFAULT_SUSPICIOUS_PORTS)
+                .then(|| self.get_suspicious_ports()),
+            baseline_file: self.baseline_file.clone(),
         }
     }
 }
 
+/// Ids of the arguments given on the command line or in the environment
+fn explicit_settings(matches: &clap::ArgMatches) -> Vec<String> {
+    matches
+        .ids()
+        .filter(|id| {
+            matches!(
+                matches.value_source(id.as_str()),
+                Some(clap::parser::ValueSource::CommandLine)
+                    | Some(clap::parser::ValueSource::EnvVariable)
+            )
+        })
+        .map(|id| id.to_string())
+        .collect()
+}
+
+fn join_ports(ports: &[u16]) -> String {
+    ports
+        .iter()
+        .map(|p| p.to_string())
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
 impl LogLevel {
     /// Convert LogLevel to RUST_LOG environment variable value
     pub fn to_rust_log(&self) -> &'static str {
@@ -871,26 +2201,61 @@ mod tests {
             smart_filter: false,
             only_groups: None,
             console: false,
+            tui: false,
             verbose: false,
             docker: false,
+            udp: false,
+            bind_addr: Vec::new(),
             show_pid: false,
             log_level: LogLevel::Info,
+            log_file: None,
+            log_format: LogFormat::Text,
+            log_rotation: LogRotation::Daily,
             show_history: false,
             clear_history: false,
             show_filters: false,
             performance: false,
             show_context: false,
+            friendly_names: false,
+            report: None,
+            report_format: ReportFormat::Terminal,
+            report_send: false,
+            fail_if_found: false,
+            no_daemon: false,
+            update_channel: UpdateChannel::Stable,
             kill_all: false,
             kill_group: None,
             kill_project: None,
             restart: None,
+            pick: false,
+            keep_alive: false,
+            watch: Vec::new(),
+            pre_restart: None,
+            post_restart: None,
+            tmux: false,
+            tmux_session: None,
+            no_tmux: false,
+            restart_env: Vec::new(),
+            env_file: None,
+            clean_env: false,
+            as_user: None,
+            restart_args: Vec::new(),
+            restart_all: false,
+            restart_timeout: 10,
             show_restart_history: false,
             clear_restart: None,
+            prune_restarts: false,
+            export_restarts: None,
+            import_restarts: None,
             show_tree: false,
             json: false,
             reset: false,
             show_offenders: false,
             show_patterns: false,
+            history_port: None,
+            since: None,
+            until: None,
+            timeline: None,
             show_suggestions: false,
             show_stats: false,
             show_root_cause: false,
@@ -908,14 +2273,18 @@ mod tests {
             baseline_file: None,
             suspicious_only: false,
             remote: None,
+            remote_bin: "port-kill-console".to_string(),
             monitor_endpoint: None,
             send_interval: 30,
             scan_interval: 2,
+            max_scan_interval: 30,
             endpoint_auth: None,
             endpoint_fields: None,
             endpoint_include_audit: false,
             endpoint_retries: 3,
             endpoint_timeout: 10,
+            prometheus_port: None,
+            prometheus_addr: std::net::Ipv4Addr::LOCALHOST.into(),
             script: None,
             script_file: None,
             script_lang: "js".to_string(),
@@ -930,22 +2299,52 @@ mod tests {
             safe: false,
             positional_ports: vec![],
             preset: None,
+            no_auto_preset: false,
+            explain: false,
             list_presets: false,
             save_preset: None,
             preset_desc: None,
+            interactive: false,
             delete_preset: None,
+            import_preset: None,
+            export_preset: None,
+            yes: false,
             check_updates: false,
+            doctor: false,
+            dashboard: None,
+            serve: None,
+            machine: false,
+            install_login_item: false,
+            remove_login_item: false,
+            install_git_hooks: false,
+            reserve_branch_port: false,
+            release_branch_port: None,
+            branch_ports: false,
             self_update: false,
-            cache: None,
+            command: None,
             detect: false,
             start: None,
+            start_env: Vec::new(),
+            load_env: false,
             guard_auto_restart: false,
             up: false,
+            watch_config: false,
+            manage: false,
             down: false,
             restart_service: None,
             status: false,
             config_file: ".port-kill.yaml".to_string(),
             init_config: false,
+            no_config: false,
+            no_reload: false,
+            smart_filter_rules: Default::default(),
+            tray_quick_actions: None,
+            tray_icon_rules: Default::default(),
+            tray_menu_layout: Default::default(),
+            tray_hotkeys: Vec::new(),
+            tray_pins: Vec::new(),
+            explicit_settings: Vec::new(),
+            config_error: None,
         }
     }
 
@@ -1080,4 +2479,201 @@ mod tests {
 
         assert!(args.validate().is_err());
     }
+
+    #[test]
+    fn test_restart_overrides() {
+        let args = Args::parse_from([
+            "port-kill",
+            "--restart",
+            "3000",
+            "--restart-env",
+            "PORT=3001",
+            "--",
+            "--port",
+            "3001",
+        ]);
+        let overrides = args.restart_overrides().unwrap();
+        assert_eq!(overrides.extra_args, vec!["--port", "3001"]);
+        assert_eq!(overrides.env["PORT"], "3001");
+
+        assert!(Args::try_parse_from(["port-kill", "--restart-env", "PORT"]).is_err());
+    }
+
+    #[test]
+    fn test_start_pass_through() {
+        let args = Args::parse_from([
+            "port-kill",
+            "--start",
+            "npm:dev",
+            "--start-env",
+            "PORT=3005",
+            "--",
+            "--host",
+            "0.0.0.0",
+        ]);
+        assert_eq!(args.start.as_deref(), Some("npm:dev"));
+        assert_eq!(
+            args.start_env,
+            vec![("PORT".to_string(), "3005".to_string())]
+        );
+        assert_eq!(args.restart_args, vec!["--host", "0.0.0.0"]);
+
+        assert!(Args::try_parse_from(["port-kill", "--start-env", "PORT=3005"]).is_err());
+    }
+
+    #[test]
+    fn test_tmux_targets() {
+        let args = Args::parse_from(["port-kill", "--start", "npm:dev", "--tmux", "--tmux-session", "shop"]);
+        assert!(args.tmux);
+        assert_eq!(args.tmux_session_name(), "shop");
+        assert!(Args::try_parse_from(["port-kill", "--up", "--tmux"]).is_ok());
+        assert!(Args::try_parse_from(["port-kill", "--restart", "3000", "--tmux"]).is_ok());
+
+        assert!(Args::try_parse_from(["port-kill", "--tmux"]).is_err());
+        assert!(Args::try_parse_from(["port-kill", "--up", "--tmux-session", "shop"]).is_err());
+    }
+
+    #[test]
+    fn test_run_subcommand() {
+        let args = Args::parse_from(["port-kill", "run", "--port", "3001", "--", "rails", "s"]);
+        let Some(Command::Run(run)) = args.command else {
+            panic!("expected the run subcommand");
+        };
+        assert_eq!(run.port, vec![3001]);
+        assert_eq!(run.command, vec!["rails", "s"]);
+
+        // Everything after the command belongs to it
+        let args = Args::parse_from(["port-kill", "run", "npm", "run", "dev", "--", "--host"]);
+        let Some(Command::Run(run)) = args.command else {
+            panic!("expected the run subcommand");
+        };
+        assert!(!run.dry_run);
+        assert_eq!(run.command, vec!["npm", "run", "dev", "--", "--host"]);
+    }
+
+    #[test]
+    fn test_history_subcommand() {
+        let args = Args::parse_from([
+            "port-kill", "history", "--port", "3000", "--since", "7d", "--until", "2026-03-05",
+            "--project", "webshop", "--json",
+        ]);
+        let Some(Command::History(history)) = args.command else {
+            panic!("expected the history subcommand");
+        };
+        assert_eq!(history.since, Some(HistoryTime::Ago(chrono::Duration::days(7))));
+        assert!(history.json);
+
+        let now: chrono::DateTime<chrono::Utc> = "2026-03-10T12:00:00Z".parse().unwrap();
+        assert_eq!(
+            HistoryTime::Ago(chrono::Duration::days(7)).start(now).to_rfc3339(),
+            "2026-03-03T12:00:00+00:00"
+        );
+        let until = HistoryTime::parse("2026-03-05").unwrap();
+        assert_eq!(until.end(now).to_rfc3339(), "2026-03-06T00:00:00+00:00");
+        assert!(HistoryTime::parse("yesterday").is_err());
+
+        // Restarts are not recorded per process or project
+        assert!(Args::try_parse_from(["port-kill", "history", "--restarts", "--process", "node"]).is_err());
+
+        let args = Args::parse_from(["port-kill", "history", "export", "--format", "csv", "--interval", "1d"]);
+        let Some(Command::History(HistoryArgs {
+            action: Some(HistoryAction::Export(export)),
+            ..
+        })) = args.command
+        else {
+            panic!("expected history export");
+        };
+        assert_eq!(export.since, HistoryTime::Ago(chrono::Duration::days(30)));
+        assert_eq!(export.interval, chrono::Duration::days(1));
+        assert!(Args::try_parse_from(["port-kill", "history", "export", "--format", "prometheus-remote-write"]).is_err());
+    }
+
+    #[test]
+    fn test_add_devcontainer_ports() {
+        let dir =
+            std::env::temp_dir().join(format!("port-kill-devcontainer-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(
+            dir.join(".devcontainer.json"),
+            r#"{"forwardPorts": [3000, 27017]}"#,
+        )
+        .unwrap();
+
+        let mut args = create_test_args();
+        assert_eq!(args.add_devcontainer_ports(&dir), vec![27017]);
+        assert_eq!(
+            args.ports,
+            Some(vec!["2000-9000".to_string(), "27017".to_string()])
+        );
+        let monitored = args.get_ports_set();
+        assert!(monitored.contains(&3000) && monitored.contains(&27017));
+        assert!(args.add_devcontainer_ports(&dir).is_empty());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_preset_carries_guard_and_security_settings() {
+        let mut args = create_test_args();
+        args.guard_mode = true;
+        args.guard_ports = "3000,5432".to_string();
+        args.auto_resolve = true;
+        args.suspicious_ports = "4444".to_string();
+        let preset = args.build_preset_from_args("secure-dev".to_string(), String::new());
+        assert_eq!(preset.guard_ports, Some(vec![3000, 5432]));
+        assert!(preset.auto_resolve);
+        assert_eq!(preset.suspicious_ports, Some(vec![4444]));
+
+        let mut fresh = create_test_args();
+        fresh.apply_preset(&preset);
+        assert!(fresh.guard_mode);
+        assert!(fresh.auto_resolve);
+        assert_eq!(fresh.get_guard_ports(), vec![3000, 5432]);
+        assert_eq!(fresh.get_suspicious_ports(), vec![4444]);
+    }
+
+    #[test]
+    fn test_auto_preset_keeps_guard_off_and_explicit_settings() {
+        let matches = Args::command().get_matches_from([
+            "port-kill",
+            "--ignore-processes",
+            "Chrome",
+            "--docker",
+        ]);
+        let mut args = Args::from_arg_matches(&matches).unwrap();
+        args.explicit_settings = explicit_settings(&matches);
+        let mut preset = PortPreset::new("node".to_string(), String::new(), vec![3000]);
+        preset.ignore_processes = Some(vec!["Slack".to_string()]);
+        preset.ignore_patterns = Some(vec!["*helper*".to_string()]);
+        preset.guard_ports = Some(vec![3000]);
+        preset.auto_resolve = true;
+        preset.show_pid = true;
+
+        args.apply_auto_preset(&preset, "node");
+        assert_eq!(args.ports, Some(vec!["3000".to_string()]));
+        assert_eq!(args.ignore_processes, Some(vec!["Chrome".to_string()]));
+        assert_eq!(args.ignore_patterns, Some(vec!["*helper*".to_string()]));
+        assert!(args.docker && args.show_pid);
+        assert!(!args.guard_mode && !args.auto_resolve);
+    }
+
+    #[test]
+    fn test_explain_shows_resolved_preset() {
+        let mut args = create_test_args();
+        args.preset = Some("backend".to_string());
+        let mut preset = PortPreset::new("backend".to_string(), String::new(), vec![4000, 4001]);
+        preset.ignore_processes = Some(vec!["Chrome".to_string()]);
+        preset.smart_filter = true;
+        preset.guard_ports = Some(vec![4000]);
+        args.apply_preset(&preset);
+
+        let explained = args.explain();
+        assert!(explained.contains("preset 'backend'"));
+        assert!(explained.contains("Ports: 4000, 4001 (2 ports)"));
+        assert!(explained.contains("Ignored processes: Chrome"));
+        assert!(explained.contains("Smart filter: on"));
+        assert!(explained.contains("also ignores ports: 22, 25, 53"));
+        assert!(explained.contains("Guard mode: on"));
+        assert!(explained.contains("guarded ports: 4000"));
+    }
 }
@@ -28,9 +28,112 @@ pub fn parse_command_line(command_line: &str) -> Vec<String> {
     parts
 }
 
+/// Replace `{{name}}` placeholders (e.g. `{{port}}`, `{{cwd}}`) with their values.
+/// Placeholders without a value are an error rather than being left in the command.
+pub fn expand_placeholders(
+    text: &str,
+    values: &std::collections::HashMap<&str, String>,
+) -> Result<String, String> {
+    let mut expanded = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find("{{") {
+        let end = rest[start..]
+            .find("}}")
+            .map(|end| start + end)
+            .ok_or_else(|| format!("Unclosed placeholder in '{}'", text))?;
+        let name = rest[start + 2..end].trim();
+        let value = values.get(name).ok_or_else(|| {
+            let mut known: Vec<_> = values.keys().map(|k| format!("{{{{{}}}}}", k)).collect();
+            known.sort();
+            format!(
+                "No value for {{{{{}}}}} in '{}' (available: {})",
+                name,
+                text,
+                known.join(", ")
+            )
+        })?;
+        expanded.push_str(&rest[..start]);
+        expanded.push_str(value);
+        rest = &rest[end + 2..];
+    }
+    expanded.push_str(rest);
+    Ok(expanded)
+}
+
+/// Replace the `{{name}}` placeholders that have a value and leave any other `{{...}}`
+/// text alone, for captured commands such as `docker ps --format '{{.Names}}'`
+pub fn expand_known_placeholders(
+    text: &str,
+    values: &std::collections::HashMap<&str, String>,
+) -> String {
+    let mut expanded = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find("{{") {
+        let Some(end) = rest[start..].find("}}").map(|end| start + end) else {
+            break;
+        };
+        expanded.push_str(&rest[..start]);
+        match values.get(rest[start + 2..end].trim()) {
+            Some(value) => expanded.push_str(value),
+            None => expanded.push_str(&rest[start..end + 2]),
+        }
+        rest = &rest[end + 2..];
+    }
+    expanded.push_str(rest);
+    expanded
+}
+
+/// Join command parts into a POSIX shell command line, quoting where needed
+pub fn quote_command_line(parts: &[String]) -> String {
+    parts
+        .iter()
+        .map(|part| {
+            let plain = !part.is_empty()
+                && part
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || "-_./=:,@%+".contains(c));
+            if plain {
+                part.clone()
+            } else {
+                format!("'{}'", part.replace('\'', r"'\''"))
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
 #[cfg(test)]
 mod tests {
-    use super::parse_command_line;
+    use super::{
+        expand_known_placeholders, expand_placeholders, parse_command_line, quote_command_line,
+    };
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_expand_placeholders() {
+        let values = HashMap::from([("port", "3001".to_string()), ("cwd", "/work".to_string())]);
+        assert_eq!(
+            expand_placeholders("--port={{port}} --root {{ cwd }}/public", &values).unwrap(),
+            "--port=3001 --root /work/public"
+        );
+        assert_eq!(expand_placeholders("plain", &values).unwrap(), "plain");
+        assert!(expand_placeholders("{{host}}", &values).is_err());
+        assert!(expand_placeholders("{{port", &values).is_err());
+
+        assert_eq!(
+            expand_known_placeholders("--format '{{.Names}}' -p {{port}} {{port", &values),
+            "--format '{{.Names}}' -p 3001 {{port"
+        );
+    }
+
+    #[test]
+    fn test_quote_command_line() {
+        let parts = parse_command_line(r#"node "my script.js" --name "it's" --port=3000"#);
+        assert_eq!(
+            quote_command_line(&parts),
+            r"node 'my script.js' --name 'it'\''s' --port=3000"
+        );
+    }
 
     #[test]
     fn test_parse_command_line() {
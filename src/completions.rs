@@ -0,0 +1,243 @@
+use crate::cli::{Args, CompletionValues, CompletionsArgs};
+use crate::orchestrator::Orchestrator;
+use crate::preset_manager::PresetManager;
+use crate::process_monitor::ProcessMonitor;
+use crate::service_detector::ServiceDetector;
+use anyhow::Result;
+use clap::{CommandFactory, ValueEnum};
+use clap_complete::Shell;
+use crossbeam_channel::bounded;
+
+/// Flags whose values depend on the machine, keyed by argument id.
+/// The generated scripts ask `<bin> completions --values <kind>` for them on every tab.
+const DYNAMIC_FLAGS: &[(&str, CompletionValues)] = &[
+    ("preset", CompletionValues::Presets),
+    ("delete_preset", CompletionValues::Presets),
+    ("export_preset", CompletionValues::Presets),
+    ("restart_service", CompletionValues::Services),
+    ("start", CompletionValues::Detected),
+    ("clear", CompletionValues::Ports),
+    ("guard", CompletionValues::Ports),
+    ("restart", CompletionValues::Ports),
+    ("clear_restart", CompletionValues::Ports),
+    ("reserve_port", CompletionValues::Ports),
+];
+
+/// Handle `completions <shell>` and the hidden `completions --values <kind>`
+pub async fn run_completions(args: &Args, c: &CompletionsArgs) -> Result<()> {
+    if let Some(kind) = c.values {
+        for value in completion_values(args, kind).await {
+            println!("{}", value);
+        }
+        return Ok(());
+    }
+    if let Some(shell) = c.shell {
+        let script = match shell {
+            Shell::Bash | Shell::Fish | Shell::Zsh => generate_script(shell, &bin_name()),
+            // No hook for calling back into port-kill here, so embed what exists right now
+            _ => generate_snapshot_script(shell, &bin_name(), args).await,
+        };
+        print!("{}", script);
+    }
+    Ok(())
+}
+
+/// Current candidates for a dynamic value. Lookup failures yield no candidates,
+/// since a completion script has nowhere to show an error.
+pub async fn completion_values(args: &Args, kind: CompletionValues) -> Vec<String> {
+    let mut values = match kind {
+        CompletionValues::Presets => {
+            let mut manager = PresetManager::new();
+            let _ = manager.load_presets();
+            manager.get_preset_names()
+        }
+        CompletionValues::Services => Orchestrator::load_from_default()
+            .map(|o| o.config().services.keys().cloned().collect())
+            .unwrap_or_default(),
+        CompletionValues::Detected => ServiceDetector::new()
+            .discover_services()
+            .map(|services| services.into_iter().map(|s| s.name).collect())
+            .unwrap_or_default(),
+        CompletionValues::Ports => listening_ports(args)
+            .await
+            .unwrap_or_default()
+            .iter()
+            .map(u16::to_string)
+            .collect(),
+    };
+    values.sort();
+    values.dedup();
+    values
+}
+
+async fn listening_ports(args: &Args) -> Result<Vec<u16>> {
+    let (update_sender, _update_receiver) = bounded(100);
+    let mut monitor =
+        ProcessMonitor::new(update_sender, args.get_ports_to_monitor(), false, false)?;
+    Ok(monitor.scan_processes().await?.into_keys().collect())
+}
+
+/// Name the script should complete and call back into, i.e. how this binary was invoked
+fn bin_name() -> String {
+    std::env::args()
+        .next()
+        .and_then(|arg0| {
+            std::path::Path::new(&arg0)
+                .file_stem()
+                .map(|stem| stem.to_string_lossy().into_owned())
+        })
+        .unwrap_or_else(|| "port-kill".to_string())
+}
+
+fn kind_name(kind: CompletionValues) -> String {
+    kind.to_possible_value()
+        .map(|value| value.get_name().to_string())
+        .unwrap_or_default()
+}
+
+/// Value name that marks a dynamic flag in the generated zsh script
+fn placeholder(kind: CompletionValues) -> String {
+    format!("__PORT_KILL_{}__", kind_name(kind).to_uppercase())
+}
+
+fn dynamic_longs(cmd: &clap::Command) -> Vec<(String, CompletionValues)> {
+    DYNAMIC_FLAGS
+        .iter()
+        .filter_map(|(id, kind)| {
+            cmd.get_arguments()
+                .find(|arg| arg.get_id() == *id)
+                .and_then(|arg| arg.get_long())
+                .map(|long| (long.to_string(), *kind))
+        })
+        .collect()
+}
+
+fn generate(shell: Shell, cmd: &mut clap::Command, bin: &str) -> String {
+    let mut buf = Vec::new();
+    clap_complete::generate(shell, cmd, bin, &mut buf);
+    String::from_utf8_lossy(&buf).into_owned()
+}
+
+/// Completion script for shells that can run a command while completing
+pub fn generate_script(shell: Shell, bin: &str) -> String {
+    let mut cmd = Args::command();
+    let flags = dynamic_longs(&cmd);
+    let values_command = format!("{} completions --values", bin);
+
+    match shell {
+        Shell::Zsh => {
+            for (id, kind) in DYNAMIC_FLAGS {
+                cmd = cmd.mut_arg(*id, |arg| arg.value_name(placeholder(*kind)));
+            }
+            let mut script = generate(shell, &mut cmd, bin);
+            let helper = format!("_{}_values", bin.replace('-', "_"));
+            for (_, kind) in DYNAMIC_FLAGS {
+                let label = kind_name(*kind);
+                script = script.replace(
+                    &format!(":{}:_default", placeholder(*kind)),
+                    &format!(":{}:{{{} {}}}", label, helper, label),
+                );
+            }
+            // The helper has to exist before the completion function first runs
+            let definition = format!(
+                "{helper}() {{\n    local -a values\n    values=(${{(f)\"$({values_command} $1 2>/dev/null)\"}})\n    compadd -a values\n}}\n\n",
+                helper = helper,
+                values_command = values_command,
+            );
+            match script.find("autoload -U is-at-least\n") {
+                Some(at) => {
+                    let at = at + "autoload -U is-at-least\n".len();
+                    script.insert_str(at, &format!("\n{}", definition));
+                }
+                None => script.insert_str(0, &definition),
+            }
+            script
+        }
+        Shell::Bash => {
+            let mut script = generate(shell, &mut cmd, bin);
+            let generated = script
+                .lines()
+                .rev()
+                .find_map(|line| line.trim_start().strip_prefix("complete -F "))
+                .and_then(|rest| rest.split_whitespace().next())
+                .map(str::to_string);
+            let Some(generated) = generated else {
+                return script;
+            };
+            let wrapper = format!("{}_dynamic", generated);
+            let cases: String = flags
+                .iter()
+                .map(|(long, kind)| {
+                    format!(
+                        "        --{})\n            COMPREPLY=($(compgen -W \"$({} {} 2>/dev/null)\" -- \"${{cur}}\"))\n            return 0\n            ;;\n",
+                        long,
+                        values_command,
+                        kind_name(*kind)
+                    )
+                })
+                .collect();
+            script = script.replace(
+                &format!("complete -F {} ", generated),
+                &format!("complete -F {} ", wrapper),
+            );
+            script.push_str(&format!(
+                "\n{wrapper}() {{\n    local cur prev\n    cur=\"${{COMP_WORDS[COMP_CWORD]}}\"\n    prev=\"${{COMP_WORDS[COMP_CWORD-1]}}\"\n    case \"${{prev}}\" in\n{cases}    esac\n    {generated} \"$@\"\n}}\n",
+                wrapper = wrapper,
+                cases = cases,
+                generated = generated,
+            ));
+            script
+        }
+        Shell::Fish => {
+            let mut script = generate(shell, &mut cmd, bin);
+            for (long, kind) in &flags {
+                script.push_str(&format!(
+                    "complete -c {} -l {} -f -a \"({} {})\"\n",
+                    bin,
+                    long,
+                    values_command,
+                    kind_name(*kind)
+                ));
+            }
+            script
+        }
+        _ => generate(shell, &mut cmd, bin),
+    }
+}
+
+/// Completion script with the dynamic values as they are right now
+async fn generate_snapshot_script(shell: Shell, bin: &str, args: &Args) -> String {
+    let mut cmd = Args::command();
+    for (id, kind) in DYNAMIC_FLAGS {
+        let values = completion_values(args, *kind).await;
+        if !values.is_empty() {
+            cmd = cmd.mut_arg(*id, |arg| {
+                arg.value_parser(clap::builder::PossibleValuesParser::new(values))
+            });
+        }
+    }
+    generate(shell, &mut cmd, bin)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_scripts_call_back_for_dynamic_values() {
+        let zsh = generate_script(Shell::Zsh, "port-kill");
+        assert!(zsh.contains("_port_kill_values() {"));
+        assert!(zsh.contains(":presets:{_port_kill_values presets}'"));
+        assert!(!zsh.contains("__PORT_KILL_"));
+
+        let bash = generate_script(Shell::Bash, "port-kill");
+        assert!(bash.contains("complete -F _port__kill_dynamic "));
+        assert!(bash.contains("--restart-service)"));
+        assert!(bash.contains("port-kill completions --values services"));
+
+        let fish = generate_script(Shell::Fish, "port-kill");
+        assert!(fish.contains(
+            "complete -c port-kill -l guard -f -a \"(port-kill completions --values ports)\""
+        ));
+    }
+}
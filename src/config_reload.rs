@@ -0,0 +1,392 @@
+use crate::cli::Args;
+use crate::port_guard::PortGuardDaemon;
+use crate::preset_manager::PresetManager;
+use crate::process_monitor::{MonitorSettings, MonitorSettingsHandle};
+use crate::user_config::UserConfig;
+use ::notify::{RecommendedWatcher, RecursiveMode, Watcher};
+use anyhow::{Context, Result};
+use log::{info, warn};
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::mpsc::{unbounded_channel, UnboundedReceiver};
+
+/// Quiet period after a config change before it is applied, so editors that write
+/// a file in several steps trigger a single reload
+const RELOAD_DEBOUNCE: Duration = Duration::from_millis(300);
+
+/// Watches a fixed set of files, including ones that do not exist yet
+pub struct ConfigWatcher {
+    _watcher: RecommendedWatcher,
+    events: UnboundedReceiver<Vec<PathBuf>>,
+    files: HashSet<PathBuf>,
+}
+
+impl ConfigWatcher {
+    /// Watch `files` through their parent directories, since many editors save by
+    /// replacing the file rather than writing to it
+    pub fn new(files: Vec<PathBuf>) -> Result<Self> {
+        let (tx, events) = unbounded_channel();
+        let mut watcher =
+            ::notify::recommended_watcher(move |res: ::notify::Result<::notify::Event>| {
+                if let Ok(event) = res {
+                    if !event.kind.is_access() {
+                        let _ = tx.send(event.paths);
+                    }
+                }
+            })
+            .context("Failed to create config watcher")?;
+
+        let files: HashSet<PathBuf> = files.iter().map(|file| absolute(file)).collect();
+        let dirs: HashSet<&Path> = files.iter().filter_map(|file| file.parent()).collect();
+        for dir in dirs.into_iter().filter(|dir| dir.is_dir()) {
+            watcher
+                .watch(dir, RecursiveMode::NonRecursive)
+                .with_context(|| format!("Failed to watch {}", dir.display()))?;
+        }
+
+        Ok(Self {
+            _watcher: watcher,
+            events,
+            files,
+        })
+    }
+
+    /// Wait until one or more of the watched files change. Returns None once the
+    /// watcher has stopped.
+    pub async fn changed(&mut self) -> Option<Vec<PathBuf>> {
+        loop {
+            let paths = self.events.recv().await?;
+            let mut changed = self.relevant(paths);
+            if changed.is_empty() {
+                continue;
+            }
+            while let Ok(Some(paths)) =
+                tokio::time::timeout(RELOAD_DEBOUNCE, self.events.recv()).await
+            {
+                let more = self.relevant(paths);
+                changed.extend(more);
+            }
+            changed.sort();
+            changed.dedup();
+            return Some(changed);
+        }
+    }
+
+    fn relevant(&self, paths: Vec<PathBuf>) -> Vec<PathBuf> {
+        paths
+            .into_iter()
+            .filter(|path| self.files.contains(path))
+            .collect()
+    }
+}
+
+fn absolute(path: &Path) -> PathBuf {
+    if path.is_absolute() {
+        return path.to_path_buf();
+    }
+    std::env::current_dir()
+        .map(|cwd| cwd.join(path))
+        .unwrap_or_else(|_| path.to_path_buf())
+}
+
+/// Files whose changes affect a long-running `args`: the config file, user and
+/// project presets, and the guard's reservations
+pub fn watched_files(args: &Args) -> Vec<PathBuf> {
+    let mut files = Vec::new();
+    if !args.no_config {
+        files.push(UserConfig::default_path());
+    }
+    files.push(PathBuf::from(PresetManager::new().get_config_path()));
+    if let Some(project_presets) = std::env::current_dir()
+        .ok()
+        .and_then(|cwd| PresetManager::find_project_presets(&cwd))
+    {
+        files.push(project_presets);
+    }
+    if args.guard_mode || args.guard.is_some() {
+        files.push(PathBuf::from(args.get_reservation_file_path()));
+    }
+    files
+}
+
+/// The guard ports and auto-resolve setting `args` asks for
+fn guard_settings(args: &Args) -> (Vec<u16>, bool) {
+    match args.guard {
+        // --guard <port> always guards just that port and resolves conflicts
+        Some(port) => (vec![port], true),
+        None => (args.get_guard_ports(), args.auto_resolve),
+    }
+}
+
+/// One line per setting that differs between `old` and `new`
+pub fn describe_changes(old: &Args, new: &Args) -> Vec<String> {
+    fn list<T: ToString>(values: &Option<Vec<T>>) -> String {
+        match values {
+            Some(values) if !values.is_empty() => values
+                .iter()
+                .map(|v| v.to_string())
+                .collect::<Vec<_>>()
+                .join(", "),
+            _ => "none".to_string(),
+        }
+    }
+    fn ports(args: &Args) -> String {
+        match args.ports {
+            Some(_) => list(&args.ports),
+            None => format!("{}-{}", args.start_port, args.end_port),
+        }
+    }
+    fn hotkeys(args: &Args) -> Vec<String> {
+        args.tray_hotkeys
+            .iter()
+            .map(|(shortcut, action)| format!("{} = {}", shortcut, action))
+            .collect()
+    }
+    fn on_off(enabled: bool) -> String {
+        if enabled { "on" } else { "off" }.to_string()
+    }
+
+    let (old_guard, old_resolve) = guard_settings(old);
+    let (new_guard, new_resolve) = guard_settings(new);
+    let settings = [
+        ("ports", ports(old), ports(new)),
+        (
+            "ignored ports",
+            list(&old.ignore_ports),
+            list(&new.ignore_ports),
+        ),
+        (
+            "ignored processes",
+            list(&old.ignore_processes),
+            list(&new.ignore_processes),
+        ),
+        (
+            "ignored patterns",
+            list(&old.get_ignore_patterns()),
+            list(&new.get_ignore_patterns()),
+        ),
+        (
+            "ignored groups",
+            list(&old.ignore_groups),
+            list(&new.ignore_groups),
+        ),
+        (
+            "only groups",
+            list(&old.only_groups),
+            list(&new.only_groups),
+        ),
+        (
+            "smart filter",
+            on_off(old.smart_filter),
+            on_off(new.smart_filter),
+        ),
+        ("docker", on_off(old.docker), on_off(new.docker)),
+        (
+            "guarded ports",
+            list(&Some(old_guard)),
+            list(&Some(new_guard)),
+        ),
+        ("auto-resolve", on_off(old_resolve), on_off(new_resolve)),
+        (
+            "tray quick actions",
+            list(&Some(old.get_tray_quick_actions())),
+            list(&Some(new.get_tray_quick_actions())),
+        ),
+        (
+            "tray hotkeys",
+            list(&Some(hotkeys(old))),
+            list(&Some(hotkeys(new))),
+        ),
+    ];
+
+    let mut changes: Vec<String> = settings
+        .into_iter()
+        .filter(|(_, before, after)| before != after)
+        .map(|(name, before, after)| format!("{}: {} → {}", name, before, after))
+        .collect();
+    if old.smart_filter_rules != new.smart_filter_rules {
+        changes.push("smart filter rules updated".to_string());
+    }
+    if old.tray_icon_rules != new.tray_icon_rules {
+        changes.push("tray icon rules updated".to_string());
+    }
+    if old.tray_menu_layout != new.tray_menu_layout {
+        changes.push("tray menu layout updated".to_string());
+    }
+    if old.tray_pins != new.tray_pins {
+        changes.push("pinned ports updated".to_string());
+    }
+    changes
+}
+
+/// Rebuild the arguments after `changed` was saved. Returns the new arguments and what
+/// differs, or None when nothing relevant changed or the new config is invalid.
+fn reload_args(current: &Args, changed: &[PathBuf]) -> Option<(Args, Vec<String>)> {
+    match Args::reload() {
+        Ok(updated) => {
+            let changes = describe_changes(current, &updated);
+            (!changes.is_empty()).then_some((updated, changes))
+        }
+        Err(e) => {
+            warn!("Ignoring config change ({}): {}", changed[0].display(), e);
+            None
+        }
+    }
+}
+
+fn log_changes(changes: &[String]) {
+    for change in changes {
+        info!("🔄 Applied config change: {}", change);
+    }
+}
+
+/// Apply config, preset and reservation file changes to a running monitor and guard
+/// until the program exits. Does nothing when `--no-reload` was given.
+pub fn spawn_config_reloader(
+    args: Args,
+    monitor: Option<MonitorSettingsHandle>,
+    guard: Option<Arc<PortGuardDaemon>>,
+) {
+    if args.no_reload {
+        return;
+    }
+    let mut watcher = match ConfigWatcher::new(watched_files(&args)) {
+        Ok(watcher) => watcher,
+        Err(e) => {
+            warn!("Config changes will not be picked up: {}", e);
+            return;
+        }
+    };
+    let reservation_file = absolute(Path::new(&args.get_reservation_file_path()));
+
+    tokio::spawn(async move {
+        let mut current = args;
+        while let Some(changed) = watcher.changed().await {
+            if let Some(ref guard) = guard {
+                if changed.contains(&reservation_file) {
+                    match guard.reload_reservations().await {
+                        Ok(()) => info!(
+                            "🔄 Reloaded reservations from {}",
+                            reservation_file.display()
+                        ),
+                        Err(e) => warn!("Keeping previous reservations: {}", e),
+                    }
+                }
+            }
+            if changed.iter().all(|path| *path == reservation_file) {
+                continue;
+            }
+
+            let Some((updated, changes)) = reload_args(&current, &changed) else {
+                continue;
+            };
+            if let Some(ref monitor) = monitor {
+                match MonitorSettings::from_args(&updated) {
+                    Ok(settings) => {
+                        if let Ok(mut slot) = monitor.lock() {
+                            *slot = Some(settings);
+                        }
+                    }
+                    Err(e) => {
+                        warn!("Ignoring config change ({}): {}", changed[0].display(), e);
+                        continue;
+                    }
+                }
+            }
+            if let Some(ref guard) = guard {
+                let (ports, auto_resolve) = guard_settings(&updated);
+                guard.update_settings(ports, auto_resolve).await;
+            }
+            log_changes(&changes);
+            current = updated;
+        }
+    });
+}
+
+/// Hand updated arguments to the tray app through `slot`, which it checks on every
+/// pass of its event loop. Runs on its own thread since the tray has no async runtime.
+pub fn spawn_tray_reloader(args: Args, slot: Arc<std::sync::Mutex<Option<Args>>>) {
+    if args.no_reload {
+        return;
+    }
+    std::thread::spawn(move || {
+        let runtime = match tokio::runtime::Runtime::new() {
+            Ok(runtime) => runtime,
+            Err(e) => {
+                warn!("Config changes will not be picked up: {}", e);
+                return;
+            }
+        };
+        runtime.block_on(async move {
+            let mut watcher = match ConfigWatcher::new(watched_files(&args)) {
+                Ok(watcher) => watcher,
+                Err(e) => {
+                    warn!("Config changes will not be picked up: {}", e);
+                    return;
+                }
+            };
+            let mut current = args;
+            while let Some(changed) = watcher.changed().await {
+                let Some((updated, changes)) = reload_args(&current, &changed) else {
+                    continue;
+                };
+                if let Ok(mut slot) = slot.lock() {
+                    *slot = Some(updated.clone());
+                }
+                log_changes(&changes);
+                current = updated;
+            }
+        });
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::Parser;
+
+    #[test]
+    fn test_describe_changes() {
+        let old = Args::parse_from(["port-kill", "--ports", "3000", "--guard-mode"]);
+        let mut new = old.clone();
+        assert!(describe_changes(&old, &new).is_empty());
+
+        new.ports = Some(vec!["3000".to_string(), "4000".to_string()]);
+        new.ignore_processes = Some(vec!["Chrome".to_string()]);
+        new.guard_ports = "3000".to_string();
+        new.auto_resolve = true;
+        let changes = describe_changes(&old, &new);
+        assert_eq!(changes.len(), 4, "{:?}", changes);
+        assert!(changes.contains(&"ignored processes: none → Chrome".to_string()));
+        assert!(changes.contains(&"auto-resolve: off → on".to_string()));
+        assert_eq!(changes[0], "ports: 3000 → 3000, 4000");
+    }
+
+    #[tokio::test]
+    async fn test_watcher_reports_replaced_files() {
+        let dir = std::env::temp_dir().join(format!(
+            "port-kill-reload-{}",
+            std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .unwrap()
+                .as_nanos()
+        ));
+        std::fs::create_dir_all(&dir).unwrap();
+        let config = dir.join("config.toml");
+        let mut watcher = ConfigWatcher::new(vec![config.clone()]).unwrap();
+
+        // Unrelated files in the same directory are not reported
+        std::fs::write(dir.join("other.txt"), "x").unwrap();
+        let tmp = dir.join("config.toml.tmp");
+        std::fs::write(&tmp, "smart_filter = true\n").unwrap();
+        std::fs::rename(&tmp, &config).unwrap();
+
+        let changed = tokio::time::timeout(Duration::from_secs(5), watcher.changed())
+            .await
+            .expect("no change reported");
+        assert_eq!(changed, Some(vec![config]));
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
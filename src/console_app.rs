@@ -1,24 +1,40 @@
 use crate::{
+    anomaly::detect_anomalies,
     cli::Args,
+    config_reload::{spawn_config_reloader, ConfigWatcher},
     endpoint_monitor::EndpointMonitor,
+    history_store::HistoryStore,
+    port_activity::PortActivityLog,
     port_guard::PortGuardDaemon,
     process_monitor::ProcessMonitor,
+    restart_manager::{RestartRecipes, RestartVersion},
+    scan_interval::AdaptiveInterval,
     security_audit::SecurityAuditor,
     smart_filter::SmartFilter,
-    types::{GuardStatus, ProcessUpdate, SecurityAuditResult, StatusBarInfo},
+    system_monitor::ResourceHistory,
+    types::{
+        GuardStatus, HistoryFilter, ProcessHistory, ProcessUpdate, SecurityAuditResult,
+        StatusBarInfo,
+    },
 };
 use anyhow::Result;
 use crossbeam_channel::{bounded, Receiver};
-use log::{error, info};
+use log::{debug, error, info, warn};
 use std::collections::HashMap;
 use std::sync::Arc;
+use std::time::Duration;
 use tokio::sync::Mutex;
 
+/// How long `--start` waits for a service without a known port to open one
+const START_PORT_TIMEOUT: Duration = Duration::from_secs(15);
+
 pub struct ConsolePortKillApp {
     process_monitor: Arc<Mutex<ProcessMonitor>>,
     update_receiver: Receiver<ProcessUpdate>,
     args: Args,
     port_guard: Option<Arc<PortGuardDaemon>>,
+    /// CPU/memory samples per PID for the --performance graphs
+    resource_history: HashMap<i32, ResourceHistory>,
 }
 
 impl ConsolePortKillApp {
@@ -40,29 +56,36 @@ impl ConsolePortKillApp {
         let smart_filter = Self::create_smart_filter(&args)?;
 
         // Create process monitor with configurable ports
-        println!(
-            "DEBUG: Creating ProcessMonitor with verbose={}, performance={}",
+        // Logged rather than printed so `--json` output stays parseable
+        debug!(
+            "Creating ProcessMonitor with verbose={}, performance={}",
             args.verbose, args.performance
         );
-        let process_monitor = if let Some(filter) = smart_filter {
-            Arc::new(Mutex::new(ProcessMonitor::new_with_performance(
+        // The TUI always has CPU and memory columns
+        let performance = args.performance || args.tui;
+        let mut monitor = if let Some(filter) = smart_filter {
+            ProcessMonitor::new_with_performance(
                 update_sender,
                 args.get_ports_to_monitor(),
                 args.docker,
                 args.verbose,
                 Some(filter),
-                args.performance,
-            )?))
+                performance,
+            )?
         } else {
-            Arc::new(Mutex::new(ProcessMonitor::new_with_performance(
+            ProcessMonitor::new_with_performance(
                 update_sender,
                 args.get_ports_to_monitor(),
                 args.docker,
                 args.verbose,
                 None,
-                args.performance,
-            )?))
+                performance,
+            )?
         };
+        monitor.set_friendly_names(args.friendly_names);
+        monitor.set_udp(args.udp);
+        monitor.set_scan_interval(AdaptiveInterval::from_args(&args));
+        let process_monitor = Arc::new(Mutex::new(monitor));
 
         // Initialize Port Guard if enabled
         let port_guard = if args.guard_mode {
@@ -75,6 +98,7 @@ impl ConsolePortKillApp {
                 process_monitor.clone(),
             );
             daemon.set_process_interception(args.intercept_commands);
+            daemon.set_scan_interval(AdaptiveInterval::from_args(&args));
             daemon.set_auto_restart(args.guard_auto_restart);
             Some(Arc::new(daemon))
         } else {
@@ -86,6 +110,7 @@ impl ConsolePortKillApp {
             update_receiver,
             args,
             port_guard,
+            resource_history: HashMap::new(),
         })
     }
 
@@ -99,7 +124,7 @@ impl ConsolePortKillApp {
         let (update_sender, _update_receiver) = crossbeam_channel::bounded(100);
         let smart_filter = Self::create_smart_filter(&self.args)?;
 
-        if let Some(filter) = smart_filter {
+        let mut monitor = if let Some(filter) = smart_filter {
             ProcessMonitor::new_with_performance(
                 update_sender,
                 ports_to_scan,
@@ -107,7 +132,7 @@ impl ConsolePortKillApp {
                 self.args.verbose,
                 Some(filter),
                 self.args.performance,
-            )
+            )?
         } else {
             ProcessMonitor::new_with_performance(
                 update_sender,
@@ -116,44 +141,15 @@ impl ConsolePortKillApp {
                 self.args.verbose,
                 None,
                 self.args.performance,
-            )
-        }
+            )?
+        };
+        monitor.set_friendly_names(self.args.friendly_names);
+        monitor.set_udp(self.args.udp);
+        Ok(monitor)
     }
 
     fn create_smart_filter(args: &Args) -> Result<Option<SmartFilter>> {
-        // Get smart filter defaults
-        let (smart_ignore_ports, smart_ignore_processes, smart_ignore_groups) =
-            args.get_smart_filter_defaults();
-
-        // Combine user ignores with smart ignores
-        let mut ignore_ports = args.get_ignore_ports_set();
-        ignore_ports.extend(smart_ignore_ports);
-
-        let mut ignore_processes = args.get_ignore_processes_set();
-        ignore_processes.extend(smart_ignore_processes);
-
-        let mut ignore_groups = args.get_ignore_groups_set();
-        ignore_groups.extend(smart_ignore_groups);
-
-        // Check if any filtering is needed
-        if ignore_ports.is_empty()
-            && ignore_processes.is_empty()
-            && args.ignore_patterns.is_none()
-            && ignore_groups.is_empty()
-            && args.only_groups.is_none()
-        {
-            return Ok(None);
-        }
-
-        let filter = SmartFilter::new(
-            ignore_ports,
-            ignore_processes,
-            args.ignore_patterns.clone(),
-            ignore_groups,
-            args.get_only_groups_set(),
-        )?;
-
-        Ok(Some(filter))
+        SmartFilter::from_args(args)
     }
 
     pub async fn run(mut self) -> Result<()> {
@@ -162,18 +158,43 @@ impl ConsolePortKillApp {
         // One-shot: list current processes and exit
         if self.args.list {
             let ports_to_scan = Self::get_ports_to_scan(&self.args);
-            let mut temp_monitor = self.create_temp_monitor(ports_to_scan).await?;
-            let processes = temp_monitor.scan_processes().await?;
+            let from_daemon = if self.args.no_daemon {
+                None
+            } else {
+                crate::daemon::list_from_daemon(&ports_to_scan, self.args.udp).await
+            };
+            let (processes, source) = match from_daemon {
+                Some((mut processes, scanned_at)) => {
+                    // The daemon scanned with its own filters; apply ours on top
+                    if let Some(filter) = Self::create_smart_filter(&self.args)? {
+                        filter.filter_processes(&mut processes);
+                    }
+                    if self.args.friendly_names {
+                        crate::process_monitor::apply_friendly_names(&mut processes);
+                    }
+                    let age = (chrono::Utc::now() - scanned_at).num_seconds().max(0);
+                    (processes, format!("from the port-kill daemon, scanned {}s ago", age))
+                }
+                None => {
+                    let mut temp_monitor = self.create_temp_monitor(ports_to_scan).await?;
+                    (temp_monitor.scan_processes().await?, "one-time snapshot".to_string())
+                }
+            };
             if processes.is_empty() {
                 println!("ℹ️  No processes detected");
             } else {
-                println!("📋 Ports in use (one-time snapshot):");
+                println!("📋 Ports in use ({}):", source);
                 for (port, p) in &processes {
+                    let bind = p
+                        .bind_description()
+                        .map(|bind| format!(" on {}", bind))
+                        .unwrap_or_default();
                     println!(
-                        "  • Port {}: {} (PID {})",
+                        "  • Port {}: {} (PID {}){}",
                         port,
                         p.get_display_name(),
-                        p.pid
+                        p.pid,
+                        bind
                     );
                 }
             }
@@ -336,6 +357,7 @@ impl ConsolePortKillApp {
                 daemon.set_allowed_process_name(name.clone());
             }
             daemon.set_process_interception(self.args.intercept_commands);
+            daemon.set_scan_interval(AdaptiveInterval::from_args(&self.args));
             let guard = Arc::new(daemon);
             self.port_guard = Some(guard.clone());
             println!("🛡️  Guarding port {}. Press Ctrl+C to stop.", port);
@@ -347,11 +369,22 @@ impl ConsolePortKillApp {
             return self.output_processes_json().await;
         }
 
-        // Check if endpoint monitoring is enabled
-        if self.args.monitor_endpoint.is_some() {
+        // Check if endpoint monitoring or the Prometheus exporter is enabled
+        if self.args.monitor_endpoint.is_some() || self.args.prometheus_port.is_some() {
             return self.run_endpoint_monitoring().await;
         }
 
+        if self.args.tui || self.args.timeline.is_some() {
+            self.start_guard_and_reloader().await;
+            return crate::tui::run_tui(
+                self.process_monitor.clone(),
+                &self.args,
+                self.port_guard.clone(),
+                None,
+            )
+            .await;
+        }
+
         println!("🚀 Port Kill Console Monitor Started!");
         println!(
             "📡 Monitoring {} every 2 seconds...",
@@ -368,15 +401,7 @@ impl ConsolePortKillApp {
         println!("💡 Press Ctrl+C to quit");
         println!("");
 
-        // Start Port Guard in background (--guard or --guard-mode)
-        if let Some(guard) = &self.port_guard {
-            let guard_clone = guard.clone();
-            tokio::spawn(async move {
-                if let Err(e) = guard_clone.start().await {
-                    error!("Port Guard daemon failed: {}", e);
-                }
-            });
-        }
+        self.start_guard_and_reloader().await;
 
         // Start process monitoring in background
         let monitor = self.process_monitor.clone();
@@ -392,20 +417,81 @@ impl ConsolePortKillApp {
         Ok(())
     }
 
+    /// Start Port Guard in background (--guard or --guard-mode) and apply config, preset
+    /// and reservation edits while running
+    async fn start_guard_and_reloader(&self) {
+        if let Some(guard) = &self.port_guard {
+            let guard_clone = guard.clone();
+            tokio::spawn(async move {
+                if let Err(e) = guard_clone.start().await {
+                    error!("Port Guard daemon failed: {}", e);
+                }
+            });
+        }
+
+        let settings = self.process_monitor.lock().await.settings_handle();
+        spawn_config_reloader(self.args.clone(), Some(settings), self.port_guard.clone());
+    }
+
+    /// Add the latest CPU/memory sample of each process and forget processes that are gone
+    fn record_resource_history(
+        &mut self,
+        processes: &HashMap<u16, crate::types::ProcessInfo>,
+    ) {
+        for process_info in processes.values() {
+            self.resource_history
+                .entry(process_info.pid)
+                .or_default()
+                .record(process_info.cpu_usage, process_info.memory_usage);
+        }
+        let live: std::collections::HashSet<i32> = processes.values().map(|p| p.pid).collect();
+        self.resource_history.retain(|pid, _| live.contains(pid));
+    }
+
+    /// CPU and memory trend graphs for a process, once there are a few samples
+    fn resource_sparklines(&self, pid: i32) -> Vec<String> {
+        let Some(history) = self.resource_history.get(&pid) else {
+            return Vec::new();
+        };
+        let mut parts = Vec::new();
+        if let Some(cpu) = history.cpu_sparkline() {
+            parts.push(format!("CPU {}", cpu));
+        }
+        if let Some(memory) = history.memory_sparkline() {
+            parts.push(format!("RAM {}", memory));
+        }
+        parts
+    }
+
     /// Run endpoint monitoring mode
     async fn run_endpoint_monitoring(&mut self) -> Result<()> {
         println!("🚀 Port Kill Endpoint Monitor Started!");
-        println!(
-            "📡 Monitoring {} every {}s, sending to endpoint every {}s",
-            self.args.get_port_description(),
-            self.args.scan_interval,
-            self.args.send_interval
-        );
+        if self.args.monitor_endpoint.is_some() {
+            println!(
+                "📡 Monitoring {} every {}s, sending to endpoint every {}s",
+                self.args.get_port_description(),
+                self.args.scan_interval,
+                self.args.send_interval
+            );
+        } else {
+            println!(
+                "📡 Monitoring {} every {}s",
+                self.args.get_port_description(),
+                self.args.scan_interval
+            );
+        }
 
         if let Some(ref endpoint) = self.args.monitor_endpoint {
             println!("🌐 Endpoint: {}", endpoint);
         }
 
+        if let Some(port) = self.args.prometheus_port {
+            println!(
+                "📈 Prometheus metrics: http://{}/metrics",
+                std::net::SocketAddr::new(self.args.prometheus_addr, port)
+            );
+        }
+
         if self.args.endpoint_include_audit {
             println!("🔒 Security audit enabled");
         }
@@ -449,6 +535,9 @@ impl ConsolePortKillApp {
                 // Filter out ignored processes
                 let filtered_processes = self.filter_ignored_processes(&update.processes);
                 let filtered_count = filtered_processes.len();
+                if self.args.performance {
+                    self.record_resource_history(&update.processes);
+                }
 
                 // Update status
                 let status_info = StatusBarInfo::from_processes_with_status(&filtered_processes);
@@ -535,6 +624,7 @@ impl ConsolePortKillApp {
                                         memory_mb, memory_indicator
                                     ));
                                 }
+                                parts.extend(self.resource_sparklines(process_info.pid));
                             }
 
                             if self.args.show_pid {
@@ -584,6 +674,7 @@ impl ConsolePortKillApp {
                                         memory_mb, memory_indicator
                                     ));
                                 }
+                                parts.extend(self.resource_sparklines(process_info.pid));
                             }
 
                             if self.args.show_pid {
@@ -638,15 +729,42 @@ impl ConsolePortKillApp {
         filtered
     }
 
+    /// `message`, or what the history filter excluded when one is set
+    fn no_history_message(&self, message: &str) -> String {
+        let filter = self.args.history_filter();
+        if filter.is_empty() {
+            message.to_string()
+        } else {
+            format!("ℹ️  No kills match {}", filter)
+        }
+    }
+
+    /// ` — port 3000, since 2026-03-01` when the history views are filtered
+    fn history_filter_note(&self) -> String {
+        let filter = self.args.history_filter();
+        if filter.is_empty() {
+            String::new()
+        } else {
+            format!(" — {}", filter)
+        }
+    }
+
+    /// The kills `filter` selects, from the history database
+    fn load_history(&self, filter: &HistoryFilter) -> Result<ProcessHistory> {
+        HistoryStore::open_default()?.kills(filter)
+    }
+
     pub async fn display_history(&self) -> Result<()> {
-        let monitor = self.process_monitor.lock().await;
-        let history = monitor.get_history();
+        let history = self.load_history(&self.args.history_filter())?;
 
         if history.is_empty() {
             if self.args.json {
                 println!("[]");
             } else {
-                println!("📋 No process kill history found");
+                println!(
+                    "{}",
+                    self.no_history_message("📋 No process kill history found")
+                );
             }
             return Ok(());
         }
@@ -661,7 +779,11 @@ impl ConsolePortKillApp {
             }
         } else {
             // Output history in human-readable format
-            println!("📋 Process Kill History ({} entries):", history.len());
+            println!(
+                "📋 Process Kill History ({} entries{}):",
+                history.len(),
+                self.history_filter_note()
+            );
             println!("{}", "─".repeat(80));
 
             let recent_entries = history.get_recent_entries(20); // Show last 20 entries
@@ -696,8 +818,7 @@ impl ConsolePortKillApp {
     }
 
     pub async fn clear_history(&self) -> Result<()> {
-        let mut monitor = self.process_monitor.lock().await;
-        monitor.clear_history();
+        HistoryStore::open_default()?.clear_kills()?;
         println!("🗑️  Process kill history cleared");
         Ok(())
     }
@@ -1007,14 +1128,13 @@ impl ConsolePortKillApp {
     }
 
     pub async fn show_frequent_offenders(&self) -> Result<()> {
-        let monitor = self.process_monitor.lock().await;
-        let history = monitor.get_history();
+        let history = self.load_history(&self.args.history_filter())?;
 
         if history.is_empty() {
             if self.args.json {
                 println!("[]");
             } else {
-                println!("ℹ️  No history available. Start killing some processes to see frequent offenders!");
+                println!("{}", self.no_history_message("ℹ️  No history available. Start killing some processes to see frequent offenders!"));
             }
             return Ok(());
         }
@@ -1034,7 +1154,10 @@ impl ConsolePortKillApp {
             return Ok(());
         }
 
-        println!("🚨 Frequent Offenders (killed 2+ times):");
+        println!(
+            "🚨 Frequent Offenders (killed 2+ times{}):",
+            self.history_filter_note()
+        );
         println!("{}", "─".repeat(80));
 
         for (i, offender) in offenders.iter().enumerate() {
@@ -1081,19 +1204,21 @@ impl ConsolePortKillApp {
     }
 
     pub async fn show_time_patterns(&self) -> Result<()> {
-        let monitor = self.process_monitor.lock().await;
-        let history = monitor.get_history();
+        let history = self.load_history(&self.args.history_filter())?;
 
         if history.is_empty() {
             println!(
-                "ℹ️  No history available. Start killing some processes to see time patterns!"
+                "{}",
+                self.no_history_message(
+                    "ℹ️  No history available. Start killing some processes to see time patterns!"
+                )
             );
             return Ok(());
         }
 
         let patterns = history.get_time_patterns();
 
-        println!("📊 Time Patterns Analysis:");
+        println!("📊 Time Patterns Analysis{}:", self.history_filter_note());
         println!("{}", "─".repeat(50));
         println!("Total kills: {}", patterns.total_kills);
 
@@ -1129,9 +1254,19 @@ impl ConsolePortKillApp {
         Ok(())
     }
 
+    /// `--timeline --json`: when each port was taken and freed over the last `--timeline`
+    /// hours. Without `--json` the timeline is a tab of the TUI.
+    pub fn show_port_timeline_json(&self) -> Result<()> {
+        let hours = self.args.timeline.unwrap_or(24);
+        let until = chrono::Utc::now();
+        let since = until - chrono::Duration::hours(hours as i64);
+        let timelines = PortActivityLog::load().timeline(self.args.history_port, since, until);
+        println!("{}", serde_json::to_string(&timelines)?);
+        Ok(())
+    }
+
     pub async fn show_ignore_suggestions(&self) -> Result<()> {
-        let monitor = self.process_monitor.lock().await;
-        let history = monitor.get_history();
+        let history = self.load_history(&HistoryFilter::default())?;
 
         if history.is_empty() {
             if self.args.json {
@@ -1217,15 +1352,17 @@ impl ConsolePortKillApp {
     }
 
     pub async fn show_history_statistics(&self) -> Result<()> {
-        let monitor = self.process_monitor.lock().await;
-        let history = monitor.get_history();
+        let history = self.load_history(&self.args.history_filter())?;
 
         if history.is_empty() {
             if self.args.json {
                 println!("null");
             } else {
                 println!(
-                    "ℹ️  No history available. Start killing some processes to see statistics!"
+                    "{}",
+                    self.no_history_message(
+                        "ℹ️  No history available. Start killing some processes to see statistics!"
+                    )
                 );
             }
             return Ok(());
@@ -1239,7 +1376,7 @@ impl ConsolePortKillApp {
             return Ok(());
         }
 
-        println!("📊 History Statistics:");
+        println!("📊 History Statistics{}:", self.history_filter_note());
         println!("{}", "─".repeat(50));
         println!("Total kills: {}", stats.total_kills);
         println!("Unique processes: {}", stats.unique_processes);
@@ -1271,10 +1408,14 @@ impl ConsolePortKillApp {
     }
 
     pub async fn show_root_cause_analysis(&self) -> Result<()> {
-        let monitor = self.process_monitor.lock().await;
-        let history = monitor.get_history();
+        let history = self.load_history(&HistoryFilter::default())?;
+        let anomalies = detect_anomalies(
+            history.get_recent_entries(usize::MAX),
+            PortActivityLog::load().events(),
+            chrono::Utc::now(),
+        );
 
-        if history.is_empty() {
+        if history.is_empty() && anomalies.is_empty() {
             if self.args.json {
                 println!("null");
             } else {
@@ -1283,7 +1424,8 @@ impl ConsolePortKillApp {
             return Ok(());
         }
 
-        let analysis = history.get_root_cause_analysis();
+        let mut analysis = history.get_root_cause_analysis();
+        analysis.anomalies = anomalies;
 
         if self.args.json {
             // Output JSON for API consumption
@@ -1296,6 +1438,19 @@ impl ConsolePortKillApp {
         println!("{}", analysis.summary);
         println!();
 
+        if !analysis.anomalies.is_empty() {
+            println!("🚨 Anomalies:");
+            for (i, anomaly) in analysis.anomalies.iter().enumerate() {
+                println!(
+                    "{}. {} ({} ago)",
+                    i + 1,
+                    anomaly.description,
+                    format_time_ago(anomaly.at)
+                );
+            }
+            println!();
+        }
+
         // Show conflicts
         if !analysis.conflicts.is_empty() {
             println!("⚠️  Detected Conflicts:");
@@ -1346,7 +1501,8 @@ impl ConsolePortKillApp {
             }
         }
 
-        if analysis.conflicts.is_empty()
+        if analysis.anomalies.is_empty()
+            && analysis.conflicts.is_empty()
             && analysis.patterns.is_empty()
             && analysis.recommendations.is_empty()
         {
@@ -1617,6 +1773,9 @@ impl ConsolePortKillApp {
         println!("🔄 Restarting process on port {}...", port);
 
         let mut monitor = self.process_monitor.lock().await;
+        monitor
+            .get_restart_manager_mut()
+            .set_port_free_timeout(std::time::Duration::from_secs(self.args.restart_timeout));
         
         // Check if we have restart info for this port
         if !monitor.get_restart_manager().can_restart(port) {
@@ -1625,16 +1784,98 @@ impl ConsolePortKillApp {
             return Ok(());
         }
 
+        if self.args.pick {
+            let versions = monitor
+                .get_restart_manager()
+                .get_restart_info(port)
+                .map(|info| info.versions())
+                .unwrap_or_default();
+            match prompt_for_restart_version(port, &versions)? {
+                Some(index) => monitor.get_restart_manager_mut().select_version(port, index)?,
+                None => {
+                    println!("Cancelled");
+                    return Ok(());
+                }
+            }
+        }
+
+        let mut overrides = self.args.restart_overrides()?;
+
+        // Offer to bring the process back where its output was visible
+        let tmux_session = monitor
+            .get_restart_manager()
+            .get_restart_info(port)
+            .and_then(|info| info.terminal.as_ref())
+            .filter(|terminal| terminal.is_tmux())
+            .and_then(|terminal| terminal.tmux_session_name());
+        if let Some(session) = tmux_session {
+            if !self.args.tmux
+                && !self.args.no_tmux
+                && self.args.watch.is_empty()
+                && !self.args.keep_alive
+                && std::io::IsTerminal::is_terminal(&std::io::stdin())
+            {
+                overrides.in_tmux = prompt_yes_no(&format!(
+                    "Port {} was started in tmux session '{}'. Restart it in a new window there?",
+                    port, session
+                ))?;
+            }
+        }
+
         // Show what we're going to restart
         if let Some(restart_info) = monitor.get_restart_manager().get_restart_info(port) {
+            let restart_info = restart_info.with_overrides(&overrides);
             println!("   Command: {:?}", restart_info.command.join(" "));
             println!("   Working Directory: {}", restart_info.working_directory);
+            if let Some(container) = &restart_info.container {
+                println!("   Docker: {}", container.describe());
+            }
+            if let Some(user) = &restart_info.as_user {
+                println!("   Run As: {}", user);
+            }
+            if restart_info.clean_env {
+                println!("   Environment: clean (only PATH, HOME and similar essentials are kept)");
+            }
+            if overrides.in_tmux {
+                println!("   Opening in a new tmux window");
+            }
+            for (key, value) in &overrides.env {
+                println!("   Env override: {}={}", key, value);
+            }
+        }
+
+        if !self.args.watch.is_empty() {
+            println!(
+                "👀 Restarting port {} whenever files change in: {} (press Ctrl+C to stop)",
+                port,
+                self.args
+                    .watch
+                    .iter()
+                    .map(|p| p.display().to_string())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            );
+            return monitor
+                .watch_and_restart_port(port, &overrides, &self.args.watch)
+                .await;
+        }
+
+        if self.args.keep_alive {
+            println!("🛡️  Keeping port {} alive (press Ctrl+C to stop)", port);
+            return monitor.keep_alive_port(port, &overrides).await;
         }
 
         // Perform the restart
-        match monitor.restart_process_on_port(port).await {
+        match monitor.restart_process_on_port_with(port, &overrides).await {
             Ok(()) => {
                 println!("✅ Process on port {} restarted successfully", port);
+                if !overrides.in_tmux {
+                    println!(
+                        "   Output: {}",
+                        monitor.get_restart_manager().log_path(port).display()
+                    );
+                }
+                monitor.get_restart_manager_mut().wait_for_hooks();
             }
             Err(e) => {
                 println!("❌ Failed to restart process on port {}: {}", port, e);
@@ -1644,6 +1885,35 @@ impl ConsolePortKillApp {
         Ok(())
     }
 
+    /// Restart every port with a saved command and report the result per port. Returns the
+    /// exit code: 1 when a port failed to restart.
+    pub async fn restart_all(&self) -> Result<i32> {
+        let mut monitor = self.process_monitor.lock().await;
+        monitor
+            .get_restart_manager_mut()
+            .set_port_free_timeout(std::time::Duration::from_secs(self.args.restart_timeout));
+        let outcomes = monitor.restart_all_ports().await;
+        monitor.get_restart_manager_mut().wait_for_hooks();
+
+        if self.args.json {
+            println!("{}", serde_json::to_string_pretty(&outcomes)?);
+        } else if outcomes.is_empty() {
+            println!("ℹ️  No processes available for restart");
+            println!("💡 Tip: Kill processes with verbose mode (-v) to save restart information");
+        } else {
+            println!("🔄 Restarting {} port(s)...", outcomes.len());
+            for outcome in &outcomes {
+                match (&outcome.pid, &outcome.error) {
+                    (Some(pid), _) => println!("   ✅ Port {}: started (PID {})", outcome.port, pid),
+                    (None, Some(e)) => println!("   ❌ Port {}: {}", outcome.port, e),
+                    (None, None) => println!("   ❌ Port {}: not restarted", outcome.port),
+                }
+            }
+        }
+
+        Ok(if outcomes.iter().any(|o| o.error.is_some()) { 1 } else { 0 })
+    }
+
     /// Show restart history
     pub async fn show_restart_history(&self) -> Result<()> {
         let monitor = self.process_monitor.lock().await;
@@ -1659,22 +1929,84 @@ impl ConsolePortKillApp {
         println!("📋 RESTART HISTORY");
         println!("   {} port(s) can be restarted:", restartable_ports.len());
         println!();
+        let mut stale = 0;
 
         for port in restartable_ports {
             if let Some(restart_info) = restart_manager.get_restart_info(port) {
                 println!("   Port {}", port);
+                if let Some(reason) = restart_info.stale_reason() {
+                    stale += 1;
+                    println!("      ⚠️  Stale: {}", reason);
+                } else if let Some(days) = restart_info.unused_days() {
+                    println!("      ⏳ Not used in {} days", days);
+                }
                 println!("      Command: {}", restart_info.command.join(" "));
                 println!("      Working Dir: {}", restart_info.working_directory);
+                if let Some(pre) = &restart_info.pre {
+                    println!("      Pre-restart Hook: {}", pre);
+                }
+                if let Some(post) = &restart_info.post {
+                    println!("      Post-restart Hook: {}", post);
+                }
+                if let Some(user) = &restart_info.as_user {
+                    println!("      Run As: {}", user);
+                }
+                if let Some(container) = &restart_info.container {
+                    println!("      Docker: {}", container.describe());
+                }
+                if let Some(terminal) = &restart_info.terminal {
+                    println!("      Started In: {}", terminal.describe());
+                }
+                if let Some(pid) = restart_manager.running_pid(port) {
+                    println!(
+                        "      Running: PID {} (output: {})",
+                        pid,
+                        restart_manager.log_path(port).display()
+                    );
+                }
                 println!("      Last Restarted: {}", format_time_ago(restart_info.last_restarted));
+                if !restart_info.previous.is_empty() {
+                    println!(
+                        "      Earlier commands: {} (use --restart {} --pick)",
+                        restart_info.previous.len(),
+                        port
+                    );
+                }
                 println!();
             }
         }
 
         println!("💡 Use --restart <port> to restart a specific port");
+        if stale > 0 {
+            println!("💡 {} stale entr{} - use --prune-restarts to remove them", stale, if stale == 1 { "y" } else { "ies" });
+        }
         
         Ok(())
     }
 
+    /// Remove restart entries that can no longer be restarted as saved
+    pub async fn prune_restarts(&self) -> Result<()> {
+        let mut monitor = self.process_monitor.lock().await;
+        let pruned = monitor.get_restart_manager_mut().prune_stale()?;
+
+        if self.args.json {
+            let pruned: Vec<_> = pruned
+                .iter()
+                .map(|(port, reason)| serde_json::json!({ "port": port, "reason": reason }))
+                .collect();
+            println!("{}", serde_json::to_string_pretty(&pruned)?);
+        } else if pruned.is_empty() {
+            println!("✅ No stale restart entries");
+        } else {
+            println!("🧹 Removed {} stale restart entr{}:", pruned.len(), if pruned.len() == 1 { "y" } else { "ies" });
+            for (port, reason) in &pruned {
+                println!("   • Port {}: {}", port, reason);
+            }
+        }
+
+        Ok(())
+    }
+
     /// Clear restart history for a specific port
     pub async fn clear_restart_history(&self, port: u16) -> Result<()> {
         let mut monitor = self.process_monitor.lock().await;
@@ -1692,23 +2024,95 @@ impl ConsolePortKillApp {
         Ok(())
     }
 
+    /// Export saved restart commands as recipes. Working directories under the
+    /// current directory are stored relative to it.
+    pub async fn export_restarts(&self, path: &std::path::Path) -> Result<()> {
+        let monitor = self.process_monitor.lock().await;
+        let base = std::env::current_dir()?;
+        let recipes = monitor.get_restart_manager().export_recipes(&base);
+
+        if recipes.restarts.is_empty() {
+            println!("ℹ️  No restart commands saved yet, nothing to export");
+            return Ok(());
+        }
+
+        std::fs::write(path, serde_json::to_string_pretty(&recipes)?)?;
+        println!(
+            "✅ Exported {} restart recipe(s) to {}",
+            recipes.restarts.len(),
+            path.display()
+        );
+        for recipe in &recipes.restarts {
+            println!("   • Port {}: {}", recipe.port, recipe.command.join(" "));
+        }
+
+        Ok(())
+    }
+
+    /// Import restart recipes, resolving relative working directories against
+    /// the current directory
+    pub async fn import_restarts(&self, path: &std::path::Path) -> Result<()> {
+        let contents = std::fs::read_to_string(path)
+            .map_err(|e| anyhow::anyhow!("Failed to read {}: {}", path.display(), e))?;
+        let recipes: RestartRecipes = serde_json::from_str(&contents)
+            .map_err(|e| anyhow::anyhow!("Invalid restart recipes in {}: {}", path.display(), e))?;
+
+        let mut monitor = self.process_monitor.lock().await;
+        let base = std::env::current_dir()?;
+        let imported = monitor
+            .get_restart_manager_mut()
+            .import_recipes(&recipes, &base)?;
+
+        println!(
+            "✅ Imported {} restart recipe(s) from {}",
+            imported.len(),
+            path.display()
+        );
+        for port in &imported {
+            println!("   • Port {}", port);
+        }
+        if !imported.is_empty() {
+            println!("💡 Use --restart <port> or --restart-all to start them");
+        }
+
+        Ok(())
+    }
+
     /// Detect available services
     pub async fn detect_services(&self) -> Result<()> {
-        use crate::service_detector::ServiceDetector;
-
-        println!("🔍 Detecting available services...");
-        println!();
+        use crate::service_detector::{PortMap, ServiceDetector};
 
         let detector = ServiceDetector::new();
         let services = detector.discover_services()?;
+        let port_map = PortMap::from_services(&services);
+
+        if self.args.json {
+            crate::cache::output::print_or_json(
+                &serde_json::json!({
+                    "services": services,
+                    "projects": port_map.projects,
+                    "shared_ports": port_map.shared,
+                }),
+                true,
+            );
+            return Ok(());
+        }
+
+        println!("🔍 Detecting available services...");
+        println!();
 
         if services.is_empty() {
             println!("ℹ️  No services detected in current directory");
             println!("💡 Tip: Run this command from a project directory containing:");
-            println!("   • package.json (npm scripts)");
+            println!("   • package.json (npm scripts, workspace packages)");
             println!("   • docker-compose.yml (Docker services)");
             println!("   • Procfile (Procfile processes)");
-            println!("   • app.py/manage.py (Python apps)");
+            println!("   • app.py/manage.py (Python and Django apps)");
+            println!("   • Cargo.toml, go.mod (Rust binaries, Go main packages)");
+            println!("   • pom.xml/build.gradle (Spring Boot), Gemfile (Rails)");
+            println!("   • deno.json tasks, bun.lockb (Bun scripts)");
+            println!("   • Makefile, justfile (run/serve/dev targets)");
+            println!("   • .vscode/tasks.json (background and server tasks)");
             return Ok(());
         }
 
@@ -1725,41 +2129,185 @@ impl ConsolePortKillApp {
             println!();
         }
 
+        // Only worth a table when there is more than one project, as in a monorepo
+        if port_map.projects.len() > 1 {
+            println!("🗺️  PORTS BY PROJECT");
+            for project in &port_map.projects {
+                let ports = if project.ports.is_empty() {
+                    "no known port".to_string()
+                } else {
+                    project
+                        .ports
+                        .iter()
+                        .map(|port| port.to_string())
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                };
+                println!(
+                    "   {} ({}): {}",
+                    project.project,
+                    project.directory.display(),
+                    ports
+                );
+            }
+            for shared in &port_map.shared {
+                println!(
+                    "   ⚠️  Port {} is expected by {}; they can't run at the same time",
+                    shared.port,
+                    shared.projects.join(", ")
+                );
+            }
+            println!();
+        }
+
         println!("💡 Use --start <name> to start a service (e.g., --start npm:dev)");
 
         Ok(())
     }
 
-    /// Start a detected service
+    /// Start a detected service. A missing or ambiguous name opens a picker of the
+    /// detected services, and the service started is remembered for this directory.
     pub async fn start_service(&self, service_name: &str) -> Result<()> {
+        use crate::command_line::quote_command_line;
+        use crate::restart_manager::RestartManager;
         use crate::service_detector::ServiceDetector;
+        use crate::service_picker::{
+            pick_service, resolve_start_target, StartChoices, StartTarget,
+        };
 
-        println!("🚀 Starting service: {}...", service_name);
-
-        let detector = ServiceDetector::new();
+        let tmux_session = self.args.tmux.then(|| self.args.tmux_session_name());
+        let detector = ServiceDetector::new()
+            .load_project_env(self.args.load_env)
+            .in_tmux(tmux_session.clone());
         let services = detector.discover_services()?;
 
-        // Find the service
-        let service = services.iter().find(|s| s.name == service_name);
-
-        if service.is_none() {
-            println!("❌ Service '{}' not found", service_name);
-            println!("💡 Use --detect to list available services");
+        if services.is_empty() {
+            println!("❌ No services detected in the current directory");
+            println!("💡 Use --detect to see which project files are recognized");
             return Ok(());
         }
 
-        let service = service.unwrap();
+        let cwd = std::env::current_dir()?;
+        let mut choices = StartChoices::load();
+        let service = match resolve_start_target(&services, service_name) {
+            StartTarget::Found(index) => &services[index],
+            StartTarget::Ambiguous(candidates) => {
+                if !std::io::IsTerminal::is_terminal(&std::io::stdin()) {
+                    if service_name.is_empty() {
+                        println!("❌ --start needs a service name outside a terminal");
+                    } else if candidates.is_empty() {
+                        println!("❌ Service '{}' not found", service_name);
+                    } else {
+                        let names: Vec<&str> = candidates
+                            .iter()
+                            .map(|&index| services[index].name.as_str())
+                            .collect();
+                        println!(
+                            "❌ Service '{}' not found; did you mean {}?",
+                            service_name,
+                            names.join(" or ")
+                        );
+                    }
+                    println!("💡 Use --detect to list available services");
+                    return Ok(());
+                }
+
+                // Nothing matched: show every service rather than an empty list
+                let query = if candidates.is_empty() {
+                    ""
+                } else {
+                    service_name
+                };
+                match pick_service(&services, query, choices.get(&cwd))? {
+                    Some(index) => &services[index],
+                    None => {
+                        println!("No service started");
+                        return Ok(());
+                    }
+                }
+            }
+        };
 
+        // The port reserved for the git branch is the service's PORT unless --start-env sets one
+        let mut start_env = self.args.start_env.clone();
+        if !start_env.iter().any(|(key, _)| key == "PORT") {
+            if let Some(port) = crate::branch_ports::current_branch_port() {
+                println!("🔌 Using port {} reserved for this branch", port);
+                start_env.push(("PORT".to_string(), port.to_string()));
+            }
+        }
+
+        // A PORT given with --start-env is where the service will listen
+        let port = start_env
+            .iter()
+            .find(|(key, _)| key == "PORT")
+            .and_then(|(_, value)| value.parse::<u16>().ok())
+            .or(service.inferred_port);
+
+        println!("🚀 Starting service: {}...", service.name);
         println!("   Starting: {}", service.description);
-        if let Some(port) = service.inferred_port {
+        if let Some(port) = port {
             println!("   Expected Port: {}", port);
         }
         println!();
 
-        match detector.start_service(service) {
-            Ok(pid) => {
-                println!("✅ Service started successfully with PID {}", pid);
-                println!("💡 The service is now running in the background");
+        match detector.start_service_with(service, &self.args.restart_args, &start_env) {
+            Ok(started) => {
+                println!("✅ Service started successfully with PID {}", started.pid);
+                println!("   Command: {}", quote_command_line(&started.command));
+                if !started.project_env.is_empty() {
+                    println!(
+                        "   Environment: {} variables from {}",
+                        started.project_env.vars.len(),
+                        started.project_env.sources.join(" and ")
+                    );
+                }
+                match tmux_session {
+                    Some(ref session) => println!(
+                        "💡 The service is running in tmux session '{}' (tmux attach -t {})",
+                        session, session
+                    ),
+                    None => println!("💡 The service is now running in the background"),
+                }
+                choices.remember(&cwd, &service.name);
+                if let Err(e) = choices.save() {
+                    warn!("Could not remember the service for this directory: {}", e);
+                }
+
+                // Without a known port, see which one the service opens
+                let port = port.or_else(|| {
+                    println!("⏳ Waiting for the service to open a port...");
+                    crate::process_monitor::wait_for_listening_port(
+                        started.pid,
+                        START_PORT_TIMEOUT,
+                    )
+                    .inspect(|port| println!("   Listening on port {}", port))
+                });
+
+                // Keep the exact command so --restart <port> can run it again, even if
+                // the service is never killed through port-kill
+                if let Some(port) = port {
+                    let working_directory = cwd.join(&started.working_directory);
+                    let working_directory = working_directory
+                        .canonicalize()
+                        .unwrap_or(working_directory);
+                    // The environment the service got, since the restart may run outside
+                    // the project shell
+                    let saved = RestartManager::new().and_then(|mut manager| {
+                        manager.save_started_command(
+                            port,
+                            started.command.clone(),
+                            &working_directory.to_string_lossy(),
+                            started.resolved_env(&start_env),
+                        )
+                    });
+                    match saved {
+                        Ok(()) => {
+                            println!("💡 Restart it later with: port-kill --restart {}", port)
+                        }
+                        Err(e) => warn!("Could not save the restart command: {}", e),
+                    }
+                }
             }
             Err(e) => {
                 println!("❌ Failed to start service: {}", e);
@@ -1771,7 +2319,8 @@ impl ConsolePortKillApp {
 
     /// Initialize a sample configuration file
     pub async fn init_config(&self) -> Result<()> {
-        use crate::orchestrator::create_sample_config;
+        use crate::orchestrator::{create_detected_config, create_sample_config};
+        use crate::service_detector::ServiceDetector;
         use std::path::Path;
 
         let config_path = Path::new(&self.args.config_file);
@@ -1782,8 +2331,19 @@ impl ConsolePortKillApp {
             return Ok(());
         }
 
-        create_sample_config(config_path)?;
-        println!("✅ Created sample configuration file: {}", config_path.display());
+        // Start from the services in this directory when there are any
+        let detected = ServiceDetector::new().discover_services().unwrap_or_default();
+        if detected.is_empty() {
+            create_sample_config(config_path)?;
+            println!("✅ Created sample configuration file: {}", config_path.display());
+        } else {
+            create_detected_config(config_path, &detected)?;
+            println!(
+                "✅ Created {} with {} detected service(s)",
+                config_path.display(),
+                detected.len()
+            );
+        }
         println!();
         println!("📝 Edit the file to configure your services, then run:");
         println!("   port-kill --up     # Start all services");
@@ -1796,9 +2356,8 @@ impl ConsolePortKillApp {
     /// Start all services from config
     pub async fn orchestrate_up(&self) -> Result<()> {
         use crate::orchestrator::Orchestrator;
-        use std::path::Path;
 
-        let config_path = Path::new(&self.args.config_file);
+        let config_path = &self.args.get_config_file_path();
 
         if !config_path.exists() {
             println!("❌ Configuration file not found: {}", config_path.display());
@@ -1810,6 +2369,14 @@ impl ConsolePortKillApp {
         println!();
 
         let mut orchestrator = Orchestrator::load(config_path)?;
+        orchestrator.load_project_env(self.args.load_env);
+        if self.args.tmux {
+            orchestrator.run_in_tmux(self.args.tmux_session_name());
+        }
+        // Service output would draw over the --manage TUI, so it goes to log files
+        if self.args.manage {
+            orchestrator.capture_output(crate::tui::service_log_dir());
+        }
 
         match orchestrator.start_all().await {
             Ok(()) => {
@@ -1822,18 +2389,63 @@ impl ConsolePortKillApp {
                 println!("❌ Failed to start services: {}", e);
                 // Try to stop any services that were started
                 let _ = orchestrator.stop_all().await;
+                return Ok(());
             }
         }
 
+        if self.args.manage {
+            crate::tui::run_tui(
+                self.process_monitor.clone(),
+                &self.args,
+                self.port_guard.clone(),
+                Some(orchestrator),
+            )
+            .await?;
+        } else if self.args.watch_config {
+            self.watch_orchestrator_config(&mut orchestrator, config_path).await?;
+        }
+
         Ok(())
     }
 
+    /// Keep services in line with the config file until Ctrl+C, then stop them
+    async fn watch_orchestrator_config(
+        &self,
+        orchestrator: &mut crate::orchestrator::Orchestrator,
+        config_path: &std::path::Path,
+    ) -> Result<()> {
+        let mut watcher = ConfigWatcher::new(vec![config_path.to_path_buf()])?;
+        println!("👀 Watching {} for changes. Press Ctrl+C to stop all services.", config_path.display());
+
+        loop {
+            tokio::select! {
+                changed = watcher.changed() => {
+                    if changed.is_none() {
+                        break;
+                    }
+                    match orchestrator.reload().await {
+                        Ok(changes) => {
+                            for change in &changes {
+                                info!("🔄 Applied config change: {}", change);
+                            }
+                        }
+                        Err(e) => warn!("Ignoring config change: {}", e),
+                    }
+                }
+                _ = tokio::signal::ctrl_c() => break,
+            }
+        }
+
+        println!();
+        println!("🛑 Stopping services...");
+        orchestrator.stop_all().await
+    }
+
     /// Stop all services from config
     pub async fn orchestrate_down(&self) -> Result<()> {
         use crate::orchestrator::Orchestrator;
-        use std::path::Path;
 
-        let config_path = Path::new(&self.args.config_file);
+        let config_path = &self.args.get_config_file_path();
 
         if !config_path.exists() {
             println!("❌ Configuration file not found: {}", config_path.display());
@@ -1860,9 +2472,8 @@ impl ConsolePortKillApp {
     /// Restart a specific service from config
     pub async fn orchestrate_restart(&self, service_name: &str) -> Result<()> {
         use crate::orchestrator::Orchestrator;
-        use std::path::Path;
 
-        let config_path = Path::new(&self.args.config_file);
+        let config_path = &self.args.get_config_file_path();
 
         if !config_path.exists() {
             println!("❌ Configuration file not found: {}", config_path.display());
@@ -1872,6 +2483,10 @@ impl ConsolePortKillApp {
         println!("🔄 Restarting service '{}'...", service_name);
 
         let mut orchestrator = Orchestrator::load(config_path)?;
+        orchestrator.load_project_env(self.args.load_env);
+        if self.args.tmux {
+            orchestrator.run_in_tmux(self.args.tmux_session_name());
+        }
 
         match orchestrator.restart_service(service_name).await {
             Ok(()) => {
@@ -1888,9 +2503,8 @@ impl ConsolePortKillApp {
     /// Show status of all configured services
     pub async fn orchestrate_status(&self) -> Result<()> {
         use crate::orchestrator::Orchestrator;
-        use std::path::Path;
 
-        let config_path = Path::new(&self.args.config_file);
+        let config_path = &self.args.get_config_file_path();
 
         if !config_path.exists() {
             println!("❌ Configuration file not found: {}", config_path.display());
@@ -1939,6 +2553,48 @@ impl ConsolePortKillApp {
     }
 }
 
+/// Ask a yes/no question on stdin, defaulting to yes
+fn prompt_yes_no(question: &str) -> Result<bool> {
+    use std::io::Write;
+
+    print!("{} [Y/n] ", question);
+    std::io::stdout().flush()?;
+
+    let mut input = String::new();
+    std::io::stdin().read_line(&mut input)?;
+    Ok(!matches!(input.trim().to_lowercase().as_str(), "n" | "no"))
+}
+
+/// List the remembered commands for a port and read which one to restart.
+/// Returns the chosen index, or None when the user cancels with an empty answer.
+fn prompt_for_restart_version(port: u16, versions: &[RestartVersion]) -> Result<Option<usize>> {
+    use std::io::Write;
+
+    println!("Saved commands for port {}:", port);
+    for (i, version) in versions.iter().enumerate() {
+        println!(
+            "  [{}] {} ({} ago)",
+            i + 1,
+            version.command.join(" "),
+            format_time_ago(version.last_used)
+        );
+        println!("      in {}", version.working_directory);
+    }
+    print!("Select a command to restart (empty to cancel): ");
+    std::io::stdout().flush()?;
+
+    let mut input = String::new();
+    std::io::stdin().read_line(&mut input)?;
+    let input = input.trim();
+    if input.is_empty() {
+        return Ok(None);
+    }
+    match input.parse::<usize>() {
+        Ok(n) if (1..=versions.len()).contains(&n) => Ok(Some(n - 1)),
+        _ => Err(anyhow::anyhow!("Invalid selection '{}'", input)),
+    }
+}
+
 fn format_time_ago(time: chrono::DateTime<chrono::Utc>) -> String {
     let now = chrono::Utc::now();
     let duration = now.signed_duration_since(time);
@@ -0,0 +1,849 @@
+use crate::cli::{Args, CtlArgs, CtlCommand, DaemonArgs};
+use crate::machine::{self, params, required_params, RpcError, METHOD_NOT_FOUND};
+use crate::orchestrator::Orchestrator;
+use crate::port_guard::PortGuardDaemon;
+use crate::process_monitor::ProcessMonitor;
+use crate::restart_manager::RestartManager;
+use crate::scan_interval::AdaptiveInterval;
+use crate::types::ProcessInfo;
+use crate::user_config::UserConfig;
+use anyhow::{anyhow, Context, Result};
+use chrono::{DateTime, Local, Utc};
+use tracing::{debug, info, warn};
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::collections::{HashMap, HashSet};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::io::{AsyncBufReadExt, AsyncRead, AsyncWrite, AsyncWriteExt, BufReader, Lines};
+use tokio::sync::{Mutex, Notify};
+
+/// How long `--list`, the TUI and the tray wait for a daemon before scanning by themselves
+const DAEMON_LIST_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// Where the daemon listens when no `--socket` is given
+pub fn default_socket() -> String {
+    #[cfg(windows)]
+    {
+        let user = std::env::var("USERNAME").unwrap_or_else(|_| "default".to_string());
+        format!(r"\\.\pipe\port-kill-daemon-{}", user)
+    }
+    #[cfg(not(windows))]
+    {
+        UserConfig::default_path()
+            .with_file_name("daemon.sock")
+            .to_string_lossy()
+            .into_owned()
+    }
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct ServiceParams {
+    service: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct RestartParams {
+    service: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct PortParams {
+    port: u16,
+}
+
+/// The daemon's latest scan of its ports
+struct Snapshot {
+    processes: HashMap<u16, ProcessInfo>,
+    scanned_at: DateTime<Utc>,
+}
+
+/// State shared by the connections to `port-kill daemon`
+pub struct Daemon {
+    args: Args,
+    socket: String,
+    started_at: DateTime<Utc>,
+    clients: AtomicUsize,
+    guard: Option<Arc<PortGuardDaemon>>,
+    /// Scans the monitored ports in the background so `list` answers from memory
+    monitor: Mutex<ProcessMonitor>,
+    ports: HashSet<u16>,
+    snapshot: Mutex<Option<Snapshot>>,
+    /// Set by `list`: someone is looking, so keep scanning at `--scan-interval`
+    queried: AtomicBool,
+    /// Scan right away, e.g. after a kill
+    rescan: Notify,
+    /// Services started through the daemon. They keep running between `ctl` calls
+    /// and are stopped with the daemon.
+    services: Mutex<Option<Orchestrator>>,
+    stop: Notify,
+}
+
+impl Daemon {
+    /// The daemon's own methods; everything else is answered by machine mode
+    pub(crate) async fn call(&self, method: &str, params_value: Value) -> Result<Value, RpcError> {
+        match method {
+            "daemon.status" => {
+                let guard = match &self.guard {
+                    Some(guard) => Some(guard.get_status().await),
+                    None => None,
+                };
+                Ok(json!({
+                    "pid": std::process::id(),
+                    "startedAt": self.started_at,
+                    "uptimeSecs": (Utc::now() - self.started_at).num_seconds(),
+                    "socket": self.socket,
+                    "clients": self.clients.load(Ordering::SeqCst),
+                    "ports": self.ports.len(),
+                    "scannedAt": self.snapshot.lock().await.as_ref().map(|snapshot| snapshot.scanned_at),
+                    "guard": guard,
+                    "services": self.service_statuses().await,
+                }))
+            }
+            "restart" => {
+                let PortParams { port } = required_params(params_value)?;
+                let pid = self.restart_port(port).await.map_err(RpcError::failed)?;
+                Ok(json!({ "port": port, "pid": pid }))
+            }
+            "daemon.stop" => {
+                self.stop.notify_one();
+                Ok(Value::Null)
+            }
+            // A client killed or restarted something itself
+            "daemon.rescan" => {
+                self.ports_changed().await;
+                Ok(Value::Null)
+            }
+            "services.up" => {
+                let ServiceParams { service } = params(params_value)?;
+                let mut services = self.services.lock().await;
+                if services.is_none() {
+                    *services = Some(self.load_services().map_err(RpcError::failed)?);
+                }
+                let orchestrator = services.as_mut().expect("services were just loaded");
+                match service {
+                    Some(name) => orchestrator.start_service(&name).await,
+                    None => orchestrator.start_all().await,
+                }
+                .map_err(RpcError::failed)?;
+                drop(services);
+                Ok(json!({ "services": self.service_statuses().await }))
+            }
+            "services.down" => {
+                let ServiceParams { service } = params(params_value)?;
+                if let Some(orchestrator) = self.services.lock().await.as_mut() {
+                    match service {
+                        Some(name) => orchestrator.stop_service(&name).await,
+                        None => orchestrator.stop_all().await,
+                    }
+                    .map_err(RpcError::failed)?;
+                }
+                Ok(json!({ "services": self.service_statuses().await }))
+            }
+            "services.restart" => {
+                let RestartParams { service } = required_params(params_value)?;
+                let mut services = self.services.lock().await;
+                if services.is_none() {
+                    *services = Some(self.load_services().map_err(RpcError::failed)?);
+                }
+                let orchestrator = services.as_mut().expect("services were just loaded");
+                orchestrator
+                    .restart_service(&service)
+                    .await
+                    .map_err(RpcError::failed)?;
+                drop(services);
+                Ok(json!({ "services": self.service_statuses().await }))
+            }
+            "services.status" => Ok(json!({ "services": self.service_statuses().await })),
+            _ => Err(RpcError::new(
+                METHOD_NOT_FOUND,
+                format!("Method '{}' not found", method),
+            )),
+        }
+    }
+
+    /// Processes on `ports` (all the monitored ones when None) from the last background
+    /// scan. None when some of the ports aren't monitored or nothing was scanned yet.
+    pub(crate) async fn cached_processes(
+        &self,
+        ports: Option<&[u16]>,
+    ) -> Option<(HashMap<u16, ProcessInfo>, DateTime<Utc>)> {
+        self.queried.store(true, Ordering::SeqCst);
+        if ports.is_some_and(|ports| !ports.iter().all(|port| self.ports.contains(port))) {
+            return None;
+        }
+        let snapshot = self.snapshot.lock().await;
+        let snapshot = snapshot.as_ref()?;
+        let processes = snapshot
+            .processes
+            .iter()
+            .filter(|(port, _)| ports.is_none_or(|ports| ports.contains(port)))
+            .map(|(&port, process)| (port, process.clone()))
+            .collect();
+        Some((processes, snapshot.scanned_at))
+    }
+
+    /// Forget the last scan and take a new one, after something on the ports changed
+    pub(crate) async fn ports_changed(&self) {
+        *self.snapshot.lock().await = None;
+        self.rescan.notify_one();
+    }
+
+    /// Kill what listens on `port` and launch its saved command, like `--restart`.
+    /// Returns the new PID when it is still running.
+    async fn restart_port(&self, port: u16) -> Result<Option<u32>> {
+        let mut monitor = self.monitor.lock().await;
+        // Commands saved by other port-kill runs since the daemon started
+        let mut manager = RestartManager::new()?;
+        manager.set_port_free_timeout(Duration::from_secs(self.args.restart_timeout));
+        if !manager.can_restart(port) {
+            return Err(anyhow!(
+                "No restart information for port {} (kill its process once to save it)",
+                port
+            ));
+        }
+        *monitor.get_restart_manager_mut() = manager;
+        let restarted = monitor.restart_process_on_port(port).await;
+        drop(monitor);
+        self.ports_changed().await;
+        restarted?;
+        Ok(self.monitor.lock().await.get_restart_manager().running_pid(port))
+    }
+
+    /// The config's services, set up like `--up --manage`: output goes to log files
+    /// since the daemon has no terminal to show it in
+    fn load_services(&self) -> Result<Orchestrator> {
+        let config_path = self.args.get_config_file_path();
+        if !config_path.exists() {
+            return Err(anyhow!(
+                "Configuration file not found: {} (create one with port-kill --init-config)",
+                config_path.display()
+            ));
+        }
+        let mut orchestrator = Orchestrator::load(&config_path)?;
+        orchestrator.load_project_env(self.args.load_env);
+        if self.args.tmux {
+            orchestrator.run_in_tmux(self.args.tmux_session_name());
+        }
+        orchestrator.capture_output(UserConfig::default_path().with_file_name("logs"));
+        Ok(orchestrator)
+    }
+
+    async fn service_statuses(&self) -> Value {
+        match self.services.lock().await.as_mut() {
+            Some(orchestrator) => {
+                for name in orchestrator.reap_exited() {
+                    warn!(service = %name, "Service '{}' exited", name);
+                }
+                json!(orchestrator.get_status())
+            }
+            None => json!([]),
+        }
+    }
+
+    /// Stop what the daemon started before it exits
+    async fn shut_down(&self) {
+        if let Some(orchestrator) = self.services.lock().await.as_mut() {
+            if let Err(e) = orchestrator.stop_all().await {
+                warn!(error = %e, "Failed to stop services: {}", e);
+            }
+        }
+        if let Some(guard) = &self.guard {
+            if let Err(e) = guard.stop().await {
+                warn!(error = %e, "Failed to stop Port Guard: {}", e);
+            }
+        }
+    }
+}
+
+/// A monitor of the monitored ports, with the filters of the command line
+fn build_monitor(args: &Args) -> Result<ProcessMonitor> {
+    let (update_sender, _update_receiver) = crossbeam_channel::bounded(100);
+    let mut monitor = ProcessMonitor::new_with_performance(
+        update_sender,
+        args.get_ports_to_monitor(),
+        args.docker,
+        args.verbose,
+        crate::smart_filter::SmartFilter::from_args(args)?,
+        args.performance,
+    )?;
+    monitor.set_friendly_names(args.friendly_names);
+    monitor.set_udp(args.udp);
+    Ok(monitor)
+}
+
+/// Port Guard on the guard ports, as `--guard-mode` sets it up
+fn start_guard(args: &Args) -> Result<Arc<PortGuardDaemon>> {
+    let monitor = build_monitor(args)?;
+    let mut guard = PortGuardDaemon::new(
+        args.get_guard_ports(),
+        args.get_reservation_file_path(),
+        args.auto_resolve,
+        Arc::new(Mutex::new(monitor)),
+    );
+    guard.set_process_interception(args.intercept_commands);
+    guard.set_scan_interval(AdaptiveInterval::from_args(args));
+    guard.set_auto_restart(args.guard_auto_restart);
+    let guard = Arc::new(guard);
+    let running = guard.clone();
+    tokio::spawn(async move {
+        if let Err(e) = running.start().await {
+            warn!(error = %e, "Port Guard stopped: {}", e);
+        }
+    });
+    Ok(guard)
+}
+
+/// Keep the snapshot `list` answers from up to date. Backs off like the other
+/// monitoring loops while the ports stay the same and nobody asks.
+async fn scan_ports(daemon: Arc<Daemon>) {
+    let mut interval = AdaptiveInterval::from_args(&daemon.args);
+    loop {
+        let scanned = daemon.monitor.lock().await.scan_processes().await;
+        let mut changed = daemon.queried.swap(false, Ordering::SeqCst);
+        match scanned {
+            Ok(processes) => {
+                let mut snapshot = daemon.snapshot.lock().await;
+                changed |= snapshot
+                    .as_ref()
+                    .is_none_or(|previous| previous.processes != processes);
+                *snapshot = Some(Snapshot {
+                    processes,
+                    scanned_at: Utc::now(),
+                });
+            }
+            Err(e) => warn!(error = %e, "Daemon scan failed: {}", e),
+        }
+        tokio::select! {
+            _ = tokio::time::sleep(interval.record(changed)) => {}
+            _ = daemon.rescan.notified() => {
+                interval.record(true);
+            }
+        }
+    }
+}
+
+/// Serve one client, counting it while it is connected
+fn spawn_client<R, W>(daemon: &Arc<Daemon>, input: R, output: W)
+where
+    R: AsyncRead + Unpin + Send + 'static,
+    W: AsyncWrite + Unpin + Send + 'static,
+{
+    let daemon = daemon.clone();
+    tokio::spawn(async move {
+        let clients = daemon.clients.fetch_add(1, Ordering::SeqCst) + 1;
+        debug!(clients, "Daemon client connected");
+        if let Err(e) = machine::serve(daemon.args.clone(), input, output, Some(daemon.clone())).await {
+            warn!(error = %e, "Daemon client failed: {}", e);
+        }
+        let clients = daemon.clients.fetch_sub(1, Ordering::SeqCst) - 1;
+        debug!(clients, "Daemon client disconnected");
+    });
+}
+
+/// `port-kill daemon`: scan, guard and run services in one process, answering the
+/// machine-mode JSON-RPC protocol (plus `daemon.*` and `services.*`) on a Unix
+/// socket or named pipe until `port-kill ctl stop` or Ctrl+C
+pub async fn run_daemon(args: &Args, daemon_args: &DaemonArgs) -> Result<()> {
+    let socket = daemon_args.socket.clone().unwrap_or_else(default_socket);
+    if connect(&socket).await.is_ok() {
+        return Err(anyhow!("A port-kill daemon is already running on {}", socket));
+    }
+
+    let guard = if args.guard_mode {
+        Some(start_guard(args)?)
+    } else {
+        None
+    };
+    let daemon = Arc::new(Daemon {
+        args: args.clone(),
+        socket: socket.clone(),
+        started_at: Utc::now(),
+        clients: AtomicUsize::new(0),
+        guard,
+        monitor: Mutex::new(build_monitor(args)?),
+        ports: args.get_ports_to_monitor().into_iter().collect(),
+        snapshot: Mutex::new(None),
+        queried: AtomicBool::new(false),
+        rescan: Notify::new(),
+        services: Mutex::new(None),
+        stop: Notify::new(),
+    });
+    let scanner = tokio::spawn(scan_ports(daemon.clone()));
+
+    if daemon_args.up {
+        if let Err(e) = daemon.call("services.up", Value::Null).await {
+            scanner.abort();
+            daemon.shut_down().await;
+            return Err(anyhow!("Failed to start services: {}", e.message()));
+        }
+    }
+
+    let served = listen(&daemon, &socket).await;
+    scanner.abort();
+    daemon.shut_down().await;
+    served?;
+    println!("🛑 port-kill daemon stopped");
+    Ok(())
+}
+
+#[cfg(unix)]
+async fn listen(daemon: &Arc<Daemon>, socket: &str) -> Result<()> {
+    use nix::sys::stat::{umask, Mode};
+    use std::os::unix::fs::DirBuilderExt;
+    use tokio::net::UnixListener;
+
+    let path = std::path::Path::new(socket);
+    // Nothing answered on it, so it is left over from a daemon that did not exit cleanly
+    if path.exists() {
+        std::fs::remove_file(path)?;
+    }
+    if let Some(parent) = path.parent() {
+        std::fs::DirBuilder::new().recursive(true).mode(0o700).create(parent)?;
+    }
+    // Anyone who can connect can kill the user's processes, so the socket is created
+    // owner-only instead of being chmod'ed once others could already have connected
+    let previous_umask = umask(Mode::from_bits_truncate(0o177));
+    let listener = UnixListener::bind(path);
+    umask(previous_umask);
+    let listener = listener.with_context(|| format!("Failed to listen on {}", socket))?;
+    println!("🛰️  port-kill daemon listening on {} (PID {})", socket, std::process::id());
+    info!(socket, pid = std::process::id(), "Daemon listening on {}", socket);
+
+    let result = loop {
+        tokio::select! {
+            accepted = listener.accept() => match accepted {
+                Ok((stream, _)) => {
+                    let (input, output) = stream.into_split();
+                    spawn_client(daemon, input, output);
+                }
+                Err(e) => break Err(e.into()),
+            },
+            _ = daemon.stop.notified() => break Ok(()),
+            _ = tokio::signal::ctrl_c() => break Ok(()),
+        }
+    };
+    let _ = std::fs::remove_file(path);
+    result
+}
+
+#[cfg(windows)]
+async fn listen(daemon: &Arc<Daemon>, socket: &str) -> Result<()> {
+    use tokio::net::windows::named_pipe::ServerOptions;
+
+    let mut server = ServerOptions::new()
+        .first_pipe_instance(true)
+        .create(socket)
+        .with_context(|| format!("Failed to listen on {}", socket))?;
+    println!("🛰️  port-kill daemon listening on {} (PID {})", socket, std::process::id());
+    info!(socket, pid = std::process::id(), "Daemon listening on {}", socket);
+
+    loop {
+        tokio::select! {
+            connected = server.connect() => {
+                connected?;
+                // The next client needs a fresh pipe instance
+                let client = std::mem::replace(&mut server, ServerOptions::new().create(socket)?);
+                let (input, output) = tokio::io::split(client);
+                spawn_client(daemon, input, output);
+            }
+            _ = daemon.stop.notified() => return Ok(()),
+            _ = tokio::signal::ctrl_c() => return Ok(()),
+        }
+    }
+}
+
+type Reader = Box<dyn AsyncRead + Unpin + Send>;
+type Writer = Box<dyn AsyncWrite + Unpin + Send>;
+
+async fn connect(socket: &str) -> std::io::Result<(Reader, Writer)> {
+    #[cfg(unix)]
+    {
+        let (input, output) = tokio::net::UnixStream::connect(socket).await?.into_split();
+        Ok((Box::new(input), Box::new(output)))
+    }
+    #[cfg(windows)]
+    {
+        let client = tokio::net::windows::named_pipe::ClientOptions::new().open(socket)?;
+        let (input, output) = tokio::io::split(client);
+        Ok((Box::new(input), Box::new(output)))
+    }
+}
+
+/// A connection to the daemon
+struct Client {
+    lines: Lines<BufReader<Reader>>,
+    output: Writer,
+    next_id: u64,
+}
+
+impl Client {
+    async fn connect(socket: &str) -> Result<Self> {
+        let (input, output) = connect(socket).await.map_err(|_| {
+            anyhow!(
+                "port-kill daemon is not running (nothing on {}); start it with `port-kill daemon`",
+                socket
+            )
+        })?;
+        Ok(Self {
+            lines: BufReader::new(input).lines(),
+            output,
+            next_id: 1,
+        })
+    }
+
+    /// Call `method` and wait for its result, skipping notifications that arrive first
+    async fn request(&mut self, method: &str, params: Value) -> Result<Value> {
+        let id = self.next_id;
+        self.next_id += 1;
+        let request = json!({ "jsonrpc": "2.0", "id": id, "method": method, "params": params });
+        self.output.write_all(format!("{}\n", request).as_bytes()).await?;
+        self.output.flush().await?;
+        while let Some(message) = self.next_message().await? {
+            if message.get("id") != Some(&json!(id)) {
+                continue;
+            }
+            if let Some(error) = message.get("error") {
+                return Err(anyhow!(
+                    "{}",
+                    error["message"].as_str().unwrap_or("the daemon returned an error")
+                ));
+            }
+            return Ok(message.get("result").cloned().unwrap_or(Value::Null));
+        }
+        Err(anyhow!("The daemon closed the connection"))
+    }
+
+    async fn next_message(&mut self) -> Result<Option<Value>> {
+        while let Some(line) = self.lines.next_line().await? {
+            if !line.trim().is_empty() {
+                return Ok(Some(serde_json::from_str(&line)?));
+            }
+        }
+        Ok(None)
+    }
+}
+
+/// A running daemon, for the TUI and the tray: they list from its scans instead of
+/// scanning themselves and tell it when they kill something
+pub struct DaemonClient {
+    socket: String,
+    client: Client,
+}
+
+impl DaemonClient {
+    /// The daemon on `PORT_KILL_SOCKET` or the default socket. None when none answers.
+    pub async fn connect() -> Option<Self> {
+        let socket = std::env::var("PORT_KILL_SOCKET").unwrap_or_else(|_| default_socket());
+        match tokio::time::timeout(DAEMON_LIST_TIMEOUT, Client::connect(&socket)).await {
+            Ok(Ok(client)) => Some(Self { socket, client }),
+            Ok(Err(e)) => {
+                debug!(error = %e, "No daemon to list from: {}", e);
+                None
+            }
+            Err(_) => {
+                warn!(socket, "The port-kill daemon did not answer, scanning directly");
+                None
+            }
+        }
+    }
+
+    pub fn socket(&self) -> &str {
+        &self.socket
+    }
+
+    /// Processes on `ports`, with when the daemon scanned them
+    pub async fn list(
+        &mut self,
+        ports: &[u16],
+        udp: bool,
+    ) -> Result<(HashMap<u16, ProcessInfo>, DateTime<Utc>)> {
+        let result = tokio::time::timeout(
+            DAEMON_LIST_TIMEOUT,
+            self.client.request("list", json!({ "ports": ports, "udp": udp })),
+        )
+        .await
+        .map_err(|_| anyhow!("The port-kill daemon did not answer"))??;
+        let processes: Vec<ProcessInfo> = serde_json::from_value(result["processes"].clone())?;
+        let scanned_at = result["scannedAt"]
+            .as_str()
+            .and_then(|at| at.parse::<DateTime<Utc>>().ok())
+            .unwrap_or_else(Utc::now);
+        Ok((
+            processes
+                .into_iter()
+                .map(|process| (process.port, process))
+                .collect(),
+            scanned_at,
+        ))
+    }
+
+    /// Make the daemon scan again, after a kill or restart made outside of it
+    pub async fn rescan(&mut self) -> Result<()> {
+        self.client.request("daemon.rescan", Value::Null).await?;
+        Ok(())
+    }
+}
+
+/// Processes on `ports` from a running daemon, with when it scanned them, so `--list`
+/// doesn't have to scan. None when no daemon answers in time.
+pub async fn list_from_daemon(
+    ports: &[u16],
+    udp: bool,
+) -> Option<(HashMap<u16, ProcessInfo>, DateTime<Utc>)> {
+    let mut daemon = DaemonClient::connect().await?;
+    match daemon.list(ports, udp).await {
+        Ok(listed) => Some(listed),
+        Err(e) => {
+            warn!(socket = daemon.socket(), error = %e, "Listing from the daemon failed, scanning directly: {}", e);
+            None
+        }
+    }
+}
+
+/// Tell a running daemon that something on its ports changed; nothing when none runs
+pub async fn rescan_daemon() {
+    if let Some(mut daemon) = DaemonClient::connect().await {
+        if let Err(e) = daemon.rescan().await {
+            debug!(error = %e, "Failed to ask the daemon to rescan: {}", e);
+        }
+    }
+}
+
+/// `port-kill ctl`: send one command to the daemon and print its answer
+pub async fn run_ctl(ctl: &CtlArgs) -> Result<()> {
+    let socket = ctl.socket.clone().unwrap_or_else(default_socket);
+    let mut client = Client::connect(&socket).await?;
+
+    let (method, params) = match &ctl.command {
+        CtlCommand::Status => ("daemon.status", Value::Null),
+        CtlCommand::List { ports } if ports.is_empty() => ("list", Value::Null),
+        CtlCommand::List { ports } => ("list", json!({ "ports": ports })),
+        CtlCommand::Kill { port } => ("kill", json!({ "port": port })),
+        CtlCommand::Reserve {
+            port,
+            project,
+            process,
+        } => (
+            "guard.reserve",
+            json!({ "port": port, "project": project, "process": process }),
+        ),
+        CtlCommand::Release { port } => ("guard.release", json!({ "port": port })),
+        CtlCommand::Up { service } => ("services.up", json!({ "service": service })),
+        CtlCommand::Down { service } => ("services.down", json!({ "service": service })),
+        // A number is a port; anything else names a service
+        CtlCommand::Restart { target } => match target.parse::<u16>() {
+            Ok(port) => ("restart", json!({ "port": port })),
+            Err(_) => ("services.restart", json!({ "service": target })),
+        },
+        CtlCommand::Services => ("services.status", Value::Null),
+        CtlCommand::Watch => ("subscribe", Value::Null),
+        CtlCommand::Stop => ("daemon.stop", Value::Null),
+        CtlCommand::Call { method, params } => {
+            let params = match params {
+                Some(params) => serde_json::from_str(params).context("Params are not valid JSON")?,
+                None => Value::Null,
+            };
+            (method.as_str(), params)
+        }
+    };
+    let result = client.request(method, params).await?;
+
+    if let CtlCommand::Watch = ctl.command {
+        if !ctl.json {
+            println!("👀 Watching the daemon's ports. Press Ctrl+C to stop.");
+        }
+        loop {
+            tokio::select! {
+                message = client.next_message() => match message? {
+                    Some(event) if ctl.json => println!("{}", event),
+                    Some(event) => println!(
+                        "{}  {:<18} {}",
+                        Local::now().format("%H:%M:%S"),
+                        event["method"].as_str().unwrap_or_default(),
+                        event["params"]
+                    ),
+                    None => return Err(anyhow!("The daemon closed the connection")),
+                },
+                _ = tokio::signal::ctrl_c() => return Ok(()),
+            }
+        }
+    }
+
+    if ctl.json {
+        println!("{}", serde_json::to_string_pretty(&result)?);
+        return Ok(());
+    }
+    match &ctl.command {
+        CtlCommand::Status => print_status(&result),
+        CtlCommand::List { .. } => {
+            let processes = result["processes"].as_array().cloned().unwrap_or_default();
+            if processes.is_empty() {
+                println!("No processes on the daemon's ports");
+            }
+            for process in processes {
+                println!(
+                    "{:>6}  PID {:<8} {}",
+                    process["port"].as_u64().unwrap_or_default(),
+                    process["pid"].as_i64().unwrap_or_default(),
+                    process["name"].as_str().unwrap_or_default()
+                );
+            }
+        }
+        CtlCommand::Kill { port } => match result["killed"].as_array() {
+            Some(pids) if !pids.is_empty() => {
+                for pid in pids {
+                    println!("✅ Killed PID {} on port {}", pid, port);
+                }
+            }
+            _ => println!("ℹ️  Nothing was listening on port {}", port),
+        },
+        CtlCommand::Reserve { port, project, .. } => {
+            println!("🔒 Port {} reserved for {}", port, project)
+        }
+        CtlCommand::Release { port } => {
+            if result["released"].as_bool() == Some(true) {
+                println!("🔓 Port {} released", port);
+            } else {
+                println!("ℹ️  Port {} was not reserved", port);
+            }
+        }
+        CtlCommand::Restart { .. } if result.get("port").is_some() => match result["pid"].as_u64() {
+            Some(pid) => println!("✅ Restarted port {} (PID {})", result["port"], pid),
+            None => println!("✅ Restarted port {}", result["port"]),
+        },
+        CtlCommand::Up { .. }
+        | CtlCommand::Down { .. }
+        | CtlCommand::Restart { .. }
+        | CtlCommand::Services => print_services(&result["services"]),
+        CtlCommand::Stop => println!("🛑 Stopping the port-kill daemon"),
+        CtlCommand::Call { .. } | CtlCommand::Watch => {
+            println!("{}", serde_json::to_string_pretty(&result)?)
+        }
+    }
+    Ok(())
+}
+
+fn print_status(status: &Value) {
+    let uptime = status["uptimeSecs"].as_i64().unwrap_or_default();
+    let since = status["startedAt"]
+        .as_str()
+        .and_then(|at| at.parse::<DateTime<Utc>>().ok())
+        .map(|at| at.with_timezone(&Local).format("%Y-%m-%d %H:%M").to_string())
+        .unwrap_or_default();
+    println!(
+        "🟢 port-kill daemon running (PID {}) since {}, up {}h {}m",
+        status["pid"],
+        since,
+        uptime / 3600,
+        uptime % 3600 / 60
+    );
+    println!("   Socket: {}", status["socket"].as_str().unwrap_or_default());
+    println!("   Clients: {}", status["clients"]);
+    match status["guard"].as_object() {
+        Some(guard) => {
+            let ports: Vec<String> = guard["watched_ports"]
+                .as_array()
+                .map(|ports| ports.iter().map(Value::to_string).collect())
+                .unwrap_or_default();
+            println!(
+                "   🛡️  Guard: watching {} ({} conflicts resolved)",
+                ports.join(", "),
+                guard["conflicts_resolved"]
+            );
+        }
+        None => println!("   🛡️  Guard: off (start the daemon with --guard-mode)"),
+    }
+    print_services(&status["services"]);
+}
+
+fn print_services(services: &Value) {
+    let services = services.as_array().cloned().unwrap_or_default();
+    if services.is_empty() {
+        println!("   🚀 Services: none started (port-kill ctl up)");
+        return;
+    }
+    println!("   🚀 Services:");
+    for service in services {
+        let icon = if service["running"].as_bool() == Some(true) {
+            "🟢"
+        } else {
+            "⚪"
+        };
+        let port = service["port"]
+            .as_u64()
+            .map(|port| format!(":{}", port))
+            .unwrap_or_default();
+        println!(
+            "      {} {:<16} {:<8} {}",
+            icon,
+            service["name"].as_str().unwrap_or_default(),
+            service["state"].as_str().unwrap_or_default(),
+            port
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::Parser;
+
+    #[tokio::test]
+    async fn test_daemon_over_socket() {
+        let dir = std::env::temp_dir().join(format!("port-kill-daemon-{}", std::process::id()));
+        let socket = dir.join("daemon.sock").to_string_lossy().into_owned();
+        let args = Args::parse_from([
+            "port-kill",
+            "--config-file",
+            &dir.join("missing.yaml").to_string_lossy(),
+            "--ports",
+            "1",
+        ]);
+        let daemon_args = DaemonArgs {
+            socket: Some(socket.clone()),
+            up: false,
+        };
+        let running = tokio::spawn(async move { run_daemon(&args, &daemon_args).await });
+
+        let mut client = loop {
+            if let Ok(client) = Client::connect(&socket).await {
+                break client;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+        };
+        let status = client.request("daemon.status", Value::Null).await.unwrap();
+        assert_eq!(status["pid"], std::process::id());
+        assert_eq!(status["clients"], 1);
+        assert_eq!(status["guard"], Value::Null);
+        // Machine-mode methods work over the socket too
+        let listed = client.request("list", Value::Null).await.unwrap();
+        assert_eq!(listed["processes"], json!([]));
+        // Once the background scan ran, list answers from it
+        while client.request("daemon.status", Value::Null).await.unwrap()["scannedAt"].is_null() {
+            tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+        }
+        let status = client.request("daemon.status", Value::Null).await.unwrap();
+        let listed = client.request("list", json!({ "ports": [1] })).await.unwrap();
+        assert_eq!(listed["scannedAt"], status["scannedAt"]);
+        let error = client.request("restart", json!({ "port": 1 })).await.unwrap_err();
+        assert!(error.to_string().starts_with("No restart information for port 1"));
+        let error = client.request("services.up", Value::Null).await.unwrap_err();
+        assert!(error.to_string().starts_with("Configuration file not found"));
+        assert!(client.request("nope", Value::Null).await.is_err());
+
+        // A second daemon on the same socket is refused
+        let second = DaemonArgs {
+            socket: Some(socket.clone()),
+            up: false,
+        };
+        let error = run_daemon(&Args::parse_from(["port-kill"]), &second).await.unwrap_err();
+        assert!(error.to_string().contains("already running"));
+
+        client.request("daemon.stop", Value::Null).await.unwrap();
+        running.await.unwrap().unwrap();
+        assert!(!std::path::Path::new(&socket).exists());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,448 @@
+use crate::cli::Args;
+use crate::history_store::HistoryStore;
+use crate::orchestrator::Orchestrator;
+use crate::port_guard::read_reservation_file;
+use crate::process_monitor::get_processes_on_ports;
+use crate::types::{PortReservation, ProcessHistoryEntry, ProcessInfo};
+use anyhow::{Context, Result};
+use axum::extract::ws::{Message, WebSocket, WebSocketUpgrade};
+use axum::extract::State;
+use axum::response::{Html, IntoResponse, Json};
+use axum::routing::get;
+use axum::Router;
+use chrono::{DateTime, Duration as ChronoDuration, NaiveDate, Utc};
+use log::warn;
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::net::SocketAddr;
+use std::path::Path;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::watch;
+
+/// Address `--dashboard` listens on when none is given
+pub const DEFAULT_DASHBOARD_ADDR: &str = "127.0.0.1:7878";
+
+/// How often the dashboard rescans ports and pushes a snapshot to open pages
+const REFRESH_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Days covered by the kills-per-day chart
+const HISTORY_DAYS: i64 = 14;
+
+/// Guard events kept for the page, oldest dropped first
+const MAX_GUARD_EVENTS: usize = 50;
+
+const PAGE: &str = include_str!("dashboard.html");
+
+/// A service from `.port-kill.yaml`, running when something listens on its port
+#[derive(Debug, Clone, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ServiceSnapshot {
+    pub name: String,
+    pub command: String,
+    pub port: Option<u16>,
+    pub running: bool,
+    pub pid: Option<i32>,
+}
+
+/// A port reservation and whoever holds the port right now
+#[derive(Debug, Clone, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ReservationSnapshot {
+    pub port: u16,
+    pub project_name: String,
+    pub process_name: String,
+    pub holder: Option<String>,
+    /// The port is held by a process other than the one it is reserved for
+    pub conflict: bool,
+}
+
+/// A reservation conflict appearing or clearing while the dashboard runs
+#[derive(Debug, Clone, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct GuardEvent {
+    pub at: DateTime<Utc>,
+    pub port: u16,
+    pub message: String,
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct DayKills {
+    pub date: NaiveDate,
+    pub kills: usize,
+}
+
+/// Kill history aggregated for the charts
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HistorySnapshot {
+    pub total_kills: usize,
+    /// One entry per day of the last `HISTORY_DAYS` days, oldest first
+    pub kills_per_day: Vec<DayKills>,
+    /// Most killed ports as (port, kills), busiest first
+    pub top_ports: Vec<(u16, usize)>,
+    /// Latest kills, newest first
+    pub recent: Vec<ProcessHistoryEntry>,
+}
+
+/// Everything the dashboard page shows, sent as JSON by `/api/snapshot` and `/ws`
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DashboardSnapshot {
+    pub generated_at: DateTime<Utc>,
+    pub monitored_ports: usize,
+    pub processes: Vec<ProcessInfo>,
+    pub services: Vec<ServiceSnapshot>,
+    pub reservations: Vec<ReservationSnapshot>,
+    pub guard_events: Vec<GuardEvent>,
+    pub history: HistorySnapshot,
+}
+
+fn service_snapshots(
+    orchestrator: Option<&Orchestrator>,
+    processes: &HashMap<u16, ProcessInfo>,
+) -> Vec<ServiceSnapshot> {
+    let Some(orchestrator) = orchestrator else {
+        return Vec::new();
+    };
+    let mut services: Vec<ServiceSnapshot> = orchestrator
+        .config()
+        .services
+        .iter()
+        .map(|(name, service)| {
+            let process = service.port.and_then(|port| processes.get(&port));
+            ServiceSnapshot {
+                name: name.clone(),
+                command: service.command.clone(),
+                port: service.port,
+                running: process.is_some(),
+                pid: process.map(|process| process.pid),
+            }
+        })
+        .collect();
+    services.sort_by(|a, b| a.name.cmp(&b.name));
+    services
+}
+
+pub fn reservation_snapshots(
+    reservations: &HashMap<u16, PortReservation>,
+    processes: &HashMap<u16, ProcessInfo>,
+) -> Vec<ReservationSnapshot> {
+    let mut snapshots: Vec<ReservationSnapshot> = reservations
+        .values()
+        .map(|reservation| {
+            let holder = processes.get(&reservation.port).map(|p| p.name.clone());
+            ReservationSnapshot {
+                port: reservation.port,
+                project_name: reservation.project_name.clone(),
+                process_name: reservation.process_name.clone(),
+                conflict: holder
+                    .as_ref()
+                    .is_some_and(|name| *name != reservation.process_name),
+                holder,
+            }
+        })
+        .collect();
+    snapshots.sort_by_key(|snapshot| snapshot.port);
+    snapshots
+}
+
+/// Events for conflicts that appeared or cleared between two scans
+pub fn guard_events(
+    previous: &[ReservationSnapshot],
+    current: &[ReservationSnapshot],
+    at: DateTime<Utc>,
+) -> Vec<GuardEvent> {
+    let conflicted = |reservations: &[ReservationSnapshot], port: u16| {
+        reservations.iter().any(|r| r.port == port && r.conflict)
+    };
+    let mut events = Vec::new();
+    for reservation in current.iter().filter(|r| r.conflict) {
+        if !conflicted(previous, reservation.port) {
+            events.push(GuardEvent {
+                at,
+                port: reservation.port,
+                message: format!(
+                    "Reserved for {} but taken by {}",
+                    reservation.process_name,
+                    reservation.holder.as_deref().unwrap_or("another process")
+                ),
+            });
+        }
+    }
+    for reservation in previous.iter().filter(|r| r.conflict) {
+        if !conflicted(current, reservation.port) {
+            events.push(GuardEvent {
+                at,
+                port: reservation.port,
+                message: "Conflict cleared".to_string(),
+            });
+        }
+    }
+    events
+}
+
+/// Kill counts for the charts, aggregated by the store so nothing is capped
+fn history_snapshot(store: &HistoryStore, today: NaiveDate) -> Result<HistorySnapshot> {
+    let first_day = today - ChronoDuration::days(HISTORY_DAYS - 1);
+    let mut per_day: BTreeMap<NaiveDate, usize> = (0..HISTORY_DAYS)
+        .map(|offset| (first_day + ChronoDuration::days(offset), 0))
+        .collect();
+    let since = first_day.and_hms_opt(0, 0, 0).unwrap_or_default().and_utc();
+    for (date, kills) in store.kills_per_day(since)? {
+        if let Some(count) = per_day.get_mut(&date) {
+            *count = kills as usize;
+        }
+    }
+
+    Ok(HistorySnapshot {
+        total_kills: store.kill_total()? as usize,
+        kills_per_day: per_day
+            .into_iter()
+            .map(|(date, kills)| DayKills { date, kills })
+            .collect(),
+        top_ports: store
+            .top_ports(5)?
+            .into_iter()
+            .map(|(port, kills)| (port, kills as usize))
+            .collect(),
+        recent: store.recent_kills(10)?.into_iter().rev().collect(),
+    })
+}
+
+/// Scan ports and read services, reservations and history for one snapshot.
+/// `events` carries the guard events seen so far and gets the new ones appended.
+fn collect_snapshot(
+    args: &Args,
+    previous: &[ReservationSnapshot],
+    events: &mut VecDeque<GuardEvent>,
+) -> DashboardSnapshot {
+    let ports = args.get_ports_to_monitor();
+    let (_, processes) = get_processes_on_ports(&ports, args);
+
+    let config_path = args.get_config_file_path();
+    let orchestrator = config_path
+        .exists()
+        .then(|| Orchestrator::load(&config_path).ok())
+        .flatten();
+
+    let reservations = read_reservation_file(Path::new(&args.get_reservation_file_path()))
+        .map(|loaded| loaded.data)
+        .unwrap_or_default();
+    let reservations = reservation_snapshots(&reservations, &processes);
+    let now = Utc::now();
+    events.extend(guard_events(previous, &reservations, now));
+    while events.len() > MAX_GUARD_EVENTS {
+        events.pop_front();
+    }
+
+    let history = HistoryStore::open_default()
+        .and_then(|store| history_snapshot(&store, now.date_naive()))
+        .unwrap_or_default();
+
+    let mut process_list: Vec<ProcessInfo> = processes.values().cloned().collect();
+    process_list.sort_by_key(|process| process.port);
+
+    DashboardSnapshot {
+        generated_at: now,
+        monitored_ports: ports.len(),
+        services: service_snapshots(orchestrator.as_ref(), &processes),
+        processes: process_list,
+        reservations,
+        guard_events: events.iter().rev().cloned().collect(),
+        history,
+    }
+}
+
+type SnapshotReceiver = watch::Receiver<Arc<DashboardSnapshot>>;
+
+async fn page() -> Html<&'static str> {
+    Html(PAGE)
+}
+
+async fn snapshot(State(snapshots): State<SnapshotReceiver>) -> Json<DashboardSnapshot> {
+    let latest = snapshots.borrow().clone();
+    Json((*latest).clone())
+}
+
+async fn websocket(
+    ws: WebSocketUpgrade,
+    State(snapshots): State<SnapshotReceiver>,
+) -> impl IntoResponse {
+    ws.on_upgrade(|socket| push_snapshots(socket, snapshots))
+}
+
+/// Send the latest snapshot, then every new one until the page goes away
+async fn push_snapshots(mut socket: WebSocket, mut snapshots: SnapshotReceiver) {
+    loop {
+        let json = match serde_json::to_string(&**snapshots.borrow_and_update()) {
+            Ok(json) => json,
+            Err(e) => {
+                warn!("Failed to encode dashboard snapshot: {}", e);
+                return;
+            }
+        };
+        if socket.send(Message::Text(json)).await.is_err() || snapshots.changed().await.is_err() {
+            return;
+        }
+    }
+}
+
+/// Serve the dashboard on `addr` until the program is stopped. A single background
+/// scan feeds every open page, so extra tabs do not cost extra `lsof` runs.
+pub async fn run_dashboard(args: Args, addr: &str) -> Result<()> {
+    let addr: SocketAddr = addr
+        .parse()
+        .with_context(|| format!("Invalid dashboard address '{}'", addr))?;
+    if !addr.ip().is_loopback() {
+        warn!(
+            "⚠️  The dashboard on {} is reachable from other machines and has no authentication",
+            addr
+        );
+    }
+
+    let mut events = VecDeque::new();
+    let first = collect_snapshot(&args, &[], &mut events);
+    let (tx, snapshots) = watch::channel(Arc::new(first));
+    tokio::spawn(async move {
+        loop {
+            tokio::time::sleep(REFRESH_INTERVAL).await;
+            let args = args.clone();
+            let previous = tx.borrow().reservations.clone();
+            let mut seen = std::mem::take(&mut events);
+            let scanned = tokio::task::spawn_blocking(move || {
+                let snapshot = collect_snapshot(&args, &previous, &mut seen);
+                (snapshot, seen)
+            })
+            .await;
+            match scanned {
+                Ok((snapshot, seen)) => {
+                    events = seen;
+                    if tx.send(Arc::new(snapshot)).is_err() {
+                        break;
+                    }
+                }
+                Err(e) => warn!("Dashboard scan failed: {}", e),
+            }
+        }
+    });
+
+    let app = Router::new()
+        .route("/", get(page))
+        .route("/api/snapshot", get(snapshot))
+        .route("/ws", get(websocket))
+        .layer(axum::middleware::from_fn_with_state(
+            addr.ip(),
+            crate::rest_api::own_host_only,
+        ))
+        .with_state(snapshots);
+    let listener = tokio::net::TcpListener::bind(addr)
+        .await
+        .with_context(|| format!("Failed to listen on {}", addr))?;
+    println!("📊 Dashboard running at http://{} (Ctrl+C to stop)", addr);
+    axum::serve(listener, app)
+        .await
+        .context("Dashboard server stopped")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn process(port: u16, name: &str) -> ProcessInfo {
+        ProcessInfo {
+            pid: 4242,
+            port,
+            command: name.to_string(),
+            name: name.to_string(),
+            container_id: None,
+            container_name: None,
+            command_line: None,
+            working_directory: None,
+            process_group: None,
+            project_name: None,
+            cpu_usage: None,
+            memory_usage: None,
+            memory_percentage: None,
+            protocol: crate::types::Protocol::Tcp,
+            bind_address: None,
+            address_family: None,
+        }
+    }
+
+    #[test]
+    fn test_history_snapshot() {
+        let today = NaiveDate::from_ymd_opt(2026, 3, 14).unwrap();
+        let kill = |port, date: &str| {
+            let mut entry = ProcessHistoryEntry::new(&process(port, "node"), "user".to_string());
+            entry.killed_at = format!("{}T12:00:00Z", date).parse().unwrap();
+            entry
+        };
+        let path = std::env::temp_dir().join(format!(
+            "port-kill-dashboard-{}.db",
+            std::process::id()
+        ));
+        let _ = std::fs::remove_file(&path);
+        let store = HistoryStore::open(&path).unwrap();
+        for (port, date) in [
+            (3000, "2026-01-01"),
+            (8080, "2026-03-13"),
+            (3000, "2026-03-14"),
+            (3000, "2026-03-14"),
+        ] {
+            store.record_kill(&kill(port, date)).unwrap();
+        }
+        let history = history_snapshot(&store, today).unwrap();
+        assert_eq!(history.total_kills, 4);
+        assert_eq!(history.kills_per_day.len(), HISTORY_DAYS as usize);
+        assert_eq!(
+            history.kills_per_day.last(),
+            Some(&DayKills {
+                date: today,
+                kills: 2
+            })
+        );
+        // Kills older than the chart still count towards the totals
+        assert_eq!(history.top_ports, vec![(3000, 3), (8080, 1)]);
+        assert_eq!(history.recent[0].port, 3000);
+        assert_eq!(history.recent.len(), 4);
+
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_guard_events() {
+        let reservation = PortReservation {
+            port: 3000,
+            project_name: "web".to_string(),
+            process_name: "node".to_string(),
+            reserved_at: Utc::now(),
+            expires_at: None,
+            auto_renew: false,
+        };
+        let reservations = HashMap::from([(3000, reservation)]);
+        let held = reservation_snapshots(
+            &reservations,
+            &HashMap::from([(3000, process(3000, "node"))]),
+        );
+        let taken = reservation_snapshots(
+            &reservations,
+            &HashMap::from([(3000, process(3000, "python3"))]),
+        );
+        assert!(!held[0].conflict);
+        assert!(taken[0].conflict);
+
+        let now = Utc::now();
+        let appeared = guard_events(&held, &taken, now);
+        assert_eq!(appeared.len(), 1);
+        assert_eq!(
+            appeared[0].message,
+            "Reserved for node but taken by python3"
+        );
+        assert!(guard_events(&taken, &taken, now).is_empty());
+        assert_eq!(
+            guard_events(&taken, &held, now)[0].message,
+            "Conflict cleared"
+        );
+    }
+}
@@ -0,0 +1,210 @@
+use anyhow::{anyhow, bail, Context, Result};
+use serde::Deserialize;
+use std::process::Command;
+
+/// Menu id prefix of the container actions; the rest is `<action>:<container id>`
+pub const DOCKER_MENU_PREFIX: &str = "docker:";
+
+/// A running container as listed by `docker ps`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DockerContainer {
+    pub id: String,
+    pub name: String,
+    pub image: String,
+    /// Host ports the container publishes, sorted and without duplicates
+    pub ports: Vec<u16>,
+    /// Docker's own summary, e.g. `Up 2 hours (healthy)`
+    pub status: String,
+}
+
+/// One line of `docker ps --format '{{json .}}'`
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct PsLine {
+    #[serde(rename = "ID")]
+    id: String,
+    names: String,
+    image: String,
+    #[serde(default)]
+    ports: String,
+    #[serde(default)]
+    status: String,
+}
+
+impl DockerContainer {
+    /// Running containers, through the Docker daemon rather than the PIDs of their proxies
+    pub fn list() -> Result<Vec<Self>> {
+        let output = Command::new("docker")
+            .args(["ps", "--no-trunc", "--format", "{{json .}}"])
+            .output()
+            .context("Failed to execute docker ps command")?;
+        if !output.status.success() {
+            bail!(
+                "docker ps failed: {}",
+                String::from_utf8_lossy(&output.stderr).trim()
+            );
+        }
+        String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .map(Self::from_ps_line)
+            .collect()
+    }
+
+    fn from_ps_line(line: &str) -> Result<Self> {
+        let ps: PsLine = serde_json::from_str(line)
+            .with_context(|| format!("Unexpected docker ps output: {}", line))?;
+        Ok(Self {
+            id: ps.id.chars().take(12).collect(),
+            name: ps.names,
+            image: ps.image,
+            ports: published_ports(&ps.ports),
+            status: ps.status,
+        })
+    }
+
+    pub fn publishes_any(&self, ports: &[u16]) -> bool {
+        self.ports.iter().any(|port| ports.contains(port))
+    }
+
+    /// e.g. `:5432, :8080`, or `no published ports`
+    pub fn ports_label(&self) -> String {
+        if self.ports.is_empty() {
+            return "no published ports".to_string();
+        }
+        self.ports
+            .iter()
+            .map(|port| format!(":{}", port))
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+}
+
+/// Host ports in a `docker ps` Ports column such as
+/// `0.0.0.0:5432->5432/tcp, :::5432->5432/tcp, 0.0.0.0:8000-8001->8000-8001/tcp`
+fn published_ports(column: &str) -> Vec<u16> {
+    let mut ports = Vec::new();
+    for mapping in column.split(',') {
+        let Some((host, _)) = mapping.trim().split_once("->") else {
+            // Exposed but not published
+            continue;
+        };
+        let host = host.rsplit(':').next().unwrap_or(host);
+        match host.split_once('-') {
+            Some((start, end)) => {
+                if let (Ok(start), Ok(end)) = (start.parse::<u16>(), end.parse::<u16>()) {
+                    ports.extend(start..=end);
+                }
+            }
+            None => ports.extend(host.parse::<u16>()),
+        }
+    }
+    ports.sort_unstable();
+    ports.dedup();
+    ports
+}
+
+/// What the tray's container submenu can do with a container
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ContainerAction {
+    Stop,
+    Restart,
+    Remove,
+}
+
+impl ContainerAction {
+    pub const ALL: [ContainerAction; 3] = [
+        ContainerAction::Stop,
+        ContainerAction::Restart,
+        ContainerAction::Remove,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            ContainerAction::Stop => "Stop",
+            ContainerAction::Restart => "Restart",
+            ContainerAction::Remove => "Remove",
+        }
+    }
+
+    fn verb(self) -> &'static str {
+        match self {
+            ContainerAction::Stop => "stop",
+            ContainerAction::Restart => "restart",
+            ContainerAction::Remove => "remove",
+        }
+    }
+
+    pub fn menu_id(self, container_id: &str) -> String {
+        format!("{}{}:{}", DOCKER_MENU_PREFIX, self.verb(), container_id)
+    }
+
+    /// The action and container id behind a menu id made by `menu_id`
+    pub fn parse_menu_id(menu_id: &str) -> Option<(Self, String)> {
+        let (verb, container_id) = menu_id.strip_prefix(DOCKER_MENU_PREFIX)?.split_once(':')?;
+        let action = Self::ALL.into_iter().find(|action| action.verb() == verb)?;
+        (!container_id.is_empty()).then(|| (action, container_id.to_string()))
+    }
+
+    /// Run the action with the docker CLI and return a one-line summary
+    pub fn run(self, container_id: &str) -> Result<String> {
+        let args: &[&str] = match self {
+            ContainerAction::Stop => &["stop", container_id],
+            ContainerAction::Restart => &["restart", container_id],
+            ContainerAction::Remove => &["rm", "-f", container_id],
+        };
+        let output = Command::new("docker")
+            .args(args)
+            .output()
+            .map_err(|e| anyhow!("Failed to run docker {}: {}", args[0], e))?;
+        if !output.status.success() {
+            bail!(
+                "Failed to {} container {}: {}",
+                self.verb(),
+                container_id,
+                String::from_utf8_lossy(&output.stderr).trim()
+            );
+        }
+        let done = match self {
+            ContainerAction::Stop => "Stopped",
+            ContainerAction::Restart => "Restarted",
+            ContainerAction::Remove => "Removed",
+        };
+        Ok(format!("{} container {}", done, container_id))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_docker_ps() {
+        let line = r#"{"Command":"\"docker-entrypoint.s…\"","CreatedAt":"2026-03-01 10:00:00 +0000 UTC","ID":"0123456789abcdef0123","Image":"postgres:16","Labels":"","LocalVolumes":"1","Mounts":"pgdata","Names":"shop-db-1","Networks":"shop_default","Ports":"0.0.0.0:5432->5432/tcp, :::5432->5432/tcp, 0.0.0.0:8000-8001->8000-8001/tcp, 6379/tcp","RunningFor":"2 hours ago","Size":"0B","State":"running","Status":"Up 2 hours (healthy)"}"#;
+        let container = DockerContainer::from_ps_line(line).unwrap();
+        assert_eq!(container.id, "0123456789ab");
+        assert_eq!(container.name, "shop-db-1");
+        assert_eq!(container.image, "postgres:16");
+        assert_eq!(container.ports, vec![5432, 8000, 8001]);
+        assert_eq!(container.ports_label(), ":5432, :8000, :8001");
+        assert_eq!(container.status, "Up 2 hours (healthy)");
+        assert!(container.publishes_any(&[3000, 8001]));
+        assert!(!container.publishes_any(&[6379]));
+        assert!(DockerContainer::from_ps_line("not json").is_err());
+    }
+
+    #[test]
+    fn test_container_menu_ids() {
+        for action in ContainerAction::ALL {
+            let id = action.menu_id("0123456789ab");
+            assert_eq!(
+                ContainerAction::parse_menu_id(&id),
+                Some((action, "0123456789ab".to_string()))
+            );
+        }
+        assert_eq!(ContainerAction::Remove.menu_id("abc"), "docker:remove:abc");
+        assert_eq!(ContainerAction::parse_menu_id("docker:pause:abc"), None);
+        assert_eq!(ContainerAction::parse_menu_id("docker:stop:"), None);
+        assert_eq!(ContainerAction::parse_menu_id("restart:3000"), None);
+    }
+}
@@ -0,0 +1,538 @@
+use crate::cli::Args;
+use crate::format_version::{
+    parse_versioned, unchanged_payload, RESERVATIONS_VERSION, RESTART_HISTORY_VERSION,
+};
+use crate::orchestrator::Orchestrator;
+use crate::preset_manager::PresetManager;
+use crate::restart_manager::RestartInfo;
+use crate::types::PortReservation;
+use crate::user_config::UserConfig;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CheckStatus {
+    Ok,
+    Warning,
+    Error,
+}
+
+/// One environment check, with what to do about it when it did not pass
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DoctorCheck {
+    pub name: String,
+    pub status: CheckStatus,
+    pub detail: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub suggested_fix: Option<String>,
+}
+
+impl DoctorCheck {
+    fn ok(name: &str, detail: impl Into<String>) -> Self {
+        Self {
+            name: name.to_string(),
+            status: CheckStatus::Ok,
+            detail: detail.into(),
+            suggested_fix: None,
+        }
+    }
+
+    fn warning(name: &str, detail: impl Into<String>, fix: impl Into<String>) -> Self {
+        Self {
+            name: name.to_string(),
+            status: CheckStatus::Warning,
+            detail: detail.into(),
+            suggested_fix: Some(fix.into()),
+        }
+    }
+
+    fn error(name: &str, detail: impl Into<String>, fix: impl Into<String>) -> Self {
+        Self {
+            name: name.to_string(),
+            status: CheckStatus::Error,
+            detail: detail.into(),
+            suggested_fix: Some(fix.into()),
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct DoctorReport {
+    pub ok: bool,
+    pub checks: Vec<DoctorCheck>,
+}
+
+/// Check everything port-kill depends on outside its own binary
+pub fn run_doctor(args: &Args) -> DoctorReport {
+    let mut checks = vec![check_port_lookup(), check_kill_permissions()];
+    #[cfg(target_os = "linux")]
+    checks.push(check_proc_access());
+    checks.push(check_docker());
+
+    checks.push(check_user_config(args));
+    checks.push(check_presets());
+    checks.push(check_orchestration_config(args));
+    checks.push(check_versioned_file::<HashMap<u16, PortReservation>>(
+        "Port reservations",
+        Path::new(&args.get_reservation_file_path()),
+        "reservations",
+        RESERVATIONS_VERSION,
+    ));
+    checks.push(check_versioned_file::<HashMap<u16, RestartInfo>>(
+        "Restart history",
+        &port_kill_dir().join("restart-history.json"),
+        "restarts",
+        RESTART_HISTORY_VERSION,
+    ));
+    checks.push(check_tray());
+
+    DoctorReport {
+        ok: checks.iter().all(|c| c.status != CheckStatus::Error),
+        checks,
+    }
+}
+
+pub fn print_doctor_report(report: &DoctorReport) {
+    println!("🩺 port-kill doctor");
+    println!();
+    for check in &report.checks {
+        let icon = match check.status {
+            CheckStatus::Ok => "✅",
+            CheckStatus::Warning => "⚠️ ",
+            CheckStatus::Error => "❌",
+        };
+        println!("{} {}: {}", icon, check.name, check.detail);
+        if let Some(ref fix) = check.suggested_fix {
+            println!("   💡 {}", fix);
+        }
+    }
+
+    let count = |status| report.checks.iter().filter(|c| c.status == status).count();
+    println!();
+    println!(
+        "{} passed, {} warning(s), {} error(s)",
+        count(CheckStatus::Ok),
+        count(CheckStatus::Warning),
+        count(CheckStatus::Error)
+    );
+}
+
+fn port_kill_dir() -> PathBuf {
+    let home = std::env::var("HOME")
+        .or_else(|_| std::env::var("USERPROFILE"))
+        .unwrap_or_else(|_| ".".to_string());
+    PathBuf::from(home).join(".port-kill")
+}
+
+/// Locate an executable on PATH
+fn find_program(name: &str) -> Option<PathBuf> {
+    let path = std::env::var_os("PATH")?;
+    std::env::split_paths(&path).find_map(|dir| {
+        [name.to_string(), format!("{}.exe", name)]
+            .iter()
+            .map(|file| dir.join(file))
+            .find(|candidate| candidate.is_file())
+    })
+}
+
+fn check_port_lookup() -> DoctorCheck {
+    #[cfg(target_os = "windows")]
+    let (tool, fix) = (
+        "netstat",
+        "netstat ships with Windows; make sure C:\\Windows\\System32 is on PATH",
+    );
+    #[cfg(target_os = "macos")]
+    let (tool, fix) = (
+        "lsof",
+        "lsof ships with macOS; make sure /usr/sbin is on PATH",
+    );
+    #[cfg(not(any(target_os = "windows", target_os = "macos")))]
+    let (tool, fix) = (
+        "lsof",
+        "Install lsof (e.g., sudo apt-get install lsof, sudo dnf install lsof, sudo pacman -S lsof)",
+    );
+
+    match find_program(tool) {
+        Some(path) => DoctorCheck::ok(
+            "Port lookup",
+            format!("{} found at {}", tool, path.display()),
+        ),
+        None => DoctorCheck::error(
+            "Port lookup",
+            format!(
+                "{} is not on PATH, so no listening ports can be found",
+                tool
+            ),
+            fix,
+        ),
+    }
+}
+
+#[cfg(not(target_os = "windows"))]
+fn check_kill_permissions() -> DoctorCheck {
+    let uid = nix::unistd::geteuid();
+    if uid.is_root() {
+        DoctorCheck::ok(
+            "Kill permissions",
+            "running as root; any process can be stopped",
+        )
+    } else {
+        DoctorCheck::warning(
+            "Kill permissions",
+            format!(
+                "running as uid {}; only your own processes can be stopped",
+                uid
+            ),
+            "Rerun with sudo to stop processes owned by other users or system services",
+        )
+    }
+}
+
+#[cfg(target_os = "windows")]
+fn check_kill_permissions() -> DoctorCheck {
+    match find_program("taskkill") {
+        Some(_) => DoctorCheck::ok(
+            "Kill permissions",
+            "taskkill available; services owned by other users need an elevated prompt",
+        ),
+        None => DoctorCheck::error(
+            "Kill permissions",
+            "taskkill is not on PATH, so processes cannot be stopped",
+            "Make sure C:\\Windows\\System32 is on PATH",
+        ),
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn check_proc_access() -> DoctorCheck {
+    if !Path::new("/proc/self/cmdline").exists() {
+        return DoctorCheck::error(
+            "/proc access",
+            "/proc is not mounted, so command lines and working directories are unavailable",
+            "Mount procfs (mount -t proc proc /proc) or run port-kill outside this sandbox",
+        );
+    }
+    match fs::read_link("/proc/1/cwd") {
+        Ok(_) => DoctorCheck::ok("/proc access", "working directories of all processes are readable"),
+        // Even root is refused without CAP_SYS_PTRACE, e.g. inside a container
+        Err(_) if nix::unistd::geteuid().is_root() => DoctorCheck::warning(
+            "/proc access",
+            "working directories of other processes are not readable, so their project context and restarts are limited",
+            "Give the container the SYS_PTRACE capability (docker run --cap-add SYS_PTRACE)",
+        ),
+        Err(_) => DoctorCheck::warning(
+            "/proc access",
+            "working directories of other users' processes are not readable, so their project context and restarts are limited",
+            "Rerun with sudo when working with system services",
+        ),
+    }
+}
+
+fn check_docker() -> DoctorCheck {
+    if find_program("docker").is_none() {
+        return DoctorCheck::ok("Docker", "not installed (only needed for --docker)");
+    }
+    match Command::new("docker")
+        .args(["info", "--format", "{{.ServerVersion}}"])
+        .output()
+    {
+        Ok(output) if output.status.success() => DoctorCheck::ok(
+            "Docker",
+            format!(
+                "daemon reachable (server {})",
+                String::from_utf8_lossy(&output.stdout).trim()
+            ),
+        ),
+        Ok(output) => {
+            let stderr = String::from_utf8_lossy(&output.stderr).to_lowercase();
+            if stderr.contains("permission denied") {
+                DoctorCheck::warning(
+                    "Docker",
+                    "no permission to use the Docker socket",
+                    "Add yourself to the docker group (sudo usermod -aG docker $USER) and log in again",
+                )
+            } else {
+                DoctorCheck::warning(
+                    "Docker",
+                    "the Docker daemon is not reachable",
+                    "Start Docker Desktop or the docker service (sudo systemctl start docker)",
+                )
+            }
+        }
+        Err(e) => DoctorCheck::warning(
+            "Docker",
+            format!("failed to run docker: {}", e),
+            "Check that the docker CLI on PATH is executable",
+        ),
+    }
+}
+
+fn check_user_config(args: &Args) -> DoctorCheck {
+    let path = UserConfig::default_path();
+    if !path.exists() {
+        return DoctorCheck::ok("Config file", format!("{} (not created)", path.display()));
+    }
+    // Startup already tried to apply it; settings that parse but don't apply fail there
+    let result = match args.config_error {
+        Some(ref error) => Err(error.clone()),
+        None => UserConfig::load(&path).map(|_| ()).map_err(|e| e.to_string()),
+    };
+    match result {
+        Ok(()) => DoctorCheck::ok("Config file", format!("{} is valid", path.display())),
+        Err(e) => DoctorCheck::error(
+            "Config file",
+            e,
+            format!(
+                "Fix the setting in {} or run with --no-config",
+                path.display()
+            ),
+        ),
+    }
+}
+
+fn check_presets() -> DoctorCheck {
+    let mut manager = PresetManager::new();
+    match manager.load_presets() {
+        Ok(()) => {
+            let mut detail = format!("{} presets available", manager.get_preset_names().len());
+            if let Some(path) = manager.project_presets_path() {
+                detail.push_str(&format!(" (project presets from {})", path.display()));
+            }
+            DoctorCheck::ok("Presets", detail)
+        }
+        Err(e) => DoctorCheck::error(
+            "Presets",
+            e.to_string(),
+            format!(
+                "Fix or move aside {} (or the project's .port-kill-presets file)",
+                manager.get_config_path()
+            ),
+        ),
+    }
+}
+
+fn check_orchestration_config(args: &Args) -> DoctorCheck {
+    let path = args.get_config_file_path();
+    if !path.exists() {
+        return DoctorCheck::ok(
+            "Orchestration config",
+            format!(
+                "{} not found (only needed for --up/--down)",
+                args.config_file
+            ),
+        );
+    }
+    match Orchestrator::load(&path) {
+        Ok(orchestrator) => DoctorCheck::ok(
+            "Orchestration config",
+            format!(
+                "{} defines {} service(s)",
+                path.display(),
+                orchestrator.config().services.len()
+            ),
+        ),
+        Err(e) => DoctorCheck::error(
+            "Orchestration config",
+            format!("{:#}", e),
+            format!(
+                "Fix {} (port-kill --init-config shows a working example)",
+                path.display()
+            ),
+        ),
+    }
+}
+
+/// Check that a versioned data file under ~/.port-kill can be read
+fn check_versioned_file<T: DeserializeOwned>(
+    name: &str,
+    path: &Path,
+    key: &str,
+    current: u32,
+) -> DoctorCheck {
+    if !path.exists() {
+        return DoctorCheck::ok(name, format!("{} (not created yet)", path.display()));
+    }
+    let parsed = fs::read_to_string(path)
+        .map_err(anyhow::Error::from)
+        .and_then(|content| parse_versioned::<T>(&content, key, current, unchanged_payload));
+    match parsed {
+        Ok(file) if file.version < current => DoctorCheck::warning(
+            name,
+            format!(
+                "{} uses format version {} and will be migrated on next use",
+                path.display(),
+                file.version
+            ),
+            format!(
+                "Nothing to do; a copy is kept as {}.v{}.bak",
+                path.display(),
+                file.version
+            ),
+        ),
+        Ok(_) => DoctorCheck::ok(name, format!("{} is valid", path.display())),
+        Err(e) => DoctorCheck::error(
+            name,
+            format!("{} is unreadable: {}", path.display(), e),
+            format!(
+                "Restore it from a .bak copy or move it aside so port-kill starts a fresh one: mv {} {}.broken",
+                path.display(),
+                path.display()
+            ),
+        ),
+    }
+}
+
+/// Why the status bar can't start in this session, if it can't: CI, SSH or another
+/// session without the window server. `manager` is `launchctl managername`.
+#[cfg_attr(not(target_os = "macos"), allow(dead_code))]
+fn macos_gui_session_missing(manager: &str, ci: bool, ssh: bool) -> Option<String> {
+    if ci {
+        return Some("running under CI".to_string());
+    }
+    match manager.trim() {
+        // Unknown: let the tray try rather than guess
+        "" | "Aqua" => None,
+        _ if ssh => Some("no GUI session over SSH".to_string()),
+        other => Some(format!("no GUI session (launchd session '{}')", other)),
+    }
+}
+
+/// Why the tray can't start in this session, if it can't
+#[cfg(target_os = "macos")]
+pub fn gui_session_missing() -> Option<String> {
+    let manager = Command::new("launchctl")
+        .arg("managername")
+        .output()
+        .map(|output| String::from_utf8_lossy(&output.stdout).into_owned())
+        .unwrap_or_default();
+    macos_gui_session_missing(
+        &manager,
+        std::env::var_os("CI").is_some(),
+        std::env::var_os("SSH_CONNECTION").is_some() || std::env::var_os("SSH_TTY").is_some(),
+    )
+}
+
+/// Why the tray can't start in this session, if it can't
+#[cfg(target_os = "windows")]
+pub fn gui_session_missing() -> Option<String> {
+    None
+}
+
+/// Why the tray can't start in this session, if it can't
+#[cfg(not(any(target_os = "macos", target_os = "windows")))]
+pub fn gui_session_missing() -> Option<String> {
+    let display =
+        std::env::var_os("DISPLAY").is_some() || std::env::var_os("WAYLAND_DISPLAY").is_some();
+    (!display).then(|| "no graphical session (DISPLAY/WAYLAND_DISPLAY unset)".to_string())
+}
+
+#[cfg(target_os = "macos")]
+fn check_tray() -> DoctorCheck {
+    match gui_session_missing() {
+        Some(reason) => DoctorCheck::warning(
+            "Tray",
+            format!("{}; port-kill falls back to console mode", reason),
+            "Run port-kill from a desktop session, or use port-kill-console here",
+        ),
+        None => DoctorCheck::ok("Tray", "menu bar icon supported"),
+    }
+}
+
+#[cfg(target_os = "windows")]
+fn check_tray() -> DoctorCheck {
+    DoctorCheck::ok("Tray", "notification area icon supported")
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "windows")))]
+fn check_tray() -> DoctorCheck {
+    if let Some(reason) = gui_session_missing() {
+        return DoctorCheck::warning(
+            "Tray",
+            format!("{}; port-kill falls back to console mode", reason),
+            "Use port-kill-console here, or run port-kill from a desktop session",
+        );
+    }
+
+    let libraries = Command::new("ldconfig")
+        .arg("-p")
+        .output()
+        .map(|output| String::from_utf8_lossy(&output.stdout).into_owned())
+        .unwrap_or_default();
+    let indicator = ["libappindicator3", "libayatana-appindicator3"]
+        .iter()
+        .any(|lib| libraries.contains(lib));
+    if indicator && libraries.contains("libgtk-3") {
+        DoctorCheck::ok("Tray", "GTK 3 and AppIndicator libraries found")
+    } else {
+        DoctorCheck::warning(
+            "Tray",
+            "GTK 3 or AppIndicator libraries are missing, so the tray icon cannot start",
+            "Install them (e.g., sudo apt-get install libgtk-3-0 libappindicator3-1) or use port-kill-console",
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_versioned_file_checks() {
+        let dir = std::env::temp_dir().join(format!(
+            "port-kill-doctor-{}",
+            std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .unwrap()
+                .as_nanos()
+        ));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("reservations.json");
+        let check = |path: &Path| {
+            check_versioned_file::<HashMap<u16, PortReservation>>(
+                "Reservations",
+                path,
+                "reservations",
+                2,
+            )
+        };
+
+        assert_eq!(check(&path).status, CheckStatus::Ok);
+
+        fs::write(&path, "{\"version\": 2, \"reservations\": {}}").unwrap();
+        assert_eq!(check(&path).status, CheckStatus::Ok);
+
+        fs::write(&path, "{}").unwrap();
+        assert_eq!(check(&path).status, CheckStatus::Warning);
+
+        fs::write(&path, "{\"version\": 2, \"reservations\": ").unwrap();
+        let broken = check(&path);
+        assert_eq!(broken.status, CheckStatus::Error);
+        assert!(broken.suggested_fix.unwrap().contains(".broken"));
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_macos_gui_session() {
+        assert_eq!(macos_gui_session_missing("Aqua\n", false, true), None);
+        assert_eq!(macos_gui_session_missing("", false, false), None);
+        assert_eq!(
+            macos_gui_session_missing("Background\n", false, true).as_deref(),
+            Some("no GUI session over SSH")
+        );
+        assert_eq!(
+            macos_gui_session_missing("StandardIO", false, false).as_deref(),
+            Some("no GUI session (launchd session 'StandardIO')")
+        );
+        assert_eq!(
+            macos_gui_session_missing("Aqua", true, false).as_deref(),
+            Some("running under CI")
+        );
+    }
+}
@@ -1,13 +1,21 @@
 use crate::cli::Args;
+use crate::dashboard::{reservation_snapshots, ReservationSnapshot};
+use crate::history_store::HistoryStore;
+use crate::port_guard::read_reservation_file;
 use crate::process_monitor::ProcessMonitor;
+use crate::scan_interval::AdaptiveInterval;
 use crate::security_audit::SecurityAuditor;
+use crate::types::ProcessInfo;
 use anyhow::{Context, Result};
 use chrono::{DateTime, Utc};
 use reqwest::Client;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::Write as _;
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
 use std::time::Duration;
-use tokio::time::{interval, sleep};
+use tokio::time::{interval, sleep, sleep_until, Instant};
 
 /// Data structure for endpoint monitoring payload
 #[derive(Debug, Serialize, Deserialize)]
@@ -48,10 +56,125 @@ pub struct PortSummary {
     pub suspicious_ports: usize,
 }
 
+/// What `/metrics` reports for `--prometheus-port`, refreshed after every scan
+#[derive(Debug, Clone, Default)]
+pub struct MetricsSnapshot {
+    pub monitored_ports: usize,
+    /// One process per port in use, with CPU and memory
+    pub processes: HashMap<u16, ProcessInfo>,
+    /// Every process listening on each port in use
+    pub processes_per_port: BTreeMap<u16, usize>,
+    /// Reserved ports held by a process other than the one they are reserved for
+    pub guard_violations: Vec<ReservationSnapshot>,
+    /// Kills from the history as (port, killed by, count)
+    pub kills: Vec<(u16, String, u64)>,
+    pub scans: u64,
+}
+
+/// Escape a Prometheus label value
+fn label(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+/// `snapshot` in the Prometheus text exposition format
+pub fn render_metrics(snapshot: &MetricsSnapshot) -> String {
+    let mut out = String::new();
+    let family = |out: &mut String, name: &str, kind: &str, help: &str| {
+        let _ = writeln!(out, "# HELP {} {}", name, help);
+        let _ = writeln!(out, "# TYPE {} {}", name, kind);
+    };
+
+    family(&mut out, "port_kill_monitored_ports", "gauge", "Ports port-kill monitors");
+    let _ = writeln!(out, "port_kill_monitored_ports {}", snapshot.monitored_ports);
+
+    family(&mut out, "port_kill_port_processes", "gauge", "Processes listening on a port");
+    for (port, count) in &snapshot.processes_per_port {
+        let _ = writeln!(out, "port_kill_port_processes{{port=\"{}\"}} {}", port, count);
+    }
+
+    let mut processes: Vec<&ProcessInfo> = snapshot.processes.values().collect();
+    processes.sort_by_key(|process| process.port);
+    family(&mut out, "port_kill_process_cpu_percent", "gauge", "CPU usage of the process on a port");
+    for process in &processes {
+        if let Some(cpu) = process.cpu_usage {
+            let _ = writeln!(
+                out,
+                "port_kill_process_cpu_percent{{port=\"{}\",pid=\"{}\",process=\"{}\"}} {}",
+                process.port,
+                process.pid,
+                label(&process.name),
+                cpu
+            );
+        }
+    }
+    family(&mut out, "port_kill_process_memory_bytes", "gauge", "Memory used by the process on a port");
+    for process in &processes {
+        if let Some(memory) = process.memory_usage {
+            let _ = writeln!(
+                out,
+                "port_kill_process_memory_bytes{{port=\"{}\",pid=\"{}\",process=\"{}\"}} {}",
+                process.port,
+                process.pid,
+                label(&process.name),
+                memory
+            );
+        }
+    }
+
+    family(&mut out, "port_kill_guard_violations", "gauge", "Reserved ports held by another process");
+    let _ = writeln!(out, "port_kill_guard_violations {}", snapshot.guard_violations.len());
+    family(&mut out, "port_kill_guard_violation", "gauge", "A reserved port held by another process");
+    for violation in &snapshot.guard_violations {
+        let _ = writeln!(
+            out,
+            "port_kill_guard_violation{{port=\"{}\",reserved_for=\"{}\",holder=\"{}\"}} 1",
+            violation.port,
+            label(&violation.project_name),
+            label(violation.holder.as_deref().unwrap_or_default())
+        );
+    }
+
+    family(&mut out, "port_kill_kills_total", "counter", "Processes port-kill killed, from its history");
+    for (port, killed_by, count) in &snapshot.kills {
+        let _ = writeln!(
+            out,
+            "port_kill_kills_total{{port=\"{}\",killed_by=\"{}\"}} {}",
+            port,
+            label(killed_by),
+            count
+        );
+    }
+
+    family(&mut out, "port_kill_scans_total", "counter", "Scans since the exporter started");
+    let _ = writeln!(out, "port_kill_scans_total {}", snapshot.scans);
+    out
+}
+
+/// Serve `/metrics` on `listener` until the program is stopped
+async fn serve_metrics(listener: tokio::net::TcpListener, metrics: Arc<Mutex<MetricsSnapshot>>) {
+    use axum::http::header;
+    use axum::routing::get;
+
+    let app = axum::Router::new().route(
+        "/metrics",
+        get(move || {
+            let body = render_metrics(&metrics.lock().unwrap());
+            async move { ([(header::CONTENT_TYPE, "text/plain; version=0.0.4")], body) }
+        }),
+    );
+    if let Err(e) = axum::serve(listener, app).await {
+        log::warn!("Prometheus exporter stopped: {}", e);
+    }
+}
+
 /// Endpoint monitor for sending periodic data to external endpoints
 pub struct EndpointMonitor {
     client: Client,
-    endpoint_url: String,
+    /// None when only exporting metrics
+    endpoint_url: Option<String>,
     auth_header: Option<String>,
     custom_fields: HashMap<String, String>,
     include_audit: bool,
@@ -59,16 +182,18 @@ pub struct EndpointMonitor {
     _timeout: Duration,
     process_monitor: ProcessMonitor,
     security_auditor: Option<SecurityAuditor>,
+    prometheus_addr: Option<SocketAddr>,
+    metrics: Arc<Mutex<MetricsSnapshot>>,
+    reservation_file: String,
 }
 
 impl EndpointMonitor {
     /// Create a new endpoint monitor
     pub fn new(args: &Args) -> Result<Self> {
-        let endpoint_url = args
-            .monitor_endpoint
-            .as_ref()
-            .ok_or_else(|| anyhow::anyhow!("Endpoint URL is required for monitoring"))?
-            .clone();
+        let endpoint_url = args.monitor_endpoint.clone();
+        if endpoint_url.is_none() && args.prometheus_port.is_none() {
+            return Err(anyhow::anyhow!("Endpoint URL is required for monitoring"));
+        }
 
         // Create HTTP client with timeout
         let client = Client::builder()
@@ -99,14 +224,16 @@ impl EndpointMonitor {
         // Create process monitor
         let (update_sender, _update_receiver) = crossbeam_channel::bounded(100);
         let ports_to_scan = args.get_ports_to_monitor();
-        let process_monitor = ProcessMonitor::new_with_performance(
+        let mut process_monitor = ProcessMonitor::new_with_performance(
             update_sender,
             ports_to_scan,
             args.docker,
             args.verbose,
             None,
-            args.performance,
+            // CPU and memory are part of the metrics
+            args.performance || args.prometheus_port.is_some(),
         )?;
+        process_monitor.set_udp(args.udp);
 
         // Create security auditor if audit is enabled
         let security_auditor = if args.endpoint_include_audit {
@@ -136,32 +263,64 @@ impl EndpointMonitor {
             _timeout: Duration::from_secs(args.endpoint_timeout),
             process_monitor,
             security_auditor,
+            prometheus_addr: args
+                .prometheus_port
+                .map(|port| SocketAddr::new(args.prometheus_addr, port)),
+            metrics: Arc::new(Mutex::new(MetricsSnapshot::default())),
+            reservation_file: args.get_reservation_file_path(),
         })
     }
 
     /// Run the endpoint monitor with dual intervals
     pub async fn run(&mut self, args: &Args) -> Result<()> {
-        let scan_interval = Duration::from_secs(args.scan_interval);
+        let mut scan_interval = AdaptiveInterval::from_args(args);
         let send_interval = Duration::from_secs(args.send_interval);
 
-        let mut scan_timer = interval(scan_interval);
+        let mut next_scan = Instant::now();
         let mut send_timer = interval(send_interval);
 
+        if let Some(addr) = self.prometheus_addr {
+            if !addr.ip().is_loopback() {
+                log::warn!(
+                    "⚠️  Prometheus metrics on {} are reachable from other machines and have no authentication",
+                    addr
+                );
+            }
+            let listener = tokio::net::TcpListener::bind(addr)
+                .await
+                .with_context(|| format!("Failed to listen on {} for Prometheus", addr))?;
+            tokio::spawn(serve_metrics(listener, self.metrics.clone()));
+        }
+
         log::info!("Starting endpoint monitor:");
-        log::info!("  - Endpoint: {}", self.endpoint_url);
-        log::info!("  - Scan interval: {}s", args.scan_interval);
+        if let Some(url) = &self.endpoint_url {
+            log::info!("  - Endpoint: {}", url);
+        }
+        if let Some(addr) = self.prometheus_addr {
+            log::info!("  - Prometheus metrics: http://{}/metrics", addr);
+        }
+        log::info!(
+            "  - Scan interval: {}s, up to {}s while idle",
+            args.scan_interval,
+            args.max_scan_interval
+        );
         log::info!("  - Send interval: {}s", args.send_interval);
         log::info!("  - Include audit: {}", self.include_audit);
 
         loop {
             tokio::select! {
-                _ = scan_timer.tick() => {
-                    // Scan processes at high frequency
-                    if let Err(e) = self.scan_processes().await {
-                        log::warn!("Failed to scan processes: {}", e);
-                    }
+                _ = sleep_until(next_scan) => {
+                    // Scan processes at high frequency, backing off while nothing changes
+                    let changed = match self.scan_processes().await {
+                        Ok(changed) => changed,
+                        Err(e) => {
+                            log::warn!("Failed to scan processes: {}", e);
+                            false
+                        }
+                    };
+                    next_scan = Instant::now() + scan_interval.record(changed);
                 }
-                _ = send_timer.tick() => {
+                _ = send_timer.tick(), if self.endpoint_url.is_some() => {
                     // Send data to endpoint at lower frequency
                     if let Err(e) = self.send_to_endpoint().await {
                         log::warn!("Failed to send data to endpoint: {}", e);
@@ -171,18 +330,59 @@ impl EndpointMonitor {
         }
     }
 
-    /// Scan processes and update internal state
-    async fn scan_processes(&mut self) -> Result<()> {
-        self.process_monitor.scan_processes().await?;
-        Ok(())
+    /// Scan processes and update internal state; returns whether anything changed
+    async fn scan_processes(&mut self) -> Result<bool> {
+        let before = self.process_monitor.get_processes().clone();
+        let after = self.process_monitor.scan_processes().await?;
+        if self.prometheus_addr.is_some() {
+            self.update_metrics(&after);
+        }
+        Ok(before != after)
+    }
+
+    fn update_metrics(&self, processes: &HashMap<u16, ProcessInfo>) {
+        let processes_per_port = self
+            .process_monitor
+            .scan_processes_multi()
+            .into_iter()
+            .filter(|(_, on_port)| !on_port.is_empty())
+            .map(|(port, on_port)| (port, on_port.len()))
+            .collect();
+        let reservations = read_reservation_file(std::path::Path::new(&self.reservation_file))
+            .map(|loaded| loaded.data)
+            .unwrap_or_default();
+        let guard_violations = reservation_snapshots(&reservations, processes)
+            .into_iter()
+            .filter(|reservation| reservation.conflict)
+            .collect();
+        let kills = match HistoryStore::open_default().and_then(|store| store.kill_counts()) {
+            Ok(kills) => kills,
+            Err(e) => {
+                log::debug!("Kill history unavailable for metrics: {}", e);
+                Vec::new()
+            }
+        };
+
+        let mut metrics = self.metrics.lock().unwrap();
+        *metrics = MetricsSnapshot {
+            monitored_ports: self.process_monitor.get_ports_to_monitor().len(),
+            processes: processes.clone(),
+            processes_per_port,
+            guard_violations,
+            kills,
+            scans: metrics.scans + 1,
+        };
     }
 
     /// Send data to the configured endpoint
     async fn send_to_endpoint(&self) -> Result<()> {
+        let Some(endpoint_url) = &self.endpoint_url else {
+            return Ok(());
+        };
         let payload = self.build_payload().await?;
 
         for attempt in 1..=self.retries {
-            match self.send_payload(&payload).await {
+            match self.send_payload(endpoint_url, &payload).await {
                 Ok(_) => {
                     log::debug!("Successfully sent data to endpoint (attempt {})", attempt);
                     return Ok(());
@@ -208,8 +408,8 @@ impl EndpointMonitor {
     }
 
     /// Send payload to endpoint with retry logic
-    async fn send_payload(&self, payload: &EndpointPayload) -> Result<()> {
-        let mut request = self.client.post(&self.endpoint_url).json(payload);
+    async fn send_payload(&self, endpoint_url: &str, payload: &EndpointPayload) -> Result<()> {
+        let mut request = self.client.post(endpoint_url).json(payload);
 
         // Add authentication header if provided
         if let Some(auth) = &self.auth_header {
@@ -343,6 +543,38 @@ impl EndpointMonitor {
     }
 }
 
+/// POST a one-off JSON message (such as a report) to `--monitor-endpoint`, with its
+/// auth header, timeout and retries
+pub async fn post_to_endpoint<T: Serialize>(args: &Args, body: &T) -> Result<()> {
+    let endpoint_url = args
+        .monitor_endpoint
+        .as_ref()
+        .ok_or_else(|| anyhow::anyhow!("Endpoint URL is required for monitoring"))?;
+    let client = Client::builder()
+        .timeout(Duration::from_secs(args.endpoint_timeout))
+        .build()
+        .context("Failed to create HTTP client")?;
+
+    let mut last_error = None;
+    for attempt in 1..=args.endpoint_retries.max(1) {
+        let mut request = client.post(endpoint_url).json(body);
+        if let Some(auth) = &args.endpoint_auth {
+            request = request.header("Authorization", auth);
+        }
+        match request.send().await {
+            Ok(response) if response.status().is_success() => return Ok(()),
+            Ok(response) => {
+                last_error = Some(anyhow::anyhow!("Endpoint returned status {}", response.status()))
+            }
+            Err(e) => last_error = Some(e.into()),
+        }
+        if attempt < args.endpoint_retries {
+            sleep(Duration::from_secs(attempt as u64 * 2)).await;
+        }
+    }
+    Err(last_error.unwrap_or_else(|| anyhow::anyhow!("Failed to send data to endpoint")))
+}
+
 /// Helper function to get hostname
 mod hostname {
     use std::ffi::OsString;
@@ -356,3 +588,59 @@ mod hostname {
             })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_metrics() {
+        let process = ProcessInfo {
+            pid: 42,
+            port: 3000,
+            command: "node".to_string(),
+            name: "node \"dev\"".to_string(),
+            container_id: None,
+            container_name: None,
+            command_line: None,
+            working_directory: None,
+            process_group: None,
+            project_name: None,
+            cpu_usage: Some(12.5),
+            memory_usage: Some(1024),
+            memory_percentage: None,
+            protocol: crate::types::Protocol::Tcp,
+            bind_address: None,
+            address_family: None,
+        };
+        let snapshot = MetricsSnapshot {
+            monitored_ports: 3,
+            processes: HashMap::from([(3000, process)]),
+            processes_per_port: BTreeMap::from([(3000, 2)]),
+            guard_violations: vec![ReservationSnapshot {
+                port: 8080,
+                project_name: "shop".to_string(),
+                process_name: "node".to_string(),
+                holder: Some("python3".to_string()),
+                conflict: true,
+            }],
+            kills: vec![(3000, "user".to_string(), 7)],
+            scans: 5,
+        };
+        let metrics = render_metrics(&snapshot);
+        for line in [
+            "# TYPE port_kill_port_processes gauge",
+            "port_kill_monitored_ports 3",
+            "port_kill_port_processes{port=\"3000\"} 2",
+            "port_kill_process_cpu_percent{port=\"3000\",pid=\"42\",process=\"node \\\"dev\\\"\"} 12.5",
+            "port_kill_process_memory_bytes{port=\"3000\",pid=\"42\",process=\"node \\\"dev\\\"\"} 1024",
+            "port_kill_guard_violations 1",
+            "port_kill_guard_violation{port=\"8080\",reserved_for=\"shop\",holder=\"python3\"} 1",
+            "# TYPE port_kill_kills_total counter",
+            "port_kill_kills_total{port=\"3000\",killed_by=\"user\"} 7",
+            "port_kill_scans_total 5",
+        ] {
+            assert!(metrics.lines().any(|l| l == line), "missing {}", line);
+        }
+    }
+}
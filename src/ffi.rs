@@ -0,0 +1,127 @@
+//! C ABI for linking port-kill from other languages, built with the `ffi` feature.
+//! The header is generated into `include/port_kill.h` by the build script.
+//!
+//! Strings returned by these functions are owned by the caller and must be released
+//! with `port_kill_string_free`. Functions that fail return NULL or -1; the reason is
+//! then available from `port_kill_last_error`.
+
+use crate::api::PortKill;
+use anyhow::{anyhow, Result};
+use std::cell::RefCell;
+use std::ffi::{c_char, CString};
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::ptr;
+use std::sync::OnceLock;
+use tokio::runtime::Runtime;
+
+thread_local! {
+    static LAST_ERROR: RefCell<Option<CString>> = const { RefCell::new(None) };
+}
+
+fn runtime() -> Result<&'static Runtime> {
+    static RUNTIME: OnceLock<Runtime> = OnceLock::new();
+    if let Some(runtime) = RUNTIME.get() {
+        return Ok(runtime);
+    }
+    let runtime = Runtime::new()?;
+    Ok(RUNTIME.get_or_init(|| runtime))
+}
+
+/// Run `f`, turning errors and panics into `on_error` and the thread's last error
+fn guarded<T>(on_error: T, f: impl FnOnce() -> Result<T>) -> T {
+    let error = match catch_unwind(AssertUnwindSafe(f)) {
+        Ok(Ok(value)) => {
+            LAST_ERROR.with(|last| *last.borrow_mut() = None);
+            return value;
+        }
+        Ok(Err(e)) => e.to_string(),
+        Err(_) => "port-kill panicked".to_string(),
+    };
+    LAST_ERROR.with(|last| {
+        *last.borrow_mut() = Some(CString::new(error.replace('\0', " ")).unwrap_or_default())
+    });
+    on_error
+}
+
+fn into_c_string(value: String) -> Result<*mut c_char> {
+    Ok(CString::new(value)?.into_raw())
+}
+
+/// Scan ports `start_port` to `end_port` (inclusive) and return the processes
+/// listening on them as a JSON array, or NULL on failure.
+#[no_mangle]
+pub extern "C" fn port_kill_scan_json(start_port: u16, end_port: u16) -> *mut c_char {
+    guarded(ptr::null_mut(), || {
+        if start_port > end_port {
+            return Err(anyhow!("start_port is greater than end_port"));
+        }
+        let port_kill = PortKill::builder().ports(start_port..=end_port).build()?;
+        let processes = runtime()?.block_on(port_kill.scan())?;
+        into_c_string(serde_json::to_string(&processes)?)
+    })
+}
+
+/// Kill the process listening on `port`. Returns 1 when a process was killed, 0 when
+/// the port was free and -1 on failure.
+#[no_mangle]
+pub extern "C" fn port_kill_kill_port(port: u16) -> i32 {
+    guarded(-1, || {
+        let port_kill = PortKill::builder().port(port).build()?;
+        let killed = runtime()?.block_on(port_kill.kill_port(port))?;
+        Ok(killed.is_some() as i32)
+    })
+}
+
+/// The error of the last failed call on this thread, or NULL. The string belongs
+/// to port-kill and stays valid until the next call on this thread.
+#[no_mangle]
+pub extern "C" fn port_kill_last_error() -> *const c_char {
+    LAST_ERROR.with(|last| {
+        last.borrow()
+            .as_ref()
+            .map_or(ptr::null(), |error| error.as_ptr())
+    })
+}
+
+/// The port-kill version, e.g. "0.5.41". The string is static and must not be freed.
+#[no_mangle]
+pub extern "C" fn port_kill_version() -> *const c_char {
+    concat!(env!("CARGO_PKG_VERSION"), "\0").as_ptr() as *const c_char
+}
+
+/// Release a string returned by port-kill. NULL is ignored.
+///
+/// # Safety
+///
+/// `value` must be NULL or a string returned by a port-kill function that has not
+/// been freed yet.
+#[no_mangle]
+pub unsafe extern "C" fn port_kill_string_free(value: *mut c_char) {
+    if !value.is_null() {
+        drop(CString::from_raw(value));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::ffi::CStr;
+
+    #[test]
+    fn test_errors_and_strings() {
+        assert!(port_kill_scan_json(4000, 3000).is_null());
+        let error = unsafe { CStr::from_ptr(port_kill_last_error()) };
+        assert_eq!(error.to_str().unwrap(), "start_port is greater than end_port");
+
+        let json = port_kill_scan_json(1, 1);
+        assert!(!json.is_null());
+        assert!(port_kill_last_error().is_null());
+        let processes: Vec<serde_json::Value> =
+            serde_json::from_str(unsafe { CStr::from_ptr(json) }.to_str().unwrap()).unwrap();
+        assert!(processes.is_empty());
+        unsafe { port_kill_string_free(json) };
+
+        let version = unsafe { CStr::from_ptr(port_kill_version()) };
+        assert_eq!(version.to_str().unwrap(), env!("CARGO_PKG_VERSION"));
+    }
+}
@@ -1,4 +1,4 @@
-use crate::types::ProcessInfo;
+use crate::types::{ProcessInfo, Protocol};
 use anyhow::Result;
 use std::path::Path;
 use std::process::Command;
@@ -182,6 +182,9 @@ impl FileMonitor {
                             cpu_usage: None,
                             memory_usage: None,
                             memory_percentage: None,
+                            protocol: Protocol::Tcp,
+                            bind_address: None,
+                            address_family: None,
                         });
                     }
                 }
@@ -270,6 +273,9 @@ impl FileMonitor {
                         cpu_usage: None,
                         memory_usage: None,
                         memory_percentage: None,
+                        protocol: Protocol::Tcp,
+                        bind_address: None,
+                        address_family: None,
                     });
                 }
             }
@@ -318,6 +324,9 @@ impl FileMonitor {
                                 cpu_usage: None,
                                 memory_usage: None,
                                 memory_percentage: None,
+                                protocol: Protocol::Tcp,
+                                bind_address: None,
+                                address_family: None,
                             });
                         }
                     }
@@ -362,6 +371,9 @@ impl FileMonitor {
                                 cpu_usage: None,
                                 memory_usage: None,
                                 memory_percentage: None,
+                                protocol: Protocol::Tcp,
+                                bind_address: None,
+                                address_family: None,
                             });
                         }
                     }
@@ -418,6 +430,9 @@ impl FileMonitor {
                         cpu_usage: None,
                         memory_usage: None,
                         memory_percentage: None,
+                        protocol: Protocol::Tcp,
+                        bind_address: None,
+                        address_family: None,
                     });
                 }
             }
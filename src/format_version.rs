@@ -0,0 +1,136 @@
+use anyhow::{anyhow, Result};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::Value;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// On-disk format versions. Bump one when its layout changes and teach the matching
+/// loader's migration how to upgrade the previous version.
+pub const PRESETS_VERSION: u32 = 2;
+pub const RESERVATIONS_VERSION: u32 = 2;
+pub const RESTART_HISTORY_VERSION: u32 = 2;
+pub const ORCHESTRATION_VERSION: u32 = 1;
+
+/// Data read from a versioned file, along with the version it was written in
+#[derive(Debug)]
+pub struct Versioned<T> {
+    pub data: T,
+    pub version: u32,
+}
+
+/// Parse JSON written as `{"version": N, "<key>": ...}`. Files from before
+/// versioning are the bare payload and count as version 1. Older payloads are
+/// upgraded one version at a time with `migrate(from_version, payload)`.
+pub fn parse_versioned<T: DeserializeOwned>(
+    content: &str,
+    key: &str,
+    current: u32,
+    migrate: impl Fn(u32, Value) -> Result<Value>,
+) -> Result<Versioned<T>> {
+    parse_versioned_value(serde_json::from_str(content)?, key, current, migrate)
+}
+
+/// Same as [`parse_versioned`] for an already parsed document (e.g. YAML)
+pub fn parse_versioned_value<T: DeserializeOwned>(
+    document: Value,
+    key: &str,
+    current: u32,
+    migrate: impl Fn(u32, Value) -> Result<Value>,
+) -> Result<Versioned<T>> {
+    let (version, mut payload) = split_version(document, key);
+    if version > current {
+        return Err(anyhow!(
+            "file uses format version {} but this port-kill only understands up to version {}; please upgrade port-kill",
+            version,
+            current
+        ));
+    }
+    for from in version..current {
+        payload = migrate(from, payload)
+            .map_err(|e| anyhow!("failed to migrate from format version {}: {}", from, e))?;
+    }
+    Ok(Versioned {
+        data: serde_json::from_value(payload)?,
+        version,
+    })
+}
+
+fn split_version(document: Value, key: &str) -> (u32, Value) {
+    match document {
+        Value::Object(mut map) if map.contains_key(key) => {
+            match map.get("version").and_then(Value::as_u64) {
+                Some(version) => (version as u32, map.remove(key).unwrap_or(Value::Null)),
+                None => (1, Value::Object(map)),
+            }
+        }
+        other => (1, other),
+    }
+}
+
+/// Serialize `data` as `{"version": N, "<key>": data}`
+pub fn to_versioned_json<T: Serialize>(data: &T, key: &str, version: u32) -> Result<String> {
+    let mut document = serde_json::Map::new();
+    document.insert("version".to_string(), Value::from(version));
+    document.insert(key.to_string(), serde_json::to_value(data)?);
+    Ok(serde_json::to_string_pretty(&Value::Object(document))?)
+}
+
+/// Migration step for versions that only added the version wrapper
+pub fn unchanged_payload(_from: u32, payload: Value) -> Result<Value> {
+    Ok(payload)
+}
+
+/// Keep a copy of a file in its old format (`<file>.v<N>.bak`) before it gets
+/// rewritten in the current one, so an older port-kill can still be pointed at it
+pub fn backup_old_format(path: &Path, version: u32) -> Result<Option<PathBuf>> {
+    let mut backup = path.as_os_str().to_owned();
+    backup.push(format!(".v{}.bak", version));
+    let backup = PathBuf::from(backup);
+    if backup.exists() || !path.exists() {
+        return Ok(None);
+    }
+    fs::copy(path, &backup)?;
+    Ok(Some(backup))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_versioned_round_trip_and_legacy() {
+        let data: HashMap<String, u16> = [("web".to_string(), 3000)].into_iter().collect();
+        let json = to_versioned_json(&data, "ports", 2).unwrap();
+        let read: Versioned<HashMap<String, u16>> =
+            parse_versioned(&json, "ports", 2, unchanged_payload).unwrap();
+        assert_eq!(read.version, 2);
+        assert_eq!(read.data, data);
+
+        // Files from before versioning are the bare payload
+        let legacy: Versioned<HashMap<String, u16>> =
+            parse_versioned(r#"{"web": 3000}"#, "ports", 2, unchanged_payload).unwrap();
+        assert_eq!(legacy.version, 1);
+        assert_eq!(legacy.data, data);
+
+        let newer = parse_versioned::<HashMap<String, u16>>(
+            r#"{"version": 3, "ports": {}}"#,
+            "ports",
+            2,
+            unchanged_payload,
+        );
+        assert!(newer.unwrap_err().to_string().contains("upgrade"));
+    }
+
+    #[test]
+    fn test_migrations_run_in_order() {
+        let read: Versioned<Vec<u32>> = parse_versioned("[1]", "items", 3, |from, payload| {
+            let mut items: Vec<u32> = serde_json::from_value(payload)?;
+            items.push(from + 1);
+            Ok(serde_json::to_value(items)?)
+        })
+        .unwrap();
+        assert_eq!(read.data, vec![1, 2, 3]);
+    }
+}
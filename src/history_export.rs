@@ -0,0 +1,331 @@
+use crate::cli::{ExportFormat, HistoryExportArgs};
+use crate::history_store::HistoryStore;
+use crate::port_activity::{OccupiedSpans, PortActivityLog};
+use crate::types::{HistoryFilter, ProcessHistoryEntry};
+use anyhow::{anyhow, Context, Result};
+use chrono::{DateTime, Duration, Utc};
+use std::collections::{BTreeMap, HashMap};
+use std::io::Write;
+
+/// Kills per port and process in each interval
+pub const KILLS_METRIC: &str = "port_kill_kills";
+/// Share of each interval a port was occupied, from 0 to 1
+pub const OCCUPIED_METRIC: &str = "port_kill_port_occupied";
+
+/// One data point of an exported series
+#[derive(Debug, Clone, PartialEq)]
+pub struct Sample {
+    pub metric: &'static str,
+    pub port: u16,
+    pub process: Option<String>,
+    /// Start of the interval
+    pub at: DateTime<Utc>,
+    pub value: f64,
+}
+
+/// Data points for each `step` between `since` and `until`, with the interval starts
+/// aligned to multiples of `step` so repeated exports line up. Occupancy is reported
+/// for every interval of a port that was occupied at some point; kills only where
+/// there were any.
+pub fn collect_samples(
+    kills: &[ProcessHistoryEntry],
+    occupied: &HashMap<u16, OccupiedSpans>,
+    since: DateTime<Utc>,
+    until: DateTime<Utc>,
+    step: Duration,
+) -> Vec<Sample> {
+    let step_secs = step.num_seconds().max(1);
+    let first = DateTime::from_timestamp(since.timestamp() - since.timestamp().rem_euclid(step_secs), 0)
+        .unwrap_or(since);
+    let step = Duration::seconds(step_secs);
+    let mut buckets = Vec::new();
+    let mut at = first;
+    while at < until {
+        buckets.push(at);
+        at += step;
+    }
+
+    let mut samples = Vec::new();
+    let mut ports: Vec<&u16> = occupied.keys().collect();
+    ports.sort_unstable();
+    for port in ports {
+        let spans = &occupied[port];
+        if !spans.iter().any(|(start, end)| *start < until && *end > first) {
+            continue;
+        }
+        for bucket in &buckets {
+            let bucket_end = *bucket + step;
+            let seconds: i64 = spans
+                .iter()
+                .map(|(start, end)| {
+                    ((*end).min(bucket_end) - (*start).max(*bucket))
+                        .num_seconds()
+                        .max(0)
+                })
+                .sum();
+            samples.push(Sample {
+                metric: OCCUPIED_METRIC,
+                port: *port,
+                process: None,
+                at: *bucket,
+                value: (seconds as f64 / step_secs as f64).min(1.0),
+            });
+        }
+    }
+
+    let mut kill_counts: BTreeMap<(DateTime<Utc>, u16, String), usize> = BTreeMap::new();
+    for kill in kills {
+        if kill.killed_at < first || kill.killed_at >= until {
+            continue;
+        }
+        let offset = (kill.killed_at - first).num_seconds() / step_secs;
+        let bucket = first + Duration::seconds(offset * step_secs);
+        *kill_counts
+            .entry((bucket, kill.port, kill.process_name.clone()))
+            .or_default() += 1;
+    }
+    samples.extend(
+        kill_counts
+            .into_iter()
+            .map(|((at, port, process), count)| Sample {
+                metric: KILLS_METRIC,
+                port,
+                process: Some(process),
+                at,
+                value: count as f64,
+            }),
+    );
+
+    samples.sort_by(|a, b| {
+        (a.at, a.metric, a.port, &a.process).cmp(&(b.at, b.metric, b.port, &b.process))
+    });
+    samples
+}
+
+/// InfluxDB line protocol, one line per sample
+pub fn to_influx(samples: &[Sample]) -> String {
+    let escape = |value: &str| {
+        value
+            .replace('\\', "\\\\")
+            .replace(',', "\\,")
+            .replace('=', "\\=")
+            .replace(' ', "\\ ")
+    };
+    let mut out = String::new();
+    for sample in samples {
+        out.push_str(sample.metric);
+        out.push_str(&format!(",port={}", sample.port));
+        if let Some(process) = &sample.process {
+            out.push_str(&format!(",process={}", escape(process)));
+        }
+        let value = if sample.metric == KILLS_METRIC {
+            format!("{}i", sample.value as i64)
+        } else {
+            sample.value.to_string()
+        };
+        out.push_str(&format!(
+            " value={} {}\n",
+            value,
+            sample.at.timestamp_nanos_opt().unwrap_or_default()
+        ));
+    }
+    out
+}
+
+pub fn to_csv(samples: &[Sample]) -> String {
+    let quote = |value: &str| {
+        if value.contains([',', '"', '\n']) {
+            format!("\"{}\"", value.replace('"', "\"\""))
+        } else {
+            value.to_string()
+        }
+    };
+    let mut out = String::from("timestamp,metric,port,process,value\n");
+    for sample in samples {
+        out.push_str(&format!(
+            "{},{},{},{},{}\n",
+            sample.at.to_rfc3339(),
+            sample.metric,
+            sample.port,
+            quote(sample.process.as_deref().unwrap_or("")),
+            sample.value
+        ));
+    }
+    out
+}
+
+/// A Prometheus remote-write `WriteRequest`, protobuf-encoded but not yet compressed
+pub fn to_remote_write(samples: &[Sample]) -> Vec<u8> {
+    // Labels sorted by name, as remote write requires: __name__, port, process
+    let mut series: BTreeMap<Vec<(&str, String)>, Vec<&Sample>> = BTreeMap::new();
+    for sample in samples {
+        let mut labels = vec![
+            ("__name__", sample.metric.to_string()),
+            ("port", sample.port.to_string()),
+        ];
+        if let Some(process) = &sample.process {
+            labels.push(("process", process.clone()));
+        }
+        series.entry(labels).or_default().push(sample);
+    }
+
+    let mut request = Vec::new();
+    for (labels, samples) in series {
+        let mut timeseries = Vec::new();
+        for (name, value) in labels {
+            let mut label = Vec::new();
+            put_bytes(&mut label, 1, name.as_bytes());
+            put_bytes(&mut label, 2, value.as_bytes());
+            put_bytes(&mut timeseries, 1, &label);
+        }
+        for sample in samples {
+            let mut encoded = Vec::new();
+            // value: double, field 1
+            encoded.push((1 << 3) | 1);
+            encoded.extend_from_slice(&sample.value.to_le_bytes());
+            // timestamp in milliseconds: int64, field 2
+            encoded.push(2 << 3);
+            put_varint(&mut encoded, sample.at.timestamp_millis() as u64);
+            put_bytes(&mut timeseries, 2, &encoded);
+        }
+        put_bytes(&mut request, 1, &timeseries);
+    }
+    request
+}
+
+fn put_varint(out: &mut Vec<u8>, mut value: u64) {
+    while value >= 0x80 {
+        out.push((value as u8) | 0x80);
+        value >>= 7;
+    }
+    out.push(value as u8);
+}
+
+/// A length-delimited protobuf field
+fn put_bytes(out: &mut Vec<u8>, field: u64, bytes: &[u8]) {
+    put_varint(out, (field << 3) | 2);
+    put_varint(out, bytes.len() as u64);
+    out.extend_from_slice(bytes);
+}
+
+/// `port-kill history export`: print the series, or POST them to `--url`
+pub async fn run_history_export(args: &HistoryExportArgs) -> Result<()> {
+    let now = Utc::now();
+    let since = args.since.start(now);
+    let until = args.until.map_or(now, |until| until.end(now).min(now));
+    if since >= until {
+        return Err(anyhow!("--since is not before --until"));
+    }
+
+    let filter = HistoryFilter {
+        port: args.port,
+        since: Some(since - args.interval),
+        until: Some(until),
+        ..Default::default()
+    };
+    let kills = HistoryStore::open_default()?.kills(&filter)?;
+    let occupied = PortActivityLog::load().occupied_intervals(args.port, until);
+    let samples = collect_samples(
+        kills.get_recent_entries(usize::MAX),
+        &occupied,
+        since,
+        until,
+        args.interval,
+    );
+
+    let (body, content_type) = match args.format {
+        ExportFormat::Influx => (to_influx(&samples).into_bytes(), "text/plain; charset=utf-8"),
+        ExportFormat::Csv => (to_csv(&samples).into_bytes(), "text/csv"),
+        ExportFormat::PrometheusRemoteWrite => (
+            snap::raw::Encoder::new()
+                .compress_vec(&to_remote_write(&samples))
+                .context("Failed to compress the remote-write request")?,
+            "application/x-protobuf",
+        ),
+    };
+
+    let Some(url) = &args.url else {
+        std::io::stdout().write_all(&body)?;
+        return Ok(());
+    };
+    let mut request = reqwest::Client::new()
+        .post(url)
+        .header("Content-Type", content_type)
+        .body(body);
+    if args.format == ExportFormat::PrometheusRemoteWrite {
+        request = request
+            .header("Content-Encoding", "snappy")
+            .header("X-Prometheus-Remote-Write-Version", "0.1.0");
+    }
+    if let Some(auth) = &args.auth {
+        request = request.header("Authorization", auth);
+    }
+    let response = request
+        .send()
+        .await
+        .with_context(|| format!("Failed to send the export to {}", url))?;
+    if !response.status().is_success() {
+        return Err(anyhow!(
+            "{} returned status {}: {}",
+            url,
+            response.status(),
+            response.text().await.unwrap_or_default()
+        ));
+    }
+    eprintln!("📤 Exported {} data points to {}", samples.len(), url);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_export_formats() {
+        let at = |time: &str| -> DateTime<Utc> { time.parse().unwrap() };
+        let since = at("2026-03-02T10:20:00Z");
+        let until = at("2026-03-02T12:00:00Z");
+        // 3000 is held from 10:30 to 11:15
+        let occupied = HashMap::from([(3000, vec![(at("2026-03-02T10:30:00Z"), at("2026-03-02T11:15:00Z"))])]);
+        let kill = ProcessHistoryEntry {
+            pid: 42,
+            port: 3000,
+            process_name: "my node".to_string(),
+            process_group: None,
+            project_name: None,
+            killed_at: at("2026-03-02T11:15:00Z"),
+            killed_by: "user".to_string(),
+            command_line: None,
+            working_directory: None,
+        };
+
+        let samples = collect_samples(&[kill], &occupied, since, until, Duration::hours(1));
+        let points: Vec<(&str, String, f64)> = samples
+            .iter()
+            .map(|s| (s.metric, s.at.format("%H:%M").to_string(), s.value))
+            .collect();
+        assert_eq!(
+            points,
+            vec![
+                (OCCUPIED_METRIC, "10:00".to_string(), 0.5),
+                (KILLS_METRIC, "11:00".to_string(), 1.0),
+                (OCCUPIED_METRIC, "11:00".to_string(), 0.25),
+            ]
+        );
+
+        let influx = to_influx(&samples);
+        assert!(influx.contains("port_kill_kills,port=3000,process=my\\ node value=1i 1772449200000000000\n"));
+        assert!(influx.starts_with("port_kill_port_occupied,port=3000 value=0.5 "));
+        assert!(to_csv(&samples).contains("2026-03-02T11:00:00+00:00,port_kill_kills,3000,my node,1\n"));
+
+        // Two series; the first starts with the __name__ label of the kills
+        let request = to_remote_write(&samples);
+        assert_eq!(request[0], 0x0a);
+        let label = b"\x0a\x08__name__\x12\x0fport_kill_kills";
+        assert!(request.windows(label.len()).any(|window| window == label));
+        assert_eq!(
+            request.windows(b"port_kill_port_occupied".len()).filter(|w| *w == b"port_kill_port_occupied").count(),
+            1
+        );
+    }
+}
@@ -0,0 +1,591 @@
+use crate::cache::output::print_or_json;
+use crate::cli::{HistoryAction, HistoryArgs};
+use crate::types::{HistoryFilter, ProcessHistory, ProcessHistoryEntry};
+use anyhow::{anyhow, Context, Result};
+use chrono::{DateTime, NaiveDate, SecondsFormat, Utc};
+use rusqlite::{params, params_from_iter, Connection, OptionalExtension, Row};
+use std::path::{Path, PathBuf};
+
+/// Schema changes, applied in order; `PRAGMA user_version` records how many ran.
+/// Never edit a released step, add a new one.
+const MIGRATIONS: &[&str] = &[
+    // 1: kills, including the ones guard mode makes, and restarts
+    "CREATE TABLE kills (
+         id INTEGER PRIMARY KEY,
+         pid INTEGER NOT NULL,
+         port INTEGER NOT NULL,
+         process_name TEXT NOT NULL,
+         process_group TEXT,
+         project_name TEXT,
+         killed_at TEXT NOT NULL,
+         killed_by TEXT NOT NULL,
+         command_line TEXT,
+         working_directory TEXT
+     );
+     CREATE INDEX kills_killed_at ON kills (killed_at);
+     CREATE INDEX kills_port ON kills (port, killed_at);
+     CREATE TABLE restarts (
+         id INTEGER PRIMARY KEY,
+         port INTEGER NOT NULL,
+         command TEXT NOT NULL,
+         working_directory TEXT NOT NULL,
+         restarted_at TEXT NOT NULL
+     );
+     CREATE INDEX restarts_port ON restarts (port, restarted_at);",
+    // 2: cache cleanups, for the space reclaimed in reports
+    "CREATE TABLE cache_cleans (
+         id INTEGER PRIMARY KEY,
+         cleaned_at TEXT NOT NULL,
+         freed_bytes INTEGER NOT NULL,
+         deleted_count INTEGER NOT NULL
+     );
+     CREATE INDEX cache_cleans_cleaned_at ON cache_cleans (cleaned_at);",
+];
+
+const KILL_COLUMNS: &str = "pid, port, process_name, process_group, project_name, killed_at, \
+                            killed_by, command_line, working_directory";
+
+/// Kill and restart history in `~/.port-kill/history.db`. Each event is one insert,
+/// and the history views query just the rows they show.
+pub struct HistoryStore {
+    conn: Connection,
+}
+
+/// A restart port-kill ran for a port
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
+pub struct RestartRecord {
+    pub port: u16,
+    pub command: String,
+    pub working_directory: String,
+    pub restarted_at: DateTime<Utc>,
+}
+
+impl HistoryStore {
+    pub fn default_path() -> PathBuf {
+        let home_dir = std::env::var("HOME")
+            .or_else(|_| std::env::var("USERPROFILE"))
+            .unwrap_or_else(|_| ".".to_string());
+        PathBuf::from(home_dir).join(".port-kill").join("history.db")
+    }
+
+    pub fn open_default() -> Result<Self> {
+        Self::open(&Self::default_path())
+    }
+
+    /// Open the store at `path`, creating it when needed. When the default store is
+    /// created, the kills of the old `~/.port-kill-history.json` are imported.
+    pub fn open(path: &Path) -> Result<Self> {
+        let legacy = PathBuf::from(ProcessHistory::get_history_file_path());
+        let legacy = (path == Self::default_path()).then_some(legacy.as_path());
+        Self::open_with_legacy(path, legacy)
+    }
+
+    fn open_with_legacy(path: &Path, legacy: Option<&Path>) -> Result<Self> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let conn = Connection::open(path)
+            .with_context(|| format!("Failed to open history database {}", path.display()))?;
+        // A daemon and a one-shot command may write at the same time
+        conn.busy_timeout(std::time::Duration::from_secs(5))?;
+
+        let mut store = Self { conn };
+        let created = store.migrate()?;
+        if let Some(legacy) = legacy.filter(|legacy| created && legacy.exists()) {
+            match store.import_legacy(legacy) {
+                Ok(count) if count > 0 => {
+                    log::info!("Imported {} kills from {}", count, legacy.display())
+                }
+                Ok(_) => {}
+                Err(e) => log::warn!("Could not import {}: {}", legacy.display(), e),
+            }
+        }
+        Ok(store)
+    }
+
+    /// Apply the migrations that have not run yet. Returns true when the database was new.
+    fn migrate(&mut self) -> Result<bool> {
+        let version: usize = self
+            .conn
+            .query_row("PRAGMA user_version", [], |row| row.get(0))?;
+        if version > MIGRATIONS.len() {
+            return Err(anyhow!(
+                "history database uses schema version {} but this port-kill only understands up to version {}; please upgrade port-kill",
+                version,
+                MIGRATIONS.len()
+            ));
+        }
+
+        let tx = self.conn.transaction()?;
+        for (index, migration) in MIGRATIONS.iter().enumerate().skip(version) {
+            tx.execute_batch(migration)
+                .with_context(|| format!("History database migration {} failed", index + 1))?;
+        }
+        tx.pragma_update(None, "user_version", MIGRATIONS.len())?;
+        tx.commit()?;
+        Ok(version == 0)
+    }
+
+    fn import_legacy(&mut self, legacy: &Path) -> Result<usize> {
+        let history = ProcessHistory::load_from_file(&legacy.to_string_lossy(), usize::MAX)
+            .map_err(|e| anyhow!("{}", e))?;
+        let entries = history.get_recent_entries(usize::MAX);
+        let tx = self.conn.transaction()?;
+        for entry in entries {
+            insert_kill(&tx, entry)?;
+        }
+        tx.commit()?;
+        Ok(entries.len())
+    }
+
+    pub fn record_kill(&self, entry: &ProcessHistoryEntry) -> Result<()> {
+        insert_kill(&self.conn, entry)
+    }
+
+    pub fn record_restart(
+        &self,
+        port: u16,
+        command: &[String],
+        working_directory: &str,
+    ) -> Result<()> {
+        self.conn.execute(
+            "INSERT INTO restarts (port, command, working_directory, restarted_at)
+             VALUES (?1, ?2, ?3, ?4)",
+            params![
+                port,
+                crate::command_line::quote_command_line(command),
+                working_directory,
+                timestamp(Utc::now())
+            ],
+        )?;
+        Ok(())
+    }
+
+    pub fn record_cache_clean(&self, freed_bytes: u64, deleted_count: usize) -> Result<()> {
+        self.conn.execute(
+            "INSERT INTO cache_cleans (cleaned_at, freed_bytes, deleted_count) VALUES (?1, ?2, ?3)",
+            params![timestamp(Utc::now()), freed_bytes as i64, deleted_count as i64],
+        )?;
+        Ok(())
+    }
+
+    /// The kills `filter` selects, oldest first
+    pub fn kills(&self, filter: &HistoryFilter) -> Result<ProcessHistory> {
+        let (clause, values) = filter_clause(filter, "killed_at");
+        let mut statement = self.conn.prepare(&format!(
+            "SELECT {} FROM kills{} ORDER BY killed_at, id",
+            KILL_COLUMNS, clause
+        ))?;
+        let entries = statement
+            .query_map(params_from_iter(values), kill_from_row)?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        Ok(ProcessHistory::from_entries(entries))
+    }
+
+    /// The last `limit` kills, oldest first
+    pub fn recent_kills(&self, limit: usize) -> Result<Vec<ProcessHistoryEntry>> {
+        let mut statement = self.conn.prepare(&format!(
+            "SELECT * FROM (SELECT {}, id FROM kills ORDER BY killed_at DESC, id DESC LIMIT ?1)
+             ORDER BY killed_at, id",
+            KILL_COLUMNS
+        ))?;
+        let entries = statement
+            .query_map([limit as i64], kill_from_row)?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        Ok(entries)
+    }
+
+    /// The restarts `filter` selects, oldest first
+    pub fn restarts(&self, filter: &HistoryFilter) -> Result<Vec<RestartRecord>> {
+        let (clause, values) = filter_clause(filter, "restarted_at");
+        let mut statement = self.conn.prepare(&format!(
+            "SELECT port, command, working_directory, restarted_at FROM restarts{}
+             ORDER BY restarted_at, id",
+            clause
+        ))?;
+        let records = statement
+            .query_map(params_from_iter(values), |row| {
+                Ok(RestartRecord {
+                    port: row.get(0)?,
+                    command: row.get(1)?,
+                    working_directory: row.get(2)?,
+                    restarted_at: parse_timestamp(row, 3)?,
+                })
+            })?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        Ok(records)
+    }
+
+    /// When `port` was last killed, if ever
+    pub fn last_kill(&self, port: u16) -> Result<Option<DateTime<Utc>>> {
+        let killed_at: Option<String> = self
+            .conn
+            .query_row(
+                "SELECT MAX(killed_at) FROM kills WHERE port = ?1",
+                [port],
+                |row| row.get(0),
+            )
+            .optional()?
+            .flatten();
+        Ok(killed_at.and_then(|at| at.parse().ok()))
+    }
+
+    /// Kills per port and per who made them (`user`, `guard`, ...), by port
+    pub fn kill_counts(&self) -> Result<Vec<(u16, String, u64)>> {
+        let mut statement = self.conn.prepare(
+            "SELECT port, killed_by, COUNT(*) FROM kills GROUP BY port, killed_by ORDER BY port, killed_by",
+        )?;
+        let counts = statement
+            .query_map([], |row| {
+                Ok((row.get::<_, u16>(0)?, row.get(1)?, row.get::<_, i64>(2)? as u64))
+            })?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        Ok(counts)
+    }
+
+    /// Number of kills recorded
+    pub fn kill_total(&self) -> Result<u64> {
+        let total: i64 = self
+            .conn
+            .query_row("SELECT COUNT(*) FROM kills", [], |row| row.get(0))?;
+        Ok(total as u64)
+    }
+
+    /// Kills per UTC day from `since` on, oldest first; days without kills are left out
+    pub fn kills_per_day(&self, since: DateTime<Utc>) -> Result<Vec<(NaiveDate, u64)>> {
+        let mut statement = self.conn.prepare(
+            "SELECT substr(killed_at, 1, 10) AS day, COUNT(*) FROM kills
+             WHERE killed_at >= ?1 GROUP BY day ORDER BY day",
+        )?;
+        let days = statement
+            .query_map([timestamp(since)], |row| {
+                Ok((row.get::<_, String>(0)?, row.get::<_, i64>(1)? as u64))
+            })?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        days.into_iter()
+            .map(|(day, kills)| {
+                let date = day
+                    .parse()
+                    .with_context(|| format!("Invalid kill date {}", day))?;
+                Ok((date, kills))
+            })
+            .collect()
+    }
+
+    /// The `limit` most killed ports as (port, kills), busiest first
+    pub fn top_ports(&self, limit: usize) -> Result<Vec<(u16, u64)>> {
+        let mut statement = self.conn.prepare(
+            "SELECT port, COUNT(*) AS kills FROM kills GROUP BY port
+             ORDER BY kills DESC, port LIMIT ?1",
+        )?;
+        let ports = statement
+            .query_map([limit as i64], |row| {
+                Ok((row.get::<_, u16>(0)?, row.get::<_, i64>(1)? as u64))
+            })?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        Ok(ports)
+    }
+
+    /// Number of cache cleanups between `since` and `until`, and the bytes they freed
+    pub fn cache_cleans(&self, since: DateTime<Utc>, until: DateTime<Utc>) -> Result<(usize, u64)> {
+        let (count, freed): (i64, i64) = self.conn.query_row(
+            "SELECT COUNT(*), COALESCE(SUM(freed_bytes), 0) FROM cache_cleans
+             WHERE cleaned_at >= ?1 AND cleaned_at < ?2",
+            params![timestamp(since), timestamp(until)],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )?;
+        Ok((count as usize, freed as u64))
+    }
+
+    pub fn clear_kills(&self) -> Result<()> {
+        self.conn.execute("DELETE FROM kills", [])?;
+        Ok(())
+    }
+}
+
+fn insert_kill(conn: &Connection, entry: &ProcessHistoryEntry) -> Result<()> {
+    conn.execute(
+        &format!(
+            "INSERT INTO kills ({}) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+            KILL_COLUMNS
+        ),
+        params![
+            entry.pid,
+            entry.port,
+            entry.process_name,
+            entry.process_group,
+            entry.project_name,
+            timestamp(entry.killed_at),
+            entry.killed_by,
+            entry.command_line,
+            entry.working_directory,
+        ],
+    )?;
+    Ok(())
+}
+
+fn kill_from_row(row: &Row) -> rusqlite::Result<ProcessHistoryEntry> {
+    Ok(ProcessHistoryEntry {
+        pid: row.get(0)?,
+        port: row.get(1)?,
+        process_name: row.get(2)?,
+        process_group: row.get(3)?,
+        project_name: row.get(4)?,
+        killed_at: parse_timestamp(row, 5)?,
+        killed_by: row.get(6)?,
+        command_line: row.get(7)?,
+        working_directory: row.get(8)?,
+    })
+}
+
+/// `port-kill history`: the kills or restarts the filters select, oldest first
+pub async fn run_history_query(args: &HistoryArgs) -> Result<()> {
+    if let Some(HistoryAction::Export(export)) = &args.action {
+        return crate::history_export::run_history_export(export).await;
+    }
+    let filter = args.filter().map_err(|e| anyhow!(e))?;
+    let store = HistoryStore::open_default()?;
+    let skip = |len: usize| args.limit.map_or(0, |limit| len.saturating_sub(limit));
+
+    if args.restarts {
+        let mut restarts = store.restarts(&filter)?;
+        restarts.drain(..skip(restarts.len()));
+        if args.json {
+            print_or_json(&restarts, true);
+        } else if restarts.is_empty() {
+            println!("{}", no_matches("restarts", &filter));
+        } else {
+            println!("{:<17} {:>6}  {:<30} COMMAND", "RESTARTED", "PORT", "DIRECTORY");
+            for restart in &restarts {
+                println!(
+                    "{:<17} {:>6}  {:<30} {}",
+                    local_time(restart.restarted_at),
+                    restart.port,
+                    restart.working_directory,
+                    restart.command
+                );
+            }
+        }
+        return Ok(());
+    }
+
+    let history = store.kills(&filter)?;
+    let kills = history.get_recent_entries(args.limit.unwrap_or(usize::MAX));
+    if args.json {
+        print_or_json(&kills, true);
+    } else if kills.is_empty() {
+        println!("{}", no_matches("kills", &filter));
+    } else {
+        println!(
+            "{:<17} {:>6}  {:>8}  {:<20} {:<20} KILLED BY",
+            "KILLED", "PORT", "PID", "PROCESS", "PROJECT"
+        );
+        for kill in kills {
+            println!(
+                "{:<17} {:>6}  {:>8}  {:<20} {:<20} {}",
+                local_time(kill.killed_at),
+                kill.port,
+                kill.pid,
+                kill.process_name,
+                kill.project_name.as_deref().unwrap_or("-"),
+                kill.killed_by
+            );
+        }
+        if kills.len() < history.len() {
+            println!("({} of {} shown)", kills.len(), history.len());
+        }
+    }
+    Ok(())
+}
+
+fn no_matches(what: &str, filter: &HistoryFilter) -> String {
+    if filter.is_empty() {
+        format!("📋 No {} recorded", what)
+    } else {
+        format!("ℹ️  No {} match {}", what, filter)
+    }
+}
+
+fn local_time(at: DateTime<Utc>) -> String {
+    at.with_timezone(&chrono::Local)
+        .format("%Y-%m-%d %H:%M")
+        .to_string()
+}
+
+/// Fixed-width UTC timestamps, so comparing the text compares the times
+fn timestamp(at: DateTime<Utc>) -> String {
+    at.to_rfc3339_opts(SecondsFormat::Micros, true)
+}
+
+fn parse_timestamp(row: &Row, index: usize) -> rusqlite::Result<DateTime<Utc>> {
+    let text: String = row.get(index)?;
+    text.parse().map_err(|e| {
+        rusqlite::Error::FromSqlConversionFailure(index, rusqlite::types::Type::Text, Box::new(e))
+    })
+}
+
+/// ` WHERE ...` for `filter` on the timestamp `column`, and its parameters. The
+/// process and project only apply to kills, since restarts don't record them.
+fn filter_clause(filter: &HistoryFilter, column: &str) -> (String, Vec<String>) {
+    let mut conditions = Vec::new();
+    let mut values = Vec::new();
+    if let Some(port) = filter.port {
+        values.push(port.to_string());
+        conditions.push(format!("port = ?{}", values.len()));
+    }
+    if let Some(since) = filter.since {
+        values.push(timestamp(since));
+        conditions.push(format!("{} >= ?{}", column, values.len()));
+    }
+    if let Some(until) = filter.until {
+        values.push(timestamp(until));
+        conditions.push(format!("{} < ?{}", column, values.len()));
+    }
+    if column == "killed_at" {
+        if let Some(process) = &filter.process {
+            values.push(process.clone());
+            conditions.push(format!("process_name = ?{} COLLATE NOCASE", values.len()));
+        }
+        if let Some(project) = &filter.project {
+            values.push(project.clone());
+            conditions.push(format!("project_name = ?{}", values.len()));
+        }
+    }
+    if conditions.is_empty() {
+        (String::new(), values)
+    } else {
+        (format!(" WHERE {}", conditions.join(" AND ")), values)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_path(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!(
+            "port-kill-history-{}-{}.db",
+            name,
+            std::process::id()
+        ))
+    }
+
+    fn kill(port: u16, killed_at: &str) -> ProcessHistoryEntry {
+        ProcessHistoryEntry {
+            pid: 42,
+            port,
+            process_name: "node".to_string(),
+            process_group: Some("Node.js".to_string()),
+            project_name: Some("shop".to_string()),
+            killed_at: killed_at.parse().unwrap(),
+            killed_by: "user".to_string(),
+            command_line: None,
+            working_directory: Some("/work/shop".to_string()),
+        }
+    }
+
+    #[test]
+    fn test_kills_are_filtered_in_sql() {
+        let path = temp_path("filter");
+        let _ = std::fs::remove_file(&path);
+        let store = HistoryStore::open(&path).unwrap();
+        for (port, killed_at) in [
+            (3000, "2026-03-01T09:00:00Z"),
+            (3000, "2026-03-05T23:30:00Z"),
+            (8080, "2026-03-05T10:00:00Z"),
+            (3000, "2026-03-06T00:10:00Z"),
+        ] {
+            store.record_kill(&kill(port, killed_at)).unwrap();
+        }
+
+        assert_eq!(store.kills(&HistoryFilter::default()).unwrap().len(), 4);
+        let filter = HistoryFilter::days(
+            Some(3000),
+            "2026-03-02".parse().ok(),
+            "2026-03-05".parse().ok(),
+        );
+        let kills = store.kills(&filter).unwrap();
+        assert_eq!(kills.len(), 1);
+        assert_eq!(
+            kills.get_recent_entries(1)[0].killed_at,
+            "2026-03-05T23:30:00Z".parse::<DateTime<Utc>>().unwrap()
+        );
+
+        let filter = HistoryFilter {
+            process: Some("NODE".to_string()),
+            project: Some("shop".to_string()),
+            since: "2026-03-05T12:00:00Z".parse().ok(),
+            ..Default::default()
+        };
+        assert_eq!(store.kills(&filter).unwrap().len(), 2);
+        let filter = HistoryFilter {
+            project: Some("blog".to_string()),
+            ..Default::default()
+        };
+        assert!(store.kills(&filter).unwrap().is_empty());
+
+        assert_eq!(
+            store.kill_counts().unwrap(),
+            vec![(3000, "user".to_string(), 3), (8080, "user".to_string(), 1)]
+        );
+
+        let recent = store.recent_kills(2).unwrap();
+        assert_eq!(recent.len(), 2);
+        assert_eq!(recent[1].port, 3000);
+        assert_eq!(recent[0].port, 3000);
+        assert_eq!(
+            store.last_kill(8080).unwrap(),
+            "2026-03-05T10:00:00Z".parse().ok()
+        );
+        assert_eq!(store.last_kill(5432).unwrap(), None);
+
+        assert_eq!(store.kill_total().unwrap(), 4);
+        assert_eq!(
+            store.kills_per_day("2026-03-02T00:00:00Z".parse().unwrap()).unwrap(),
+            vec![
+                ("2026-03-05".parse().unwrap(), 2),
+                ("2026-03-06".parse().unwrap(), 1)
+            ]
+        );
+        assert_eq!(store.top_ports(1).unwrap(), vec![(3000, 3)]);
+
+        store.clear_kills().unwrap();
+        assert!(store.kills(&HistoryFilter::default()).unwrap().is_empty());
+
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_migrations_run_once_and_import_legacy_history() {
+        let path = temp_path("migrate");
+        let legacy = path.with_extension("json");
+        let _ = std::fs::remove_file(&path);
+        let mut history = ProcessHistory::new(10);
+        history.add_entry(kill(3000, "2026-03-01T09:00:00Z"));
+        std::fs::write(
+            &legacy,
+            serde_json::to_string(history.get_recent_entries(10)).unwrap(),
+        )
+        .unwrap();
+
+        let store = HistoryStore::open_with_legacy(&path, Some(&legacy)).unwrap();
+        store
+            .record_restart(3000, &["npm".to_string(), "run".to_string(), "dev".to_string()], "/work/shop")
+            .unwrap();
+        drop(store);
+
+        // Reopening neither migrates nor imports again
+        let store = HistoryStore::open_with_legacy(&path, Some(&legacy)).unwrap();
+        assert_eq!(store.kills(&HistoryFilter::default()).unwrap().len(), 1);
+        let restarts = store.restarts(&HistoryFilter::default()).unwrap();
+        assert_eq!(restarts.len(), 1);
+        assert_eq!(restarts[0].command, "npm run dev");
+
+        store.conn.pragma_update(None, "user_version", 99).unwrap();
+        drop(store);
+        assert!(HistoryStore::open(&path).is_err());
+
+        let _ = std::fs::remove_file(&path);
+        let _ = std::fs::remove_file(&legacy);
+    }
+}
@@ -0,0 +1,374 @@
+//! JavaScript for `--script` and `--script-file`, run by an embedded QuickJS. Scripts
+//! drive port-kill through host functions (`onPort`, `kill`, `clearPort`,
+//! `guardPort`, ...); `onPort` callbacks are called with the process whenever one
+//! starts or changes on their port.
+
+use crate::cli::Args;
+use crate::file_monitor::FileMonitor;
+use crate::process_monitor::{get_processes_on_ports, kill_all_processes, kill_single_process};
+use crate::types::ProcessInfo;
+use anyhow::{anyhow, Result};
+use rquickjs::function::{Opt, Rest};
+use rquickjs::{CatchResultExt, CaughtError, Context, Ctx, Function, Object, Runtime, Value};
+use std::cell::{Ref, RefCell};
+use std::collections::{BTreeSet, HashMap};
+use std::rc::Rc;
+use std::time::Duration;
+
+/// `onPort` keeps its callbacks on the JS side, so Rust only hands processes to
+/// `__dispatch`. `callback` is what older scripts pass as a placeholder
+/// (`onPort(3000, callback)`): it logs the process, as those scripts always did.
+const PRELUDE: &str = r#"
+const __handlers = {};
+globalThis.onPort = (port, handler) => {
+    if (typeof handler !== "function") {
+        throw new TypeError("onPort(port, callback): callback must be a function");
+    }
+    __watchPort(port);
+    (__handlers[port] ??= []).push(handler);
+};
+globalThis.__dispatch = (port, process) => {
+    for (const handler of __handlers[port] ?? []) {
+        handler(process);
+    }
+};
+globalThis.callback = (p) => log(`🔍 Process detected on port ${p.port}: ${p.name} (PID: ${p.pid})`);
+globalThis.console = { log, info: log, warn: log, error: log };
+"#;
+
+/// What a script asked for through the host functions
+#[derive(Debug, Default)]
+pub struct ScriptRegistrations {
+    /// Ports with an `onPort` callback
+    pub watched_ports: BTreeSet<u16>,
+    /// `guardPort` calls: the only process name allowed on the port, if any
+    pub port_guards: HashMap<u16, Option<String>>,
+    /// `guardFile` calls: the only process name allowed to open the file, if any
+    pub file_guards: HashMap<String, Option<String>>,
+}
+
+pub struct JsRuntime {
+    runtime: Runtime,
+    context: Context,
+    registrations: Rc<RefCell<ScriptRegistrations>>,
+}
+
+impl JsRuntime {
+    /// A fresh interpreter with the host functions installed; `args` picks the
+    /// monitored ports and the processes kills must leave alone
+    pub fn new(args: &Args) -> Result<Self> {
+        let runtime = Runtime::new().map_err(|e| anyhow!("Failed to start JavaScript runtime: {}", e))?;
+        let context =
+            Context::full(&runtime).map_err(|e| anyhow!("Failed to start JavaScript runtime: {}", e))?;
+        let registrations = Rc::new(RefCell::new(ScriptRegistrations::default()));
+        context.with(|ctx| {
+            install_host_functions(&ctx, args, &registrations)
+                .and_then(|_| ctx.eval::<(), _>(PRELUDE))
+                .catch(&ctx)
+                .map_err(|e| anyhow!("Failed to set up script functions: {}", e))
+        })?;
+        Ok(Self {
+            runtime,
+            context,
+            registrations,
+        })
+    }
+
+    /// Run a whole script, including the promise jobs it queued
+    pub fn eval(&self, script: &str) -> Result<()> {
+        self.context.with(|ctx| {
+            ctx.eval::<Value, _>(script)
+                .map(|_| ())
+                .catch(&ctx)
+                .map_err(|e| anyhow!("Script error: {}", e))
+        })?;
+        self.run_pending_jobs()
+    }
+
+    /// Call the `onPort` callbacks registered for `process.port`
+    pub fn dispatch(&self, process: &ProcessInfo) -> Result<()> {
+        self.context.with(|ctx| {
+            let dispatch: Function = ctx.globals().get("__dispatch").catch(&ctx).map_err(|e| anyhow!("{}", e))?;
+            process_object(&ctx, process)
+                .and_then(|object| dispatch.call::<_, ()>((process.port, object)))
+                .catch(&ctx)
+                .map_err(|e| anyhow!("onPort({}) callback failed: {}", process.port, e))
+        })?;
+        self.run_pending_jobs()
+    }
+
+    pub fn registrations(&self) -> Ref<'_, ScriptRegistrations> {
+        self.registrations.borrow()
+    }
+
+    fn run_pending_jobs(&self) -> Result<()> {
+        loop {
+            match self.runtime.execute_pending_job() {
+                Ok(true) => continue,
+                Ok(false) => return Ok(()),
+                Err(job) => {
+                    return Err(job.0.with(|ctx| {
+                        anyhow!("Script error: {}", CaughtError::from_error(&ctx, rquickjs::Error::Exception))
+                    }))
+                }
+            }
+        }
+    }
+}
+
+/// The process as scripts see it: `{ pid, port, name, command, ... }`
+fn process_object<'js>(ctx: &Ctx<'js>, process: &ProcessInfo) -> rquickjs::Result<Object<'js>> {
+    let object = Object::new(ctx.clone())?;
+    object.set("pid", process.pid)?;
+    object.set("port", process.port)?;
+    object.set("name", process.name.as_str())?;
+    object.set("command", process.command.as_str())?;
+    object.set("commandLine", process.command_line.as_deref())?;
+    object.set("workingDirectory", process.working_directory.as_deref())?;
+    object.set("projectName", process.project_name.as_deref())?;
+    object.set("containerName", process.container_name.as_deref())?;
+    object.set("cpu", process.cpu_usage)?;
+    object.set("memory", process.memory_usage)?;
+    Ok(object)
+}
+
+/// Strings as they are, everything else as JSON (`log({ a: 1 })` prints `{"a":1}`)
+fn display<'js>(ctx: &Ctx<'js>, value: Value<'js>) -> String {
+    if let Some(string) = value.as_string() {
+        return string.to_string().unwrap_or_default();
+    }
+    match ctx.json_stringify(value) {
+        Ok(Some(json)) => json.to_string().unwrap_or_default(),
+        _ => "undefined".to_string(),
+    }
+}
+
+fn kill_pids(processes: Vec<ProcessInfo>, args: &Args) -> usize {
+    let mut killed = 0;
+    for process in processes {
+        println!("  Killing process: {} (PID: {})", process.name, process.pid);
+        match kill_single_process(process.pid, args) {
+            Ok(()) => {
+                println!("✅ Successfully killed process {}", process.pid);
+                killed += 1;
+            }
+            Err(e) => println!("❌ Failed to kill process {}: {}", process.pid, e),
+        }
+    }
+    killed
+}
+
+fn install_host_functions<'js>(
+    ctx: &Ctx<'js>,
+    args: &Args,
+    registrations: &Rc<RefCell<ScriptRegistrations>>,
+) -> rquickjs::Result<()> {
+    let globals = ctx.globals();
+
+    globals.set(
+        "log",
+        Function::new(ctx.clone(), |ctx: Ctx<'js>, values: Rest<Value<'js>>| {
+            let parts: Vec<String> = values.0.into_iter().map(|value| display(&ctx, value)).collect();
+            println!("📝 LOG: {}", parts.join(" "));
+        })?,
+    )?;
+
+    let watched = Rc::clone(registrations);
+    globals.set(
+        "__watchPort",
+        Function::new(ctx.clone(), move |port: u16| {
+            if watched.borrow_mut().watched_ports.insert(port) {
+                println!("📌 Registered handler for port {}", port);
+            }
+        })?,
+    )?;
+
+    let kill_args = args.clone();
+    globals.set(
+        "kill",
+        Function::new(ctx.clone(), move |pid: i32| {
+            println!("🔪 Killing process with PID: {}", pid);
+            match kill_single_process(pid, &kill_args) {
+                Ok(()) => {
+                    println!("✅ Successfully killed process PID {}", pid);
+                    true
+                }
+                Err(e) => {
+                    println!("❌ Failed to kill process PID {}: {}", pid, e);
+                    false
+                }
+            }
+        })?,
+    )?;
+
+    let clear_args = args.clone();
+    globals.set(
+        "clearPort",
+        Function::new(ctx.clone(), move |port: u16| {
+            println!("🧹 Clearing all processes on port {}", port);
+            match kill_all_processes(&[port], &clear_args) {
+                Ok(()) => {
+                    println!("✅ Successfully cleared processes on port {}", port);
+                    true
+                }
+                Err(e) => {
+                    println!("❌ Failed to clear processes on port {}: {}", port, e);
+                    false
+                }
+            }
+        })?,
+    )?;
+
+    let guards = Rc::clone(registrations);
+    globals.set(
+        "guardPort",
+        Function::new(ctx.clone(), move |port: u16, allowed: Opt<String>| {
+            match allowed.0.as_deref() {
+                Some(name) => println!("🛡️  Guarding port {} - only allowing '{}'", port, name),
+                None => println!("🛡️  Guarding port {} - killing all processes", port),
+            }
+            guards.borrow_mut().port_guards.insert(port, allowed.0);
+        })?,
+    )?;
+
+    let ports = args.get_ports_to_monitor();
+    globals.set(
+        "listPorts",
+        Function::new(ctx.clone(), move || ports.clone())?,
+    )?;
+
+    let lookup_args = Args {
+        verbose: false,
+        ..args.clone()
+    };
+    globals.set(
+        "getProcess",
+        Function::new(ctx.clone(), move |ctx: Ctx<'js>, port: u16| {
+            let (_, processes) = get_processes_on_ports(&[port], &lookup_args);
+            processes
+                .get(&port)
+                .map(|process| process_object(&ctx, process))
+                .transpose()
+        })?,
+    )?;
+
+    globals.set(
+        "wait",
+        Function::new(ctx.clone(), |seconds: f64| {
+            std::thread::sleep(Duration::from_secs_f64(seconds.max(0.0)));
+        })?,
+    )?;
+
+    let file_args = args.clone();
+    globals.set(
+        "killFile",
+        Function::new(ctx.clone(), move |path: String| {
+            println!("🔪 Killing all processes with file '{}' open", path);
+            let processes = FileMonitor::new().find_processes_with_file(&path).unwrap_or_default();
+            kill_pids(processes, &file_args)
+        })?,
+    )?;
+
+    let extension_args = args.clone();
+    globals.set(
+        "killFileExt",
+        Function::new(ctx.clone(), move |extension: String| {
+            println!("🔪 Killing all processes with '{}' files open", extension);
+            let processes = FileMonitor::new()
+                .find_processes_with_extension(&extension)
+                .unwrap_or_default();
+            kill_pids(processes, &extension_args)
+        })?,
+    )?;
+
+    globals.set(
+        "listFileProcesses",
+        Function::new(ctx.clone(), |ctx: Ctx<'js>, path: String| {
+            let processes = FileMonitor::new().find_processes_with_file(&path).unwrap_or_default();
+            processes
+                .iter()
+                .map(|process| process_object(&ctx, process))
+                .collect::<rquickjs::Result<Vec<_>>>()
+        })?,
+    )?;
+
+    let file_guards = Rc::clone(registrations);
+    globals.set(
+        "guardFile",
+        Function::new(ctx.clone(), move |path: String, allowed: Opt<String>| {
+            match allowed.0.as_deref() {
+                Some(name) => println!("🛡️  Guarding file '{}' - only allowing '{}'", path, name),
+                None => println!("🛡️  Guarding file '{}' - killing all processes that open it", path),
+            }
+            file_guards.borrow_mut().file_guards.insert(path, allowed.0);
+        })?,
+    )?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::Parser;
+
+    fn process(port: u16, name: &str) -> ProcessInfo {
+        ProcessInfo {
+            pid: 4242,
+            port,
+            command: name.to_string(),
+            name: name.to_string(),
+            container_id: None,
+            container_name: None,
+            command_line: None,
+            working_directory: None,
+            process_group: None,
+            project_name: None,
+            cpu_usage: None,
+            memory_usage: None,
+            memory_percentage: None,
+            protocol: crate::types::Protocol::Tcp,
+            bind_address: None,
+            address_family: None,
+        }
+    }
+
+    #[test]
+    fn test_callbacks_and_registrations() {
+        let args = Args::parse_from(["port-kill", "--ports", "3000"]);
+        let runtime = JsRuntime::new(&args).unwrap();
+        runtime
+            .eval(
+                r#"
+                const seen = [];
+                onPort(3000, p => { if (p.name !== "node") seen.push(p.pid) });
+                guardPort(8080, "nginx");
+                guardPort(9000);
+                globalThis.check = () => seen.join(",");
+                "#,
+            )
+            .unwrap();
+
+        {
+            let registrations = runtime.registrations();
+            assert_eq!(registrations.watched_ports, BTreeSet::from([3000]));
+            assert_eq!(registrations.port_guards[&8080], Some("nginx".to_string()));
+            assert_eq!(registrations.port_guards[&9000], None);
+        }
+
+        runtime.dispatch(&process(3000, "node")).unwrap();
+        runtime.dispatch(&process(3000, "python")).unwrap();
+        runtime.dispatch(&process(5000, "python")).unwrap();
+        let seen: String = runtime
+            .context
+            .with(|ctx| ctx.eval("check()"))
+            .unwrap();
+        assert_eq!(seen, "4242");
+
+        assert!(runtime.eval("onPort(3000)").is_err());
+        assert!(runtime.eval("let broken = ;").is_err());
+        runtime.eval("onPort(4000, p => { throw new Error('boom') })").unwrap();
+        let error = runtime.dispatch(&process(4000, "node")).unwrap_err();
+        assert!(error.to_string().contains("boom"), "{}", error);
+    }
+}
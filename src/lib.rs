@@ -1,24 +1,63 @@
+pub mod anomaly;
+pub mod api;
+pub mod bench;
+pub mod branch_ports;
 pub mod cache;
 pub mod cli;
 pub mod command_line;
+pub mod completions;
+pub mod config_reload;
 pub mod console_app;
+pub mod daemon;
+pub mod dashboard;
+pub mod docker_containers;
+pub mod doctor;
 pub mod endpoint_monitor;
+#[cfg(feature = "ffi")]
+pub mod ffi;
 pub mod file_monitor;
+pub mod format_version;
+pub mod history_export;
+pub mod history_store;
+pub mod js_runtime;
+pub mod logging;
+pub mod login_item;
+pub mod machine;
+pub mod notify;
 pub mod orchestrator;
+pub mod port_activity;
 pub mod port_guard;
+pub mod port_wait;
+pub mod preflight;
 pub mod preset_manager;
+pub mod preset_wizard;
 pub mod process_monitor;
+pub mod project_dir;
+pub mod project_env;
+pub mod proxy;
+pub mod quick_actions;
+pub mod report;
+pub mod rest_api;
 pub mod restart_manager;
+pub mod scan_interval;
 pub mod scripting;
 pub mod security_audit;
 pub mod service_detector;
+pub mod service_picker;
 pub mod smart_filter;
 pub mod system_monitor;
+pub mod tmux;
+pub mod tray_icon_image;
+pub mod tray_status;
+pub mod tui;
 pub mod types;
 pub mod update_check;
+pub mod user_config;
 
 // macOS-specific modules (only compiled on macOS)
 #[cfg(target_os = "macos")]
 pub mod app;
 #[cfg(target_os = "macos")]
 pub mod tray_menu;
+
+pub use api::{PortEvent, PortKill, PortKillBuilder};
@@ -0,0 +1,132 @@
+//! Log setup shared by the binaries. `tracing` events, and `log` records from code
+//! that still uses it, go to stderr and, with `--log-file`, to a rotating file, as
+//! text or JSON lines.
+
+use crate::cli::{Args, LogFormat, LogRotation};
+use anyhow::{anyhow, Context, Result};
+use std::io::IsTerminal;
+use std::path::PathBuf;
+use tracing_appender::non_blocking::WorkerGuard;
+use tracing_appender::rolling::{RollingFileAppender, Rotation};
+use tracing_subscriber::layer::SubscriberExt;
+use tracing_subscriber::util::SubscriberInitExt;
+use tracing_subscriber::{fmt, EnvFilter, Layer, Registry};
+
+/// Rotated log files kept next to the current one
+const MAX_LOG_FILES: usize = 7;
+
+type BoxedLayer = Box<dyn Layer<Registry> + Send + Sync>;
+
+/// Install the global logger. `RUST_LOG` picks the levels, falling back to
+/// `--verbose` and then `--log-level`. Keep the returned guard alive until exit, or
+/// the last lines written to the log file may be lost.
+pub fn init(args: &Args) -> Result<Option<WorkerGuard>> {
+    let default_level = if args.verbose {
+        "debug"
+    } else {
+        args.log_level.to_rust_log()
+    };
+    let filter =
+        EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new(default_level));
+
+    let mut layers: Vec<BoxedLayer> = Vec::new();
+    // Lines on stderr would draw over the TUI; it logs to --log-file only
+    if !args.tui {
+        layers.push(match args.log_format {
+            LogFormat::Text => fmt::layer()
+                .with_ansi(std::io::stderr().is_terminal())
+                .with_writer(std::io::stderr)
+                .boxed(),
+            LogFormat::Json => fmt::layer().json().with_writer(std::io::stderr).boxed(),
+        });
+    }
+
+    let mut guard = None;
+    if let Some(ref path) = args.log_file {
+        let (writer, worker) = tracing_appender::non_blocking(file_appender(path, args.log_rotation)?);
+        layers.push(match args.log_format {
+            LogFormat::Text => fmt::layer().with_ansi(false).with_writer(writer).boxed(),
+            LogFormat::Json => fmt::layer().json().with_writer(writer).boxed(),
+        });
+        guard = Some(worker);
+    }
+
+    tracing_subscriber::registry()
+        .with(layers)
+        .with(filter)
+        .try_init()
+        .map_err(|e| anyhow!("Failed to set up logging: {}", e))?;
+    Ok(guard)
+}
+
+/// Appender for `path`; rotated files get the date (and hour) before the
+/// extension, e.g. `port-kill.2025-01-31.log`
+fn file_appender(path: &str, rotation: LogRotation) -> Result<RollingFileAppender> {
+    let path = expand_home(path);
+    let directory = path
+        .parent()
+        .filter(|parent| !parent.as_os_str().is_empty())
+        .map(PathBuf::from)
+        .unwrap_or_else(|| PathBuf::from("."));
+    let prefix = path
+        .file_stem()
+        .and_then(|stem| stem.to_str())
+        .ok_or_else(|| anyhow!("--log-file needs a file name: {}", path.display()))?;
+
+    let mut builder = RollingFileAppender::builder()
+        .rotation(match rotation {
+            LogRotation::Hourly => Rotation::HOURLY,
+            LogRotation::Daily => Rotation::DAILY,
+            LogRotation::Never => Rotation::NEVER,
+        })
+        .filename_prefix(prefix)
+        .max_log_files(MAX_LOG_FILES);
+    if let Some(extension) = path.extension().and_then(|extension| extension.to_str()) {
+        builder = builder.filename_suffix(extension);
+    }
+    std::fs::create_dir_all(&directory)
+        .with_context(|| format!("Failed to create {}", directory.display()))?;
+    builder
+        .build(&directory)
+        .with_context(|| format!("Failed to open log file {}", path.display()))
+}
+
+fn expand_home(path: &str) -> PathBuf {
+    let home = std::env::var("HOME").or_else(|_| std::env::var("USERPROFILE"));
+    match (path.strip_prefix("~/"), home) {
+        (Some(rest), Ok(home)) => PathBuf::from(home).join(rest),
+        _ => PathBuf::from(path),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    #[test]
+    fn test_file_appender_names() {
+        let dir = std::env::temp_dir().join(format!("port-kill-logging-{}", std::process::id()));
+        let path = dir.join("logs").join("port-kill.log");
+
+        let mut appender = file_appender(path.to_str().unwrap(), LogRotation::Never).unwrap();
+        appender.write_all(b"started\n").unwrap();
+        appender.flush().unwrap();
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "started\n");
+
+        let mut appender = file_appender(path.to_str().unwrap(), LogRotation::Daily).unwrap();
+        appender.write_all(b"rotated\n").unwrap();
+        appender.flush().unwrap();
+        let mut names: Vec<String> = std::fs::read_dir(dir.join("logs"))
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().to_string_lossy().into_owned())
+            .collect();
+        names.sort();
+        assert_eq!(names.len(), 2);
+        let dated = &names[0];
+        assert!(dated.starts_with("port-kill.20") && dated.ends_with(".log"), "{}", dated);
+        assert_eq!(std::fs::read_to_string(dir.join("logs").join(dated)).unwrap(), "rotated\n");
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,254 @@
+use anyhow::{Context, Result};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// launchd label of the tray app's login item
+pub const LAUNCH_AGENT_LABEL: &str = "com.treadiehq.port-kill";
+
+/// Flags that manage the login item and must not end up in the command it runs
+const LOGIN_ITEM_FLAGS: [&str; 2] = ["--install-login-item", "--remove-login-item"];
+
+/// `~/Library/LaunchAgents/<label>.plist`
+pub fn launch_agent_path() -> PathBuf {
+    let home = std::env::var("HOME").unwrap_or_else(|_| ".".to_string());
+    PathBuf::from(home)
+        .join("Library/LaunchAgents")
+        .join(format!("{}.plist", LAUNCH_AGENT_LABEL))
+}
+
+/// `$XDG_CONFIG_HOME/autostart/port-kill.desktop`, `~/.config/autostart/...` by default
+pub fn autostart_entry_path() -> PathBuf {
+    let config_home = std::env::var("XDG_CONFIG_HOME")
+        .ok()
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .unwrap_or_else(|| {
+            let home = std::env::var("HOME").unwrap_or_else(|_| ".".to_string());
+            PathBuf::from(home).join(".config")
+        });
+    config_home.join("autostart").join("port-kill.desktop")
+}
+
+/// The file that registers the login item on this platform
+fn login_item_path() -> PathBuf {
+    if cfg!(target_os = "macos") {
+        launch_agent_path()
+    } else {
+        autostart_entry_path()
+    }
+}
+
+pub fn is_installed() -> bool {
+    (cfg!(target_os = "macos") || cfg!(target_os = "linux")) && login_item_path().is_file()
+}
+
+/// The arguments this instance was started with, minus the login item flags, so the
+/// tray comes back at login with the same ports and options
+pub fn current_arguments() -> Vec<String> {
+    std::env::args()
+        .skip(1)
+        .filter(|arg| !LOGIN_ITEM_FLAGS.contains(&arg.as_str()))
+        .collect()
+}
+
+#[cfg_attr(not(target_os = "macos"), allow(dead_code))]
+fn escape_xml(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// LaunchAgent that starts `program` with `args` when the user logs in
+#[cfg_attr(not(target_os = "macos"), allow(dead_code))]
+fn launch_agent_plist(program: &Path, args: &[String]) -> String {
+    let arguments: String = std::iter::once(program.to_string_lossy().into_owned())
+        .chain(args.iter().cloned())
+        .map(|arg| format!("        <string>{}</string>\n", escape_xml(&arg)))
+        .collect();
+    format!(
+        r#"<?xml version="1.0" encoding="UTF-8"?>
+<!DOCTYPE plist PUBLIC "-//Apple//DTD PLIST 1.0//EN" "http://www.apple.com/DTDs/PropertyList-1.0.dtd">
+<plist version="1.0">
+<dict>
+    <key>Label</key>
+    <string>{}</string>
+    <key>ProgramArguments</key>
+    <array>
+{}    </array>
+    <key>RunAtLoad</key>
+    <true/>
+    <key>ProcessType</key>
+    <string>Interactive</string>
+</dict>
+</plist>
+"#,
+        LAUNCH_AGENT_LABEL, arguments
+    )
+}
+
+/// Quote `arg` for the `Exec` key of a desktop entry, which has its own rules: reserved
+/// characters need double quotes, and `"`, `` ` ``, `$` and `\` a backslash inside them
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+fn quote_exec_arg(arg: &str) -> String {
+    let arg = arg.replace('%', "%%");
+    let reserved = |c: char| c.is_whitespace() || "\"'\\><~|&;$*?#()`".contains(c);
+    if !arg.is_empty() && !arg.contains(reserved) {
+        return arg;
+    }
+    let mut quoted = String::from("\"");
+    for c in arg.chars() {
+        if matches!(c, '"' | '`' | '$' | '\\') {
+            quoted.push('\\');
+        }
+        quoted.push(c);
+    }
+    quoted.push('"');
+    quoted
+}
+
+/// XDG autostart entry that starts `program` with `args` when the user logs in
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+fn autostart_entry(program: &Path, args: &[String]) -> String {
+    let exec = std::iter::once(program.to_string_lossy().into_owned())
+        .chain(args.iter().cloned())
+        .map(|arg| quote_exec_arg(&arg))
+        .collect::<Vec<_>>()
+        .join(" ")
+        // Backslashes are unescaped once more when the value is read as a string
+        .replace('\\', "\\\\");
+    format!(
+        "[Desktop Entry]\n\
+         Type=Application\n\
+         Name=Port Kill\n\
+         Comment=Monitor and free development ports\n\
+         Exec={}\n\
+         Terminal=false\n\
+         X-GNOME-Autostart-enabled=true\n",
+        exec
+    )
+}
+
+/// Start the tray app with `args` at every login. Returns the file that registers it.
+/// A LaunchAgent stands in for SMAppService, which only registers signed app bundles.
+#[cfg(target_os = "macos")]
+pub fn install(args: &[String]) -> Result<PathBuf> {
+    let program = std::env::current_exe().context("Failed to locate the port-kill binary")?;
+    write_login_item(&launch_agent_path(), &launch_agent_plist(&program, args))
+}
+
+/// The tray binary for `exe`: `port-kill` next to it when `exe` is `port-kill-console`
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+fn tray_binary(exe: &Path) -> PathBuf {
+    if exe.file_name().is_some_and(|name| name == "port-kill-console") {
+        exe.with_file_name("port-kill")
+    } else {
+        exe.to_path_buf()
+    }
+}
+
+/// Start the tray app with `args` at every login. Returns the file that registers it.
+/// `port-kill-console --install-login-item` registers the tray binary installed beside it.
+#[cfg(target_os = "linux")]
+pub fn install(args: &[String]) -> Result<PathBuf> {
+    let exe = std::env::current_exe().context("Failed to locate the port-kill binary")?;
+    let program = tray_binary(&exe);
+    if !program.is_file() {
+        anyhow::bail!(
+            "The port-kill tray binary was not found at {}",
+            program.display()
+        );
+    }
+    write_login_item(&autostart_entry_path(), &autostart_entry(&program, args))
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "linux")))]
+pub fn install(_args: &[String]) -> Result<PathBuf> {
+    anyhow::bail!("Start at login is only available on macOS and Linux")
+}
+
+#[cfg_attr(not(any(target_os = "macos", target_os = "linux")), allow(dead_code))]
+fn write_login_item(path: &Path, contents: &str) -> Result<PathBuf> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).with_context(|| format!("Failed to create {}", dir.display()))?;
+    }
+    fs::write(path, contents).with_context(|| format!("Failed to write {}", path.display()))?;
+    Ok(path.to_path_buf())
+}
+
+/// Stop starting the tray app at login. Returns false when it was not registered.
+pub fn remove() -> Result<bool> {
+    let path = login_item_path();
+    if !is_installed() {
+        return Ok(false);
+    }
+    fs::remove_file(&path).with_context(|| format!("Failed to remove {}", path.display()))?;
+    Ok(true)
+}
+
+/// Handle `--install-login-item` / `--remove-login-item`
+pub fn run_login_item_command(install_item: bool) -> Result<()> {
+    if install_item {
+        let path = install(&current_arguments())?;
+        println!("✅ port-kill will start at login ({})", path.display());
+    } else if remove()? {
+        println!("✅ port-kill will no longer start at login");
+    } else {
+        println!("ℹ️  port-kill is not set to start at login");
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_launch_agent_plist() {
+        let plist = launch_agent_plist(
+            Path::new("/Applications/Port Kill/port-kill"),
+            &[
+                "--ports".to_string(),
+                "3000,8000".to_string(),
+                "--ignore-processes".to_string(),
+                "a&b".to_string(),
+            ],
+        );
+        assert!(plist.contains("<string>com.treadiehq.port-kill</string>"));
+        assert!(plist.contains(
+            "        <string>/Applications/Port Kill/port-kill</string>\n        <string>--ports</string>\n        <string>3000,8000</string>\n"
+        ));
+        assert!(plist.contains("<string>a&amp;b</string>"));
+        assert!(plist.contains("<key>RunAtLoad</key>\n    <true/>"));
+    }
+
+    #[test]
+    fn test_autostart_entry() {
+        let entry = autostart_entry(
+            Path::new("/opt/port kill/port-kill"),
+            &[
+                "--ports".to_string(),
+                "3000,8000".to_string(),
+                "--ignore-processes".to_string(),
+                "say \"hi\" $USER 100%".to_string(),
+            ],
+        );
+        assert!(entry.starts_with("[Desktop Entry]\nType=Application\n"));
+        assert!(entry.contains(
+            "\nExec=\"/opt/port kill/port-kill\" --ports 3000,8000 --ignore-processes \"say \\\\\"hi\\\\\" \\\\$USER 100%%\"\n"
+        ));
+        assert!(entry.contains("\nX-GNOME-Autostart-enabled=true\n"));
+    }
+
+    #[test]
+    fn test_tray_binary() {
+        assert_eq!(
+            tray_binary(Path::new("/usr/local/bin/port-kill-console")),
+            PathBuf::from("/usr/local/bin/port-kill")
+        );
+        assert_eq!(
+            tray_binary(Path::new("/usr/local/bin/port-kill")),
+            PathBuf::from("/usr/local/bin/port-kill")
+        );
+    }
+}
@@ -0,0 +1,768 @@
+use crate::cli::Args;
+use crate::daemon::Daemon;
+use crate::dashboard::{guard_events, reservation_snapshots, ReservationSnapshot};
+use crate::port_guard::{
+    is_port_available, read_reservation_file, release_port_in_file, reserve_port_in_file,
+};
+use crate::process_monitor::{get_processes_on_ports, kill_all_processes, kill_single_process};
+use crate::types::{PortReservation, ProcessInfo, Protocol};
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use log::warn;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::io::{AsyncBufReadExt, AsyncRead, AsyncWrite, AsyncWriteExt, BufReader};
+use tokio::sync::mpsc;
+
+/// How often subscribed clients get port and guard changes
+const EVENT_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Ports after a conflicted one tried when suggesting another port
+const ALTERNATIVE_PORT_ATTEMPTS: u16 = 100;
+
+// JSON-RPC 2.0 error codes
+const PARSE_ERROR: i64 = -32700;
+const INVALID_REQUEST: i64 = -32600;
+pub(crate) const METHOD_NOT_FOUND: i64 = -32601;
+const INVALID_PARAMS: i64 = -32602;
+/// The method ran but failed (e.g., the process could not be killed)
+const OPERATION_FAILED: i64 = -32000;
+
+/// Event streams a client can subscribe to
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+struct Subscriptions {
+    /// `port.opened` / `port.closed`
+    ports: bool,
+    /// `guard.event`
+    guard: bool,
+    /// `conflict.detected` / `conflict.resolved`
+    conflicts: bool,
+}
+
+impl Subscriptions {
+    fn any(&self) -> bool {
+        self.ports || self.guard || self.conflicts
+    }
+}
+
+/// Who a reserved port belongs to
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ReservedFor {
+    pub project: String,
+    pub process: String,
+}
+
+/// The process holding a port
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct PortHolder {
+    pub pid: i32,
+    pub name: String,
+}
+
+/// Something an editor can offer to resolve a conflict. `kill` carries the request
+/// to send back to port-kill as is.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+pub enum SuggestedAction {
+    Kill {
+        pid: i32,
+        label: String,
+        request: Value,
+    },
+    UsePort {
+        port: u16,
+        label: String,
+    },
+}
+
+/// A reserved port taken by another process, with what to do about it. Sent as
+/// `conflict.detected`.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PortConflictNotice {
+    pub port: u16,
+    pub at: DateTime<Utc>,
+    pub holder: Option<PortHolder>,
+    pub reserved_for: Option<ReservedFor>,
+    pub suggested_actions: Vec<SuggestedAction>,
+}
+
+/// Kill the holder (when there is one) and move to `alternative` (when one is free)
+fn suggested_actions(holder: Option<&PortHolder>, alternative: Option<u16>) -> Vec<SuggestedAction> {
+    let mut actions = Vec::new();
+    if let Some(holder) = holder {
+        actions.push(SuggestedAction::Kill {
+            pid: holder.pid,
+            label: format!("Kill {} (PID {})", holder.name, holder.pid),
+            request: json!({ "jsonrpc": "2.0", "method": "kill", "params": { "pid": holder.pid } }),
+        });
+    }
+    if let Some(port) = alternative {
+        actions.push(SuggestedAction::UsePort {
+            port,
+            label: format!("Use port {} instead", port),
+        });
+    }
+    actions
+}
+
+/// First port after `port` that nothing listens on and nobody reserved
+async fn alternative_port(port: u16, reserved: &HashSet<u16>) -> Option<u16> {
+    let last = port.saturating_add(ALTERNATIVE_PORT_ATTEMPTS);
+    for candidate in port.saturating_add(1)..=last {
+        if !reserved.contains(&candidate) && is_port_available(candidate).await {
+            return Some(candidate);
+        }
+    }
+    None
+}
+
+/// Reserved ports held by a process other than the one they are reserved for, keyed
+/// by port with the holder
+fn reservation_conflicts(
+    reservations: &HashMap<u16, PortReservation>,
+    processes: &HashMap<u16, ProcessInfo>,
+) -> HashMap<u16, PortHolder> {
+    reservation_snapshots(reservations, processes)
+        .into_iter()
+        .filter(|snapshot| snapshot.conflict)
+        .filter_map(|snapshot| {
+            let process = processes.get(&snapshot.port)?;
+            Some((
+                snapshot.port,
+                PortHolder {
+                    pid: process.pid,
+                    name: process.name.clone(),
+                },
+            ))
+        })
+        .collect()
+}
+
+/// Conflicts that appeared (or changed holder) and ports whose conflict cleared
+fn conflict_changes(
+    previous: &HashMap<u16, PortHolder>,
+    current: &HashMap<u16, PortHolder>,
+) -> (Vec<u16>, Vec<u16>) {
+    let mut detected: Vec<u16> = current
+        .iter()
+        .filter(|(port, holder)| previous.get(port) != Some(holder))
+        .map(|(port, _)| *port)
+        .collect();
+    let mut resolved: Vec<u16> = previous
+        .keys()
+        .filter(|port| !current.contains_key(port))
+        .copied()
+        .collect();
+    detected.sort_unstable();
+    resolved.sort_unstable();
+    (detected, resolved)
+}
+
+/// `conflict.detected` notices for `ports`, with suggested actions
+async fn conflict_notices(
+    ports: &[u16],
+    conflicts: &HashMap<u16, PortHolder>,
+    reservations: &HashMap<u16, PortReservation>,
+) -> Vec<PortConflictNotice> {
+    let reserved: HashSet<u16> = reservations.keys().copied().collect();
+    let mut notices = Vec::new();
+    for &port in ports {
+        let holder = conflicts.get(&port).cloned();
+        let alternative = alternative_port(port, &reserved).await;
+        notices.push(PortConflictNotice {
+            port,
+            at: Utc::now(),
+            suggested_actions: suggested_actions(holder.as_ref(), alternative),
+            holder,
+            reserved_for: reservations.get(&port).map(|reservation| ReservedFor {
+                project: reservation.project_name.clone(),
+                process: reservation.process_name.clone(),
+            }),
+        });
+    }
+    notices
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct RpcError {
+    code: i64,
+    message: String,
+}
+
+impl RpcError {
+    pub(crate) fn new(code: i64, message: impl Into<String>) -> Self {
+        Self {
+            code,
+            message: message.into(),
+        }
+    }
+
+    pub(crate) fn invalid_params(e: impl std::fmt::Display) -> Self {
+        Self::new(INVALID_PARAMS, format!("Invalid params: {}", e))
+    }
+
+    pub(crate) fn failed(e: impl std::fmt::Display) -> Self {
+        Self::new(OPERATION_FAILED, e.to_string())
+    }
+
+    pub(crate) fn message(&self) -> &str {
+        &self.message
+    }
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct ListParams {
+    ports: Option<Vec<u16>>,
+    /// Scan now instead of answering from the daemon's last scan
+    #[serde(default)]
+    fresh: bool,
+    /// Include processes bound to UDP ports; defaults to the server's `--udp`
+    udp: Option<bool>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct KillParams {
+    port: Option<u16>,
+    pid: Option<i32>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ReserveParams {
+    port: u16,
+    project: String,
+    process: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ReleaseParams {
+    port: u16,
+}
+
+#[derive(Debug, Deserialize)]
+struct CheckParams {
+    port: u16,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct SubscribeParams {
+    /// "ports", "guard" and/or "conflicts"; all of them when omitted
+    events: Option<Vec<String>>,
+}
+
+pub(crate) fn params<T: for<'de> Deserialize<'de> + Default>(params: Value) -> Result<T, RpcError> {
+    if params.is_null() {
+        return Ok(T::default());
+    }
+    serde_json::from_value(params).map_err(RpcError::invalid_params)
+}
+
+pub(crate) fn required_params<T: for<'de> Deserialize<'de>>(params: Value) -> Result<T, RpcError> {
+    serde_json::from_value(params).map_err(RpcError::invalid_params)
+}
+
+fn response(id: Value, result: Result<Value, RpcError>) -> Value {
+    match result {
+        Ok(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+        Err(error) => json!({
+            "jsonrpc": "2.0",
+            "id": id,
+            "error": { "code": error.code, "message": error.message },
+        }),
+    }
+}
+
+fn notification(method: &str, params: Value) -> Value {
+    json!({ "jsonrpc": "2.0", "method": method, "params": params })
+}
+
+fn sorted_processes(processes: HashMap<u16, ProcessInfo>) -> Vec<ProcessInfo> {
+    let mut list: Vec<ProcessInfo> = processes.into_values().collect();
+    list.sort_by_key(|process| process.port);
+    list
+}
+
+fn read_reservations(args: &Args) -> HashMap<u16, PortReservation> {
+    read_reservation_file(Path::new(&args.get_reservation_file_path()))
+        .map(|loaded| loaded.data)
+        .unwrap_or_default()
+}
+
+/// Run one method. Blocking scans and kills happen on the blocking pool. Methods this
+/// module does not know go to the daemon, when serving one.
+async fn call(
+    args: &Args,
+    subscriptions: &Mutex<Subscriptions>,
+    daemon: Option<&Daemon>,
+    method: &str,
+    params_value: Value,
+) -> Result<Value, RpcError> {
+    match method {
+        "list" => {
+            let ListParams { ports, fresh, udp } = params(params_value)?;
+            let udp = udp.unwrap_or(args.udp);
+            // The daemon's scan only has UDP sockets when it runs with --udp itself
+            if let Some(daemon) = daemon.filter(|_| !fresh && (args.udp || !udp)) {
+                if let Some((mut processes, scanned_at)) = daemon.cached_processes(ports.as_deref()).await {
+                    if !udp {
+                        processes.retain(|_, process| process.protocol == Protocol::Tcp);
+                    }
+                    return Ok(json!({
+                        "processes": sorted_processes(processes),
+                        "scannedAt": scanned_at,
+                    }));
+                }
+            }
+            let mut args = args.clone();
+            args.udp = udp;
+            let processes = tokio::task::spawn_blocking(move || {
+                let ports = ports.unwrap_or_else(|| args.get_ports_to_monitor());
+                get_processes_on_ports(&ports, &args).1
+            })
+            .await
+            .map_err(RpcError::failed)?;
+            Ok(json!({ "processes": sorted_processes(processes), "scannedAt": Utc::now() }))
+        }
+        "kill" => {
+            let KillParams { port, pid } = params(params_value)?;
+            let args = args.clone();
+            let killed = tokio::task::spawn_blocking(move || match (port, pid) {
+                (_, Some(pid)) => kill_single_process(pid, &args).map(|()| vec![pid]),
+                (Some(port), None) => {
+                    let (_, processes) = get_processes_on_ports(&[port], &args);
+                    kill_all_processes(&[port], &args)?;
+                    Ok(processes.values().map(|process| process.pid).collect())
+                }
+                (None, None) => Err(anyhow::anyhow!("kill needs a port or a pid")),
+            })
+            .await
+            .map_err(RpcError::failed)?
+            .map_err(RpcError::failed)?;
+            if let Some(daemon) = daemon {
+                daemon.ports_changed().await;
+            }
+            Ok(json!({ "killed": killed }))
+        }
+        "guard.status" => {
+            let args = args.clone();
+            let reservations = tokio::task::spawn_blocking(move || {
+                let ports = args.get_ports_to_monitor();
+                let (_, processes) = get_processes_on_ports(&ports, &args);
+                reservation_snapshots(&read_reservations(&args), &processes)
+            })
+            .await
+            .map_err(RpcError::failed)?;
+            Ok(json!({ "reservations": reservations }))
+        }
+        "port.check" => {
+            let CheckParams { port } = required_params(params_value)?;
+            let args = args.clone();
+            let (processes, reservations) = tokio::task::spawn_blocking(move || {
+                (get_processes_on_ports(&[port], &args).1, read_reservations(&args))
+            })
+            .await
+            .map_err(RpcError::failed)?;
+
+            let holder = processes.get(&port).map(|process| PortHolder {
+                pid: process.pid,
+                name: process.name.clone(),
+            });
+            let reserved_for = reservations.get(&port).map(|reservation| ReservedFor {
+                project: reservation.project_name.clone(),
+                process: reservation.process_name.clone(),
+            });
+            let free = holder.is_none() && reserved_for.is_none();
+            let actions = if free {
+                Vec::new()
+            } else {
+                let reserved: HashSet<u16> = reservations.keys().copied().collect();
+                suggested_actions(holder.as_ref(), alternative_port(port, &reserved).await)
+            };
+            Ok(json!({
+                "port": port,
+                "free": free,
+                "holder": holder,
+                "reservedFor": reserved_for,
+                "suggestedActions": actions,
+            }))
+        }
+        "guard.reserve" => {
+            let ReserveParams {
+                port,
+                project,
+                process,
+            } = required_params(params_value)?;
+            let process = process.unwrap_or_else(|| project.clone());
+            reserve_port_in_file(
+                Path::new(&args.get_reservation_file_path()),
+                port,
+                &project,
+                &process,
+            )
+            .map_err(RpcError::failed)?;
+            Ok(json!({ "reserved": port }))
+        }
+        "guard.release" => {
+            let ReleaseParams { port } = required_params(params_value)?;
+            let released = release_port_in_file(Path::new(&args.get_reservation_file_path()), port)
+                .map_err(RpcError::failed)?;
+            Ok(json!({ "released": released }))
+        }
+        "subscribe" | "unsubscribe" => {
+            let SubscribeParams { events } = params(params_value)?;
+            let events = events.unwrap_or_else(|| {
+                ["ports", "guard", "conflicts"].map(String::from).to_vec()
+            });
+            let on = method == "subscribe";
+            let mut subscriptions = subscriptions.lock().unwrap();
+            for event in &events {
+                match event.as_str() {
+                    "ports" => subscriptions.ports = on,
+                    "guard" => subscriptions.guard = on,
+                    "conflicts" => subscriptions.conflicts = on,
+                    other => {
+                        return Err(RpcError::invalid_params(format!(
+                            "unknown event stream '{}' (expected \"ports\", \"guard\" or \"conflicts\")",
+                            other
+                        )))
+                    }
+                }
+            }
+            let mut active = Vec::new();
+            if subscriptions.ports {
+                active.push("ports");
+            }
+            if subscriptions.guard {
+                active.push("guard");
+            }
+            if subscriptions.conflicts {
+                active.push("conflicts");
+            }
+            Ok(json!({ "subscribed": active }))
+        }
+        "shutdown" => Ok(Value::Null),
+        _ => match daemon {
+            Some(daemon) => daemon.call(method, params_value).await,
+            None => Err(RpcError::new(
+                METHOD_NOT_FOUND,
+                format!("Method '{}' not found", method),
+            )),
+        },
+    }
+}
+
+/// Answer one line of input. Returns the response (none for notifications) and whether
+/// the client asked to shut down.
+async fn handle_line(
+    args: &Args,
+    subscriptions: &Mutex<Subscriptions>,
+    daemon: Option<&Daemon>,
+    line: &str,
+) -> (Option<Value>, bool) {
+    let request: Value = match serde_json::from_str(line) {
+        Ok(request) => request,
+        Err(e) => {
+            let error = RpcError::new(PARSE_ERROR, format!("Parse error: {}", e));
+            return (Some(response(Value::Null, Err(error))), false);
+        }
+    };
+    let id = request.get("id").cloned();
+    let Some(method) = request.get("method").and_then(Value::as_str) else {
+        let error = RpcError::new(INVALID_REQUEST, "Invalid request: missing method");
+        return (Some(response(id.unwrap_or(Value::Null), Err(error))), false);
+    };
+    let params = request.get("params").cloned().unwrap_or(Value::Null);
+
+    let result = call(args, subscriptions, daemon, method, params).await;
+    let shutdown = method == "shutdown";
+    (id.map(|id| response(id, result)), shutdown)
+}
+
+/// `port.opened` / `port.closed` notifications for what changed between two scans
+fn port_events(previous: &HashMap<u16, ProcessInfo>, current: &HashMap<u16, ProcessInfo>) -> Vec<Value> {
+    let mut events = Vec::new();
+    let mut ports: Vec<u16> = previous.keys().chain(current.keys()).copied().collect();
+    ports.sort_unstable();
+    ports.dedup();
+    for port in ports {
+        match (previous.get(&port), current.get(&port)) {
+            (None, Some(process)) => events.push(notification("port.opened", json!(process))),
+            (Some(process), None) => events.push(notification(
+                "port.closed",
+                json!({ "port": port, "pid": process.pid, "name": process.name }),
+            )),
+            // Another process took the port between scans
+            (Some(old), Some(new)) if old.pid != new.pid => {
+                events.push(notification(
+                    "port.closed",
+                    json!({ "port": port, "pid": old.pid, "name": old.name }),
+                ));
+                events.push(notification("port.opened", json!(new)));
+            }
+            _ => {}
+        }
+    }
+    events
+}
+
+/// Scan on an interval while anything is subscribed and send the changes to `out`
+async fn stream_events(
+    args: Args,
+    subscriptions: Arc<Mutex<Subscriptions>>,
+    out: mpsc::UnboundedSender<Value>,
+) {
+    let mut previous: Option<Scan> = None;
+    loop {
+        tokio::time::sleep(EVENT_INTERVAL).await;
+        let subscribed = *subscriptions.lock().unwrap();
+        if !subscribed.any() {
+            previous = None;
+            continue;
+        }
+
+        let scan_args = args.clone();
+        let scanned = tokio::task::spawn_blocking(move || {
+            let ports = scan_args.get_ports_to_monitor();
+            let (_, processes) = get_processes_on_ports(&ports, &scan_args);
+            let reservations = read_reservations(&scan_args);
+            Scan {
+                snapshots: reservation_snapshots(&reservations, &processes),
+                conflicts: reservation_conflicts(&reservations, &processes),
+                processes,
+                reservations,
+            }
+        })
+        .await;
+        let scan = match scanned {
+            Ok(scan) => scan,
+            Err(e) => {
+                warn!("Machine mode scan failed: {}", e);
+                continue;
+            }
+        };
+
+        // The first scan after subscribing is the baseline; clients call "list" for it
+        if let Some(ref old) = previous {
+            let mut events = Vec::new();
+            if subscribed.ports {
+                events.extend(port_events(&old.processes, &scan.processes));
+            }
+            if subscribed.guard {
+                events.extend(
+                    guard_events(&old.snapshots, &scan.snapshots, Utc::now())
+                        .into_iter()
+                        .map(|event| notification("guard.event", json!(event))),
+                );
+            }
+            if subscribed.conflicts {
+                let (detected, resolved) = conflict_changes(&old.conflicts, &scan.conflicts);
+                for notice in conflict_notices(&detected, &scan.conflicts, &scan.reservations).await {
+                    events.push(notification("conflict.detected", json!(notice)));
+                }
+                for port in resolved {
+                    events.push(notification(
+                        "conflict.resolved",
+                        json!({ "port": port, "at": Utc::now() }),
+                    ));
+                }
+            }
+            for event in events {
+                if out.send(event).is_err() {
+                    return;
+                }
+            }
+        }
+        previous = Some(scan);
+    }
+}
+
+/// What one event scan saw
+struct Scan {
+    processes: HashMap<u16, ProcessInfo>,
+    reservations: HashMap<u16, PortReservation>,
+    snapshots: Vec<ReservationSnapshot>,
+    conflicts: HashMap<u16, PortHolder>,
+}
+
+/// `--machine`: newline-delimited JSON-RPC 2.0 on stdin/stdout for editor plugins and
+/// wrappers. Logs go to stderr; stdout only carries responses and notifications.
+pub async fn run_machine(args: Args) -> Result<()> {
+    serve(args, tokio::io::stdin(), tokio::io::stdout(), None).await
+}
+
+/// Answer requests from `input` on `output` until the input ends or the client sends
+/// `shutdown`: stdin/stdout for `--machine`, or one connection to `port-kill daemon`
+pub(crate) async fn serve<R, W>(
+    args: Args,
+    input: R,
+    mut output: W,
+    daemon: Option<Arc<Daemon>>,
+) -> Result<()>
+where
+    R: AsyncRead + Unpin,
+    W: AsyncWrite + Unpin + Send + 'static,
+{
+    let (out, mut outgoing) = mpsc::unbounded_channel::<Value>();
+    let writer = tokio::spawn(async move {
+        while let Some(message) = outgoing.recv().await {
+            let line = format!("{}\n", message);
+            if output.write_all(line.as_bytes()).await.is_err() || output.flush().await.is_err() {
+                break;
+            }
+        }
+    });
+
+    let subscriptions = Arc::new(Mutex::new(Subscriptions::default()));
+    let events = tokio::spawn(stream_events(
+        args.clone(),
+        subscriptions.clone(),
+        out.clone(),
+    ));
+
+    let mut lines = BufReader::new(input).lines();
+    while let Some(line) = lines.next_line().await? {
+        if line.trim().is_empty() {
+            continue;
+        }
+        let (reply, shutdown) = handle_line(&args, &subscriptions, daemon.as_deref(), &line).await;
+        if let Some(reply) = reply {
+            let _ = out.send(reply);
+        }
+        if shutdown {
+            break;
+        }
+    }
+
+    events.abort();
+    drop(out);
+    let _ = writer.await;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::Parser;
+
+    fn process(port: u16, pid: i32) -> ProcessInfo {
+        ProcessInfo {
+            pid,
+            port,
+            command: "node".to_string(),
+            name: "node".to_string(),
+            container_id: None,
+            container_name: None,
+            command_line: None,
+            working_directory: None,
+            process_group: None,
+            project_name: None,
+            cpu_usage: None,
+            memory_usage: None,
+            memory_percentage: None,
+            protocol: crate::types::Protocol::Tcp,
+            bind_address: None,
+            address_family: None,
+        }
+    }
+
+    #[test]
+    fn test_port_events() {
+        let previous = HashMap::from([(3000, process(3000, 10)), (4000, process(4000, 20))]);
+        let current = HashMap::from([(3000, process(3000, 11)), (5000, process(5000, 30))]);
+        let methods: Vec<(String, u64)> = port_events(&previous, &current)
+            .iter()
+            .map(|event| {
+                (
+                    event["method"].as_str().unwrap().to_string(),
+                    event["params"]["port"].as_u64().unwrap(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            methods,
+            vec![
+                ("port.closed".to_string(), 3000),
+                ("port.opened".to_string(), 3000),
+                ("port.closed".to_string(), 4000),
+                ("port.opened".to_string(), 5000),
+            ]
+        );
+        assert!(port_events(&current, &current).is_empty());
+    }
+
+    #[test]
+    fn test_conflict_changes() {
+        let holder = |pid| PortHolder {
+            pid,
+            name: "python3".to_string(),
+        };
+        let previous = HashMap::from([(3000, holder(10)), (4000, holder(20))]);
+        let current = HashMap::from([(3000, holder(11)), (4000, holder(20)), (5000, holder(30))]);
+        // A new holder on an already conflicted port is a new conflict
+        assert_eq!(conflict_changes(&previous, &current), (vec![3000, 5000], vec![]));
+        assert_eq!(conflict_changes(&current, &HashMap::new()), (vec![], vec![3000, 4000, 5000]));
+    }
+
+    #[test]
+    fn test_suggested_actions() {
+        let holder = PortHolder {
+            pid: 42,
+            name: "python3".to_string(),
+        };
+        let actions = json!(suggested_actions(Some(&holder), Some(3001)));
+        assert_eq!(actions[0]["kind"], "kill");
+        assert_eq!(actions[0]["label"], "Kill python3 (PID 42)");
+        assert_eq!(actions[0]["request"]["method"], "kill");
+        assert_eq!(actions[0]["request"]["params"]["pid"], 42);
+        assert_eq!(actions[1], json!({ "kind": "usePort", "port": 3001, "label": "Use port 3001 instead" }));
+
+        assert!(suggested_actions(None, None).is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_handle_line_errors_and_subscriptions() {
+        let args = Args::parse_from(["port-kill", "--machine"]);
+        let subscriptions = Mutex::new(Subscriptions::default());
+
+        let (reply, _) = handle_line(&args, &subscriptions, None, "{not json").await;
+        assert_eq!(reply.unwrap()["error"]["code"], PARSE_ERROR);
+
+        let (reply, _) = handle_line(&args, &subscriptions, None, r#"{"jsonrpc":"2.0","id":1,"method":"nope"}"#).await;
+        let reply = reply.unwrap();
+        assert_eq!(reply["id"], 1);
+        assert_eq!(reply["error"]["code"], METHOD_NOT_FOUND);
+
+        let (reply, _) = handle_line(
+            &args,
+            &subscriptions,
+            None,
+            r#"{"jsonrpc":"2.0","id":2,"method":"subscribe","params":{"events":["guard"]}}"#,
+        )
+        .await;
+        assert_eq!(reply.unwrap()["result"]["subscribed"], json!(["guard"]));
+        assert_eq!(
+            *subscriptions.lock().unwrap(),
+            Subscriptions {
+                ports: false,
+                guard: true,
+                conflicts: false,
+            }
+        );
+
+        // Notifications (no id) get no response
+        let (reply, _) = handle_line(&args, &subscriptions, None, r#"{"jsonrpc":"2.0","method":"unsubscribe"}"#).await;
+        assert!(reply.is_none());
+        assert!(!subscriptions.lock().unwrap().any());
+
+        let (reply, _) = handle_line(&args, &subscriptions, None, r#"{"jsonrpc":"2.0","id":3,"method":"guard.release"}"#).await;
+        assert_eq!(reply.unwrap()["error"]["code"], INVALID_PARAMS);
+
+        let (reply, shutdown) = handle_line(&args, &subscriptions, None, r#"{"jsonrpc":"2.0","id":4,"method":"shutdown"}"#).await;
+        assert_eq!(reply.unwrap()["result"], Value::Null);
+        assert!(shutdown);
+    }
+}
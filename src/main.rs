@@ -1,32 +1,51 @@
 #[cfg(target_os = "macos")]
 use anyhow::Result;
 #[cfg(target_os = "macos")]
-use clap::Parser;
-#[cfg(target_os = "macos")]
 use log::info;
 #[cfg(target_os = "macos")]
 use port_kill::cache::output::print_or_json;
 #[cfg(target_os = "macos")]
 use port_kill::cache::{
+    backup::prune_backups,
+    budget::{check_budget, print_budget_report},
     clean::clean_caches,
     doctor::doctor,
+    hf::update_pins,
     list::{list_caches, print_list_table},
-    restore::restore_last_backup,
+    plan::{plan_clean, print_plan_table},
+    remote::run_remote_cache,
+    restore::{list_backup_entries, print_backup_list, restore_backup},
+    trends::{compute_trends, load_snapshots, print_trends, record_snapshot},
+    watch::watch_disk,
 };
 use port_kill::update_check;
 #[cfg(target_os = "macos")]
 use port_kill::{app::PortKillApp, cli::Args, console_app::ConsolePortKillApp};
 
+// Counts allocations for `port-kill bench` in builds with the `bench` feature
+#[cfg(feature = "bench")]
+#[global_allocator]
+static ALLOCATOR: port_kill::bench::CountingAllocator = port_kill::bench::CountingAllocator;
+
 #[cfg(target_os = "macos")]
 fn main() -> Result<()> {
     // Parse command-line arguments
-    let mut args = Args::parse();
+    let mut args = Args::parse_with_config();
+
+    // Handle self-update
+    if args.self_update {
+        let rt = tokio::runtime::Runtime::new()?;
+        if let Err(e) = rt.block_on(update_check::self_update(args.update_channel)) {
+            eprintln!("⚠️  Self-update failed: {}", e);
+        }
+        return Ok(());
+    }
 
     // Handle update check
     if args.check_updates {
         let current_version = env!("CARGO_PKG_VERSION");
         let rt = tokio::runtime::Runtime::new()?;
-        match rt.block_on(update_check::check_for_updates(current_version)) {
+        match rt.block_on(update_check::check_for_updates(current_version, args.update_channel)) {
             Ok(Some(update_info)) => {
                 update_check::print_update_check_result(&update_info);
                 return Ok(());
@@ -47,17 +66,130 @@ fn main() -> Result<()> {
     let is_quick_operation = args.list_presets
         || args.save_preset.is_some()
         || args.delete_preset.is_some()
-        || args.cache.is_some();
+        || args.import_preset.is_some()
+        || args.export_preset.is_some()
+        || args.explain
+        || args.doctor
+        || args.install_login_item
+        || args.remove_login_item
+        || args.install_git_hooks
+        || args.reserve_branch_port
+        || args.release_branch_port.is_some()
+        || args.branch_ports
+        || args.machine
+        || args.fail_if_found
+        || args.command.is_some();
 
     // Check for updates only for long-running operations
     if !is_quick_operation {
         let current_version = env!("CARGO_PKG_VERSION");
         let rt = tokio::runtime::Runtime::new()?;
-        if let Ok(Some(update_info)) = rt.block_on(update_check::check_for_updates(current_version)) {
+        if let Ok(Some(update_info)) = rt.block_on(update_check::check_for_updates(current_version, args.update_channel)) {
             update_check::print_update_notification(&update_info);
         }
     }
 
+    // Shell completions
+    if let Some(port_kill::cli::Command::Completions(ref c)) = args.command {
+        let rt = tokio::runtime::Runtime::new()?;
+        rt.block_on(port_kill::completions::run_completions(&args, c))?;
+        return Ok(());
+    }
+
+    // Clear a command's ports, then run it
+    if let Some(port_kill::cli::Command::Run(ref run)) = args.command {
+        let rt = tokio::runtime::Runtime::new()?;
+        rt.block_on(port_kill::preflight::run_preflight(&args, run))?;
+        return Ok(());
+    }
+
+    // Query the kill and restart history
+    if let Some(port_kill::cli::Command::History(ref history)) = args.command {
+        let rt = tokio::runtime::Runtime::new()?;
+        rt.block_on(port_kill::history_store::run_history_query(history))?;
+        return Ok(());
+    }
+
+    // One long-lived instance, and the client that talks to it
+    if let Some(port_kill::cli::Command::Daemon(ref daemon)) = args.command {
+        let _log_guard = port_kill::logging::init(&args)?;
+        let rt = tokio::runtime::Runtime::new()?;
+        rt.block_on(port_kill::daemon::run_daemon(&args, daemon))?;
+        return Ok(());
+    }
+    if let Some(port_kill::cli::Command::Ctl(ref ctl)) = args.command {
+        let rt = tokio::runtime::Runtime::new()?;
+        rt.block_on(port_kill::daemon::run_ctl(ctl))?;
+        return Ok(());
+    }
+
+    // Scan performance
+    if let Some(port_kill::cli::Command::Bench(ref bench)) = args.command {
+        let rt = tokio::runtime::Runtime::new()?;
+        rt.block_on(port_kill::bench::run_bench(&args, bench))?;
+        return Ok(());
+    }
+
+    // Block until ports open or free up
+    if let Some(port_kill::cli::Command::Wait(ref wait)) = args.command {
+        let rt = tokio::runtime::Runtime::new()?;
+        let code = rt.block_on(port_kill::port_wait::run_wait(wait))?;
+        std::process::exit(code);
+    }
+
+    // Forward a busy port to another instance
+    if let Some(port_kill::cli::Command::Proxy(ref proxy)) = args.command {
+        let _log_guard = port_kill::logging::init(&args)?;
+        let rt = tokio::runtime::Runtime::new()?;
+        rt.block_on(port_kill::proxy::run_proxy(&args, proxy))?;
+        return Ok(());
+    }
+
+    // JSON-RPC on stdin/stdout for editor plugins
+    if args.machine {
+        let rt = tokio::runtime::Runtime::new()?;
+        rt.block_on(port_kill::machine::run_machine(args))?;
+        return Ok(());
+    }
+
+    // Start at login
+    if args.install_login_item || args.remove_login_item {
+        port_kill::login_item::run_login_item_command(args.install_login_item)?;
+        return Ok(());
+    }
+
+    // Per-branch port reservations
+    if args.install_git_hooks {
+        port_kill::branch_ports::run_install_git_hooks(&args)?;
+        return Ok(());
+    }
+    if args.reserve_branch_port {
+        port_kill::branch_ports::run_reserve_branch_port(&args)?;
+        return Ok(());
+    }
+    if let Some(ref branch) = args.release_branch_port {
+        port_kill::branch_ports::run_release_branch_port(&args, branch)?;
+        return Ok(());
+    }
+    if args.branch_ports {
+        port_kill::branch_ports::print_branch_ports(args.json);
+        return Ok(());
+    }
+
+    // Environment checks
+    if args.doctor {
+        let report = port_kill::doctor::run_doctor(&args);
+        if args.json {
+            print_or_json(&report, true);
+        } else {
+            port_kill::doctor::print_doctor_report(&report);
+        }
+        if !report.ok {
+            std::process::exit(1);
+        }
+        return Ok(());
+    }
+
     // Handle preset functionality
     if args.list_presets {
         match Args::list_available_presets() {
@@ -79,11 +211,26 @@ fn main() -> Result<()> {
             eprintln!("Error: {}", e);
             std::process::exit(1);
         }
-        let desc = args
-            .preset_desc
-            .clone()
-            .unwrap_or_else(|| "User-defined preset".to_string());
-        let preset = args.build_preset_from_args(name.clone(), desc);
+        let preset = if args.interactive {
+            let rt = tokio::runtime::Runtime::new()?;
+            match rt.block_on(port_kill::preset_wizard::run_preset_wizard(&args, &name)) {
+                Ok(Some(preset)) => preset,
+                Ok(None) => {
+                    println!("Preset '{}' was not saved.", name);
+                    return Ok(());
+                }
+                Err(e) => {
+                    eprintln!("Error: {}", e);
+                    std::process::exit(1);
+                }
+            }
+        } else {
+            let desc = args
+                .preset_desc
+                .clone()
+                .unwrap_or_else(|| "User-defined preset".to_string());
+            args.build_preset_from_args(name.clone(), desc)
+        };
         let mut mgr = port_kill::preset_manager::PresetManager::new();
         if let Err(e) = mgr.load_presets() {
             eprintln!("Error: {}", e);
@@ -121,12 +268,47 @@ fn main() -> Result<()> {
         return Ok(());
     }
 
-    // Apply preset if specified
+    // Export preset
+    if let Some(name) = args.export_preset.clone() {
+        match Args::export_preset_json(&name) {
+            Ok(json) => {
+                println!("{}", json);
+                return Ok(());
+            }
+            Err(e) => {
+                eprintln!("Error: {}", e);
+                std::process::exit(1);
+            }
+        }
+    }
+
+    // Import presets
+    if let Some(source) = args.import_preset.clone() {
+        let rt = tokio::runtime::Runtime::new()?;
+        match rt.block_on(Args::import_presets(&source, args.yes)) {
+            Ok(names) if names.is_empty() => println!("✅ Presets already up to date."),
+            Ok(names) => println!("✅ Imported preset(s): {}", names.join(", ")),
+            Err(e) => {
+                eprintln!("Error: {}", e);
+                std::process::exit(1);
+            }
+        }
+        return Ok(());
+    }
+
+    // Apply preset if specified, otherwise pick one from the project type
     if let Some(preset_name) = args.preset.clone() {
         if let Err(e) = args.load_preset(&preset_name) {
             eprintln!("Error: {}", e);
             std::process::exit(1);
         }
+    } else if let Some((preset_name, reason)) = args.auto_select_preset() {
+        if args.verbose || args.log_level.is_info_enabled() {
+            eprintln!(
+                "ℹ️  Using preset '{}' ({}); pass --preset or --no-auto-preset to override",
+                preset_name, reason
+            );
+        }
     }
 
     // Validate arguments
@@ -135,6 +317,18 @@ fn main() -> Result<()> {
         std::process::exit(1);
     }
 
+    // Show what the preset and flags resolve to without running anything
+    if args.explain {
+        println!("{}", args.explain());
+        return Ok(());
+    }
+
+    // CI gate: fail when a monitored port is taken
+    if args.fail_if_found {
+        let code = port_kill::preflight::run_fail_if_found(&args)?;
+        std::process::exit(code);
+    }
+
     // Set up logging level based on log_level argument
     let log_level = if args.verbose {
         // Verbose flag overrides log_level for backward compatibility
@@ -145,15 +339,47 @@ fn main() -> Result<()> {
     std::env::set_var("RUST_LOG", log_level);
 
     // Initialize logging
-    env_logger::init();
+    let _log_guard = port_kill::logging::init(&args)?;
 
     info!("Starting Port Kill application...");
     info!("Monitoring: {}", args.get_port_description());
 
     // Handle cache subcommand: route to console-like behavior
-    if let Some(cache_cmd) = args.cache.clone() {
-        let c = cache_cmd.args();
-        if c.list || c.dry_run {
+    if let Some(c) = args.command.as_ref().and_then(|cmd| cmd.args()) {
+        if let Some(host) = args.get_remote_host() {
+            match run_remote_cache(&host, &args.remote_bin, c) {
+                Ok(0) => {}
+                Ok(code) => std::process::exit(code),
+                Err(e) => {
+                    eprintln!("Error: {}", e);
+                    std::process::exit(1);
+                }
+            }
+            return Ok(());
+        }
+        if c.dry_run {
+            let plan = tokio::runtime::Runtime::new()
+                .unwrap()
+                .block_on(plan_clean(
+                    &c.lang,
+                    c.npx,
+                    c.js_pm,
+                    c.hf,
+                    c.torch,
+                    c.vercel,
+                    c.cloudflare,
+                    c.max_age(),
+                    c.safe_delete,
+                    c.trash,
+                ));
+            if c.json {
+                print_or_json(&plan, true);
+            } else {
+                print_plan_table(&plan);
+            }
+            return Ok(());
+        }
+        if c.list {
             let resp = tokio::runtime::Runtime::new()
                 .unwrap()
                 .block_on(list_caches(
@@ -164,13 +390,40 @@ fn main() -> Result<()> {
                     c.torch,
                     c.vercel,
                     c.cloudflare,
-                    c.stale_days,
+                    c.max_age(),
                 ));
             if c.json {
                 print_or_json(&resp, true);
             } else {
                 print_list_table(&resp);
             }
+            if let Err(e) = record_snapshot() {
+                eprintln!("Warning: Failed to record cache history: {}", e);
+            }
+            return Ok(());
+        }
+        if let (true, Some(budget)) = (c.check, c.budget) {
+            let listing = tokio::runtime::Runtime::new()
+                .unwrap()
+                .block_on(list_caches(
+                    &c.lang,
+                    c.npx,
+                    c.js_pm,
+                    c.hf,
+                    c.torch,
+                    c.vercel,
+                    c.cloudflare,
+                    c.max_age(),
+                ));
+            let report = check_budget(&listing, budget);
+            if c.json {
+                print_or_json(&report, true);
+            } else {
+                print_budget_report(&report);
+            }
+            if report.over_budget {
+                std::process::exit(1);
+            }
             return Ok(());
         }
         if c.clean {
@@ -181,20 +434,32 @@ fn main() -> Result<()> {
                     c.npx,
                     c.js_pm,
                     c.safe_delete,
+                    c.compress,
+                    c.trash,
                     c.force,
                     c.hf,
                     c.torch,
                     c.vercel,
                     c.cloudflare,
-                    c.stale_days,
+                    c.max_age(),
+                    &c.retention_policy(),
                 ));
             print_or_json(&resp, c.json);
             return Ok(());
         }
-        if c.restore_last {
+        if c.list_backups {
+            let resp = list_backup_entries();
+            if c.json {
+                print_or_json(&resp, true);
+            } else {
+                print_backup_list(&resp);
+            }
+            return Ok(());
+        }
+        if c.restore || c.restore_last {
             let resp = tokio::runtime::Runtime::new()
                 .unwrap()
-                .block_on(restore_last_backup());
+                .block_on(restore_backup(c.backup.as_deref(), c.only.as_deref(), c.select, c.json));
             print_or_json(&resp, c.json);
             if resp.error.is_some() {
                 std::process::exit(1);
@@ -202,15 +467,62 @@ fn main() -> Result<()> {
             return Ok(());
         }
         if c.doctor {
-            let report = tokio::runtime::Runtime::new().unwrap().block_on(doctor());
+            let report = tokio::runtime::Runtime::new().unwrap().block_on(doctor(c.fix, c.trash));
             print_or_json(&report, c.json);
             return Ok(());
         }
+        if c.prune_backups {
+            let resp = prune_backups(&c.retention_policy());
+            print_or_json(&resp, c.json);
+            if resp.error.is_some() {
+                std::process::exit(1);
+            }
+            return Ok(());
+        }
+        if c.trends {
+            let resp = compute_trends(&load_snapshots());
+            if c.json {
+                print_or_json(&resp, true);
+            } else {
+                print_trends(&resp);
+            }
+            return Ok(());
+        }
+        if !c.pin.is_empty() || !c.unpin.is_empty() {
+            match update_pins(&c.pin, &c.unpin) {
+                Ok(pins) => print_or_json(&pins, c.json),
+                Err(e) => {
+                    eprintln!("Error: Failed to update Hugging Face pins: {}", e);
+                    std::process::exit(1);
+                }
+            }
+            return Ok(());
+        }
+        if c.watch_disk {
+            tokio::runtime::Runtime::new()
+                .unwrap()
+                .block_on(watch_disk(
+                    c.disk_threshold(),
+                    std::time::Duration::from_secs(c.watch_interval),
+                    &c.auto_clean,
+                    c.json,
+                ));
+            return Ok(());
+        }
     }
 
     // Handle new lifecycle management features
     // These run in console mode even from the GUI binary
     
+    if args.restart_all {
+        let rt = tokio::runtime::Runtime::new()?;
+        let code = rt.block_on(async {
+            let app = ConsolePortKillApp::new(args)?;
+            app.restart_all().await
+        })?;
+        std::process::exit(code);
+    }
+
     if let Some(port) = args.restart {
         let rt = tokio::runtime::Runtime::new()?;
         rt.block_on(async {
@@ -238,6 +550,33 @@ fn main() -> Result<()> {
         return Ok(());
     }
 
+    if args.prune_restarts {
+        let rt = tokio::runtime::Runtime::new()?;
+        rt.block_on(async {
+            let app = ConsolePortKillApp::new(args)?;
+            app.prune_restarts().await
+        })?;
+        return Ok(());
+    }
+
+    if let Some(path) = args.export_restarts.clone() {
+        let rt = tokio::runtime::Runtime::new()?;
+        rt.block_on(async {
+            let app = ConsolePortKillApp::new(args)?;
+            app.export_restarts(&path).await
+        })?;
+        return Ok(());
+    }
+
+    if let Some(path) = args.import_restarts.clone() {
+        let rt = tokio::runtime::Runtime::new()?;
+        rt.block_on(async {
+            let app = ConsolePortKillApp::new(args)?;
+            app.import_restarts(&path).await
+        })?;
+        return Ok(());
+    }
+
     if args.detect {
         let rt = tokio::runtime::Runtime::new()?;
         rt.block_on(async {
@@ -301,6 +640,33 @@ fn main() -> Result<()> {
         return Ok(());
     }
 
+    if let Some(addr) = args.dashboard.clone() {
+        let rt = tokio::runtime::Runtime::new()?;
+        rt.block_on(port_kill::dashboard::run_dashboard(args, &addr))?;
+        return Ok(());
+    }
+
+    if let Some(addr) = args.serve.clone() {
+        let rt = tokio::runtime::Runtime::new()?;
+        rt.block_on(port_kill::rest_api::run_server(args, &addr))?;
+        return Ok(());
+    }
+
+    // Without a GUI session (SSH, CI) the status bar can't be created, so monitor in
+    // the terminal instead of crashing
+    let headless = port_kill::doctor::gui_session_missing();
+    if args.console || args.tui || headless.is_some() {
+        if let Some(reason) = headless.filter(|_| !args.console && !args.tui) {
+            eprintln!("⚠️  {}, so the status bar is unavailable; running in console mode", reason);
+        }
+        let rt = tokio::runtime::Runtime::new()?;
+        rt.block_on(async {
+            let app = ConsolePortKillApp::new(args)?;
+            app.run().await
+        })?;
+        return Ok(());
+    }
+
     // Create and run the application (GUI mode)
     let app = PortKillApp::new(args)?;
     app.run()?;
@@ -312,17 +678,22 @@ fn main() -> Result<()> {
 #[cfg(target_os = "windows")]
 use anyhow::Result;
 #[cfg(target_os = "windows")]
-use clap::Parser;
-#[cfg(target_os = "windows")]
 use log::info;
 #[cfg(target_os = "windows")]
 use port_kill::cache::output::print_or_json;
 #[cfg(target_os = "windows")]
 use port_kill::cache::{
+    backup::prune_backups,
+    budget::{check_budget, print_budget_report},
     clean::clean_caches,
     doctor::doctor,
+    hf::update_pins,
     list::{list_caches, print_list_table},
-    restore::restore_last_backup,
+    plan::{plan_clean, print_plan_table},
+    remote::run_remote_cache,
+    restore::{list_backup_entries, print_backup_list, restore_backup},
+    trends::{compute_trends, load_snapshots, print_trends, record_snapshot},
+    watch::watch_disk,
 };
 #[cfg(target_os = "windows")]
 use port_kill::{cli::Args, console_app::ConsolePortKillApp};
@@ -331,11 +702,11 @@ use port_kill::{cli::Args, console_app::ConsolePortKillApp};
 #[tokio::main]
 async fn main() -> Result<()> {
     // Parse command-line arguments
-    let mut args = Args::parse();
+    let mut args = Args::parse_with_config();
 
     // Handle self-update
     if args.self_update {
-        match port_kill::update_check::self_update().await {
+        match port_kill::update_check::self_update(args.update_channel).await {
             Ok(()) => return Ok(()),
             Err(e) => {
                 eprintln!("⚠️  Self-update failed: {}", e);
@@ -347,7 +718,7 @@ async fn main() -> Result<()> {
     // Handle update check
     if args.check_updates {
         let current_version = env!("CARGO_PKG_VERSION");
-        match port_kill::update_check::check_for_updates(current_version).await {
+        match port_kill::update_check::check_for_updates(current_version, args.update_channel).await {
             Ok(Some(update_info)) => {
                 port_kill::update_check::print_update_check_result(&update_info);
                 return Ok(());
@@ -368,16 +739,120 @@ async fn main() -> Result<()> {
     let is_quick_operation = args.list_presets
         || args.save_preset.is_some()
         || args.delete_preset.is_some()
-        || args.cache.is_some();
+        || args.import_preset.is_some()
+        || args.export_preset.is_some()
+        || args.explain
+        || args.doctor
+        || args.install_login_item
+        || args.remove_login_item
+        || args.install_git_hooks
+        || args.reserve_branch_port
+        || args.release_branch_port.is_some()
+        || args.branch_ports
+        || args.machine
+        || args.fail_if_found
+        || args.command.is_some();
 
     // Check for updates only for long-running operations
     if !is_quick_operation {
         let current_version = env!("CARGO_PKG_VERSION");
-        if let Ok(Some(update_info)) = port_kill::update_check::check_for_updates(current_version).await {
+        if let Ok(Some(update_info)) = port_kill::update_check::check_for_updates(current_version, args.update_channel).await {
             port_kill::update_check::print_update_notification(&update_info);
         }
     }
 
+    // Shell completions
+    if let Some(port_kill::cli::Command::Completions(ref c)) = args.command {
+        port_kill::completions::run_completions(&args, c).await?;
+        return Ok(());
+    }
+
+    // Clear a command's ports, then run it
+    if let Some(port_kill::cli::Command::Run(ref run)) = args.command {
+        port_kill::preflight::run_preflight(&args, run).await?;
+        return Ok(());
+    }
+
+    // Query the kill and restart history
+    if let Some(port_kill::cli::Command::History(ref history)) = args.command {
+        port_kill::history_store::run_history_query(history).await?;
+        return Ok(());
+    }
+
+    // One long-lived instance, and the client that talks to it
+    if let Some(port_kill::cli::Command::Daemon(ref daemon)) = args.command {
+        let _log_guard = port_kill::logging::init(&args)?;
+        port_kill::daemon::run_daemon(&args, daemon).await?;
+        return Ok(());
+    }
+    if let Some(port_kill::cli::Command::Ctl(ref ctl)) = args.command {
+        port_kill::daemon::run_ctl(ctl).await?;
+        return Ok(());
+    }
+
+    // Scan performance
+    if let Some(port_kill::cli::Command::Bench(ref bench)) = args.command {
+        port_kill::bench::run_bench(&args, bench).await?;
+        return Ok(());
+    }
+
+    // Block until ports open or free up
+    if let Some(port_kill::cli::Command::Wait(ref wait)) = args.command {
+        let code = port_kill::port_wait::run_wait(wait).await?;
+        std::process::exit(code);
+    }
+
+    // Forward a busy port to another instance
+    if let Some(port_kill::cli::Command::Proxy(ref proxy)) = args.command {
+        let _log_guard = port_kill::logging::init(&args)?;
+        port_kill::proxy::run_proxy(&args, proxy).await?;
+        return Ok(());
+    }
+
+    // JSON-RPC on stdin/stdout for editor plugins
+    if args.machine {
+        port_kill::machine::run_machine(args).await?;
+        return Ok(());
+    }
+
+    // Start at login
+    if args.install_login_item || args.remove_login_item {
+        port_kill::login_item::run_login_item_command(args.install_login_item)?;
+        return Ok(());
+    }
+
+    // Per-branch port reservations
+    if args.install_git_hooks {
+        port_kill::branch_ports::run_install_git_hooks(&args)?;
+        return Ok(());
+    }
+    if args.reserve_branch_port {
+        port_kill::branch_ports::run_reserve_branch_port(&args)?;
+        return Ok(());
+    }
+    if let Some(ref branch) = args.release_branch_port {
+        port_kill::branch_ports::run_release_branch_port(&args, branch)?;
+        return Ok(());
+    }
+    if args.branch_ports {
+        port_kill::branch_ports::print_branch_ports(args.json);
+        return Ok(());
+    }
+
+    // Environment checks
+    if args.doctor {
+        let report = port_kill::doctor::run_doctor(&args);
+        if args.json {
+            print_or_json(&report, true);
+        } else {
+            port_kill::doctor::print_doctor_report(&report);
+        }
+        if !report.ok {
+            std::process::exit(1);
+        }
+        return Ok(());
+    }
+
     // Handle preset functionality
     if args.list_presets {
         match Args::list_available_presets() {
@@ -399,11 +874,25 @@ async fn main() -> Result<()> {
             eprintln!("Error: {}", e);
             std::process::exit(1);
         }
-        let desc = args
-            .preset_desc
-            .clone()
-            .unwrap_or_else(|| "User-defined preset".to_string());
-        let preset = args.build_preset_from_args(name.clone(), desc);
+        let preset = if args.interactive {
+            match port_kill::preset_wizard::run_preset_wizard(&args, &name).await {
+                Ok(Some(preset)) => preset,
+                Ok(None) => {
+                    println!("Preset '{}' was not saved.", name);
+                    return Ok(());
+                }
+                Err(e) => {
+                    eprintln!("Error: {}", e);
+                    std::process::exit(1);
+                }
+            }
+        } else {
+            let desc = args
+                .preset_desc
+                .clone()
+                .unwrap_or_else(|| "User-defined preset".to_string());
+            args.build_preset_from_args(name.clone(), desc)
+        };
         let mut mgr = port_kill::preset_manager::PresetManager::new();
         if let Err(e) = mgr.load_presets() {
             eprintln!("Error: {}", e);
@@ -441,20 +930,66 @@ async fn main() -> Result<()> {
         return Ok(());
     }
 
-    // Apply preset if specified
-    if let Some(preset_name) = args.preset.clone() {
-        if let Err(e) = args.load_preset(&preset_name) {
-            eprintln!("Error: {}", e);
-            std::process::exit(1);
-        }
-    }
-
-    // Validate arguments
-    if let Err(e) = args.validate() {
+    // Export preset
+    if let Some(name) = args.export_preset.clone() {
+        match Args::export_preset_json(&name) {
+            Ok(json) => {
+                println!("{}", json);
+                return Ok(());
+            }
+            Err(e) => {
+                eprintln!("Error: {}", e);
+                std::process::exit(1);
+            }
+        }
+    }
+
+    // Import presets
+    if let Some(source) = args.import_preset.clone() {
+        match Args::import_presets(&source, args.yes).await {
+            Ok(names) if names.is_empty() => println!("✅ Presets already up to date."),
+            Ok(names) => println!("✅ Imported preset(s): {}", names.join(", ")),
+            Err(e) => {
+                eprintln!("Error: {}", e);
+                std::process::exit(1);
+            }
+        }
+        return Ok(());
+    }
+
+    // Apply preset if specified, otherwise pick one from the project type
+    if let Some(preset_name) = args.preset.clone() {
+        if let Err(e) = args.load_preset(&preset_name) {
+            eprintln!("Error: {}", e);
+            std::process::exit(1);
+        }
+    } else if let Some((preset_name, reason)) = args.auto_select_preset() {
+        if args.verbose || args.log_level.is_info_enabled() {
+            eprintln!(
+                "ℹ️  Using preset '{}' ({}); pass --preset or --no-auto-preset to override",
+                preset_name, reason
+            );
+        }
+    }
+
+    // Validate arguments
+    if let Err(e) = args.validate() {
         eprintln!("Error: {}", e);
         std::process::exit(1);
     }
 
+    // Show what the preset and flags resolve to without running anything
+    if args.explain {
+        println!("{}", args.explain());
+        return Ok(());
+    }
+
+    // CI gate: fail when a monitored port is taken
+    if args.fail_if_found {
+        let code = port_kill::preflight::run_fail_if_found(&args)?;
+        std::process::exit(code);
+    }
+
     // Set up logging level based on log_level argument
     let log_level = if args.verbose {
         // Verbose flag overrides log_level for backward compatibility
@@ -465,15 +1000,46 @@ async fn main() -> Result<()> {
     std::env::set_var("RUST_LOG", log_level);
 
     // Initialize logging
-    env_logger::init();
+    let _log_guard = port_kill::logging::init(&args)?;
 
     info!("Starting Port Kill application on Windows...");
     info!("Monitoring: {}", args.get_port_description());
 
     // Handle cache subcommand: route to console-like behavior
-    if let Some(cache_cmd) = args.cache.clone() {
-        let c = cache_cmd.args();
-        if c.list || c.dry_run {
+    if let Some(c) = args.command.as_ref().and_then(|cmd| cmd.args()) {
+        if let Some(host) = args.get_remote_host() {
+            match run_remote_cache(&host, &args.remote_bin, c) {
+                Ok(0) => {}
+                Ok(code) => std::process::exit(code),
+                Err(e) => {
+                    eprintln!("Error: {}", e);
+                    std::process::exit(1);
+                }
+            }
+            return Ok(());
+        }
+        if c.dry_run {
+            let plan = plan_clean(
+                &c.lang,
+                c.npx,
+                c.js_pm,
+                c.hf,
+                c.torch,
+                c.vercel,
+                c.cloudflare,
+                c.max_age(),
+                c.safe_delete,
+                c.trash,
+            )
+            .await;
+            if c.json {
+                print_or_json(&plan, true);
+            } else {
+                print_plan_table(&plan);
+            }
+            return Ok(());
+        }
+        if c.list {
             let resp = list_caches(
                 &c.lang,
                 c.npx,
@@ -482,7 +1048,7 @@ async fn main() -> Result<()> {
                 c.torch,
                 c.vercel,
                 c.cloudflare,
-                c.stale_days,
+                c.max_age(),
             )
             .await;
             if c.json {
@@ -490,6 +1056,32 @@ async fn main() -> Result<()> {
             } else {
                 print_list_table(&resp);
             }
+            if let Err(e) = record_snapshot() {
+                eprintln!("Warning: Failed to record cache history: {}", e);
+            }
+            return Ok(());
+        }
+        if let (true, Some(budget)) = (c.check, c.budget) {
+            let listing = list_caches(
+                &c.lang,
+                c.npx,
+                c.js_pm,
+                c.hf,
+                c.torch,
+                c.vercel,
+                c.cloudflare,
+                c.max_age(),
+            )
+            .await;
+            let report = check_budget(&listing, budget);
+            if c.json {
+                print_or_json(&report, true);
+            } else {
+                print_budget_report(&report);
+            }
+            if report.over_budget {
+                std::process::exit(1);
+            }
             return Ok(());
         }
         if c.clean {
@@ -498,19 +1090,31 @@ async fn main() -> Result<()> {
                 c.npx,
                 c.js_pm,
                 c.safe_delete,
+                c.compress,
+                c.trash,
                 c.force,
                 c.hf,
                 c.torch,
                 c.vercel,
                 c.cloudflare,
-                c.stale_days,
+                c.max_age(),
+                &c.retention_policy(),
             )
             .await;
             print_or_json(&resp, c.json);
             return Ok(());
         }
-        if c.restore_last {
-            let resp = restore_last_backup().await;
+        if c.list_backups {
+            let resp = list_backup_entries();
+            if c.json {
+                print_or_json(&resp, true);
+            } else {
+                print_backup_list(&resp);
+            }
+            return Ok(());
+        }
+        if c.restore || c.restore_last {
+            let resp = restore_backup(c.backup.as_deref(), c.only.as_deref(), c.select, c.json).await;
             print_or_json(&resp, c.json);
             if resp.error.is_some() {
                 std::process::exit(1);
@@ -518,14 +1122,57 @@ async fn main() -> Result<()> {
             return Ok(());
         }
         if c.doctor {
-            let report = doctor().await;
+            let report = doctor(c.fix, c.trash).await;
             print_or_json(&report, c.json);
             return Ok(());
         }
+        if c.prune_backups {
+            let resp = prune_backups(&c.retention_policy());
+            print_or_json(&resp, c.json);
+            if resp.error.is_some() {
+                std::process::exit(1);
+            }
+            return Ok(());
+        }
+        if c.trends {
+            let resp = compute_trends(&load_snapshots());
+            if c.json {
+                print_or_json(&resp, true);
+            } else {
+                print_trends(&resp);
+            }
+            return Ok(());
+        }
+        if !c.pin.is_empty() || !c.unpin.is_empty() {
+            match update_pins(&c.pin, &c.unpin) {
+                Ok(pins) => print_or_json(&pins, c.json),
+                Err(e) => {
+                    eprintln!("Error: Failed to update Hugging Face pins: {}", e);
+                    std::process::exit(1);
+                }
+            }
+            return Ok(());
+        }
+        if c.watch_disk {
+            watch_disk(
+                c.disk_threshold(),
+                std::time::Duration::from_secs(c.watch_interval),
+                &c.auto_clean,
+                c.json,
+            )
+            .await;
+            return Ok(());
+        }
     }
 
     // Handle new lifecycle management features
     
+    if args.restart_all {
+        let app = ConsolePortKillApp::new(args)?;
+        let code = app.restart_all().await?;
+        std::process::exit(code);
+    }
+
     if let Some(port) = args.restart {
         let app = ConsolePortKillApp::new(args)?;
         app.restart_port(port).await?;
@@ -544,6 +1191,24 @@ async fn main() -> Result<()> {
         return Ok(());
     }
 
+    if args.prune_restarts {
+        let app = ConsolePortKillApp::new(args)?;
+        app.prune_restarts().await?;
+        return Ok(());
+    }
+
+    if let Some(path) = args.export_restarts.clone() {
+        let app = ConsolePortKillApp::new(args)?;
+        app.export_restarts(&path).await?;
+        return Ok(());
+    }
+
+    if let Some(path) = args.import_restarts.clone() {
+        let app = ConsolePortKillApp::new(args)?;
+        app.import_restarts(&path).await?;
+        return Ok(());
+    }
+
     if args.detect {
         let app = ConsolePortKillApp::new(args)?;
         app.detect_services().await?;
@@ -586,6 +1251,16 @@ async fn main() -> Result<()> {
         return Ok(());
     }
 
+    if let Some(addr) = args.dashboard.clone() {
+        port_kill::dashboard::run_dashboard(args, &addr).await?;
+        return Ok(());
+    }
+
+    if let Some(addr) = args.serve.clone() {
+        port_kill::rest_api::run_server(args, &addr).await?;
+        return Ok(());
+    }
+
     // Create and run the console application
     let app = ConsolePortKillApp::new(args)?;
     app.run().await?;
@@ -597,17 +1272,22 @@ async fn main() -> Result<()> {
 #[cfg(target_os = "linux")]
 use anyhow::Result;
 #[cfg(target_os = "linux")]
-use clap::Parser;
-#[cfg(target_os = "linux")]
 use log::info;
 #[cfg(target_os = "linux")]
 use port_kill::cache::output::print_or_json;
 #[cfg(target_os = "linux")]
 use port_kill::cache::{
+    backup::prune_backups,
+    budget::{check_budget, print_budget_report},
     clean::clean_caches,
     doctor::doctor,
+    hf::update_pins,
     list::{list_caches, print_list_table},
-    restore::restore_last_backup,
+    plan::{plan_clean, print_plan_table},
+    remote::run_remote_cache,
+    restore::{list_backup_entries, print_backup_list, restore_backup},
+    trends::{compute_trends, load_snapshots, print_trends, record_snapshot},
+    watch::watch_disk,
 };
 #[cfg(target_os = "linux")]
 use port_kill::{cli::Args, console_app::ConsolePortKillApp};
@@ -616,11 +1296,11 @@ use port_kill::{cli::Args, console_app::ConsolePortKillApp};
 #[tokio::main]
 async fn main() -> Result<()> {
     // Parse command-line arguments
-    let mut args = Args::parse();
+    let mut args = Args::parse_with_config();
 
     // Handle self-update
     if args.self_update {
-        match port_kill::update_check::self_update().await {
+        match port_kill::update_check::self_update(args.update_channel).await {
             Ok(()) => return Ok(()),
             Err(e) => {
                 eprintln!("⚠️  Self-update failed: {}", e);
@@ -632,7 +1312,7 @@ async fn main() -> Result<()> {
     // Handle update check
     if args.check_updates {
         let current_version = env!("CARGO_PKG_VERSION");
-        match port_kill::update_check::check_for_updates(current_version).await {
+        match port_kill::update_check::check_for_updates(current_version, args.update_channel).await {
             Ok(Some(update_info)) => {
                 port_kill::update_check::print_update_check_result(&update_info);
                 return Ok(());
@@ -653,16 +1333,120 @@ async fn main() -> Result<()> {
     let is_quick_operation = args.list_presets
         || args.save_preset.is_some()
         || args.delete_preset.is_some()
-        || args.cache.is_some();
+        || args.import_preset.is_some()
+        || args.export_preset.is_some()
+        || args.explain
+        || args.doctor
+        || args.install_login_item
+        || args.remove_login_item
+        || args.install_git_hooks
+        || args.reserve_branch_port
+        || args.release_branch_port.is_some()
+        || args.branch_ports
+        || args.machine
+        || args.fail_if_found
+        || args.command.is_some();
 
     // Check for updates only for long-running operations
     if !is_quick_operation {
         let current_version = env!("CARGO_PKG_VERSION");
-        if let Ok(Some(update_info)) = port_kill::update_check::check_for_updates(current_version).await {
+        if let Ok(Some(update_info)) = port_kill::update_check::check_for_updates(current_version, args.update_channel).await {
             port_kill::update_check::print_update_notification(&update_info);
         }
     }
 
+    // Shell completions
+    if let Some(port_kill::cli::Command::Completions(ref c)) = args.command {
+        port_kill::completions::run_completions(&args, c).await?;
+        return Ok(());
+    }
+
+    // Clear a command's ports, then run it
+    if let Some(port_kill::cli::Command::Run(ref run)) = args.command {
+        port_kill::preflight::run_preflight(&args, run).await?;
+        return Ok(());
+    }
+
+    // Query the kill and restart history
+    if let Some(port_kill::cli::Command::History(ref history)) = args.command {
+        port_kill::history_store::run_history_query(history).await?;
+        return Ok(());
+    }
+
+    // One long-lived instance, and the client that talks to it
+    if let Some(port_kill::cli::Command::Daemon(ref daemon)) = args.command {
+        let _log_guard = port_kill::logging::init(&args)?;
+        port_kill::daemon::run_daemon(&args, daemon).await?;
+        return Ok(());
+    }
+    if let Some(port_kill::cli::Command::Ctl(ref ctl)) = args.command {
+        port_kill::daemon::run_ctl(ctl).await?;
+        return Ok(());
+    }
+
+    // Scan performance
+    if let Some(port_kill::cli::Command::Bench(ref bench)) = args.command {
+        port_kill::bench::run_bench(&args, bench).await?;
+        return Ok(());
+    }
+
+    // Block until ports open or free up
+    if let Some(port_kill::cli::Command::Wait(ref wait)) = args.command {
+        let code = port_kill::port_wait::run_wait(wait).await?;
+        std::process::exit(code);
+    }
+
+    // Forward a busy port to another instance
+    if let Some(port_kill::cli::Command::Proxy(ref proxy)) = args.command {
+        let _log_guard = port_kill::logging::init(&args)?;
+        port_kill::proxy::run_proxy(&args, proxy).await?;
+        return Ok(());
+    }
+
+    // JSON-RPC on stdin/stdout for editor plugins
+    if args.machine {
+        port_kill::machine::run_machine(args).await?;
+        return Ok(());
+    }
+
+    // Start at login
+    if args.install_login_item || args.remove_login_item {
+        port_kill::login_item::run_login_item_command(args.install_login_item)?;
+        return Ok(());
+    }
+
+    // Per-branch port reservations
+    if args.install_git_hooks {
+        port_kill::branch_ports::run_install_git_hooks(&args)?;
+        return Ok(());
+    }
+    if args.reserve_branch_port {
+        port_kill::branch_ports::run_reserve_branch_port(&args)?;
+        return Ok(());
+    }
+    if let Some(ref branch) = args.release_branch_port {
+        port_kill::branch_ports::run_release_branch_port(&args, branch)?;
+        return Ok(());
+    }
+    if args.branch_ports {
+        port_kill::branch_ports::print_branch_ports(args.json);
+        return Ok(());
+    }
+
+    // Environment checks
+    if args.doctor {
+        let report = port_kill::doctor::run_doctor(&args);
+        if args.json {
+            print_or_json(&report, true);
+        } else {
+            port_kill::doctor::print_doctor_report(&report);
+        }
+        if !report.ok {
+            std::process::exit(1);
+        }
+        return Ok(());
+    }
+
     // Handle preset functionality
     if args.list_presets {
         match Args::list_available_presets() {
@@ -684,11 +1468,25 @@ async fn main() -> Result<()> {
             eprintln!("Error: {}", e);
             std::process::exit(1);
         }
-        let desc = args
-            .preset_desc
-            .clone()
-            .unwrap_or_else(|| "User-defined preset".to_string());
-        let preset = args.build_preset_from_args(name.clone(), desc);
+        let preset = if args.interactive {
+            match port_kill::preset_wizard::run_preset_wizard(&args, &name).await {
+                Ok(Some(preset)) => preset,
+                Ok(None) => {
+                    println!("Preset '{}' was not saved.", name);
+                    return Ok(());
+                }
+                Err(e) => {
+                    eprintln!("Error: {}", e);
+                    std::process::exit(1);
+                }
+            }
+        } else {
+            let desc = args
+                .preset_desc
+                .clone()
+                .unwrap_or_else(|| "User-defined preset".to_string());
+            args.build_preset_from_args(name.clone(), desc)
+        };
         let mut mgr = port_kill::preset_manager::PresetManager::new();
         if let Err(e) = mgr.load_presets() {
             eprintln!("Error: {}", e);
@@ -726,12 +1524,46 @@ async fn main() -> Result<()> {
         return Ok(());
     }
 
-    // Apply preset if specified
+    // Export preset
+    if let Some(name) = args.export_preset.clone() {
+        match Args::export_preset_json(&name) {
+            Ok(json) => {
+                println!("{}", json);
+                return Ok(());
+            }
+            Err(e) => {
+                eprintln!("Error: {}", e);
+                std::process::exit(1);
+            }
+        }
+    }
+
+    // Import presets
+    if let Some(source) = args.import_preset.clone() {
+        match Args::import_presets(&source, args.yes).await {
+            Ok(names) if names.is_empty() => println!("✅ Presets already up to date."),
+            Ok(names) => println!("✅ Imported preset(s): {}", names.join(", ")),
+            Err(e) => {
+                eprintln!("Error: {}", e);
+                std::process::exit(1);
+            }
+        }
+        return Ok(());
+    }
+
+    // Apply preset if specified, otherwise pick one from the project type
     if let Some(preset_name) = args.preset.clone() {
         if let Err(e) = args.load_preset(&preset_name) {
             eprintln!("Error: {}", e);
             std::process::exit(1);
         }
+    } else if let Some((preset_name, reason)) = args.auto_select_preset() {
+        if args.verbose || args.log_level.is_info_enabled() {
+            eprintln!(
+                "ℹ️  Using preset '{}' ({}); pass --preset or --no-auto-preset to override",
+                preset_name, reason
+            );
+        }
     }
 
     // Validate arguments
@@ -740,6 +1572,18 @@ async fn main() -> Result<()> {
         std::process::exit(1);
     }
 
+    // Show what the preset and flags resolve to without running anything
+    if args.explain {
+        println!("{}", args.explain());
+        return Ok(());
+    }
+
+    // CI gate: fail when a monitored port is taken
+    if args.fail_if_found {
+        let code = port_kill::preflight::run_fail_if_found(&args)?;
+        std::process::exit(code);
+    }
+
     // Set up logging level based on log_level argument
     let log_level = if args.verbose {
         // Verbose flag overrides log_level for backward compatibility
@@ -750,15 +1594,46 @@ async fn main() -> Result<()> {
     std::env::set_var("RUST_LOG", log_level);
 
     // Initialize logging
-    env_logger::init();
+    let _log_guard = port_kill::logging::init(&args)?;
 
     info!("Starting Port Kill application on Linux...");
     info!("Monitoring: {}", args.get_port_description());
 
     // Handle cache subcommand: route to console-like behavior
-    if let Some(cache_cmd) = args.cache.clone() {
-        let c = cache_cmd.args();
-        if c.list || c.dry_run {
+    if let Some(c) = args.command.as_ref().and_then(|cmd| cmd.args()) {
+        if let Some(host) = args.get_remote_host() {
+            match run_remote_cache(&host, &args.remote_bin, c) {
+                Ok(0) => {}
+                Ok(code) => std::process::exit(code),
+                Err(e) => {
+                    eprintln!("Error: {}", e);
+                    std::process::exit(1);
+                }
+            }
+            return Ok(());
+        }
+        if c.dry_run {
+            let plan = plan_clean(
+                &c.lang,
+                c.npx,
+                c.js_pm,
+                c.hf,
+                c.torch,
+                c.vercel,
+                c.cloudflare,
+                c.max_age(),
+                c.safe_delete,
+                c.trash,
+            )
+            .await;
+            if c.json {
+                print_or_json(&plan, true);
+            } else {
+                print_plan_table(&plan);
+            }
+            return Ok(());
+        }
+        if c.list {
             let resp = list_caches(
                 &c.lang,
                 c.npx,
@@ -767,7 +1642,7 @@ async fn main() -> Result<()> {
                 c.torch,
                 c.vercel,
                 c.cloudflare,
-                c.stale_days,
+                c.max_age(),
             )
             .await;
             if c.json {
@@ -775,6 +1650,32 @@ async fn main() -> Result<()> {
             } else {
                 print_list_table(&resp);
             }
+            if let Err(e) = record_snapshot() {
+                eprintln!("Warning: Failed to record cache history: {}", e);
+            }
+            return Ok(());
+        }
+        if let (true, Some(budget)) = (c.check, c.budget) {
+            let listing = list_caches(
+                &c.lang,
+                c.npx,
+                c.js_pm,
+                c.hf,
+                c.torch,
+                c.vercel,
+                c.cloudflare,
+                c.max_age(),
+            )
+            .await;
+            let report = check_budget(&listing, budget);
+            if c.json {
+                print_or_json(&report, true);
+            } else {
+                print_budget_report(&report);
+            }
+            if report.over_budget {
+                std::process::exit(1);
+            }
             return Ok(());
         }
         if c.clean {
@@ -783,19 +1684,31 @@ async fn main() -> Result<()> {
                 c.npx,
                 c.js_pm,
                 c.safe_delete,
+                c.compress,
+                c.trash,
                 c.force,
                 c.hf,
                 c.torch,
                 c.vercel,
                 c.cloudflare,
-                c.stale_days,
+                c.max_age(),
+                &c.retention_policy(),
             )
             .await;
             print_or_json(&resp, c.json);
             return Ok(());
         }
-        if c.restore_last {
-            let resp = restore_last_backup().await;
+        if c.list_backups {
+            let resp = list_backup_entries();
+            if c.json {
+                print_or_json(&resp, true);
+            } else {
+                print_backup_list(&resp);
+            }
+            return Ok(());
+        }
+        if c.restore || c.restore_last {
+            let resp = restore_backup(c.backup.as_deref(), c.only.as_deref(), c.select, c.json).await;
             print_or_json(&resp, c.json);
             if resp.error.is_some() {
                 std::process::exit(1);
@@ -803,14 +1716,57 @@ async fn main() -> Result<()> {
             return Ok(());
         }
         if c.doctor {
-            let report = doctor().await;
+            let report = doctor(c.fix, c.trash).await;
             print_or_json(&report, c.json);
             return Ok(());
         }
+        if c.prune_backups {
+            let resp = prune_backups(&c.retention_policy());
+            print_or_json(&resp, c.json);
+            if resp.error.is_some() {
+                std::process::exit(1);
+            }
+            return Ok(());
+        }
+        if c.trends {
+            let resp = compute_trends(&load_snapshots());
+            if c.json {
+                print_or_json(&resp, true);
+            } else {
+                print_trends(&resp);
+            }
+            return Ok(());
+        }
+        if !c.pin.is_empty() || !c.unpin.is_empty() {
+            match update_pins(&c.pin, &c.unpin) {
+                Ok(pins) => print_or_json(&pins, c.json),
+                Err(e) => {
+                    eprintln!("Error: Failed to update Hugging Face pins: {}", e);
+                    std::process::exit(1);
+                }
+            }
+            return Ok(());
+        }
+        if c.watch_disk {
+            watch_disk(
+                c.disk_threshold(),
+                std::time::Duration::from_secs(c.watch_interval),
+                &c.auto_clean,
+                c.json,
+            )
+            .await;
+            return Ok(());
+        }
     }
 
     // Handle new lifecycle management features
     
+    if args.restart_all {
+        let app = ConsolePortKillApp::new(args)?;
+        let code = app.restart_all().await?;
+        std::process::exit(code);
+    }
+
     if let Some(port) = args.restart {
         let app = ConsolePortKillApp::new(args)?;
         app.restart_port(port).await?;
@@ -829,6 +1785,24 @@ async fn main() -> Result<()> {
         return Ok(());
     }
 
+    if args.prune_restarts {
+        let app = ConsolePortKillApp::new(args)?;
+        app.prune_restarts().await?;
+        return Ok(());
+    }
+
+    if let Some(path) = args.export_restarts.clone() {
+        let app = ConsolePortKillApp::new(args)?;
+        app.export_restarts(&path).await?;
+        return Ok(());
+    }
+
+    if let Some(path) = args.import_restarts.clone() {
+        let app = ConsolePortKillApp::new(args)?;
+        app.import_restarts(&path).await?;
+        return Ok(());
+    }
+
     if args.detect {
         let app = ConsolePortKillApp::new(args)?;
         app.detect_services().await?;
@@ -871,6 +1845,16 @@ async fn main() -> Result<()> {
         return Ok(());
     }
 
+    if let Some(addr) = args.dashboard.clone() {
+        port_kill::dashboard::run_dashboard(args, &addr).await?;
+        return Ok(());
+    }
+
+    if let Some(addr) = args.serve.clone() {
+        port_kill::rest_api::run_server(args, &addr).await?;
+        return Ok(());
+    }
+
     // Create and run the console application
     let app = ConsolePortKillApp::new(args)?;
     app.run().await?;
@@ -1,12 +1,18 @@
 use anyhow::Result;
-use clap::Parser;
 use log::info;
 use port_kill::cache::output::print_or_json;
 use port_kill::cache::{
+    backup::prune_backups,
+    budget::{check_budget, print_budget_report},
     clean::clean_caches,
     doctor::doctor,
+    hf::update_pins,
     list::{list_caches, print_list_table},
-    restore::restore_last_backup,
+    plan::{plan_clean, print_plan_table},
+    remote::run_remote_cache,
+    restore::{list_backup_entries, print_backup_list, restore_backup},
+    trends::{compute_trends, load_snapshots, print_trends, record_snapshot},
+    watch::watch_disk,
 };
 use port_kill::update_check;
 use port_kill::{
@@ -15,14 +21,19 @@ use port_kill::{
     scripting::{load_script_file, ScriptEngine},
 };
 
+// Counts allocations for `port-kill bench` in builds with the `bench` feature
+#[cfg(feature = "bench")]
+#[global_allocator]
+static ALLOCATOR: port_kill::bench::CountingAllocator = port_kill::bench::CountingAllocator;
+
 #[tokio::main]
 async fn main() -> Result<()> {
     // Parse command-line arguments
-    let mut args = Args::parse();
+    let mut args = Args::parse_with_config();
 
     // Handle self-update
     if args.self_update {
-        match update_check::self_update().await {
+        match update_check::self_update(args.update_channel).await {
             Ok(()) => return Ok(()),
             Err(e) => {
                 eprintln!("⚠️  Self-update failed: {}", e);
@@ -34,7 +45,7 @@ async fn main() -> Result<()> {
     // Handle update check
     if args.check_updates {
         let current_version = env!("CARGO_PKG_VERSION");
-        match update_check::check_for_updates(current_version).await {
+        match update_check::check_for_updates(current_version, args.update_channel).await {
             Ok(Some(update_info)) => {
                 update_check::print_update_check_result(&update_info);
                 return Ok(());
@@ -55,16 +66,120 @@ async fn main() -> Result<()> {
     let is_quick_operation = args.list_presets
         || args.save_preset.is_some()
         || args.delete_preset.is_some()
-        || args.cache.is_some();
+        || args.import_preset.is_some()
+        || args.export_preset.is_some()
+        || args.explain
+        || args.doctor
+        || args.install_login_item
+        || args.remove_login_item
+        || args.install_git_hooks
+        || args.reserve_branch_port
+        || args.release_branch_port.is_some()
+        || args.branch_ports
+        || args.machine
+        || args.fail_if_found
+        || args.command.is_some();
 
     // Check for updates only for long-running operations
     if !is_quick_operation {
         let current_version = env!("CARGO_PKG_VERSION");
-        if let Ok(Some(update_info)) = update_check::check_for_updates(current_version).await {
+        if let Ok(Some(update_info)) = update_check::check_for_updates(current_version, args.update_channel).await {
             update_check::print_update_notification(&update_info);
         }
     }
 
+    // Shell completions
+    if let Some(port_kill::cli::Command::Completions(ref c)) = args.command {
+        port_kill::completions::run_completions(&args, c).await?;
+        return Ok(());
+    }
+
+    // Clear a command's ports, then run it
+    if let Some(port_kill::cli::Command::Run(ref run)) = args.command {
+        port_kill::preflight::run_preflight(&args, run).await?;
+        return Ok(());
+    }
+
+    // Query the kill and restart history
+    if let Some(port_kill::cli::Command::History(ref history)) = args.command {
+        port_kill::history_store::run_history_query(history).await?;
+        return Ok(());
+    }
+
+    // One long-lived instance, and the client that talks to it
+    if let Some(port_kill::cli::Command::Daemon(ref daemon)) = args.command {
+        let _log_guard = port_kill::logging::init(&args)?;
+        port_kill::daemon::run_daemon(&args, daemon).await?;
+        return Ok(());
+    }
+    if let Some(port_kill::cli::Command::Ctl(ref ctl)) = args.command {
+        port_kill::daemon::run_ctl(ctl).await?;
+        return Ok(());
+    }
+
+    // Scan performance
+    if let Some(port_kill::cli::Command::Bench(ref bench)) = args.command {
+        port_kill::bench::run_bench(&args, bench).await?;
+        return Ok(());
+    }
+
+    // Block until ports open or free up
+    if let Some(port_kill::cli::Command::Wait(ref wait)) = args.command {
+        let code = port_kill::port_wait::run_wait(wait).await?;
+        std::process::exit(code);
+    }
+
+    // Forward a busy port to another instance
+    if let Some(port_kill::cli::Command::Proxy(ref proxy)) = args.command {
+        let _log_guard = port_kill::logging::init(&args)?;
+        port_kill::proxy::run_proxy(&args, proxy).await?;
+        return Ok(());
+    }
+
+    // JSON-RPC on stdin/stdout for editor plugins
+    if args.machine {
+        port_kill::machine::run_machine(args).await?;
+        return Ok(());
+    }
+
+    // Start at login
+    if args.install_login_item || args.remove_login_item {
+        port_kill::login_item::run_login_item_command(args.install_login_item)?;
+        return Ok(());
+    }
+
+    // Per-branch port reservations
+    if args.install_git_hooks {
+        port_kill::branch_ports::run_install_git_hooks(&args)?;
+        return Ok(());
+    }
+    if args.reserve_branch_port {
+        port_kill::branch_ports::run_reserve_branch_port(&args)?;
+        return Ok(());
+    }
+    if let Some(ref branch) = args.release_branch_port {
+        port_kill::branch_ports::run_release_branch_port(&args, branch)?;
+        return Ok(());
+    }
+    if args.branch_ports {
+        port_kill::branch_ports::print_branch_ports(args.json);
+        return Ok(());
+    }
+
+    // Environment checks
+    if args.doctor {
+        let report = port_kill::doctor::run_doctor(&args);
+        if args.json {
+            print_or_json(&report, true);
+        } else {
+            port_kill::doctor::print_doctor_report(&report);
+        }
+        if !report.ok {
+            std::process::exit(1);
+        }
+        return Ok(());
+    }
+
     // Handle preset functionality
     if args.list_presets {
         match Args::list_available_presets() {
@@ -86,11 +201,25 @@ async fn main() -> Result<()> {
             eprintln!("Error: {}", e);
             std::process::exit(1);
         }
-        let desc = args
-            .preset_desc
-            .clone()
-            .unwrap_or_else(|| "User-defined preset".to_string());
-        let preset = args.build_preset_from_args(name.clone(), desc);
+        let preset = if args.interactive {
+            match port_kill::preset_wizard::run_preset_wizard(&args, &name).await {
+                Ok(Some(preset)) => preset,
+                Ok(None) => {
+                    println!("Preset '{}' was not saved.", name);
+                    return Ok(());
+                }
+                Err(e) => {
+                    eprintln!("Error: {}", e);
+                    std::process::exit(1);
+                }
+            }
+        } else {
+            let desc = args
+                .preset_desc
+                .clone()
+                .unwrap_or_else(|| "User-defined preset".to_string());
+            args.build_preset_from_args(name.clone(), desc)
+        };
         let mut mgr = port_kill::preset_manager::PresetManager::new();
         if let Err(e) = mgr.load_presets() {
             eprintln!("Error: {}", e);
@@ -128,12 +257,46 @@ async fn main() -> Result<()> {
         return Ok(());
     }
 
-    // Apply preset if specified
+    // Export preset
+    if let Some(name) = args.export_preset.clone() {
+        match Args::export_preset_json(&name) {
+            Ok(json) => {
+                println!("{}", json);
+                return Ok(());
+            }
+            Err(e) => {
+                eprintln!("Error: {}", e);
+                std::process::exit(1);
+            }
+        }
+    }
+
+    // Import presets
+    if let Some(source) = args.import_preset.clone() {
+        match Args::import_presets(&source, args.yes).await {
+            Ok(names) if names.is_empty() => println!("✅ Presets already up to date."),
+            Ok(names) => println!("✅ Imported preset(s): {}", names.join(", ")),
+            Err(e) => {
+                eprintln!("Error: {}", e);
+                std::process::exit(1);
+            }
+        }
+        return Ok(());
+    }
+
+    // Apply preset if specified, otherwise pick one from the project type
     if let Some(preset_name) = args.preset.clone() {
         if let Err(e) = args.load_preset(&preset_name) {
             eprintln!("Error: {}", e);
             std::process::exit(1);
         }
+    } else if let Some((preset_name, reason)) = args.auto_select_preset() {
+        if args.verbose || args.log_level.is_info_enabled() {
+            eprintln!(
+                "ℹ️  Using preset '{}' ({}); pass --preset or --no-auto-preset to override",
+                preset_name, reason
+            );
+        }
     }
 
     // Validate arguments
@@ -142,10 +305,53 @@ async fn main() -> Result<()> {
         std::process::exit(1);
     }
 
+    // Show what the preset and flags resolve to without running anything
+    if args.explain {
+        println!("{}", args.explain());
+        return Ok(());
+    }
+
+    // CI gate: fail when a monitored port is taken
+    if args.fail_if_found {
+        let code = port_kill::preflight::run_fail_if_found(&args)?;
+        std::process::exit(code);
+    }
+
     // Handle cache subcommand
-    if let Some(cache_cmd) = args.cache.clone() {
-        let c = cache_cmd.args();
-        if c.list || c.dry_run {
+    if let Some(c) = args.command.as_ref().and_then(|cmd| cmd.args()) {
+        if let Some(host) = args.get_remote_host() {
+            match run_remote_cache(&host, &args.remote_bin, c) {
+                Ok(0) => {}
+                Ok(code) => std::process::exit(code),
+                Err(e) => {
+                    eprintln!("Error: {}", e);
+                    std::process::exit(1);
+                }
+            }
+            return Ok(());
+        }
+        if c.dry_run {
+            let plan = plan_clean(
+                &c.lang,
+                c.npx,
+                c.js_pm,
+                c.hf,
+                c.torch,
+                c.vercel,
+                c.cloudflare,
+                c.max_age(),
+                c.safe_delete,
+                c.trash,
+            )
+            .await;
+            if c.json {
+                print_or_json(&plan, true);
+            } else {
+                print_plan_table(&plan);
+            }
+            return Ok(());
+        }
+        if c.list {
             let resp = list_caches(
                 &c.lang,
                 c.npx,
@@ -154,7 +360,7 @@ async fn main() -> Result<()> {
                 c.torch,
                 c.vercel,
                 c.cloudflare,
-                c.stale_days,
+                c.max_age(),
             )
             .await;
             if c.json {
@@ -162,6 +368,32 @@ async fn main() -> Result<()> {
             } else {
                 print_list_table(&resp);
             }
+            if let Err(e) = record_snapshot() {
+                eprintln!("Warning: Failed to record cache history: {}", e);
+            }
+            return Ok(());
+        }
+        if let (true, Some(budget)) = (c.check, c.budget) {
+            let listing = list_caches(
+                &c.lang,
+                c.npx,
+                c.js_pm,
+                c.hf,
+                c.torch,
+                c.vercel,
+                c.cloudflare,
+                c.max_age(),
+            )
+            .await;
+            let report = check_budget(&listing, budget);
+            if c.json {
+                print_or_json(&report, true);
+            } else {
+                print_budget_report(&report);
+            }
+            if report.over_budget {
+                std::process::exit(1);
+            }
             return Ok(());
         }
         if c.clean {
@@ -170,19 +402,31 @@ async fn main() -> Result<()> {
                 c.npx,
                 c.js_pm,
                 c.safe_delete,
+                c.compress,
+                c.trash,
                 c.force,
                 c.hf,
                 c.torch,
                 c.vercel,
                 c.cloudflare,
-                c.stale_days,
+                c.max_age(),
+                &c.retention_policy(),
             )
             .await;
             print_or_json(&resp, c.json);
             return Ok(());
         }
-        if c.restore_last {
-            let resp = restore_last_backup().await;
+        if c.list_backups {
+            let resp = list_backup_entries();
+            if c.json {
+                print_or_json(&resp, true);
+            } else {
+                print_backup_list(&resp);
+            }
+            return Ok(());
+        }
+        if c.restore || c.restore_last {
+            let resp = restore_backup(c.backup.as_deref(), c.only.as_deref(), c.select, c.json).await;
             print_or_json(&resp, c.json);
             if resp.error.is_some() {
                 std::process::exit(1);
@@ -190,21 +434,58 @@ async fn main() -> Result<()> {
             return Ok(());
         }
         if c.doctor {
-            let report = doctor().await;
+            let report = doctor(c.fix, c.trash).await;
             print_or_json(&report, c.json);
             return Ok(());
         }
+        if c.prune_backups {
+            let resp = prune_backups(&c.retention_policy());
+            print_or_json(&resp, c.json);
+            if resp.error.is_some() {
+                std::process::exit(1);
+            }
+            return Ok(());
+        }
+        if c.trends {
+            let resp = compute_trends(&load_snapshots());
+            if c.json {
+                print_or_json(&resp, true);
+            } else {
+                print_trends(&resp);
+            }
+            return Ok(());
+        }
+        if !c.pin.is_empty() || !c.unpin.is_empty() {
+            match update_pins(&c.pin, &c.unpin) {
+                Ok(pins) => print_or_json(&pins, c.json),
+                Err(e) => {
+                    eprintln!("Error: Failed to update Hugging Face pins: {}", e);
+                    std::process::exit(1);
+                }
+            }
+            return Ok(());
+        }
+        if c.watch_disk {
+            watch_disk(
+                c.disk_threshold(),
+                std::time::Duration::from_secs(c.watch_interval),
+                &c.auto_clean,
+                c.json,
+            )
+            .await;
+            return Ok(());
+        }
     }
 
-    // Set up logging level based on verbose flag
+    // Set up logging level based on verbose flag, then --log-level (or config.toml)
     if args.verbose {
         std::env::set_var("RUST_LOG", "debug");
     } else if std::env::var("RUST_LOG").is_err() {
-        std::env::set_var("RUST_LOG", "info");
+        std::env::set_var("RUST_LOG", args.log_level.to_rust_log());
     }
 
     // Initialize logging
-    env_logger::init();
+    let _log_guard = port_kill::logging::init(&args)?;
 
     info!("Starting Console Port Kill application...");
     info!("Monitoring: {}", args.get_port_description());
@@ -248,6 +529,12 @@ async fn main() -> Result<()> {
         return Ok(());
     }
 
+    if args.restart_all {
+        let app = ConsolePortKillApp::new(args)?;
+        let code = app.restart_all().await?;
+        std::process::exit(code);
+    }
+
     if let Some(port) = args.restart {
         let app = ConsolePortKillApp::new(args)?;
         app.restart_port(port).await?;
@@ -266,6 +553,24 @@ async fn main() -> Result<()> {
         return Ok(());
     }
 
+    if args.prune_restarts {
+        let app = ConsolePortKillApp::new(args)?;
+        app.prune_restarts().await?;
+        return Ok(());
+    }
+
+    if let Some(path) = args.export_restarts.clone() {
+        let app = ConsolePortKillApp::new(args)?;
+        app.export_restarts(&path).await?;
+        return Ok(());
+    }
+
+    if let Some(path) = args.import_restarts.clone() {
+        let app = ConsolePortKillApp::new(args)?;
+        app.import_restarts(&path).await?;
+        return Ok(());
+    }
+
     if args.detect {
         let app = ConsolePortKillApp::new(args)?;
         app.detect_services().await?;
@@ -308,6 +613,16 @@ async fn main() -> Result<()> {
         return Ok(());
     }
 
+    if let Some(addr) = args.dashboard.clone() {
+        port_kill::dashboard::run_dashboard(args, &addr).await?;
+        return Ok(());
+    }
+
+    if let Some(addr) = args.serve.clone() {
+        port_kill::rest_api::run_server(args, &addr).await?;
+        return Ok(());
+    }
+
     if args.reset {
         let app = ConsolePortKillApp::new(args)?;
         app.reset_development_ports().await?;
@@ -326,6 +641,12 @@ async fn main() -> Result<()> {
         return Ok(());
     }
 
+    if args.timeline.is_some() && args.json {
+        let app = ConsolePortKillApp::new(args)?;
+        app.show_port_timeline_json()?;
+        return Ok(());
+    }
+
     if args.show_suggestions {
         let app = ConsolePortKillApp::new(args)?;
         app.show_ignore_suggestions().await?;
@@ -344,6 +665,11 @@ async fn main() -> Result<()> {
         return Ok(());
     }
 
+    if let Some(period) = args.report {
+        port_kill::report::run_report(&args, period).await?;
+        return Ok(());
+    }
+
     if args.audit {
         let app = ConsolePortKillApp::new(args)?;
         app.perform_security_audit().await?;
@@ -358,10 +684,36 @@ async fn main() -> Result<()> {
     }
 
     if args.guard_mode {
+        // Guard the ports the project's services are expected on as well
+        let detected = args.add_detected_guard_ports();
+        if !detected.is_empty() {
+            println!(
+                "🛡️  Also guarding ports of services detected here: {}",
+                detected
+                    .iter()
+                    .map(|port| port.to_string())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            );
+        }
+
         // Extract reservation parameters before moving args
         let reserve_port = args.reserve_port;
-        let project_name = args.project_name.clone();
-        let process_name = args.process_name.clone();
+        let mut project_name = args.project_name.clone();
+        let mut process_name = args.process_name.clone();
+
+        // A port a detected project expects is reserved for that project by default
+        if let Some(port) =
+            reserve_port.filter(|_| project_name.is_none() || process_name.is_none())
+        {
+            let port_map = port_kill::service_detector::ServiceDetector::new()
+                .port_map()
+                .unwrap_or_default();
+            if let Some((project, service)) = port_map.owner(port) {
+                project_name.get_or_insert_with(|| project.project.clone());
+                process_name.get_or_insert_with(|| service.service.clone());
+            }
+        }
 
         let app = ConsolePortKillApp::new(args)?;
 
@@ -4,12 +4,13 @@
 use port_kill::{
     cli::Args,
     console_app::ConsolePortKillApp,
+    login_item,
+    notify::send_desktop_notification,
     types::{ProcessInfo, StatusBarInfo},
     process_monitor::{get_processes_on_ports, kill_all_processes, kill_single_process},
 };
 use libappindicator::{AppIndicator, AppIndicatorStatus};
 use anyhow::Result;
-use clap::Parser;
 use log::{error, info};
 use std::env;
 use std::process;
@@ -20,12 +21,12 @@ use std::cell::RefCell;
 
 // GTK initialization for tray support
 use gtk::prelude::*;
-use gtk::{Menu, MenuItem, SeparatorMenuItem};
+use gtk::{CheckMenuItem, Menu, MenuItem, SeparatorMenuItem};
 
 #[tokio::main]
 async fn main() -> Result<()> {
     // Parse command-line arguments
-    let args = Args::parse();
+    let args = Args::parse_with_config();
     
     // Validate arguments
     if let Err(e) = args.validate() {
@@ -43,11 +44,17 @@ async fn main() -> Result<()> {
     env::set_var("RUST_LOG", log_level);
 
     // Initialize logging
-    env_logger::init();
+    let _log_guard = port_kill::logging::init(&args)?;
     
     info!("Starting Port Kill application on Linux...");
     info!("Monitoring: {}", args.get_port_description());
 
+    // Start at login
+    if args.install_login_item || args.remove_login_item {
+        login_item::run_login_item_command(args.install_login_item)?;
+        return Ok(());
+    }
+
     // Check if console mode is requested
     if args.console {
         info!("Starting console mode...");
@@ -128,6 +135,7 @@ async fn start_tray_mode(args: Args) -> Result<()> {
         }
     });
     menu.append(&kill_all_item);
+    menu.append(&login_item_toggle());
     
     let quit_item = MenuItem::with_label("Quit");
     quit_item.connect_activate(move |_| {
@@ -189,6 +197,7 @@ async fn start_tray_mode(args: Args) -> Result<()> {
                 }
             });
             new_menu.append(&kill_all_item);
+            new_menu.append(&login_item_toggle());
 
             let quit_item = MenuItem::with_label("Quit");
             quit_item.connect_activate(move |_| {
@@ -375,6 +384,28 @@ fn create_process_menu_with_verbose(args: &Args, processes: &HashMap<u16, Proces
     menu
 }
 
+/// "Start at login" check item, ticked when the autostart entry exists
+fn login_item_toggle() -> CheckMenuItem {
+    let item = CheckMenuItem::with_label("Start at login");
+    item.set_active(login_item::is_installed());
+    item.connect_toggled(|item| {
+        let result = if item.is_active() {
+            login_item::install(&login_item::current_arguments())
+                .map(|_| "Port Kill will start at login".to_string())
+        } else {
+            login_item::remove().map(|_| "Port Kill will no longer start at login".to_string())
+        };
+        match result {
+            Ok(summary) => send_desktop_notification("Port Kill", &summary),
+            Err(e) => {
+                error!("Failed to update the login item: {}", e);
+                send_desktop_notification("Port Kill", &e.to_string());
+            }
+        }
+    });
+    item
+}
+
 /// Update the tray icon based on process count
 fn update_tray_icon(indicator: &mut AppIndicator, process_count: usize) {
     let icon_name = match process_count {
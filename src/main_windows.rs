@@ -5,16 +5,22 @@ use port_kill::{
     process_monitor::{get_processes_on_ports, kill_all_processes},
 };
 use port_kill::cache::{
+    backup::prune_backups,
+    budget::{check_budget, print_budget_report},
     list::{list_caches, print_list_table},
+    plan::{plan_clean, print_plan_table},
+    remote::run_remote_cache,
     clean::clean_caches,
-    restore::restore_last_backup,
+    restore::{list_backup_entries, print_backup_list, restore_backup},
+    trends::{compute_trends, load_snapshots, print_trends, record_snapshot},
     doctor::doctor,
+    hf::update_pins,
+    watch::watch_disk,
 };
 use port_kill::cache::output::print_or_json;
 use port_kill::update_check;
 use tray_item::TrayItem;
 use anyhow::Result;
-use clap::Parser;
 use log::{error, info};
 use std::collections::HashMap;
 use std::time::Duration;
@@ -22,7 +28,7 @@ use std::time::Duration;
 #[tokio::main]
 async fn main() -> Result<()> {
     // Parse command line arguments
-    let args = Args::parse();
+    let args = Args::parse_with_config();
     
     // Validate arguments
     if let Err(e) = args.validate() {
@@ -40,14 +46,14 @@ async fn main() -> Result<()> {
     std::env::set_var("RUST_LOG", log_level);
     
     // Initialize logging
-    env_logger::init();
+    let _log_guard = port_kill::logging::init(&args)?;
     
     info!("Starting Port Kill application on Windows...");
     info!("Monitoring: {}", args.get_port_description());
     
     // Handle self-update
     if args.self_update {
-        match update_check::self_update().await {
+        match update_check::self_update(args.update_channel).await {
             Ok(()) => return Ok(()),
             Err(e) => {
                 eprintln!("⚠️  Self-update failed: {}", e);
@@ -59,7 +65,7 @@ async fn main() -> Result<()> {
     // Handle explicit update check
     if args.check_updates {
         let current_version = env!("CARGO_PKG_VERSION");
-        match update_check::check_for_updates(current_version).await {
+        match update_check::check_for_updates(current_version, args.update_channel).await {
             Ok(Some(update_info)) => {
                 update_check::print_update_check_result(&update_info);
                 return Ok(());
@@ -77,29 +83,73 @@ async fn main() -> Result<()> {
 
     // Background update notification (non-blocking)
     let current_version = env!("CARGO_PKG_VERSION");
-    if let Ok(Some(update_info)) = update_check::check_for_updates(current_version).await {
+    if let Ok(Some(update_info)) = update_check::check_for_updates(current_version, args.update_channel).await {
         update_check::print_update_notification(&update_info);
     }
 
     // Handle cache subcommand on Windows too (parity with console binary)
-    if let Some(cache_cmd) = args.cache.clone() {
-        let c = cache_cmd.args();
-        if c.list || c.dry_run {
-            let resp = list_caches(&c.lang, c.npx, c.js_pm, c.hf, c.torch, c.vercel, c.cloudflare, c.stale_days).await;
+    if let Some(c) = args.command.as_ref().and_then(|cmd| cmd.args()) {
+        if let Some(host) = args.get_remote_host() {
+            match run_remote_cache(&host, &args.remote_bin, c) {
+                Ok(0) => {}
+                Ok(code) => std::process::exit(code),
+                Err(e) => {
+                    eprintln!("Error: {}", e);
+                    std::process::exit(1);
+                }
+            }
+            return Ok(());
+        }
+        if c.dry_run {
+            let plan = plan_clean(&c.lang, c.npx, c.js_pm, c.hf, c.torch, c.vercel, c.cloudflare, c.max_age(), c.safe_delete, c.trash).await;
+            if c.json {
+                print_or_json(&plan, true);
+            } else {
+                print_plan_table(&plan);
+            }
+            return Ok(());
+        }
+        if c.list {
+            let resp = list_caches(&c.lang, c.npx, c.js_pm, c.hf, c.torch, c.vercel, c.cloudflare, c.max_age()).await;
             if c.json {
                 print_or_json(&resp, true);
             } else {
                 print_list_table(&resp);
             }
+            if let Err(e) = record_snapshot() {
+                eprintln!("Warning: Failed to record cache history: {}", e);
+            }
+            return Ok(());
+        }
+        if let (true, Some(budget)) = (c.check, c.budget) {
+            let listing = list_caches(&c.lang, c.npx, c.js_pm, c.hf, c.torch, c.vercel, c.cloudflare, c.max_age()).await;
+            let report = check_budget(&listing, budget);
+            if c.json {
+                print_or_json(&report, true);
+            } else {
+                print_budget_report(&report);
+            }
+            if report.over_budget {
+                std::process::exit(1);
+            }
             return Ok(());
         }
         if c.clean {
-            let resp = clean_caches(&c.lang, c.npx, c.js_pm, c.safe_delete, c.force, c.hf, c.torch, c.vercel, c.cloudflare, c.stale_days).await;
+            let resp = clean_caches(&c.lang, c.npx, c.js_pm, c.safe_delete, c.compress, c.trash, c.force, c.hf, c.torch, c.vercel, c.cloudflare, c.max_age(), &c.retention_policy()).await;
             print_or_json(&resp, c.json);
             return Ok(());
         }
-        if c.restore_last {
-            let resp = restore_last_backup().await;
+        if c.list_backups {
+            let resp = list_backup_entries();
+            if c.json {
+                print_or_json(&resp, true);
+            } else {
+                print_backup_list(&resp);
+            }
+            return Ok(());
+        }
+        if c.restore || c.restore_last {
+            let resp = restore_backup(c.backup.as_deref(), c.only.as_deref(), c.select, c.json).await;
             print_or_json(&resp, c.json);
             if resp.error.is_some() {
                 std::process::exit(1);
@@ -107,10 +157,47 @@ async fn main() -> Result<()> {
             return Ok(());
         }
         if c.doctor {
-            let report = doctor().await;
+            let report = doctor(c.fix, c.trash).await;
             print_or_json(&report, c.json);
             return Ok(());
         }
+        if c.prune_backups {
+            let resp = prune_backups(&c.retention_policy());
+            print_or_json(&resp, c.json);
+            if resp.error.is_some() {
+                std::process::exit(1);
+            }
+            return Ok(());
+        }
+        if c.trends {
+            let resp = compute_trends(&load_snapshots());
+            if c.json {
+                print_or_json(&resp, true);
+            } else {
+                print_trends(&resp);
+            }
+            return Ok(());
+        }
+        if !c.pin.is_empty() || !c.unpin.is_empty() {
+            match update_pins(&c.pin, &c.unpin) {
+                Ok(pins) => print_or_json(&pins, c.json),
+                Err(e) => {
+                    eprintln!("Error: Failed to update Hugging Face pins: {}", e);
+                    std::process::exit(1);
+                }
+            }
+            return Ok(());
+        }
+        if c.watch_disk {
+            watch_disk(
+                c.disk_threshold(),
+                std::time::Duration::from_secs(c.watch_interval),
+                &c.auto_clean,
+                c.json,
+            )
+            .await;
+            return Ok(());
+        }
     }
     
     // Check if running in console mode
@@ -0,0 +1,141 @@
+use std::process::Command;
+
+/// Show a desktop notification using the platform's built-in tooling.
+/// Best effort: failures are logged and otherwise ignored.
+pub fn send_desktop_notification(title: &str, body: &str) {
+    let result = platform_notify(title, body);
+    if let Err(e) = result {
+        log::debug!("Desktop notification failed: {}", e);
+    }
+}
+
+/// Buttons offered on notifications about a single process
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NotificationAction {
+    /// Relaunch the process with its saved command
+    Restart,
+    /// Add the process name to `ignore_processes` in the config file
+    IgnoreProcess,
+    /// Open a terminal with details about the port
+    OpenDetails,
+}
+
+impl NotificationAction {
+    pub fn label(self) -> &'static str {
+        match self {
+            NotificationAction::Restart => "Restart",
+            NotificationAction::IgnoreProcess => "Ignore process",
+            NotificationAction::OpenDetails => "Open details",
+        }
+    }
+}
+
+/// Show a notification with action buttons and block until it is answered.
+/// Returns the chosen action, or None when it was dismissed. Platforms without
+/// actionable notifications get a plain one and always return None.
+pub fn send_actionable_notification(
+    title: &str,
+    body: &str,
+    actions: &[NotificationAction],
+) -> Option<NotificationAction> {
+    match platform_notify_with_actions(title, body, actions) {
+        Ok(chosen) => chosen,
+        Err(e) => {
+            log::debug!("Actionable notification failed: {}", e);
+            send_desktop_notification(title, body);
+            None
+        }
+    }
+}
+
+#[cfg(target_os = "macos")]
+fn platform_notify_with_actions(
+    title: &str,
+    body: &str,
+    actions: &[NotificationAction],
+) -> Result<Option<NotificationAction>, mac_notification_sys::error::Error> {
+    use mac_notification_sys::{MainButton, Notification, NotificationResponse};
+
+    let labels: Vec<&str> = actions.iter().map(|action| action.label()).collect();
+    let mut notification = Notification::new();
+    notification
+        .title(title)
+        .message(body)
+        .close_button("Dismiss");
+    match labels.as_slice() {
+        [] => {}
+        [label] => {
+            notification.main_button(MainButton::SingleAction(label));
+        }
+        labels => {
+            notification.main_button(MainButton::DropdownActions("Actions", labels));
+        }
+    }
+
+    Ok(match notification.send()? {
+        NotificationResponse::ActionButton(label) => actions
+            .iter()
+            .copied()
+            .find(|action| action.label() == label),
+        _ => None,
+    })
+}
+
+#[cfg(not(target_os = "macos"))]
+fn platform_notify_with_actions(
+    title: &str,
+    body: &str,
+    _actions: &[NotificationAction],
+) -> std::io::Result<Option<NotificationAction>> {
+    platform_notify(title, body)?;
+    Ok(None)
+}
+
+#[cfg(target_os = "macos")]
+fn platform_notify(title: &str, body: &str) -> std::io::Result<()> {
+    let script = format!(
+        "display notification \"{}\" with title \"{}\"",
+        escape_applescript(body),
+        escape_applescript(title)
+    );
+    Command::new("osascript").arg("-e").arg(script).output()?;
+    Ok(())
+}
+
+#[cfg(target_os = "macos")]
+fn escape_applescript(s: &str) -> String {
+    s.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+#[cfg(target_os = "linux")]
+fn platform_notify(title: &str, body: &str) -> std::io::Result<()> {
+    Command::new("notify-send")
+        .arg("--app-name=Port Kill")
+        .arg(title)
+        .arg(body)
+        .output()?;
+    Ok(())
+}
+
+#[cfg(target_os = "windows")]
+fn platform_notify(title: &str, body: &str) -> std::io::Result<()> {
+    let script = format!(
+        "[void] [System.Reflection.Assembly]::LoadWithPartialName('System.Windows.Forms'); \
+         $n = New-Object System.Windows.Forms.NotifyIcon; \
+         $n.Icon = [System.Drawing.SystemIcons]::Information; \
+         $n.Visible = $true; \
+         $n.ShowBalloonTip(10000, '{}', '{}', 'Info'); \
+         Start-Sleep -Seconds 10; $n.Dispose()",
+        title.replace('\'', "''"),
+        body.replace('\'', "''")
+    );
+    Command::new("powershell")
+        .args(["-NoProfile", "-WindowStyle", "Hidden", "-Command", &script])
+        .spawn()?;
+    Ok(())
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "linux", target_os = "windows")))]
+fn platform_notify(_title: &str, _body: &str) -> std::io::Result<()> {
+    Ok(())
+}
@@ -1,13 +1,18 @@
+use crate::format_version::ORCHESTRATION_VERSION;
+use crate::project_dir::find_upward;
+use crate::project_env::ProjectEnv;
+use crate::service_detector::{DiscoveredService, PortMap};
 use anyhow::{Context, Result};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::fs;
 use std::path::{Path, PathBuf};
 use std::process::{Child, Command};
+use std::time::Instant;
 use tokio::time::{sleep, Duration};
 
 /// Configuration for a single service
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct ServiceConfig {
     /// Command to run the service
     pub command: String,
@@ -28,20 +33,152 @@ pub struct ServiceConfig {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub depends_on: Option<Vec<String>>,
     
-    /// Health check command (optional)
+    /// Health check: a command that exits with 0, or an `http://`/`https://` URL that
+    /// answers with a 2xx status, once the service is ready. Dependents wait for it.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub healthcheck: Option<String>,
+
+    /// Seconds between health check attempts (default 2)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub healthcheck_interval: Option<u64>,
+
+    /// Seconds a single health check may take before it counts as failed (default 5)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub healthcheck_timeout: Option<u64>,
+
+    /// Failed health checks allowed before the service fails to start (default 30)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub healthcheck_retries: Option<u32>,
     
     /// Delay before considering service started (seconds)
     #[serde(skip_serializing_if = "Option::is_none")]
     pub startup_delay: Option<u64>,
+
+    /// Wait up to this many seconds (0: no limit) for `port` to accept connections
+    /// before the service counts as started and its dependents start
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub wait_for_port: Option<u64>,
+}
+
+/// Fill in `{{port}}` (the service's port) and `{{cwd}}` (port-kill's current directory)
+fn expand_service_placeholders(text: &str, service: &ServiceConfig) -> std::result::Result<String, String> {
+    let mut values = HashMap::new();
+    if let Some(port) = service.port {
+        values.insert("port", port.to_string());
+    }
+    if let Ok(cwd) = std::env::current_dir() {
+        values.insert("cwd", cwd.to_string_lossy().to_string());
+    }
+    crate::command_line::expand_placeholders(text, &values)
+}
+
+/// How a `healthcheck` is probed
+#[derive(Debug, Clone, PartialEq)]
+enum HealthProbe {
+    /// Healthy when a GET answers with a 2xx status
+    Http(String),
+    /// Healthy when the command exits with status 0. It runs in the shell (`sh -c`,
+    /// `cmd /C` on Windows) so `||`, pipes and quoting work as they do in a terminal.
+    Command(String),
+}
+
+/// A service's `healthcheck` with its placeholders filled in, run in the service's
+/// directory and environment
+#[derive(Debug, Clone)]
+struct HealthCheck {
+    probe: HealthProbe,
+    dir: PathBuf,
+    env: Vec<(String, String)>,
+    interval: Duration,
+    timeout: Duration,
+    retries: u32,
+}
+
+impl HealthCheck {
+    fn new(
+        check: &str,
+        service: &ServiceConfig,
+        dir: PathBuf,
+        env: Vec<(String, String)>,
+        expand: impl Fn(&str) -> Result<String>,
+    ) -> Result<Self> {
+        let probe = if check.starts_with("http://") || check.starts_with("https://") {
+            HealthProbe::Http(expand(check)?)
+        } else {
+            let command = expand(check)?;
+            if command.trim().is_empty() {
+                return Err(anyhow::anyhow!("Empty healthcheck command"));
+            }
+            HealthProbe::Command(command)
+        };
+        Ok(Self {
+            probe,
+            dir,
+            env,
+            interval: Duration::from_secs(service.healthcheck_interval.unwrap_or(2)),
+            timeout: Duration::from_secs(service.healthcheck_timeout.unwrap_or(5)),
+            retries: service.healthcheck_retries.unwrap_or(30),
+        })
+    }
+
+    /// Run the check once; the error says why the service isn't healthy
+    async fn probe(&self) -> std::result::Result<(), String> {
+        match self.probe {
+            HealthProbe::Http(ref url) => {
+                let client = reqwest::Client::builder()
+                    .timeout(self.timeout)
+                    .build()
+                    .map_err(|e| e.to_string())?;
+                let response = client.get(url).send().await.map_err(|e| e.to_string())?;
+                if response.status().is_success() {
+                    Ok(())
+                } else {
+                    Err(format!("{} answered {}", url, response.status()))
+                }
+            }
+            HealthProbe::Command(ref command) => {
+                #[cfg(not(target_os = "windows"))]
+                let mut cmd = {
+                    let mut cmd = tokio::process::Command::new("sh");
+                    cmd.arg("-c").arg(command);
+                    cmd
+                };
+                #[cfg(target_os = "windows")]
+                let mut cmd = {
+                    let mut cmd = tokio::process::Command::new("cmd");
+                    cmd.arg("/C").arg(command);
+                    cmd
+                };
+                cmd.current_dir(&self.dir)
+                    .envs(self.env.iter().cloned())
+                    .stdin(std::process::Stdio::null())
+                    .stdout(std::process::Stdio::null())
+                    .stderr(std::process::Stdio::null())
+                    .kill_on_drop(true);
+                match tokio::time::timeout(self.timeout, cmd.status()).await {
+                    Ok(Ok(status)) if status.success() => Ok(()),
+                    Ok(Ok(status)) => Err(format!("'{}' failed with {}", command, status)),
+                    Ok(Err(e)) => Err(format!("'{}' failed to run: {}", command, e)),
+                    Err(_) => Err(format!(
+                        "'{}' took longer than {} seconds",
+                        command,
+                        self.timeout.as_secs()
+                    )),
+                }
+            }
+        }
+    }
 }
 
 /// Main orchestration configuration
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct OrchestrationConfig {
-    /// Version of the config format
-    #[serde(skip_serializing_if = "Option::is_none")]
+    /// Version of the config format (`version: 1` and `version: "1"` both work)
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "deserialize_version"
+    )]
     pub version: Option<String>,
     
     /// Services to manage
@@ -50,6 +187,47 @@ pub struct OrchestrationConfig {
     /// Global environment variables
     #[serde(skip_serializing_if = "Option::is_none")]
     pub env: Option<HashMap<String, String>>,
+
+    /// Start services with their directory's direnv/mise environment (like `--load-env`)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub load_env: Option<bool>,
+
+    /// Run each service in its own window of this tmux session (like `--tmux`)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tmux_session: Option<String>,
+}
+
+impl OrchestrationConfig {
+    /// Format version of this config; files without a version are version 1
+    pub fn format_version(&self) -> Result<u32> {
+        let version = match self.version {
+            Some(ref version) => version.trim(),
+            None => return Ok(1),
+        };
+        // Accept "1" as well as "1.0"
+        version
+            .split('.')
+            .next()
+            .and_then(|major| major.parse().ok())
+            .ok_or_else(|| anyhow::anyhow!("Invalid config version '{}'", version))
+    }
+}
+
+fn deserialize_version<'de, D>(deserializer: D) -> std::result::Result<Option<String>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Version {
+        Text(String),
+        Number(serde_yaml::Number),
+    }
+
+    Ok(Option::<Version>::deserialize(deserializer)?.map(|version| match version {
+        Version::Text(text) => text,
+        Version::Number(number) => number.to_string(),
+    }))
 }
 
 /// Tracks running services
@@ -59,6 +237,7 @@ pub struct RunningService {
     pub pid: u32,
     pub port: Option<u16>,
     pub child: Option<Child>,
+    pub started_at: Instant,
 }
 
 /// Service orchestrator
@@ -66,6 +245,16 @@ pub struct Orchestrator {
     config: OrchestrationConfig,
     config_path: PathBuf,
     running_services: HashMap<String, RunningService>,
+    /// Restarts requested per service since the orchestrator was loaded
+    restarts: HashMap<String, u32>,
+    /// Services whose process exited on its own, with the exit code
+    exited: HashMap<String, Option<i32>>,
+    /// Where service output goes instead of the terminal, one `<service>.log` each
+    log_dir: Option<PathBuf>,
+    /// Load direnv/mise environments even when the config does not ask for it
+    load_env: bool,
+    /// tmux session given on the command line, which wins over the config's
+    tmux_session: Option<String>,
 }
 
 impl Orchestrator {
@@ -76,27 +265,42 @@ impl Orchestrator {
         
         let config: OrchestrationConfig = serde_yaml::from_str(&content)
             .context("Failed to parse YAML configuration")?;
+
+        let version = config.format_version()?;
+        if version > ORCHESTRATION_VERSION {
+            anyhow::bail!(
+                "{} uses config version {} but this port-kill only understands up to version {}; please upgrade port-kill",
+                config_path.display(),
+                version,
+                ORCHESTRATION_VERSION
+            );
+        }
         
         Ok(Self {
             config,
             config_path: config_path.to_path_buf(),
             running_services: HashMap::new(),
+            restarts: HashMap::new(),
+            exited: HashMap::new(),
+            log_dir: None,
+            load_env: false,
+            tmux_session: None,
         })
     }
     
     /// Try to find and load config from default locations
     pub fn load_from_default() -> Result<Self> {
-        let possible_paths = vec![
-            PathBuf::from(".port-kill.yaml"),
-            PathBuf::from(".port-kill.yml"),
-            PathBuf::from("port-kill.yaml"),
-            PathBuf::from("port-kill.yml"),
+        let possible_names = [
+            ".port-kill.yaml",
+            ".port-kill.yml",
+            "port-kill.yaml",
+            "port-kill.yml",
         ];
         
-        for path in possible_paths {
-            if path.exists() {
-                return Self::load(&path);
-            }
+        // Look in the current directory and its parents up to the git root
+        let cwd = std::env::current_dir()?;
+        if let Some(path) = find_upward(&cwd, &possible_names) {
+            return Self::load(&path);
         }
         
         Err(anyhow::anyhow!(
@@ -108,10 +312,84 @@ impl Orchestrator {
     pub fn config(&self) -> &OrchestrationConfig {
         &self.config
     }
+
+    /// Send the output of services started from now on to `<dir>/<service>.log`
+    pub fn capture_output(&mut self, dir: PathBuf) {
+        self.log_dir = Some(dir);
+    }
+
+    /// Give services started from now on the direnv/mise environment of their directory
+    pub fn load_project_env(&mut self, load: bool) {
+        self.load_env = load;
+    }
+
+    /// Start services from now on in their own window of the tmux `session`
+    pub fn run_in_tmux(&mut self, session: String) {
+        self.tmux_session = Some(session);
+    }
+
+    /// Notice services whose process has exited on its own. Returns their names.
+    pub fn reap_exited(&mut self) -> Vec<String> {
+        let mut exited = Vec::new();
+        for (name, service) in &mut self.running_services {
+            if let Some(ref mut child) = service.child {
+                if let Ok(Some(status)) = child.try_wait() {
+                    self.exited.insert(name.clone(), status.code());
+                    exited.push(name.clone());
+                }
+            }
+        }
+        for name in &exited {
+            self.running_services.remove(name);
+        }
+        exited.sort();
+        exited
+    }
     
+    /// Re-read the config file and bring running services in line with it: removed
+    /// and changed services are stopped, then anything not running is started.
+    /// Returns one line per service that changed.
+    pub async fn reload(&mut self) -> Result<Vec<String>> {
+        let updated = Self::load(&self.config_path)?.config;
+        let env_changed = updated.env != self.config.env
+            || updated.load_env != self.config.load_env
+            || updated.tmux_session != self.config.tmux_session;
+
+        let mut changes = Vec::new();
+        let mut to_stop = Vec::new();
+        for (name, service) in &self.config.services {
+            match updated.services.get(name) {
+                None => {
+                    changes.push(format!("service '{}' removed", name));
+                    to_stop.push(name.clone());
+                }
+                Some(new) if new != service || env_changed => {
+                    changes.push(format!("service '{}' changed", name));
+                    to_stop.push(name.clone());
+                }
+                Some(_) => {}
+            }
+        }
+        for name in updated.services.keys() {
+            if !self.config.services.contains_key(name) {
+                changes.push(format!("service '{}' added", name));
+            }
+        }
+        changes.sort();
+
+        for name in to_stop {
+            if self.running_services.contains_key(&name) {
+                self.stop_service(&name).await?;
+            }
+        }
+        self.config = updated;
+        self.start_all().await?;
+        Ok(changes)
+    }
+
     /// Start all services in dependency order
     pub async fn start_all(&mut self) -> Result<()> {
-        log::info!("Starting all services...");
+        tracing::info!("Starting all services...");
         
         let service_order = self.resolve_dependencies()?;
         
@@ -119,13 +397,13 @@ impl Orchestrator {
             self.start_service(&service_name).await?;
         }
         
-        log::info!("All services started successfully");
+        tracing::info!("All services started successfully");
         Ok(())
     }
     
     /// Stop all running services
     pub async fn stop_all(&mut self) -> Result<()> {
-        log::info!("Stopping all services...");
+        tracing::info!("Stopping all services...");
         
         let service_names: Vec<String> = self.running_services.keys().cloned().collect();
         
@@ -134,14 +412,14 @@ impl Orchestrator {
         }
         
         self.running_services.clear();
-        log::info!("All services stopped");
+        tracing::info!("All services stopped");
         Ok(())
     }
     
     /// Start a specific service
     pub async fn start_service(&mut self, service_name: &str) -> Result<()> {
         if self.running_services.contains_key(service_name) {
-            log::warn!("Service '{}' is already running", service_name);
+            tracing::warn!(service = %service_name, "Service '{}' is already running", service_name);
             return Ok(());
         }
         
@@ -149,7 +427,7 @@ impl Orchestrator {
             .ok_or_else(|| anyhow::anyhow!("Service '{}' not found in configuration", service_name))?
             .clone();
         
-        log::info!("Starting service '{}'...", service_name);
+        tracing::info!(service = %service_name, "Starting service '{}'...", service_name);
         
         // Start dependencies first
         if let Some(ref deps) = service_config.depends_on {
@@ -161,8 +439,15 @@ impl Orchestrator {
             }
         }
         
-        // Parse and execute the command
-        let parts = crate::command_line::parse_command_line(&service_config.command);
+        // Resolve {{port}} / {{cwd}} placeholders, then parse and execute the command
+        let expand = |text: &str| {
+            expand_service_placeholders(text, &service_config)
+                .map_err(|e| anyhow::anyhow!("Service '{}': {}", service_name, e))
+        };
+        let parts = crate::command_line::parse_command_line(&service_config.command)
+            .iter()
+            .map(|part| expand(part))
+            .collect::<Result<Vec<_>>>()?;
         if parts.is_empty() {
             return Err(anyhow::anyhow!("Empty command for service '{}'", service_name));
         }
@@ -170,23 +455,94 @@ impl Orchestrator {
         let program = &parts[0];
         let args = &parts[1..];
         
-        let config_parent = self.config_path.parent().unwrap_or(Path::new("."));
+        // A bare file name has an empty parent, which is the current directory
+        let config_parent = self
+            .config_path
+            .parent()
+            .filter(|parent| !parent.as_os_str().is_empty())
+            .unwrap_or(Path::new("."));
         let working_dir = if let Some(ref dir) = service_config.dir {
-            config_parent.join(dir)
+            config_parent.join(expand(dir)?)
         } else {
             config_parent.to_path_buf()
         };
         
+        // The project's direnv/mise environment comes first so the config can override it
+        let project_env = if self.load_env || self.config.load_env == Some(true) {
+            let project_env = ProjectEnv::load(&working_dir)
+                .context(format!("Failed to load the environment for service '{}'", service_name))?;
+            if !project_env.is_empty() {
+                tracing::info!(
+                    service = %service_name,
+                    "Loaded {} environment for service '{}'",
+                    project_env.sources.join(" and "),
+                    service_name
+                );
+            }
+            project_env
+        } else {
+            ProjectEnv::default()
+        };
+        let mut env = Vec::new();
+        for vars in [&self.config.env, &service_config.env].into_iter().flatten() {
+            for (key, value) in vars {
+                env.push((key.clone(), expand(value)?));
+            }
+        }
+        let health_check = match service_config.healthcheck {
+            Some(ref check) => {
+                let env = project_env
+                    .vars
+                    .iter()
+                    .filter_map(|(key, value)| Some((key.clone(), value.clone()?)))
+                    .chain(env.iter().cloned())
+                    .collect();
+                let check = HealthCheck::new(check, &service_config, working_dir.clone(), env, expand)
+                    .map_err(|e| anyhow::anyhow!("Service '{}': {}", service_name, e))?;
+                Some(check)
+            }
+            None => None,
+        };
+
+        // In tmux the service gets a window with its own scrollback instead of a child process
+        if let Some(session) = self
+            .tmux_session
+            .clone()
+            .or_else(|| self.config.tmux_session.clone())
+        {
+            let env: Vec<(String, String)> = project_env
+                .vars
+                .iter()
+                .filter_map(|(key, value)| Some((key.clone(), value.clone()?)))
+                .chain(env)
+                .collect();
+            let pid = crate::tmux::open_window(
+                &session,
+                service_name,
+                &working_dir,
+                &env,
+                &crate::command_line::quote_command_line(&parts),
+            )
+            .context(format!("Failed to start service '{}' in tmux", service_name))?;
+            return self
+                .started(service_name, &service_config, pid, None, health_check)
+                .await;
+        }
+
         let mut cmd = Command::new(program);
         cmd.args(args).current_dir(&working_dir);
+        if let Some(ref dir) = self.log_dir {
+            fs::create_dir_all(dir)?;
+            let log = fs::OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(dir.join(format!("{}.log", service_name)))?;
+            cmd.stdout(log.try_clone()?).stderr(log);
+        }
         
         // Add environment variables
-        if let Some(ref global_env) = self.config.env {
-            cmd.envs(global_env);
-        }
-        if let Some(ref service_env) = service_config.env {
-            cmd.envs(service_env);
-        }
+        project_env.apply(&mut cmd);
+        cmd.envs(env);
         
         // Spawn the process
         let child = cmd.spawn()
@@ -194,11 +550,24 @@ impl Orchestrator {
         
         let pid = child.id();
         
-        log::info!("Service '{}' started with PID {}", service_name, pid);
-        
+        tracing::info!(service = %service_name, pid, "Service '{}' started with PID {}", service_name, pid);
+        self.started(service_name, &service_config, pid, Some(child), health_check)
+            .await
+    }
+
+    /// Wait out the service's startup delay and record it as running, then wait for its
+    /// port and health check so that dependents only start once it is ready
+    async fn started(
+        &mut self,
+        service_name: &str,
+        service_config: &ServiceConfig,
+        pid: u32,
+        child: Option<Child>,
+        health_check: Option<HealthCheck>,
+    ) -> Result<()> {
         // Wait for startup delay if specified
         if let Some(delay) = service_config.startup_delay {
-            log::info!("Waiting {} seconds for service '{}' to start...", delay, service_name);
+            tracing::info!(service = %service_name, "Waiting {} seconds for service '{}' to start...", delay, service_name);
             sleep(Duration::from_secs(delay)).await;
         }
         
@@ -209,53 +578,139 @@ impl Orchestrator {
                 name: service_name.to_string(),
                 pid,
                 port: service_config.port,
-                child: Some(child),
+                child,
+                started_at: Instant::now(),
             },
         );
-        
+        self.exited.remove(service_name);
+
+        match (service_config.wait_for_port, service_config.port) {
+            (Some(_), None) => {
+                tracing::warn!(service = %service_name, "Service '{}' sets wait_for_port but has no port", service_name);
+            }
+            (Some(timeout), Some(port)) => {
+                tracing::info!(service = %service_name, port, "Waiting for service '{}' to open port {}...", service_name, port);
+                let pending = crate::port_wait::wait_for_ports(
+                    "localhost",
+                    &[port],
+                    crate::cli::WaitState::Open,
+                    Duration::from_secs(timeout),
+                    Duration::from_millis(250),
+                )
+                .await;
+                if !pending.is_empty() {
+                    return Err(anyhow::anyhow!(
+                        "Service '{}' did not open port {} within {} seconds",
+                        service_name,
+                        port,
+                        timeout
+                    ));
+                }
+            }
+            (None, _) => {}
+        }
+
+        if let Some(check) = health_check {
+            if let Err(e) = self.wait_until_healthy(service_name, &check).await {
+                // An unhealthy service isn't left running behind the error
+                if let Err(stop_error) = self.stop_service(service_name).await {
+                    tracing::warn!(service = %service_name, error = %stop_error, "Failed to stop service '{}': {}", service_name, stop_error);
+                }
+                return Err(e);
+            }
+        }
+
         Ok(())
     }
+
+    /// Probe the health check every interval until it passes, the service exits or the
+    /// retries run out
+    async fn wait_until_healthy(&mut self, service_name: &str, check: &HealthCheck) -> Result<()> {
+        tracing::info!(service = %service_name, "Waiting for service '{}' to pass its health check...", service_name);
+        let mut failures = 0;
+        loop {
+            let error = match check.probe().await {
+                Ok(()) => {
+                    tracing::info!(service = %service_name, "Service '{}' is healthy", service_name);
+                    return Ok(());
+                }
+                Err(error) => error,
+            };
+
+            let exit_status = self
+                .running_services
+                .get_mut(service_name)
+                .and_then(|service| service.child.as_mut())
+                .and_then(|child| child.try_wait().ok().flatten());
+            if let Some(status) = exit_status {
+                return Err(anyhow::anyhow!(
+                    "Service '{}' exited ({}) before passing its health check",
+                    service_name,
+                    status
+                ));
+            }
+            if failures >= check.retries {
+                return Err(anyhow::anyhow!(
+                    "Service '{}' failed its health check {} times: {}",
+                    service_name,
+                    failures + 1,
+                    error
+                ));
+            }
+            failures += 1;
+            tracing::debug!(
+                service = %service_name,
+                "Health check for service '{}' failed ({}/{}): {}",
+                service_name,
+                failures,
+                check.retries,
+                error
+            );
+            sleep(check.interval).await;
+        }
+    }
     
     /// Stop a specific service
     pub async fn stop_service(&mut self, service_name: &str) -> Result<()> {
         let mut service = match self.running_services.remove(service_name) {
             Some(s) => s,
             None => {
-                log::warn!("Service '{}' is not running", service_name);
+                tracing::warn!(service = %service_name, "Service '{}' is not running", service_name);
                 return Ok(());
             }
         };
         
-        log::info!("Stopping service '{}' (PID {})...", service_name, service.pid);
+        tracing::info!(service = %service_name, pid = service.pid, "Stopping service '{}' (PID {})...", service_name, service.pid);
         
-        if let Some(mut child) = service.child.take() {
-            // Try graceful shutdown first
-            #[cfg(not(target_os = "windows"))]
-            {
-                use nix::sys::signal::{kill, Signal};
-                use nix::unistd::Pid;
-                
-                if let Err(e) = kill(Pid::from_raw(service.pid as i32), Signal::SIGTERM) {
-                    log::warn!("Failed to send SIGTERM to service '{}': {}", service_name, e);
-                }
-                
-                // Wait a bit for graceful shutdown
-                sleep(Duration::from_millis(500)).await;
-            }
+        // Try graceful shutdown first
+        #[cfg(not(target_os = "windows"))]
+        {
+            use nix::sys::signal::{kill, Signal};
+            use nix::unistd::Pid;
             
-            #[cfg(target_os = "windows")]
-            {
-                let _ = Command::new("taskkill")
-                    .args(&["/PID", &service.pid.to_string(), "/T"])
-                    .output();
-                
-                sleep(Duration::from_millis(500)).await;
+            if let Err(e) = kill(Pid::from_raw(service.pid as i32), Signal::SIGTERM) {
+                tracing::warn!(service = %service_name, error = %e, "Failed to send SIGTERM to service '{}': {}", service_name, e);
             }
             
-            // Force kill if still running
+            // Wait a bit for graceful shutdown
+            sleep(Duration::from_millis(500)).await;
+        }
+        
+        #[cfg(target_os = "windows")]
+        {
+            let _ = Command::new("taskkill")
+                .args(&["/PID", &service.pid.to_string(), "/T"])
+                .output();
+            
+            sleep(Duration::from_millis(500)).await;
+        }
+        
+        // Force kill if still running; a service in tmux has no child and its window
+        // closes once SIGTERM ends it
+        if let Some(mut child) = service.child.take() {
             match child.kill() {
-                Ok(_) => log::info!("Service '{}' stopped", service_name),
-                Err(e) => log::warn!("Failed to kill service '{}': {}", service_name, e),
+                Ok(_) => tracing::info!(service = %service_name, "Service '{}' stopped", service_name),
+                Err(e) => tracing::warn!(service = %service_name, error = %e, "Failed to kill service '{}': {}", service_name, e),
             }
         }
         
@@ -264,13 +719,14 @@ impl Orchestrator {
     
     /// Restart a specific service
     pub async fn restart_service(&mut self, service_name: &str) -> Result<()> {
-        log::info!("Restarting service '{}'...", service_name);
+        tracing::info!(service = %service_name, "Restarting service '{}'...", service_name);
         
         self.stop_service(service_name).await?;
         sleep(Duration::from_secs(1)).await;
         self.start_service(service_name).await?;
+        *self.restarts.entry(service_name.to_string()).or_default() += 1;
         
-        log::info!("Service '{}' restarted successfully", service_name);
+        tracing::info!(service = %service_name, "Service '{}' restarted successfully", service_name);
         Ok(())
     }
     
@@ -280,6 +736,12 @@ impl Orchestrator {
         
         for (name, config) in &self.config.services {
             let running = self.running_services.get(name);
+            let exit_code = self.exited.get(name).copied();
+            let state = match (running, exit_code) {
+                (Some(_), _) => ServiceState::Running,
+                (None, Some(_)) => ServiceState::Exited,
+                (None, None) => ServiceState::Stopped,
+            };
             
             statuses.push(ServiceStatus {
                 name: name.clone(),
@@ -287,6 +749,10 @@ impl Orchestrator {
                 pid: running.map(|s| s.pid),
                 port: config.port,
                 command: config.command.clone(),
+                state,
+                exit_code: exit_code.flatten(),
+                uptime_secs: running.map(|s| s.started_at.elapsed().as_secs()),
+                restarts: self.restarts.get(name).copied().unwrap_or(0),
             });
         }
         
@@ -347,6 +813,17 @@ impl Orchestrator {
     
 }
 
+/// Whether a configured service is up
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ServiceState {
+    Running,
+    /// Not started, or stopped by port-kill
+    Stopped,
+    /// The process ended on its own
+    Exited,
+}
+
 /// Status of a service
 #[derive(Debug, Clone, Serialize)]
 pub struct ServiceStatus {
@@ -355,6 +832,60 @@ pub struct ServiceStatus {
     pub pid: Option<u32>,
     pub port: Option<u16>,
     pub command: String,
+    pub state: ServiceState,
+    /// Exit code when the service exited on its own (None if killed by a signal)
+    pub exit_code: Option<i32>,
+    pub uptime_secs: Option<u64>,
+    pub restarts: u32,
+}
+
+/// Create a configuration file with one service per detected service, keeping the
+/// port each one is expected on and the workspace package it runs in
+pub fn create_detected_config(path: &Path, services: &[DiscoveredService]) -> Result<()> {
+    let mut config = String::from(
+        "# Port Kill Orchestration Configuration\n\
+         # Generated from the services detected in this directory; remove the ones you\n\
+         # don't need and add depends_on where a service needs another one first.\n",
+    );
+    for shared in &PortMap::from_services(services).shared {
+        config.push_str(&format!(
+            "# Port {} is expected by {}; change one of them before starting both.\n",
+            shared.port,
+            shared.projects.join(", ")
+        ));
+    }
+    config.push_str("\nversion: \"1\"\n\nservices:\n");
+    for service in services {
+        let command = serde_yaml::to_string(&service.command_line())
+            .context("Failed to write service command")?;
+        config.push_str(&format!(
+            "  # {}\n  {}:\n    command: {}\n",
+            service.description,
+            service.name.replace(':', "-"),
+            command.trim_end()
+        ));
+        if let Some(port) = service.inferred_port {
+            config.push_str(&format!("    port: {}\n", port));
+        }
+        // Workspace packages run in their own directory, relative to the config file
+        let dir = service
+            .working_directory
+            .strip_prefix(".")
+            .unwrap_or(&service.working_directory);
+        if !dir.as_os_str().is_empty() {
+            let dir = if dir.is_absolute() {
+                dir.display().to_string()
+            } else {
+                format!("./{}", dir.display())
+            };
+            let dir = serde_yaml::to_string(&dir).context("Failed to write service directory")?;
+            config.push_str(&format!("    dir: {}\n", dir.trim_end()));
+        }
+    }
+
+    fs::write(path, config).context("Failed to write generated configuration")?;
+
+    Ok(())
 }
 
 /// Create a sample configuration file
@@ -369,6 +900,12 @@ env:
   NODE_ENV: development
   DEBUG: "true"
 
+# Load each service's direnv (.envrc) / mise environment first (same as --load-env)
+# load_env: true
+
+# Run each service in its own window of a tmux session (same as --tmux)
+# tmux_session: myapp
+
 services:
   # Frontend service
   frontend:
@@ -387,14 +924,17 @@ services:
     depends_on:
       - database
     env:
-      PORT: "8000"
+      PORT: "{{port}}"
       DATABASE_URL: postgres://localhost:5432/myapp
   
   # Database service
   database:
     command: docker-compose up database
     port: 5432
-    startup_delay: 5
+    wait_for_port: 30   # backend starts once 5432 accepts connections
+    healthcheck: pg_isready -h localhost -p {{port}}
+    healthcheck_interval: 2
+    healthcheck_retries: 15
 "#;
     
     fs::write(path, sample)
@@ -406,7 +946,10 @@ services:
 #[cfg(test)]
 mod tests {
     use crate::command_line::parse_command_line;
-    use crate::orchestrator::{OrchestrationConfig, Orchestrator, ServiceConfig};
+    use crate::orchestrator::{
+        expand_service_placeholders, OrchestrationConfig, Orchestrator, ServiceConfig,
+        ServiceState,
+    };
 
     #[test]
     fn test_parse_command() {
@@ -422,6 +965,88 @@ mod tests {
         assert_eq!(parts, vec!["node", "my script.js", "--arg", "value with spaces"]);
     }
 
+    #[test]
+    fn test_service_placeholders() {
+        let service = ServiceConfig {
+            command: "npm run dev -- --port {{port}}".to_string(),
+            port: Some(3000),
+            dir: None,
+            depends_on: None,
+            env: None,
+            startup_delay: None,
+            healthcheck: None,
+            healthcheck_interval: None,
+            healthcheck_timeout: None,
+            healthcheck_retries: None,
+            wait_for_port: None,
+        };
+        assert_eq!(
+            expand_service_placeholders(&service.command, &service).unwrap(),
+            "npm run dev -- --port 3000"
+        );
+
+        let portless = ServiceConfig { port: None, ..service };
+        assert!(expand_service_placeholders("--port {{port}}", &portless).is_err());
+    }
+
+    #[test]
+    fn test_create_detected_config() {
+        use crate::orchestrator::create_detected_config;
+        use crate::service_detector::{DiscoveredService, ServiceType};
+        use std::path::PathBuf;
+
+        let services = vec![
+            DiscoveredService {
+                name: "npm:dev".to_string(),
+                service_type: ServiceType::NpmScript {
+                    script_name: "dev".to_string(),
+                    package_json_path: PathBuf::from("package.json"),
+                },
+                working_directory: PathBuf::from("."),
+                inferred_port: Some(5173),
+                description: "npm run dev (Node.js)".to_string(),
+            },
+            DiscoveredService {
+                name: "go:main".to_string(),
+                service_type: ServiceType::Custom {
+                    command: vec!["go".to_string(), "run".to_string(), ".".to_string()],
+                    working_dir: PathBuf::from("."),
+                },
+                working_directory: PathBuf::from("."),
+                inferred_port: None,
+                description: "go run . (Go)".to_string(),
+            },
+            DiscoveredService {
+                name: "web:dev".to_string(),
+                service_type: ServiceType::NpmScript {
+                    script_name: "dev".to_string(),
+                    package_json_path: PathBuf::from("./packages/web/package.json"),
+                },
+                working_directory: PathBuf::from("./packages/web"),
+                inferred_port: Some(3000),
+                description: "npm run dev in packages/web (workspace)".to_string(),
+            },
+        ];
+        let path = std::env::temp_dir().join(format!(
+            "port-kill-detected-config-{}.yaml",
+            std::process::id()
+        ));
+        create_detected_config(&path, &services).unwrap();
+
+        let config = Orchestrator::load(&path).unwrap().config().clone();
+        assert_eq!(config.services["npm-dev"].command, "npm run dev");
+        assert_eq!(config.services["npm-dev"].port, Some(5173));
+        assert_eq!(config.services["go-main"].command, "go run .");
+        assert_eq!(config.services["go-main"].port, None);
+        assert_eq!(config.services["npm-dev"].dir, None);
+        assert_eq!(
+            config.services["web-dev"].dir.as_deref(),
+            Some("./packages/web")
+        );
+
+        let _ = std::fs::remove_file(&path);
+    }
+
     #[test]
     fn test_resolve_dependencies_detects_cycle() {
         let mut services = std::collections::HashMap::new();
@@ -435,6 +1060,10 @@ mod tests {
                 env: None,
                 startup_delay: None,
                 healthcheck: None,
+                healthcheck_interval: None,
+                healthcheck_timeout: None,
+                healthcheck_retries: None,
+                wait_for_port: None,
             },
         );
         services.insert(
@@ -447,12 +1076,18 @@ mod tests {
                 env: None,
                 startup_delay: None,
                 healthcheck: None,
+                healthcheck_interval: None,
+                healthcheck_timeout: None,
+                healthcheck_retries: None,
+                wait_for_port: None,
             },
         );
 
         let config = OrchestrationConfig {
             version: Some("1".to_string()),
             env: None,
+            load_env: None,
+            tmux_session: None,
             services,
         };
 
@@ -460,6 +1095,11 @@ mod tests {
             config,
             running_services: std::collections::HashMap::new(),
             config_path: std::path::PathBuf::from(".port-kill.yaml"),
+            restarts: std::collections::HashMap::new(),
+            exited: std::collections::HashMap::new(),
+            log_dir: None,
+            load_env: false,
+            tmux_session: None,
         };
 
         let result = orchestrator.resolve_dependencies();
@@ -501,5 +1141,106 @@ services:
             result.err()
         );
     }
+
+    #[tokio::test]
+    async fn test_status_tracks_exits_and_restarts() {
+        let root_temp = std::env::temp_dir().join("port-kill-test-status");
+        let _ = std::fs::remove_dir_all(&root_temp);
+        std::fs::create_dir_all(&root_temp).unwrap();
+        let config_path = root_temp.join("port-kill.yaml");
+        std::fs::write(
+            &config_path,
+            "services:\n  once:\n    command: sh -c 'echo ready; exit 3'\n  idle:\n    command: sleep 30\n",
+        )
+        .unwrap();
+
+        let mut orchestrator = Orchestrator::load(&config_path).unwrap();
+        orchestrator.capture_output(root_temp.join("logs"));
+        orchestrator.start_all().await.unwrap();
+        orchestrator.restart_service("idle").await.unwrap();
+
+        let mut exited = Vec::new();
+        for _ in 0..50 {
+            exited = orchestrator.reap_exited();
+            if !exited.is_empty() {
+                break;
+            }
+            tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
+        }
+        assert_eq!(exited, vec!["once".to_string()]);
+
+        let statuses = orchestrator.get_status();
+        let idle = &statuses[0];
+        assert_eq!((idle.state, idle.restarts), (ServiceState::Running, 1));
+        assert!(idle.uptime_secs.is_some());
+        let once = &statuses[1];
+        assert_eq!((once.state, once.exit_code), (ServiceState::Exited, Some(3)));
+        assert_eq!(
+            std::fs::read_to_string(root_temp.join("logs/once.log")).unwrap(),
+            "ready\n"
+        );
+
+        orchestrator.stop_all().await.unwrap();
+        assert_eq!(orchestrator.get_status()[0].state, ServiceState::Stopped);
+        let _ = std::fs::remove_dir_all(&root_temp);
+    }
+
+    #[tokio::test]
+    async fn test_dependents_wait_for_health_check() {
+        let root_temp = std::env::temp_dir().join("port-kill-test-health");
+        let _ = std::fs::remove_dir_all(&root_temp);
+        std::fs::create_dir_all(&root_temp).unwrap();
+        let config_path = root_temp.join("port-kill.yaml");
+        std::fs::write(
+            &config_path,
+            r#"services:
+  db:
+    command: sh -c 'sleep 1 && touch ready && sleep 30'
+    healthcheck: test -f ready || exit 1
+    healthcheck_interval: 1
+  app:
+    command: sh -c 'test -f ready && sleep 30'
+    depends_on: [db]
+  broken:
+    command: sleep 30
+    healthcheck: http://127.0.0.1:1/health
+    healthcheck_interval: 0
+    healthcheck_retries: 1
+"#,
+        )
+        .unwrap();
+
+        let mut orchestrator = Orchestrator::load(&config_path).unwrap();
+        orchestrator.start_service("app").await.unwrap();
+        tokio::time::sleep(tokio::time::Duration::from_millis(300)).await;
+        assert!(orchestrator.reap_exited().is_empty());
+
+        let err = orchestrator.start_service("broken").await.unwrap_err();
+        assert!(err.to_string().contains("failed its health check 2 times"));
+        let broken = orchestrator.get_status().into_iter().find(|s| s.name == "broken").unwrap();
+        assert!(!broken.running);
+
+        orchestrator.stop_all().await.unwrap();
+        let _ = std::fs::remove_dir_all(&root_temp);
+    }
+
+    #[test]
+    fn test_config_format_version() {
+        let config: OrchestrationConfig =
+            serde_yaml::from_str("version: 1\nservices: {}\n").unwrap();
+        assert_eq!(config.version.as_deref(), Some("1"));
+        assert_eq!(config.format_version().unwrap(), 1);
+
+        let unversioned: OrchestrationConfig = serde_yaml::from_str("services: {}\n").unwrap();
+        assert_eq!(unversioned.format_version().unwrap(), 1);
+
+        let root_temp = std::env::temp_dir().join("port-kill-test-version");
+        std::fs::create_dir_all(&root_temp).unwrap();
+        let config_path = root_temp.join("port-kill.yaml");
+        std::fs::write(&config_path, "version: \"2\"\nservices: {}\n").unwrap();
+        let err = Orchestrator::load(&config_path).err().unwrap();
+        assert!(err.to_string().contains("upgrade port-kill"));
+        let _ = std::fs::remove_dir_all(&root_temp);
+    }
 }
 
@@ -0,0 +1,406 @@
+use crate::types::ProcessInfo;
+use anyhow::{Context, Result};
+use chrono::{DateTime, Duration, Local, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::path::PathBuf;
+
+/// Spans a port was occupied, from when it was taken to when it was freed
+pub type OccupiedSpans = Vec<(DateTime<Utc>, DateTime<Utc>)>;
+
+/// Hours shown by `--timeline` without a value
+pub const DEFAULT_TIMELINE_HOURS: &str = "24";
+/// Oldest events are dropped beyond this, which is weeks of a busy dev machine
+const MAX_EVENTS: usize = 5000;
+/// Columns of a timeline row
+pub const TIMELINE_SLOTS: usize = 48;
+/// Occupations needed before guessing how often a port gets taken
+const MIN_OCCUPATIONS_FOR_INTERVAL: usize = 3;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ActivityKind {
+    Occupied,
+    Freed,
+}
+
+/// A monitored port changing hands, as seen by a running monitor
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PortActivityEvent {
+    pub at: DateTime<Utc>,
+    pub port: u16,
+    pub kind: ActivityKind,
+    /// The process that took the port, or that held it until it was freed
+    pub pid: i32,
+    pub process_name: String,
+}
+
+/// When each monitored port became occupied or free, stored next to the kill history
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PortActivityLog {
+    events: Vec<PortActivityEvent>,
+}
+
+impl PortActivityLog {
+    pub fn file_path() -> PathBuf {
+        let home_dir = std::env::var("HOME")
+            .or_else(|_| std::env::var("USERPROFILE"))
+            .unwrap_or_else(|_| ".".to_string());
+        PathBuf::from(home_dir).join(".port-kill-activity.json")
+    }
+
+    /// The saved log, or an empty one when there is none yet or it cannot be read
+    pub fn load() -> Self {
+        std::fs::read_to_string(Self::file_path())
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self) -> Result<()> {
+        let path = Self::file_path();
+        let content = serde_json::to_string(self)?;
+        std::fs::write(&path, content)
+            .with_context(|| format!("Failed to write {}", path.display()))
+    }
+
+    pub fn events(&self) -> &[PortActivityEvent] {
+        &self.events
+    }
+
+    /// The event that put the current holder on each port still occupied
+    fn holders(&self) -> HashMap<u16, &PortActivityEvent> {
+        let mut holders = HashMap::new();
+        for event in &self.events {
+            match event.kind {
+                ActivityKind::Occupied => holders.insert(event.port, event),
+                ActivityKind::Freed => holders.remove(&event.port),
+            };
+        }
+        holders
+    }
+
+    /// The process that took `port` most recently, whether it still holds it or not
+    pub fn last_holder(&self, port: u16) -> Option<&str> {
+        self.events
+            .iter()
+            .rev()
+            .find(|event| event.port == port && event.kind == ActivityKind::Occupied)
+            .map(|event| event.process_name.as_str())
+    }
+
+    /// Log the ports among `ports` that changed hands since the last recorded state.
+    /// Returns whether anything was added.
+    pub fn record_scan(
+        &mut self,
+        ports: &[u16],
+        processes: &HashMap<u16, ProcessInfo>,
+        at: DateTime<Utc>,
+    ) -> bool {
+        let monitored: HashSet<u16> = ports.iter().copied().collect();
+        let holders: HashMap<u16, (i32, String)> = self
+            .holders()
+            .into_iter()
+            .map(|(port, event)| (port, (event.pid, event.process_name.clone())))
+            .collect();
+        let mut changed_ports: Vec<u16> = holders
+            .keys()
+            .chain(processes.keys())
+            .copied()
+            .filter(|port| monitored.contains(port))
+            .collect::<HashSet<_>>()
+            .into_iter()
+            .collect();
+        changed_ports.sort_unstable();
+
+        let before = self.events.len();
+        for port in changed_ports {
+            let holder = holders.get(&port);
+            let current = processes.get(&port);
+            if let (Some((pid, _)), Some(process)) = (holder, current) {
+                if *pid == process.pid {
+                    continue;
+                }
+            }
+            if let Some((pid, process_name)) = holder {
+                self.events.push(PortActivityEvent {
+                    at,
+                    port,
+                    kind: ActivityKind::Freed,
+                    pid: *pid,
+                    process_name: process_name.clone(),
+                });
+            }
+            if let Some(process) = current {
+                self.events.push(PortActivityEvent {
+                    at,
+                    port,
+                    kind: ActivityKind::Occupied,
+                    pid: process.pid,
+                    process_name: process.name.clone(),
+                });
+            }
+        }
+        if self.events.len() > MAX_EVENTS {
+            let excess = self.events.len() - MAX_EVENTS;
+            self.events.drain(..excess);
+        }
+        self.events.len() != before
+    }
+
+    /// For each port (or only `port`), the spans it was occupied before `until`,
+    /// the last one open until `until` while the port is still held
+    pub fn occupied_intervals(
+        &self,
+        port: Option<u16>,
+        until: DateTime<Utc>,
+    ) -> HashMap<u16, OccupiedSpans> {
+        let mut occupied_since: HashMap<u16, DateTime<Utc>> = HashMap::new();
+        let mut intervals: HashMap<u16, OccupiedSpans> = HashMap::new();
+        for event in &self.events {
+            if port.is_some_and(|port| port != event.port) || event.at >= until {
+                continue;
+            }
+            let spans = intervals.entry(event.port).or_default();
+            if let Some(start) = occupied_since.remove(&event.port) {
+                spans.push((start, event.at));
+            }
+            if event.kind == ActivityKind::Occupied {
+                occupied_since.insert(event.port, event.at);
+            }
+        }
+        for (port, start) in occupied_since {
+            intervals.entry(port).or_default().push((start, until));
+        }
+        intervals
+    }
+
+    /// One row per port that was occupied at some point between `since` and `until`,
+    /// optionally only for `port`
+    pub fn timeline(
+        &self,
+        port: Option<u16>,
+        since: DateTime<Utc>,
+        until: DateTime<Utc>,
+    ) -> Vec<PortTimeline> {
+        let slot = (until - since) / TIMELINE_SLOTS as i32;
+
+        let mut timelines: Vec<PortTimeline> = self
+            .occupied_intervals(port, until)
+            .into_iter()
+            .filter_map(|(port, mut intervals)| {
+                intervals.retain(|(_, end)| *end > since);
+                if intervals.is_empty() {
+                    return None;
+                }
+                let taken: Vec<&PortActivityEvent> = self
+                    .events
+                    .iter()
+                    .filter(|event| {
+                        event.port == port
+                            && event.kind == ActivityKind::Occupied
+                            && event.at < until
+                    })
+                    .collect();
+                let taken_at: Vec<DateTime<Utc>> = taken
+                    .iter()
+                    .map(|event| event.at)
+                    .filter(|at| *at >= since)
+                    .collect();
+
+                let slots = (0..TIMELINE_SLOTS)
+                    .map(|i| {
+                        let slot_start = since + slot * i as i32;
+                        let slot_end = slot_start + slot;
+                        intervals
+                            .iter()
+                            .any(|(start, end)| *start < slot_end && *end > slot_start)
+                    })
+                    .collect();
+                Some(PortTimeline {
+                    port,
+                    slots,
+                    occupations: taken_at.len(),
+                    typical_interval_minutes: typical_interval(&taken_at),
+                    last_holder: taken.last().map(|event| event.process_name.clone()),
+                })
+            })
+            .collect();
+        timelines.sort_by_key(|timeline| timeline.port);
+        timelines
+    }
+}
+
+/// Median gap between the times a port was taken, once there are enough of them
+fn typical_interval(taken_at: &[DateTime<Utc>]) -> Option<i64> {
+    if taken_at.len() < MIN_OCCUPATIONS_FOR_INTERVAL {
+        return None;
+    }
+    let mut gaps: Vec<i64> = taken_at
+        .windows(2)
+        .map(|pair| (pair[1] - pair[0]).num_minutes())
+        .collect();
+    gaps.sort_unstable();
+    Some(gaps[gaps.len() / 2])
+}
+
+/// Occupancy of a port over the timeline window, one flag per column
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct PortTimeline {
+    pub port: u16,
+    pub slots: Vec<bool>,
+    /// Times the port was taken inside the window
+    pub occupations: usize,
+    pub typical_interval_minutes: Option<i64>,
+    pub last_holder: Option<String>,
+}
+
+impl PortTimeline {
+    /// e.g. `:5000  ··█··█··█  taken 3× (node), about every 60 min`
+    pub fn render(&self) -> String {
+        let bar: String = self
+            .slots
+            .iter()
+            .map(|occupied| if *occupied { '█' } else { '·' })
+            .collect();
+        let mut summary = match self.occupations {
+            0 => "held throughout".to_string(),
+            n => format!("taken {}×", n),
+        };
+        if let Some(holder) = &self.last_holder {
+            summary.push_str(&format!(" ({})", holder));
+        }
+        if let Some(minutes) = self.typical_interval_minutes {
+            summary.push_str(&format!(", about every {}", format_interval(minutes)));
+        }
+        format!("{:>7}  {}  {}", format!(":{}", self.port), bar, summary)
+    }
+}
+
+fn format_interval(minutes: i64) -> String {
+    if minutes < 120 {
+        format!("{} min", minutes.max(1))
+    } else {
+        format!("{} h", (minutes + 30) / 60)
+    }
+}
+
+/// Time labels under the rows: the start of the window on the left, its end on the right
+pub fn timeline_axis(since: DateTime<Utc>, until: DateTime<Utc>) -> String {
+    let format = if until - since >= Duration::hours(24) {
+        "%a %H:%M"
+    } else {
+        "%H:%M"
+    };
+    let start = since.with_timezone(&Local).format(format).to_string();
+    let end = until.with_timezone(&Local).format(format).to_string();
+    let gap = TIMELINE_SLOTS.saturating_sub(start.len() + end.len());
+    format!("{:>7}  {}{}{}", "", start, " ".repeat(gap), end)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn process(pid: i32, port: u16, name: &str) -> ProcessInfo {
+        ProcessInfo {
+            pid,
+            port,
+            command: name.to_string(),
+            name: name.to_string(),
+            container_id: None,
+            container_name: None,
+            command_line: None,
+            working_directory: None,
+            process_group: None,
+            project_name: None,
+            cpu_usage: None,
+            memory_usage: None,
+            memory_percentage: None,
+            protocol: crate::types::Protocol::Tcp,
+            bind_address: None,
+            address_family: None,
+        }
+    }
+
+    #[test]
+    fn test_record_scan() {
+        let start: DateTime<Utc> = "2026-03-02T10:00:00Z".parse().unwrap();
+        let ports = [3000, 5000];
+        let mut log = PortActivityLog::default();
+
+        let scan = HashMap::from([
+            (3000, process(10, 3000, "node")),
+            (8080, process(11, 8080, "java")),
+        ]);
+        assert!(log.record_scan(&ports, &scan, start));
+        // Same holder again, nothing new; 8080 is not monitored
+        assert!(!log.record_scan(&ports, &scan, start + Duration::minutes(1)));
+        assert_eq!(log.events().len(), 1);
+
+        // Another process on 3000 frees it and takes it in the same scan
+        let scan = HashMap::from([(3000, process(12, 3000, "vite"))]);
+        assert!(log.record_scan(&ports, &scan, start + Duration::minutes(2)));
+        assert!(log.record_scan(&ports, &HashMap::new(), start + Duration::minutes(3)));
+        let kinds: Vec<(ActivityKind, i32)> =
+            log.events().iter().map(|e| (e.kind, e.pid)).collect();
+        assert_eq!(log.last_holder(3000), Some("vite"));
+        assert_eq!(log.last_holder(5000), None);
+        assert_eq!(
+            kinds,
+            vec![
+                (ActivityKind::Occupied, 10),
+                (ActivityKind::Freed, 10),
+                (ActivityKind::Occupied, 12),
+                (ActivityKind::Freed, 12),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_timeline() {
+        let since: DateTime<Utc> = "2026-03-02T00:00:00Z".parse().unwrap();
+        let until = since + Duration::hours(24);
+        let ports = [3000, 5000];
+        let mut log = PortActivityLog::default();
+
+        // 3000 is held from before the window until noon
+        let dev = HashMap::from([(3000, process(10, 3000, "node"))]);
+        log.record_scan(&ports, &dev, since - Duration::hours(2));
+        log.record_scan(&ports, &HashMap::new(), since + Duration::hours(12));
+        // Something grabs 5000 for a few minutes every hour from 18:00
+        for hour in 18..24 {
+            let at = since + Duration::hours(hour);
+            let cron = HashMap::from([(5000, process(100 + hour as i32, 5000, "cron-job"))]);
+            log.record_scan(&ports, &cron, at);
+            log.record_scan(&ports, &HashMap::new(), at + Duration::minutes(5));
+        }
+
+        let timelines = log.timeline(None, since, until);
+        assert_eq!(timelines.len(), 2);
+        let dev = &timelines[0];
+        assert_eq!(dev.port, 3000);
+        assert_eq!(dev.occupations, 0);
+        assert!(dev.render().ends_with("held throughout (node)"));
+        assert!(dev.slots[..24].iter().all(|occupied| *occupied));
+        assert!(dev.slots[24..].iter().all(|occupied| !*occupied));
+
+        let cron = &timelines[1];
+        assert_eq!(cron.occupations, 6);
+        assert_eq!(cron.typical_interval_minutes, Some(60));
+        assert_eq!(cron.slots.iter().filter(|occupied| **occupied).count(), 6);
+        assert!(cron.render().starts_with("  :5000  "));
+        assert!(cron
+            .render()
+            .ends_with("taken 6× (cron-job), about every 60 min"));
+
+        assert_eq!(log.timeline(Some(5000), since, until).len(), 1);
+        assert!(log
+            .timeline(
+                None,
+                since + Duration::hours(48),
+                until + Duration::hours(48)
+            )
+            .is_empty());
+    }
+}
@@ -1,6 +1,6 @@
 use anyhow::Result;
 use chrono::{Duration, Utc};
-use log::{info, warn};
+use tracing::{info, warn};
 use std::collections::HashMap;
 use std::fs;
 use std::path::Path;
@@ -8,18 +8,24 @@ use std::sync::Arc;
 use tokio::sync::Mutex;
 use tokio::time::{sleep, Duration as TokioDuration};
 
-use crate::process_monitor::ProcessMonitor;
+use crate::format_version::{
+    backup_old_format, parse_versioned, to_versioned_json, unchanged_payload, Versioned,
+    RESERVATIONS_VERSION,
+};
+use crate::process_monitor::{record_kill, ProcessMonitor};
+use crate::scan_interval::AdaptiveInterval;
 use crate::types::{
-    GuardStatus, PortConflict, PortConflictType, PortReservation, PortResolution, ProcessInfo,
+    GuardStatus, PortConflict, PortConflictType, PortReservation, PortResolution,
+    ProcessHistoryEntry, ProcessInfo,
 };
 use std::collections::HashSet;
 
 /// Port Guard daemon that proactively prevents port conflicts
 pub struct PortGuardDaemon {
-    watched_ports: Vec<u16>,
+    watched_ports: Arc<Mutex<Vec<u16>>>,
     reservations: Arc<Mutex<HashMap<u16, PortReservation>>>,
     reservation_file: String,
-    auto_resolve: bool,
+    auto_resolve: Arc<Mutex<bool>>,
     auto_restart: bool,
     conflicts_resolved: Arc<Mutex<usize>>,
     is_running: Arc<Mutex<bool>>,
@@ -27,6 +33,7 @@ pub struct PortGuardDaemon {
     intercepted_commands: Arc<Mutex<HashSet<String>>>,
     process_interception_enabled: bool,
     allowed_process_name: Option<String>,
+    scan_interval: AdaptiveInterval,
 }
 
 impl PortGuardDaemon {
@@ -38,10 +45,10 @@ impl PortGuardDaemon {
         process_monitor: Arc<Mutex<ProcessMonitor>>,
     ) -> Self {
         Self {
-            watched_ports,
+            watched_ports: Arc::new(Mutex::new(watched_ports)),
             reservations: Arc::new(Mutex::new(HashMap::new())),
             reservation_file,
-            auto_resolve,
+            auto_resolve: Arc::new(Mutex::new(auto_resolve)),
             auto_restart: false,
             conflicts_resolved: Arc::new(Mutex::new(0)),
             is_running: Arc::new(Mutex::new(false)),
@@ -49,6 +56,7 @@ impl PortGuardDaemon {
             intercepted_commands: Arc::new(Mutex::new(HashSet::new())),
             process_interception_enabled: true,
             allowed_process_name: None,
+            scan_interval: AdaptiveInterval::fixed(TokioDuration::from_secs(2)),
         }
     }
 
@@ -62,6 +70,11 @@ impl PortGuardDaemon {
         self.auto_restart = enabled;
     }
 
+    /// How often the guard checks its ports, e.g. backing off while nothing changes
+    pub fn set_scan_interval(&mut self, interval: AdaptiveInterval) {
+        self.scan_interval = interval;
+    }
+
     /// Start the Port Guard daemon
     pub async fn start(&self) -> Result<()> {
         // Load existing reservations
@@ -75,7 +88,7 @@ impl PortGuardDaemon {
 
         info!(
             "🛡️  Port Guard daemon started, watching ports: {:?}",
-            self.watched_ports
+            self.watched_ports.lock().await.clone()
         );
 
         // Start the main monitoring loop
@@ -84,6 +97,17 @@ impl PortGuardDaemon {
         Ok(())
     }
 
+    /// Apply guard settings from a reloaded config while the daemon is running
+    pub async fn update_settings(&self, watched_ports: Vec<u16>, auto_resolve: bool) {
+        *self.watched_ports.lock().await = watched_ports;
+        *self.auto_resolve.lock().await = auto_resolve;
+    }
+
+    /// Re-read the reservation file after it was changed by another port-kill or by hand
+    pub async fn reload_reservations(&self) -> Result<()> {
+        self.load_reservations().await
+    }
+
     /// Stop the Port Guard daemon
     pub async fn stop(&self) -> Result<()> {
         {
@@ -100,10 +124,16 @@ impl PortGuardDaemon {
 
     /// Main monitoring loop
     async fn monitor_loop(&self) -> Result<()> {
+        let mut scan_interval = self.scan_interval.clone();
+        let mut last_seen = None;
         while *self.is_running.lock().await {
-            // Check for port conflicts every 2 seconds
-            if let Err(e) = self.check_port_conflicts().await {
-                warn!("Error checking port conflicts: {}", e);
+            let mut changed = false;
+            match self.check_port_conflicts().await {
+                Ok(seen) => {
+                    changed = last_seen.as_ref() != Some(&seen);
+                    last_seen = Some(seen);
+                }
+                Err(e) => warn!("Error checking port conflicts: {}", e),
             }
 
             // Check for dead processes and restart them if auto-restart is enabled
@@ -118,14 +148,14 @@ impl PortGuardDaemon {
                 warn!("Error cleaning up expired reservations: {}", e);
             }
 
-            // Sleep for 2 seconds
-            sleep(TokioDuration::from_secs(2)).await;
+            sleep(scan_interval.record(changed)).await;
         }
         Ok(())
     }
 
-    /// Check for port conflicts and resolve them
-    async fn check_port_conflicts(&self) -> Result<()> {
+    /// Check for port conflicts and resolve them. Returns the port and PID of each
+    /// process on a watched port, so the loop can tell when something changed.
+    async fn check_port_conflicts(&self) -> Result<Vec<(u16, i32)>> {
         let effective_ports = self.effective_watched_ports().await;
 
         let monitor = self.process_monitor.lock().await;
@@ -142,6 +172,12 @@ impl PortGuardDaemon {
 
         self.enforce_allowed_processes(&port_processes).await?;
 
+        let mut seen: Vec<(u16, i32)> = port_processes
+            .iter()
+            .flat_map(|(port, processes)| processes.iter().map(move |p| (*port, p.pid)))
+            .collect();
+        seen.sort_unstable();
+
         // Check for conflicts
         for (port, processes_on_port) in port_processes {
             let allowed_name = self.allowed_name_for_port(port).await;
@@ -163,17 +199,22 @@ impl PortGuardDaemon {
                 };
 
                 info!(
+                    port,
+                    existing_pid = conflict.existing_process.pid,
+                    new_pid = conflict.new_process.pid,
                     "⚠️  Port conflict detected on port {}: {} vs {}",
-                    port, conflict.existing_process.name, conflict.new_process.name
+                    port,
+                    conflict.existing_process.name,
+                    conflict.new_process.name
                 );
 
                 if let Err(e) = self.resolve_conflict(conflict).await {
-                    warn!("Failed to resolve port conflict: {}", e);
+                    warn!(port, error = %e, "Failed to resolve port conflict: {}", e);
                 }
             }
         }
 
-        Ok(())
+        Ok(seen)
     }
 
     /// Check for dead processes and restart them
@@ -208,15 +249,18 @@ impl PortGuardDaemon {
         // Restart all dead processes
         for (port, project_name) in ports_to_restart {
             info!(
+                port,
+                project = %project_name,
                 "🔄 Detected dead process on port {} (reserved for {}), restarting...",
-                port, project_name
+                port,
+                project_name
             );
             
             let mut monitor_mut = self.process_monitor.lock().await;
             if let Err(e) = monitor_mut.restart_process_on_port(port).await {
-                warn!("Failed to auto-restart process on port {}: {}", port, e);
+                warn!(port, error = %e, "Failed to auto-restart process on port {}: {}", port, e);
             } else {
-                info!("✅ Successfully auto-restarted process on port {}", port);
+                info!(port, "✅ Successfully auto-restarted process on port {}", port);
             }
         }
         
@@ -225,9 +269,10 @@ impl PortGuardDaemon {
 
     /// Resolve a port conflict
     async fn resolve_conflict(&self, mut conflict: PortConflict) -> Result<()> {
-        if !self.auto_resolve {
+        if !*self.auto_resolve.lock().await {
             conflict.resolution = Some(PortResolution::NotifyUser);
             info!(
+                port = conflict.port,
                 "🔔 Port conflict on {} - manual resolution required",
                 conflict.port
             );
@@ -245,15 +290,21 @@ impl PortGuardDaemon {
         };
 
         info!(
+            port = conflict.port,
+            pid = older_process.pid,
+            process = %older_process.name,
             "🔧 Auto-resolving port conflict on {} by killing process {} (PID: {})",
-            conflict.port, older_process.name, older_process.pid
+            conflict.port,
+            older_process.name,
+            older_process.pid
         );
 
         // Kill the older process
         if let Err(e) = self.kill_process(older_process.pid).await {
-            warn!("Failed to kill process {}: {}", older_process.pid, e);
+            warn!(pid = older_process.pid, error = %e, "Failed to kill process {}: {}", older_process.pid, e);
             return Err(e);
         }
+        record_kill(&ProcessHistoryEntry::new(older_process, "guard".to_string()));
 
         // Update conflict resolution
         conflict.resolution = Some(PortResolution::KillExisting);
@@ -264,7 +315,7 @@ impl PortGuardDaemon {
             *count += 1;
         }
 
-        info!("✅ Port conflict resolved on port {}", conflict.port);
+        info!(port = conflict.port, "✅ Port conflict resolved on port {}", conflict.port);
         Ok(())
     }
 
@@ -291,8 +342,11 @@ impl PortGuardDaemon {
         }
 
         info!(
+            port,
+            project = %project_name_clone,
             "🔒 Port {} reserved for project '{}'",
-            port, project_name_clone
+            port,
+            project_name_clone
         );
         self.save_reservations().await?;
         Ok(())
@@ -305,7 +359,7 @@ impl PortGuardDaemon {
             reservations.remove(&port);
         }
 
-        info!("🔓 Port {} reservation released", port);
+        info!(port, "🔓 Port {} reservation released", port);
         self.save_reservations().await?;
         Ok(())
     }
@@ -318,11 +372,11 @@ impl PortGuardDaemon {
 
         GuardStatus {
             is_active: is_running,
-            watched_ports: self.watched_ports.clone(),
+            watched_ports: self.watched_ports.lock().await.clone(),
             active_reservations: reservations.values().cloned().collect(),
             conflicts_resolved,
             last_activity: Some(Utc::now()),
-            auto_resolve_enabled: self.auto_resolve,
+            auto_resolve_enabled: *self.auto_resolve.lock().await,
         }
     }
 
@@ -332,8 +386,11 @@ impl PortGuardDaemon {
             return Ok(());
         }
 
-        let content = fs::read_to_string(&self.reservation_file)?;
-        let reservations: HashMap<u16, PortReservation> = serde_json::from_str(&content)?;
+        let loaded = read_reservation_file(Path::new(&self.reservation_file))?;
+        if loaded.version < RESERVATIONS_VERSION {
+            backup_old_format(Path::new(&self.reservation_file), loaded.version)?;
+        }
+        let reservations = loaded.data;
 
         {
             let mut current_reservations = self.reservations.lock().await;
@@ -355,10 +412,7 @@ impl PortGuardDaemon {
         }
 
         let reservations = self.reservations.lock().await;
-        let content = serde_json::to_string_pretty(&*reservations)?;
-        fs::write(&self.reservation_file, content)?;
-
-        Ok(())
+        write_reservation_file(Path::new(&self.reservation_file), &reservations)
     }
 
     /// Clean up expired reservations
@@ -377,7 +431,7 @@ impl PortGuardDaemon {
 
         for port in to_remove {
             reservations.remove(&port);
-            info!("🧹 Cleaned up expired reservation for port {}", port);
+            info!(port, "🧹 Cleaned up expired reservation for port {}", port);
         }
 
         Ok(())
@@ -466,7 +520,7 @@ impl PortGuardDaemon {
                     if !is_port_available(port).await {
                         info!("⚠️  Port {} is busy, attempting to resolve conflict", port);
 
-                        if self.auto_resolve {
+                        if *self.auto_resolve.lock().await {
                             // Try to kill the conflicting process
                             if let Err(e) = self.resolve_port_conflict(port).await {
                                 warn!("Failed to resolve port conflict: {}", e);
@@ -585,11 +639,17 @@ impl PortGuardDaemon {
                 None => conflicting_processes[0],
             };
             info!(
+                port,
+                pid = process_to_kill.pid,
+                process = %process_to_kill.name,
                 "🔧 Killing conflicting process {} (PID: {}) on port {}",
-                process_to_kill.name, process_to_kill.pid, port
+                process_to_kill.name,
+                process_to_kill.pid,
+                port
             );
 
             self.kill_process(process_to_kill.pid).await?;
+            record_kill(&ProcessHistoryEntry::new(process_to_kill, "guard".to_string()));
 
             // Wait a moment for the process to die
             sleep(TokioDuration::from_millis(500)).await;
@@ -649,7 +709,7 @@ impl PortGuardDaemon {
 
             for process in processes {
                 if process.name != allowed_name {
-                    disallowed_processes.push((*port, process.pid, process.name.clone()));
+                    disallowed_processes.push(process);
                 }
             }
         }
@@ -658,22 +718,33 @@ impl PortGuardDaemon {
             return Ok(());
         }
 
-        for (port, pid, name) in disallowed_processes {
-            if !self.auto_resolve {
+        for process in disallowed_processes {
+            let (port, pid, name) = (process.port, process.pid, &process.name);
+            if !*self.auto_resolve.lock().await {
                 info!(
+                    port,
+                    pid,
+                    process = %name,
                     "🔔 Unauthorized process '{}' on port {} - manual resolution required",
-                    name, port
+                    name,
+                    port
                 );
                 continue;
             }
 
             info!(
+                port,
+                pid,
+                process = %name,
                 "🚨 Unauthorized process '{}' (PID: {}) on port {} - KILLING",
-                name, pid, port
+                name,
+                pid,
+                port
             );
 
-            if let Err(e) = self.kill_process(pid).await {
-                warn!("Failed to kill unauthorized process {}: {}", pid, e);
+            match self.kill_process(pid).await {
+                Ok(()) => record_kill(&ProcessHistoryEntry::new(process, "guard".to_string())),
+                Err(e) => warn!(pid, error = %e, "Failed to kill unauthorized process {}: {}", pid, e),
             }
         }
 
@@ -682,7 +753,7 @@ impl PortGuardDaemon {
 
     /// Returns the effective set of watched ports: the initial list merged with all reserved ports.
     async fn effective_watched_ports(&self) -> HashSet<u16> {
-        let mut ports: HashSet<u16> = self.watched_ports.iter().copied().collect();
+        let mut ports: HashSet<u16> = self.watched_ports.lock().await.iter().copied().collect();
         let reservations = self.reservations.lock().await;
         ports.extend(reservations.keys());
         ports
@@ -711,16 +782,78 @@ impl PortGuardDaemon {
     }
 }
 
-/// Check if a port is available for binding
+/// Read a reservation file as written by the guard, in any supported format version
+pub fn read_reservation_file(path: &Path) -> Result<Versioned<HashMap<u16, PortReservation>>> {
+    let content = fs::read_to_string(path)?;
+    parse_versioned(
+        &content,
+        "reservations",
+        RESERVATIONS_VERSION,
+        unchanged_payload,
+    )
+}
+
+fn write_reservation_file(path: &Path, reservations: &HashMap<u16, PortReservation>) -> Result<()> {
+    let content = to_versioned_json(reservations, "reservations", RESERVATIONS_VERSION)?;
+    fs::write(path, content)?;
+    Ok(())
+}
+
+/// Reserve `port` for `process_name` in the reservation file at `path`, without a running
+/// guard. The reservation does not expire; a running guard picks it up from the file.
+pub fn reserve_port_in_file(
+    path: &Path,
+    port: u16,
+    project_name: &str,
+    process_name: &str,
+) -> Result<()> {
+    let mut reservations = if path.exists() {
+        read_reservation_file(path)?.data
+    } else {
+        HashMap::new()
+    };
+    reservations.insert(
+        port,
+        PortReservation {
+            port,
+            project_name: project_name.to_string(),
+            process_name: process_name.to_string(),
+            reserved_at: Utc::now(),
+            expires_at: None,
+            auto_renew: false,
+        },
+    );
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    write_reservation_file(path, &reservations)
+}
+
+/// Drop the reservation of `port` from the file at `path`. Returns false when there was none.
+pub fn release_port_in_file(path: &Path, port: u16) -> Result<bool> {
+    if !path.exists() {
+        return Ok(false);
+    }
+    let mut reservations = read_reservation_file(path)?.data;
+    if reservations.remove(&port).is_none() {
+        return Ok(false);
+    }
+    write_reservation_file(path, &reservations)?;
+    Ok(true)
+}
+
+/// Check if a port is available for binding, on IPv4 and (where there is one) IPv6 loopback
 pub async fn is_port_available(port: u16) -> bool {
-    use std::net::Ipv4Addr;
+    use std::net::{Ipv4Addr, Ipv6Addr};
     use std::net::{SocketAddr, TcpListener};
 
     let addr = SocketAddr::from((Ipv4Addr::LOCALHOST, port));
-    match TcpListener::bind(addr) {
-        Ok(_) => true,
-        Err(_) => false,
+    if TcpListener::bind(addr).is_err() {
+        return false;
     }
+    // A server bound to ::1 alone doesn't block 127.0.0.1; hosts without IPv6 fail otherwise
+    let addr = SocketAddr::from((Ipv6Addr::LOCALHOST, port));
+    !matches!(TcpListener::bind(addr), Err(e) if e.kind() == std::io::ErrorKind::AddrInUse)
 }
 
 /// Find an available port starting from a given port
@@ -0,0 +1,179 @@
+//! Waiting for ports to open or to free up: `port-kill wait`, and services with
+//! `wait_for_port` in .port-kill.yaml.
+
+use crate::cli::{WaitArgs, WaitState};
+use anyhow::Result;
+use std::time::{Duration, Instant};
+use tokio::net::TcpStream;
+
+/// How long one connection attempt may take before the port counts as closed
+const CONNECT_TIMEOUT: Duration = Duration::from_millis(500);
+
+/// Parse a duration such as `500ms`, `60s`, `2m` or `1h`. A bare number means seconds.
+pub fn parse_duration(input: &str) -> Result<Duration, String> {
+    let trimmed = input.trim();
+    let split = trimmed
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(trimmed.len());
+    let (number, unit) = trimmed.split_at(split);
+    let value: u64 = number
+        .parse()
+        .map_err(|_| format!("Invalid duration: '{}'", input))?;
+    match unit.trim().to_ascii_lowercase().as_str() {
+        "ms" => Ok(Duration::from_millis(value)),
+        "" | "s" => Ok(Duration::from_secs(value)),
+        "m" | "min" => Ok(Duration::from_secs(value * 60)),
+        "h" => Ok(Duration::from_secs(value * 60 * 60)),
+        _ => Err(format!(
+            "Invalid duration unit in '{}' (use ms, s, m or h)",
+            input
+        )),
+    }
+}
+
+/// Whether nothing is listening on local `port`. Binding is the same check a server
+/// starting on it will make; the connects cover listeners bound to a more specific
+/// address, including IPv6-only ones on `::1`.
+pub fn is_port_free(port: u16) -> bool {
+    match std::net::TcpListener::bind(("0.0.0.0", port)) {
+        Err(e) if e.kind() == std::io::ErrorKind::AddrInUse => return false,
+        // e.g. a privileged port: fall back to the connect check
+        _ => {}
+    }
+    let loopback: [std::net::IpAddr; 2] = [
+        std::net::Ipv4Addr::LOCALHOST.into(),
+        std::net::Ipv6Addr::LOCALHOST.into(),
+    ];
+    loopback.into_iter().all(|ip| {
+        let addr = std::net::SocketAddr::new(ip, port);
+        std::net::TcpStream::connect_timeout(&addr, Duration::from_millis(200)).is_err()
+    })
+}
+
+/// Whether `host:port` accepts connections, on any of the addresses `host` resolves to
+pub async fn is_port_open(host: &str, port: u16) -> bool {
+    matches!(
+        tokio::time::timeout(CONNECT_TIMEOUT, TcpStream::connect((host, port))).await,
+        Ok(Ok(_))
+    )
+}
+
+async fn in_state(host: &str, port: u16, state: WaitState) -> bool {
+    match state {
+        WaitState::Open => is_port_open(host, port).await,
+        WaitState::Free if is_local(host) => is_port_free(port),
+        WaitState::Free => !is_port_open(host, port).await,
+    }
+}
+
+fn is_local(host: &str) -> bool {
+    matches!(host, "localhost" | "127.0.0.1" | "::1" | "0.0.0.0")
+}
+
+/// Poll every `interval` until all `ports` on `host` are in `state`. Returns the ports
+/// that still aren't once `timeout` runs out; a zero timeout waits forever.
+pub async fn wait_for_ports(
+    host: &str,
+    ports: &[u16],
+    state: WaitState,
+    timeout: Duration,
+    interval: Duration,
+) -> Vec<u16> {
+    let deadline = (!timeout.is_zero()).then(|| Instant::now() + timeout);
+    let mut pending = ports.to_vec();
+    loop {
+        let mut waiting = Vec::new();
+        for &port in &pending {
+            if !in_state(host, port, state).await {
+                waiting.push(port);
+            }
+        }
+        pending = waiting;
+        if pending.is_empty() || deadline.is_some_and(|deadline| Instant::now() >= deadline) {
+            return pending;
+        }
+        tokio::time::sleep(interval).await;
+    }
+}
+
+fn state_name(state: WaitState) -> &'static str {
+    match state {
+        WaitState::Open => "open",
+        WaitState::Free => "free",
+    }
+}
+
+fn join_ports(ports: &[u16]) -> String {
+    ports
+        .iter()
+        .map(|port| port.to_string())
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// `port-kill wait`: returns the exit status, 1 when the timeout ran out
+pub async fn run_wait(args: &WaitArgs) -> Result<i32> {
+    let started = Instant::now();
+    if !args.quiet {
+        println!(
+            "⏳ Waiting for {} port {} to be {}...",
+            args.host,
+            join_ports(&args.port),
+            state_name(args.state)
+        );
+    }
+
+    let pending = wait_for_ports(&args.host, &args.port, args.state, args.timeout, args.interval).await;
+    if pending.is_empty() {
+        if !args.quiet {
+            println!(
+                "✅ Port {} {} after {:.1}s",
+                join_ports(&args.port),
+                state_name(args.state),
+                started.elapsed().as_secs_f64()
+            );
+        }
+        return Ok(0);
+    }
+    if !args.quiet {
+        eprintln!(
+            "❌ Timed out after {:.1}s: port {} is not {}",
+            started.elapsed().as_secs_f64(),
+            join_ports(&pending),
+            state_name(args.state)
+        );
+    }
+    Ok(1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_duration() {
+        assert_eq!(parse_duration("500ms"), Ok(Duration::from_millis(500)));
+        assert_eq!(parse_duration("60s"), Ok(Duration::from_secs(60)));
+        assert_eq!(parse_duration("45"), Ok(Duration::from_secs(45)));
+        assert_eq!(parse_duration("2m"), Ok(Duration::from_secs(120)));
+        assert_eq!(parse_duration("1h"), Ok(Duration::from_secs(3600)));
+        assert!(parse_duration("soon").is_err());
+        assert!(parse_duration("5d").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_wait_for_open_and_free() {
+        let listener = std::net::TcpListener::bind(("127.0.0.1", 0)).unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let (ports, interval) = ([port], Duration::from_millis(50));
+
+        let open = wait_for_ports("127.0.0.1", &ports, WaitState::Open, Duration::from_secs(2), interval).await;
+        assert!(open.is_empty());
+        let free = wait_for_ports("127.0.0.1", &ports, WaitState::Free, Duration::from_millis(200), interval).await;
+        assert_eq!(free, vec![port]);
+
+        drop(listener);
+        let free = wait_for_ports("127.0.0.1", &ports, WaitState::Free, Duration::from_secs(2), interval).await;
+        assert!(free.is_empty());
+    }
+}
@@ -0,0 +1,355 @@
+use crate::cli::{Args, RunArgs};
+use crate::command_line::quote_command_line;
+use crate::port_guard::{is_port_available, read_reservation_file};
+use crate::process_monitor::{get_processes_on_ports, kill_all_processes};
+use crate::service_detector::{
+    command_port, compose_published_ports, ProjectPorts, ServiceDetector,
+};
+use crate::smart_filter::SmartFilter;
+use crate::types::ProcessInfo;
+use anyhow::{anyhow, Result};
+use serde::Serialize;
+use std::path::Path;
+use std::time::Duration;
+
+/// How long to wait for cleared ports to become free before giving up
+const RELEASE_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Programs whose port comes from the JS dev server they run (vite, next, webpack)
+/// or from `PORT` in .env
+const JS_RUNNERS: [&str; 12] = [
+    "npm",
+    "npx",
+    "yarn",
+    "pnpm",
+    "pnpx",
+    "bun",
+    "bunx",
+    "node",
+    "vite",
+    "next",
+    "webpack",
+    "webpack-dev-server",
+];
+
+const COMPOSE_FILES: [&str; 4] = [
+    "docker-compose.yml",
+    "docker-compose.yaml",
+    "compose.yml",
+    "compose.yaml",
+];
+
+/// Ports `command` is expected to listen on when run in `dir`: a port given on the
+/// command line, then the port of the detected service it starts, then the usual
+/// default of the server it runs. Empty when there is no telling.
+pub fn predict_ports(command: &[String], dir: &Path) -> Vec<u16> {
+    let command_line = quote_command_line(command);
+    if let Some(port) = command_port(&command_line) {
+        return vec![port];
+    }
+
+    // `npm run dev`, `make serve`, `cargo run --bin api`, ...
+    let services = ServiceDetector::with_paths(vec![dir.to_path_buf()])
+        .discover_services()
+        .unwrap_or_default();
+    let detected = services
+        .iter()
+        .find(|service| service.command_line() == command_line)
+        .and_then(|service| service.inferred_port);
+    if let Some(port) = detected {
+        return vec![port];
+    }
+
+    let words: Vec<&str> = command.iter().map(String::as_str).collect();
+    let program = words
+        .first()
+        .and_then(|program| Path::new(program).file_name())
+        .map(|program| program.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let has = |word: &str| words.contains(&word);
+
+    match program.as_str() {
+        "docker-compose" => compose_ports(&words[1..], dir),
+        "docker" if words.get(1) == Some(&"compose") => compose_ports(&words[2..], dir),
+        "rails" if has("s") || has("server") => vec![3000],
+        "python" | "python3" if has("runserver") => {
+            // `runserver 8001` or `runserver 0.0.0.0:8001`
+            let address = words
+                .iter()
+                .skip_while(|word| **word != "runserver")
+                .nth(1)
+                .and_then(|address| address.rsplit(':').next()?.parse().ok());
+            vec![address.unwrap_or(8000)]
+        }
+        "python" | "python3" if has("http.server") => {
+            let port = words.last().and_then(|port| port.parse().ok());
+            vec![port.unwrap_or(8000)]
+        }
+        "flask" if has("run") => vec![5000],
+        "uvicorn" | "gunicorn" => vec![8000],
+        "php" if has("artisan") && has("serve") => vec![8000],
+        "hugo" if has("server") => vec![1313],
+        "jekyll" if has("serve") => vec![4000],
+        _ if program == "bin/rails" || (has("bin/rails") && (has("s") || has("server"))) => {
+            vec![3000]
+        }
+        _ if JS_RUNNERS.contains(&program.as_str()) => ProjectPorts::read(dir)
+            .script_port(&command_line)
+            .into_iter()
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// Host ports `docker compose up [options] [services]` publishes
+fn compose_ports(args: &[&str], dir: &Path) -> Vec<u16> {
+    if args.first() != Some(&"up") {
+        return Vec::new();
+    }
+    let Some(content) = COMPOSE_FILES
+        .iter()
+        .find_map(|file_name| std::fs::read_to_string(dir.join(file_name)).ok())
+    else {
+        return Vec::new();
+    };
+
+    let published = compose_published_ports(&content);
+    let services: Vec<&str> = args[1..]
+        .iter()
+        .copied()
+        .filter(|arg| !arg.starts_with('-'))
+        .collect();
+    let mut ports: Vec<u16> = published
+        .iter()
+        .filter(|(name, _)| services.is_empty() || services.contains(&name.as_str()))
+        .map(|(_, port)| *port)
+        .collect();
+    ports.sort_unstable();
+    ports.dedup();
+    ports
+}
+
+/// `port-kill run -- <command>`: clear the ports the command is about to need,
+/// then replace port-kill with it
+pub async fn run_preflight(args: &Args, run: &RunArgs) -> Result<()> {
+    let dir = std::env::current_dir()?;
+    let command_line = quote_command_line(&run.command);
+    let ports = if run.port.is_empty() {
+        predict_ports(&run.command, &dir)
+    } else {
+        run.port.clone()
+    };
+
+    if ports.is_empty() {
+        println!(
+            "ℹ️  Could not tell which port `{}` will use; pass --port to check one",
+            command_line
+        );
+    } else {
+        println!(
+            "🔍 `{}` will listen on {}",
+            command_line,
+            describe_ports(&ports)
+        );
+    }
+
+    // Reservations belong to a project; only that project may take the port
+    let project = dir
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string());
+    let reservations = read_reservation_file(Path::new(&args.get_reservation_file_path()))
+        .map(|versioned| versioned.data)
+        .unwrap_or_default();
+    for port in &ports {
+        if let Some(reservation) = reservations.get(port) {
+            if project.as_deref() != Some(reservation.project_name.as_str()) {
+                return Err(anyhow!(
+                    "Port {} is reserved for {} ({}); pass --port to pick another one",
+                    port,
+                    reservation.project_name,
+                    reservation.process_name
+                ));
+            }
+        }
+    }
+
+    let (_, busy) = get_processes_on_ports(&ports, args);
+    let mut busy_ports: Vec<u16> = busy.keys().copied().collect();
+    busy_ports.sort_unstable();
+    for port in &busy_ports {
+        let process = &busy[port];
+        println!(
+            "⚠️  Port {} is in use by {} (PID {})",
+            port,
+            process.get_display_name(),
+            process.pid
+        );
+    }
+
+    if run.dry_run {
+        if busy_ports.is_empty() {
+            println!("✅ Nothing to clear; would run `{}`", command_line);
+        } else {
+            println!(
+                "Would clear {} and run `{}`",
+                describe_ports(&busy_ports),
+                command_line
+            );
+        }
+        return Ok(());
+    }
+
+    if !busy_ports.is_empty() {
+        if args.safe {
+            println!(
+                "Clear {} and run `{}`? y/N",
+                describe_ports(&busy_ports),
+                command_line
+            );
+            let mut input = String::new();
+            std::io::stdin().read_line(&mut input)?;
+            if !matches!(input.trim(), "y" | "Y" | "yes" | "YES") {
+                println!("Cancelled.");
+                return Ok(());
+            }
+        }
+        kill_all_processes(&busy_ports, args)?;
+        wait_for_release(&busy_ports).await?;
+        println!("✅ Cleared {}", describe_ports(&busy_ports));
+    }
+
+    exec(&run.command)
+}
+
+/// What `--fail-if-found` prints
+#[derive(Debug, Serialize)]
+struct PortReport {
+    clean: bool,
+    ports_checked: usize,
+    occupied: Vec<ProcessInfo>,
+}
+
+/// `--fail-if-found`: print a JSON report of the monitored ports and return the exit
+/// status, 1 when any of them is in use
+pub fn run_fail_if_found(args: &Args) -> Result<i32> {
+    let ports = args.get_ports_to_monitor();
+    let (_, mut processes) = get_processes_on_ports(&ports, args);
+    if let Some(filter) = SmartFilter::from_args(args)? {
+        filter.filter_processes(&mut processes);
+    }
+    let mut occupied: Vec<ProcessInfo> = processes.into_values().collect();
+    occupied.sort_by_key(|process| process.port);
+
+    let report = PortReport {
+        clean: occupied.is_empty(),
+        ports_checked: ports.len(),
+        occupied,
+    };
+    println!("{}", serde_json::to_string_pretty(&report)?);
+    if report.clean {
+        return Ok(0);
+    }
+    let found: Vec<String> = report
+        .occupied
+        .iter()
+        .map(|process| format!("{} ({}, PID {})", process.port, process.name, process.pid))
+        .collect();
+    eprintln!("❌ Monitored ports in use: {}", found.join(", "));
+    Ok(1)
+}
+
+fn describe_ports(ports: &[u16]) -> String {
+    let list = ports
+        .iter()
+        .map(|port| port.to_string())
+        .collect::<Vec<_>>()
+        .join(", ");
+    if ports.len() == 1 {
+        format!("port {}", list)
+    } else {
+        format!("ports {}", list)
+    }
+}
+
+async fn wait_for_release(ports: &[u16]) -> Result<()> {
+    let deadline = tokio::time::Instant::now() + RELEASE_TIMEOUT;
+    for &port in ports {
+        while !is_port_available(port).await {
+            if tokio::time::Instant::now() >= deadline {
+                return Err(anyhow!(
+                    "Port {} is still in use; is its process in --ignore-ports or --ignore-processes?",
+                    port
+                ));
+            }
+            tokio::time::sleep(Duration::from_millis(100)).await;
+        }
+    }
+    Ok(())
+}
+
+/// Run `command` in place of port-kill, so it gets the terminal and signals directly
+fn exec(command: &[String]) -> Result<()> {
+    let (program, args) = command
+        .split_first()
+        .ok_or_else(|| anyhow!("No command to run"))?;
+    let mut process = std::process::Command::new(program);
+    process.args(args);
+
+    #[cfg(not(target_os = "windows"))]
+    {
+        use std::os::unix::process::CommandExt;
+        let error = process.exec();
+        Err(anyhow!("Failed to run {}: {}", program, error))
+    }
+
+    #[cfg(target_os = "windows")]
+    {
+        use anyhow::Context;
+        let status = process
+            .status()
+            .with_context(|| format!("Failed to run {}", program))?;
+        std::process::exit(status.code().unwrap_or(1));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn predict(command: &str, dir: &Path) -> Vec<u16> {
+        let command: Vec<String> = command.split_whitespace().map(String::from).collect();
+        predict_ports(&command, dir)
+    }
+
+    #[test]
+    fn test_predict_ports() {
+        let dir = std::env::temp_dir().join(format!("port-kill-preflight-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(
+            dir.join("package.json"),
+            r#"{"scripts": {"dev": "vite --port 5174", "start": "node server.js"}}"#,
+        )
+        .unwrap();
+        std::fs::write(
+            dir.join("docker-compose.yml"),
+            "services:\n  web:\n    ports: [\"8080:80\"]\n  db:\n    ports: [\"5432:5432\"]\n",
+        )
+        .unwrap();
+
+        assert_eq!(predict("npm run dev", &dir), vec![5174]);
+        assert_eq!(predict("npm run dev -- --port 4000", &dir), vec![4000]);
+        assert_eq!(predict("npx vite", &dir), vec![5173]);
+        assert_eq!(predict("rails s", &dir), vec![3000]);
+        assert_eq!(predict("bin/rails server", &dir), vec![3000]);
+        assert_eq!(
+            predict("python manage.py runserver 0.0.0.0:9000", &dir),
+            vec![9000]
+        );
+        assert_eq!(predict("python3 -m http.server", &dir), vec![8000]);
+        assert_eq!(predict("docker compose up", &dir), vec![5432, 8080]);
+        assert_eq!(predict("docker-compose up -d web", &dir), vec![8080]);
+        assert_eq!(predict("echo hello", &dir), Vec::<u16>::new());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
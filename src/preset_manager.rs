@@ -1,15 +1,29 @@
+use crate::format_version::{
+    backup_old_format, parse_versioned, parse_versioned_value, to_versioned_json,
+    unchanged_payload, Versioned, PRESETS_VERSION,
+};
+use crate::project_dir::find_upward;
 use anyhow::Result;
 use serde::{Deserialize, Serialize};
 use std::collections::{HashMap, HashSet};
 use std::fs;
-use std::path::Path;
+use std::path::{Path, PathBuf};
+
+/// File names checked for project-local presets, in order of preference
+pub const PROJECT_PRESET_FILES: [&str; 3] = [
+    ".port-kill-presets.yaml",
+    ".port-kill-presets.yml",
+    ".port-kill-presets.json",
+];
 
 /// Represents a port preset configuration
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PortPreset {
     /// Name of the preset
+    #[serde(default)]
     pub name: String,
     /// Description of what this preset is for
+    #[serde(default)]
     pub description: String,
     /// List of ports to monitor
     pub ports: Vec<u16>,
@@ -24,15 +38,32 @@ pub struct PortPreset {
     /// Only show processes from specific groups
     pub only_groups: Option<Vec<String>>,
     /// Enable smart filtering
+    #[serde(default)]
     pub smart_filter: bool,
     /// Enable Docker container monitoring
+    #[serde(default)]
     pub docker: bool,
     /// Show process IDs
+    #[serde(default)]
     pub show_pid: bool,
     /// Enable performance metrics
+    #[serde(default)]
     pub performance: bool,
     /// Show project context
+    #[serde(default)]
     pub show_context: bool,
+    /// Ports to protect; when set, the preset turns on guard mode
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub guard_ports: Option<Vec<u16>>,
+    /// Auto-resolve guard mode conflicts by killing the conflicting process
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub auto_resolve: bool,
+    /// Ports the security audit flags as suspicious
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub suspicious_ports: Option<Vec<u16>>,
+    /// Baseline file of approved ports for the security audit
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub baseline_file: Option<String>,
 }
 
 impl PortPreset {
@@ -52,6 +83,10 @@ impl PortPreset {
             show_pid: false,
             performance: false,
             show_context: false,
+            guard_ports: None,
+            auto_resolve: false,
+            suspicious_ports: None,
+            baseline_file: None,
         }
     }
 
@@ -79,6 +114,10 @@ impl PortPreset {
             show_pid: false,
             performance: false,
             show_context: false,
+            guard_ports: None,
+            auto_resolve: false,
+            suspicious_ports: None,
+            baseline_file: None,
         }
     }
 
@@ -103,6 +142,10 @@ impl PortPreset {
             show_pid: false,
             performance: false,
             show_context: false,
+            guard_ports: None,
+            auto_resolve: false,
+            suspicious_ports: None,
+            baseline_file: None,
         }
     }
 }
@@ -113,6 +156,12 @@ pub struct PresetManager {
     config_path: String,
     /// Track names of default presets to avoid saving them to user config
     default_preset_names: HashSet<String>,
+    /// Project preset file merged on top of user presets, if one was found
+    project_path: Option<PathBuf>,
+    /// Presets as defined in the project file, used to keep them out of the user config
+    project_presets: HashMap<String, PortPreset>,
+    /// User presets hidden by a project preset of the same name
+    shadowed_user_presets: HashMap<String, PortPreset>,
 }
 
 impl PresetManager {
@@ -127,10 +176,15 @@ impl PresetManager {
             presets: HashMap::new(),
             config_path,
             default_preset_names: HashSet::new(),
+            project_path: None,
+            project_presets: HashMap::new(),
+            shadowed_user_presets: HashMap::new(),
         }
     }
 
-    /// Load presets from file
+    /// Load default, user and project presets. Project presets (found by walking up
+    /// from the current directory to the git root) win over user presets so a `--preset` name
+    /// behaves the same for everyone working in the repository.
     pub fn load_presets(&mut self) -> Result<()> {
         // First, load default presets
         self.load_default_presets();
@@ -138,17 +192,83 @@ impl PresetManager {
         // Then try to load user presets from file
         if Path::new(&self.config_path).exists() {
             let content = fs::read_to_string(&self.config_path)?;
-            let user_presets: HashMap<String, PortPreset> = serde_json::from_str(&content)?;
+            let user_presets: Versioned<HashMap<String, PortPreset>> =
+                parse_versioned(&content, "presets", PRESETS_VERSION, unchanged_payload).map_err(
+                    |e| anyhow::anyhow!("Invalid presets in {}: {}", self.config_path, e),
+                )?;
+            if user_presets.version < PRESETS_VERSION {
+                backup_old_format(Path::new(&self.config_path), user_presets.version)?;
+            }
 
             // Merge user presets (they override defaults)
-            for (name, preset) in user_presets {
+            for (name, preset) in user_presets.data {
                 self.presets.insert(name, preset);
             }
         }
 
+        let project_file = std::env::current_dir()
+            .ok()
+            .and_then(|dir| Self::find_project_presets(&dir));
+        if let Some(path) = project_file {
+            self.load_project_presets(&path)?;
+        }
+
+        Ok(())
+    }
+
+    /// Find the nearest project preset file in `start` or one of its parents,
+    /// up to the git root
+    pub fn find_project_presets(start: &Path) -> Option<PathBuf> {
+        find_upward(start, &PROJECT_PRESET_FILES)
+    }
+
+    /// Merge presets from a project preset file (YAML or JSON, keyed by preset name)
+    pub fn load_project_presets(&mut self, path: &Path) -> Result<()> {
+        let content = fs::read_to_string(path)?;
+        let is_json = path.extension().is_some_and(|ext| ext == "json");
+        let document: Result<serde_json::Value> = if is_json {
+            serde_json::from_str(&content).map_err(Into::into)
+        } else {
+            serde_yaml::from_str(&content).map_err(Into::into)
+        };
+        let project_presets = document
+            .and_then(|document| {
+                parse_versioned_value::<HashMap<String, PortPreset>>(
+                    document,
+                    "presets",
+                    PRESETS_VERSION,
+                    unchanged_payload,
+                )
+            })
+            .map_err(|e| anyhow::anyhow!("Invalid project presets in {}: {}", path.display(), e))?
+            .data;
+
+        for (name, mut preset) in project_presets {
+            if preset.name.is_empty() {
+                preset.name = name.clone();
+            }
+            if let Some(user_preset) = self.presets.insert(name.clone(), preset.clone()) {
+                if !self.default_preset_names.contains(&name) {
+                    self.shadowed_user_presets.insert(name.clone(), user_preset);
+                }
+            }
+            self.project_presets.insert(name, preset);
+        }
+        self.project_path = Some(path.to_path_buf());
+
         Ok(())
     }
 
+    /// Path of the project preset file that was loaded, if any
+    pub fn project_presets_path(&self) -> Option<&Path> {
+        self.project_path.as_deref()
+    }
+
+    /// Whether a preset came from the project preset file
+    pub fn is_project_preset(&self, name: &str) -> bool {
+        self.project_presets.contains_key(name)
+    }
+
     /// Save presets to file (only saves user-defined presets, not defaults)
     pub fn save_presets(&self) -> Result<()> {
         // Create directory if it doesn't exist
@@ -162,6 +282,17 @@ impl PresetManager {
         let user_presets: HashMap<String, PortPreset> = self
             .presets
             .iter()
+            .filter_map(|(name, preset)| {
+                // Unchanged project presets stay in the repository; keep whatever the
+                // user had saved under that name instead
+                match self.project_presets.get(name) {
+                    Some(project) if Self::presets_match(preset, project) => {
+                        self.shadowed_user_presets.get(name)
+                    }
+                    _ => Some(preset),
+                }
+                .map(|preset| (name, preset))
+            })
             .filter(|(name, preset)| match default_manager.presets.get(*name) {
                 Some(default_preset) => !Self::presets_match(preset, default_preset),
                 None => true,
@@ -169,7 +300,7 @@ impl PresetManager {
             .map(|(k, v)| (k.clone(), v.clone()))
             .collect();
 
-        let content = serde_json::to_string_pretty(&user_presets)?;
+        let content = to_versioned_json(&user_presets, "presets", PRESETS_VERSION)?;
         fs::write(&self.config_path, content)?;
 
         Ok(())
@@ -290,6 +421,10 @@ impl PresetManager {
             && left.show_pid == right.show_pid
             && left.performance == right.performance
             && left.show_context == right.show_context
+            && left.guard_ports == right.guard_ports
+            && left.auto_resolve == right.auto_resolve
+            && left.suspicious_ports == right.suspicious_ports
+            && left.baseline_file == right.baseline_file
     }
 
     /// Get a preset by name
@@ -318,13 +453,21 @@ impl PresetManager {
         output.push_str("📋 Available Port Presets:\n");
         output.push_str(&"=".repeat(50));
         output.push('\n');
+        if let Some(ref path) = self.project_path {
+            output.push_str(&format!("📁 Project presets: {}\n\n", path.display()));
+        }
 
         let mut preset_names: Vec<_> = self.presets.keys().collect();
         preset_names.sort();
 
         for name in preset_names {
             if let Some(preset) = self.presets.get(name) {
-                output.push_str(&format!("• {}: {}\n", name, preset.description));
+                let source = if self.is_project_preset(name) {
+                    " (project)"
+                } else {
+                    ""
+                };
+                output.push_str(&format!("• {}{}: {}\n", name, source, preset.description));
                 output.push_str(&format!(
                     "  Ports: {}\n",
                     preset
@@ -361,6 +504,26 @@ impl PresetManager {
                     output.push_str("  Smart filtering: enabled\n");
                 }
 
+                if let Some(ref guard_ports) = preset.guard_ports {
+                    output.push_str(&format!(
+                        "  Guards ports: {}{}\n",
+                        guard_ports
+                            .iter()
+                            .map(|p| p.to_string())
+                            .collect::<Vec<_>>()
+                            .join(", "),
+                        if preset.auto_resolve {
+                            " (auto-resolve)"
+                        } else {
+                            ""
+                        }
+                    ));
+                }
+
+                if preset.suspicious_ports.is_some() || preset.baseline_file.is_some() {
+                    output.push_str("  Security audit settings: custom\n");
+                }
+
                 output.push('\n');
             }
         }
@@ -368,12 +531,154 @@ impl PresetManager {
         output
     }
 
+    /// Serialize a preset in the shareable format accepted by `--import-preset`
+    pub fn export_preset(&self, name: &str) -> Result<String> {
+        let preset = self
+            .presets
+            .get(name)
+            .ok_or_else(|| anyhow::anyhow!("Preset '{}' not found", name))?;
+        let mut document = HashMap::new();
+        document.insert(name.to_string(), preset.clone());
+        Ok(serde_json::to_string_pretty(&document)?)
+    }
+
+    /// Describe what importing `preset` would change, one line per difference.
+    /// Returns an empty list when the preset is already identical.
+    pub fn describe_import(&self, preset: &PortPreset) -> Vec<String> {
+        let existing = match self.presets.get(&preset.name) {
+            Some(existing) => existing,
+            None => {
+                return Self::preset_fields(preset)
+                    .into_iter()
+                    .filter(|(_, value)| !value.is_empty())
+                    .map(|(field, value)| format!("{}: {}", field, value))
+                    .collect()
+            }
+        };
+
+        Self::preset_fields(existing)
+            .into_iter()
+            .zip(Self::preset_fields(preset))
+            .filter(|((_, old), (_, new))| old != new)
+            .map(|((field, old), (_, new))| {
+                let show = |v: String| {
+                    if v.is_empty() {
+                        "(none)".to_string()
+                    } else {
+                        v
+                    }
+                };
+                format!("{}: {} → {}", field, show(old), show(new))
+            })
+            .collect()
+    }
+
+    /// Where an existing preset comes from ("project", "built-in" or "user")
+    pub fn preset_source(&self, name: &str) -> Option<&'static str> {
+        let preset = self.presets.get(name)?;
+        if self.is_project_preset(name) {
+            return Some("project");
+        }
+        let mut defaults = PresetManager::new();
+        defaults.load_default_presets();
+        match defaults.presets.get(name) {
+            Some(default) if Self::presets_match(preset, default) => Some("built-in"),
+            _ => Some("user"),
+        }
+    }
+
+    /// Displayable field values used to preview preset changes
+    fn preset_fields(preset: &PortPreset) -> Vec<(&'static str, String)> {
+        fn join<T: ToString>(values: &Option<Vec<T>>) -> String {
+            values
+                .as_deref()
+                .unwrap_or_default()
+                .iter()
+                .map(|v| v.to_string())
+                .collect::<Vec<_>>()
+                .join(", ")
+        }
+        let flag = |on: bool| if on { "on".to_string() } else { String::new() };
+
+        vec![
+            ("description", preset.description.clone()),
+            ("ports", join(&Some(preset.ports.clone()))),
+            ("ignore ports", join(&preset.ignore_ports)),
+            ("ignore processes", join(&preset.ignore_processes)),
+            ("ignore patterns", join(&preset.ignore_patterns)),
+            ("ignore groups", join(&preset.ignore_groups)),
+            ("only groups", join(&preset.only_groups)),
+            ("smart filter", flag(preset.smart_filter)),
+            ("docker", flag(preset.docker)),
+            ("show pid", flag(preset.show_pid)),
+            ("performance", flag(preset.performance)),
+            ("show context", flag(preset.show_context)),
+            ("guard ports", join(&preset.guard_ports)),
+            ("auto resolve", flag(preset.auto_resolve)),
+            ("suspicious ports", join(&preset.suspicious_ports)),
+            (
+                "baseline file",
+                preset.baseline_file.clone().unwrap_or_default(),
+            ),
+        ]
+    }
+
     /// Get the config file path
     pub fn get_config_path(&self) -> &str {
         &self.config_path
     }
 }
 
+/// Read a shared preset document from an http(s) URL or a local file
+pub async fn fetch_preset_source(source: &str) -> Result<String> {
+    if source.starts_with("http://") || source.starts_with("https://") {
+        let response = reqwest::Client::new()
+            .get(source)
+            .header("User-Agent", "port-kill-presets")
+            .send()
+            .await?;
+        if !response.status().is_success() {
+            return Err(anyhow::anyhow!(
+                "Failed to fetch presets from {}: {}",
+                source,
+                response.status()
+            ));
+        }
+        Ok(response.text().await?)
+    } else {
+        fs::read_to_string(source)
+            .map_err(|e| anyhow::anyhow!("Failed to read presets from {}: {}", source, e))
+    }
+}
+
+/// Parse a shared preset document (JSON or YAML): either a single preset with a
+/// `name`, or presets keyed by name as written by `--export-preset`
+pub fn parse_shared_presets(content: &str) -> Result<Vec<PortPreset>> {
+    if let Ok(preset) = serde_yaml::from_str::<PortPreset>(content) {
+        if preset.name.is_empty() {
+            return Err(anyhow::anyhow!("Shared preset is missing a name"));
+        }
+        return Ok(vec![preset]);
+    }
+
+    let presets: HashMap<String, PortPreset> = serde_yaml::from_str(content)
+        .map_err(Into::into)
+        .and_then(|document| {
+            parse_versioned_value(document, "presets", PRESETS_VERSION, unchanged_payload)
+        })
+        .map_err(|e| anyhow::anyhow!("Invalid preset document: {}", e))?
+        .data;
+    let mut presets: Vec<PortPreset> = presets
+        .into_iter()
+        .map(|(name, mut preset)| {
+            preset.name = name;
+            preset
+        })
+        .collect();
+    presets.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(presets)
+}
+
 impl Default for PresetManager {
     fn default() -> Self {
         Self::new()
@@ -454,9 +759,11 @@ mod tests {
         manager.save_presets().expect("Failed to save presets");
 
         let content = fs::read_to_string(&config_path).expect("Failed to read presets file");
-        let saved_presets: HashMap<String, PortPreset> =
-            serde_json::from_str(&content).expect("Failed to parse presets file");
-        assert!(saved_presets.contains_key("dev"));
+        let saved_presets: Versioned<HashMap<String, PortPreset>> =
+            parse_versioned(&content, "presets", PRESETS_VERSION, unchanged_payload)
+                .expect("Failed to parse presets file");
+        assert_eq!(saved_presets.version, PRESETS_VERSION);
+        assert!(saved_presets.data.contains_key("dev"));
 
         let mut reloaded = PresetManager::new();
         reloaded.config_path = config_path.to_string_lossy().to_string();
@@ -468,4 +775,120 @@ mod tests {
 
         let _ = fs::remove_dir_all(&test_dir);
     }
+
+    #[test]
+    fn test_project_presets_override_user_presets() {
+        let test_dir = std::env::temp_dir().join(format!(
+            "port-kill-project-presets-{}",
+            std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .unwrap()
+                .as_nanos()
+        ));
+        let nested = test_dir.join("services/api");
+        fs::create_dir_all(&nested).expect("Failed to create test directory");
+        fs::create_dir_all(test_dir.join(".git")).expect("Failed to create test directory");
+        let config_path = test_dir.join("presets.json");
+        let project_file = test_dir.join(".port-kill-presets.yaml");
+        fs::write(
+            &project_file,
+            "backend:\n  description: Team backend\n  ports: [4000, 5432]\n  docker: true\n",
+        )
+        .expect("Failed to write project presets");
+
+        assert_eq!(
+            PresetManager::find_project_presets(&nested),
+            Some(project_file.clone())
+        );
+
+        let mut manager = PresetManager::new();
+        manager.config_path = config_path.to_string_lossy().to_string();
+        manager.load_default_presets();
+        manager.add_preset(PortPreset::new(
+            "backend".to_string(),
+            "My backend".to_string(),
+            vec![8000],
+        ));
+        manager
+            .load_project_presets(&project_file)
+            .expect("Failed to load project presets");
+
+        let backend = manager
+            .get_preset("backend")
+            .expect("Missing backend preset");
+        assert_eq!(backend.name, "backend");
+        assert_eq!(backend.ports, vec![4000, 5432]);
+        assert!(backend.docker);
+        assert!(manager.is_project_preset("backend"));
+
+        // Saving keeps the user's own preset rather than copying the project one
+        manager.save_presets().expect("Failed to save presets");
+        let content = fs::read_to_string(&config_path).expect("Failed to read presets file");
+        let saved: Versioned<HashMap<String, PortPreset>> =
+            parse_versioned(&content, "presets", PRESETS_VERSION, unchanged_payload)
+                .expect("Failed to parse presets file");
+        assert_eq!(saved.data["backend"].ports, vec![8000]);
+
+        let _ = fs::remove_dir_all(&test_dir);
+    }
+
+    #[test]
+    fn test_parse_shared_presets_and_preview() {
+        let single = parse_shared_presets(r#"{"name": "api", "ports": [4000]}"#).unwrap();
+        assert_eq!(single.len(), 1);
+        assert_eq!(single[0].name, "api");
+
+        let keyed =
+            parse_shared_presets("dev:\n  description: Team dev\n  ports: [3000, 5173]\n").unwrap();
+        assert_eq!(keyed[0].name, "dev");
+        assert_eq!(keyed[0].ports, vec![3000, 5173]);
+
+        assert!(parse_shared_presets(r#"{"ports": [4000]}"#).is_err());
+
+        let mut manager = PresetManager::new();
+        manager.load_default_presets();
+        assert_eq!(manager.preset_source("dev"), Some("built-in"));
+        let changes = manager.describe_import(&keyed[0]);
+        assert!(changes
+            .iter()
+            .any(|c| c.starts_with("ports: ") && c.ends_with("→ 3000, 5173")));
+
+        let exported = manager.export_preset("dev").unwrap();
+        let round_trip = parse_shared_presets(&exported).unwrap();
+        assert!(manager.describe_import(&round_trip[0]).is_empty());
+    }
+
+    #[test]
+    fn test_unversioned_presets_file_is_migrated() {
+        let test_dir = std::env::temp_dir().join(format!(
+            "port-kill-presets-v1-{}",
+            std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .unwrap()
+                .as_nanos()
+        ));
+        fs::create_dir_all(&test_dir).expect("Failed to create test directory");
+        let config_path = test_dir.join("presets.json");
+        fs::write(
+            &config_path,
+            r#"{"api": {"name": "api", "description": "API", "ports": [4000],
+                "ignore_ports": null, "ignore_processes": null, "ignore_patterns": null,
+                "ignore_groups": null, "only_groups": null, "smart_filter": false,
+                "docker": false, "show_pid": false, "performance": false,
+                "show_context": false}}"#,
+        )
+        .expect("Failed to write presets file");
+
+        let mut manager = PresetManager::new();
+        manager.config_path = config_path.to_string_lossy().to_string();
+        manager.load_presets().expect("Failed to load presets");
+        assert_eq!(manager.get_preset("api").unwrap().ports, vec![4000]);
+        assert!(test_dir.join("presets.json.v1.bak").exists());
+
+        manager.save_presets().expect("Failed to save presets");
+        let content = fs::read_to_string(&config_path).expect("Failed to read presets file");
+        assert!(content.contains("\"version\": 2"));
+
+        let _ = fs::remove_dir_all(&test_dir);
+    }
 }
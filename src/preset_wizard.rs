@@ -0,0 +1,254 @@
+use crate::cli::Args;
+use crate::preset_manager::PortPreset;
+use crate::process_monitor::ProcessMonitor;
+use crate::types::ProcessInfo;
+use anyhow::{anyhow, Result};
+use crossbeam_channel::bounded;
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::io::{BufRead, Write};
+
+/// What the wizard suggests from the current listeners, before the user weighs in
+#[derive(Debug, Clone, Default)]
+pub struct PresetProposal {
+    /// Listeners worth monitoring, sorted by port
+    pub listeners: Vec<ProcessInfo>,
+    /// Listeners owned by browsers, system daemons and other background services
+    pub background: Vec<ProcessInfo>,
+    /// Process groups seen among the listeners worth monitoring
+    pub groups: Vec<String>,
+    /// Whether any listener runs inside a container
+    pub containers: bool,
+}
+
+impl PresetProposal {
+    /// Split the scanned listeners into project services and background noise
+    pub fn from_processes(
+        processes: &HashMap<u16, ProcessInfo>,
+        background_names: &HashSet<String>,
+    ) -> Self {
+        let mut proposal = Self::default();
+        let mut sorted: Vec<&ProcessInfo> = processes.values().collect();
+        sorted.sort_by_key(|p| p.port);
+
+        let mut groups = BTreeSet::new();
+        for process in sorted {
+            proposal.containers |= process.container_id.is_some();
+            if background_names.contains(&process.name) {
+                proposal.background.push(process.clone());
+            } else {
+                if let Some(ref group) = process.process_group {
+                    groups.insert(group.clone());
+                }
+                proposal.listeners.push(process.clone());
+            }
+        }
+        proposal.groups = groups.into_iter().collect();
+        proposal
+    }
+
+    /// Walk through the proposal, reading answers from `input`.
+    /// Returns None when nothing was selected.
+    pub fn walk_through(&self, name: &str, input: &mut impl BufRead) -> Result<Option<PortPreset>> {
+        let mut ports = Vec::new();
+        for process in &self.listeners {
+            if ask(input, &format!("Monitor {}?", describe(process)), true)? {
+                ports.push(process.port);
+            }
+        }
+        for process in &self.background {
+            if ask(input, &format!("Monitor {}?", describe(process)), false)? {
+                ports.push(process.port);
+            }
+        }
+        if ports.is_empty() {
+            return Ok(None);
+        }
+
+        let mut preset = PortPreset::new(name.to_string(), String::new(), ports);
+
+        let background_names: BTreeSet<String> =
+            self.background.iter().map(|p| p.name.clone()).collect();
+        if !background_names.is_empty() {
+            let names = background_names.into_iter().collect::<Vec<_>>();
+            if ask(input, &format!("Ignore {}?", names.join(", ")), true)? {
+                preset.ignore_processes = Some(names);
+            }
+        }
+
+        if self.groups.len() > 1
+            && ask(
+                input,
+                &format!("Only show {} processes?", self.groups.join(", ")),
+                false,
+            )?
+        {
+            preset.only_groups = Some(self.groups.clone());
+        }
+
+        preset.smart_filter = ask(
+            input,
+            "Enable smart filtering of system processes?",
+            !self.background.is_empty(),
+        )?;
+        preset.docker = ask(
+            input,
+            "Enable Docker container monitoring?",
+            self.containers,
+        )?;
+        preset.show_context = ask(input, "Show project context for each process?", false)?;
+
+        let default_description = self
+            .listeners
+            .iter()
+            .find_map(|p| p.project_name.as_ref())
+            .map(|project| format!("Services from {}", project))
+            .unwrap_or_else(|| "User-defined preset".to_string());
+        preset.description = ask_text(input, "Description", &default_description)?;
+
+        Ok(Some(preset))
+    }
+}
+
+/// Scan the ports `args` selects and build a preset interactively on stdin
+pub async fn run_preset_wizard(args: &Args, name: &str) -> Result<Option<PortPreset>> {
+    let ports = args.get_ports_to_monitor();
+    println!(
+        "🧙 Building preset '{}' from what is listening on {}",
+        name,
+        args.get_port_description()
+    );
+
+    let (update_sender, _update_receiver) = bounded(100);
+    let mut monitor = ProcessMonitor::new(update_sender, ports, args.docker, args.verbose)?;
+    let processes = monitor.scan_processes().await?;
+    if processes.is_empty() {
+        return Err(anyhow!(
+            "Nothing is listening on {}; widen the scan with --ports or --start-port/--end-port",
+            args.get_port_description()
+        ));
+    }
+
+    let mut smart_args = args.clone();
+    smart_args.smart_filter = true;
+    let (_, background_names, _) = smart_args.get_smart_filter_defaults();
+
+    let proposal = PresetProposal::from_processes(&processes, &background_names);
+    println!("Found {} listener(s):", processes.len());
+    for process in proposal.listeners.iter().chain(&proposal.background) {
+        println!("  {}", describe(process));
+    }
+    println!();
+
+    let stdin = std::io::stdin();
+    let mut input = stdin.lock();
+    let preset = match proposal.walk_through(name, &mut input)? {
+        Some(preset) => preset,
+        None => return Ok(None),
+    };
+
+    println!();
+    println!("{}", serde_json::to_string_pretty(&preset)?);
+    if ask(&mut input, &format!("Save preset '{}'?", name), true)? {
+        Ok(Some(preset))
+    } else {
+        Ok(None)
+    }
+}
+
+fn describe(process: &ProcessInfo) -> String {
+    let mut text = format!(":{} {}", process.port, process.name);
+    if let Some(ref group) = process.process_group {
+        text.push_str(&format!(" ({})", group));
+    }
+    if let Some(ref container) = process.container_name {
+        text.push_str(&format!(" [container {}]", container));
+    } else if let Some(ref project) = process.project_name {
+        text.push_str(&format!(" — {}", project));
+    }
+    text
+}
+
+fn ask(input: &mut impl BufRead, question: &str, default: bool) -> Result<bool> {
+    print!("{} {} ", question, if default { "[Y/n]" } else { "[y/N]" });
+    std::io::stdout().flush()?;
+
+    let mut answer = String::new();
+    input.read_line(&mut answer)?;
+    Ok(match answer.trim().to_lowercase().as_str() {
+        "y" | "yes" => true,
+        "n" | "no" => false,
+        _ => default,
+    })
+}
+
+fn ask_text(input: &mut impl BufRead, question: &str, default: &str) -> Result<String> {
+    print!("{} [{}]: ", question, default);
+    std::io::stdout().flush()?;
+
+    let mut answer = String::new();
+    input.read_line(&mut answer)?;
+    let answer = answer.trim();
+    Ok(if answer.is_empty() {
+        default.to_string()
+    } else {
+        answer.to_string()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    fn listener(port: u16, name: &str, group: Option<&str>) -> ProcessInfo {
+        ProcessInfo {
+            pid: port as i32,
+            port,
+            command: name.to_string(),
+            name: name.to_string(),
+            container_id: None,
+            container_name: None,
+            command_line: None,
+            working_directory: None,
+            process_group: group.map(str::to_string),
+            project_name: Some("shop".to_string()),
+            cpu_usage: None,
+            memory_usage: None,
+            memory_percentage: None,
+            protocol: crate::types::Protocol::Tcp,
+            bind_address: None,
+            address_family: None,
+        }
+    }
+
+    #[test]
+    fn test_wizard_proposes_and_applies_answers() {
+        let processes: HashMap<u16, ProcessInfo> = [
+            listener(3000, "node", Some("Node.js")),
+            listener(8000, "python3", Some("Python")),
+            listener(7000, "ControlCe", None),
+        ]
+        .into_iter()
+        .map(|p| (p.port, p))
+        .collect();
+        let background: HashSet<String> = ["ControlCe".to_string()].into_iter().collect();
+
+        let proposal = PresetProposal::from_processes(&processes, &background);
+        assert_eq!(proposal.listeners.len(), 2);
+        assert_eq!(proposal.background[0].port, 7000);
+        assert_eq!(proposal.groups, vec!["Node.js", "Python"]);
+
+        // Keep 3000, drop 8000, default for 7000 (no), then defaults for everything else
+        let mut answers = Cursor::new("y\nn\n\n\n\n\n\n\n\n");
+        let preset = proposal
+            .walk_through("mine", &mut answers)
+            .unwrap()
+            .unwrap();
+        assert_eq!(preset.ports, vec![3000]);
+        assert_eq!(preset.ignore_processes, Some(vec!["ControlCe".to_string()]));
+        assert_eq!(preset.only_groups, None);
+        assert!(preset.smart_filter);
+        assert!(!preset.docker);
+        assert_eq!(preset.description, "Services from shop");
+    }
+}
@@ -1,8 +1,16 @@
-use crate::restart_manager::RestartManager;
+use crate::anomaly::{load_anomalies, AnomalyNotifier};
+use crate::port_activity::PortActivityLog;
+use crate::restart_manager::{
+    is_ignored_watch_path, next_keep_alive_backoff, watch_relative_path, ContainerTarget,
+    RestartManager, RestartOutcome, RestartOverrides,
+};
+use crate::scan_interval::AdaptiveInterval;
 use crate::smart_filter::{FilterStats, SmartFilter};
 use crate::system_monitor::SystemMonitor;
-use crate::types::{ProcessHistory, ProcessHistoryEntry, ProcessInfo, ProcessUpdate};
+use crate::history_store::HistoryStore;
+use crate::types::{AddressFamily, ProcessHistoryEntry, ProcessInfo, Protocol, ProcessUpdate};
 use anyhow::{Context, Result};
+use chrono::Utc;
 use crossbeam_channel::Sender;
 use log::{error, info, warn};
 #[cfg(not(target_os = "windows"))]
@@ -10,11 +18,37 @@ use nix::sys::signal::{kill, Signal};
 #[cfg(not(target_os = "windows"))]
 use nix::unistd::Pid;
 use std::collections::{HashMap, HashSet};
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
 use std::process::Command;
+use std::sync::Arc;
 use std::time::Duration;
 use tokio::time::sleep;
 
 const MONITORING_INTERVAL: Duration = Duration::from_secs(2);
+/// Quiet period after a file change before `--watch` restarts the process
+const WATCH_DEBOUNCE: Duration = Duration::from_millis(300);
+/// How often --keep-alive checks whether a restarted container is still running
+const CONTAINER_POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Scan settings that can change while monitoring is running
+pub struct MonitorSettings {
+    pub ports: Vec<u16>,
+    pub docker: bool,
+    pub smart_filter: Option<SmartFilter>,
+}
+
+impl MonitorSettings {
+    pub fn from_args(args: &crate::cli::Args) -> Result<Self> {
+        Ok(Self {
+            ports: args.get_ports_to_monitor(),
+            docker: args.docker,
+            smart_filter: SmartFilter::from_args(args)?,
+        })
+    }
+}
+
+/// Slot for new settings, picked up by the monitoring loop before its next scan
+pub type MonitorSettingsHandle = Arc<std::sync::Mutex<Option<MonitorSettings>>>;
 
 pub struct ProcessMonitor {
     update_sender: Sender<ProcessUpdate>,
@@ -22,11 +56,14 @@ pub struct ProcessMonitor {
     ports_to_monitor: Vec<u16>,
     docker_enabled: bool,
     verbose: bool,
-    history: ProcessHistory,
     smart_filter: Option<SmartFilter>,
     system_monitor: SystemMonitor,
     performance_enabled: bool,
     restart_manager: RestartManager,
+    pending_settings: MonitorSettingsHandle,
+    friendly_names: bool,
+    udp: bool,
+    scan_interval: AdaptiveInterval,
 }
 
 impl ProcessMonitor {
@@ -42,12 +79,14 @@ impl ProcessMonitor {
             ports_to_monitor,
             docker_enabled,
             verbose,
-            history: ProcessHistory::load_from_file(&ProcessHistory::get_history_file_path(), 100)
-                .unwrap_or_else(|_| ProcessHistory::new(100)),
             smart_filter: None,
             system_monitor: SystemMonitor::new(),
             performance_enabled: false,
             restart_manager: RestartManager::new().unwrap_or_default(),
+            pending_settings: MonitorSettingsHandle::default(),
+            friendly_names: false,
+            udp: false,
+            scan_interval: AdaptiveInterval::fixed(MONITORING_INTERVAL),
         })
     }
 
@@ -64,12 +103,14 @@ impl ProcessMonitor {
             ports_to_monitor,
             docker_enabled,
             verbose,
-            history: ProcessHistory::load_from_file(&ProcessHistory::get_history_file_path(), 100)
-                .unwrap_or_else(|_| ProcessHistory::new(100)),
             smart_filter: Some(smart_filter),
             system_monitor: SystemMonitor::new(),
             performance_enabled: false,
             restart_manager: RestartManager::new().unwrap_or_default(),
+            pending_settings: MonitorSettingsHandle::default(),
+            friendly_names: false,
+            udp: false,
+            scan_interval: AdaptiveInterval::fixed(MONITORING_INTERVAL),
         })
     }
 
@@ -87,15 +128,49 @@ impl ProcessMonitor {
             ports_to_monitor,
             docker_enabled,
             verbose,
-            history: ProcessHistory::load_from_file(&ProcessHistory::get_history_file_path(), 100)
-                .unwrap_or_else(|_| ProcessHistory::new(100)),
             smart_filter,
             system_monitor: SystemMonitor::new(),
             performance_enabled,
             restart_manager: RestartManager::new().unwrap_or_default(),
+            pending_settings: MonitorSettingsHandle::default(),
+            friendly_names: false,
+            udp: false,
+            scan_interval: AdaptiveInterval::fixed(MONITORING_INTERVAL),
         })
     }
 
+    /// Name generic runtimes after their project in scan results (`--friendly-names`)
+    pub fn set_friendly_names(&mut self, enabled: bool) {
+        self.friendly_names = enabled;
+    }
+
+    /// Include processes bound to UDP ports in scans (`--udp`)
+    pub fn set_udp(&mut self, enabled: bool) {
+        self.udp = enabled;
+    }
+
+    /// How often `start_monitoring` scans, e.g. backing off while nothing changes
+    pub fn set_scan_interval(&mut self, interval: AdaptiveInterval) {
+        self.scan_interval = interval;
+    }
+
+    /// Handle for changing the scanned ports and filters of a running monitor
+    pub fn settings_handle(&self) -> MonitorSettingsHandle {
+        self.pending_settings.clone()
+    }
+
+    fn apply_pending_settings(&mut self) {
+        let pending = match self.pending_settings.lock() {
+            Ok(mut slot) => slot.take(),
+            Err(_) => None,
+        };
+        if let Some(settings) = pending {
+            self.ports_to_monitor = settings.ports;
+            self.docker_enabled = settings.docker;
+            self.smart_filter = settings.smart_filter;
+        }
+    }
+
     pub fn get_process_start_time(&mut self, pid: i32) -> Option<u64> {
         self.system_monitor.get_process_start_time(pid)
     }
@@ -121,11 +196,22 @@ impl ProcessMonitor {
 
         info!("Starting process monitoring on {}", port_description);
 
+        let mut activity = PortActivityLog::load();
+        let mut anomalies = AnomalyNotifier::default();
         loop {
+            self.apply_pending_settings();
             let old_processes = self.current_processes.clone();
+            let mut changed = false;
             match self.scan_processes().await {
                 Ok(processes) => {
+                    if activity.record_scan(&self.ports_to_monitor, &processes, Utc::now()) {
+                        if let Err(e) = activity.save() {
+                            warn!("Failed to save port activity: {}", e);
+                        }
+                        notify_anomalies(&mut anomalies, &activity);
+                    }
                     if old_processes != processes {
+                        changed = true;
                         let update = ProcessUpdate::new(processes.clone());
                         info!("Process update: {} processes found", update.count);
 
@@ -139,7 +225,7 @@ impl ProcessMonitor {
                 }
             }
 
-            sleep(MONITORING_INTERVAL).await;
+            sleep(self.scan_interval.record(changed)).await;
         }
     }
 
@@ -156,26 +242,61 @@ impl ProcessMonitor {
             smart_filter: false,
             only_groups: None,
             console: false,
+            tui: false,
             verbose: false, // Set to false to avoid infinite recursion in get_processes_on_ports
             docker: self.docker_enabled,
+            udp: self.udp,
+            bind_addr: Vec::new(),
             show_pid: false,
             log_level: crate::cli::LogLevel::Info,
+            log_file: None,
+            log_format: crate::cli::LogFormat::Text,
+            log_rotation: crate::cli::LogRotation::Daily,
             show_history: false,
             clear_history: false,
             show_filters: false,
             performance: self.performance_enabled,
             show_context: false,
+            friendly_names: false,
+            report: None,
+            report_format: crate::cli::ReportFormat::Terminal,
+            report_send: false,
+            fail_if_found: false,
+            no_daemon: false,
+            update_channel: crate::cli::UpdateChannel::Stable,
             kill_all: false,
             kill_group: None,
             kill_project: None,
             restart: None,
+            pick: false,
+            keep_alive: false,
+            watch: Vec::new(),
+            pre_restart: None,
+            post_restart: None,
+            tmux: false,
+            tmux_session: None,
+            no_tmux: false,
+            restart_env: Vec::new(),
+            env_file: None,
+            clean_env: false,
+            as_user: None,
+            restart_args: Vec::new(),
+            restart_all: false,
+            restart_timeout: 10,
             show_restart_history: false,
             clear_restart: None,
+            prune_restarts: false,
+            export_restarts: None,
+            import_restarts: None,
             show_tree: false,
             json: false,
             reset: false,
             show_offenders: false,
             show_patterns: false,
+            history_port: None,
+            since: None,
+            until: None,
+            timeline: None,
             show_suggestions: false,
             show_stats: false,
             show_root_cause: false,
@@ -193,14 +314,18 @@ impl ProcessMonitor {
             baseline_file: None,
             suspicious_only: false,
             remote: None,
+            remote_bin: "port-kill-console".to_string(),
             monitor_endpoint: None,
             send_interval: 30,
             scan_interval: 2,
+            max_scan_interval: 30,
             endpoint_auth: None,
             endpoint_fields: None,
             endpoint_include_audit: false,
             endpoint_retries: 3,
             endpoint_timeout: 10,
+            prometheus_port: None,
+            prometheus_addr: std::net::Ipv4Addr::LOCALHOST.into(),
             script: None,
             script_file: None,
             script_lang: "js".to_string(),
@@ -215,22 +340,52 @@ impl ProcessMonitor {
             safe: false,
             positional_ports: vec![],
             preset: None,
+            no_auto_preset: false,
+            explain: false,
             list_presets: false,
             save_preset: None,
             preset_desc: None,
+            interactive: false,
             delete_preset: None,
+            import_preset: None,
+            export_preset: None,
+            yes: false,
             check_updates: false,
+            doctor: false,
+            dashboard: None,
+            serve: None,
+            machine: false,
+            install_login_item: false,
+            remove_login_item: false,
+            install_git_hooks: false,
+            reserve_branch_port: false,
+            release_branch_port: None,
+            branch_ports: false,
             self_update: false,
-            cache: None,
+            command: None,
             detect: false,
             start: None,
+            start_env: Vec::new(),
+            load_env: false,
             guard_auto_restart: false,
             up: false,
+            watch_config: false,
+            manage: false,
             down: false,
             restart_service: None,
             status: false,
             config_file: ".port-kill.yaml".to_string(),
             init_config: false,
+            no_config: false,
+            no_reload: false,
+            smart_filter_rules: Default::default(),
+            tray_quick_actions: None,
+            tray_icon_rules: Default::default(),
+            tray_menu_layout: Default::default(),
+            tray_hotkeys: Vec::new(),
+            tray_pins: Vec::new(),
+            explicit_settings: Vec::new(),
+            config_error: None,
         };
         
         let (_count, mut processes) = get_processes_on_ports(&self.ports_to_monitor, &args);
@@ -287,6 +442,10 @@ impl ProcessMonitor {
             filter.filter_processes(&mut processes);
         }
 
+        if self.friendly_names {
+            apply_friendly_names(&mut processes);
+        }
+
         // Update internal state so that subsequent operations (kill, restart, history)
         // have access to the most recent process metadata
         self.current_processes = processes.clone();
@@ -308,26 +467,61 @@ impl ProcessMonitor {
             smart_filter: false,
             only_groups: None,
             console: false,
+            tui: false,
             verbose: false,
             docker: self.docker_enabled,
+            udp: self.udp,
+            bind_addr: Vec::new(),
             show_pid: false,
             log_level: crate::cli::LogLevel::Info,
+            log_file: None,
+            log_format: crate::cli::LogFormat::Text,
+            log_rotation: crate::cli::LogRotation::Daily,
             show_history: false,
             clear_history: false,
             show_filters: false,
             performance: false,
             show_context: false,
+            friendly_names: false,
+            report: None,
+            report_format: crate::cli::ReportFormat::Terminal,
+            report_send: false,
+            fail_if_found: false,
+            no_daemon: false,
+            update_channel: crate::cli::UpdateChannel::Stable,
             kill_all: false,
             kill_group: None,
             kill_project: None,
             restart: None,
+            pick: false,
+            keep_alive: false,
+            watch: Vec::new(),
+            pre_restart: None,
+            post_restart: None,
+            tmux: false,
+            tmux_session: None,
+            no_tmux: false,
+            restart_env: Vec::new(),
+            env_file: None,
+            clean_env: false,
+            as_user: None,
+            restart_args: Vec::new(),
+            restart_all: false,
+            restart_timeout: 10,
             show_restart_history: false,
             clear_restart: None,
+            prune_restarts: false,
+            export_restarts: None,
+            import_restarts: None,
             show_tree: false,
             json: false,
             reset: false,
             show_offenders: false,
             show_patterns: false,
+            history_port: None,
+            since: None,
+            until: None,
+            timeline: None,
             show_suggestions: false,
             show_stats: false,
             show_root_cause: false,
@@ -345,14 +539,18 @@ impl ProcessMonitor {
             baseline_file: None,
             suspicious_only: false,
             remote: None,
+            remote_bin: "port-kill-console".to_string(),
             monitor_endpoint: None,
             send_interval: 30,
             scan_interval: 2,
+            max_scan_interval: 30,
             endpoint_auth: None,
             endpoint_fields: None,
             endpoint_include_audit: false,
             endpoint_retries: 3,
             endpoint_timeout: 10,
+            prometheus_port: None,
+            prometheus_addr: std::net::Ipv4Addr::LOCALHOST.into(),
             script: None,
             script_file: None,
             script_lang: "js".to_string(),
@@ -367,27 +565,56 @@ impl ProcessMonitor {
             safe: false,
             positional_ports: vec![],
             preset: None,
+            no_auto_preset: false,
+            explain: false,
             list_presets: false,
             save_preset: None,
             preset_desc: None,
+            interactive: false,
             delete_preset: None,
+            import_preset: None,
+            export_preset: None,
+            yes: false,
             check_updates: false,
+            doctor: false,
+            dashboard: None,
+            serve: None,
+            machine: false,
+            install_login_item: false,
+            remove_login_item: false,
+            install_git_hooks: false,
+            reserve_branch_port: false,
+            release_branch_port: None,
+            branch_ports: false,
             self_update: false,
-            cache: None,
+            command: None,
             detect: false,
             start: None,
+            start_env: Vec::new(),
+            load_env: false,
             guard_auto_restart: false,
             up: false,
+            watch_config: false,
+            manage: false,
             down: false,
             restart_service: None,
             status: false,
             config_file: ".port-kill.yaml".to_string(),
             init_config: false,
+            no_config: false,
+            no_reload: false,
+            smart_filter_rules: Default::default(),
+            tray_quick_actions: None,
+            tray_icon_rules: Default::default(),
+            tray_menu_layout: Default::default(),
+            tray_hotkeys: Vec::new(),
+            tray_pins: Vec::new(),
+            explicit_settings: Vec::new(),
+            config_error: None,
         };
         get_processes_on_ports_multi(&self.ports_to_monitor, &args)
     }
 
-    #[allow(dead_code)]
     async fn get_process_on_port(&self, port: u16) -> Result<ProcessInfo> {
         #[cfg(target_os = "windows")]
         {
@@ -507,6 +734,9 @@ impl ProcessMonitor {
             cpu_usage: None,
             memory_usage: None,
             memory_percentage: None,
+            protocol: Protocol::Tcp,
+            bind_address: None,
+            address_family: None,
         };
 
         // Determine process group and project name
@@ -558,6 +788,9 @@ impl ProcessMonitor {
                             cpu_usage: None,
                             memory_usage: None,
                             memory_percentage: None,
+                            protocol: Protocol::Tcp,
+                            bind_address: None,
+                            address_family: None,
                         };
 
                         // Determine process group and project name
@@ -589,6 +822,9 @@ impl ProcessMonitor {
                         cpu_usage: None,
                         memory_usage: None,
                         memory_percentage: None,
+                        protocol: Protocol::Tcp,
+                        bind_address: None,
+                        address_family: None,
                     };
 
                     // Determine process group and project name
@@ -631,6 +867,9 @@ impl ProcessMonitor {
             cpu_usage: None,
             memory_usage: None,
             memory_percentage: None,
+            protocol: Protocol::Tcp,
+            bind_address: None,
+            address_family: None,
         };
 
         // Determine process group and project name
@@ -944,8 +1183,17 @@ impl ProcessMonitor {
             .find(|p| p.pid == pid)
             .cloned();
 
-        // Save to restart manager — fetch verbose info on demand if not already available
-        if let Some(ref proc_info) = process_info {
+        // Containers are brought back through Docker rather than by rerunning their proxy process
+        let container = process_info.as_ref().and_then(ContainerTarget::for_process);
+        if let (Some(proc_info), Some(container)) = (&process_info, &container) {
+            if let Err(e) = self
+                .restart_manager
+                .save_container_for_restart(proc_info.port, container)
+            {
+                warn!("Failed to save restart info for port {}: {}", proc_info.port, e);
+            }
+        } else if let Some(ref proc_info) = process_info {
+            // Save to restart manager — fetch verbose info on demand if not already available
             let mut cmd_line = proc_info.command_line.clone();
             let mut work_dir = proc_info.working_directory.clone();
 
@@ -965,10 +1213,13 @@ impl ProcessMonitor {
             }
 
             if let (Some(ref cl), Some(ref wd)) = (&cmd_line, &work_dir) {
+                // Capture the victim's own environment so restarts see the same PORT, NODE_ENV, etc.
+                let env_vars = RestartManager::read_process_environment(proc_info.pid as u32);
                 if let Err(e) = self.restart_manager.save_process_for_restart(
                     proc_info.port,
                     cl,
-                    wd
+                    wd,
+                    env_vars,
                 ) {
                     warn!("Failed to save restart info for port {}: {}", proc_info.port, e);
                 }
@@ -1050,17 +1301,8 @@ impl ProcessMonitor {
         // Add to history if we found the process info and add_to_history is true
         if add_to_history {
             if let Some(process_info) = process_info {
-                let history_entry = ProcessHistoryEntry::new(&process_info, context.to_string());
-                self.history.add_entry(history_entry);
+                record_kill(&ProcessHistoryEntry::new(&process_info, context.to_string()));
                 info!("Added process {} to history", pid);
-
-                // Save history to file
-                if let Err(e) = self
-                    .history
-                    .save_to_file(&ProcessHistory::get_history_file_path())
-                {
-                    warn!("Failed to save history to file: {}", e);
-                }
             }
         }
 
@@ -1118,8 +1360,7 @@ impl ProcessMonitor {
             );
 
             // Add to history before killing
-            let history_entry = ProcessHistoryEntry::new(&process_info, "bulk".to_string());
-            self.history.add_entry(history_entry);
+            record_kill(&ProcessHistoryEntry::new(&process_info, "bulk".to_string()));
 
             if let Err(e) = self
                 .kill_process_with_context(process_info.pid, "bulk", false)
@@ -1137,14 +1378,6 @@ impl ProcessMonitor {
             ));
         }
 
-        // Save history to file after killing all processes
-        if let Err(e) = self
-            .history
-            .save_to_file(&ProcessHistory::get_history_file_path())
-        {
-            warn!("Failed to save history to file: {}", e);
-        }
-
         info!("All processes killed successfully");
         Ok(())
     }
@@ -1159,25 +1392,6 @@ impl ProcessMonitor {
         }
     }
 
-    pub fn get_history(&self) -> &ProcessHistory {
-        &self.history
-    }
-
-    pub fn get_recent_history(&self, limit: usize) -> &[ProcessHistoryEntry] {
-        self.history.get_recent_entries(limit)
-    }
-
-    pub fn clear_history(&mut self) {
-        self.history.clear();
-        // Save empty history to file
-        if let Err(e) = self
-            .history
-            .save_to_file(&ProcessHistory::get_history_file_path())
-        {
-            warn!("Failed to save cleared history to file: {}", e);
-        }
-    }
-
     pub fn get_filter_stats(&self) -> Option<FilterStats> {
         self.smart_filter
             .as_ref()
@@ -1196,18 +1410,24 @@ impl ProcessMonitor {
 
     /// Restart a process on a specific port
     pub async fn restart_process_on_port(&mut self, port: u16) -> Result<()> {
+        self.restart_process_on_port_with(port, &RestartOverrides::default()).await
+    }
+
+    /// Restart a process on a specific port, applying one-off argument/env overrides
+    pub async fn restart_process_on_port_with(
+        &mut self,
+        port: u16,
+        overrides: &RestartOverrides,
+    ) -> Result<()> {
         info!("Attempting to restart process on port {}", port);
 
         // First, kill any existing process on the port
-        if let Some(process_info) = self.current_processes.get(&port).cloned() {
-            self.kill_process_with_context(process_info.pid, "restart", true).await?;
-            
-            // Wait a moment for the port to be released
-            tokio::time::sleep(Duration::from_millis(1000)).await;
+        if let Some(pid) = self.find_occupant_pid(port).await {
+            self.kill_process_with_context(pid, "restart", true).await?;
         }
 
         // Now restart using saved command
-        match self.restart_manager.restart_port(port) {
+        match self.restart_manager.restart_port_with(port, overrides) {
             Ok(pid) => {
                 info!("Successfully restarted process on port {} with PID {}", port, pid);
                 Ok(())
@@ -1219,6 +1439,248 @@ impl ProcessMonitor {
         }
     }
 
+    /// Restart a port and keep its process alive: whenever it exits, relaunch it
+    /// after an exponential backoff. Runs until port-kill is interrupted.
+    pub async fn keep_alive_port(&mut self, port: u16, overrides: &RestartOverrides) -> Result<()> {
+        if let Some(pid) = self.find_occupant_pid(port).await {
+            self.kill_process_with_context(pid, "restart", true).await?;
+        }
+
+        let mut child = self.restart_manager.spawn_restart(port, overrides)?;
+        let mut backoff = None;
+        loop {
+            let pid = child.id();
+            let started = std::time::Instant::now();
+            info!("Supervising process on port {} (PID {})", port, pid);
+
+            let status = tokio::task::spawn_blocking(move || child.wait())
+                .await
+                .context("Supervisor task failed")?;
+
+            // `docker start` and `docker compose up -d` return once the container runs,
+            // so watch the container itself until it stops
+            let container = self
+                .restart_manager
+                .get_restart_info(port)
+                .and_then(|info| info.container.clone());
+            if let (Some(container), Ok(exit)) = (container, &status) {
+                if exit.success() {
+                    info!("Supervising {} on port {}", container.describe(), port);
+                    loop {
+                        let running = container.clone();
+                        if !tokio::task::spawn_blocking(move || running.is_running())
+                            .await
+                            .unwrap_or(false)
+                        {
+                            break;
+                        }
+                        sleep(CONTAINER_POLL_INTERVAL).await;
+                    }
+                    let delay = next_keep_alive_backoff(backoff, started.elapsed());
+                    warn!(
+                        "{} on port {} stopped; relaunching in {:?}",
+                        container.describe(),
+                        port,
+                        delay
+                    );
+                    child = self.relaunch_until_started(port, overrides, delay).await;
+                    backoff = Some(delay);
+                    continue;
+                }
+            }
+
+            let delay = next_keep_alive_backoff(backoff, started.elapsed());
+            match status {
+                Ok(status) => warn!(
+                    "Process on port {} (PID {}) exited with {}; relaunching in {:?}",
+                    port, pid, status, delay
+                ),
+                Err(e) => warn!(
+                    "Lost track of process on port {} (PID {}): {}; relaunching in {:?}",
+                    port, pid, e, delay
+                ),
+            }
+
+            child = self.relaunch_until_started(port, overrides, delay).await;
+            backoff = Some(delay);
+        }
+    }
+
+    /// Relaunch after `delay`, retrying with growing delays if the relaunch itself fails
+    async fn relaunch_until_started(
+        &mut self,
+        port: u16,
+        overrides: &RestartOverrides,
+        mut delay: Duration,
+    ) -> std::process::Child {
+        loop {
+            sleep(delay).await;
+            match self.restart_manager.spawn_restart(port, overrides) {
+                Ok(child) => return child,
+                Err(e) => {
+                    delay = next_keep_alive_backoff(Some(delay), Duration::ZERO);
+                    error!(
+                        "Failed to relaunch process on port {}: {}; retrying in {:?}",
+                        port, e, delay
+                    );
+                }
+            }
+        }
+    }
+
+    /// Restart a port, then kill and relaunch its saved command whenever files under
+    /// `paths` change. Runs until port-kill is interrupted.
+    pub async fn watch_and_restart_port(
+        &mut self,
+        port: u16,
+        overrides: &RestartOverrides,
+        paths: &[std::path::PathBuf],
+    ) -> Result<()> {
+        use ::notify::{RecursiveMode, Watcher};
+
+        let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+        let mut watcher = ::notify::recommended_watcher(
+            move |res: ::notify::Result<::notify::Event>| {
+                if let Ok(event) = res {
+                    if !event.kind.is_access() {
+                        let _ = tx.send(event.paths);
+                    }
+                }
+            },
+        )
+        .context("Failed to create file watcher")?;
+        for path in paths {
+            watcher
+                .watch(path, RecursiveMode::Recursive)
+                .with_context(|| format!("Failed to watch {}", path.display()))?;
+        }
+
+        if let Some(pid) = self.find_occupant_pid(port).await {
+            self.kill_process_with_context(pid, "restart", true).await?;
+        }
+        let mut child = self.restart_manager.spawn_restart(port, overrides)?;
+
+        // Events may name paths as given or resolved, so strip either form of the root
+        let roots: Vec<std::path::PathBuf> = paths
+            .iter()
+            .flat_map(|path| [path.clone(), path.canonicalize().unwrap_or_else(|_| path.clone())])
+            .collect();
+        let ignored = |path: &std::path::PathBuf| {
+            is_ignored_watch_path(watch_relative_path(path, &roots))
+        };
+
+        while let Some(changed) = rx.recv().await {
+            let mut relevant: Vec<_> = changed.into_iter().filter(|p| !ignored(p)).collect();
+            if relevant.is_empty() {
+                continue;
+            }
+            // Let bursts of saves settle before restarting
+            while let Ok(Some(more)) =
+                tokio::time::timeout(WATCH_DEBOUNCE, rx.recv()).await
+            {
+                relevant.extend(more.into_iter().filter(|p| !ignored(p)));
+            }
+
+            info!(
+                "{} change(s) detected (e.g. {}), restarting port {}",
+                relevant.len(),
+                relevant[0].display(),
+                port
+            );
+            let _ = child.kill();
+            let _ = child.wait();
+            // The saved command may have started the real server as a grandchild
+            if let Some(pid) = self.find_occupant_pid(port).await {
+                let _ = self.kill_process_with_context(pid, "restart", true).await;
+            }
+
+            match self.restart_manager.spawn_restart(port, overrides) {
+                Ok(new_child) => child = new_child,
+                Err(e) => {
+                    error!("Failed to restart process on port {}: {}", port, e);
+                    return Err(e);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Restart every port in the restart history that has a saved command.
+    /// Current occupants are killed first, then all saved commands are launched
+    /// together without waiting on each other.
+    pub async fn restart_all_ports(&mut self) -> Vec<RestartOutcome> {
+        let ports = self.restart_manager.list_launchable_ports();
+        let mut outcomes = Vec::new();
+        let mut to_launch = Vec::new();
+
+        for port in ports.iter().copied() {
+            match self.find_occupant_pid(port).await {
+                Some(pid) => match self.kill_process_with_context(pid, "restart", true).await {
+                    Ok(()) => to_launch.push(port),
+                    Err(e) => outcomes.push(RestartOutcome {
+                        port,
+                        pid: None,
+                        error: Some(format!("Failed to stop current occupant (PID {}): {}", pid, e)),
+                    }),
+                },
+                None => to_launch.push(port),
+            }
+        }
+
+        // Each launch can wait up to --restart-timeout for its port, so they run at once
+        let mut launches = tokio::task::JoinSet::new();
+        for port in to_launch {
+            match self.restart_manager.launcher(port, &RestartOverrides::default()) {
+                Ok(launcher) => {
+                    launches.spawn_blocking(move || (port, launcher.run_detached()));
+                }
+                Err(e) => outcomes.push(RestartOutcome { port, pid: None, error: Some(e.to_string()) }),
+            }
+        }
+        while let Some(joined) = launches.join_next().await {
+            let (port, launched) = match joined {
+                Ok(joined) => joined,
+                Err(e) => {
+                    // A panicked launch loses its port; it shows up as missing below
+                    error!("Restart task failed: {}", e);
+                    continue;
+                }
+            };
+            match launched {
+                Ok((pid, post_hook)) => {
+                    info!("Restarted process on port {} with PID {}", port, pid);
+                    self.restart_manager.finish_launch(port, post_hook);
+                    outcomes.push(RestartOutcome { port, pid: Some(pid), error: None });
+                }
+                Err(e) => {
+                    error!("Failed to restart process on port {}: {}", port, e);
+                    outcomes.push(RestartOutcome { port, pid: None, error: Some(e.to_string()) });
+                }
+            }
+        }
+
+        for port in ports {
+            if !outcomes.iter().any(|outcome| outcome.port == port) {
+                outcomes.push(RestartOutcome {
+                    port,
+                    pid: None,
+                    error: Some("Restart task failed".to_string()),
+                });
+            }
+        }
+
+        outcomes.sort_by_key(|o| o.port);
+        outcomes
+    }
+
+    /// PID currently listening on a port, from the last scan or a fresh lookup
+    async fn find_occupant_pid(&self, port: u16) -> Option<i32> {
+        if let Some(process_info) = self.current_processes.get(&port) {
+            return Some(process_info.pid);
+        }
+        self.get_process_on_port(port).await.ok().map(|p| p.pid)
+    }
+
     /// Get restart manager reference
     pub fn get_restart_manager(&self) -> &RestartManager {
         &self.restart_manager
@@ -1290,6 +1752,29 @@ impl ProcessMonitor {
     }
 }
 
+/// Warn about and notify the anomalies found since the last check
+pub fn notify_anomalies(notifier: &mut AnomalyNotifier, activity: &PortActivityLog) {
+    match load_anomalies(activity.events()) {
+        Ok(current) => {
+            for anomaly in notifier.new_anomalies(&current) {
+                warn!("Anomaly: {}", anomaly.description);
+                crate::notify::send_desktop_notification(
+                    "Port Kill: unusual activity",
+                    &anomaly.description,
+                );
+            }
+        }
+        Err(e) => log::debug!("Anomaly check failed: {}", e),
+    }
+}
+
+/// Add a kill to the history database; a failure only costs the history entry
+pub fn record_kill(entry: &ProcessHistoryEntry) {
+    if let Err(e) = HistoryStore::open_default().and_then(|store| store.record_kill(entry)) {
+        warn!("Failed to save kill to history: {}", e);
+    }
+}
+
 // Platform-agnostic process management functions
 pub fn get_processes_on_ports(
     ports: &[u16],
@@ -1297,6 +1782,132 @@ pub fn get_processes_on_ports(
 ) -> (
     usize,
     std::collections::HashMap<u16, crate::types::ProcessInfo>,
+) {
+    let (_, mut processes) = find_processes_on_ports(ports, args);
+    if args.udp {
+        // A TCP listener wins when the same port is also bound over UDP
+        for process_info in get_udp_processes_on_ports(ports, args) {
+            processes.entry(process_info.port).or_insert(process_info);
+        }
+    }
+    if !args.bind_addr.is_empty() {
+        processes.retain(|_, process_info| args.bind_address_allowed(process_info.bind_address));
+    }
+    if args.friendly_names {
+        apply_friendly_names(&mut processes);
+    }
+    (processes.len(), processes)
+}
+
+/// Rename generic runtimes (`node`, `python`, ...) after the project they run, e.g.
+/// `node (webshop-frontend)`, for `--friendly-names`. Runs after filtering, so
+/// `--ignore-processes` still matches the real process names.
+pub fn apply_friendly_names(processes: &mut HashMap<u16, ProcessInfo>) {
+    for process_info in processes.values_mut() {
+        if process_info.working_directory.is_none() {
+            process_info.working_directory = process_working_directory(process_info.pid);
+        }
+        if process_info.project_name.is_none() {
+            process_info.project_name = process_info.extract_project_name();
+        }
+        if let Some(name) = process_info.friendly_name() {
+            process_info.name = name;
+        }
+    }
+}
+
+/// Current directory of a process, which tells which project it belongs to
+fn process_working_directory(pid: i32) -> Option<String> {
+    #[cfg(target_os = "linux")]
+    {
+        std::fs::read_link(format!("/proc/{}/cwd", pid))
+            .ok()
+            .map(|dir| dir.to_string_lossy().to_string())
+            .filter(|dir| dir != "/")
+    }
+
+    #[cfg(all(unix, not(target_os = "linux")))]
+    {
+        let output = Command::new("lsof")
+            .args(["-a", "-p", &pid.to_string(), "-d", "cwd", "-F", "n"])
+            .output()
+            .ok()?;
+        String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .find_map(|line| line.strip_prefix('n'))
+            .filter(|dir| !dir.is_empty() && *dir != "/")
+            .map(String::from)
+    }
+
+    #[cfg(target_os = "windows")]
+    {
+        let _ = pid;
+        None
+    }
+}
+
+/// Wait up to `timeout` for `pid` or one of its child processes (`npm run dev`
+/// starting `node`) to listen on a TCP port, and return the port
+pub fn wait_for_listening_port(pid: u32, timeout: Duration) -> Option<u16> {
+    let deadline = std::time::Instant::now() + timeout;
+    loop {
+        if let Some(&port) = listening_ports_of(pid).first() {
+            return Some(port);
+        }
+        if std::time::Instant::now() >= deadline {
+            return None;
+        }
+        std::thread::sleep(Duration::from_millis(500));
+    }
+}
+
+/// TCP ports `pid` and its descendants listen on, lowest first
+fn listening_ports_of(pid: u32) -> Vec<u16> {
+    let mut system = sysinfo::System::new();
+    system.refresh_processes();
+    let mut pids = vec![sysinfo::Pid::from_u32(pid)];
+    // Parents come before their children only by chance, so repeat until nothing is added
+    loop {
+        let children: Vec<sysinfo::Pid> = system
+            .processes()
+            .iter()
+            .filter(|(child, process)| {
+                !pids.contains(child) && process.parent().is_some_and(|p| pids.contains(&p))
+            })
+            .map(|(child, _)| *child)
+            .collect();
+        if children.is_empty() {
+            break;
+        }
+        pids.extend(children);
+    }
+
+    let pid_list = pids
+        .iter()
+        .map(|pid| pid.to_string())
+        .collect::<Vec<_>>()
+        .join(",");
+    let Ok(output) = Command::new("lsof")
+        .args(["-a", "-p", &pid_list, "-iTCP", "-sTCP:LISTEN", "-P", "-n", "-F", "n"])
+        .output()
+    else {
+        return Vec::new();
+    };
+    let mut ports: Vec<u16> = String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .filter_map(|line| line.strip_prefix('n')?.rsplit(':').next()?.parse().ok())
+        .collect();
+    ports.sort_unstable();
+    ports.dedup();
+    ports
+}
+
+fn find_processes_on_ports(
+    ports: &[u16],
+    args: &crate::cli::Args,
+) -> (
+    usize,
+    std::collections::HashMap<u16, crate::types::ProcessInfo>,
 ) {
     // If verbose mode is enabled, use ProcessMonitor to get detailed information
     if args.verbose {
@@ -1445,13 +2056,21 @@ pub fn get_processes_on_ports_multi(
         return HashMap::new();
     }
     #[cfg(target_os = "windows")]
-    {
-        get_processes_on_ports_windows_multi(ports, args)
-    }
+    let mut processes = get_processes_on_ports_windows_multi(ports, args);
     #[cfg(not(target_os = "windows"))]
-    {
-        get_processes_on_ports_unix_multi(ports, args)
+    let mut processes = get_processes_on_ports_unix_multi(ports, args);
+    if args.udp {
+        for process_info in get_udp_processes_on_ports(ports, args) {
+            processes.entry(process_info.port).or_default().push(process_info);
+        }
     }
+    if !args.bind_addr.is_empty() {
+        for on_port in processes.values_mut() {
+            on_port.retain(|process_info| args.bind_address_allowed(process_info.bind_address));
+        }
+        processes.retain(|_, on_port| !on_port.is_empty());
+    }
+    processes
 }
 
 #[cfg(not(target_os = "windows"))]
@@ -1523,7 +2142,7 @@ fn get_processes_on_ports_windows_multi(
     let ignore_processes = args.get_ignore_processes_set();
 
     if let Ok(output) = std::process::Command::new("netstat")
-        .args(&["-ano", "-p", "TCP"])
+        .args(&["-ano"])
         .output()
     {
         if output.status.success() {
@@ -1553,9 +2172,10 @@ fn get_processes_on_ports_windows(
     let ignore_ports = args.get_ignore_ports_set();
     let ignore_processes = args.get_ignore_processes_set();
 
-    // On Windows, use netstat to find all listening TCP ports
+    // On Windows, use netstat to find all listening TCP ports. `-p TCP` would only
+    // list IPv4 sockets, so take every protocol and keep the LISTENING lines.
     let output = std::process::Command::new("netstat")
-        .args(&["-ano", "-p", "TCP"])
+        .args(&["-ano"])
         .output();
 
     match output {
@@ -1599,14 +2219,8 @@ fn parse_netstat_output(
             continue;
         }
 
-        // Extract port from local address (e.g., "0.0.0.0:8080" or "[::]:8080")
-        let local_addr = parts[1];
-        let port = if let Some(port_str) = local_addr.split(':').last() {
-            match port_str.parse::<u16>() {
-                Ok(p) => p,
-                Err(_) => continue,
-            }
-        } else {
+        // Local address, e.g. "0.0.0.0:8080" or "[::]:8080"
+        let Some((bind_address, port)) = parse_local_address(parts[1], AddressFamily::Ipv4) else {
             continue;
         };
 
@@ -1663,6 +2277,9 @@ fn parse_netstat_output(
             cpu_usage: None,
             memory_usage: None,
             memory_percentage: None,
+            protocol: crate::types::Protocol::Tcp,
+            bind_address,
+            address_family: bind_address.as_ref().map(AddressFamily::of),
         };
 
         process_info.process_group = process_info.determine_process_group();
@@ -1697,6 +2314,35 @@ fn get_process_name_windows(pid: i32) -> Option<String> {
     None
 }
 
+/// Splits a local socket address as lsof, ss and netstat print it (`127.0.0.1:3000`,
+/// `[::1]:3000`, `[fe80::1%en0]:3000`, `*:3000`) into bind address and port. The `*`
+/// wildcard is the unspecified address of `family`.
+pub(crate) fn parse_local_address(
+    address: &str,
+    family: AddressFamily,
+) -> Option<(Option<IpAddr>, u16)> {
+    let (host, port) = address.rsplit_once(':')?;
+    let port = port.parse().ok()?;
+    let host = host.trim_start_matches('[').trim_end_matches(']');
+    // Drop the zone of link-local addresses
+    let host = host.split('%').next().unwrap_or(host);
+    let bind_address = match (host, family) {
+        ("*", AddressFamily::Ipv4) => Some(IpAddr::V4(Ipv4Addr::UNSPECIFIED)),
+        ("*", AddressFamily::Ipv6) => Some(IpAddr::V6(Ipv6Addr::UNSPECIFIED)),
+        (host, _) => host.parse().ok(),
+    };
+    Some((bind_address, port))
+}
+
+/// The family in lsof's TYPE column
+fn lsof_family(socket_type: &str) -> AddressFamily {
+    if socket_type == "IPv6" {
+        AddressFamily::Ipv6
+    } else {
+        AddressFamily::Ipv4
+    }
+}
+
 fn parse_lsof_output(
     stdout: &str,
     ports_filter: &HashSet<u16>,
@@ -1715,10 +2361,8 @@ fn parse_lsof_output(
             Err(_) => continue,
         };
 
-        let port_str = parts[8].split(':').last().unwrap_or("0");
-        let port = match port_str.parse::<u16>() {
-            Ok(port) => port,
-            Err(_) => continue,
+        let Some((bind_address, port)) = parse_local_address(parts[8], lsof_family(parts[4])) else {
+            continue;
         };
 
         if !ports_filter.is_empty() && !ports_filter.contains(&port) {
@@ -1765,6 +2409,9 @@ fn parse_lsof_output(
             cpu_usage: None,
             memory_usage: None,
             memory_percentage: None,
+            protocol: crate::types::Protocol::Tcp,
+            bind_address,
+            address_family: bind_address.as_ref().map(AddressFamily::of),
         };
 
         process_info.process_group = process_info.determine_process_group();
@@ -1792,10 +2439,8 @@ pub(crate) fn parse_lsof_output_multi(
             Ok(pid) => pid,
             Err(_) => continue,
         };
-        let port_str = parts[8].split(':').last().unwrap_or("0");
-        let port = match port_str.parse::<u16>() {
-            Ok(port) => port,
-            Err(_) => continue,
+        let Some((bind_address, port)) = parse_local_address(parts[8], lsof_family(parts[4])) else {
+            continue;
         };
         if !ports_filter.is_empty() && !ports_filter.contains(&port) {
             continue;
@@ -1820,6 +2465,9 @@ pub(crate) fn parse_lsof_output_multi(
             cpu_usage: None,
             memory_usage: None,
             memory_percentage: None,
+            protocol: crate::types::Protocol::Tcp,
+            bind_address,
+            address_family: bind_address.as_ref().map(AddressFamily::of),
         };
         process_info.process_group = process_info.determine_process_group();
         process_info.project_name = process_info.extract_project_name();
@@ -1843,13 +2491,7 @@ fn parse_netstat_output_multi(
         if parts.len() < 5 {
             continue;
         }
-        let local_addr = parts[1];
-        let port = if let Some(port_str) = local_addr.split(':').last() {
-            match port_str.parse::<u16>() {
-                Ok(p) => p,
-                Err(_) => continue,
-            }
-        } else {
+        let Some((bind_address, port)) = parse_local_address(parts[1], AddressFamily::Ipv4) else {
             continue;
         };
         if !ports_filter.is_empty() && !ports_filter.contains(&port) {
@@ -1880,6 +2522,9 @@ fn parse_netstat_output_multi(
             cpu_usage: None,
             memory_usage: None,
             memory_percentage: None,
+            protocol: crate::types::Protocol::Tcp,
+            bind_address,
+            address_family: bind_address.as_ref().map(AddressFamily::of),
         };
         process_info.process_group = process_info.determine_process_group();
         process_info.project_name = process_info.extract_project_name();
@@ -1887,6 +2532,180 @@ fn parse_netstat_output_multi(
     }
 }
 
+/// Processes bound to UDP sockets on `ports` (`--udp`), after the ignore lists. UDP has no
+/// listening state, so this is every socket bound to one of the ports without a peer.
+pub fn get_udp_processes_on_ports(ports: &[u16], args: &crate::cli::Args) -> Vec<ProcessInfo> {
+    if ports.is_empty() {
+        return Vec::new();
+    }
+    let ports_filter: HashSet<u16> = ports.iter().copied().collect();
+    let ignore_ports = args.get_ignore_ports_set();
+    let ignore_processes = args.get_ignore_processes_set();
+
+    let mut seen = HashSet::new();
+    let mut processes = Vec::new();
+    for UdpSocket {
+        name,
+        pid,
+        port,
+        bind_address,
+    } in udp_sockets()
+    {
+        if !ports_filter.contains(&port) || ignore_ports.contains(&port) {
+            continue;
+        }
+        if ignore_processes.contains(&name) {
+            log::info!(
+                "Ignoring process {} (PID {}) on UDP port {} (ignored process by user configuration)",
+                name,
+                pid,
+                port
+            );
+            continue;
+        }
+        if !args.bind_address_allowed(bind_address) {
+            continue;
+        }
+        // IPv4 and IPv6 sockets of one process show up separately
+        if !seen.insert((pid, port)) {
+            continue;
+        }
+        let mut process_info = ProcessInfo {
+            pid,
+            port,
+            command: name.clone(),
+            name,
+            container_id: None,
+            container_name: None,
+            command_line: None,
+            working_directory: None,
+            process_group: None,
+            project_name: None,
+            cpu_usage: None,
+            memory_usage: None,
+            memory_percentage: None,
+            protocol: Protocol::Udp,
+            bind_address,
+            address_family: bind_address.as_ref().map(AddressFamily::of),
+        };
+        process_info.process_group = process_info.determine_process_group();
+        process_info.project_name = process_info.extract_project_name();
+        processes.push(process_info);
+    }
+    processes
+}
+
+#[derive(Debug, PartialEq)]
+struct UdpSocket {
+    name: String,
+    pid: i32,
+    port: u16,
+    bind_address: Option<IpAddr>,
+}
+
+/// All bound UDP sockets
+#[cfg(not(target_os = "windows"))]
+fn udp_sockets() -> Vec<UdpSocket> {
+    match std::process::Command::new("lsof").args(["-P", "-n", "-iUDP"]).output() {
+        Ok(output) => return parse_lsof_udp_output(&String::from_utf8_lossy(&output.stdout)),
+        Err(e) => log::debug!("Failed to run lsof for UDP ports: {}", e),
+    }
+    // Minimal Linux installs often ship ss but not lsof
+    #[cfg(target_os = "linux")]
+    if let Ok(output) = std::process::Command::new("ss").args(["-ulpnH"]).output() {
+        return parse_ss_udp_output(&String::from_utf8_lossy(&output.stdout));
+    }
+    Vec::new()
+}
+
+#[cfg(target_os = "windows")]
+fn udp_sockets() -> Vec<UdpSocket> {
+    // Without `-p`, so IPv6 sockets are listed too
+    let output = match std::process::Command::new("netstat")
+        .args(&["-ano"])
+        .output()
+    {
+        Ok(output) => output,
+        Err(e) => {
+            log::warn!("Failed to run netstat for UDP ports: {}", e);
+            return Vec::new();
+        }
+    };
+    // netstat output format: Proto  Local Address  Foreign Address  PID
+    // Example: UDP    0.0.0.0:5353     *:*     1234
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .filter_map(|line| {
+            let parts: Vec<&str> = line.split_whitespace().collect();
+            if parts.len() < 4 || parts[0] != "UDP" {
+                return None;
+            }
+            let (bind_address, port) = parse_local_address(parts[1], AddressFamily::Ipv4)?;
+            let pid = parts[3].parse().ok()?;
+            let name = get_process_name_windows(pid).unwrap_or_else(|| "Unknown".to_string());
+            Some(UdpSocket {
+                name,
+                pid,
+                port,
+                bind_address,
+            })
+        })
+        .collect()
+}
+
+/// Parses `lsof -P -n -iUDP`, skipping connected sockets (`local->remote`)
+#[cfg(not(target_os = "windows"))]
+fn parse_lsof_udp_output(stdout: &str) -> Vec<UdpSocket> {
+    stdout
+        .lines()
+        .skip(1)
+        .filter_map(|line| {
+            let parts: Vec<&str> = line.split_whitespace().collect();
+            if parts.len() < 9 || parts[8].contains("->") {
+                return None;
+            }
+            let (bind_address, port) = parse_local_address(parts[8], lsof_family(parts[4]))?;
+            Some(UdpSocket {
+                name: parts[0].to_string(),
+                pid: parts[1].parse().ok()?,
+                port,
+                bind_address,
+            })
+        })
+        .collect()
+}
+
+/// Parses `ss -ulpnH`, e.g. `UNCONN 0 0 0.0.0.0:5353 0.0.0.0:* users:(("avahi-daemon",pid=612,fd=12))`.
+/// A socket shared by several processes lists each of them, and `*` is a dual-stack socket.
+#[cfg(not(target_os = "windows"))]
+fn parse_ss_udp_output(stdout: &str) -> Vec<UdpSocket> {
+    let mut sockets = Vec::new();
+    for line in stdout.lines() {
+        let parts: Vec<&str> = line.split_whitespace().collect();
+        if parts.len() < 6 {
+            continue;
+        }
+        let Some((bind_address, port)) = parse_local_address(parts[3], AddressFamily::Ipv6) else {
+            continue;
+        };
+        let users = parts[5..].join(" ");
+        for user in users.split("(\"").skip(1) {
+            let Some((name, rest)) = user.split_once("\",pid=") else {
+                continue;
+            };
+            if let Some(pid) = rest.split(',').next().and_then(|p| p.parse().ok()) {
+                sockets.push(UdpSocket {
+                    name: name.to_string(),
+                    pid,
+                    port,
+                    bind_address,
+                });
+            }
+        }
+    }
+    sockets
+}
+
 #[cfg(target_os = "windows")]
 pub fn kill_all_processes(ports: &[u16], args: &crate::cli::Args) -> anyhow::Result<()> {
     use std::collections::HashSet;
@@ -1912,7 +2731,7 @@ pub fn kill_all_processes(ports: &[u16], args: &crate::cli::Args) -> anyhow::Res
     }
 
     let output = match std::process::Command::new("netstat")
-        .args(&["-ano", "-p", "TCP"])
+        .args(&["-ano"])
         .output()
     {
         Ok(output) => output,
@@ -1933,12 +2752,10 @@ pub fn kill_all_processes(ports: &[u16], args: &crate::cli::Args) -> anyhow::Res
         if parts.len() < 5 {
             continue;
         }
-        let local_addr = parts[1];
-        let port = match local_addr.split(':').last().and_then(|s| s.parse::<u16>().ok()) {
-            Some(p) => p,
-            None => continue,
+        let Some((bind_address, port)) = parse_local_address(parts[1], AddressFamily::Ipv4) else {
+            continue;
         };
-        if !ports_filter.contains(&port) {
+        if !ports_filter.contains(&port) || !args.bind_address_allowed(bind_address) {
             continue;
         }
         let pid = match parts[4].parse::<i32>() {
@@ -1958,6 +2775,14 @@ pub fn kill_all_processes(ports: &[u16], args: &crate::cli::Args) -> anyhow::Res
         }
     }
 
+    if args.udp {
+        for process_info in get_udp_processes_on_ports(ports, args) {
+            if !pids_to_kill.contains(&process_info.pid) {
+                pids_to_kill.push(process_info.pid);
+            }
+        }
+    }
+
     if pids_to_kill.is_empty() {
         log::info!("No processes found to kill on the specified ports");
         return Ok(());
@@ -2019,15 +2844,16 @@ pub fn kill_all_processes(ports: &[u16], args: &crate::cli::Args) -> anyhow::Res
     for line in lines {
         let parts: Vec<&str> = line.split_whitespace().collect();
         if parts.len() >= 9 {
-            if let (Ok(pid), Ok(port)) = (
+            if let (Ok(pid), Some((bind_address, port))) = (
                 parts[1].parse::<i32>(),
-                parts[8].split(':').last().unwrap_or("0").parse::<u16>(),
+                parse_local_address(parts[8], lsof_family(parts[4])),
             ) {
                 let name = parts[0].to_string();
 
                 // Check if this process should be ignored
-                let should_ignore =
-                    ignore_ports.contains(&port) || ignore_processes.contains(&name);
+                let should_ignore = ignore_ports.contains(&port)
+                    || ignore_processes.contains(&name)
+                    || !args.bind_address_allowed(bind_address);
 
                 if !should_ignore && !pids_to_kill.contains(&pid) {
                     pids_to_kill.push(pid);
@@ -2038,6 +2864,14 @@ pub fn kill_all_processes(ports: &[u16], args: &crate::cli::Args) -> anyhow::Res
         }
     }
 
+    if args.udp {
+        for process_info in get_udp_processes_on_ports(ports, args) {
+            if !pids_to_kill.contains(&process_info.pid) {
+                pids_to_kill.push(process_info.pid);
+            }
+        }
+    }
+
     if pids_to_kill.is_empty() {
         log::info!("No processes found to kill (all were ignored or none found)");
         return Ok(());
@@ -2270,4 +3104,64 @@ python   5678 user   23u  IPv6 0x5678      0t0  TCP *:3000 (LISTEN)";
         assert!(names.contains(&"node"));
         assert!(names.contains(&"python"));
     }
+
+    #[test]
+    fn test_parse_local_address() {
+        let parse = |address, family| {
+            parse_local_address(address, family).map(|(ip, port)| (ip.map(|ip| ip.to_string()), port))
+        };
+        let v4 = AddressFamily::Ipv4;
+        let v6 = AddressFamily::Ipv6;
+        assert_eq!(parse("127.0.0.1:3000", v4), Some((Some("127.0.0.1".into()), 3000)));
+        assert_eq!(parse("[::1]:3000", v6), Some((Some("::1".into()), 3000)));
+        assert_eq!(parse("[fe80::1%lo0]:3000", v6), Some((Some("fe80::1".into()), 3000)));
+        assert_eq!(parse("*:3000", v4), Some((Some("0.0.0.0".into()), 3000)));
+        assert_eq!(parse("*:3000", v6), Some((Some("::".into()), 3000)));
+        assert_eq!(parse("localhost:3000", v4), Some((None, 3000)));
+        assert_eq!(parse("*:*", v4), None);
+    }
+
+    #[test]
+    fn test_parse_udp_sockets() {
+        let sockets = |sockets: Vec<UdpSocket>| {
+            sockets
+                .into_iter()
+                .map(|socket| (socket.name, socket.pid, socket.port, socket.bind_address.unwrap().to_string()))
+                .collect::<Vec<_>>()
+        };
+        let lsof = "COMMAND     PID USER   FD   TYPE DEVICE SIZE/OFF NODE NAME
+mDNSRespo   612 root    8u  IPv4 0x1234      0t0  UDP *:5353
+mDNSRespo   612 root    9u  IPv6 0x5678      0t0  UDP *:5353
+curl       4242 user    5u  IPv4 0x9abc      0t0  UDP 10.0.0.2:51234->1.1.1.1:53";
+        assert_eq!(
+            sockets(parse_lsof_udp_output(lsof)),
+            vec![
+                ("mDNSRespo".to_string(), 612, 5353, "0.0.0.0".to_string()),
+                ("mDNSRespo".to_string(), 612, 5353, "::".to_string()),
+            ]
+        );
+
+        let ss = "UNCONN 0 0 0.0.0.0:5353 0.0.0.0:* users:((\"avahi-daemon\",pid=612,fd=12))
+UNCONN 0 0 [::1]:27015 [::]:* users:((\"srcds\",pid=70,fd=3),(\"srcds\",pid=71,fd=3))
+UNCONN 0 0 127.0.0.53%lo:53 0.0.0.0:*";
+        assert_eq!(
+            sockets(parse_ss_udp_output(ss)),
+            vec![
+                ("avahi-daemon".to_string(), 612, 5353, "0.0.0.0".to_string()),
+                ("srcds".to_string(), 70, 27015, "::1".to_string()),
+                ("srcds".to_string(), 71, 27015, "::1".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_listening_ports_of_own_process() {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        // Needs lsof, like the rest of the scanning
+        if Command::new("lsof").arg("-v").output().is_err() {
+            return;
+        }
+        assert!(listening_ports_of(std::process::id()).contains(&port));
+    }
 }
@@ -0,0 +1,78 @@
+use std::path::{Path, PathBuf};
+
+/// Directories searched for project files (`.port-kill.yaml`, project presets, audit
+/// baselines): `start` and its parents up to the enclosing git root, the way git
+/// looks for `.gitignore`. Outside a repository only `start` itself is searched.
+pub fn search_dirs(start: &Path) -> Vec<PathBuf> {
+    let mut dirs = Vec::new();
+    for dir in start.ancestors() {
+        dirs.push(dir.to_path_buf());
+        // `.git` is a file in worktrees and submodules
+        if dir.join(".git").exists() {
+            return dirs;
+        }
+    }
+    dirs.truncate(1);
+    dirs
+}
+
+/// Nearest existing file among `names` (relative paths), checking each directory
+/// from `start` upward before moving on to its parent
+pub fn find_upward<S: AsRef<Path>>(start: &Path, names: &[S]) -> Option<PathBuf> {
+    search_dirs(start).into_iter().find_map(|dir| {
+        names
+            .iter()
+            .map(|name| dir.join(name))
+            .find(|path| path.is_file())
+    })
+}
+
+/// Resolve a relative path given on the command line: use it as is when it exists
+/// here, otherwise look for it in the parent directories up to the git root
+pub fn resolve_upward(path: &str) -> PathBuf {
+    let given = PathBuf::from(path);
+    if given.is_absolute() || given.exists() {
+        return given;
+    }
+    std::env::current_dir()
+        .ok()
+        .and_then(|cwd| find_upward(&cwd, &[&given]))
+        .unwrap_or(given)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn test_search_stops_at_git_root() {
+        let base = std::env::temp_dir().join(format!(
+            "port-kill-project-dir-{}",
+            std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .unwrap()
+                .as_nanos()
+        ));
+        let repo = base.join("repo");
+        let package = repo.join("packages/web");
+        fs::create_dir_all(&package).unwrap();
+        fs::create_dir_all(repo.join(".git")).unwrap();
+        fs::write(base.join(".port-kill.yaml"), "services: {}\n").unwrap();
+        fs::write(repo.join(".port-kill.yaml"), "services: {}\n").unwrap();
+
+        assert_eq!(search_dirs(&package).last(), Some(&repo));
+        assert_eq!(
+            find_upward(&package, &[".port-kill.yaml"]),
+            Some(repo.join(".port-kill.yaml"))
+        );
+
+        // Outside a repository nothing above the starting directory is used
+        let loose = base.join("loose");
+        fs::create_dir_all(&loose).unwrap();
+        assert_eq!(search_dirs(&loose), vec![loose.clone()]);
+        assert_eq!(find_upward(&loose, &[".port-kill.yaml"]), None);
+
+        let _ = fs::remove_dir_all(&base);
+    }
+}
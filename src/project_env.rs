@@ -0,0 +1,162 @@
+use crate::project_dir::find_upward;
+use anyhow::{Context, Result};
+use std::collections::BTreeMap;
+use std::io::ErrorKind;
+use std::path::Path;
+use std::process::Command;
+
+/// Files that make mise set up an environment for a directory
+const MISE_FILES: &[&str] = &[".mise.toml", "mise.toml", ".mise.local.toml", ".tool-versions"];
+
+/// Environment changes a project's direnv and mise setup would make in the developer's
+/// shell. `None` values are variables the project unsets.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ProjectEnv {
+    pub vars: BTreeMap<String, Option<String>>,
+    /// Tools whose environment was loaded, for messages ("direnv", "mise")
+    pub sources: Vec<&'static str>,
+}
+
+impl ProjectEnv {
+    /// Evaluate the mise and direnv environments for `dir`. Each tool only runs when
+    /// its files exist in `dir` or a parent up to the git root; a tool that is not
+    /// installed is skipped with a warning. direnv runs last, so it wins when both
+    /// set the same variable, as with the usual shell hook order.
+    pub fn load(dir: &Path) -> Result<Self> {
+        let mut env = Self::default();
+
+        if find_upward(dir, MISE_FILES).is_some() {
+            if let Some(output) = run_tool(dir, "mise", &["env", "--json"])? {
+                env.merge("mise", parse_env_json(&output).context("Unexpected output from mise env")?);
+            }
+        }
+
+        if let Some(envrc) = find_upward(dir, &[".envrc"]) {
+            match run_tool(dir, "direnv", &["export", "json"]) {
+                Ok(Some(output)) => env.merge(
+                    "direnv",
+                    parse_env_json(&output).context("Unexpected output from direnv export")?,
+                ),
+                Ok(None) => {}
+                // Usually an .envrc that was never allowed
+                Err(e) => {
+                    return Err(e.context(format!(
+                        "Could not load {} (run `direnv allow` in {} first)",
+                        envrc.display(),
+                        envrc.parent().unwrap_or(dir).display()
+                    )))
+                }
+            }
+        }
+
+        Ok(env)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.vars.is_empty()
+    }
+
+    /// Set and unset the project's variables on `cmd`
+    pub fn apply(&self, cmd: &mut Command) {
+        for (key, value) in &self.vars {
+            match value {
+                Some(value) => cmd.env(key, value),
+                None => cmd.env_remove(key),
+            };
+        }
+    }
+
+    fn merge(&mut self, source: &'static str, vars: BTreeMap<String, Option<String>>) {
+        if vars.is_empty() {
+            return;
+        }
+        self.vars.extend(vars);
+        self.sources.push(source);
+    }
+}
+
+/// Run `program` in `dir` and return its stdout, or `None` when it is not installed
+fn run_tool(dir: &Path, program: &str, args: &[&str]) -> Result<Option<String>> {
+    let output = match Command::new(program).args(args).current_dir(dir).output() {
+        Ok(output) => output,
+        Err(e) if e.kind() == ErrorKind::NotFound => {
+            log::warn!(
+                "{} is not installed; starting without the environment it would load",
+                program
+            );
+            return Ok(None);
+        }
+        Err(e) => return Err(e).context(format!("Failed to run {}", program)),
+    };
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(anyhow::anyhow!(
+            "{} {} failed: {}",
+            program,
+            args.join(" "),
+            stderr.trim()
+        ));
+    }
+
+    Ok(Some(String::from_utf8_lossy(&output.stdout).to_string()))
+}
+
+/// Parse the `{"KEY": "value", "GONE": null}` object both `direnv export json` and
+/// `mise env --json` print. direnv prints nothing when the environment is unchanged.
+fn parse_env_json(output: &str) -> Result<BTreeMap<String, Option<String>>> {
+    if output.trim().is_empty() {
+        return Ok(BTreeMap::new());
+    }
+    let vars: BTreeMap<String, Option<String>> = serde_json::from_str(output)?;
+    // direnv's bookkeeping variables only make sense to a shell hook
+    Ok(vars
+        .into_iter()
+        .filter(|(key, _)| !key.starts_with("DIRENV_"))
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_env_json() {
+        let vars = parse_env_json(
+            r#"{"DATABASE_URL": "postgres://localhost/dev", "OLD": null, "DIRENV_DIFF": "abc"}"#,
+        )
+        .unwrap();
+        assert_eq!(
+            vars.get("DATABASE_URL"),
+            Some(&Some("postgres://localhost/dev".to_string()))
+        );
+        assert_eq!(vars.get("OLD"), Some(&None));
+        assert!(!vars.contains_key("DIRENV_DIFF"));
+
+        assert!(parse_env_json("\n").unwrap().is_empty());
+        assert!(parse_env_json("not json").is_err());
+    }
+
+    #[test]
+    fn test_load_without_project_files_is_empty() {
+        let dir = std::env::temp_dir().join(format!("port-kill-project-env-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+
+        let env = ProjectEnv::load(&dir).unwrap();
+        assert!(env.is_empty());
+        assert!(env.sources.is_empty());
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_later_source_wins() {
+        let mut env = ProjectEnv::default();
+        env.merge("mise", BTreeMap::from([("PORT".to_string(), Some("3000".to_string()))]));
+        env.merge("direnv", BTreeMap::from([("PORT".to_string(), Some("4000".to_string()))]));
+        env.merge("direnv", BTreeMap::new());
+
+        assert_eq!(env.vars.get("PORT"), Some(&Some("4000".to_string())));
+        assert_eq!(env.sources, vec!["mise", "direnv"]);
+    }
+}
@@ -0,0 +1,222 @@
+//! `port-kill proxy`: a small TCP forwarder so clients still aimed at a busy or
+//! legacy port reach the new instance, without killing whatever holds the port.
+
+use crate::cli::{Args, ProxyArgs};
+use anyhow::{anyhow, Result};
+use std::io::ErrorKind;
+use std::net::{IpAddr, SocketAddr};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::net::{TcpListener, TcpStream};
+use tracing::{debug, info, warn};
+
+/// Connections and bytes forwarded so far, shared by the accept loops
+#[derive(Debug, Default)]
+pub struct ProxyStats {
+    pub connections: AtomicU64,
+    /// Client to target
+    pub bytes_sent: AtomicU64,
+    /// Target back to client
+    pub bytes_received: AtomicU64,
+}
+
+/// Split `--to` into host and port; a bare port means this machine
+pub fn parse_target(target: &str) -> Result<(String, u16)> {
+    let target = target.trim();
+    if let Ok(port) = target.parse::<u16>() {
+        return Ok(("localhost".to_string(), port));
+    }
+    let (host, port) = target
+        .rsplit_once(':')
+        .ok_or_else(|| anyhow!("Invalid --to '{}' (use PORT or HOST:PORT)", target))?;
+    let port = port
+        .parse::<u16>()
+        .map_err(|_| anyhow!("Invalid port in --to '{}'", target))?;
+    let host = host.trim_start_matches('[').trim_end_matches(']');
+    if host.is_empty() {
+        return Err(anyhow!("Invalid --to '{}' (use PORT or HOST:PORT)", target));
+    }
+    Ok((host.to_string(), port))
+}
+
+fn is_local(host: &str) -> bool {
+    matches!(host, "localhost" | "127.0.0.1" | "::1" | "0.0.0.0" | "::")
+}
+
+/// Pause after a failed accept before trying again
+const ACCEPT_RETRY_DELAY: Duration = Duration::from_millis(100);
+
+/// Accept connections on `listener` and pipe each one to `target` until the task is
+/// dropped
+pub async fn forward(listener: TcpListener, target: (String, u16), stats: Arc<ProxyStats>) {
+    let target = Arc::new(target);
+    loop {
+        let (inbound, peer) = match listener.accept().await {
+            Ok(accepted) => accepted,
+            Err(e) => {
+                // Errors such as running out of file descriptors repeat until something
+                // changes, so back off instead of spinning
+                warn!(error = %e, "Failed to accept connection");
+                tokio::time::sleep(ACCEPT_RETRY_DELAY).await;
+                continue;
+            }
+        };
+        stats.connections.fetch_add(1, Ordering::Relaxed);
+        let (target, stats) = (Arc::clone(&target), Arc::clone(&stats));
+        tokio::spawn(async move {
+            let (host, port) = (target.0.as_str(), target.1);
+            let mut inbound = inbound;
+            let mut outbound = match TcpStream::connect((host, port)).await {
+                Ok(outbound) => outbound,
+                Err(e) => {
+                    warn!(peer = %peer, port, error = %e, "Target refused connection");
+                    return;
+                }
+            };
+            debug!(peer = %peer, port, "Forwarding connection");
+            match tokio::io::copy_bidirectional(&mut inbound, &mut outbound).await {
+                Ok((sent, received)) => {
+                    stats.bytes_sent.fetch_add(sent, Ordering::Relaxed);
+                    stats.bytes_received.fetch_add(received, Ordering::Relaxed);
+                }
+                Err(e) => debug!(peer = %peer, error = %e, "Connection closed"),
+            }
+        });
+    }
+}
+
+/// `port-kill proxy`: runs until Ctrl+C or `--duration`
+pub async fn run_proxy(args: &Args, proxy: &ProxyArgs) -> Result<()> {
+    let (host, port) = parse_target(&proxy.to)?;
+    if port == proxy.port && is_local(&host) {
+        return Err(anyhow!("Port {} can't be forwarded to itself", proxy.port));
+    }
+
+    let mut listeners = Vec::new();
+    for &address in &proxy.bind {
+        match TcpListener::bind(SocketAddr::new(address, proxy.port)).await {
+            Ok(listener) => listeners.push(listener),
+            Err(e) if e.kind() == ErrorKind::AddrInUse => {
+                warn!(port = proxy.port, address = %address, "Address already taken, skipping");
+            }
+            // e.g. ::1 on a host without IPv6
+            Err(e) if e.kind() == ErrorKind::AddrNotAvailable => {
+                debug!(address = %address, error = %e, "Address not available, skipping");
+            }
+            Err(e) => return Err(anyhow!("Failed to listen on {}:{}: {}", address, proxy.port, e)),
+        }
+    }
+    if listeners.is_empty() {
+        return Err(anyhow!(
+            "Nothing to listen on: port {} is taken on {}{}. Pick addresses the occupant doesn't hold with --bind",
+            proxy.port,
+            join_addresses(&proxy.bind),
+            occupant(proxy.port, args)
+        ));
+    }
+
+    for listener in &listeners {
+        if let Ok(local) = listener.local_addr() {
+            println!("🔀 Forwarding {} -> {}:{}", local, host, port);
+        }
+    }
+    match proxy.duration {
+        Some(duration) => println!("   Stopping after {:?}", duration),
+        None => println!("   Press Ctrl+C to stop"),
+    }
+    info!(port = proxy.port, target = %format!("{}:{}", host, port), "Proxy started");
+
+    let started = Instant::now();
+    let stats = Arc::new(ProxyStats::default());
+    let tasks: Vec<_> = listeners
+        .into_iter()
+        .map(|listener| tokio::spawn(forward(listener, (host.clone(), port), Arc::clone(&stats))))
+        .collect();
+
+    match proxy.duration {
+        Some(duration) => {
+            tokio::select! {
+                _ = tokio::signal::ctrl_c() => {}
+                _ = tokio::time::sleep(duration) => {}
+            }
+        }
+        None => tokio::signal::ctrl_c().await?,
+    }
+    for task in tasks {
+        task.abort();
+    }
+
+    println!(
+        "\n✅ Forwarded {} connection(s) in {:.0}s ({} bytes sent, {} bytes received)",
+        stats.connections.load(Ordering::Relaxed),
+        started.elapsed().as_secs_f64(),
+        stats.bytes_sent.load(Ordering::Relaxed),
+        stats.bytes_received.load(Ordering::Relaxed)
+    );
+    Ok(())
+}
+
+fn join_addresses(addresses: &[IpAddr]) -> String {
+    addresses
+        .iter()
+        .map(|address| address.to_string())
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// " (held by node, PID 1234)", or nothing when the occupant can't be found
+fn occupant(port: u16, args: &Args) -> String {
+    let mut args = args.clone();
+    args.verbose = false;
+    let (_, processes) = crate::process_monitor::get_processes_on_ports(&[port], &args);
+    processes
+        .get(&port)
+        .map(|process| format!(" (held by {}, PID {})", process.name, process.pid))
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    #[test]
+    fn test_parse_target() {
+        assert_eq!(parse_target("3001").unwrap(), ("localhost".to_string(), 3001));
+        assert_eq!(parse_target("10.0.0.5:8080").unwrap(), ("10.0.0.5".to_string(), 8080));
+        assert_eq!(parse_target("[::1]:3001").unwrap(), ("::1".to_string(), 3001));
+        assert!(parse_target("api.local").is_err());
+        assert!(parse_target(":3001").is_err());
+        assert!(parse_target("host:99999").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_forward() {
+        let upstream = TcpListener::bind(("127.0.0.1", 0)).await.unwrap();
+        let upstream_port = upstream.local_addr().unwrap().port();
+        tokio::spawn(async move {
+            let (mut socket, _) = upstream.accept().await.unwrap();
+            let mut buffer = [0u8; 4];
+            socket.read_exact(&mut buffer).await.unwrap();
+            socket.write_all(b"pong").await.unwrap();
+        });
+
+        let listener = TcpListener::bind(("127.0.0.1", 0)).await.unwrap();
+        let proxy_port = listener.local_addr().unwrap().port();
+        let stats = Arc::new(ProxyStats::default());
+        let proxy = tokio::spawn(forward(
+            listener,
+            ("127.0.0.1".to_string(), upstream_port),
+            Arc::clone(&stats),
+        ));
+
+        let mut client = TcpStream::connect(("127.0.0.1", proxy_port)).await.unwrap();
+        client.write_all(b"ping").await.unwrap();
+        let mut reply = [0u8; 4];
+        client.read_exact(&mut reply).await.unwrap();
+        assert_eq!(&reply, b"pong");
+        assert_eq!(stats.connections.load(Ordering::Relaxed), 1);
+        proxy.abort();
+    }
+}
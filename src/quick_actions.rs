@@ -0,0 +1,240 @@
+use crate::types::SecurityAuditResult;
+use anyhow::{anyhow, bail, Result};
+use std::fmt;
+use std::path::PathBuf;
+use std::process::Command;
+use std::str::FromStr;
+
+/// Menu id prefix of quick-action items; the rest of the id is the action's spec
+pub const QUICK_ACTION_PREFIX: &str = "quick:";
+
+/// One-click tray item that runs an existing port-kill command.
+/// Written in the config file as `reset`, `restart:3000`, `start:web` or `audit`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum QuickAction {
+    /// `--reset`: free the common development ports
+    ResetPorts,
+    /// `--restart <port>`: kill and relaunch with the saved command
+    Restart(u16),
+    /// `--restart-service <name>`: (re)start a service from `.port-kill.yaml`
+    StartService(String),
+    /// `--audit`: security audit, summarized in a notification
+    Audit,
+}
+
+impl QuickAction {
+    /// Actions shown when the config file does not list any
+    pub fn defaults() -> Vec<Self> {
+        vec![QuickAction::ResetPorts, QuickAction::Audit]
+    }
+
+    pub fn label(&self) -> String {
+        match self {
+            QuickAction::ResetPorts => "Reset Common Ports".to_string(),
+            QuickAction::Restart(port) => format!("Restart :{}", port),
+            QuickAction::StartService(name) => format!("Start {}", name),
+            QuickAction::Audit => "Run Security Audit".to_string(),
+        }
+    }
+
+    pub fn menu_id(&self) -> String {
+        format!("{}{}", QUICK_ACTION_PREFIX, self)
+    }
+
+    /// Arguments for `port-kill-console` that perform this action
+    pub fn console_args(&self) -> Vec<String> {
+        match self {
+            QuickAction::ResetPorts => vec!["--reset".to_string()],
+            QuickAction::Restart(port) => vec!["--restart".to_string(), port.to_string()],
+            QuickAction::StartService(name) => {
+                vec!["--restart-service".to_string(), name.clone()]
+            }
+            QuickAction::Audit => vec!["--audit".to_string(), "--json".to_string()],
+        }
+    }
+
+    /// Run the action and return a one-line summary for a notification
+    pub fn run(&self) -> Result<String> {
+        let output = Command::new(console_binary())
+            .args(self.console_args())
+            .output()
+            .map_err(|e| anyhow!("Failed to run port-kill-console: {}", e))?;
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            let reason = last_line(&stderr)
+                .or_else(|| last_line(&stdout))
+                .unwrap_or("exited with an error");
+            bail!("{}: {}", self.label(), reason);
+        }
+
+        match self {
+            QuickAction::Audit => Ok(audit_summary(&stdout)),
+            _ => Ok(format!("{} done", self.label())),
+        }
+    }
+}
+
+impl fmt::Display for QuickAction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            QuickAction::ResetPorts => write!(f, "reset"),
+            QuickAction::Restart(port) => write!(f, "restart:{}", port),
+            QuickAction::StartService(name) => write!(f, "start:{}", name),
+            QuickAction::Audit => write!(f, "audit"),
+        }
+    }
+}
+
+impl FromStr for QuickAction {
+    type Err = anyhow::Error;
+
+    fn from_str(spec: &str) -> Result<Self> {
+        let spec = spec.trim();
+        match spec.split_once(':') {
+            None if spec == "reset" => Ok(QuickAction::ResetPorts),
+            None if spec == "audit" => Ok(QuickAction::Audit),
+            Some(("restart", port)) => port
+                .trim()
+                .parse()
+                .map(QuickAction::Restart)
+                .map_err(|_| anyhow!("Invalid port in quick action '{}'", spec)),
+            Some(("start", name)) if !name.trim().is_empty() => {
+                Ok(QuickAction::StartService(name.trim().to_string()))
+            }
+            _ => Err(anyhow!(
+                "Unknown quick action '{}' (expected reset, restart:<port>, start:<service> or audit)",
+                spec
+            )),
+        }
+    }
+}
+
+/// What a global shortcut in the tray app does.
+/// Written in the config file as `kill:<port>`, `list`, or any quick action.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum HotkeyAction {
+    /// Kill whatever listens on the port
+    Kill(u16),
+    /// Show the current process list in a notification
+    List,
+    Quick(QuickAction),
+}
+
+impl fmt::Display for HotkeyAction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            HotkeyAction::Kill(port) => write!(f, "kill:{}", port),
+            HotkeyAction::List => write!(f, "list"),
+            HotkeyAction::Quick(action) => write!(f, "{}", action),
+        }
+    }
+}
+
+impl FromStr for HotkeyAction {
+    type Err = anyhow::Error;
+
+    fn from_str(spec: &str) -> Result<Self> {
+        let spec = spec.trim();
+        if spec == "list" {
+            return Ok(HotkeyAction::List);
+        }
+        if let Some(port) = spec.strip_prefix("kill:") {
+            return port
+                .trim()
+                .parse()
+                .map(HotkeyAction::Kill)
+                .map_err(|_| anyhow!("Invalid port in hotkey action '{}'", spec));
+        }
+        spec.parse().map(HotkeyAction::Quick).map_err(|_| {
+            anyhow!(
+                "Unknown hotkey action '{}' (expected kill:<port>, list, reset, restart:<port>, start:<service> or audit)",
+                spec
+            )
+        })
+    }
+}
+
+/// `port-kill-console` next to this binary, or the one on PATH
+pub(crate) fn console_binary() -> PathBuf {
+    let name = format!("port-kill-console{}", std::env::consts::EXE_SUFFIX);
+    std::env::current_exe()
+        .ok()
+        .map(|exe| exe.with_file_name(&name))
+        .filter(|path| path.is_file())
+        .unwrap_or_else(|| PathBuf::from(name))
+}
+
+fn last_line(text: &str) -> Option<&str> {
+    text.lines()
+        .map(str::trim)
+        .rev()
+        .find(|line| !line.is_empty())
+}
+
+fn audit_summary(output: &str) -> String {
+    // Skip any diagnostics printed ahead of the JSON document
+    let json = output.find('{').map_or(output, |at| &output[at..]);
+    match serde_json::from_str::<SecurityAuditResult>(json) {
+        Ok(result) => format!(
+            "Security score {:.0}/100, {} suspicious process(es)",
+            result.security_score,
+            result.suspicious_processes.len()
+        ),
+        // `--audit` prints a plain message instead of JSON when nothing is listening
+        Err(_) => last_line(output)
+            .unwrap_or("Security audit done")
+            .to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_quick_actions() {
+        for spec in ["reset", "restart:3000", "start:web", "audit"] {
+            let action: QuickAction = spec.parse().unwrap();
+            assert_eq!(action.to_string(), spec);
+        }
+        assert_eq!(
+            "restart:3000"
+                .parse::<QuickAction>()
+                .unwrap()
+                .console_args(),
+            vec!["--restart", "3000"]
+        );
+        assert_eq!(
+            QuickAction::StartService("web".to_string()).menu_id(),
+            "quick:start:web"
+        );
+        assert!("restart:http".parse::<QuickAction>().is_err());
+        assert!("start:".parse::<QuickAction>().is_err());
+        assert!("deploy".parse::<QuickAction>().is_err());
+
+        assert_eq!(
+            "kill:3000".parse::<HotkeyAction>().unwrap(),
+            HotkeyAction::Kill(3000)
+        );
+        assert_eq!(
+            "audit".parse::<HotkeyAction>().unwrap(),
+            HotkeyAction::Quick(QuickAction::Audit)
+        );
+        assert_eq!("list".parse::<HotkeyAction>().unwrap().to_string(), "list");
+        assert!("kill:all".parse::<HotkeyAction>().is_err());
+    }
+
+    #[test]
+    fn test_audit_summary() {
+        let output = "DEBUG: scanning\n{\"audit_timestamp\": \"2026-01-01T00:00:00Z\", \"total_ports_scanned\": 1, \"suspicious_processes\": [], \"approved_processes\": [], \"security_score\": 92.5, \"recommendations\": [], \"baseline_comparison\": null}\n";
+        assert_eq!(
+            audit_summary(output),
+            "Security score 92/100, 0 suspicious process(es)"
+        );
+        assert_eq!(
+            audit_summary("ℹ️  No processes found to audit\n"),
+            "ℹ️  No processes found to audit"
+        );
+    }
+}
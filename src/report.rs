@@ -0,0 +1,424 @@
+use crate::cache::output::human_size;
+use crate::cli::{Args, ReportFormat, ReportPeriod};
+use crate::history_store::HistoryStore;
+use crate::types::HistoryFilter;
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::fmt::Write;
+
+/// Rows listed per section of a report
+const TOP_ENTRIES: usize = 5;
+
+/// A digest of the kill, restart and cache history over one period
+#[derive(Debug, Clone, Serialize)]
+pub struct Report {
+    pub period: &'static str,
+    pub since: DateTime<Utc>,
+    pub until: DateTime<Utc>,
+    pub kills: usize,
+    pub restarts: usize,
+    pub most_killed: Vec<ProcessCount>,
+    pub busiest_ports: Vec<PortCount>,
+    /// Processes guard mode killed for taking a guarded or reserved port
+    pub guard_violations: Vec<GuardViolation>,
+    pub cache_cleans: usize,
+    pub cache_reclaimed_bytes: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ProcessCount {
+    pub process: String,
+    pub kills: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct PortCount {
+    pub port: u16,
+    pub kills: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct GuardViolation {
+    pub port: u16,
+    pub process: String,
+    pub count: usize,
+}
+
+impl Report {
+    /// The report for the `period` ending at `until`
+    pub fn build(store: &HistoryStore, period: ReportPeriod, until: DateTime<Utc>) -> Result<Self> {
+        let since = until - period.duration();
+        let filter = HistoryFilter {
+            since: Some(since),
+            until: Some(until),
+            ..Default::default()
+        };
+        let history = store.kills(&filter)?;
+        let kills = history.get_recent_entries(usize::MAX);
+        let (cache_cleans, cache_reclaimed_bytes) = store.cache_cleans(since, until)?;
+
+        Ok(Self {
+            period: period.name(),
+            since,
+            until,
+            kills: kills.len(),
+            restarts: store.restarts(&filter)?.len(),
+            most_killed: top(kills.iter().map(|kill| kill.get_display_name()))
+                .into_iter()
+                .map(|(process, kills)| ProcessCount { process, kills })
+                .collect(),
+            busiest_ports: top(kills.iter().map(|kill| kill.port))
+                .into_iter()
+                .map(|(port, kills)| PortCount { port, kills })
+                .collect(),
+            guard_violations: top(
+                kills
+                    .iter()
+                    .filter(|kill| kill.killed_by == "guard")
+                    .map(|kill| (kill.port, kill.process_name.clone())),
+            )
+            .into_iter()
+            .map(|((port, process), count)| GuardViolation {
+                port,
+                process,
+                count,
+            })
+            .collect(),
+            cache_cleans,
+            cache_reclaimed_bytes,
+        })
+    }
+
+    fn title(&self) -> String {
+        let mut period = self.period.to_string();
+        period[..1].make_ascii_uppercase();
+        format!(
+            "{} port-kill report, {} to {}",
+            period,
+            self.since.format("%Y-%m-%d"),
+            self.until.format("%Y-%m-%d")
+        )
+    }
+
+    /// One line for a notification
+    pub fn summary(&self) -> String {
+        let mut parts = vec![format!("{} kills", self.kills)];
+        if let Some(busiest) = self.busiest_ports.first() {
+            parts.push(format!("busiest port {}", busiest.port));
+        }
+        let violations: usize = self.guard_violations.iter().map(|v| v.count).sum();
+        if violations > 0 {
+            parts.push(format!("{} guard violations", violations));
+        }
+        if self.cache_reclaimed_bytes > 0 {
+            parts.push(format!(
+                "{} of cache reclaimed",
+                human_size(self.cache_reclaimed_bytes)
+            ));
+        }
+        parts.join(", ")
+    }
+
+    pub fn render(&self, format: ReportFormat) -> String {
+        match format {
+            ReportFormat::Terminal => self.render_terminal(),
+            ReportFormat::Markdown => self.render_markdown(),
+            ReportFormat::Html => self.render_html(),
+        }
+    }
+
+    fn render_terminal(&self) -> String {
+        let mut out = String::new();
+        let _ = writeln!(out, "📊 {}", self.title());
+        let _ = writeln!(out, "{}", "─".repeat(60));
+        let _ = writeln!(out, "Kills: {}   Restarts: {}", self.kills, self.restarts);
+
+        let _ = writeln!(out, "\n🔁 Most-killed processes:");
+        if self.most_killed.is_empty() {
+            let _ = writeln!(out, "   none");
+        }
+        for entry in &self.most_killed {
+            let _ = writeln!(out, "   {:<30} {:>4} kills", entry.process, entry.kills);
+        }
+
+        let _ = writeln!(out, "\n🔌 Busiest ports:");
+        if self.busiest_ports.is_empty() {
+            let _ = writeln!(out, "   none");
+        }
+        for entry in &self.busiest_ports {
+            let _ = writeln!(out, "   {:<30} {:>4} kills", entry.port, entry.kills);
+        }
+
+        let _ = writeln!(out, "\n🛡️  Guard violations:");
+        if self.guard_violations.is_empty() {
+            let _ = writeln!(out, "   none");
+        }
+        for violation in &self.guard_violations {
+            let _ = writeln!(
+                out,
+                "   {:<30} {:>4} times",
+                format!("{} on port {}", violation.process, violation.port),
+                violation.count
+            );
+        }
+
+        let _ = writeln!(
+            out,
+            "\n🧹 Cache reclaimed: {} in {} cleanups",
+            human_size(self.cache_reclaimed_bytes),
+            self.cache_cleans
+        );
+        out
+    }
+
+    fn render_markdown(&self) -> String {
+        let mut out = String::new();
+        let _ = writeln!(out, "# {}\n", self.title());
+        let _ = writeln!(out, "- Kills: {}", self.kills);
+        let _ = writeln!(out, "- Restarts: {}", self.restarts);
+        let _ = writeln!(
+            out,
+            "- Cache reclaimed: {} in {} cleanups",
+            human_size(self.cache_reclaimed_bytes),
+            self.cache_cleans
+        );
+
+        let _ = writeln!(out, "\n## Most-killed processes\n");
+        markdown_table(
+            &mut out,
+            &["Process", "Kills"],
+            self.most_killed
+                .iter()
+                .map(|entry| vec![entry.process.clone(), entry.kills.to_string()]),
+        );
+        let _ = writeln!(out, "\n## Busiest ports\n");
+        markdown_table(
+            &mut out,
+            &["Port", "Kills"],
+            self.busiest_ports
+                .iter()
+                .map(|entry| vec![entry.port.to_string(), entry.kills.to_string()]),
+        );
+        let _ = writeln!(out, "\n## Guard violations\n");
+        markdown_table(
+            &mut out,
+            &["Port", "Process", "Times"],
+            self.guard_violations.iter().map(|violation| {
+                vec![
+                    violation.port.to_string(),
+                    violation.process.clone(),
+                    violation.count.to_string(),
+                ]
+            }),
+        );
+        out
+    }
+
+    fn render_html(&self) -> String {
+        let mut out = String::new();
+        let title = escape_html(&self.title());
+        let _ = writeln!(
+            out,
+            "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>{0}</title>\n\
+             <style>body{{font-family:sans-serif;max-width:40em;margin:2em auto}}\
+             table{{border-collapse:collapse}}td,th{{border:1px solid #ccc;padding:.3em .8em;text-align:left}}</style>\n\
+             </head>\n<body>\n<h1>{0}</h1>",
+            title
+        );
+        let _ = writeln!(
+            out,
+            "<ul>\n<li>Kills: {}</li>\n<li>Restarts: {}</li>\n<li>Cache reclaimed: {} in {} cleanups</li>\n</ul>",
+            self.kills,
+            self.restarts,
+            human_size(self.cache_reclaimed_bytes),
+            self.cache_cleans
+        );
+
+        let _ = writeln!(out, "<h2>Most-killed processes</h2>");
+        html_table(
+            &mut out,
+            &["Process", "Kills"],
+            self.most_killed
+                .iter()
+                .map(|entry| vec![entry.process.clone(), entry.kills.to_string()]),
+        );
+        let _ = writeln!(out, "<h2>Busiest ports</h2>");
+        html_table(
+            &mut out,
+            &["Port", "Kills"],
+            self.busiest_ports
+                .iter()
+                .map(|entry| vec![entry.port.to_string(), entry.kills.to_string()]),
+        );
+        let _ = writeln!(out, "<h2>Guard violations</h2>");
+        html_table(
+            &mut out,
+            &["Port", "Process", "Times"],
+            self.guard_violations.iter().map(|violation| {
+                vec![
+                    violation.port.to_string(),
+                    violation.process.clone(),
+                    violation.count.to_string(),
+                ]
+            }),
+        );
+        let _ = writeln!(out, "</body>\n</html>");
+        out
+    }
+}
+
+/// The most frequent values, most frequent first, ties in order of first appearance
+fn top<T: Eq + std::hash::Hash + Clone>(values: impl Iterator<Item = T>) -> Vec<(T, usize)> {
+    let mut counts: Vec<(T, usize)> = Vec::new();
+    let mut index: HashMap<T, usize> = HashMap::new();
+    for value in values {
+        match index.get(&value) {
+            Some(&i) => counts[i].1 += 1,
+            None => {
+                index.insert(value.clone(), counts.len());
+                counts.push((value, 1));
+            }
+        }
+    }
+    counts.sort_by_key(|(_, count)| std::cmp::Reverse(*count));
+    counts.truncate(TOP_ENTRIES);
+    counts
+}
+
+fn markdown_table(out: &mut String, headers: &[&str], rows: impl Iterator<Item = Vec<String>>) {
+    let rows: Vec<Vec<String>> = rows.collect();
+    if rows.is_empty() {
+        let _ = writeln!(out, "None.");
+        return;
+    }
+    let _ = writeln!(out, "| {} |", headers.join(" | "));
+    let _ = writeln!(out, "|{}", "---|".repeat(headers.len()));
+    for row in rows {
+        let cells: Vec<String> = row.iter().map(|cell| cell.replace('|', "\\|")).collect();
+        let _ = writeln!(out, "| {} |", cells.join(" | "));
+    }
+}
+
+fn html_table(out: &mut String, headers: &[&str], rows: impl Iterator<Item = Vec<String>>) {
+    let rows: Vec<Vec<String>> = rows.collect();
+    if rows.is_empty() {
+        let _ = writeln!(out, "<p>None.</p>");
+        return;
+    }
+    let _ = writeln!(out, "<table>");
+    let _ = writeln!(out, "<tr><th>{}</th></tr>", headers.join("</th><th>"));
+    for row in rows {
+        let cells: Vec<String> = row.iter().map(|cell| escape_html(cell)).collect();
+        let _ = writeln!(out, "<tr><td>{}</td></tr>", cells.join("</td><td>"));
+    }
+    let _ = writeln!(out, "</table>");
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// What `--report-send` posts to the `[notifications]` endpoint
+#[derive(Serialize)]
+struct ReportMessage<'a> {
+    #[serde(rename = "type")]
+    kind: &'static str,
+    report: &'a Report,
+    markdown: String,
+}
+
+/// `--report <period>`: print the report, and send it with `--report-send`
+pub async fn run_report(args: &Args, period: ReportPeriod) -> Result<()> {
+    let store = HistoryStore::open_default()?;
+    let report = Report::build(&store, period, Utc::now())?;
+
+    if args.json {
+        println!("{}", serde_json::to_string_pretty(&report)?);
+    } else {
+        print!("{}", report.render(args.report_format));
+    }
+
+    if args.report_send {
+        crate::notify::send_desktop_notification(&report.title(), &report.summary());
+        if args.monitor_endpoint.is_some() {
+            let message = ReportMessage {
+                kind: "report",
+                report: &report,
+                markdown: report.render(ReportFormat::Markdown),
+            };
+            crate::endpoint_monitor::post_to_endpoint(args, &message).await?;
+            eprintln!("📤 Report sent to {}", args.monitor_endpoint.as_deref().unwrap_or_default());
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::ProcessHistoryEntry;
+
+    fn kill(port: u16, name: &str, killed_by: &str, killed_at: &str) -> ProcessHistoryEntry {
+        ProcessHistoryEntry {
+            pid: 42,
+            port,
+            process_name: name.to_string(),
+            process_group: None,
+            project_name: None,
+            killed_at: killed_at.parse().unwrap(),
+            killed_by: killed_by.to_string(),
+            command_line: None,
+            working_directory: None,
+        }
+    }
+
+    #[test]
+    fn test_weekly_report() {
+        let path = std::env::temp_dir().join(format!("port-kill-report-{}.db", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let store = HistoryStore::open(&path).unwrap();
+        for entry in [
+            kill(3000, "node", "user", "2026-03-09T10:00:00Z"),
+            kill(3000, "node", "user", "2026-03-10T10:00:00Z"),
+            kill(8080, "java", "guard", "2026-03-11T10:00:00Z"),
+            kill(3000, "node", "user", "2026-03-12T10:00:00Z"),
+            // Before the week
+            kill(5432, "postgres", "user", "2026-03-01T10:00:00Z"),
+        ] {
+            store.record_kill(&entry).unwrap();
+        }
+
+        let until = "2026-03-14T00:00:00Z".parse().unwrap();
+        let report = Report::build(&store, ReportPeriod::Weekly, until).unwrap();
+        assert_eq!(report.kills, 4);
+        assert_eq!(
+            report.most_killed[0],
+            ProcessCount {
+                process: "node".to_string(),
+                kills: 3
+            }
+        );
+        assert_eq!(report.busiest_ports[1], PortCount { port: 8080, kills: 1 });
+        assert_eq!(
+            report.guard_violations,
+            vec![GuardViolation {
+                port: 8080,
+                process: "java".to_string(),
+                count: 1
+            }]
+        );
+        assert_eq!(report.summary(), "4 kills, busiest port 3000, 1 guard violations");
+
+        let markdown = report.render(ReportFormat::Markdown);
+        assert!(markdown.starts_with("# Weekly port-kill report, 2026-03-07 to 2026-03-14"));
+        assert!(markdown.contains("| 8080 | java | 1 |"));
+        assert!(report.render(ReportFormat::Html).contains("<td>node</td><td>3</td>"));
+
+        let _ = std::fs::remove_file(&path);
+    }
+}
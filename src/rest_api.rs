@@ -0,0 +1,332 @@
+//! `--serve`: a REST API over HTTP for dashboards and editor plugins that would rather
+//! not shell out or speak the JSON-RPC of `--machine`.
+
+use crate::cli::Args;
+use crate::port_guard::read_reservation_file;
+use crate::process_monitor::{get_processes_on_ports, kill_all_processes, kill_single_process};
+use crate::types::{GuardStatus, PortReservation, ProcessInfo, StatusBarInfo};
+use anyhow::{Context, Result};
+use axum::extract::{Path, Request, State};
+use axum::http::{header, Method, StatusCode};
+use axum::middleware::{self, Next};
+use axum::response::{IntoResponse, Json, Response};
+use axum::routing::{get, post};
+use axum::Router;
+use log::warn;
+use serde::Serialize;
+use serde_json::{json, Value};
+use std::collections::{BTreeMap, HashMap};
+use std::net::{IpAddr, SocketAddr};
+use std::sync::Arc;
+
+/// Address `--serve` listens on when none is given
+pub const DEFAULT_SERVE_ADDR: &str = "127.0.0.1:7777";
+
+struct ApiState {
+    args: Args,
+}
+
+type SharedState = Arc<ApiState>;
+
+/// A monitored port that is in use or reserved
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PortEntry {
+    pub port: u16,
+    pub process: Option<ProcessInfo>,
+    pub reservation: Option<PortReservation>,
+}
+
+/// An error answered as `{"error": "..."}` with its status code
+struct ApiError(StatusCode, String);
+
+impl ApiError {
+    fn internal(e: impl std::fmt::Display) -> Self {
+        Self(StatusCode::INTERNAL_SERVER_ERROR, e.to_string())
+    }
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        (self.0, Json(json!({ "error": self.1 }))).into_response()
+    }
+}
+
+/// Scan the monitored ports on the blocking pool
+async fn scan(args: &Args) -> Result<HashMap<u16, ProcessInfo>, ApiError> {
+    let args = args.clone();
+    tokio::task::spawn_blocking(move || get_processes_on_ports(&args.get_ports_to_monitor(), &args).1)
+        .await
+        .map_err(ApiError::internal)
+}
+
+fn read_reservations(args: &Args) -> HashMap<u16, PortReservation> {
+    read_reservation_file(std::path::Path::new(&args.get_reservation_file_path()))
+        .map(|loaded| loaded.data)
+        .unwrap_or_default()
+}
+
+async fn ports(State(state): State<SharedState>) -> Result<Json<Vec<PortEntry>>, ApiError> {
+    let mut processes = scan(&state.args).await?;
+    let mut reservations = read_reservations(&state.args);
+    let mut entries: BTreeMap<u16, PortEntry> = BTreeMap::new();
+    for port in processes.keys().chain(reservations.keys()).copied().collect::<Vec<_>>() {
+        entries.entry(port).or_insert_with(|| PortEntry {
+            port,
+            process: processes.remove(&port),
+            reservation: reservations.remove(&port),
+        });
+    }
+    Ok(Json(entries.into_values().collect()))
+}
+
+async fn processes(State(state): State<SharedState>) -> Result<Json<Vec<ProcessInfo>>, ApiError> {
+    let mut processes: Vec<ProcessInfo> = scan(&state.args).await?.into_values().collect();
+    processes.sort_by_key(|process| process.port);
+    Ok(Json(processes))
+}
+
+/// Only processes on the monitored ports can be killed, not anything else the user runs
+async fn kill(State(state): State<SharedState>, Path(pid): Path<i32>) -> Result<Json<Value>, ApiError> {
+    let processes = scan(&state.args).await?;
+    if !processes.values().any(|process| process.pid == pid) {
+        return Err(ApiError(
+            StatusCode::NOT_FOUND,
+            format!("No process with PID {} on the monitored ports", pid),
+        ));
+    }
+    let args = state.args.clone();
+    tokio::task::spawn_blocking(move || kill_single_process(pid, &args))
+        .await
+        .map_err(ApiError::internal)?
+        .map_err(ApiError::internal)?;
+    Ok(Json(json!({ "killed": [pid] })))
+}
+
+/// Like `kill`, only the monitored ports can be cleared
+async fn clear(State(state): State<SharedState>, Path(port): Path<u16>) -> Result<Json<Value>, ApiError> {
+    if !state.args.get_ports_to_monitor().contains(&port) {
+        return Err(ApiError(
+            StatusCode::FORBIDDEN,
+            format!("Port {} is not monitored", port),
+        ));
+    }
+    let args = state.args.clone();
+    let killed = tokio::task::spawn_blocking(move || {
+        let (_, processes) = get_processes_on_ports(&[port], &args);
+        kill_all_processes(&[port], &args)?;
+        Ok::<Vec<i32>, anyhow::Error>(processes.values().map(|process| process.pid).collect())
+    })
+    .await
+    .map_err(ApiError::internal)?
+    .map_err(ApiError::internal)?;
+    Ok(Json(json!({ "port": port, "killed": killed })))
+}
+
+/// The guard settings and the reservations in the reservation file. `--serve` runs no
+/// guard itself, so nothing is active or resolved here.
+async fn guards(State(state): State<SharedState>) -> Json<GuardStatus> {
+    let mut active_reservations: Vec<PortReservation> =
+        read_reservations(&state.args).into_values().collect();
+    active_reservations.sort_by_key(|reservation| reservation.port);
+    Json(GuardStatus {
+        is_active: false,
+        watched_ports: state.args.get_guard_ports(),
+        active_reservations,
+        conflicts_resolved: 0,
+        last_activity: None,
+        auto_resolve_enabled: state.args.auto_resolve,
+    })
+}
+
+/// The status line the console monitor prints for the monitored ports
+async fn status(State(state): State<SharedState>) -> Result<Json<StatusBarInfo>, ApiError> {
+    let processes = scan(&state.args).await?;
+    Ok(Json(StatusBarInfo::from_processes_with_status(&processes)))
+}
+
+/// Whether `host` (a `Host` header) names this machine: `localhost`, a loopback address
+/// or the address the server is bound to. Pages on other names that resolve here
+/// (DNS rebinding) are refused. With an unspecified bind address any IP literal works,
+/// since rebinding needs a host name.
+fn is_own_host(host: &str, bound: IpAddr) -> bool {
+    let name = match host.rsplit_once(':') {
+        Some((name, port)) if !name.ends_with(':') && port.bytes().all(|b| b.is_ascii_digit()) => name,
+        _ => host,
+    };
+    if name.eq_ignore_ascii_case("localhost") {
+        return true;
+    }
+    match name.trim_start_matches('[').trim_end_matches(']').parse::<IpAddr>() {
+        Ok(ip) => ip.is_loopback() || ip == bound || bound.is_unspecified(),
+        Err(_) => false,
+    }
+}
+
+/// Refuse requests whose `Host` is not this machine, for `--serve` and `--dashboard`
+pub(crate) async fn own_host_only(State(bound): State<IpAddr>, request: Request, next: Next) -> Response {
+    let host = request.headers().get(header::HOST).and_then(|v| v.to_str().ok());
+    if !host.is_some_and(|host| is_own_host(host, bound)) {
+        return ApiError(
+            StatusCode::FORBIDDEN,
+            format!("Requests for host {} are not allowed", host.unwrap_or("(none)")),
+        )
+        .into_response();
+    }
+    next.run(request).await
+}
+
+/// Browsers send `Origin` with cross-site requests. Refuse those that change anything,
+/// so a web page can't make the API kill processes. Requests that change anything must
+/// also be JSON, which a page can't send to another origin without a CORS preflight.
+async fn same_origin(request: Request, next: Next) -> Response {
+    if request.method() != Method::GET {
+        let json = request
+            .headers()
+            .get(header::CONTENT_TYPE)
+            .and_then(|v| v.to_str().ok())
+            .is_some_and(|v| v.split(';').next().unwrap_or("").trim().eq_ignore_ascii_case("application/json"));
+        if !json {
+            return ApiError(
+                StatusCode::UNSUPPORTED_MEDIA_TYPE,
+                "POST requests need Content-Type: application/json".to_string(),
+            )
+            .into_response();
+        }
+        let origin = request.headers().get(header::ORIGIN).and_then(|v| v.to_str().ok());
+        let host = request.headers().get(header::HOST).and_then(|v| v.to_str().ok());
+        if let Some(origin) = origin {
+            let own = host.is_some_and(|host| {
+                origin.strip_prefix("http://").or_else(|| origin.strip_prefix("https://")) == Some(host)
+            });
+            if !own {
+                return ApiError(
+                    StatusCode::FORBIDDEN,
+                    format!("Requests from {} are not allowed", origin),
+                )
+                .into_response();
+            }
+        }
+    }
+    next.run(request).await
+}
+
+/// The API's routes, with the monitored ports, filters and reservation file of `args`,
+/// answering requests for `localhost` and the `bound` address
+pub fn router(args: Args, bound: IpAddr) -> Router {
+    let state = Arc::new(ApiState { args });
+    Router::new()
+        .route("/ports", get(ports))
+        .route("/processes", get(processes))
+        .route("/kill/:pid", post(kill))
+        .route("/clear/:port", post(clear))
+        .route("/guards", get(guards))
+        .route("/status", get(status))
+        .layer(middleware::from_fn(same_origin))
+        .layer(middleware::from_fn_with_state(bound, own_host_only))
+        .with_state(state)
+}
+
+/// Serve the API on `addr` until the program is stopped
+pub async fn run_server(args: Args, addr: &str) -> Result<()> {
+    let addr: SocketAddr = addr
+        .parse()
+        .with_context(|| format!("Invalid --serve address '{}'", addr))?;
+    if !addr.ip().is_loopback() {
+        warn!(
+            "⚠️  The API on {} is reachable from other machines and has no authentication",
+            addr
+        );
+    }
+    let listener = tokio::net::TcpListener::bind(addr)
+        .await
+        .with_context(|| format!("Failed to listen on {}", addr))?;
+    println!("🌐 REST API running at http://{} (Ctrl+C to stop)", addr);
+    println!("   GET /ports /processes /guards /status, POST /kill/{{pid}} /clear/{{port}}");
+    axum::serve(listener, router(args, addr.ip()))
+        .await
+        .context("API server stopped")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::Parser;
+
+    #[test]
+    fn test_is_own_host() {
+        let loopback: IpAddr = "127.0.0.1".parse().unwrap();
+        assert!(is_own_host("localhost:7777", loopback));
+        assert!(is_own_host("127.0.0.1:7777", loopback));
+        assert!(is_own_host("[::1]:7777", loopback));
+        assert!(is_own_host("localhost", loopback));
+        assert!(!is_own_host("evil.example:7777", loopback));
+        assert!(!is_own_host("192.168.1.5:7777", loopback));
+
+        let lan: IpAddr = "192.168.1.5".parse().unwrap();
+        assert!(is_own_host("192.168.1.5:7777", lan));
+        assert!(is_own_host("10.0.0.2:7777", "0.0.0.0".parse().unwrap()));
+        assert!(!is_own_host("evil.example", "0.0.0.0".parse().unwrap()));
+    }
+
+    #[tokio::test]
+    async fn test_routes() {
+        let dir = std::env::temp_dir().join(format!("port-kill-api-{}", std::process::id()));
+        let args = Args::parse_from([
+            "port-kill",
+            "--ports",
+            "1",
+            "--reservation-file",
+            &dir.join("reservations.json").to_string_lossy(),
+        ]);
+        let listener = tokio::net::TcpListener::bind(("127.0.0.1", 0)).await.unwrap();
+        let base = format!("http://{}", listener.local_addr().unwrap());
+        let bound = listener.local_addr().unwrap().ip();
+        let server = tokio::spawn(async move { axum::serve(listener, router(args, bound)).await });
+        let client = reqwest::Client::new();
+
+        let status: Value = client.get(format!("{}/status", base)).send().await.unwrap().json().await.unwrap();
+        assert_eq!(status["text"], "0");
+        let guards: Value = client.get(format!("{}/guards", base)).send().await.unwrap().json().await.unwrap();
+        assert_eq!(guards["active_reservations"], json!([]));
+        assert_eq!(guards["is_active"], false);
+
+        // Only PIDs on the monitored ports can be killed, not this test process
+        let unmonitored = client
+            .post(format!("{}/kill/{}", base, std::process::id()))
+            .header("Content-Type", "application/json")
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(unmonitored.status().as_u16(), 404);
+        // Nor can ports outside the monitored ones be cleared
+        let unmonitored = client
+            .post(format!("{}/clear/2", base))
+            .header("Content-Type", "application/json")
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(unmonitored.status().as_u16(), 403);
+        let form = client.post(format!("{}/clear/1", base)).send().await.unwrap();
+        assert_eq!(form.status().as_u16(), 415);
+        let foreign = client
+            .post(format!("{}/clear/1", base))
+            .header("Content-Type", "application/json")
+            .header("Origin", "http://evil.example")
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(foreign.status().as_u16(), 403);
+        // A rebound DNS name sends a matching Origin and Host
+        let rebound = client
+            .get(format!("{}/processes", base))
+            .header("Host", "evil.example:7777")
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(rebound.status().as_u16(), 403);
+        let unknown = client.get(format!("{}/nope", base)).send().await.unwrap();
+        assert_eq!(unknown.status().as_u16(), 404);
+        server.abort();
+    }
+}
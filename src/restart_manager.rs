@@ -1,24 +1,634 @@
-use crate::types::ProcessHistoryEntry;
+use crate::format_version::{
+    backup_old_format, parse_versioned, to_versioned_json, unchanged_payload, Versioned,
+    RESTART_HISTORY_VERSION,
+};
+use crate::history_store::HistoryStore;
+use crate::types::{ProcessHistoryEntry, ProcessInfo};
 use anyhow::{Context, Result};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::fs;
-use std::path::PathBuf;
-use std::process::{Child, Command};
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command, Stdio};
 use std::thread;
+use std::time::Duration;
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct RestartInfo {
     pub port: u16,
     pub command: Vec<String>,
     pub working_directory: String,
     pub env_vars: HashMap<String, String>,
     pub last_restarted: chrono::DateTime<chrono::Utc>,
+    /// Earlier distinct commands seen on this port, most recent first
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub previous: Vec<RestartVersion>,
+    /// Shell command run before the saved command is launched; a failure aborts the restart
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pre: Option<String>,
+    /// Shell command run once the restarted process accepts connections on the port
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub post: Option<String>,
+    /// Terminal the process was started from, when known
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub terminal: Option<TerminalContext>,
+    /// Launch with only `CLEAN_ENV_VARS` from the saved environment instead of
+    /// inheriting port-kill's environment
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub clean_env: bool,
+    /// Account to launch the command as (via sudo; not supported on Windows)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub as_user: Option<String>,
+    /// Docker container that served the port; `command` then starts it through Docker
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub container: Option<ContainerTarget>,
+}
+
+/// A Docker container found behind a port at kill time
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ContainerTarget {
+    pub id: String,
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub compose_service: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub compose_project: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub compose_working_dir: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub compose_files: Vec<String>,
+}
+
+/// Processes that forward a published port into a container (names as lsof
+/// truncates them)
+const DOCKER_PROXY_NAMES: &[&str] = &["docker-pr", "com.docke", "vpnkit", "rootlessk"];
+
+impl ContainerTarget {
+    /// The container behind a port occupant: either one the scan already
+    /// attributed to a container, or Docker's port proxy
+    pub fn for_process(process_info: &ProcessInfo) -> Option<Self> {
+        if let Some(id) = &process_info.container_id {
+            if let Some(target) = Self::inspect(id) {
+                return Some(target);
+            }
+        }
+        let is_proxy = DOCKER_PROXY_NAMES
+            .iter()
+            .any(|name| process_info.name.starts_with(name));
+        if is_proxy {
+            Self::for_port(process_info.port)
+        } else {
+            None
+        }
+    }
+
+    /// The running container publishing `port`
+    pub fn for_port(port: u16) -> Option<Self> {
+        let output = Command::new("docker")
+            .args(["ps", "-q", "--filter", &format!("publish={}", port)])
+            .output()
+            .ok()?;
+        if !output.status.success() {
+            return None;
+        }
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        Self::inspect(stdout.lines().next()?.trim())
+    }
+
+    /// Read the container's identity and compose labels with `docker inspect`
+    pub fn inspect(container: &str) -> Option<Self> {
+        let output = Command::new("docker")
+            .args(["inspect", container])
+            .output()
+            .ok()?;
+        if !output.status.success() {
+            return None;
+        }
+        let inspected: serde_json::Value = serde_json::from_slice(&output.stdout).ok()?;
+        Self::from_inspect(inspected.get(0)?)
+    }
+
+    fn from_inspect(inspected: &serde_json::Value) -> Option<Self> {
+        let label = |key: &str| {
+            inspected["Config"]["Labels"][key]
+                .as_str()
+                .filter(|v| !v.is_empty())
+                .map(str::to_string)
+        };
+        Some(Self {
+            id: inspected["Id"].as_str()?.chars().take(12).collect(),
+            name: inspected["Name"]
+                .as_str()
+                .unwrap_or_default()
+                .trim_start_matches('/')
+                .to_string(),
+            compose_service: label("com.docker.compose.service"),
+            compose_project: label("com.docker.compose.project"),
+            compose_working_dir: label("com.docker.compose.project.working_dir"),
+            compose_files: label("com.docker.compose.project.config_files")
+                .map(|files| files.split(',').map(str::to_string).collect())
+                .unwrap_or_default(),
+        })
+    }
+
+    /// `docker compose up -d <service>` for compose services, `docker start` otherwise
+    pub fn restart_command(&self) -> Vec<String> {
+        match &self.compose_service {
+            Some(service) => {
+                let mut argv = vec!["docker".to_string(), "compose".to_string()];
+                if let Some(project) = &self.compose_project {
+                    argv.extend(["-p".to_string(), project.clone()]);
+                }
+                for file in &self.compose_files {
+                    argv.extend(["-f".to_string(), file.clone()]);
+                }
+                argv.extend(["up".to_string(), "-d".to_string(), service.clone()]);
+                argv
+            }
+            None => vec!["docker".to_string(), "start".to_string(), self.id.clone()],
+        }
+    }
+
+    /// Whether Docker reports the container as running. Compose may recreate it under
+    /// a new ID, so it is looked up by name when there is one.
+    pub fn is_running(&self) -> bool {
+        let container = if self.name.is_empty() { &self.id } else { &self.name };
+        Command::new("docker")
+            .args(["inspect", "--format", "{{.State.Running}}", container])
+            .output()
+            .is_ok_and(|output| {
+                output.status.success() && String::from_utf8_lossy(&output.stdout).trim() == "true"
+            })
+    }
+
+    pub fn describe(&self) -> String {
+        match (&self.compose_service, &self.compose_project) {
+            (Some(service), Some(project)) => {
+                format!("compose service {} (project {})", service, project)
+            }
+            (Some(service), None) => format!("compose service {}", service),
+            _ => format!("container {} ({})", self.name, self.id),
+        }
+    }
+}
+
+/// Where a saved process was running, recovered from its environment
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct TerminalContext {
+    /// tmux server socket, from `TMUX`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tmux_socket: Option<String>,
+    /// tmux session id (e.g. `$0`), from `TMUX`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tmux_session: Option<String>,
+    /// tmux pane id (e.g. `%3`), from `TMUX_PANE`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tmux_pane: Option<String>,
+    /// Terminal application, from `TERM_PROGRAM` (e.g. `iTerm.app`, `vscode`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub program: Option<String>,
+}
+
+impl TerminalContext {
+    pub fn from_env(env: &HashMap<String, String>) -> Option<Self> {
+        // TMUX is "<socket>,<server pid>,<session id>"
+        let tmux = env.get("TMUX").map(|v| v.split(',').collect::<Vec<_>>());
+        let context = Self {
+            tmux_socket: tmux
+                .as_ref()
+                .and_then(|parts| parts.first())
+                .filter(|socket| !socket.is_empty())
+                .map(|socket| socket.to_string()),
+            tmux_session: tmux
+                .as_ref()
+                .and_then(|parts| parts.get(2))
+                .map(|id| format!("${}", id)),
+            tmux_pane: env.get("TMUX_PANE").cloned(),
+            program: env.get("TERM_PROGRAM").cloned(),
+        };
+        (context != Self::default()).then_some(context)
+    }
+
+    pub fn is_tmux(&self) -> bool {
+        self.tmux_session.is_some()
+    }
+
+    /// tmux invocation talking to the server the process ran under
+    fn tmux_command(&self) -> Command {
+        let mut cmd = Command::new("tmux");
+        if let Some(socket) = &self.tmux_socket {
+            cmd.args(["-S", socket]);
+        }
+        cmd
+    }
+
+    /// Current name of the recorded tmux session, if it still exists
+    pub fn tmux_session_name(&self) -> Option<String> {
+        let session = self.tmux_session.as_ref()?;
+        let output = self
+            .tmux_command()
+            .args(["display-message", "-p", "-t", session, "#{session_name}"])
+            .output()
+            .ok()?;
+        let name = String::from_utf8_lossy(&output.stdout).trim().to_string();
+        (output.status.success() && !name.is_empty()).then_some(name)
+    }
+
+    /// Human-readable description, e.g. `tmux (pane %3) in iTerm.app`
+    pub fn describe(&self) -> String {
+        let mut parts = Vec::new();
+        if self.is_tmux() {
+            match &self.tmux_pane {
+                Some(pane) => parts.push(format!("tmux (pane {})", pane)),
+                None => parts.push("tmux".to_string()),
+            }
+        }
+        if let Some(program) = &self.program {
+            parts.push(program.clone());
+        }
+        parts.join(" in ")
+    }
+}
+
+/// A command that previously occupied a port, kept so `--restart <port> --pick` can bring it back
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RestartVersion {
+    pub command: Vec<String>,
+    pub working_directory: String,
+    pub env_vars: HashMap<String, String>,
+    pub last_used: chrono::DateTime<chrono::Utc>,
+}
+
+/// How many earlier commands are remembered per port
+const MAX_PREVIOUS_VERSIONS: usize = 5;
+
+/// File format written by `--export-restarts` and read by `--import-restarts`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RestartRecipes {
+    pub version: u32,
+    pub restarts: Vec<RestartRecipe>,
+}
+
+/// A shareable restart entry: how to start whatever usually runs on a port
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RestartRecipe {
+    pub port: u16,
+    pub command: Vec<String>,
+    /// Relative to the directory the recipes were exported from when inside it
+    pub working_directory: String,
+    /// Only variables from `RECIPE_ENV_VARS`; the rest of a saved environment is
+    /// machine-specific or may hold secrets
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub env: HashMap<String, String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pre: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub post: Option<String>,
+}
+
+const RECIPE_FORMAT_VERSION: u32 = 1;
+
+/// Environment variables that are worth sharing in a recipe
+const RECIPE_ENV_VARS: &[&str] = &[
+    "NODE_ENV",
+    "PORT",
+    "HOST",
+    "DEBUG",
+    "RAILS_ENV",
+    "FLASK_ENV",
+    "DJANGO_SETTINGS_MODULE",
+];
+
+/// One-off tweaks applied to a saved command for a single restart (not persisted)
+#[derive(Debug, Clone, Default)]
+pub struct RestartOverrides {
+    /// Arguments appended to the saved command
+    pub extra_args: Vec<String>,
+    /// Environment variables set on top of the saved environment
+    pub env: HashMap<String, String>,
+    /// Pre-restart hook replacing the saved one
+    pub pre: Option<String>,
+    /// Post-restart hook replacing the saved one
+    pub post: Option<String>,
+    /// Respawn in a new window of the tmux session the process ran in
+    pub in_tmux: bool,
+    /// Start from a minimal environment (see `RestartInfo::clean_env`)
+    pub clean_env: bool,
+    /// Account to launch as, replacing the saved one
+    pub as_user: Option<String>,
+}
+
+impl RestartOverrides {
+    pub fn is_empty(&self) -> bool {
+        self.extra_args.is_empty()
+            && self.env.is_empty()
+            && self.pre.is_none()
+            && self.post.is_none()
+            && !self.in_tmux
+            && !self.clean_env
+            && self.as_user.is_none()
+    }
+}
+
+/// How long a post-restart hook waits for the port to accept connections
+const POST_HOOK_PORT_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// How long a restart waits for the old process to release the port (see --restart-timeout)
+pub const DEFAULT_PORT_FREE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Entries not restarted or re-saved for this long are pointed out in the restart
+/// history; they still work, so they are not pruned
+const RESTART_ENTRY_MAX_AGE_DAYS: i64 = 90;
+
+/// Variables of port-kill's own environment saved with a restart entry when the
+/// process's environment can't be read. Its whole environment is saved otherwise, and
+/// restart-history.json is only readable by its owner since that includes secrets.
+const SAVED_ENV_VARS: &[&str] = &[
+    "PATH",
+    "HOME",
+    "USER",
+    "LOGNAME",
+    "SHELL",
+    "LANG",
+    "LC_ALL",
+    "TERM",
+    "TERM_PROGRAM",
+    "TMUX",
+    "TMUX_PANE",
+    "TMPDIR",
+    "NODE_ENV",
+    "PORT",
+    "HOST",
+    "DEBUG",
+    "RAILS_ENV",
+    "RACK_ENV",
+    "FLASK_ENV",
+    "FLASK_APP",
+    "DJANGO_SETTINGS_MODULE",
+    "PYTHONPATH",
+    "VIRTUAL_ENV",
+    "GOPATH",
+    "CARGO_HOME",
+    "RUSTUP_HOME",
+    "JAVA_HOME",
+    "NVM_DIR",
+    "SYSTEMROOT",
+    "USERPROFILE",
+    "APPDATA",
+    "LOCALAPPDATA",
+    "TEMP",
+    "TMP",
+    "COMSPEC",
+    "PATHEXT",
+];
+
+/// Variables kept under --clean-env so the command can still be found and run
+const CLEAN_ENV_VARS: &[&str] = &[
+    "PATH",
+    "HOME",
+    "USER",
+    "LOGNAME",
+    "SHELL",
+    "LANG",
+    "TERM",
+    "TMPDIR",
+    "SYSTEMROOT",
+    "USERPROFILE",
+    "APPDATA",
+    "LOCALAPPDATA",
+    "TEMP",
+    "TMP",
+    "COMSPEC",
+    "PATHEXT",
+];
+
+impl RestartInfo {
+    fn as_version(&self) -> RestartVersion {
+        RestartVersion {
+            command: self.command.clone(),
+            working_directory: self.working_directory.clone(),
+            env_vars: self.env_vars.clone(),
+            last_used: self.last_restarted,
+        }
+    }
+
+    fn same_command(&self, version: &RestartVersion) -> bool {
+        self.command == version.command && self.working_directory == version.working_directory
+    }
+
+    /// All remembered commands for this port, the current one first
+    pub fn versions(&self) -> Vec<RestartVersion> {
+        std::iter::once(self.as_version())
+            .chain(self.previous.iter().cloned())
+            .collect()
+    }
+
+    /// Make `version` the current command, remembering the one it replaces
+    fn replace_current(&mut self, version: RestartVersion) {
+        let old = self.as_version();
+        self.command = version.command;
+        self.working_directory = version.working_directory;
+        self.env_vars = version.env_vars;
+        self.last_restarted = version.last_used;
+
+        let mut previous = std::mem::take(&mut self.previous);
+        previous.retain(|v| !self.same_command(v));
+        if !self.same_command(&old) {
+            previous.insert(0, old);
+        }
+        previous.truncate(MAX_PREVIOUS_VERSIONS);
+        self.previous = previous;
+    }
+
+    /// Short label for menus, e.g. `node server.js` or `npm run`
+    pub fn display_name(&self) -> String {
+        let program = self
+            .command
+            .first()
+            .map(|p| {
+                std::path::Path::new(p)
+                    .file_name()
+                    .map(|n| n.to_string_lossy().to_string())
+                    .unwrap_or_else(|| p.clone())
+            })
+            .unwrap_or_default();
+        match self.command.get(1) {
+            Some(arg) if !arg.starts_with('-') => {
+                let arg = std::path::Path::new(arg)
+                    .file_name()
+                    .map(|n| n.to_string_lossy().to_string())
+                    .unwrap_or_else(|| arg.clone());
+                format!("{} {}", program, arg)
+            }
+            _ => program,
+        }
+    }
+
+    /// The entry as it will be launched once `overrides` are applied
+    pub fn with_overrides(&self, overrides: &RestartOverrides) -> RestartInfo {
+        let mut info = self.clone();
+        info.command.extend(overrides.extra_args.iter().cloned());
+        info.clean_env |= overrides.clean_env;
+        if info.clean_env {
+            // Fall back to port-kill's own values for essentials the saved env lacks
+            info.env_vars = CLEAN_ENV_VARS
+                .iter()
+                .filter_map(|key| {
+                    let value = self
+                        .env_vars
+                        .get(*key)
+                        .cloned()
+                        .or_else(|| std::env::var(key).ok())?;
+                    Some((key.to_string(), value))
+                })
+                .collect();
+        }
+        info.env_vars
+            .extend(overrides.env.iter().map(|(k, v)| (k.clone(), v.clone())));
+        if overrides.pre.is_some() {
+            info.pre = overrides.pre.clone();
+        }
+        if overrides.post.is_some() {
+            info.post = overrides.post.clone();
+        }
+        if overrides.as_user.is_some() {
+            info.as_user = overrides.as_user.clone();
+        }
+        info
+    }
+
+    /// Why this entry can no longer be restarted as saved, if it can't: its
+    /// working directory or program is gone
+    pub fn stale_reason(&self) -> Option<String> {
+        let info = self.with_placeholders();
+        let program = match info.command.first() {
+            Some(program) => program,
+            None => return Some("no command saved".to_string()),
+        };
+
+        let working_directory = Path::new(&info.working_directory);
+        if !working_directory.is_dir() {
+            return Some(format!(
+                "working directory {} no longer exists",
+                working_directory.display()
+            ));
+        }
+        let path_var = info
+            .env_vars
+            .get("PATH")
+            .cloned()
+            .or_else(|| std::env::var("PATH").ok())
+            .unwrap_or_default();
+        if !program_exists(program, working_directory, &path_var) {
+            return Some(format!("program {} not found", program));
+        }
+        None
+    }
+
+    /// Days since the entry was last restarted or saved, when that is long enough
+    /// to be worth pointing out
+    pub fn unused_days(&self) -> Option<i64> {
+        let days = chrono::Utc::now()
+            .signed_duration_since(self.last_restarted)
+            .num_days();
+        (days >= RESTART_ENTRY_MAX_AGE_DAYS).then_some(days)
+    }
+
+    /// Resolve `{{port}}`, `{{cwd}}` (port-kill's current directory) and `{{home}}`
+    /// in the command, working directory, environment values and hooks. Captured
+    /// commands can contain other `{{...}}` text (Go templates, Jinja, Helm), which
+    /// is left alone.
+    pub fn with_placeholders(&self) -> RestartInfo {
+        let mut values = HashMap::from([
+            ("port", self.port.to_string()),
+            ("home", RestartManager::get_home_dir()),
+        ]);
+        if let Ok(cwd) = std::env::current_dir() {
+            values.insert("cwd", cwd.to_string_lossy().to_string());
+        }
+        let expand = |text: &str| crate::command_line::expand_known_placeholders(text, &values);
+
+        let mut info = self.clone();
+        info.command = self.command.iter().map(|part| expand(part)).collect();
+        info.working_directory = expand(&self.working_directory);
+        for value in info.env_vars.values_mut() {
+            *value = expand(value);
+        }
+        info.pre = self.pre.as_deref().map(expand);
+        info.post = self.post.as_deref().map(expand);
+        info
+    }
+}
+
+/// First delay before relaunching a process that exited under --keep-alive
+pub const KEEP_ALIVE_INITIAL_BACKOFF: Duration = Duration::from_secs(1);
+/// Longest delay between relaunches under --keep-alive
+pub const KEEP_ALIVE_MAX_BACKOFF: Duration = Duration::from_secs(60);
+/// A process that stayed up this long is considered healthy again and resets the backoff
+const KEEP_ALIVE_STABLE_UPTIME: Duration = Duration::from_secs(30);
+
+/// Delay before the next --keep-alive relaunch: doubles on each quick exit,
+/// resets once the process has stayed up for a while
+pub fn next_keep_alive_backoff(previous: Option<Duration>, uptime: Duration) -> Duration {
+    match previous {
+        Some(previous) if uptime < KEEP_ALIVE_STABLE_UPTIME => {
+            (previous * 2).min(KEEP_ALIVE_MAX_BACKOFF)
+        }
+        _ => KEEP_ALIVE_INITIAL_BACKOFF,
+    }
+}
+
+/// Directories whose changes never trigger a --watch restart (VCS metadata, dependencies, build output)
+const IGNORED_WATCH_DIRS: &[&str] = &[
+    ".git",
+    "node_modules",
+    "target",
+    "dist",
+    "build",
+    ".next",
+    "__pycache__",
+];
+
+/// `path` relative to the longest of the watched `roots` it lies under, so that a
+/// project inside e.g. `~/build/` is not ignored as a whole
+pub fn watch_relative_path<'a>(path: &'a Path, roots: &[PathBuf]) -> &'a Path {
+    roots
+        .iter()
+        .filter_map(|root| path.strip_prefix(root).ok())
+        .min_by_key(|relative| relative.components().count())
+        .unwrap_or(path)
+}
+
+/// Whether a changed path, relative to the watched directory, should be ignored by
+/// `--restart <port> --watch`
+pub fn is_ignored_watch_path(path: &std::path::Path) -> bool {
+    path.components().any(|c| {
+        IGNORED_WATCH_DIRS
+            .iter()
+            .any(|ignored| c.as_os_str() == std::ffi::OsStr::new(ignored))
+    }) || path
+        .file_name()
+        .and_then(|n| n.to_str())
+        .is_some_and(|n| n.ends_with('~') || n.ends_with(".swp") || n.starts_with(".#"))
+}
+
+/// Result of relaunching one port during `--restart-all`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RestartOutcome {
+    pub port: u16,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pid: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
 }
 
 pub struct RestartManager {
     restart_history_path: PathBuf,
     restart_info: HashMap<u16, RestartInfo>,
+    /// Post-restart hooks still waiting for their port or running
+    pending_hooks: Vec<thread::JoinHandle<()>>,
+    /// How long to wait for a port to be released before launching its command
+    port_free_timeout: Duration,
 }
 
 impl RestartManager {
@@ -30,6 +640,8 @@ impl RestartManager {
         let mut manager = Self {
             restart_history_path: restart_history_path.clone(),
             restart_info: HashMap::new(),
+            pending_hooks: Vec::new(),
+            port_free_timeout: DEFAULT_PORT_FREE_TIMEOUT,
         };
 
         // Create the .port-kill directory if it doesn't exist
@@ -45,75 +657,307 @@ impl RestartManager {
         Ok(manager)
     }
 
-    /// Save a process for future restart
+    /// Set how long a restart waits for the previous process to release its port
+    pub fn set_port_free_timeout(&mut self, timeout: Duration) {
+        self.port_free_timeout = timeout;
+    }
+
+    /// Save a process for future restart.
+    /// `env_vars` should be the killed process's own environment (see
+    /// `read_process_environment`); when it is unavailable the `SAVED_ENV_VARS` of
+    /// port-kill's own environment are used instead.
     pub fn save_process_for_restart(
         &mut self,
         port: u16,
         command_line: &str,
         working_directory: &str,
+        env_vars: Option<HashMap<String, String>>,
     ) -> Result<()> {
         // Parse command line into command and args
         let command_parts = crate::command_line::parse_command_line(command_line);
+        self.save_restart_command(port, command_parts, working_directory, env_vars)
+    }
 
-        // Get current environment variables (filter to common dev vars)
-        let env_vars = Self::get_relevant_env_vars();
+    /// Save a command port-kill launched itself (`--start`) with the environment it was
+    /// launched with, as a kill saves the environment read from the process
+    pub fn save_started_command(
+        &mut self,
+        port: u16,
+        command: Vec<String>,
+        working_directory: &str,
+        env_vars: HashMap<String, String>,
+    ) -> Result<()> {
+        self.save_restart_command(port, command, working_directory, Some(env_vars))
+    }
 
-        let restart_info = RestartInfo {
-            port,
+    /// Save restart info for a process that is about to be killed outside ProcessMonitor
+    /// (e.g. from the tray). The command line, working directory and environment are read
+    /// from the running process when the scan did not provide them.
+    /// Returns false when not enough is known to restart it later.
+    pub fn save_process_info(&mut self, process_info: &ProcessInfo) -> Result<bool> {
+        if let Some(container) = ContainerTarget::for_process(process_info) {
+            self.save_container_for_restart(process_info.port, &container)?;
+            return Ok(true);
+        }
+
+        use sysinfo::{Pid, ProcessRefreshKind, System, UpdateKind};
+
+        let pid = Pid::from_u32(process_info.pid as u32);
+        let mut system = System::new();
+        system.refresh_process_specifics(
+            pid,
+            ProcessRefreshKind::new()
+                .with_cmd(UpdateKind::Always)
+                .with_cwd(UpdateKind::Always)
+                .with_environ(UpdateKind::Always),
+        );
+        let process = system.process(pid);
+
+        let command = match &process_info.command_line {
+            Some(command_line) => crate::command_line::parse_command_line(command_line),
+            None => process.map(|p| p.cmd().to_vec()).unwrap_or_default(),
+        };
+        let working_directory = process_info.working_directory.clone().or_else(|| {
+            process
+                .and_then(|p| p.cwd())
+                .map(|cwd| cwd.to_string_lossy().to_string())
+        });
+        let env_vars = process
+            .map(|p| p.environ())
+            .filter(|environ| !environ.is_empty())
+            .map(parse_environ);
+
+        match working_directory {
+            Some(working_directory) if !command.is_empty() => {
+                self.save_restart_command(
+                    process_info.port,
+                    command,
+                    &working_directory,
+                    env_vars,
+                )?;
+                Ok(true)
+            }
+            _ => Ok(false),
+        }
+    }
+
+    /// Save a command with its whole environment, or with the `SAVED_ENV_VARS` of
+    /// port-kill's own when its environment is unknown
+    fn save_restart_command(
+        &mut self,
+        port: u16,
+        command_parts: Vec<String>,
+        working_directory: &str,
+        env_vars: Option<HashMap<String, String>>,
+    ) -> Result<()> {
+        let env_vars = env_vars.unwrap_or_else(|| {
+            log::warn!(
+                "Could not read the environment of the process on port {}; its restart gets only {} from port-kill's own (add others with --restart-env or --env-file)",
+                port,
+                SAVED_ENV_VARS.join(", ")
+            );
+            Self::get_relevant_env_vars()
+        });
+
+        let version = RestartVersion {
             command: command_parts,
             working_directory: working_directory.to_string(),
             env_vars,
-            last_restarted: chrono::Utc::now(),
+            last_used: chrono::Utc::now(),
         };
 
-        self.restart_info.insert(port, restart_info);
+        // Keep earlier distinct commands for this port so they can be picked later
+        match self.restart_info.get_mut(&port) {
+            Some(existing) => existing.replace_current(version),
+            None => {
+                self.restart_info.insert(
+                    port,
+                    RestartInfo {
+                        port,
+                        command: version.command,
+                        working_directory: version.working_directory,
+                        env_vars: version.env_vars,
+                        last_restarted: version.last_used,
+                        previous: Vec::new(),
+                        pre: None,
+                        post: None,
+                        terminal: None,
+                        clean_env: false,
+                        as_user: None,
+                        container: None,
+                    },
+                );
+            }
+        }
+        if let Some(info) = self.restart_info.get_mut(&port) {
+            info.terminal = TerminalContext::from_env(&info.env_vars);
+            info.container = None;
+        }
         self.save()?;
 
         log::info!("Saved restart info for port {}", port);
         Ok(())
     }
 
+    /// Save a Docker container so a restart starts it again through Docker
+    pub fn save_container_for_restart(
+        &mut self,
+        port: u16,
+        container: &ContainerTarget,
+    ) -> Result<()> {
+        let working_directory = container
+            .compose_working_dir
+            .clone()
+            .or_else(|| {
+                std::env::current_dir()
+                    .ok()
+                    .map(|d| d.to_string_lossy().to_string())
+            })
+            .unwrap_or_else(Self::get_home_dir);
+        self.save_restart_command(
+            port,
+            container.restart_command(),
+            &working_directory,
+            // docker runs the container with its own environment
+            Some(Self::get_relevant_env_vars()),
+        )?;
+        if let Some(info) = self.restart_info.get_mut(&port) {
+            info.container = Some(container.clone());
+        }
+        self.save()
+    }
+
     /// Save a process from history entry
     pub fn save_from_history_entry(&mut self, entry: &ProcessHistoryEntry) -> Result<()> {
         if let (Some(ref command_line), Some(ref working_directory)) =
             (&entry.command_line, &entry.working_directory)
         {
-            self.save_process_for_restart(entry.port, command_line, working_directory)?;
+            self.save_process_for_restart(entry.port, command_line, working_directory, None)?;
         }
         Ok(())
     }
 
     /// Restart a process on a specific port.
-    /// Returns the PID of the spawned process. A background reaper thread
-    /// ensures the child is waited on so it doesn't become a zombie.
+    /// Returns the PID of the spawned process, which runs detached from the
+    /// terminal with its output in `log_path(port)`.
     pub fn restart_port(&mut self, port: u16) -> Result<u32> {
-        let restart_info = self
-            .restart_info
-            .get(&port)
-            .ok_or_else(|| anyhow::anyhow!("No restart information found for port {}", port))?;
+        self.restart_port_with(port, &RestartOverrides::default())
+    }
 
-        log::info!(
-            "Restarting process on port {} with command: {:?}",
-            port,
-            restart_info.command
-        );
+    /// Restart a process on a specific port with one-off argument/env overrides
+    pub fn restart_port_with(&mut self, port: u16, overrides: &RestartOverrides) -> Result<u32> {
+        let state_dir = self.state_dir();
+        if overrides.in_tmux {
+            return self.launch(port, overrides, |info| spawn_in_tmux(info, &state_dir));
+        }
 
-        let mut child = self.execute_restart(restart_info)?;
-        let pid = child.id();
+        self.launch(port, overrides, |info| spawn_detached(info, &state_dir))
+    }
 
-        // Spawn a background thread to reap the child when it exits, preventing zombies.
-        // Without this, the child would remain in the process table as a defunct/zombie
-        // entry until port-kill itself exits — problematic in long-running modes like --guard.
-        thread::spawn(move || {
-            let _ = child.wait();
-        });
+    /// Launch the saved command for a port and hand back the child, for callers
+    /// that supervise it themselves (e.g. --keep-alive)
+    pub fn spawn_restart(&mut self, port: u16, overrides: &RestartOverrides) -> Result<Child> {
+        self.launch(port, overrides, execute_restart)
+    }
+
+    /// Run the pre hook, wait for the port to be free, start the command with
+    /// `start`, then schedule the post hook and record the restart
+    fn launch<T>(
+        &mut self,
+        port: u16,
+        overrides: &RestartOverrides,
+        start: impl FnOnce(&RestartInfo) -> Result<T>,
+    ) -> Result<T> {
+        let (started, post_hook) = self.launcher(port, overrides)?.run(start)?;
+        self.finish_launch(port, post_hook);
+        Ok(started)
+    }
 
+    /// What a restart of `port` needs, to run it away from the manager (e.g. several
+    /// ports at once); hand the result back to [`RestartManager::finish_launch`]
+    pub fn launcher(&self, port: u16, overrides: &RestartOverrides) -> Result<PortLauncher> {
+        let restart_info = self
+            .restart_info
+            .get(&port)
+            .ok_or_else(|| anyhow::anyhow!("No restart information found for port {}", port))?
+            .with_overrides(overrides)
+            .with_placeholders();
+        Ok(PortLauncher {
+            restart_info,
+            state_dir: self.state_dir(),
+            port_free_timeout: self.port_free_timeout,
+        })
+    }
+
+    /// Record that `port` was restarted and keep its post hook for `wait_for_hooks`
+    pub fn finish_launch(&mut self, port: u16, post_hook: Option<thread::JoinHandle<()>>) {
+        if let Some(handle) = post_hook {
+            self.pending_hooks.retain(|h| !h.is_finished());
+            self.pending_hooks.push(handle);
+        }
         if let Some(info) = self.restart_info.get_mut(&port) {
             info.last_restarted = chrono::Utc::now();
         }
         let _ = self.save();
+    }
+
+    /// Directory holding the restart history, logs and PID files
+    fn state_dir(&self) -> PathBuf {
+        self.restart_history_path
+            .parent()
+            .map(Path::to_path_buf)
+            .unwrap_or_default()
+    }
+
+    /// File that collects the output of detached restarts of `port`
+    pub fn log_path(&self, port: u16) -> PathBuf {
+        restart_log_path(&self.state_dir(), port)
+    }
+
+    /// PID of the last detached restart of `port`, if that process is still running
+    pub fn running_pid(&self, port: u16) -> Option<u32> {
+        let pid = fs::read_to_string(restart_pid_path(&self.state_dir(), port))
+            .ok()?
+            .trim()
+            .parse::<u32>()
+            .ok()?;
+        let mut system = sysinfo::System::new();
+        system
+            .refresh_process(sysinfo::Pid::from_u32(pid))
+            .then_some(pid)
+    }
+
+    /// Block until post-restart hooks started so far have finished, so one-shot
+    /// commands don't exit before their hooks run
+    pub fn wait_for_hooks(&mut self) {
+        for handle in self.pending_hooks.drain(..) {
+            let _ = handle.join();
+        }
+    }
 
-        Ok(pid)
+    /// Make a remembered command (index into `RestartInfo::versions`) the current one for a port
+    pub fn select_version(&mut self, port: u16, index: usize) -> Result<()> {
+        let info = self
+            .restart_info
+            .get_mut(&port)
+            .ok_or_else(|| anyhow::anyhow!("No restart information found for port {}", port))?;
+        if index == 0 {
+            return Ok(());
+        }
+        let version =
+            info.previous.get(index - 1).cloned().ok_or_else(|| {
+                anyhow::anyhow!("No saved command #{} for port {}", index + 1, port)
+            })?;
+        info.replace_current(version);
+        // Only keep the container when the chosen command is the one that starts it
+        if info
+            .container
+            .as_ref()
+            .is_some_and(|c| c.restart_command() != info.command)
+        {
+            info.container = None;
+        }
+        self.save()
     }
 
     /// Get restart info for a port
@@ -133,9 +977,132 @@ impl RestartManager {
         ports
     }
 
+    /// Entries that can no longer be restarted as saved, with the reason
+    pub fn stale_entries(&self) -> Vec<(u16, String)> {
+        let mut stale: Vec<(u16, String)> = self
+            .restart_info
+            .values()
+            .filter_map(|info| info.stale_reason().map(|reason| (info.port, reason)))
+            .collect();
+        stale.sort();
+        stale
+    }
+
+    /// Remove every stale entry, returning what was removed and why
+    pub fn prune_stale(&mut self) -> Result<Vec<(u16, String)>> {
+        let stale = self.stale_entries();
+        for (port, _) in &stale {
+            self.restart_info.remove(port);
+            let _ = fs::remove_file(restart_pid_path(&self.state_dir(), *port));
+        }
+        if !stale.is_empty() {
+            self.save()?;
+        }
+        Ok(stale)
+    }
+
+    /// Launchable entries, most recently saved or restarted first
+    pub fn recent_launchable(&self, limit: usize) -> Vec<&RestartInfo> {
+        let mut entries: Vec<&RestartInfo> = self
+            .restart_info
+            .values()
+            .filter(|info| !info.command.is_empty())
+            .collect();
+        entries.sort_by_key(|info| std::cmp::Reverse(info.last_restarted));
+        entries.truncate(limit);
+        entries
+    }
+
+    /// Ports whose saved entry still has a command to run
+    pub fn list_launchable_ports(&self) -> Vec<u16> {
+        let mut ports: Vec<u16> = self
+            .restart_info
+            .values()
+            .filter(|info| !info.command.is_empty())
+            .map(|info| info.port)
+            .collect();
+        ports.sort();
+        ports
+    }
+
+    /// Portable recipes for every launchable entry. Working directories under
+    /// `base` are written relative to it so the file works from another checkout.
+    pub fn export_recipes(&self, base: &Path) -> RestartRecipes {
+        let mut restarts: Vec<RestartRecipe> = self
+            .restart_info
+            .values()
+            .filter(|info| !info.command.is_empty())
+            .map(|info| RestartRecipe {
+                port: info.port,
+                command: info.command.clone(),
+                working_directory: relative_to(Path::new(&info.working_directory), base),
+                env: info
+                    .env_vars
+                    .iter()
+                    .filter(|(key, _)| RECIPE_ENV_VARS.contains(&key.as_str()))
+                    .map(|(key, value)| (key.clone(), value.clone()))
+                    .collect(),
+                pre: info.pre.clone(),
+                post: info.post.clone(),
+            })
+            .collect();
+        restarts.sort_by_key(|recipe| recipe.port);
+
+        RestartRecipes {
+            version: RECIPE_FORMAT_VERSION,
+            restarts,
+        }
+    }
+
+    /// Save recipes as restart entries, resolving relative working directories
+    /// against `base`. Existing entries for the same ports are kept as earlier
+    /// commands. Returns the imported ports.
+    pub fn import_recipes(&mut self, recipes: &RestartRecipes, base: &Path) -> Result<Vec<u16>> {
+        if recipes.version > RECIPE_FORMAT_VERSION {
+            return Err(anyhow::anyhow!(
+                "Restart recipes use format version {}, but this port-kill only understands version {}",
+                recipes.version,
+                RECIPE_FORMAT_VERSION
+            ));
+        }
+
+        let mut imported = Vec::new();
+        for recipe in &recipes.restarts {
+            if recipe.command.is_empty() {
+                log::warn!(
+                    "Skipping restart recipe for port {}: no command",
+                    recipe.port
+                );
+                continue;
+            }
+            // Absolute paths are kept as they are by `join`
+            let working_directory = match recipe.working_directory.as_str() {
+                "." | "" => base.to_path_buf(),
+                dir => base.join(dir),
+            };
+            let mut env_vars = Self::get_relevant_env_vars();
+            env_vars.extend(recipe.env.clone());
+
+            self.save_restart_command(
+                recipe.port,
+                recipe.command.clone(),
+                &working_directory.to_string_lossy(),
+                Some(env_vars),
+            )?;
+            if let Some(info) = self.restart_info.get_mut(&recipe.port) {
+                info.pre = recipe.pre.clone();
+                info.post = recipe.post.clone();
+            }
+            imported.push(recipe.port);
+        }
+        self.save()?;
+        Ok(imported)
+    }
+
     /// Clear restart info for a specific port
     pub fn clear_port(&mut self, port: u16) -> Result<()> {
         self.restart_info.remove(&port);
+        let _ = fs::remove_file(restart_pid_path(&self.state_dir(), port));
         self.save()
     }
 
@@ -147,64 +1114,56 @@ impl RestartManager {
 
     // Private methods
 
-    fn execute_restart(&self, restart_info: &RestartInfo) -> Result<Child> {
-        if restart_info.command.is_empty() {
-            return Err(anyhow::anyhow!("No command to execute"));
-        }
-
-        let program = &restart_info.command[0];
-        let args = &restart_info.command[1..];
-
-        let mut cmd = Command::new(program);
-        cmd.args(args)
-            .current_dir(&restart_info.working_directory)
-            .envs(&restart_info.env_vars);
+    /// Read the environment of a running process (/proc/<pid>/environ on Linux,
+    /// the process arguments area on macOS, the PEB on Windows).
+    /// Returns None when the process is gone or its environment is not readable
+    /// (e.g. it belongs to another user).
+    pub fn read_process_environment(pid: u32) -> Option<HashMap<String, String>> {
+        use sysinfo::{Pid, ProcessRefreshKind, System, UpdateKind};
 
-        // Spawn the process
-        let child = cmd
-            .spawn()
-            .context(format!("Failed to restart process: {}", program))?;
-
-        log::info!(
-            "Successfully spawned process with PID {} for port {}",
-            child.id(),
-            restart_info.port
-        );
-
-        Ok(child)
+        let pid = Pid::from_u32(pid);
+        let mut system = System::new();
+        if !system.refresh_process_specifics(
+            pid,
+            ProcessRefreshKind::new().with_environ(UpdateKind::Always),
+        ) {
+            return None;
+        }
+        let environ = system.process(pid)?.environ();
+        if environ.is_empty() {
+            return None;
+        }
+        Some(parse_environ(environ))
     }
 
     fn get_relevant_env_vars() -> HashMap<String, String> {
-        let mut env_vars = HashMap::new();
-
-        // Common development environment variables
-        let relevant_vars = [
-            "PATH",
-            "NODE_ENV",
-            "PYTHON_PATH",
-            "PYTHONPATH",
-            "GOPATH",
-            "CARGO_HOME",
-            "RUSTUP_HOME",
-            "DATABASE_URL",
-            "PORT",
-            "HOST",
-            "DEBUG",
-        ];
-
-        for var_name in &relevant_vars {
-            if let Ok(value) = std::env::var(var_name) {
-                env_vars.insert(var_name.to_string(), value);
-            }
-        }
-
-        env_vars
+        SAVED_ENV_VARS
+            .iter()
+            .filter_map(|key| Some((key.to_string(), std::env::var(key).ok()?)))
+            .collect()
     }
 
+    /// Write the restart history through a temporary file, so a crash never leaves it
+    /// half written. Only the owner can read it: the saved environments hold secrets.
     fn save(&self) -> Result<()> {
-        let json = serde_json::to_string_pretty(&self.restart_info)
+        let json = to_versioned_json(&self.restart_info, "restarts", RESTART_HISTORY_VERSION)
             .context("Failed to serialize restart info")?;
-        fs::write(&self.restart_history_path, json)
+        let tmp = self.restart_history_path.with_extension("json.tmp");
+        let _ = fs::remove_file(&tmp);
+        let mut options = fs::OpenOptions::new();
+        options.write(true).create_new(true);
+        #[cfg(not(target_os = "windows"))]
+        {
+            use std::os::unix::fs::OpenOptionsExt;
+            options.mode(0o600);
+        }
+        let mut file = options
+            .open(&tmp)
+            .context("Failed to write restart history file")?;
+        std::io::Write::write_all(&mut file, json.as_bytes())
+            .and_then(|()| file.sync_all())
+            .context("Failed to write restart history file")?;
+        fs::rename(&tmp, &self.restart_history_path)
             .context("Failed to write restart history file")?;
         Ok(())
     }
@@ -212,8 +1171,17 @@ impl RestartManager {
     fn load(&mut self) -> Result<()> {
         let json = fs::read_to_string(&self.restart_history_path)
             .context("Failed to read restart history file")?;
-        self.restart_info =
-            serde_json::from_str(&json).context("Failed to parse restart history file")?;
+        let history: Versioned<HashMap<u16, RestartInfo>> = parse_versioned(
+            &json,
+            "restarts",
+            RESTART_HISTORY_VERSION,
+            unchanged_payload,
+        )
+        .context("Failed to parse restart history file")?;
+        if history.version < RESTART_HISTORY_VERSION {
+            backup_old_format(&self.restart_history_path, history.version)?;
+        }
+        self.restart_info = history.data;
         Ok(())
     }
 
@@ -232,17 +1200,575 @@ impl RestartManager {
     }
 }
 
+/// One port's restart, prepared by [`RestartManager::launcher`]
+pub struct PortLauncher {
+    restart_info: RestartInfo,
+    state_dir: PathBuf,
+    port_free_timeout: Duration,
+}
+
+impl PortLauncher {
+    /// Launch detached, as `RestartManager::restart_port` does. Returns the PID and the
+    /// post hook still running.
+    pub fn run_detached(self) -> Result<(u32, Option<thread::JoinHandle<()>>)> {
+        let state_dir = self.state_dir.clone();
+        self.run(|info| spawn_detached(info, &state_dir))
+    }
+
+    /// Run the pre hook, wait for the port to be free, start the command with `start`,
+    /// record the restart in the history and start the post hook
+    fn run<T>(
+        self,
+        start: impl FnOnce(&RestartInfo) -> Result<T>,
+    ) -> Result<(T, Option<thread::JoinHandle<()>>)> {
+        let restart_info = self.restart_info;
+        let port = restart_info.port;
+
+        log::info!(
+            "Restarting process on port {} with command: {:?}",
+            port,
+            restart_info.command
+        );
+
+        if let Some(pre) = &restart_info.pre {
+            run_hook("pre-restart", pre, &restart_info)?;
+        }
+
+        // The old process may still be shutting down; launching now would hit EADDRINUSE
+        if !wait_for_port_free(port, self.port_free_timeout) {
+            return Err(anyhow::anyhow!(
+                "Port {} is still in use after waiting {}s for the previous process to release it; \
+                 not starting {:?} (use --restart-timeout to wait longer)",
+                port,
+                self.port_free_timeout.as_secs(),
+                restart_info.command.join(" ")
+            ));
+        }
+
+        let started = start(&restart_info)?;
+
+        let recorded = HistoryStore::open(&self.state_dir.join("history.db")).and_then(|store| {
+            store.record_restart(
+                port,
+                &restart_info.command,
+                &restart_info.working_directory,
+            )
+        });
+        if let Err(e) = recorded {
+            log::warn!("Failed to save restart to history: {}", e);
+        }
+
+        // Wait for the server in the background so callers are not blocked
+        let post_hook = restart_info.post.clone().map(|post| {
+            thread::spawn(move || {
+                if !wait_for_listening(restart_info.port, POST_HOOK_PORT_TIMEOUT) {
+                    log::warn!(
+                        "Port {} did not accept connections within {:?}; running post-restart hook anyway",
+                        restart_info.port,
+                        POST_HOOK_PORT_TIMEOUT
+                    );
+                }
+                if let Err(e) = run_hook("post-restart", &post, &restart_info) {
+                    log::warn!("{}", e);
+                }
+            })
+        });
+
+        Ok((started, post_hook))
+    }
+}
+
+/// Run a restart hook through the shell in the entry's working directory and environment.
+/// `PORT_KILL_PORT` is set to the port being restarted.
+fn run_hook(kind: &str, hook: &str, info: &RestartInfo) -> Result<()> {
+    log::info!("Running {} hook for port {}: {}", kind, info.port, hook);
+
+    #[cfg(target_os = "windows")]
+    let mut cmd = {
+        let mut cmd = Command::new("cmd");
+        cmd.args(["/C", hook]);
+        cmd
+    };
+    #[cfg(not(target_os = "windows"))]
+    let mut cmd = {
+        let mut cmd = Command::new("sh");
+        cmd.args(["-c", hook]);
+        cmd
+    };
+
+    let status = cmd
+        .current_dir(&info.working_directory)
+        .envs(&info.env_vars)
+        .env("PORT_KILL_PORT", info.port.to_string())
+        .status()
+        .with_context(|| format!("Failed to run {} hook: {}", kind, hook))?;
+    if !status.success() {
+        return Err(anyhow::anyhow!(
+            "{} hook for port {} failed ({}): {}",
+            kind,
+            info.port,
+            status,
+            hook
+        ));
+    }
+    Ok(())
+}
+
+/// Poll until something accepts TCP connections on localhost:`port`
+fn wait_for_listening(port: u16, timeout: Duration) -> bool {
+    let addr = std::net::SocketAddr::from(([127, 0, 0, 1], port));
+    let deadline = std::time::Instant::now() + timeout;
+    while std::time::Instant::now() < deadline {
+        if std::net::TcpStream::connect_timeout(&addr, Duration::from_millis(200)).is_ok() {
+            return true;
+        }
+        thread::sleep(Duration::from_millis(250));
+    }
+    false
+}
+
+/// The saved command, ready to spawn in its working directory and environment
+fn restart_command(restart_info: &RestartInfo) -> Result<Command> {
+    let argv = launch_argv(restart_info)?;
+    let (program, args) = argv
+        .split_first()
+        .ok_or_else(|| anyhow::anyhow!("No command to execute"))?;
+
+    let mut cmd = Command::new(program);
+    cmd.args(args).current_dir(&restart_info.working_directory);
+    if restart_info.clean_env {
+        cmd.env_clear();
+    }
+    cmd.envs(launch_env(restart_info));
+    Ok(cmd)
+}
+
+/// Variables that describe the account; the target account's own values are kept
+/// when switching users
+const ACCOUNT_ENV_VARS: &[&str] = &["HOME", "USER", "LOGNAME"];
+
+/// Account `as_user` names when it is someone other than the current user
+fn other_user(restart_info: &RestartInfo) -> Option<&str> {
+    restart_info
+        .as_user
+        .as_deref()
+        .filter(|user| !is_current_user(user))
+}
+
+/// The saved environment to launch with, without the current account's
+/// HOME/USER/LOGNAME when the command runs as someone else
+fn launch_env(restart_info: &RestartInfo) -> Vec<(&String, &String)> {
+    let switching = other_user(restart_info).is_some();
+    let mut env: Vec<_> = restart_info
+        .env_vars
+        .iter()
+        .filter(|(key, _)| !(switching && ACCOUNT_ENV_VARS.contains(&key.as_str())))
+        .collect();
+    env.sort();
+    env
+}
+
+/// The saved command, wrapped to switch accounts when `as_user` names someone
+/// other than the current user
+fn launch_argv(restart_info: &RestartInfo) -> Result<Vec<String>> {
+    if restart_info.command.is_empty() {
+        return Err(anyhow::anyhow!("No command to execute"));
+    }
+    let Some(user) = other_user(restart_info) else {
+        return Ok(restart_info.command.clone());
+    };
+    check_can_run_as(user, restart_info)?;
+    Ok(sudo_argv(user, restart_info, &restart_info.command))
+}
+
+/// `sudo -n -u <user> --preserve-env=<names> -- <command>`, with the saved
+/// restart's variables and the given command. sudo resets the
+/// environment, so the saved variables are set on sudo's own environment and
+/// --preserve-env passes them through. Only their names go on the command line,
+/// since argv is visible to every user through ps.
+fn sudo_argv(user: &str, restart_info: &RestartInfo, command: &[String]) -> Vec<String> {
+    let names: Vec<&str> = launch_env(restart_info)
+        .into_iter()
+        .map(|(key, _)| key.as_str())
+        .filter(|key| !key.contains(','))
+        .collect();
+    let mut argv = vec![
+        "sudo".to_string(),
+        "-n".to_string(),
+        "-u".to_string(),
+        user.to_string(),
+    ];
+    if !names.is_empty() {
+        argv.push(format!("--preserve-env={}", names.join(",")));
+    }
+    argv.push("--".to_string());
+    argv.extend(command.iter().cloned());
+    argv
+}
+
+fn is_current_user(user: &str) -> bool {
+    std::env::var("USER")
+        .or_else(|_| std::env::var("USERNAME"))
+        .map(|current| current == user)
+        .unwrap_or(false)
+}
+
+/// Make sure switching to `user` will work without prompting, with an
+/// explanation of what to fix when it won't. Runs `true` through the same sudo
+/// command line and environment as the restart, so a sudoers rule that refuses
+/// --preserve-env fails here rather than in the detached process's log.
+fn check_can_run_as(user: &str, restart_info: &RestartInfo) -> Result<()> {
+    #[cfg(not(target_os = "windows"))]
+    {
+        let argv = sudo_argv(user, restart_info, &["true".to_string()]);
+        let mut probe = Command::new(&argv[0]);
+        probe.args(&argv[1..]);
+        if restart_info.clean_env {
+            probe.env_clear();
+        }
+        let output = match probe.envs(launch_env(restart_info)).output() {
+            Ok(output) => output,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                return Err(anyhow::anyhow!(
+                    "--as-user {} needs sudo, which is not installed",
+                    user
+                ))
+            }
+            Err(e) => return Err(anyhow::anyhow!("Failed to run sudo: {}", e)),
+        };
+        if output.status.success() {
+            return Ok(());
+        }
+        let stderr = String::from_utf8_lossy(&output.stderr).trim().to_string();
+        if stderr.contains("unknown user") {
+            Err(anyhow::anyhow!(
+                "Cannot restart as '{}': no such user",
+                user
+            ))
+        } else if stderr.contains("preserve the environment") {
+            Err(anyhow::anyhow!(
+                "Cannot restart as '{}': sudo won't pass the saved environment through. \
+                 Allow it in sudoers with the SETENV tag (e.g. NOPASSWD:SETENV:)",
+                user
+            ))
+        } else if stderr.contains("password is required") {
+            Err(anyhow::anyhow!(
+                "Cannot restart as '{}': sudo needs a password. Run `sudo -v` first, \
+                 or allow it without one in sudoers (NOPASSWD)",
+                user
+            ))
+        } else {
+            Err(anyhow::anyhow!(
+                "Cannot restart as '{}': not allowed to run commands as that user ({})",
+                user,
+                stderr
+            ))
+        }
+    }
+    #[cfg(target_os = "windows")]
+    {
+        let _ = restart_info;
+        // runas would have to prompt for the password, and restarts run detached
+        Err(anyhow::anyhow!(
+            "Cannot restart as '{}': --as-user is not supported on Windows",
+            user
+        ))
+    }
+}
+
+/// Start the command as a child of port-kill that shares its terminal, for
+/// callers that supervise it in the foreground (--keep-alive, --watch)
+fn execute_restart(restart_info: &RestartInfo) -> Result<Child> {
+    let child = restart_command(restart_info)?
+        .spawn()
+        .with_context(|| format!("Failed to restart process: {}", restart_info.command[0]))?;
+
+    log::info!(
+        "Successfully spawned process with PID {} for port {}",
+        child.id(),
+        restart_info.port
+    );
+
+    Ok(child)
+}
+
+/// Start the command detached from port-kill and its terminal: in a new session
+/// (Unix) or without a console (Windows), with output appended to the port's log
+/// file and its PID recorded, so it outlives the terminal and can be found later
+fn spawn_detached(restart_info: &RestartInfo, state_dir: &Path) -> Result<u32> {
+    let port = restart_info.port;
+    let log_path = restart_log_path(state_dir, port);
+    let pid_path = restart_pid_path(state_dir, port);
+    for dir in [log_path.parent(), pid_path.parent()].into_iter().flatten() {
+        fs::create_dir_all(dir).with_context(|| format!("Failed to create {}", dir.display()))?;
+    }
+    let log = fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&log_path)
+        .with_context(|| format!("Failed to open {}", log_path.display()))?;
+
+    let mut cmd = restart_command(restart_info)?;
+    cmd.stdin(Stdio::null())
+        .stdout(log.try_clone()?)
+        .stderr(log);
+
+    #[cfg(not(target_os = "windows"))]
+    {
+        use std::os::unix::process::CommandExt;
+        // SAFETY: setsid is async-signal-safe and only affects the forked child
+        unsafe {
+            cmd.pre_exec(|| {
+                nix::unistd::setsid()
+                    .map(|_| ())
+                    .map_err(std::io::Error::from)
+            });
+        }
+    }
+    #[cfg(target_os = "windows")]
+    {
+        use std::os::windows::process::CommandExt;
+        const DETACHED_PROCESS: u32 = 0x0000_0008;
+        const CREATE_NEW_PROCESS_GROUP: u32 = 0x0000_0200;
+        cmd.creation_flags(DETACHED_PROCESS | CREATE_NEW_PROCESS_GROUP);
+    }
+
+    let mut child = cmd
+        .spawn()
+        .with_context(|| format!("Failed to restart process: {}", restart_info.command[0]))?;
+    let pid = child.id();
+
+    if let Err(e) = fs::write(&pid_path, format!("{}\n", pid)) {
+        log::warn!("Failed to write PID file {}: {}", pid_path.display(), e);
+    }
+
+    // Reap the child when it exits so it doesn't linger as a zombie while
+    // port-kill keeps running (e.g. --guard or the tray)
+    thread::spawn(move || {
+        let _ = child.wait();
+    });
+
+    log::info!(
+        "Started detached process with PID {} for port {} (output: {})",
+        pid,
+        port,
+        log_path.display()
+    );
+    Ok(pid)
+}
+
+fn restart_log_path(state_dir: &Path, port: u16) -> PathBuf {
+    state_dir.join("logs").join(format!("port-{}.log", port))
+}
+
+fn restart_pid_path(state_dir: &Path, port: u16) -> PathBuf {
+    state_dir.join("pids").join(format!("port-{}.pid", port))
+}
+
+/// Start the command in a new window of the tmux session recorded for it, so its
+/// output stays visible. Returns the PID of the process in the new pane.
+///
+/// The environment goes through a file only the user can read, which the window
+/// sources and deletes, rather than `new-window -e`: tmux arguments are visible to
+/// every user through ps.
+fn spawn_in_tmux(restart_info: &RestartInfo, state_dir: &Path) -> Result<u32> {
+    let terminal = restart_info
+        .terminal
+        .as_ref()
+        .filter(|t| t.is_tmux())
+        .ok_or_else(|| anyhow::anyhow!("Port {} was not started inside tmux", restart_info.port))?;
+    let session = terminal.tmux_session.as_deref().unwrap_or_default();
+
+    let mut cmd = terminal.tmux_command();
+    cmd.args(["new-window", "-d", "-P", "-F", "#{pane_pid}"])
+        .args(["-t", &format!("{}:", session)])
+        .args(["-n", &format!(":{}", restart_info.port)])
+        .args(["-c", &restart_info.working_directory]);
+    let env_path = write_tmux_env_file(restart_info, state_dir)?;
+    let env_file =
+        crate::command_line::quote_command_line(&[env_path.to_string_lossy().to_string()]);
+    let script = format!(
+        ". {file}; rm -f {file}; exec {command}",
+        file = env_file,
+        command = crate::command_line::quote_command_line(&launch_argv(restart_info)?)
+    );
+    cmd.arg(crate::command_line::quote_command_line(&[
+        "sh".to_string(),
+        "-c".to_string(),
+        script,
+    ]));
+
+    let output = cmd.output().context("Failed to run tmux");
+    if !output.as_ref().is_ok_and(|output| output.status.success()) {
+        // No window started, so nothing else will delete the file
+        let _ = fs::remove_file(&env_path);
+    }
+    let output = output?;
+    if !output.status.success() {
+        return Err(anyhow::anyhow!(
+            "tmux could not open a window in session {}: {}",
+            session,
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    let pid = String::from_utf8_lossy(&output.stdout)
+        .trim()
+        .parse::<u32>()
+        .context("Unexpected output from tmux new-window")?;
+
+    log::info!(
+        "Started process with PID {} for port {} in tmux session {}",
+        pid,
+        restart_info.port,
+        session
+    );
+    Ok(pid)
+}
+
+/// Write the launch environment as `export KEY='value'` lines to a file only the
+/// current user can read, for `spawn_in_tmux`
+fn write_tmux_env_file(restart_info: &RestartInfo, state_dir: &Path) -> Result<PathBuf> {
+    let dir = state_dir.join("pids");
+    fs::create_dir_all(&dir).with_context(|| format!("Failed to create {}", dir.display()))?;
+    let path = dir.join(format!("port-{}.tmux-env", restart_info.port));
+    let mut exports = String::new();
+    for (key, value) in launch_env(restart_info) {
+        // The new pane gets its own tmux variables; other names can't be exported
+        let valid = !key.is_empty()
+            && !key.starts_with(|c: char| c.is_ascii_digit())
+            && key.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+        if valid && key != "TMUX" && key != "TMUX_PANE" {
+            exports.push_str(&format!(
+                "export {}={}\n",
+                key,
+                crate::command_line::quote_command_line(std::slice::from_ref(value))
+            ));
+        }
+    }
+
+    let _ = fs::remove_file(&path);
+    let mut options = fs::OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(not(target_os = "windows"))]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    let mut file = options
+        .open(&path)
+        .with_context(|| format!("Failed to create {}", path.display()))?;
+    std::io::Write::write_all(&mut file, exports.as_bytes())
+        .with_context(|| format!("Failed to write {}", path.display()))?;
+    Ok(path)
+}
+
+/// Poll until `port` is free, giving up after `timeout`
+fn wait_for_port_free(port: u16, timeout: Duration) -> bool {
+    let deadline = std::time::Instant::now() + timeout;
+    loop {
+        if crate::port_wait::is_port_free(port) {
+            return true;
+        }
+        if std::time::Instant::now() >= deadline {
+            return false;
+        }
+        thread::sleep(Duration::from_millis(100));
+    }
+}
+
+/// Whether `program` can be run: paths are checked directly (relative ones
+/// against `working_directory`), bare names are looked up in `path_var`
+fn program_exists(program: &str, working_directory: &Path, path_var: &str) -> bool {
+    let program_path = Path::new(program);
+    if program_path.components().count() > 1 || program_path.is_absolute() {
+        return working_directory.join(program_path).exists();
+    }
+
+    #[cfg(target_os = "windows")]
+    let candidates = [
+        program.to_string(),
+        format!("{}.exe", program),
+        format!("{}.cmd", program),
+        format!("{}.bat", program),
+    ];
+    #[cfg(not(target_os = "windows"))]
+    let candidates = [program.to_string()];
+
+    std::env::split_paths(path_var).any(|dir| {
+        candidates
+            .iter()
+            .any(|candidate| dir.join(candidate).is_file())
+    })
+}
+
+/// `path` relative to `base` when it lies inside it (`.` for `base` itself),
+/// otherwise unchanged
+fn relative_to(path: &Path, base: &Path) -> String {
+    match path.strip_prefix(base) {
+        Ok(rel) if rel.as_os_str().is_empty() => ".".to_string(),
+        Ok(rel) => rel.to_string_lossy().to_string(),
+        Err(_) => path.to_string_lossy().to_string(),
+    }
+}
+
+/// Read a dotenv-style file: `KEY=VALUE` lines, optionally prefixed with
+/// `export`, with `#` comments and quoted values
+pub fn parse_env_file(path: &Path) -> Result<HashMap<String, String>> {
+    let contents = fs::read_to_string(path)
+        .with_context(|| format!("Failed to read env file {}", path.display()))?;
+
+    let mut vars = HashMap::new();
+    for (number, line) in contents.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let line = line.strip_prefix("export ").unwrap_or(line);
+        let (key, value) = line
+            .split_once('=')
+            .filter(|(key, _)| !key.trim().is_empty())
+            .ok_or_else(|| {
+                anyhow::anyhow!(
+                    "{}:{}: expected KEY=VALUE, found '{}'",
+                    path.display(),
+                    number + 1,
+                    line
+                )
+            })?;
+        let value = value.trim();
+        let value = ['"', '\'']
+            .iter()
+            .find_map(|q| value.strip_prefix(*q).and_then(|v| v.strip_suffix(*q)))
+            .unwrap_or(value);
+        vars.insert(key.trim().to_string(), value.to_string());
+    }
+    Ok(vars)
+}
+
+/// Turn `KEY=value` strings into a map, skipping malformed entries
+fn parse_environ(environ: &[String]) -> HashMap<String, String> {
+    environ
+        .iter()
+        .filter_map(|kv| kv.split_once('='))
+        .filter(|(key, _)| !key.is_empty())
+        .map(|(key, value)| (key.to_string(), value.to_string()))
+        .collect()
+}
+
 impl Default for RestartManager {
     fn default() -> Self {
         Self::new().unwrap_or_else(|_| Self {
             restart_history_path: Self::get_default_path(),
             restart_info: HashMap::new(),
+            pending_hooks: Vec::new(),
+            port_free_timeout: DEFAULT_PORT_FREE_TIMEOUT,
         })
     }
 }
 
 #[cfg(test)]
 mod tests {
+    use super::*;
     use crate::command_line::parse_command_line;
 
     #[test]
@@ -261,4 +1787,520 @@ mod tests {
             vec!["node", "my script.js", "--arg", "value with spaces"]
         );
     }
+
+    #[test]
+    fn test_parse_environ() {
+        let environ = vec![
+            "PORT=3000".to_string(),
+            "DATABASE_URL=postgres://u:p@localhost/db?a=b".to_string(),
+            "garbage".to_string(),
+        ];
+        let env = parse_environ(&environ);
+        assert_eq!(env.len(), 2);
+        assert_eq!(env["PORT"], "3000");
+        assert_eq!(env["DATABASE_URL"], "postgres://u:p@localhost/db?a=b");
+    }
+
+    #[cfg(any(target_os = "linux", target_os = "macos"))]
+    #[test]
+    fn test_read_process_environment_self() {
+        let env = RestartManager::read_process_environment(std::process::id());
+        assert!(env.is_some_and(|env| !env.is_empty()));
+    }
+
+    #[test]
+    fn test_with_overrides() {
+        let info = RestartInfo {
+            port: 3000,
+            command: vec!["npm".to_string(), "run".to_string(), "dev".to_string()],
+            working_directory: "/tmp".to_string(),
+            env_vars: HashMap::from([
+                ("PORT".to_string(), "3000".to_string()),
+                ("NODE_ENV".to_string(), "development".to_string()),
+            ]),
+            last_restarted: chrono::Utc::now(),
+            pre: Some("make assets".to_string()),
+            ..Default::default()
+        };
+        let overrides = RestartOverrides {
+            extra_args: vec!["--port".to_string(), "3001".to_string()],
+            env: HashMap::from([("PORT".to_string(), "3001".to_string())]),
+            pre: None,
+            post: Some("curl -f localhost:3001/health".to_string()),
+            in_tmux: false,
+            clean_env: false,
+            as_user: None,
+        };
+
+        let tweaked = info.with_overrides(&overrides);
+        assert_eq!(tweaked.command, vec!["npm", "run", "dev", "--port", "3001"]);
+        assert_eq!(tweaked.env_vars["PORT"], "3001");
+        assert_eq!(tweaked.env_vars["NODE_ENV"], "development");
+        assert_eq!(tweaked.pre.as_deref(), Some("make assets"));
+        assert_eq!(
+            tweaked.post.as_deref(),
+            Some("curl -f localhost:3001/health")
+        );
+        assert_eq!(info.command.len(), 3);
+    }
+
+    #[test]
+    fn test_next_keep_alive_backoff() {
+        let quick = Duration::from_secs(2);
+        let mut backoff = next_keep_alive_backoff(None, quick);
+        assert_eq!(backoff, KEEP_ALIVE_INITIAL_BACKOFF);
+        backoff = next_keep_alive_backoff(Some(backoff), quick);
+        assert_eq!(backoff, Duration::from_secs(2));
+        backoff = next_keep_alive_backoff(Some(Duration::from_secs(40)), quick);
+        assert_eq!(backoff, KEEP_ALIVE_MAX_BACKOFF);
+        backoff = next_keep_alive_backoff(Some(backoff), Duration::from_secs(300));
+        assert_eq!(backoff, KEEP_ALIVE_INITIAL_BACKOFF);
+    }
+
+    fn version(command: &str, cwd: &str) -> RestartVersion {
+        RestartVersion {
+            command: parse_command_line(command),
+            working_directory: cwd.to_string(),
+            env_vars: HashMap::new(),
+            last_used: chrono::Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_replace_current_keeps_distinct_versions() {
+        let first = version("npm run dev", "/work/app-a");
+        let mut info = RestartInfo {
+            port: 3000,
+            command: first.command.clone(),
+            working_directory: first.working_directory.clone(),
+            last_restarted: first.last_used,
+            ..Default::default()
+        };
+
+        info.replace_current(version("npm run dev", "/work/app-b"));
+        info.replace_current(version("npm run dev", "/work/app-b"));
+        assert_eq!(info.working_directory, "/work/app-b");
+        assert_eq!(info.previous.len(), 1);
+        assert_eq!(info.previous[0].working_directory, "/work/app-a");
+
+        // Bringing back an older command swaps it with the current one
+        info.replace_current(info.previous[0].clone());
+        assert_eq!(info.working_directory, "/work/app-a");
+        assert_eq!(info.previous.len(), 1);
+        assert_eq!(info.previous[0].working_directory, "/work/app-b");
+
+        for i in 0..10 {
+            info.replace_current(version("cargo run", &format!("/work/{}", i)));
+        }
+        assert_eq!(info.previous.len(), MAX_PREVIOUS_VERSIONS);
+        assert_eq!(info.versions().len(), MAX_PREVIOUS_VERSIONS + 1);
+    }
+
+    #[test]
+    fn test_is_ignored_watch_path() {
+        use std::path::Path;
+        assert!(!is_ignored_watch_path(Path::new("src/main.rs")));
+        assert!(is_ignored_watch_path(Path::new(
+            "node_modules/react/index.js"
+        )));
+        assert!(is_ignored_watch_path(Path::new("/repo/.git/index")));
+        assert!(is_ignored_watch_path(Path::new("src/.main.rs.swp")));
+        assert!(!is_ignored_watch_path(Path::new("src/targets.rs")));
+
+        let roots = [PathBuf::from("/home/me/build/shop")];
+        let changed = Path::new("/home/me/build/shop/src/main.rs");
+        assert_eq!(watch_relative_path(changed, &roots), Path::new("src/main.rs"));
+        assert!(!is_ignored_watch_path(watch_relative_path(changed, &roots)));
+        let output = Path::new("/home/me/build/shop/build/app.js");
+        assert!(is_ignored_watch_path(watch_relative_path(output, &roots)));
+    }
+
+    #[cfg(not(target_os = "windows"))]
+    #[test]
+    fn test_run_hook() {
+        let info = RestartInfo {
+            port: 3000,
+            command: vec!["true".to_string()],
+            working_directory: "/".to_string(),
+            last_restarted: chrono::Utc::now(),
+            ..Default::default()
+        };
+        assert!(run_hook("pre-restart", "test \"$PORT_KILL_PORT\" = 3000", &info).is_ok());
+        assert!(run_hook("pre-restart", "exit 3", &info).is_err());
+    }
+
+    #[test]
+    fn test_display_name() {
+        let mut info = RestartInfo {
+            port: 3000,
+            command: parse_command_line("/usr/local/bin/node /work/app/server.js --port 3000"),
+            working_directory: "/work/app".to_string(),
+            last_restarted: chrono::Utc::now(),
+            ..Default::default()
+        };
+        assert_eq!(info.display_name(), "node server.js");
+        info.command = parse_command_line("python3 -m http.server");
+        assert_eq!(info.display_name(), "python3");
+    }
+
+    #[test]
+    fn test_wait_for_port_free() {
+        let listener = std::net::TcpListener::bind(("127.0.0.1", 0)).unwrap();
+        let port = listener.local_addr().unwrap().port();
+        assert!(!wait_for_port_free(port, Duration::from_millis(300)));
+        drop(listener);
+        assert!(wait_for_port_free(port, Duration::from_secs(2)));
+    }
+
+    #[test]
+    fn test_recipes_round_trip() {
+        let dir = std::env::temp_dir().join(format!("port-kill-recipes-{}", std::process::id()));
+        let mut manager = RestartManager {
+            restart_history_path: dir.join("restart-history.json"),
+            restart_info: HashMap::new(),
+            pending_hooks: Vec::new(),
+            port_free_timeout: DEFAULT_PORT_FREE_TIMEOUT,
+        };
+        fs::create_dir_all(&dir).unwrap();
+
+        let env = HashMap::from([
+            ("NODE_ENV".to_string(), "development".to_string()),
+            ("AWS_SECRET_ACCESS_KEY".to_string(), "hunter2".to_string()),
+        ]);
+        manager
+            .save_process_for_restart(3000, "npm run dev", "/work/shop/web", Some(env))
+            .unwrap();
+
+        // The private restart history keeps secrets, shared recipes leave them out
+        let saved = &manager.get_restart_info(3000).unwrap().env_vars;
+        assert_eq!(saved["AWS_SECRET_ACCESS_KEY"], "hunter2");
+
+        let recipes = manager.export_recipes(Path::new("/work/shop"));
+        assert_eq!(recipes.restarts.len(), 1);
+        let recipe = &recipes.restarts[0];
+        assert_eq!(recipe.working_directory, "web");
+        assert_eq!(recipe.env.len(), 1);
+        assert_eq!(recipe.env["NODE_ENV"], "development");
+
+        manager.restart_info.clear();
+        let imported = manager
+            .import_recipes(&recipes, Path::new("/home/me/shop"))
+            .unwrap();
+        assert_eq!(imported, vec![3000]);
+        let info = manager.get_restart_info(3000).unwrap();
+        assert_eq!(info.command, vec!["npm", "run", "dev"]);
+        assert_eq!(info.working_directory, "/home/me/shop/web");
+        assert_eq!(info.env_vars["NODE_ENV"], "development");
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_terminal_context_from_env() {
+        let env = HashMap::from([
+            (
+                "TMUX".to_string(),
+                "/tmp/tmux-1000/default,4242,2".to_string(),
+            ),
+            ("TMUX_PANE".to_string(), "%7".to_string()),
+            ("TERM_PROGRAM".to_string(), "tmux".to_string()),
+        ]);
+        let terminal = TerminalContext::from_env(&env).unwrap();
+        assert!(terminal.is_tmux());
+        assert_eq!(
+            terminal.tmux_socket.as_deref(),
+            Some("/tmp/tmux-1000/default")
+        );
+        assert_eq!(terminal.tmux_session.as_deref(), Some("$2"));
+        assert_eq!(terminal.tmux_pane.as_deref(), Some("%7"));
+
+        let env = HashMap::from([("TERM_PROGRAM".to_string(), "vscode".to_string())]);
+        let terminal = TerminalContext::from_env(&env).unwrap();
+        assert!(!terminal.is_tmux());
+        assert_eq!(terminal.describe(), "vscode");
+
+        assert!(TerminalContext::from_env(&HashMap::new()).is_none());
+    }
+
+    #[cfg(not(target_os = "windows"))]
+    #[test]
+    fn test_spawn_detached_logs_output() {
+        let dir = std::env::temp_dir().join(format!("port-kill-detached-{}", std::process::id()));
+        let info = RestartInfo {
+            port: 3999,
+            command: vec![
+                "sh".to_string(),
+                "-c".to_string(),
+                "echo started".to_string(),
+            ],
+            working_directory: std::env::temp_dir().to_string_lossy().to_string(),
+            last_restarted: chrono::Utc::now(),
+            ..Default::default()
+        };
+
+        let pid = spawn_detached(&info, &dir).unwrap();
+        let recorded = fs::read_to_string(restart_pid_path(&dir, 3999)).unwrap();
+        assert_eq!(recorded.trim(), pid.to_string());
+
+        let log_path = restart_log_path(&dir, 3999);
+        let deadline = std::time::Instant::now() + Duration::from_secs(5);
+        while !fs::read_to_string(&log_path)
+            .unwrap_or_default()
+            .contains("started")
+        {
+            assert!(
+                std::time::Instant::now() < deadline,
+                "no output in {}",
+                log_path.display()
+            );
+            thread::sleep(Duration::from_millis(50));
+        }
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_parse_env_file() {
+        let path = std::env::temp_dir().join(format!("port-kill-env-{}", std::process::id()));
+        fs::write(
+            &path,
+            "# staging\nNODE_ENV=staging\nexport API_URL=\"https://api.example.com\"\n\nGREETING='hi there'\n",
+        )
+        .unwrap();
+        let vars = parse_env_file(&path).unwrap();
+        assert_eq!(vars.len(), 3);
+        assert_eq!(vars["NODE_ENV"], "staging");
+        assert_eq!(vars["API_URL"], "https://api.example.com");
+        assert_eq!(vars["GREETING"], "hi there");
+
+        fs::write(&path, "NODE_ENV=staging\noops\n").unwrap();
+        let err = parse_env_file(&path).unwrap_err().to_string();
+        assert!(err.contains(":2:"), "{}", err);
+        let _ = fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_save_keeps_the_whole_environment_private() {
+        let dir = std::env::temp_dir().join(format!("port-kill-restart-save-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("restart-history.json");
+        fs::write(&path, "{}").unwrap();
+        let mut manager = RestartManager {
+            restart_history_path: path.clone(),
+            restart_info: HashMap::new(),
+            pending_hooks: Vec::new(),
+            port_free_timeout: DEFAULT_PORT_FREE_TIMEOUT,
+        };
+        let env_vars = HashMap::from([
+            ("DATABASE_URL".to_string(), "postgres://localhost/app".to_string()),
+            ("PORT".to_string(), "3000".to_string()),
+        ]);
+        manager
+            .save_process_for_restart(3000, "npm run dev", "/work/app", Some(env_vars.clone()))
+            .unwrap();
+
+        assert_eq!(manager.get_restart_info(3000).unwrap().env_vars, env_vars);
+        assert!(!path.with_extension("json.tmp").exists());
+        #[cfg(not(target_os = "windows"))]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            assert_eq!(fs::metadata(&path).unwrap().permissions().mode() & 0o777, 0o600);
+        }
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_clean_env_override() {
+        let info = RestartInfo {
+            port: 3000,
+            command: parse_command_line("npm run dev"),
+            working_directory: "/work/app".to_string(),
+            env_vars: HashMap::from([
+                ("PATH".to_string(), "/usr/bin".to_string()),
+                ("AWS_PROFILE".to_string(), "prod".to_string()),
+            ]),
+            last_restarted: chrono::Utc::now(),
+            ..Default::default()
+        };
+        let overrides = RestartOverrides {
+            env: HashMap::from([("NODE_ENV".to_string(), "staging".to_string())]),
+            clean_env: true,
+            ..Default::default()
+        };
+        let clean = info.with_overrides(&overrides);
+        assert!(clean.clean_env);
+        assert_eq!(clean.env_vars["PATH"], "/usr/bin");
+        assert_eq!(clean.env_vars["NODE_ENV"], "staging");
+        assert!(!clean.env_vars.contains_key("AWS_PROFILE"));
+    }
+
+    #[cfg(not(target_os = "windows"))]
+    #[test]
+    fn test_launch_argv_for_current_user_is_unwrapped() {
+        let info = RestartInfo {
+            port: 3000,
+            command: parse_command_line("npm run dev"),
+            working_directory: "/work/app".to_string(),
+            last_restarted: chrono::Utc::now(),
+            as_user: std::env::var("USER").ok(),
+            ..Default::default()
+        };
+        assert_eq!(launch_argv(&info).unwrap(), vec!["npm", "run", "dev"]);
+    }
+
+    #[cfg(not(target_os = "windows"))]
+    #[test]
+    fn test_tmux_env_file() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = std::env::temp_dir().join(format!("port-kill-tmux-env-{}", std::process::id()));
+        let info = RestartInfo {
+            port: 3000,
+            env_vars: HashMap::from([
+                ("DATABASE_URL".to_string(), "postgres://u:it's@db/app".to_string()),
+                ("TMUX_PANE".to_string(), "%3".to_string()),
+                ("BAD-NAME".to_string(), "x".to_string()),
+            ]),
+            ..Default::default()
+        };
+        let path = write_tmux_env_file(&info, &dir).unwrap();
+        assert_eq!(fs::metadata(&path).unwrap().permissions().mode() & 0o777, 0o600);
+        assert_eq!(
+            fs::read_to_string(&path).unwrap(),
+            "export DATABASE_URL='postgres://u:it'\\''s@db/app'\n"
+        );
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_sudo_argv_keeps_values_out_of_argv() {
+        let info = RestartInfo {
+            port: 3000,
+            command: parse_command_line("npm run dev"),
+            env_vars: HashMap::from([
+                ("DATABASE_URL".to_string(), "postgres://u:secret@db/app".to_string()),
+                ("HOME".to_string(), "/home/me".to_string()),
+                ("NODE_ENV".to_string(), "development".to_string()),
+            ]),
+            as_user: Some("port-kill-nobody".to_string()),
+            ..Default::default()
+        };
+        let argv = sudo_argv("port-kill-nobody", &info, &info.command);
+        assert_eq!(
+            argv,
+            vec![
+                "sudo",
+                "-n",
+                "-u",
+                "port-kill-nobody",
+                "--preserve-env=DATABASE_URL,NODE_ENV",
+                "--",
+                "npm",
+                "run",
+                "dev"
+            ]
+        );
+        let env: Vec<_> = launch_env(&info).into_iter().map(|(key, _)| key.as_str()).collect();
+        assert_eq!(env, vec!["DATABASE_URL", "NODE_ENV"]);
+    }
+
+    #[test]
+    fn test_container_restart_command() {
+        let inspected = serde_json::json!({
+            "Id": "4f1c2d3e5a6b7c8d9e0f",
+            "Name": "/shop-web-1",
+            "Config": {"Labels": {
+                "com.docker.compose.service": "web",
+                "com.docker.compose.project": "shop",
+                "com.docker.compose.project.working_dir": "/work/shop",
+                "com.docker.compose.project.config_files": "/work/shop/compose.yml"
+            }}
+        });
+        let target = ContainerTarget::from_inspect(&inspected).unwrap();
+        assert_eq!(target.id, "4f1c2d3e5a6b");
+        assert_eq!(target.name, "shop-web-1");
+        assert_eq!(
+            target.restart_command(),
+            vec![
+                "docker",
+                "compose",
+                "-p",
+                "shop",
+                "-f",
+                "/work/shop/compose.yml",
+                "up",
+                "-d",
+                "web"
+            ]
+        );
+
+        let plain = ContainerTarget::from_inspect(&serde_json::json!({
+            "Id": "0123456789abcdef",
+            "Name": "/redis",
+            "Config": {"Labels": {}}
+        }))
+        .unwrap();
+        assert_eq!(
+            plain.restart_command(),
+            vec!["docker", "start", "0123456789ab"]
+        );
+    }
+
+    #[test]
+    fn test_restart_placeholders() {
+        let info = RestartInfo {
+            port: 3001,
+            command: parse_command_line("npm run dev -- --port {{port}}"),
+            working_directory: "{{home}}/shop".to_string(),
+            env_vars: HashMap::from([("PORT".to_string(), "{{port}}".to_string())]),
+            last_restarted: chrono::Utc::now(),
+            post: Some("curl -f localhost:{{port}}/health".to_string()),
+            ..Default::default()
+        };
+        let resolved = info.with_placeholders();
+        assert_eq!(resolved.command.last().unwrap(), "3001");
+        assert_eq!(resolved.env_vars["PORT"], "3001");
+        assert_eq!(
+            resolved.working_directory,
+            format!("{}/shop", RestartManager::get_home_dir())
+        );
+        assert_eq!(
+            resolved.post.as_deref(),
+            Some("curl -f localhost:3001/health")
+        );
+
+        // Other template syntax in a captured command is kept as it is
+        let mut docker = info.clone();
+        docker.command = parse_command_line("docker ps --format '{{.Names}}' --filter {{branch}}");
+        assert_eq!(
+            docker.with_placeholders().command,
+            vec!["docker", "ps", "--format", "{{.Names}}", "--filter", "{{branch}}"]
+        );
+    }
+
+    #[test]
+    fn test_stale_reason() {
+        let mut info = RestartInfo {
+            port: 3000,
+            command: vec!["sh".to_string(), "-c".to_string(), "true".to_string()],
+            working_directory: std::env::temp_dir().to_string_lossy().to_string(),
+            last_restarted: chrono::Utc::now(),
+            ..Default::default()
+        };
+        assert_eq!(info.stale_reason(), None);
+
+        info.command[0] = "port-kill-no-such-binary".to_string();
+        assert!(info.stale_reason().unwrap().contains("not found"));
+
+        info.command[0] = "sh".to_string();
+        info.working_directory = "/nonexistent/port-kill/project".to_string();
+        assert!(info.stale_reason().unwrap().contains("no longer exists"));
+
+        info.working_directory = std::env::temp_dir().to_string_lossy().to_string();
+        assert_eq!(info.unused_days(), None);
+        // Old entries still work, so age alone doesn't make them stale
+        info.last_restarted = chrono::Utc::now() - chrono::Duration::days(120);
+        assert_eq!(info.stale_reason(), None);
+        assert_eq!(info.unused_days(), Some(120));
+    }
 }
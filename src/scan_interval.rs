@@ -0,0 +1,85 @@
+//! Scan interval of the monitoring loops. It backs off while the ports stay the
+//! same, up to `--max-scan-interval`, and drops back to `--scan-interval` as soon as
+//! a scan sees a change, so always-on monitoring idles cheaply.
+
+use crate::cli::Args;
+use std::time::Duration;
+
+/// Unchanged scans in a row before the interval doubles
+const IDLE_SCANS_BEFORE_BACKOFF: u32 = 3;
+
+#[derive(Debug, Clone)]
+pub struct AdaptiveInterval {
+    min: Duration,
+    max: Duration,
+    current: Duration,
+    idle_scans: u32,
+}
+
+impl AdaptiveInterval {
+    /// Between `min` and `max`; a `max` at or below `min` keeps the interval fixed
+    pub fn new(min: Duration, max: Duration) -> Self {
+        let min = min.max(Duration::from_millis(100));
+        Self {
+            min,
+            max: max.max(min),
+            current: min,
+            idle_scans: 0,
+        }
+    }
+
+    /// Always `interval`, never backing off
+    pub fn fixed(interval: Duration) -> Self {
+        Self::new(interval, interval)
+    }
+
+    /// `--scan-interval` to `--max-scan-interval`
+    pub fn from_args(args: &Args) -> Self {
+        Self::new(
+            Duration::from_secs(args.scan_interval),
+            Duration::from_secs(args.max_scan_interval),
+        )
+    }
+
+    pub fn current(&self) -> Duration {
+        self.current
+    }
+
+    /// Record whether the last scan saw a change, and get the wait before the next one
+    pub fn record(&mut self, changed: bool) -> Duration {
+        if changed {
+            self.idle_scans = 0;
+            if self.current != self.min {
+                log::debug!("Ports changed, scanning every {:?} again", self.min);
+            }
+            self.current = self.min;
+        } else if self.current < self.max {
+            self.idle_scans += 1;
+            if self.idle_scans >= IDLE_SCANS_BEFORE_BACKOFF {
+                self.idle_scans = 0;
+                self.current = (self.current * 2).min(self.max);
+                log::debug!("No changes, scanning every {:?}", self.current);
+            }
+        }
+        self.current
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_backoff_and_reset() {
+        let mut interval = AdaptiveInterval::new(Duration::from_secs(2), Duration::from_secs(10));
+        let waits: Vec<u64> = (0..10).map(|_| interval.record(false).as_secs()).collect();
+        assert_eq!(waits, vec![2, 2, 4, 4, 4, 8, 8, 8, 10, 10]);
+        assert_eq!(interval.record(true), Duration::from_secs(2));
+        assert_eq!(interval.record(false), Duration::from_secs(2));
+
+        let mut fixed = AdaptiveInterval::fixed(Duration::from_secs(2));
+        assert!((0..10).all(|_| fixed.record(false) == Duration::from_secs(2)));
+        let inverted = AdaptiveInterval::new(Duration::from_secs(5), Duration::from_secs(1));
+        assert_eq!(inverted.max, Duration::from_secs(5));
+    }
+}
@@ -1,5 +1,6 @@
 use crate::{
-    cli::Args, file_monitor::FileMonitor, process_monitor::ProcessMonitor, types::ProcessInfo,
+    cli::Args, file_monitor::FileMonitor, js_runtime::JsRuntime,
+    process_monitor::ProcessMonitor, scan_interval::AdaptiveInterval, types::ProcessInfo,
 };
 use anyhow::Result;
 use std::collections::HashMap;
@@ -14,6 +15,16 @@ enum GuardConfig {
     AllowOnly(String), // Only allow specific process name
 }
 
+impl GuardConfig {
+    /// `AllowOnly` when the script named a process, otherwise `KillAll`
+    fn from_allowed(allowed: &Option<String>) -> Self {
+        match allowed {
+            Some(name) => GuardConfig::AllowOnly(name.clone()),
+            None => GuardConfig::KillAll,
+        }
+    }
+}
+
 /// Scripting engine for port-kill
 pub struct ScriptEngine {
     process_monitor: Arc<Mutex<ProcessMonitor>>,
@@ -51,14 +62,35 @@ impl ScriptEngine {
         }
     }
 
-    /// Execute JavaScript script
+    /// Execute JavaScript script in the embedded interpreter
     async fn execute_javascript(&mut self, script: &str) -> Result<()> {
         println!("🚀 Executing JavaScript script...");
-        println!("📝 Script: {}", script);
 
-        // For now, we'll implement a simple command parser
-        // Later we'll integrate a proper JavaScript runtime
-        self.parse_and_execute_commands(script).await
+        let runtime = JsRuntime::new(&self.args)?;
+        runtime.eval(script)?;
+
+        let watched_ports: Vec<u16> = {
+            let registrations = runtime.registrations();
+            for (port, allowed) in &registrations.port_guards {
+                self.port_guards.insert(*port, GuardConfig::from_allowed(allowed));
+            }
+            for (path, allowed) in &registrations.file_guards {
+                self.file_guards
+                    .insert(path.clone(), GuardConfig::from_allowed(allowed));
+            }
+            registrations.watched_ports.iter().copied().collect()
+        };
+
+        if !watched_ports.is_empty() || !self.port_guards.is_empty() {
+            println!("📡 Starting port monitoring for script...");
+            self.start_monitoring(watched_ports, |process| {
+                if let Err(e) = runtime.dispatch(process) {
+                    println!("❌ {}", e);
+                }
+            })
+            .await?;
+        }
+        Ok(())
     }
 
     /// Execute Python script
@@ -122,7 +154,18 @@ impl ScriptEngine {
         // Start monitoring if we have any port handlers or guards
         if !self.port_handlers.read().unwrap().is_empty() || !self.port_guards.is_empty() {
             println!("📡 Starting port monitoring for script...");
-            self.start_monitoring().await?;
+            let watched_ports: Vec<u16> = self.port_handlers.read().unwrap().keys().cloned().collect();
+            let port_handlers = Arc::clone(&self.port_handlers);
+            self.start_monitoring(watched_ports, move |process| {
+                if let Ok(handlers) = port_handlers.read() {
+                    if let Some(handlers) = handlers.get(&process.port) {
+                        for h in handlers {
+                            h(process.clone());
+                        }
+                    }
+                }
+            })
+            .await?;
         }
 
         Ok(())
@@ -525,8 +568,13 @@ impl ScriptEngine {
         None
     }
 
-    /// Start monitoring for script handlers
-    async fn start_monitoring(&mut self) -> Result<()> {
+    /// Watch `watched_ports` and the guarded ports until Ctrl+C, enforcing guards and
+    /// calling `on_process` for each process that starts or changes on a watched port
+    async fn start_monitoring(
+        &mut self,
+        watched_ports: Vec<u16>,
+        mut on_process: impl FnMut(&ProcessInfo),
+    ) -> Result<()> {
         println!("🔄 Starting continuous monitoring...");
         println!("💡 Press Ctrl+C to stop");
 
@@ -539,7 +587,6 @@ impl ScriptEngine {
 
         // Start the monitoring loop
         let monitor = self.process_monitor.clone();
-        let watched_ports: Vec<u16> = self.port_handlers.read().unwrap().keys().cloned().collect();
         let guard_ports: Vec<u16> = self.port_guards.keys().cloned().collect();
         let all_monitored_ports: Vec<u16> = watched_ports
             .iter()
@@ -547,49 +594,68 @@ impl ScriptEngine {
             .cloned()
             .collect();
         let port_guards = self.port_guards.clone();
-        let port_handlers = Arc::clone(&self.port_handlers);
-
-        tokio::spawn(async move {
-            let mut last_processes: HashMap<u16, ProcessInfo> = HashMap::new();
-
-            loop {
-                if let Ok(mut monitor) = monitor.try_lock() {
-                    if let Ok(processes) = monitor.scan_processes().await {
-                        // Get current ports first
-                        let current_ports: std::collections::HashSet<u16> =
-                            processes.keys().cloned().collect();
-
-                        for (port, process_info) in processes {
-                            if all_monitored_ports.contains(&port) {
-                                // Check if this is a new or changed process
-                                let is_new = !last_processes.contains_key(&port);
-                                let is_changed =
-                                    if let Some(last_process) = last_processes.get(&port) {
-                                        last_process.pid != process_info.pid
-                                            || last_process.name != process_info.name
-                                    } else {
-                                        false
-                                    };
-
-                                if is_new || is_changed {
-                                    if is_new {
-                                        println!(
-                                            "🟢 NEW: Process started on port {}: {} (PID: {})",
-                                            process_info.port, process_info.name, process_info.pid
-                                        );
-                                    } else {
-                                        println!(
-                                            "🔄 CHANGED: Process on port {}: {} (PID: {})",
-                                            process_info.port, process_info.name, process_info.pid
-                                        );
-                                    }
+        let mut scan_interval = AdaptiveInterval::from_args(&self.args);
+        let ctrl_c = tokio::signal::ctrl_c();
+        tokio::pin!(ctrl_c);
+
+        let mut last_processes: HashMap<u16, ProcessInfo> = HashMap::new();
+
+        loop {
+            let mut changed = false;
+            if let Ok(mut monitor) = monitor.try_lock() {
+                if let Ok(processes) = monitor.scan_processes().await {
+                    // Get current ports first
+                    let current_ports: std::collections::HashSet<u16> =
+                        processes.keys().cloned().collect();
 
-                                    // Check if this port has a guard and handle accordingly (enforce for both new and changed)
-                                    if let Some(guard_config) = port_guards.get(&port) {
-                                        match guard_config {
-                                            GuardConfig::KillAll => {
-                                                println!("🚨 Unauthorized process on port {}: {} (PID: {}) - KILLING", 
-                                                         port, process_info.name, process_info.pid);
+                    for (port, process_info) in processes {
+                        if all_monitored_ports.contains(&port) {
+                            // Check if this is a new or changed process
+                            let is_new = !last_processes.contains_key(&port);
+                            let is_changed =
+                                if let Some(last_process) = last_processes.get(&port) {
+                                    last_process.pid != process_info.pid
+                                        || last_process.name != process_info.name
+                                } else {
+                                    false
+                                };
+
+                            if is_new || is_changed {
+                                changed = true;
+                                if is_new {
+                                    println!(
+                                        "🟢 NEW: Process started on port {}: {} (PID: {})",
+                                        process_info.port, process_info.name, process_info.pid
+                                    );
+                                } else {
+                                    println!(
+                                        "🔄 CHANGED: Process on port {}: {} (PID: {})",
+                                        process_info.port, process_info.name, process_info.pid
+                                    );
+                                }
+
+                                // Check if this port has a guard and handle accordingly (enforce for both new and changed)
+                                if let Some(guard_config) = port_guards.get(&port) {
+                                    match guard_config {
+                                        GuardConfig::KillAll => {
+                                            println!("🚨 Unauthorized process on port {}: {} (PID: {}) - KILLING", 
+                                                     port, process_info.name, process_info.pid);
+                                            if let Err(e) =
+                                                monitor.kill_process(process_info.pid).await
+                                            {
+                                                println!(
+                                                    "❌ Failed to kill process {}: {}",
+                                                    process_info.pid, e
+                                                );
+                                            } else {
+                                                println!("✅ Successfully killed unauthorized process {} on port {}", 
+                                                         process_info.pid, port);
+                                            }
+                                        }
+                                        GuardConfig::AllowOnly(allowed_name) => {
+                                            if process_info.name != *allowed_name {
+                                                println!("🚨 Unauthorized process '{}' on port {}: {} (PID: {}) - KILLING", 
+                                                         process_info.name, port, process_info.name, process_info.pid);
                                                 if let Err(e) =
                                                     monitor.kill_process(process_info.pid).await
                                                 {
@@ -601,67 +667,50 @@ impl ScriptEngine {
                                                     println!("✅ Successfully killed unauthorized process {} on port {}", 
                                                              process_info.pid, port);
                                                 }
-                                            }
-                                            GuardConfig::AllowOnly(allowed_name) => {
-                                                if process_info.name != *allowed_name {
-                                                    println!("🚨 Unauthorized process '{}' on port {}: {} (PID: {}) - KILLING", 
-                                                             process_info.name, port, process_info.name, process_info.pid);
-                                                    if let Err(e) =
-                                                        monitor.kill_process(process_info.pid).await
-                                                    {
-                                                        println!(
-                                                            "❌ Failed to kill process {}: {}",
-                                                            process_info.pid, e
-                                                        );
-                                                    } else {
-                                                        println!("✅ Successfully killed unauthorized process {} on port {}", 
-                                                                 process_info.pid, port);
-                                                    }
-                                                } else {
-                                                    println!("✅ Authorized process '{}' (PID: {}) on port {}", 
-                                                             process_info.name, process_info.pid, port);
-                                                }
-                                            }
-                                        }
-                                    }
-
-                                    // Invoke onPort handlers for this port
-                                    if let Ok(handlers) = port_handlers.read() {
-                                        if let Some(handlers) = handlers.get(&port) {
-                                            for h in handlers {
-                                                h(process_info.clone());
+                                            } else {
+                                                println!("✅ Authorized process '{}' (PID: {}) on port {}", 
+                                                         process_info.name, process_info.pid, port);
                                             }
                                         }
                                     }
                                 }
 
-                                // Update our tracking
-                                last_processes.insert(port, process_info);
+                                // Invoke onPort handlers for this port
+                                if watched_ports.contains(&port) {
+                                    on_process(&process_info);
+                                }
                             }
-                        }
 
-                        // Check for processes that disappeared
-                        for (port, last_process) in last_processes.iter() {
-                            if all_monitored_ports.contains(port) && !current_ports.contains(port) {
-                                println!(
-                                    "🔴 REMOVED: Process stopped on port {}: {} (PID: {})",
-                                    port, last_process.name, last_process.pid
-                                );
-                            }
+                            // Update our tracking
+                            last_processes.insert(port, process_info);
                         }
+                    }
 
-                        // Clean up tracking for ports that are no longer active
-                        last_processes.retain(|port, _| current_ports.contains(port));
+                    // Check for processes that disappeared
+                    for (port, last_process) in last_processes.iter() {
+                        if all_monitored_ports.contains(port) && !current_ports.contains(port) {
+                            changed = true;
+                            println!(
+                                "🔴 REMOVED: Process stopped on port {}: {} (PID: {})",
+                                port, last_process.name, last_process.pid
+                            );
+                        }
                     }
+
+                    // Clean up tracking for ports that are no longer active
+                    last_processes.retain(|port, _| current_ports.contains(port));
                 }
+            }
 
-                // Sleep for 2 seconds
-                tokio::time::sleep(tokio::time::Duration::from_secs(2)).await;
+            tokio::select! {
+                result = &mut ctrl_c => {
+                    result?;
+                    break;
+                }
+                _ = tokio::time::sleep(scan_interval.record(changed)) => {}
             }
-        });
+        }
 
-        // Keep the main thread alive
-        tokio::signal::ctrl_c().await?;
         println!("🛑 Script monitoring stopped");
 
         Ok(())
@@ -702,26 +751,61 @@ mod tests {
                 smart_filter: false,
                 only_groups: None,
                 console: false,
+                tui: false,
                 verbose: false,
                 docker: false,
+                udp: false,
+                bind_addr: Vec::new(),
                 show_pid: false,
                 log_level: crate::cli::LogLevel::Info,
+                log_file: None,
+                log_format: crate::cli::LogFormat::Text,
+                log_rotation: crate::cli::LogRotation::Daily,
                 show_history: false,
                 clear_history: false,
                 show_filters: false,
                 performance: false,
                 show_context: false,
+                friendly_names: false,
+                report: None,
+                report_format: crate::cli::ReportFormat::Terminal,
+                report_send: false,
+                fail_if_found: false,
+                no_daemon: false,
+                update_channel: crate::cli::UpdateChannel::Stable,
                 kill_all: false,
                 kill_group: None,
                 kill_project: None,
                 restart: None,
+                pick: false,
+                keep_alive: false,
+                watch: Vec::new(),
+                pre_restart: None,
+                post_restart: None,
+                tmux: false,
+                tmux_session: None,
+                no_tmux: false,
+                restart_env: Vec::new(),
+                env_file: None,
+                clean_env: false,
+                as_user: None,
+                restart_args: Vec::new(),
+                restart_all: false,
+                restart_timeout: 10,
                 show_restart_history: false,
                 clear_restart: None,
+                prune_restarts: false,
+                export_restarts: None,
+                import_restarts: None,
                 show_tree: false,
                 json: false,
                 reset: false,
                 show_offenders: false,
                 show_patterns: false,
+                history_port: None,
+                since: None,
+                until: None,
+                timeline: None,
                 show_suggestions: false,
                 show_stats: false,
                 show_root_cause: false,
@@ -739,14 +823,18 @@ mod tests {
                 baseline_file: None,
                 suspicious_only: false,
                 remote: None,
+                remote_bin: "port-kill-console".to_string(),
                 monitor_endpoint: None,
                 send_interval: 30,
                 scan_interval: 2,
+                max_scan_interval: 30,
                 endpoint_auth: None,
                 endpoint_fields: None,
                 endpoint_include_audit: false,
                 endpoint_retries: 3,
                 endpoint_timeout: 10,
+                prometheus_port: None,
+                prometheus_addr: std::net::Ipv4Addr::LOCALHOST.into(),
                 script: None,
                 script_file: None,
                 clear: None,
@@ -760,22 +848,52 @@ mod tests {
                 safe: false,
                 positional_ports: vec![],
                 preset: None,
+                no_auto_preset: false,
+                explain: false,
                 list_presets: false,
                 save_preset: None,
                 preset_desc: None,
+                interactive: false,
                 delete_preset: None,
+                import_preset: None,
+                export_preset: None,
+                yes: false,
                 check_updates: false,
+                doctor: false,
+                dashboard: None,
+                serve: None,
+                machine: false,
+                install_login_item: false,
+                remove_login_item: false,
+                install_git_hooks: false,
+                reserve_branch_port: false,
+                release_branch_port: None,
+                branch_ports: false,
                 self_update: false,
-                cache: None,
+                command: None,
                 detect: false,
                 start: None,
+                start_env: Vec::new(),
+                load_env: false,
                 guard_auto_restart: false,
                 up: false,
+                watch_config: false,
+                manage: false,
                 down: false,
                 restart_service: None,
                 status: false,
                 config_file: ".port-kill.yaml".to_string(),
                 init_config: false,
+                no_config: false,
+                no_reload: false,
+                smart_filter_rules: Default::default(),
+                tray_quick_actions: None,
+                tray_icon_rules: Default::default(),
+                tray_menu_layout: Default::default(),
+                tray_hotkeys: Vec::new(),
+                tray_pins: Vec::new(),
+                explicit_settings: Vec::new(),
+                config_error: None,
             },
         );
 
@@ -813,26 +931,61 @@ mod tests {
                 smart_filter: false,
                 only_groups: None,
                 console: false,
+                tui: false,
                 verbose: false,
                 docker: false,
+                udp: false,
+                bind_addr: Vec::new(),
                 show_pid: false,
                 log_level: crate::cli::LogLevel::Info,
+                log_file: None,
+                log_format: crate::cli::LogFormat::Text,
+                log_rotation: crate::cli::LogRotation::Daily,
                 show_history: false,
                 clear_history: false,
                 show_filters: false,
                 performance: false,
                 show_context: false,
+                friendly_names: false,
+                report: None,
+                report_format: crate::cli::ReportFormat::Terminal,
+                report_send: false,
+                fail_if_found: false,
+                no_daemon: false,
+                update_channel: crate::cli::UpdateChannel::Stable,
                 kill_all: false,
                 kill_group: None,
                 kill_project: None,
                 restart: None,
+                pick: false,
+                keep_alive: false,
+                watch: Vec::new(),
+                pre_restart: None,
+                post_restart: None,
+                tmux: false,
+                tmux_session: None,
+                no_tmux: false,
+                restart_env: Vec::new(),
+                env_file: None,
+                clean_env: false,
+                as_user: None,
+                restart_args: Vec::new(),
+                restart_all: false,
+                restart_timeout: 10,
                 show_restart_history: false,
                 clear_restart: None,
+                prune_restarts: false,
+                export_restarts: None,
+                import_restarts: None,
                 show_tree: false,
                 json: false,
                 reset: false,
                 show_offenders: false,
                 show_patterns: false,
+                history_port: None,
+                since: None,
+                until: None,
+                timeline: None,
                 show_suggestions: false,
                 show_stats: false,
                 show_root_cause: false,
@@ -850,14 +1003,18 @@ mod tests {
                 baseline_file: None,
                 suspicious_only: false,
                 remote: None,
+                remote_bin: "port-kill-console".to_string(),
                 monitor_endpoint: None,
                 send_interval: 30,
                 scan_interval: 2,
+                max_scan_interval: 30,
                 endpoint_auth: None,
                 endpoint_fields: None,
                 endpoint_include_audit: false,
                 endpoint_retries: 3,
                 endpoint_timeout: 10,
+                prometheus_port: None,
+                prometheus_addr: std::net::Ipv4Addr::LOCALHOST.into(),
                 script: None,
                 script_file: None,
                 clear: None,
@@ -871,22 +1028,52 @@ mod tests {
                 safe: false,
                 positional_ports: vec![],
                 preset: None,
+                no_auto_preset: false,
+                explain: false,
                 list_presets: false,
                 save_preset: None,
                 preset_desc: None,
+                interactive: false,
                 delete_preset: None,
+                import_preset: None,
+                export_preset: None,
+                yes: false,
                 check_updates: false,
+                doctor: false,
+                dashboard: None,
+                serve: None,
+                machine: false,
+                install_login_item: false,
+                remove_login_item: false,
+                install_git_hooks: false,
+                reserve_branch_port: false,
+                release_branch_port: None,
+                branch_ports: false,
                 self_update: false,
-                cache: None,
+                command: None,
                 detect: false,
                 start: None,
+                start_env: Vec::new(),
+                load_env: false,
                 guard_auto_restart: false,
                 up: false,
+                watch_config: false,
+                manage: false,
                 down: false,
                 restart_service: None,
                 status: false,
                 config_file: ".port-kill.yaml".to_string(),
                 init_config: false,
+                no_config: false,
+                no_reload: false,
+                smart_filter_rules: Default::default(),
+                tray_quick_actions: None,
+                tray_icon_rules: Default::default(),
+                tray_menu_layout: Default::default(),
+                tray_hotkeys: Vec::new(),
+                tray_pins: Vec::new(),
+                explicit_settings: Vec::new(),
+                config_error: None,
             },
         );
 
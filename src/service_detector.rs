@@ -1,8 +1,11 @@
+use crate::command_line::{parse_command_line, quote_command_line};
+use crate::project_env::ProjectEnv;
 use anyhow::{Context, Result};
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::fs;
 use std::path::{Path, PathBuf};
-use std::process::{Child, Command};
+use std::process::Command;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum ServiceType {
@@ -36,23 +39,105 @@ pub struct DiscoveredService {
     pub description: String,
 }
 
+impl DiscoveredService {
+    /// The command `start_service` runs, as a single line for config files
+    pub fn command_line(&self) -> String {
+        match &self.service_type {
+            ServiceType::NpmScript { script_name, .. } => format!("npm run {}", script_name),
+            ServiceType::DockerCompose { service_name, .. } => {
+                format!("docker-compose up {}", service_name)
+            }
+            ServiceType::Procfile {
+                process_name,
+                procfile_path,
+            } => fs::read_to_string(procfile_path)
+                .ok()
+                .and_then(|content| {
+                    content
+                        .lines()
+                        .filter_map(ServiceDetector::parse_procfile_line)
+                        .find(|(name, _)| name == process_name)
+                })
+                .map(|(_, command)| command)
+                .unwrap_or_default(),
+            ServiceType::PythonApp { script_path } => {
+                let script = script_path
+                    .file_name()
+                    .unwrap_or_default()
+                    .to_string_lossy();
+                if script == "manage.py" {
+                    format!("python {} runserver", script)
+                } else {
+                    format!("python {}", script)
+                }
+            }
+            ServiceType::Custom { command, .. } => quote_command_line(command),
+        }
+    }
+}
+
+/// A service `start_service_with` launched, and exactly how
+#[derive(Debug, Clone)]
+pub struct StartedService {
+    pub pid: u32,
+    /// The program and arguments, including the pass-through arguments
+    pub command: Vec<String>,
+    pub working_directory: PathBuf,
+    /// The direnv/mise environment the service was started with, if it was loaded
+    pub project_env: ProjectEnv,
+}
+
+impl StartedService {
+    /// The environment the service was launched with: port-kill's own, as changed by
+    /// the project's direnv/mise setup, then `extra_env` (`--start-env`)
+    pub fn resolved_env(&self, extra_env: &[(String, String)]) -> HashMap<String, String> {
+        let mut env: HashMap<String, String> = std::env::vars().collect();
+        for (key, value) in &self.project_env.vars {
+            match value {
+                Some(value) => env.insert(key.clone(), value.clone()),
+                None => env.remove(key),
+            };
+        }
+        env.extend(extra_env.iter().cloned());
+        env
+    }
+}
+
 pub struct ServiceDetector {
     search_paths: Vec<PathBuf>,
+    load_env: bool,
+    tmux_session: Option<String>,
 }
 
 impl ServiceDetector {
     pub fn new() -> Self {
         Self {
             search_paths: vec![PathBuf::from(".")],
+            load_env: false,
+            tmux_session: None,
         }
     }
 
     pub fn with_paths(paths: Vec<PathBuf>) -> Self {
         Self {
             search_paths: paths,
+            load_env: false,
+            tmux_session: None,
         }
     }
 
+    /// Start services with the direnv/mise environment of their working directory
+    pub fn load_project_env(mut self, load: bool) -> Self {
+        self.load_env = load;
+        self
+    }
+
+    /// Start services in their own window of this tmux session instead of in the background
+    pub fn in_tmux(mut self, session: Option<String>) -> Self {
+        self.tmux_session = session;
+        self
+    }
+
     /// Discover all services in the search paths
     pub fn discover_services(&self) -> Result<Vec<DiscoveredService>> {
         let mut services = Vec::new();
@@ -61,6 +146,9 @@ impl ServiceDetector {
             // Search for package.json (Node.js/npm)
             services.extend(self.discover_npm_services(search_path)?);
 
+            // Search for npm/yarn/pnpm workspace packages and turbo/nx tasks
+            services.extend(self.discover_workspace_services(search_path));
+
             // Search for docker-compose.yml
             services.extend(self.discover_docker_services(search_path)?);
 
@@ -69,41 +157,120 @@ impl ServiceDetector {
 
             // Search for Python apps
             services.extend(self.discover_python_services(search_path)?);
+
+            // Search for Cargo binaries and Go main packages
+            services.extend(self.discover_cargo_services(search_path)?);
+            services.extend(self.discover_go_services(search_path)?);
+
+            // Search for Spring Boot (Maven/Gradle), Rails and Deno tasks
+            services.extend(self.discover_jvm_services(search_path));
+            services.extend(self.discover_rails_services(search_path));
+            services.extend(self.discover_deno_services(search_path)?);
+
+            // Search for Makefile targets and justfile recipes that start servers
+            services.extend(self.discover_task_runner_services(search_path));
+
+            // Search for VS Code tasks
+            services.extend(self.discover_vscode_tasks(search_path));
         }
 
         Ok(services)
     }
 
+    /// Discover the services and map each project (workspace package) to its ports
+    pub fn port_map(&self) -> Result<PortMap> {
+        Ok(PortMap::from_services(&self.discover_services()?))
+    }
+
     /// Start a discovered service
     /// Returns the PID of the spawned process. The child process is detached
     /// to prevent zombie process accumulation.
     pub fn start_service(&self, service: &DiscoveredService) -> Result<u32> {
+        Ok(self.start_service_with(service, &[], &[])?.pid)
+    }
+
+    /// Start a discovered service with `extra_args` passed on to it and `env` set on
+    /// top of port-kill's environment
+    pub fn start_service_with(
+        &self,
+        service: &DiscoveredService,
+        extra_args: &[String],
+        env: &[(String, String)],
+    ) -> Result<StartedService> {
         log::info!("Starting service: {}", service.name);
 
-        let mut child = match &service.service_type {
+        let mut command = match &service.service_type {
             ServiceType::NpmScript {
                 script_name,
                 package_json_path,
-            } => self.start_npm_script(script_name, package_json_path),
+            } => self.npm_script_command(script_name, package_json_path, extra_args),
 
             ServiceType::DockerCompose {
                 service_name,
                 compose_file_path,
-            } => self.start_docker_service(service_name, compose_file_path),
+            } => self.docker_service_command(service_name, compose_file_path, extra_args),
 
             ServiceType::Procfile {
                 process_name,
                 procfile_path,
-            } => self.start_procfile_process(process_name, procfile_path),
+            } => self.procfile_process_command(process_name, procfile_path, extra_args),
 
-            ServiceType::PythonApp { script_path } => self.start_python_app(script_path),
+            ServiceType::PythonApp { script_path } => {
+                self.python_app_command(script_path, extra_args)
+            }
 
             ServiceType::Custom {
                 command,
                 working_dir,
-            } => self.start_custom_command(command, working_dir),
+            } => self.custom_command(command, working_dir, extra_args),
         }?;
-        
+
+        let working_directory = command
+            .get_current_dir()
+            .map(Path::to_path_buf)
+            .unwrap_or_else(|| service.working_directory.clone());
+        // Variables given on the command line win over the project's own
+        let project_env = if self.load_env {
+            ProjectEnv::load(&working_directory)?
+        } else {
+            ProjectEnv::default()
+        };
+        project_env.apply(&mut command);
+        command.envs(env.iter().cloned());
+
+        let started_command: Vec<String> = std::iter::once(command.get_program())
+            .chain(command.get_args())
+            .map(|part| part.to_string_lossy().to_string())
+            .collect();
+
+        if let Some(ref session) = self.tmux_session {
+            // The window starts from the tmux server's environment, so only additions carry over
+            let env: Vec<(String, String)> = project_env
+                .vars
+                .iter()
+                .filter_map(|(key, value)| Some((key.clone(), value.clone()?)))
+                .chain(env.iter().cloned())
+                .collect();
+            let pid = crate::tmux::open_window(
+                session,
+                &service.name,
+                &working_directory,
+                &env,
+                &quote_command_line(&started_command),
+            )
+            .context(format!("Failed to start {}", service.name))?;
+            return Ok(StartedService {
+                pid,
+                command: started_command,
+                working_directory,
+                project_env,
+            });
+        }
+
+        let mut child = command
+            .spawn()
+            .context(format!("Failed to start {}", service.name))?;
+
         let pid = child.id();
 
         // Spawn a background thread to reap the child when it exits,
@@ -112,7 +279,12 @@ impl ServiceDetector {
             let _ = child.wait();
         });
 
-        Ok(pid)
+        Ok(StartedService {
+            pid,
+            command: started_command,
+            working_directory,
+            project_env,
+        })
     }
 
     // Private methods for discovering services
@@ -131,15 +303,35 @@ impl ServiceDetector {
         let package_json: serde_json::Value = serde_json::from_str(&content)
             .context("Failed to parse package.json")?;
 
+        let project_ports = ProjectPorts::read(search_path);
+
+        // Projects with a Bun lockfile run their scripts with bun
+        let uses_bun = ["bun.lockb", "bun.lock"]
+            .iter()
+            .any(|lockfile| search_path.join(lockfile).exists());
+
         // Extract scripts
         if let Some(scripts) = package_json.get("scripts").and_then(|s| s.as_object()) {
-            for (script_name, _script_value) in scripts {
+            for (script_name, script_value) in scripts {
                 // Skip test and build scripts
                 if script_name.contains("test") || script_name.contains("build") {
                     continue;
                 }
 
-                let inferred_port = Self::infer_port_from_script_name(script_name);
+                let inferred_port = project_ports
+                    .script_port(script_value.as_str().unwrap_or_default())
+                    .or_else(|| Self::infer_port_from_script_name(script_name));
+
+                if uses_bun {
+                    services.push(Self::command_service(
+                        format!("bun:{}", script_name),
+                        &["bun", "run", script_name],
+                        search_path,
+                        inferred_port,
+                        format!("bun run {} (Bun)", script_name),
+                    ));
+                    continue;
+                }
                 
                 services.push(DiscoveredService {
                     name: format!("npm:{}", script_name),
@@ -157,6 +349,141 @@ impl ServiceDetector {
         Ok(services)
     }
 
+    fn discover_workspace_services(&self, search_path: &Path) -> Vec<DiscoveredService> {
+        let mut services = Vec::new();
+        let runner = Self::package_runner(search_path);
+
+        for package_dir in Self::workspace_packages(search_path) {
+            let read_json = |file_name: &str| -> Option<serde_json::Value> {
+                let content = fs::read_to_string(package_dir.join(file_name)).ok()?;
+                serde_json::from_str(&content).ok()
+            };
+            let package_json = read_json("package.json");
+            let project_json = read_json("project.json");
+            let json_name = |json: &Option<serde_json::Value>| {
+                json.as_ref()
+                    .and_then(|json| json.get("name")?.as_str().map(String::from))
+            };
+            let dir_name = package_dir
+                .file_name()
+                .unwrap_or_default()
+                .to_string_lossy()
+                .to_string();
+            // `@acme/web` is listed as `web:<script>`
+            let package_name = json_name(&package_json).unwrap_or_else(|| dir_name.clone());
+            let short_name = package_name
+                .rsplit('/')
+                .next()
+                .unwrap_or(&package_name)
+                .to_string();
+            let relative_dir = package_dir
+                .strip_prefix(search_path)
+                .unwrap_or(&package_dir)
+                .display()
+                .to_string();
+            let project_ports = ProjectPorts::read(&package_dir);
+            let mut script_names = Vec::new();
+
+            let scripts = package_json
+                .as_ref()
+                .and_then(|json| json.get("scripts")?.as_object().cloned())
+                .unwrap_or_default();
+            for (script_name, script_value) in &scripts {
+                // Skip test and build scripts, as for the root package
+                if script_name.contains("test") || script_name.contains("build") {
+                    continue;
+                }
+                script_names.push(script_name.clone());
+
+                let name = format!("{}:{}", short_name, script_name);
+                let inferred_port = project_ports
+                    .script_port(script_value.as_str().unwrap_or_default())
+                    .or_else(|| Self::infer_port_from_script_name(script_name));
+                let description = format!(
+                    "{} run {} in {} (workspace)",
+                    runner, script_name, relative_dir
+                );
+
+                if runner == "npm" {
+                    services.push(DiscoveredService {
+                        name,
+                        service_type: ServiceType::NpmScript {
+                            script_name: script_name.clone(),
+                            package_json_path: package_dir.join("package.json"),
+                        },
+                        working_directory: package_dir.clone(),
+                        inferred_port,
+                        description,
+                    });
+                } else {
+                    services.push(Self::command_service(
+                        name,
+                        &[runner, "run", script_name],
+                        &package_dir,
+                        inferred_port,
+                        description,
+                    ));
+                }
+            }
+
+            // Nx targets the package's own scripts don't already cover
+            let project = json_name(&project_json).unwrap_or(package_name);
+            let targets = project_json
+                .as_ref()
+                .and_then(|json| json.get("targets")?.as_object().cloned())
+                .unwrap_or_default();
+            for (target_name, target) in &targets {
+                if target_name.contains("test")
+                    || target_name.contains("build")
+                    || script_names.contains(target_name)
+                {
+                    continue;
+                }
+
+                let target_port = target
+                    .get("options")
+                    .and_then(|options| options.get("port"))
+                    .and_then(|port| port.as_u64())
+                    .and_then(|port| u16::try_from(port).ok());
+                let nx_target = format!("{}:{}", project, target_name);
+                services.push(Self::command_service(
+                    format!("{}:{}", short_name, target_name),
+                    &["npx", "nx", "run", &nx_target],
+                    search_path,
+                    target_port.or_else(|| Self::infer_port_from_script_name(target_name)),
+                    format!("nx run {} (Nx)", nx_target),
+                ));
+            }
+        }
+
+        // Turborepo tasks run in every workspace package that defines them
+        if let Some(turbo) = read_jsonc(&search_path.join("turbo.json")) {
+            // `tasks` since Turborepo 2, `pipeline` before
+            let tasks = turbo
+                .get("tasks")
+                .or_else(|| turbo.get("pipeline"))
+                .and_then(|tasks| tasks.as_object());
+            for task_name in tasks.into_iter().flat_map(|tasks| tasks.keys()) {
+                // Skip test and build tasks, and `package#task` overrides
+                if task_name.contains("test")
+                    || task_name.contains("build")
+                    || task_name.contains('#')
+                {
+                    continue;
+                }
+                services.push(Self::command_service(
+                    format!("turbo:{}", task_name),
+                    &["npx", "turbo", "run", task_name],
+                    search_path,
+                    None,
+                    format!("turbo run {} (all workspace packages)", task_name),
+                ));
+            }
+        }
+
+        services
+    }
+
     fn discover_docker_services(&self, search_path: &Path) -> Result<Vec<DiscoveredService>> {
         let mut services = Vec::new();
         
@@ -171,6 +498,7 @@ impl ServiceDetector {
 
             let content = fs::read_to_string(&compose_path)
                 .context("Failed to read docker-compose file")?;
+            let published_ports = compose_published_ports(&content);
 
             // Simple YAML parsing for service names (proper YAML parser would be better)
             for line in content.lines() {
@@ -182,7 +510,7 @@ impl ServiceDetector {
                             compose_file_path: compose_path.clone(),
                         },
                         working_directory: search_path.to_path_buf(),
-                        inferred_port: None,
+                        inferred_port: published_ports.get(&service_name).copied(),
                         description: format!("Docker Compose service: {}", service_name),
                     });
                 }
@@ -244,7 +572,11 @@ impl ServiceDetector {
                     },
                     working_directory: search_path.to_path_buf(),
                     inferred_port,
-                    description: format!("Python app: {}", py_file),
+                    description: if *py_file == "manage.py" {
+                        "Django app: manage.py runserver".to_string()
+                    } else {
+                        format!("Python app: {}", py_file)
+                    },
                 });
             }
         }
@@ -252,100 +584,693 @@ impl ServiceDetector {
         Ok(services)
     }
 
-    // Private methods for starting services
+    fn discover_cargo_services(&self, search_path: &Path) -> Result<Vec<DiscoveredService>> {
+        let manifest_path = search_path.join("Cargo.toml");
+
+        if !manifest_path.exists() {
+            return Ok(Vec::new());
+        }
+
+        let content = fs::read_to_string(&manifest_path).context("Failed to read Cargo.toml")?;
+        let manifest: toml::Value =
+            toml::from_str(&content).context("Failed to parse Cargo.toml")?;
+
+        let binaries = Self::cargo_binaries(&manifest, search_path);
+        let single = binaries.len() == 1;
+        Ok(binaries
+            .iter()
+            .map(|binary| {
+                let mut command = vec!["cargo", "run"];
+                if !single {
+                    command.extend(["--bin", binary.as_str()]);
+                }
+                Self::command_service(
+                    format!("cargo:{}", binary),
+                    &command,
+                    search_path,
+                    None,
+                    format!("{} (Rust)", command.join(" ")),
+                )
+            })
+            .collect())
+    }
+
+    fn discover_go_services(&self, search_path: &Path) -> Result<Vec<DiscoveredService>> {
+        if !search_path.join("go.mod").exists() {
+            return Ok(Vec::new());
+        }
+
+        // The module root and every cmd/<name> directory can be a main package
+        let mut packages = Vec::new();
+        if Self::is_go_main_package(search_path) {
+            packages.push(("main".to_string(), ".".to_string()));
+        }
+        if let Ok(entries) = fs::read_dir(search_path.join("cmd")) {
+            let mut commands: Vec<String> = entries
+                .flatten()
+                .filter(|entry| Self::is_go_main_package(&entry.path()))
+                .filter_map(|entry| entry.file_name().to_str().map(String::from))
+                .collect();
+            commands.sort();
+            packages.extend(
+                commands
+                    .into_iter()
+                    .map(|command| (command.clone(), format!("./cmd/{}", command))),
+            );
+        }
+
+        Ok(packages
+            .iter()
+            .map(|(name, package)| {
+                Self::command_service(
+                    format!("go:{}", name),
+                    &["go", "run", package],
+                    search_path,
+                    None,
+                    format!("go run {} (Go)", package),
+                )
+            })
+            .collect())
+    }
+
+    fn discover_jvm_services(&self, search_path: &Path) -> Vec<DiscoveredService> {
+        let mut services = Vec::new();
 
-    fn start_npm_script(&self, script_name: &str, package_json_path: &Path) -> Result<Child> {
+        if let Ok(pom) = fs::read_to_string(search_path.join("pom.xml")) {
+            if pom.contains("spring-boot-maven-plugin") {
+                let maven = Self::build_tool(search_path, "mvnw", "mvnw.cmd", "mvn");
+                services.push(Self::command_service(
+                    "maven:spring-boot:run".to_string(),
+                    &[&maven, "spring-boot:run"],
+                    search_path,
+                    Some(8080), // Spring Boot default
+                    "mvn spring-boot:run (Spring Boot)".to_string(),
+                ));
+            }
+        }
+
+        let spring_gradle = ["build.gradle", "build.gradle.kts"].iter().any(|file| {
+            fs::read_to_string(search_path.join(file))
+                .map(|content| content.contains("org.springframework.boot"))
+                .unwrap_or(false)
+        });
+        if spring_gradle {
+            let gradle = Self::build_tool(search_path, "gradlew", "gradlew.bat", "gradle");
+            services.push(Self::command_service(
+                "gradle:bootRun".to_string(),
+                &[&gradle, "bootRun"],
+                search_path,
+                Some(8080), // Spring Boot default
+                "gradle bootRun (Spring Boot)".to_string(),
+            ));
+        }
+
+        services
+    }
+
+    fn discover_rails_services(&self, search_path: &Path) -> Vec<DiscoveredService> {
+        let rails_gem = fs::read_to_string(search_path.join("Gemfile"))
+            .map(|gemfile| {
+                gemfile.lines().any(|line| {
+                    let line = line.trim();
+                    line.starts_with("gem \"rails\"") || line.starts_with("gem 'rails'")
+                })
+            })
+            .unwrap_or(false);
+
+        if !rails_gem || !search_path.join("bin/rails").exists() {
+            return Vec::new();
+        }
+
+        vec![Self::command_service(
+            "rails:server".to_string(),
+            &["ruby", "bin/rails", "server"],
+            search_path,
+            Some(3000), // Rails default
+            "bin/rails server (Rails)".to_string(),
+        )]
+    }
+
+    fn discover_deno_services(&self, search_path: &Path) -> Result<Vec<DiscoveredService>> {
+        let mut services = Vec::new();
+
+        for config_name in ["deno.json", "deno.jsonc"] {
+            let config_path = search_path.join(config_name);
+
+            if !config_path.exists() {
+                continue;
+            }
+
+            let content = fs::read_to_string(&config_path)
+                .context(format!("Failed to read {}", config_name))?;
+            let config: serde_json::Value =
+                serde_json::from_str(&Self::strip_json_comments(&content))
+                    .context(format!("Failed to parse {}", config_name))?;
+
+            let project_ports = ProjectPorts::read(search_path);
+            if let Some(tasks) = config.get("tasks").and_then(|t| t.as_object()) {
+                for (task_name, task) in tasks {
+                    // Skip test and build tasks, as for npm scripts
+                    if task_name.contains("test") || task_name.contains("build") {
+                        continue;
+                    }
+
+                    services.push(Self::command_service(
+                        format!("deno:{}", task_name),
+                        &["deno", "task", task_name],
+                        search_path,
+                        project_ports
+                            .script_port(task.as_str().unwrap_or_default())
+                            .or_else(|| Self::infer_port_from_script_name(task_name)),
+                        format!("deno task {} (Deno)", task_name),
+                    ));
+                }
+            }
+            break;
+        }
+
+        Ok(services)
+    }
+
+    fn discover_task_runner_services(&self, search_path: &Path) -> Vec<DiscoveredService> {
+        let mut services = Vec::new();
+        let project_ports = ProjectPorts::read(search_path);
+
+        type Parser = fn(&str) -> Vec<(String, String)>;
+        let runners: [(&str, &str, &[&str], Parser); 2] = [
+            (
+                "make",
+                "Makefile",
+                &["GNUmakefile", "makefile", "Makefile"],
+                Self::parse_makefile_targets,
+            ),
+            (
+                "just",
+                "justfile",
+                &["justfile", "Justfile", ".justfile"],
+                Self::parse_justfile_recipes,
+            ),
+        ];
+        for (runner, label, file_names, parse) in runners {
+            // Like make and just themselves, use the first file found
+            let Some(content) = file_names
+                .iter()
+                .find_map(|file_name| fs::read_to_string(search_path.join(file_name)).ok())
+            else {
+                continue;
+            };
+
+            for (target, recipe) in parse(&content) {
+                if !Self::looks_like_server_task(&target) {
+                    continue;
+                }
+                services.push(Self::command_service(
+                    format!("{}:{}", runner, target),
+                    &[runner, &target],
+                    search_path,
+                    project_ports
+                        .script_port(&recipe)
+                        .or_else(|| Self::infer_port_from_script_name(&target)),
+                    format!("{} {} ({})", runner, target, label),
+                ));
+            }
+        }
+
+        services
+    }
+
+    fn discover_vscode_tasks(&self, search_path: &Path) -> Vec<DiscoveredService> {
+        let Some(tasks) = read_jsonc(&search_path.join(".vscode/tasks.json")) else {
+            return Vec::new();
+        };
+        let project_ports = ProjectPorts::read(search_path);
+        let workspace_folder = search_path.to_string_lossy().to_string();
+        // Other `${...}` variables need VS Code to resolve them
+        let substitute = |value: &str| -> Option<String> {
+            let value = value
+                .replace("${workspaceFolder}", &workspace_folder)
+                .replace("${workspaceRoot}", &workspace_folder);
+            (!value.contains("${")).then_some(value)
+        };
+
+        let mut services = Vec::new();
+        for task in tasks
+            .get("tasks")
+            .and_then(|tasks| tasks.as_array())
+            .into_iter()
+            .flatten()
+        {
+            let text = |key: &str| task.get(key).and_then(|value| value.as_str());
+            let Some(label) = text("label").or_else(|| text("script")) else {
+                continue;
+            };
+            // `group` is either "build" or `{"kind": "build"}`
+            let group = task
+                .get("group")
+                .and_then(|group| group.as_str().or_else(|| group.get("kind")?.as_str()))
+                .unwrap_or_default();
+            let background = task
+                .get("isBackground")
+                .and_then(|background| background.as_bool())
+                .unwrap_or(false);
+            let slug: String = label
+                .to_lowercase()
+                .split_whitespace()
+                .collect::<Vec<_>>()
+                .join("-");
+            if matches!(group, "build" | "test")
+                || slug.contains("test")
+                || slug.contains("build")
+                || !(background || Self::looks_like_server_task(&slug))
+            {
+                continue;
+            }
+
+            let cwd = task
+                .get("options")
+                .and_then(|options| options.get("cwd"))
+                .and_then(|cwd| cwd.as_str())
+                .or_else(|| text("path"))
+                .map(|cwd| substitute(cwd).map(|cwd| search_path.join(cwd)))
+                .unwrap_or_else(|| Some(search_path.to_path_buf()));
+            let args: Option<Vec<String>> = task
+                .get("args")
+                .and_then(|args| args.as_array())
+                .into_iter()
+                .flatten()
+                .map(|arg| {
+                    // Args are strings or `{"value": ..., "quoting": ...}`
+                    arg.as_str()
+                        .or_else(|| arg.get("value")?.as_str())
+                        .and_then(substitute)
+                })
+                .collect();
+            let command = match (text("type").unwrap_or("process"), text("command")) {
+                ("npm", _) => text("script")
+                    .map(|script| vec!["npm".to_string(), "run".to_string(), script.to_string()]),
+                ("process", Some(command)) => substitute(command).map(|command| vec![command]),
+                ("shell", Some(command)) => substitute(command).map(|command| {
+                    // Plain commands run directly so pass-through arguments stay arguments
+                    if command.contains(['&', '|', ';', '<', '>', '$', '`']) {
+                        shell_invocation(&command)
+                    } else {
+                        parse_command_line(&command)
+                    }
+                }),
+                _ => None,
+            };
+            let (Some(cwd), Some(args), Some(mut command)) = (cwd, args, command) else {
+                continue;
+            };
+            if command.is_empty() {
+                continue;
+            }
+            command.extend(args);
+
+            let command_line = quote_command_line(&command);
+            let parts: Vec<&str> = command.iter().map(String::as_str).collect();
+            services.push(Self::command_service(
+                format!("vscode:{}", slug),
+                &parts,
+                &cwd,
+                project_ports
+                    .script_port(&command_line)
+                    .or_else(|| Self::infer_port_from_script_name(&slug)),
+                format!("{} (VS Code task \"{}\")", command_line, label),
+            ));
+        }
+
+        services
+    }
+
+    // Private methods for building the commands that start services
+
+    fn npm_script_command(
+        &self,
+        script_name: &str,
+        package_json_path: &Path,
+        extra_args: &[String],
+    ) -> Result<Command> {
         let working_dir = package_json_path.parent().unwrap_or(Path::new("."));
 
-        Command::new("npm")
-            .arg("run")
-            .arg(script_name)
-            .current_dir(working_dir)
-            .spawn()
-            .context(format!("Failed to start npm script: {}", script_name))
+        let mut command = Command::new("npm");
+        command.arg("run").arg(script_name).current_dir(working_dir);
+        // npm only forwards arguments that follow `--`
+        if !extra_args.is_empty() {
+            command.arg("--").args(extra_args);
+        }
+        Ok(command)
     }
 
-    fn start_docker_service(&self, service_name: &str, compose_file_path: &Path) -> Result<Child> {
+    fn docker_service_command(
+        &self,
+        service_name: &str,
+        compose_file_path: &Path,
+        extra_args: &[String],
+    ) -> Result<Command> {
         let working_dir = compose_file_path.parent().unwrap_or(Path::new("."));
 
-        Command::new("docker-compose")
+        // Options for `up` go before the service name
+        let mut command = Command::new("docker-compose");
+        command
             .arg("up")
+            .args(extra_args)
             .arg(service_name)
-            .current_dir(working_dir)
-            .spawn()
-            .context(format!("Failed to start docker service: {}", service_name))
+            .current_dir(working_dir);
+        Ok(command)
     }
 
-    fn start_procfile_process(&self, process_name: &str, procfile_path: &Path) -> Result<Child> {
+    fn procfile_process_command(
+        &self,
+        process_name: &str,
+        procfile_path: &Path,
+        extra_args: &[String],
+    ) -> Result<Command> {
         let working_dir = procfile_path.parent().unwrap_or(Path::new("."));
-        
+
         // Read the Procfile to get the command
-        let content = fs::read_to_string(procfile_path)
-            .context("Failed to read Procfile")?;
+        let content = fs::read_to_string(procfile_path).context("Failed to read Procfile")?;
 
         for line in content.lines() {
-            if let Some((name, command)) = Self::parse_procfile_line(line) {
+            if let Some((name, mut command)) = Self::parse_procfile_line(line) {
                 if name == process_name {
-                    return self.execute_shell_command(&command, working_dir);
+                    if !extra_args.is_empty() {
+                        command = format!("{} {}", command, quote_command_line(extra_args));
+                    }
+                    return Ok(self.shell_command(&command, working_dir));
                 }
             }
         }
 
-        Err(anyhow::anyhow!("Process {} not found in Procfile", process_name))
+        Err(anyhow::anyhow!(
+            "Process {} not found in Procfile",
+            process_name
+        ))
     }
 
-    fn start_python_app(&self, script_path: &Path) -> Result<Child> {
+    fn python_app_command(&self, script_path: &Path, extra_args: &[String]) -> Result<Command> {
         let working_dir = script_path.parent().unwrap_or(Path::new("."));
-        let script_name = script_path.file_name().unwrap().to_str().unwrap();
+        let script_name = script_path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .ok_or_else(|| anyhow::anyhow!("Invalid Python script path"))?;
+
+        let mut command = Command::new("python");
+        command.arg(script_name);
+        if script_name == "manage.py" {
+            command.arg("runserver");
+        }
+        command.args(extra_args).current_dir(working_dir);
+        Ok(command)
+    }
 
-        let command = if script_name == "manage.py" {
-            vec!["python", script_name, "runserver"]
-        } else {
-            vec!["python", script_name]
+    fn custom_command(
+        &self,
+        parts: &[String],
+        working_dir: &Path,
+        extra_args: &[String],
+    ) -> Result<Command> {
+        if parts.is_empty() {
+            return Err(anyhow::anyhow!("Empty command"));
+        }
+
+        let mut command = Command::new(&parts[0]);
+        command.args(&parts[1..]).current_dir(working_dir);
+        // `cargo run` passes on only the arguments after `--`
+        if parts.len() >= 2 && parts[0] == "cargo" && parts[1] == "run" && !extra_args.is_empty() {
+            command.arg("--");
+        }
+        command.args(extra_args);
+        Ok(command)
+    }
+
+    fn shell_command(&self, command: &str, working_dir: &Path) -> Command {
+        #[cfg(target_os = "windows")]
+        let mut shell = {
+            let mut shell = Command::new("cmd");
+            shell.arg("/C");
+            shell
         };
 
-        Command::new(command[0])
-            .args(&command[1..])
-            .current_dir(working_dir)
-            .spawn()
-            .context(format!("Failed to start Python app: {}", script_name))
+        #[cfg(not(target_os = "windows"))]
+        let mut shell = {
+            let mut shell = Command::new("sh");
+            shell.arg("-c");
+            shell
+        };
+
+        shell.arg(command).current_dir(working_dir);
+        shell
     }
 
-    fn start_custom_command(&self, command: &[String], working_dir: &Path) -> Result<Child> {
-        if command.is_empty() {
-            return Err(anyhow::anyhow!("Empty command"));
+    // Helper methods
+
+    fn command_service(
+        name: String,
+        command: &[&str],
+        working_dir: &Path,
+        inferred_port: Option<u16>,
+        description: String,
+    ) -> DiscoveredService {
+        DiscoveredService {
+            name,
+            service_type: ServiceType::Custom {
+                command: command.iter().map(|part| part.to_string()).collect(),
+                working_dir: working_dir.to_path_buf(),
+            },
+            working_directory: working_dir.to_path_buf(),
+            inferred_port,
+            description,
         }
+    }
 
-        Command::new(&command[0])
-            .args(&command[1..])
-            .current_dir(working_dir)
-            .spawn()
-            .context("Failed to start custom command")
+    /// Binaries `cargo run` can start: `[[bin]]` targets, the package itself
+    /// when it has src/main.rs, and src/bin/*
+    fn cargo_binaries(manifest: &toml::Value, dir: &Path) -> Vec<String> {
+        let mut binaries: Vec<String> = manifest
+            .get("bin")
+            .and_then(|bins| bins.as_array())
+            .map(|bins| {
+                bins.iter()
+                    .filter_map(|bin| bin.get("name")?.as_str().map(String::from))
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        let package = manifest
+            .get("package")
+            .and_then(|package| package.get("name"))
+            .and_then(|name| name.as_str());
+        if let Some(package) = package {
+            if dir.join("src/main.rs").is_file() {
+                binaries.push(package.to_string());
+            }
+        }
+
+        if let Ok(entries) = fs::read_dir(dir.join("src/bin")) {
+            let mut extra: Vec<String> = entries
+                .flatten()
+                .map(|entry| entry.path())
+                .filter_map(|path| {
+                    let name = if path.extension().is_some_and(|ext| ext == "rs") {
+                        path.file_stem()
+                    } else if path.join("main.rs").is_file() {
+                        path.file_name()
+                    } else {
+                        None
+                    };
+                    name?.to_str().map(String::from)
+                })
+                .collect();
+            extra.sort();
+            binaries.extend(extra);
+        }
+
+        let mut seen = std::collections::HashSet::new();
+        binaries.retain(|binary| seen.insert(binary.clone()));
+        binaries
     }
 
-    fn execute_shell_command(&self, command: &str, working_dir: &Path) -> Result<Child> {
-        #[cfg(target_os = "windows")]
-        {
-            Command::new("cmd")
-                .arg("/C")
-                .arg(command)
-                .current_dir(working_dir)
-                .spawn()
-                .context("Failed to execute shell command")
+    fn is_go_main_package(dir: &Path) -> bool {
+        let Ok(entries) = fs::read_dir(dir) else {
+            return false;
+        };
+        entries.flatten().any(|entry| {
+            let file_name = entry.file_name();
+            let file_name = file_name.to_string_lossy();
+            file_name.ends_with(".go")
+                && !file_name.ends_with("_test.go")
+                && fs::read_to_string(entry.path())
+                    .map(|source| source.lines().any(|line| line.trim() == "package main"))
+                    .unwrap_or(false)
+        })
+    }
+
+    /// The project's Maven/Gradle wrapper when it ships one, the global tool otherwise
+    fn build_tool(dir: &Path, wrapper: &str, windows_wrapper: &str, tool: &str) -> String {
+        if cfg!(target_os = "windows") {
+            if dir.join(windows_wrapper).is_file() {
+                return windows_wrapper.to_string();
+            }
+        } else if dir.join(wrapper).is_file() {
+            return format!("./{}", wrapper);
         }
+        tool.to_string()
+    }
 
-        #[cfg(not(target_os = "windows"))]
-        {
-            Command::new("sh")
-                .arg("-c")
-                .arg(command)
-                .current_dir(working_dir)
-                .spawn()
-                .context("Failed to execute shell command")
+    /// The package manager a JavaScript project uses, going by its lockfiles
+    fn package_runner(dir: &Path) -> &'static str {
+        let has = |file_name: &str| dir.join(file_name).exists();
+        if has("pnpm-lock.yaml") || has("pnpm-workspace.yaml") {
+            "pnpm"
+        } else if has("yarn.lock") {
+            "yarn"
+        } else if has("bun.lockb") || has("bun.lock") {
+            "bun"
+        } else {
+            "npm"
         }
     }
 
-    // Helper methods
+    /// Package directories of an npm/yarn/pnpm workspace: the `workspaces` globs in
+    /// package.json or the `packages` in pnpm-workspace.yaml, falling back to Nx's
+    /// apps/, libs/ and packages/ when nx.json is present
+    fn workspace_packages(root: &Path) -> Vec<PathBuf> {
+        let package_json: Option<serde_json::Value> = fs::read_to_string(root.join("package.json"))
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok());
+        let workspaces = package_json.as_ref().and_then(|json| {
+            let workspaces = json.get("workspaces")?;
+            // Yarn also accepts `{"packages": [...]}`
+            workspaces
+                .as_array()
+                .or_else(|| workspaces.get("packages")?.as_array())
+                .cloned()
+        });
+        let mut patterns: Vec<String> = workspaces
+            .unwrap_or_default()
+            .iter()
+            .filter_map(|pattern| pattern.as_str().map(String::from))
+            .collect();
+
+        if let Ok(content) = fs::read_to_string(root.join("pnpm-workspace.yaml")) {
+            if let Ok(workspace) = serde_yaml::from_str::<serde_yaml::Value>(&content) {
+                patterns.extend(
+                    workspace
+                        .get("packages")
+                        .and_then(|packages| packages.as_sequence())
+                        .into_iter()
+                        .flatten()
+                        .filter_map(|pattern| pattern.as_str().map(String::from)),
+                );
+            }
+        }
+
+        if patterns.is_empty() && root.join("nx.json").exists() {
+            patterns = ["apps/*", "libs/*", "packages/*"]
+                .map(String::from)
+                .to_vec();
+        }
+
+        let (excluded, included): (Vec<String>, Vec<String>) = patterns
+            .into_iter()
+            .partition(|pattern| pattern.starts_with('!'));
+        let mut packages: Vec<PathBuf> = included
+            .iter()
+            .flat_map(|pattern| Self::expand_workspace_pattern(root, pattern))
+            .filter(|dir| dir.join("package.json").is_file() || dir.join("project.json").is_file())
+            .filter(|dir| {
+                !excluded.iter().any(|pattern| {
+                    Self::expand_workspace_pattern(root, &pattern[1..]).contains(dir)
+                })
+            })
+            .collect();
+        packages.sort();
+        packages.dedup();
+        packages
+    }
+
+    /// Directories matching a workspace glob such as `packages/*`, `apps/web` or
+    /// `services/*-api`. Only the last path segment may hold a `*`; `**` is
+    /// treated as one level.
+    fn expand_workspace_pattern(root: &Path, pattern: &str) -> Vec<PathBuf> {
+        let pattern = pattern.trim_start_matches("./").trim_end_matches('/');
+        let pattern = pattern.strip_suffix("/**").unwrap_or(pattern);
+        let (parent, last) = match pattern.rsplit_once('/') {
+            Some((parent, last)) => (root.join(parent), last),
+            None => (root.to_path_buf(), pattern),
+        };
+        if !last.contains('*') {
+            let dir = parent.join(last);
+            return if dir.is_dir() { vec![dir] } else { Vec::new() };
+        }
+
+        let (prefix, suffix) = last.split_once('*').unwrap_or((last, ""));
+        let suffix = suffix.trim_start_matches('*');
+        let Ok(entries) = fs::read_dir(&parent) else {
+            return Vec::new();
+        };
+        entries
+            .flatten()
+            .map(|entry| entry.path())
+            .filter(|path| path.is_dir())
+            .filter(|path| {
+                let name = path.file_name().unwrap_or_default().to_string_lossy();
+                name.len() >= prefix.len() + suffix.len()
+                    && name.starts_with(prefix)
+                    && name.ends_with(suffix)
+                    && !name.starts_with('.')
+                    && name != "node_modules"
+            })
+            .collect()
+    }
+
+    /// Drop `//` and `/* */` comments and trailing commas so JSONC files such as
+    /// deno.jsonc, turbo.json and devcontainer.json parse as JSON
+    fn strip_json_comments(content: &str) -> String {
+        let mut output = String::with_capacity(content.len());
+        let mut chars = content.chars().peekable();
+        let mut in_string = false;
+
+        while let Some(c) = chars.next() {
+            if in_string {
+                output.push(c);
+                match c {
+                    '\\' => output.extend(chars.next()),
+                    '"' => in_string = false,
+                    _ => {}
+                }
+                continue;
+            }
+            match (c, chars.peek()) {
+                ('"', _) => {
+                    in_string = true;
+                    output.push(c);
+                }
+                ('/', Some('/')) => while chars.next_if(|&next| next != '\n').is_some() {},
+                ('/', Some('*')) => {
+                    chars.next();
+                    let mut previous = ' ';
+                    for next in chars.by_ref() {
+                        if previous == '*' && next == '/' {
+                            break;
+                        }
+                        previous = next;
+                    }
+                }
+                ('}' | ']', _) => {
+                    let kept = output.trim_end().len();
+                    if output[..kept].ends_with(',') {
+                        output.remove(kept - 1);
+                    }
+                    output.push(c);
+                }
+                _ => output.push(c),
+            }
+        }
+
+        output
+    }
 
     fn infer_port_from_script_name(script_name: &str) -> Option<u16> {
         // Try to infer port from common script names
@@ -387,6 +1312,119 @@ impl ServiceDetector {
         None
     }
 
+    /// Task names that usually start a long-running server: `run`, `serve`,
+    /// `dev`, `start` and `server`, alone or as a word such as `dev-api` or `run_web`
+    fn looks_like_server_task(name: &str) -> bool {
+        const SERVER_WORDS: [&str; 5] = ["run", "serve", "server", "dev", "start"];
+        let name = name.to_lowercase();
+        !name.contains("test")
+            && !name.contains("build")
+            && name
+                .split(['-', '_', ':', '.'])
+                .any(|word| SERVER_WORDS.contains(&word))
+    }
+
+    /// Explicit targets of a Makefile with their recipes, skipping special
+    /// targets (`.PHONY`), pattern rules and variable assignments
+    fn parse_makefile_targets(content: &str) -> Vec<(String, String)> {
+        let mut targets: Vec<(String, String)> = Vec::new();
+        let mut current: Vec<usize> = Vec::new();
+
+        for line in content.lines() {
+            if line.starts_with('\t') {
+                for &index in &current {
+                    let recipe = &mut targets[index].1;
+                    recipe.push_str(line.trim());
+                    recipe.push('\n');
+                }
+                continue;
+            }
+            if line.trim().is_empty() || line.trim_start().starts_with('#') {
+                continue;
+            }
+            current.clear();
+
+            let Some((names, rest)) = line.split_once(':') else {
+                continue;
+            };
+            // `VAR := value`, `VAR ::= value` and `VAR = value` are not rules
+            if rest.starts_with('=') || rest.starts_with(":=") || names.contains('=') {
+                continue;
+            }
+            for name in names.split_whitespace() {
+                if name.starts_with('.') || name.contains('%') || name.contains('$') {
+                    continue;
+                }
+                if let Some(index) = targets.iter().position(|(target, _)| target == name) {
+                    current.push(index);
+                } else {
+                    targets.push((name.to_string(), String::new()));
+                    current.push(targets.len() - 1);
+                }
+            }
+        }
+
+        targets
+    }
+
+    /// Public recipes of a justfile with their bodies, skipping settings,
+    /// aliases, variables and `_private` or `[private]` recipes
+    fn parse_justfile_recipes(content: &str) -> Vec<(String, String)> {
+        let mut recipes: Vec<(String, String)> = Vec::new();
+        let mut in_recipe = false;
+        let mut private = false;
+
+        for line in content.lines() {
+            if line.starts_with([' ', '\t']) {
+                if in_recipe {
+                    if let Some((_, body)) = recipes.last_mut() {
+                        body.push_str(line.trim());
+                        body.push('\n');
+                    }
+                }
+                continue;
+            }
+            in_recipe = false;
+
+            let trimmed = line.trim();
+            if trimmed.is_empty() || trimmed.starts_with('#') {
+                continue;
+            }
+            if trimmed.starts_with('[') {
+                private |= trimmed.contains("private");
+                continue;
+            }
+            let first_word = trimmed.split_whitespace().next().unwrap_or_default();
+            if ["set", "alias", "export", "import", "mod"].contains(&first_word)
+                || trimmed.contains(":=")
+            {
+                private = false;
+                continue;
+            }
+
+            let Some((header, _dependencies)) = trimmed.split_once(':') else {
+                private = false;
+                continue;
+            };
+            let name = header
+                .trim_start_matches('@')
+                .split_whitespace()
+                .next()
+                .unwrap_or_default();
+            let valid = !name.is_empty()
+                && name
+                    .chars()
+                    .all(|c| c.is_alphanumeric() || c == '_' || c == '-');
+            if valid && !private && !name.starts_with('_') {
+                recipes.push((name.to_string(), String::new()));
+                in_recipe = true;
+            }
+            private = false;
+        }
+
+        recipes
+    }
+
     fn parse_procfile_line(line: &str) -> Option<(String, String)> {
         // Format: "process_name: command"
         let trimmed = line.trim();
@@ -413,6 +1451,375 @@ impl Default for ServiceDetector {
     }
 }
 
+/// A detected service and the port it is expected on
+#[derive(Debug, Clone, Serialize, PartialEq, Eq)]
+pub struct ServicePort {
+    pub service: String,
+    pub port: Option<u16>,
+}
+
+/// The detected services of one project (the directory itself or a workspace package)
+/// and the ports they are expected on
+#[derive(Debug, Clone, Serialize, PartialEq, Eq)]
+pub struct ProjectPortMapping {
+    /// Directory name, the project name reservations and preflight use
+    pub project: String,
+    pub directory: PathBuf,
+    pub ports: Vec<u16>,
+    pub services: Vec<ServicePort>,
+}
+
+/// A port the services of more than one project expect
+#[derive(Debug, Clone, Serialize, PartialEq, Eq)]
+pub struct SharedPort {
+    pub port: u16,
+    pub projects: Vec<String>,
+}
+
+/// Which project of a monorepo expects which ports, so guard mode, reservations and
+/// the orchestrator all agree on who a port belongs to
+#[derive(Debug, Clone, Default, Serialize, PartialEq, Eq)]
+pub struct PortMap {
+    pub projects: Vec<ProjectPortMapping>,
+    /// Ports two projects would fight over when started together
+    pub shared: Vec<SharedPort>,
+}
+
+impl PortMap {
+    /// Group `services` by the directory they run in
+    pub fn from_services(services: &[DiscoveredService]) -> Self {
+        let mut projects: Vec<ProjectPortMapping> = Vec::new();
+        for service in services {
+            let index = match projects
+                .iter()
+                .position(|project| project.directory == service.working_directory)
+            {
+                Some(index) => index,
+                None => {
+                    projects.push(ProjectPortMapping {
+                        project: project_name(&service.working_directory),
+                        directory: service.working_directory.clone(),
+                        ports: Vec::new(),
+                        services: Vec::new(),
+                    });
+                    projects.len() - 1
+                }
+            };
+            let project = &mut projects[index];
+            project.services.push(ServicePort {
+                service: service.name.clone(),
+                port: service.inferred_port,
+            });
+            if let Some(port) = service.inferred_port {
+                if !project.ports.contains(&port) {
+                    project.ports.push(port);
+                }
+            }
+        }
+        for project in &mut projects {
+            project.ports.sort_unstable();
+        }
+        projects.sort_by(|a, b| a.directory.cmp(&b.directory));
+
+        let mut shared: Vec<SharedPort> = Vec::new();
+        for project in &projects {
+            for &port in &project.ports {
+                match shared.iter_mut().find(|shared| shared.port == port) {
+                    Some(shared) => shared.projects.push(project.project.clone()),
+                    None => shared.push(SharedPort {
+                        port,
+                        projects: vec![project.project.clone()],
+                    }),
+                }
+            }
+        }
+        shared.retain(|shared| shared.projects.len() > 1);
+        shared.sort_by_key(|shared| shared.port);
+
+        Self { projects, shared }
+    }
+
+    /// Every port some project expects, in order
+    pub fn ports(&self) -> Vec<u16> {
+        let mut ports: Vec<u16> = self
+            .projects
+            .iter()
+            .flat_map(|project| project.ports.iter().copied())
+            .collect();
+        ports.sort_unstable();
+        ports.dedup();
+        ports
+    }
+
+    /// The project and service expecting `port`, unless several projects expect it
+    pub fn owner(&self, port: u16) -> Option<(&ProjectPortMapping, &ServicePort)> {
+        if self.shared.iter().any(|shared| shared.port == port) {
+            return None;
+        }
+        self.projects.iter().find_map(|project| {
+            let service = project
+                .services
+                .iter()
+                .find(|service| service.port == Some(port))?;
+            Some((project, service))
+        })
+    }
+}
+
+/// Name of the directory a service runs in, resolving `.` to the current directory
+fn project_name(dir: &Path) -> String {
+    dir.canonicalize()
+        .ok()
+        .as_deref()
+        .unwrap_or(dir)
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_else(|| dir.display().to_string())
+}
+
+/// Ports a project's config files say its dev servers listen on
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ProjectPorts {
+    /// `PORT` in .env
+    pub env_port: Option<u16>,
+    /// `server.port` in vite.config.*, or Vite's default when the file exists
+    pub vite: Option<u16>,
+    /// Next.js's default when next.config.* exists; the port itself is set with -p or PORT
+    pub next: Option<u16>,
+    /// `devServer.port` in webpack.config.*, or webpack-dev-server's default
+    pub webpack: Option<u16>,
+}
+
+const VITE_DEFAULT_PORT: u16 = 5173;
+const NEXT_DEFAULT_PORT: u16 = 3000;
+const WEBPACK_DEFAULT_PORT: u16 = 8080;
+const JS_CONFIG_EXTENSIONS: [&str; 4] = ["js", "mjs", "cjs", "ts"];
+
+impl ProjectPorts {
+    pub fn read(dir: &Path) -> Self {
+        let config = |stem: &str| {
+            JS_CONFIG_EXTENSIONS
+                .iter()
+                .find_map(|ext| fs::read_to_string(dir.join(format!("{}.{}", stem, ext))).ok())
+        };
+
+        Self {
+            env_port: fs::read_to_string(dir.join(".env"))
+                .ok()
+                .and_then(|env| env_file_port(&env)),
+            vite: config("vite.config")
+                .map(|content| config_block_port(&content, "server").unwrap_or(VITE_DEFAULT_PORT)),
+            next: config("next.config").map(|_| NEXT_DEFAULT_PORT),
+            webpack: config("webpack.config").map(|content| {
+                config_block_port(&content, "devServer").unwrap_or(WEBPACK_DEFAULT_PORT)
+            }),
+        }
+    }
+
+    /// Port a package.json script or Deno task will listen on: a port given in the
+    /// command itself, then the config of the dev server it runs, then `.env`
+    pub fn script_port(&self, command: &str) -> Option<u16> {
+        if let Some(port) = command_port(command) {
+            return Some(port);
+        }
+        let runs = |tool: &str| {
+            command
+                .split(|c: char| c.is_whitespace() || c == '&' || c == ';')
+                .any(|word| word == tool)
+        };
+        if runs("vite") {
+            return self.vite.or(Some(VITE_DEFAULT_PORT));
+        }
+        if runs("webpack-dev-server") || (runs("webpack") && runs("serve")) {
+            return self.webpack.or(Some(WEBPACK_DEFAULT_PORT));
+        }
+        if runs("next") {
+            return self.env_port.or(self.next).or(Some(NEXT_DEFAULT_PORT));
+        }
+        self.env_port
+    }
+}
+
+/// `--port 4000`, `--port=4000`, `-p 4000` or `PORT=4000` in a command
+pub(crate) fn command_port(command: &str) -> Option<u16> {
+    let pattern = regex::Regex::new(r"(?:^|\s)(?:--port[= ]|-p |PORT=)(\d{2,5})\b").ok()?;
+    pattern.captures(command)?[1].parse().ok()
+}
+
+/// The first `port: N` after `block` (e.g. `server` in a Vite config)
+fn config_block_port(content: &str, block: &str) -> Option<u16> {
+    let pattern = regex::Regex::new(r"\bport\s*:\s*(\d{2,5})\b").ok()?;
+    let start = content.find(block)?;
+    pattern.captures(&content[start..])?[1].parse().ok()
+}
+
+/// `PORT` from the contents of a .env file
+fn env_file_port(content: &str) -> Option<u16> {
+    content.lines().find_map(|line| {
+        let line = line.trim().trim_start_matches("export ");
+        let value = line.strip_prefix("PORT")?.trim_start().strip_prefix('=')?;
+        value
+            .trim()
+            .trim_matches(|c| c == '"' || c == '\'')
+            .parse()
+            .ok()
+    })
+}
+
+/// First host port each docker-compose service publishes, from `ports` entries
+/// like `"8080:80"`, `"127.0.0.1:8080:80/tcp"` or `{published: 8080, target: 80}`
+pub(crate) fn compose_published_ports(content: &str) -> HashMap<String, u16> {
+    let Ok(compose) = serde_yaml::from_str::<serde_yaml::Value>(content) else {
+        return HashMap::new();
+    };
+    let Some(services) = compose.get("services").and_then(|s| s.as_mapping()) else {
+        return HashMap::new();
+    };
+
+    services
+        .iter()
+        .filter_map(|(name, service)| {
+            let ports = service.get("ports")?.as_sequence()?;
+            let port = ports.iter().find_map(|entry| match entry {
+                serde_yaml::Value::Mapping(long) => match long.get("published")? {
+                    serde_yaml::Value::Number(port) => port.as_u64()?.try_into().ok(),
+                    serde_yaml::Value::String(port) => port.parse().ok(),
+                    _ => None,
+                },
+                serde_yaml::Value::String(short) => {
+                    // A lone container port has no fixed host port
+                    let mapping = short.split('/').next()?;
+                    let mut parts = mapping.rsplit(':');
+                    parts.next()?;
+                    parts.next()?.split('-').next()?.parse().ok()
+                }
+                _ => None,
+            })?;
+            Some((name.as_str()?.to_string(), port))
+        })
+        .collect()
+}
+
+/// A built-in preset suggested by the files in a project directory
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProjectPreset {
+    pub preset: &'static str,
+    pub reason: String,
+}
+
+const COMPOSE_FILES: [&str; 4] = [
+    "docker-compose.yml",
+    "docker-compose.yaml",
+    "compose.yml",
+    "compose.yaml",
+];
+const PYTHON_FILES: [&str; 5] = [
+    "pyproject.toml",
+    "requirements.txt",
+    "Pipfile",
+    "manage.py",
+    "setup.py",
+];
+const REACT_PACKAGES: [&str; 4] = ["react", "next", "vite", "react-scripts"];
+
+/// A JSON file that may contain comments and trailing commas
+fn read_jsonc(path: &Path) -> Option<serde_json::Value> {
+    let content = fs::read_to_string(path).ok()?;
+    serde_json::from_str(&ServiceDetector::strip_json_comments(&content)).ok()
+}
+
+/// `command` run through the platform shell
+fn shell_invocation(command: &str) -> Vec<String> {
+    let (shell, flag) = if cfg!(target_os = "windows") {
+        ("cmd", "/C")
+    } else {
+        ("sh", "-c")
+    };
+    vec![shell.to_string(), flag.to_string(), command.to_string()]
+}
+
+/// Ports a dev container forwards (`forwardPorts` and `appPort` in
+/// .devcontainer/devcontainer.json or .devcontainer.json), which are the ports the
+/// editor expects the project to listen on
+pub fn devcontainer_ports(dir: &Path) -> Vec<u16> {
+    let Some(config) = [".devcontainer/devcontainer.json", ".devcontainer.json"]
+        .iter()
+        .find_map(|file_name| read_jsonc(&dir.join(file_name)))
+    else {
+        return Vec::new();
+    };
+
+    let mut ports = Vec::new();
+    for key in ["forwardPorts", "appPort"] {
+        let entries = match config.get(key) {
+            Some(serde_json::Value::Array(entries)) => entries.clone(),
+            Some(entry) => vec![entry.clone()],
+            None => Vec::new(),
+        };
+        for entry in entries {
+            // `3000`, `"3000"`, or `"db:5432"` for a port of another container
+            let port = match &entry {
+                serde_json::Value::Number(port) => {
+                    port.as_u64().and_then(|port| u16::try_from(port).ok())
+                }
+                serde_json::Value::String(port) => {
+                    let mut segments = port.rsplit(':');
+                    let last = segments.next();
+                    // appPort publishes `host:container`, forwardPorts names `host:port`
+                    let port = if key == "appPort" {
+                        segments.next().or(last)
+                    } else {
+                        last
+                    };
+                    port.and_then(|port| port.parse().ok())
+                }
+                _ => None,
+            };
+            if let Some(port) = port.filter(|&port| port > 0 && !ports.contains(&port)) {
+                ports.push(port);
+            }
+        }
+    }
+    ports
+}
+
+/// Pick a preset from the project type in `dir`: a compose file or a mixed
+/// Node/Python repo gets `full`, React apps `react`, other Node apps `node`,
+/// Python apps `python` and Rust crates `dev`.
+pub fn detect_project_preset(dir: &Path) -> Option<ProjectPreset> {
+    let found = |names: &[&'static str]| names.iter().copied().find(|n| dir.join(n).is_file());
+    let suggest = |preset, reason: String| Some(ProjectPreset { preset, reason });
+
+    if let Some(compose) = found(&COMPOSE_FILES) {
+        return suggest("full", format!("found {}", compose));
+    }
+
+    let package_json = dir.join("package.json");
+    let python = found(&PYTHON_FILES);
+    match (package_json.is_file(), python) {
+        (true, Some(python)) => suggest("full", format!("found package.json and {}", python)),
+        (true, None) => match react_dependency(&package_json) {
+            Some(package) => suggest("react", format!("package.json depends on {}", package)),
+            None => suggest("node", "found package.json".to_string()),
+        },
+        (false, Some(python)) => suggest("python", format!("found {}", python)),
+        (false, None) if dir.join("Cargo.toml").is_file() => {
+            suggest("dev", "found Cargo.toml".to_string())
+        }
+        (false, None) => None,
+    }
+}
+
+fn react_dependency(package_json: &Path) -> Option<&'static str> {
+    let content = fs::read_to_string(package_json).ok()?;
+    let json: serde_json::Value = serde_json::from_str(&content).ok()?;
+    REACT_PACKAGES.iter().copied().find(|package| {
+        ["dependencies", "devDependencies"]
+            .iter()
+            .any(|section| json.get(section).and_then(|deps| deps.get(package)).is_some())
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -431,10 +1838,492 @@ mod tests {
         assert_eq!(result, Some("web".to_string()));
     }
 
+    #[test]
+    fn test_resolved_env() {
+        let started = StartedService {
+            pid: 1,
+            command: vec!["npm".to_string(), "run".to_string(), "dev".to_string()],
+            working_directory: PathBuf::from("."),
+            project_env: ProjectEnv {
+                vars: [
+                    ("DATABASE_URL".to_string(), Some("postgres://localhost/dev".to_string())),
+                    ("PATH".to_string(), None),
+                ]
+                .into_iter()
+                .collect(),
+                sources: vec!["direnv"],
+            },
+        };
+
+        let env = started.resolved_env(&[("PORT".to_string(), "3005".to_string())]);
+        assert_eq!(env["DATABASE_URL"], "postgres://localhost/dev");
+        assert_eq!(env["PORT"], "3005");
+        assert!(!env.contains_key("PATH"));
+        // The rest of port-kill's environment is kept
+        if let Ok(home) = std::env::var("HOME") {
+            assert_eq!(env["HOME"], home);
+        }
+    }
+
+    #[test]
+    fn test_port_map() {
+        let service = |name: &str, dir: &str, port| DiscoveredService {
+            name: name.to_string(),
+            service_type: ServiceType::Custom {
+                command: vec!["npm".to_string(), "run".to_string(), "dev".to_string()],
+                working_dir: PathBuf::from(dir),
+            },
+            working_directory: PathBuf::from(dir),
+            inferred_port: port,
+            description: String::new(),
+        };
+        let map = PortMap::from_services(&[
+            service("web:dev", "repo/packages/web", Some(3000)),
+            service("web:start", "repo/packages/web", Some(3000)),
+            service("api:dev", "repo/packages/api", Some(8000)),
+            service("api:worker", "repo/packages/api", None),
+            service("admin:dev", "repo/packages/admin", Some(3000)),
+        ]);
+
+        let projects: Vec<_> = map.projects.iter().map(|p| p.project.as_str()).collect();
+        assert_eq!(projects, ["admin", "api", "web"]);
+        assert_eq!(map.projects[1].ports, [8000]);
+        assert_eq!(map.projects[1].services.len(), 2);
+        assert_eq!(map.projects[2].ports, [3000]);
+        assert_eq!(map.ports(), [3000, 8000]);
+        assert_eq!(
+            map.shared,
+            [SharedPort {
+                port: 3000,
+                projects: vec!["admin".to_string(), "web".to_string()],
+            }]
+        );
+
+        let (project, service) = map.owner(8000).unwrap();
+        assert_eq!((project.project.as_str(), service.service.as_str()), ("api", "api:dev"));
+        // Nobody owns a port two projects expect
+        assert!(map.owner(3000).is_none());
+        assert!(map.owner(5432).is_none());
+    }
+
     #[test]
     fn test_infer_port() {
         assert_eq!(ServiceDetector::infer_port_from_script_name("dev"), Some(3000));
         assert_eq!(ServiceDetector::infer_port_from_script_name("dev:api"), Some(8000));
     }
-}
 
+    #[test]
+    fn test_pass_through_args() {
+        let detector = ServiceDetector::with_paths(Vec::new());
+        let extra_args = vec!["--host".to_string(), "0.0.0.0".to_string()];
+        let args = |command: Command| -> Vec<String> {
+            command
+                .get_args()
+                .map(|arg| arg.to_string_lossy().to_string())
+                .collect()
+        };
+
+        let npm = detector
+            .npm_script_command("dev", Path::new("app/package.json"), &extra_args)
+            .unwrap();
+        assert_eq!(args(npm), ["run", "dev", "--", "--host", "0.0.0.0"]);
+        let npm = detector
+            .npm_script_command("dev", Path::new("app/package.json"), &[])
+            .unwrap();
+        assert_eq!(args(npm), ["run", "dev"]);
+
+        let docker = detector
+            .docker_service_command("web", Path::new("docker-compose.yml"), &extra_args)
+            .unwrap();
+        assert_eq!(args(docker), ["up", "--host", "0.0.0.0", "web"]);
+
+        let parts =
+            |parts: &[&str]| -> Vec<String> { parts.iter().map(|p| p.to_string()).collect() };
+        let cargo = detector
+            .custom_command(
+                &parts(&["cargo", "run", "--bin", "api"]),
+                Path::new("."),
+                &extra_args,
+            )
+            .unwrap();
+        assert_eq!(
+            args(cargo),
+            ["run", "--bin", "api", "--", "--host", "0.0.0.0"]
+        );
+        let go = detector
+            .custom_command(&parts(&["go", "run", "."]), Path::new("."), &extra_args)
+            .unwrap();
+        assert_eq!(args(go), ["run", ".", "--host", "0.0.0.0"]);
+    }
+
+    #[test]
+    fn test_discover_polyglot_services() {
+        let dir = std::env::temp_dir().join(format!("port-kill-polyglot-{}", std::process::id()));
+        fs::create_dir_all(dir.join("src/bin")).unwrap();
+        fs::create_dir_all(dir.join("cmd/worker")).unwrap();
+        fs::create_dir_all(dir.join("bin")).unwrap();
+        fs::write(dir.join("Cargo.toml"), "[package]\nname = \"api\"\n").unwrap();
+        fs::write(dir.join("src/main.rs"), "fn main() {}\n").unwrap();
+        fs::write(dir.join("src/bin/seed.rs"), "fn main() {}\n").unwrap();
+        fs::write(dir.join("go.mod"), "module example.com/app\n").unwrap();
+        fs::write(
+            dir.join("cmd/worker/main.go"),
+            "package main\n\nfunc main() {}\n",
+        )
+        .unwrap();
+        fs::write(
+            dir.join("build.gradle.kts"),
+            "plugins { id(\"org.springframework.boot\") }\n",
+        )
+        .unwrap();
+        fs::write(dir.join("Gemfile"), "gem \"rails\", \"~> 7.1\"\n").unwrap();
+        fs::write(dir.join("bin/rails"), "").unwrap();
+        fs::write(
+            dir.join("deno.jsonc"),
+            "{\n  // dev server\n  \"tasks\": {\"dev\": \"deno run -A main.ts\", \"test\": \"deno test\"}\n}\n",
+        )
+        .unwrap();
+        fs::write(
+            dir.join("package.json"),
+            r#"{"scripts": {"dev": "bun --watch index.ts"}}"#,
+        )
+        .unwrap();
+        fs::write(dir.join("bun.lockb"), "").unwrap();
+
+        let services = ServiceDetector::with_paths(vec![dir.clone()])
+            .discover_services()
+            .unwrap();
+        let names: Vec<&str> = services.iter().map(|s| s.name.as_str()).collect();
+        assert_eq!(
+            names,
+            vec![
+                "bun:dev",
+                "cargo:api",
+                "cargo:seed",
+                "go:worker",
+                "gradle:bootRun",
+                "rails:server",
+                "deno:dev"
+            ]
+        );
+
+        let command = |name: &str| match &services
+            .iter()
+            .find(|s| s.name == name)
+            .unwrap()
+            .service_type
+        {
+            ServiceType::Custom { command, .. } => command.join(" "),
+            other => panic!("unexpected service type {:?}", other),
+        };
+        assert_eq!(command("cargo:seed"), "cargo run --bin seed");
+        assert_eq!(command("go:worker"), "go run ./cmd/worker");
+        assert_eq!(command("gradle:bootRun"), "gradle bootRun");
+        assert_eq!(command("bun:dev"), "bun run dev");
+        let rails = services.iter().find(|s| s.name == "rails:server").unwrap();
+        assert_eq!(rails.inferred_port, Some(3000));
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_discover_task_runner_services() {
+        let makefile = "\
+PORT := 8080
+.PHONY: run serve test-dev
+
+run serve: deps
+\tgo run . --port $(PORT)
+
+dev-api:
+\tuvicorn app:api --port 8001
+
+test-dev build:
+\tgo test ./...
+%.o: %.c
+\tcc -c $<
+";
+        let targets = ServiceDetector::parse_makefile_targets(makefile);
+        let names: Vec<&str> = targets.iter().map(|(name, _)| name.as_str()).collect();
+        assert_eq!(names, ["run", "serve", "dev-api", "test-dev", "build"]);
+        assert_eq!(targets[2].1, "uvicorn app:api --port 8001\n");
+
+        let justfile = "\
+set dotenv-load
+port := \"4000\"
+alias s := serve
+
+# Start the server
+serve:
+    cargo run -- --port 4000
+
+[private]
+dev-internal:
+    echo hidden
+
+_dev-helper:
+    echo hidden
+
+@run-web target=\"web\": serve
+    echo {{target}}
+";
+        let recipes = ServiceDetector::parse_justfile_recipes(justfile);
+        let names: Vec<&str> = recipes.iter().map(|(name, _)| name.as_str()).collect();
+        assert_eq!(names, ["serve", "run-web"]);
+
+        assert!(ServiceDetector::looks_like_server_task("serve"));
+        assert!(ServiceDetector::looks_like_server_task("run_web"));
+        assert!(!ServiceDetector::looks_like_server_task("test-dev"));
+        assert!(!ServiceDetector::looks_like_server_task("deps"));
+        assert!(!ServiceDetector::looks_like_server_task("runtime"));
+
+        let dir = std::env::temp_dir().join(format!("port-kill-tasks-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("Makefile"), makefile).unwrap();
+        fs::write(dir.join("justfile"), justfile).unwrap();
+        let services = ServiceDetector::with_paths(vec![dir.clone()])
+            .discover_services()
+            .unwrap();
+        let summary: Vec<(&str, Option<u16>)> = services
+            .iter()
+            .map(|service| (service.name.as_str(), service.inferred_port))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("make:run", None),
+                ("make:serve", Some(3000)),
+                ("make:dev-api", Some(8001)),
+                ("just:serve", Some(4000)),
+                ("just:run-web", None),
+            ]
+        );
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_editor_config() {
+        let dir = std::env::temp_dir().join(format!("port-kill-editor-{}", std::process::id()));
+        fs::create_dir_all(dir.join(".devcontainer")).unwrap();
+        fs::create_dir_all(dir.join(".vscode")).unwrap();
+        assert!(devcontainer_ports(&dir).is_empty());
+
+        fs::write(
+            dir.join(".devcontainer/devcontainer.json"),
+            r#"{
+  "name": "shop", // the dev container
+  /* Ports the editor forwards */
+  "forwardPorts": [3000, "8080", "db:5432", 3000,],
+  "appPort": "127.0.0.1:9229:9229",
+}"#,
+        )
+        .unwrap();
+        assert_eq!(devcontainer_ports(&dir), vec![3000, 8080, 5432, 9229]);
+
+        fs::write(
+            dir.join(".vscode/tasks.json"),
+            r#"{
+  // See https://go.microsoft.com/fwlink/?LinkId=733558
+  "version": "2.0.0",
+  "tasks": [
+    {"label": "Dev Server", "type": "shell", "command": "npm run dev", "args": ["--port", "4000"]},
+    {"label": "watch", "type": "npm", "script": "watch", "isBackground": true, "path": "web"},
+    {"label": "api", "type": "shell", "command": "cd api && ./serve.sh", "isBackground": true},
+    {"label": "serve docs", "type": "process", "command": "${config:python}", "args": ["-m", "http.server"]},
+    {"label": "test", "type": "shell", "command": "npm test", "isBackground": true},
+    {"label": "tsc: watch", "type": "shell", "command": "tsc -w", "isBackground": true, "group": "build"},
+  ],
+}"#,
+        )
+        .unwrap();
+        let services = ServiceDetector::with_paths(vec![dir.clone()])
+            .discover_services()
+            .unwrap();
+        let summary: Vec<(String, Vec<String>, Option<u16>)> = services
+            .iter()
+            .map(|service| match &service.service_type {
+                ServiceType::Custom { command, .. } => {
+                    (service.name.clone(), command.clone(), service.inferred_port)
+                }
+                other => panic!("unexpected service type {:?}", other),
+            })
+            .collect();
+        let strings = |parts: &[&str]| parts.iter().map(|p| p.to_string()).collect::<Vec<_>>();
+        assert_eq!(
+            summary,
+            vec![
+                (
+                    "vscode:dev-server".to_string(),
+                    strings(&["npm", "run", "dev", "--port", "4000"]),
+                    Some(4000)
+                ),
+                (
+                    "vscode:watch".to_string(),
+                    strings(&["npm", "run", "watch"]),
+                    None
+                ),
+                (
+                    "vscode:api".to_string(),
+                    shell_invocation("cd api && ./serve.sh"),
+                    None
+                ),
+            ]
+        );
+        assert_eq!(services[1].working_directory, dir.join("web"));
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_discover_workspace_services() {
+        let dir = std::env::temp_dir().join(format!("port-kill-workspace-{}", std::process::id()));
+        for package in [
+            "apps/web",
+            "apps/api",
+            "apps/.cache",
+            "packages/ui",
+            "tools/scripts",
+        ] {
+            fs::create_dir_all(dir.join(package)).unwrap();
+        }
+        fs::write(
+            dir.join("package.json"),
+            r#"{"workspaces": ["apps/*", "packages/ui", "!apps/api"], "scripts": {"dev": "turbo dev"}}"#,
+        )
+        .unwrap();
+        fs::write(dir.join("pnpm-lock.yaml"), "").unwrap();
+        fs::write(
+            dir.join("apps/web/package.json"),
+            r#"{"name": "@acme/web", "scripts": {"dev": "next dev -p 3001", "build": "next build"}}"#,
+        )
+        .unwrap();
+        fs::write(
+            dir.join("apps/api/package.json"),
+            r#"{"scripts": {"dev": "node server.js"}}"#,
+        )
+        .unwrap();
+        fs::write(
+            dir.join("packages/ui/project.json"),
+            r#"{"name": "ui", "targets": {"storybook": {"options": {"port": 6006}}, "test": {}}}"#,
+        )
+        .unwrap();
+        fs::write(
+            dir.join("tools/scripts/package.json"),
+            r#"{"scripts": {"dev": "x"}}"#,
+        )
+        .unwrap();
+        fs::write(
+            dir.join("turbo.json"),
+            "{\n  // pipeline\n  \"tasks\": {\"dev\": {\"persistent\": true}, \"build\": {}, \"web#lint\": {}}\n}\n",
+        )
+        .unwrap();
+
+        let services = ServiceDetector::with_paths(vec![dir.clone()])
+            .discover_services()
+            .unwrap();
+        let summary: Vec<(String, String, Option<u16>)> = services
+            .iter()
+            .filter(|service| service.name != "npm:dev")
+            .map(|service| {
+                let command = match &service.service_type {
+                    ServiceType::Custom { command, .. } => command.join(" "),
+                    other => panic!("unexpected service type {:?}", other),
+                };
+                (service.name.clone(), command, service.inferred_port)
+            })
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                (
+                    "web:dev".to_string(),
+                    "pnpm run dev".to_string(),
+                    Some(3001)
+                ),
+                (
+                    "ui:storybook".to_string(),
+                    "npx nx run ui:storybook".to_string(),
+                    Some(6006)
+                ),
+                (
+                    "turbo:dev".to_string(),
+                    "npx turbo run dev".to_string(),
+                    None
+                ),
+            ]
+        );
+        assert_eq!(
+            services[1].working_directory,
+            dir.join("apps/web"),
+            "workspace scripts run in their package"
+        );
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_infer_ports_from_config() {
+        let dir = std::env::temp_dir().join(format!("port-kill-ports-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(
+            dir.join("vite.config.ts"),
+            "export default defineConfig({\n  preview: { open: true },\n  server: { host: true, port: 4321 },\n})\n",
+        )
+        .unwrap();
+        fs::write(dir.join(".env"), "# local\nexport PORT=\"3005\"\n").unwrap();
+        fs::write(
+            dir.join("package.json"),
+            r#"{"scripts": {"dev": "vite", "start": "node server.js", "api": "PORT=9001 node api.js", "docs": "next dev -p 4000"}}"#,
+        )
+        .unwrap();
+        fs::write(
+            dir.join("docker-compose.yml"),
+            "services:\n  db:\n    image: postgres\n    ports:\n      - \"127.0.0.1:5433:5432\"\n  cache:\n    ports:\n      - target: 6379\n        published: 6380\n  worker:\n    ports:\n      - \"9229\"\n",
+        )
+        .unwrap();
+
+        let services = ServiceDetector::with_paths(vec![dir.clone()])
+            .discover_services()
+            .unwrap();
+        let port = |name: &str| {
+            services
+                .iter()
+                .find(|s| s.name == name)
+                .unwrap()
+                .inferred_port
+        };
+        assert_eq!(port("npm:dev"), Some(4321));
+        assert_eq!(port("npm:start"), Some(3005));
+        assert_eq!(port("npm:api"), Some(9001));
+        assert_eq!(port("npm:docs"), Some(4000));
+        assert_eq!(port("docker:db"), Some(5433));
+        assert_eq!(port("docker:cache"), Some(6380));
+        assert_eq!(port("docker:worker"), None);
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_detect_project_preset() {
+        let dir = std::env::temp_dir().join(format!(
+            "port-kill-project-type-{}",
+            std::process::id()
+        ));
+        fs::create_dir_all(&dir).unwrap();
+        assert_eq!(detect_project_preset(&dir), None);
+
+        fs::write(dir.join("package.json"), r#"{"dependencies": {"express": "4"}}"#).unwrap();
+        assert_eq!(detect_project_preset(&dir).unwrap().preset, "node");
+
+        fs::write(dir.join("package.json"), r#"{"devDependencies": {"vite": "5"}}"#).unwrap();
+        let detected = detect_project_preset(&dir).unwrap();
+        assert_eq!(detected.preset, "react");
+        assert_eq!(detected.reason, "package.json depends on vite");
+
+        fs::write(dir.join("requirements.txt"), "flask\n").unwrap();
+        assert_eq!(detect_project_preset(&dir).unwrap().preset, "full");
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
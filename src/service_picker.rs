@@ -0,0 +1,327 @@
+use crate::service_detector::DiscoveredService;
+use crate::tui::TerminalGuard;
+use anyhow::{anyhow, Context, Result};
+use crossterm::event::{self, Event, KeyCode, KeyEventKind, KeyModifiers};
+use crossterm::{cursor, execute, terminal};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::io::{IsTerminal, Write};
+use std::path::{Path, PathBuf};
+
+/// Rows of the picker shown at once
+const MAX_ROWS: usize = 15;
+
+/// The service last picked for `--start` in each directory
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct StartChoices {
+    choices: BTreeMap<String, String>,
+}
+
+impl StartChoices {
+    pub fn file_path() -> PathBuf {
+        let home_dir = std::env::var("HOME")
+            .or_else(|_| std::env::var("USERPROFILE"))
+            .unwrap_or_else(|_| ".".to_string());
+        PathBuf::from(home_dir).join(".port-kill-start.json")
+    }
+
+    /// The saved choices, or none when there is no file yet or it cannot be read
+    pub fn load() -> Self {
+        std::fs::read_to_string(Self::file_path())
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self) -> Result<()> {
+        let path = Self::file_path();
+        let content = serde_json::to_string_pretty(self)?;
+        std::fs::write(&path, content)
+            .with_context(|| format!("Failed to write {}", path.display()))
+    }
+
+    pub fn get(&self, dir: &Path) -> Option<&str> {
+        self.choices
+            .get(&dir.to_string_lossy().to_string())
+            .map(String::as_str)
+    }
+
+    pub fn remember(&mut self, dir: &Path, service_name: &str) {
+        self.choices
+            .insert(dir.to_string_lossy().to_string(), service_name.to_string());
+    }
+}
+
+/// How well `query` fuzzy-matches `candidate`: its characters must appear in order
+/// (ignoring case). Lower is better; consecutive and early matches score lower.
+pub fn fuzzy_score(query: &str, candidate: &str) -> Option<usize> {
+    let candidate: Vec<char> = candidate.to_lowercase().chars().collect();
+    let mut score = 0;
+    let mut next = 0;
+    let mut previous: Option<usize> = None;
+    for wanted in query.to_lowercase().chars().filter(|c| !c.is_whitespace()) {
+        let found = next + candidate[next..].iter().position(|&c| c == wanted)?;
+        score += match previous {
+            Some(previous) => (found - previous - 1) * 2,
+            None => found,
+        };
+        previous = Some(found);
+        next = found + 1;
+    }
+    Some(score)
+}
+
+/// Indexes of the services matching `query`, best first
+pub fn filter_services(services: &[DiscoveredService], query: &str) -> Vec<usize> {
+    let mut matches: Vec<(usize, usize)> = services
+        .iter()
+        .enumerate()
+        .filter_map(|(index, service)| {
+            let by_name = fuzzy_score(query, &service.name);
+            let by_command = fuzzy_score(query, &service.command_line()).map(|score| score + 100);
+            let score = match (by_name, by_command) {
+                (Some(name), Some(command)) => name.min(command),
+                (name, command) => name.or(command)?,
+            };
+            Some((score, index))
+        })
+        .collect();
+    matches.sort();
+    matches.into_iter().map(|(_, index)| index).collect()
+}
+
+/// What `--start <name>` refers to
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum StartTarget {
+    /// Exactly one service: the full name, or the part after `npm:`, `docker:`, …
+    Found(usize),
+    /// Services the name could mean, best first; empty when nothing matches
+    Ambiguous(Vec<usize>),
+}
+
+pub fn resolve_start_target(services: &[DiscoveredService], name: &str) -> StartTarget {
+    if let Some(index) = services.iter().position(|service| service.name == name) {
+        return StartTarget::Found(index);
+    }
+    let short: Vec<usize> = services
+        .iter()
+        .enumerate()
+        .filter(|(_, service)| service.name.split_once(':').map(|(_, short)| short) == Some(name))
+        .map(|(index, _)| index)
+        .collect();
+    if let [index] = short[..] {
+        return StartTarget::Found(index);
+    }
+    if !short.is_empty() {
+        return StartTarget::Ambiguous(short);
+    }
+    StartTarget::Ambiguous(filter_services(services, name))
+}
+
+/// One row per service, with `>` marking the selected one
+fn picker_rows(
+    services: &[DiscoveredService],
+    shown: &[usize],
+    selected: usize,
+    remembered: Option<&str>,
+) -> Vec<String> {
+    let name_width = shown
+        .iter()
+        .map(|&index| services[index].name.len())
+        .max()
+        .unwrap_or(0);
+    let command_width = shown
+        .iter()
+        .map(|&index| services[index].command_line().len())
+        .max()
+        .unwrap_or(0);
+    shown
+        .iter()
+        .enumerate()
+        .take(MAX_ROWS)
+        .map(|(row, &index)| {
+            let service = &services[index];
+            let port = service
+                .inferred_port
+                .map(|port| format!(":{}", port))
+                .unwrap_or_default();
+            let mut line = format!(
+                "{} {:<name_width$}  {:<command_width$}  {:<6}",
+                if row == selected { ">" } else { " " },
+                service.name,
+                service.command_line(),
+                port
+            );
+            if remembered == Some(service.name.as_str()) {
+                line.push_str("  (last choice)");
+            }
+            line.trim_end().to_string()
+        })
+        .collect()
+}
+
+fn draw(
+    services: &[DiscoveredService],
+    shown: &[usize],
+    selected: usize,
+    query: &str,
+    remembered: Option<&str>,
+) -> Result<()> {
+    let mut lines = vec![
+        "🚀 Start which service? Type to filter · ↑/↓ select · Enter start · Esc cancel"
+            .to_string(),
+        String::new(),
+        format!("  {}▏", query),
+        String::new(),
+    ];
+    if shown.is_empty() {
+        lines.push("  No detected service matches".to_string());
+    } else {
+        lines.extend(picker_rows(services, shown, selected, remembered));
+        if shown.len() > MAX_ROWS {
+            lines.push(format!("  … and {} more", shown.len() - MAX_ROWS));
+        }
+    }
+
+    let mut stdout = std::io::stdout();
+    execute!(
+        stdout,
+        cursor::MoveTo(0, 0),
+        terminal::Clear(terminal::ClearType::All)
+    )?;
+    // Raw mode needs explicit carriage returns
+    write!(stdout, "{}\r\n", lines.join("\r\n"))?;
+    stdout.flush()?;
+    Ok(())
+}
+
+/// Let the user fuzzy-search `services`, starting from `query`, with the
+/// `remembered` service selected when it matches. None when cancelled.
+pub fn pick_service(
+    services: &[DiscoveredService],
+    query: &str,
+    remembered: Option<&str>,
+) -> Result<Option<usize>> {
+    if !std::io::stdin().is_terminal() || !std::io::stdout().is_terminal() {
+        return Err(anyhow!("Choosing a service needs an interactive terminal"));
+    }
+
+    let _screen = TerminalGuard::enter()?;
+    let mut query = query.to_string();
+    let mut selected = None;
+
+    loop {
+        let shown = filter_services(services, &query);
+        // Start on the remembered service until the user moves
+        let row = selected.unwrap_or_else(|| {
+            shown
+                .iter()
+                .take(MAX_ROWS)
+                .position(|&index| Some(services[index].name.as_str()) == remembered)
+                .unwrap_or(0)
+        });
+        let row = row.min(shown.len().min(MAX_ROWS).saturating_sub(1));
+        draw(services, &shown, row, &query, remembered)?;
+
+        let Event::Key(key) = event::read()? else {
+            continue;
+        };
+        if key.kind != KeyEventKind::Press {
+            continue;
+        }
+        if key.modifiers.contains(KeyModifiers::CONTROL) && key.code == KeyCode::Char('c') {
+            return Ok(None);
+        }
+        match key.code {
+            KeyCode::Esc => return Ok(None),
+            KeyCode::Enter => return Ok(shown.get(row).copied()),
+            KeyCode::Up => selected = Some(row.saturating_sub(1)),
+            KeyCode::Down => selected = Some(row + 1),
+            KeyCode::Backspace => {
+                query.pop();
+                selected = None;
+            }
+            KeyCode::Char(c) => {
+                query.push(c);
+                selected = None;
+            }
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::service_detector::ServiceType;
+
+    fn service(name: &str, command: &[&str], port: Option<u16>) -> DiscoveredService {
+        DiscoveredService {
+            name: name.to_string(),
+            service_type: ServiceType::Custom {
+                command: command.iter().map(|part| part.to_string()).collect(),
+                working_dir: PathBuf::from("."),
+            },
+            working_directory: PathBuf::from("."),
+            inferred_port: port,
+            description: String::new(),
+        }
+    }
+
+    #[test]
+    fn test_resolve_start_target() {
+        let services = vec![
+            service("npm:dev", &["npm", "run", "dev"], Some(5173)),
+            service("deno:dev", &["deno", "task", "dev"], None),
+            service("docker:web", &["docker-compose", "up", "web"], Some(8080)),
+            service("cargo:api", &["cargo", "run", "--bin", "api"], None),
+        ];
+        assert_eq!(
+            resolve_start_target(&services, "npm:dev"),
+            StartTarget::Found(0)
+        );
+        assert_eq!(
+            resolve_start_target(&services, "web"),
+            StartTarget::Found(2)
+        );
+        assert_eq!(
+            resolve_start_target(&services, "dev"),
+            StartTarget::Ambiguous(vec![0, 1])
+        );
+        // Fuzzy: the name first, then the command
+        assert_eq!(
+            resolve_start_target(&services, "dkr"),
+            StartTarget::Ambiguous(vec![2])
+        );
+        assert_eq!(filter_services(&services, "cgo"), vec![3]);
+        assert_eq!(filter_services(&services, ""), vec![0, 1, 2, 3]);
+        assert_eq!(
+            resolve_start_target(&services, "zzz"),
+            StartTarget::Ambiguous(Vec::new())
+        );
+
+        assert_eq!(fuzzy_score("dev", "npm:dev"), Some(4));
+        assert!(fuzzy_score("nd", "npm:dev") < fuzzy_score("nv", "npm:dev"));
+        assert_eq!(fuzzy_score("vd", "npm:dev"), None);
+
+        let rows = picker_rows(&services, &[0, 2], 1, Some("docker:web"));
+        assert_eq!(
+            rows,
+            vec![
+                "  npm:dev     npm run dev            :5173",
+                "> docker:web  docker-compose up web  :8080   (last choice)",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_start_choices() {
+        let mut choices = StartChoices::default();
+        let dir = Path::new("/work/shop");
+        assert_eq!(choices.get(dir), None);
+        choices.remember(dir, "npm:dev");
+        choices.remember(dir, "docker:web");
+        assert_eq!(choices.get(dir), Some("docker:web"));
+        assert_eq!(choices.get(Path::new("/work")), None);
+    }
+}
@@ -1,4 +1,5 @@
-use crate::types::ProcessInfo;
+use crate::cli::Args;
+use crate::types::{BindFilter, ProcessInfo};
 use anyhow::Result;
 use regex::Regex;
 use std::collections::HashSet;
@@ -9,6 +10,8 @@ pub struct SmartFilter {
     ignore_patterns: Vec<Regex>,
     ignore_groups: HashSet<String>,
     only_groups: Option<HashSet<String>>,
+    /// `--bind-addr`: keep only sockets bound to one of these
+    bind_filters: Vec<BindFilter>,
 }
 
 impl SmartFilter {
@@ -40,9 +43,49 @@ impl SmartFilter {
             ignore_patterns: compiled_patterns,
             ignore_groups,
             only_groups,
+            bind_filters: Vec::new(),
         })
     }
 
+    /// Build the filter for the user's ignores plus the smart defaults.
+    /// Returns None when nothing would be filtered.
+    pub fn from_args(args: &Args) -> Result<Option<Self>> {
+        // Get smart filter defaults
+        let (smart_ignore_ports, smart_ignore_processes, smart_ignore_groups) =
+            args.get_smart_filter_defaults();
+
+        // Combine user ignores with smart ignores
+        let mut ignore_ports = args.get_ignore_ports_set();
+        ignore_ports.extend(smart_ignore_ports);
+
+        let mut ignore_processes = args.get_ignore_processes_set();
+        ignore_processes.extend(smart_ignore_processes);
+
+        let mut ignore_groups = args.get_ignore_groups_set();
+        ignore_groups.extend(smart_ignore_groups);
+
+        // Check if any filtering is needed
+        if ignore_ports.is_empty()
+            && ignore_processes.is_empty()
+            && args.get_ignore_patterns().is_none()
+            && ignore_groups.is_empty()
+            && args.only_groups.is_none()
+            && args.bind_addr.is_empty()
+        {
+            return Ok(None);
+        }
+
+        let mut filter = Self::new(
+            ignore_ports,
+            ignore_processes,
+            args.get_ignore_patterns(),
+            ignore_groups,
+            args.get_only_groups_set(),
+        )?;
+        filter.bind_filters = args.bind_addr.clone();
+        Ok(Some(filter))
+    }
+
     pub fn should_ignore_process(&self, process_info: &ProcessInfo) -> bool {
         // Check port ignore list
         if self.ignore_ports.contains(&process_info.port) {
@@ -61,6 +104,16 @@ impl SmartFilter {
             }
         }
 
+        // Check bind address filter
+        if !self.bind_filters.is_empty()
+            && !self
+                .bind_filters
+                .iter()
+                .any(|filter| filter.matches(process_info.bind_address))
+        {
+            return true;
+        }
+
         // Check only_groups filter (if specified, only show these groups)
         if let Some(ref only_groups) = self.only_groups {
             match process_info.process_group.as_ref() {
@@ -95,6 +148,44 @@ impl SmartFilter {
     }
 }
 
+/// Additions to and exceptions from the built-in smart filter lists, read from the
+/// `[smart_filter]` section of the config file
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SmartFilterRules {
+    pub ignore_ports: Vec<u16>,
+    pub ignore_processes: Vec<String>,
+    pub ignore_patterns: Vec<String>,
+    pub ignore_groups: Vec<String>,
+    /// Built-in ignores to drop, so e.g. a local Postgres on 5432 stays visible
+    pub allow_ports: Vec<u16>,
+    pub allow_processes: Vec<String>,
+    pub allow_groups: Vec<String>,
+}
+
+impl SmartFilterRules {
+    /// Merge these rules into the built-in ignore sets
+    pub fn apply(
+        &self,
+        ports: &mut HashSet<u16>,
+        processes: &mut HashSet<String>,
+        groups: &mut HashSet<String>,
+    ) {
+        ports.extend(&self.ignore_ports);
+        processes.extend(self.ignore_processes.iter().cloned());
+        groups.extend(self.ignore_groups.iter().cloned());
+
+        for port in &self.allow_ports {
+            ports.remove(port);
+        }
+        for process in &self.allow_processes {
+            processes.remove(process);
+        }
+        for group in &self.allow_groups {
+            groups.remove(group);
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct FilterStats {
     pub ignore_ports_count: usize,
@@ -173,6 +264,9 @@ mod tests {
                 cpu_usage: None,
                 memory_usage: None,
                 memory_percentage: None,
+                protocol: crate::types::Protocol::Tcp,
+                bind_address: None,
+                address_family: None,
             },
         );
 
@@ -192,6 +286,9 @@ mod tests {
                 cpu_usage: None,
                 memory_usage: None,
                 memory_percentage: None,
+                protocol: crate::types::Protocol::Tcp,
+                bind_address: None,
+                address_family: None,
             },
         );
 
@@ -211,6 +308,9 @@ mod tests {
                 cpu_usage: None,
                 memory_usage: None,
                 memory_percentage: None,
+                protocol: crate::types::Protocol::Tcp,
+                bind_address: None,
+                address_family: None,
             },
         );
 
@@ -249,6 +349,9 @@ mod tests {
                 cpu_usage: None,
                 memory_usage: None,
                 memory_percentage: None,
+                protocol: crate::types::Protocol::Tcp,
+                bind_address: None,
+                address_family: None,
             },
         );
 
@@ -268,6 +371,9 @@ mod tests {
                 cpu_usage: None,
                 memory_usage: None,
                 memory_percentage: None,
+                protocol: crate::types::Protocol::Tcp,
+                bind_address: None,
+                address_family: None,
             },
         );
 
@@ -306,6 +412,9 @@ mod tests {
                 cpu_usage: None,
                 memory_usage: None,
                 memory_percentage: None,
+                protocol: crate::types::Protocol::Tcp,
+                bind_address: None,
+                address_family: None,
             },
         );
 
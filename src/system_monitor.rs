@@ -1,5 +1,6 @@
-use std::collections::HashMap;
-use sysinfo::{Pid, System};
+use std::collections::{HashMap, VecDeque};
+use std::path::Path;
+use sysinfo::{Disks, Pid, System};
 
 pub struct SystemMonitor {
     system: System,
@@ -48,12 +49,20 @@ impl SystemMonitor {
         }
     }
 
+    /// CPU usage since the previous refresh, as reported by the OS
+    pub fn get_process_cpu_percent(&self, pid: i32) -> Option<f64> {
+        self.system
+            .process(Pid::from_u32(pid as u32))
+            .map(|process| process.cpu_usage() as f64)
+    }
+
     pub fn get_process_memory_usage(&self, pid: i32) -> Option<(u64, f64)> {
         let pid = Pid::from_u32(pid as u32);
 
         if let Some(process) = self.system.process(pid) {
-            let memory_bytes = process.memory() * 1024; // Convert from KB to bytes
-            let total_memory = self.system.total_memory() * 1024; // Convert from KB to bytes
+            // sysinfo reports bytes
+            let memory_bytes = process.memory();
+            let total_memory = self.system.total_memory();
             let memory_percentage = if total_memory > 0 {
                 (memory_bytes as f64 / total_memory as f64) * 100.0
             } else {
@@ -74,15 +83,30 @@ impl SystemMonitor {
 
     pub fn get_system_info(&self) -> SystemInfo {
         SystemInfo {
-            total_memory: self.system.total_memory() * 1024, // Convert to bytes
-            used_memory: self.system.used_memory() * 1024,   // Convert to bytes
-            total_swap: self.system.total_swap() * 1024,     // Convert to bytes
-            used_swap: self.system.used_swap() * 1024,       // Convert to bytes
+            total_memory: self.system.total_memory(),
+            used_memory: self.system.used_memory(),
+            total_swap: self.system.total_swap(),
+            used_swap: self.system.used_swap(),
             cpu_count: self.system.cpus().len(),
             load_average: sysinfo::System::load_average(),
         }
     }
 
+    /// Usage of the disk holding `path` (the mounted disk with the longest matching mount point)
+    pub fn get_disk_usage(&self, path: &Path) -> Option<DiskUsage> {
+        let disks = Disks::new_with_refreshed_list();
+        disks
+            .list()
+            .iter()
+            .filter(|disk| path.starts_with(disk.mount_point()))
+            .max_by_key(|disk| disk.mount_point().as_os_str().len())
+            .map(|disk| DiskUsage {
+                mount_point: disk.mount_point().to_string_lossy().to_string(),
+                total_bytes: disk.total_space(),
+                available_bytes: disk.available_space(),
+            })
+    }
+
     pub fn cleanup_old_processes(&mut self) {
         // Remove processes that are no longer running
         let current_pids: std::collections::HashSet<Pid> = self
@@ -107,6 +131,24 @@ pub struct SystemInfo {
     pub load_average: sysinfo::LoadAvg,
 }
 
+#[derive(Debug, Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DiskUsage {
+    pub mount_point: String,
+    pub total_bytes: u64,
+    pub available_bytes: u64,
+}
+
+impl DiskUsage {
+    pub fn free_percentage(&self) -> f64 {
+        if self.total_bytes > 0 {
+            (self.available_bytes as f64 / self.total_bytes as f64) * 100.0
+        } else {
+            0.0
+        }
+    }
+}
+
 impl SystemInfo {
     pub fn memory_percentage(&self) -> f64 {
         if self.total_memory > 0 {
@@ -144,6 +186,68 @@ impl SystemInfo {
     }
 }
 
+/// Recent CPU and memory samples of one process, oldest first
+#[derive(Debug, Clone, Default)]
+pub struct ResourceHistory {
+    cpu: VecDeque<f64>,
+    memory: VecDeque<u64>,
+}
+
+impl ResourceHistory {
+    /// Samples kept per process, one per scan (about a minute at the default interval)
+    pub const CAPACITY: usize = 30;
+
+    pub fn record(&mut self, cpu_usage: Option<f64>, memory_usage: Option<u64>) {
+        if let Some(cpu) = cpu_usage {
+            Self::push(&mut self.cpu, cpu);
+        }
+        if let Some(memory) = memory_usage {
+            Self::push(&mut self.memory, memory);
+        }
+    }
+
+    fn push<T>(samples: &mut VecDeque<T>, value: T) {
+        if samples.len() == Self::CAPACITY {
+            samples.pop_front();
+        }
+        samples.push_back(value);
+    }
+
+    /// CPU graph on a fixed 0-100% scale, so a busy process looks busy at a glance
+    pub fn cpu_sparkline(&self) -> Option<String> {
+        (self.cpu.len() > 1).then(|| sparkline(self.cpu.iter().copied(), 100.0))
+    }
+
+    /// Memory graph scaled between the lowest and highest sample, so steady growth
+    /// shows as a ramp even when it is small compared to the total
+    pub fn memory_sparkline(&self) -> Option<String> {
+        let low = *self.memory.iter().min()?;
+        let high = *self.memory.iter().max()?;
+        (self.memory.len() > 1).then(|| {
+            sparkline(
+                self.memory.iter().map(|&m| (m - low) as f64),
+                (high - low) as f64,
+            )
+        })
+    }
+}
+
+/// Render values between 0 and `max` as block characters, one per value
+pub fn sparkline(values: impl IntoIterator<Item = f64>, max: f64) -> String {
+    const BARS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+    values
+        .into_iter()
+        .map(|value| {
+            let level = if max > 0.0 {
+                (value / max * (BARS.len() - 1) as f64).round()
+            } else {
+                0.0
+            };
+            BARS[(level.max(0.0) as usize).min(BARS.len() - 1)]
+        })
+        .collect()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -167,4 +271,23 @@ mod tests {
         assert_eq!(system_info.format_memory(1048576), "1.0 MB");
         assert_eq!(system_info.format_memory(1073741824), "1.0 GB");
     }
+
+    #[test]
+    fn test_resource_history_sparklines() {
+        assert_eq!(sparkline([0.0, 50.0, 100.0, 150.0], 100.0), "▁▅██");
+        assert_eq!(sparkline([5.0, 5.0], 0.0), "▁▁");
+
+        let mut history = ResourceHistory::default();
+        history.record(Some(10.0), Some(100));
+        assert_eq!(history.cpu_sparkline(), None);
+
+        for i in 0..ResourceHistory::CAPACITY as u64 {
+            history.record(Some(100.0), Some(200 + i));
+        }
+        let cpu = history.cpu_sparkline().unwrap();
+        assert_eq!(cpu.chars().count(), ResourceHistory::CAPACITY);
+        assert!(cpu.chars().all(|c| c == '█'));
+        let memory = history.memory_sparkline().unwrap();
+        assert!(memory.starts_with('▁') && memory.ends_with('█'));
+    }
 }
@@ -0,0 +1,92 @@
+use anyhow::{Context, Result};
+use std::path::Path;
+use std::process::Command;
+
+/// Session `--start --tmux` and `--up --tmux` use without `--tmux-session`: the name of
+/// the current directory, so each project gets its own session
+pub fn default_session_name() -> String {
+    std::env::current_dir()
+        .ok()
+        .and_then(|dir| dir.file_name().map(|name| session_name(&name.to_string_lossy())))
+        .filter(|name| !name.is_empty())
+        .unwrap_or_else(|| "port-kill".to_string())
+}
+
+/// tmux does not allow `.` and `:` in session names
+fn session_name(name: &str) -> String {
+    name.replace(['.', ':'], "_")
+}
+
+fn has_session(session: &str) -> bool {
+    Command::new("tmux")
+        // "=" matches the name exactly instead of as a prefix
+        .args(["has-session", "-t", &format!("={}", session)])
+        .output()
+        .map(|output| output.status.success())
+        .unwrap_or(false)
+}
+
+/// Run `command_line` in a new window named `window` of `session`, creating the session
+/// when it does not exist yet. The window closes when the command exits. Returns the
+/// PID of the process in the window.
+pub fn open_window(
+    session: &str,
+    window: &str,
+    dir: &Path,
+    env: &[(String, String)],
+    command_line: &str,
+) -> Result<u32> {
+    let session = session_name(session);
+    let mut cmd = Command::new("tmux");
+    if has_session(&session) {
+        cmd.args(["new-window", "-d", "-t", &format!("={}:", session)]);
+    } else {
+        cmd.args(["new-session", "-d", "-s", &session]);
+    }
+    cmd.args(["-P", "-F", "#{pane_pid}", "-n", window])
+        .arg("-c")
+        .arg(dir);
+    for (key, value) in env {
+        cmd.args(["-e", &format!("{}={}", key, value)]);
+    }
+    cmd.arg(command_line);
+
+    let output = match cmd.output() {
+        Ok(output) => output,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+            return Err(anyhow::anyhow!("tmux is not installed"))
+        }
+        Err(e) => return Err(e).context("Failed to run tmux"),
+    };
+    if !output.status.success() {
+        return Err(anyhow::anyhow!(
+            "tmux could not open a window in session {}: {}",
+            session,
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    let pid = String::from_utf8_lossy(&output.stdout)
+        .trim()
+        .parse::<u32>()
+        .context("Unexpected output from tmux")?;
+
+    log::info!(
+        "Started '{}' with PID {} in tmux session {}",
+        window,
+        pid,
+        session
+    );
+    Ok(pid)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_session_name() {
+        assert_eq!(session_name("my.app"), "my_app");
+        assert_eq!(session_name("web:dev"), "web_dev");
+        assert_eq!(session_name("shop"), "shop");
+    }
+}
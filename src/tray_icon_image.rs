@@ -0,0 +1,201 @@
+use crate::tray_status::{IconLevel, IconStyle};
+
+/// Width and height of the rendered tray icon
+pub const ICON_SIZE: u32 = 32;
+
+/// Center and radius of the badge circle in the lower-right corner
+const BADGE_CENTER: f32 = 23.5;
+const BADGE_RADIUS: f32 = 8.0;
+
+/// 3×5 glyphs for the badge text, one row per entry, most significant bit on the left
+const DIGITS: [[u8; 5]; 10] = [
+    [0b111, 0b101, 0b101, 0b101, 0b111],
+    [0b010, 0b110, 0b010, 0b010, 0b111],
+    [0b111, 0b001, 0b111, 0b100, 0b111],
+    [0b111, 0b001, 0b111, 0b001, 0b111],
+    [0b101, 0b101, 0b111, 0b001, 0b001],
+    [0b111, 0b100, 0b111, 0b001, 0b111],
+    [0b111, 0b100, 0b111, 0b101, 0b111],
+    [0b111, 0b001, 0b010, 0b010, 0b010],
+    [0b111, 0b101, 0b111, 0b101, 0b111],
+    [0b111, 0b101, 0b111, 0b001, 0b111],
+];
+const PLUS: [u8; 5] = [0b000, 0b010, 0b111, 0b010, 0b000];
+
+/// A rendered tray icon
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TrayIconImage {
+    /// `ICON_SIZE`×`ICON_SIZE` RGBA pixels, row by row
+    pub rgba: Vec<u8>,
+    /// Black-on-transparent image that macOS recolors to match the menu bar
+    pub template: bool,
+}
+
+/// Text drawn on the badge; counts above 99 are shown as "99+"
+pub fn badge_text(count: usize) -> String {
+    if count > 99 {
+        "99+".to_string()
+    } else {
+        count.to_string()
+    }
+}
+
+/// Render the icon for the given level, with `badge` drawn in the lower-right
+/// corner. The adaptive style is a template image until the level turns red,
+/// since a template can't carry color.
+pub fn render(level: IconLevel, style: IconStyle, badge: Option<usize>) -> TrayIconImage {
+    let template = style == IconStyle::Adaptive && level != IconLevel::Alert;
+    let (level_r, level_g, level_b) = level.rgb();
+    let glyph = if template {
+        [0, 0, 0, 255]
+    } else {
+        [level_r, level_g, level_b, 255]
+    };
+    let badge_fill = if template {
+        [0, 0, 0, 255]
+    } else {
+        [220, 0, 0, 255]
+    };
+    let text = badge.map(badge_text);
+    let text_mask = text.as_deref().map(text_mask);
+
+    let mut rgba = Vec::with_capacity((ICON_SIZE * ICON_SIZE * 4) as usize);
+    for y in 0..ICON_SIZE {
+        for x in 0..ICON_SIZE {
+            let pixel = match text_mask {
+                Some(ref mask) => {
+                    let distance = distance(x, y, BADGE_CENTER);
+                    if distance <= BADGE_RADIUS {
+                        if mask.contains(&(x, y)) {
+                            // Knocked out in a template so the menu bar shows through
+                            if template {
+                                [0, 0, 0, 0]
+                            } else {
+                                [255, 255, 255, 255]
+                            }
+                        } else {
+                            badge_fill
+                        }
+                    } else if distance <= BADGE_RADIUS + 1.5 {
+                        // Gap between the badge and the plug
+                        [0, 0, 0, 0]
+                    } else {
+                        plug_pixel(x, y, glyph)
+                    }
+                }
+                None => plug_pixel(x, y, glyph),
+            };
+            rgba.extend_from_slice(&pixel);
+        }
+    }
+
+    TrayIconImage { rgba, template }
+}
+
+/// Ring with a dot in the middle, the shape of a socket
+fn plug_pixel(x: u32, y: u32, glyph: [u8; 4]) -> [u8; 4] {
+    let distance = distance(x, y, ICON_SIZE as f32 / 2.0);
+    if (9.0..=14.0).contains(&distance) || distance <= 4.5 {
+        glyph
+    } else {
+        [0, 0, 0, 0]
+    }
+}
+
+fn distance(x: u32, y: u32, center: f32) -> f32 {
+    let dx = x as f32 + 0.5 - center;
+    let dy = y as f32 + 0.5 - center;
+    (dx * dx + dy * dy).sqrt()
+}
+
+/// Pixels covered by `text`, centered on the badge. A single digit is drawn at
+/// double size.
+fn text_mask(text: &str) -> Vec<(u32, u32)> {
+    let glyphs: Vec<&[u8; 5]> = text
+        .chars()
+        .map(|c| match c.to_digit(10) {
+            Some(digit) => &DIGITS[digit as usize],
+            None => &PLUS,
+        })
+        .collect();
+    let scale = if glyphs.len() == 1 { 2 } else { 1 };
+    let width = glyphs.len() as u32 * 3 * scale + (glyphs.len() as u32 - 1);
+    let height = 5 * scale;
+    let left = (BADGE_CENTER - width as f32 / 2.0).round() as u32;
+    let top = (BADGE_CENTER - height as f32 / 2.0).round() as u32;
+
+    let mut mask = Vec::new();
+    for (index, glyph) in glyphs.iter().enumerate() {
+        let glyph_left = left + index as u32 * (3 * scale + 1);
+        for (row, bits) in glyph.iter().enumerate() {
+            for column in 0..3 {
+                if bits & (0b100 >> column) == 0 {
+                    continue;
+                }
+                for dy in 0..scale {
+                    for dx in 0..scale {
+                        mask.push((
+                            glyph_left + column * scale + dx,
+                            top + row as u32 * scale + dy,
+                        ));
+                    }
+                }
+            }
+        }
+    }
+    mask
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pixel(image: &TrayIconImage, x: u32, y: u32) -> [u8; 4] {
+        let offset = ((y * ICON_SIZE + x) * 4) as usize;
+        image.rgba[offset..offset + 4].try_into().unwrap()
+    }
+
+    #[test]
+    fn test_render() {
+        let idle = render(IconLevel::Idle, IconStyle::Adaptive, None);
+        assert!(idle.template);
+        assert_eq!(idle.rgba.len(), (ICON_SIZE * ICON_SIZE * 4) as usize);
+        assert_eq!(pixel(&idle, 16, 16), [0, 0, 0, 255]);
+        assert_eq!(pixel(&idle, 0, 0), [0, 0, 0, 0]);
+
+        // Red never goes through the template, so alerts keep their color
+        let alert = render(IconLevel::Alert, IconStyle::Adaptive, None);
+        assert!(!alert.template);
+        assert_eq!(pixel(&alert, 16, 16), [255, 0, 0, 255]);
+
+        let busy = render(IconLevel::Busy, IconStyle::Color, Some(7));
+        assert!(!busy.template);
+        assert_eq!(pixel(&busy, 16, 16), [255, 165, 0, 255]);
+        // The badge covers the lower-right corner and the digit is white on red
+        assert_eq!(pixel(&busy, 22, 30), [220, 0, 0, 255]);
+        let digit_pixels = (0..ICON_SIZE)
+            .flat_map(|y| (0..ICON_SIZE).map(move |x| (x, y)))
+            .filter(|&(x, y)| pixel(&busy, x, y) == [255, 255, 255, 255])
+            .count();
+        // A 7 is 7 of the 15 glyph cells, drawn at double size
+        assert_eq!(digit_pixels, 7 * 4);
+
+        // In a template the digits are cut out of the badge
+        let counted = render(IconLevel::Idle, IconStyle::Adaptive, Some(42));
+        assert_ne!(counted, idle);
+        assert_eq!(pixel(&counted, 22, 30), [0, 0, 0, 255]);
+    }
+
+    #[test]
+    fn test_badge_text() {
+        assert_eq!(badge_text(3), "3");
+        assert_eq!(badge_text(99), "99");
+        assert_eq!(badge_text(250), "99+");
+        // Every size of text fits on the badge
+        for text in ["8", "88", "99+"] {
+            for (x, y) in text_mask(text) {
+                assert!(distance(x, y, BADGE_CENTER) <= BADGE_RADIUS);
+            }
+        }
+    }
+}
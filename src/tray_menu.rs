@@ -1,20 +1,57 @@
+use crate::docker_containers::{ContainerAction, DockerContainer};
+use crate::quick_actions::QuickAction;
+use crate::tray_icon_image::{self, TrayIconImage, ICON_SIZE};
+use crate::tray_status::{
+    HealthSummary, MenuSection, PinnedStatus, TrayAlerts, TrayIconRules, TrayMenuLayout,
+};
 use crate::types::{ProcessInfo, StatusBarInfo};
 use anyhow::Result;
 use crossbeam_channel::Sender;
 use log::debug;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 #[cfg(target_os = "macos")]
 use tray_icon::{
-    menu::{Menu, MenuEvent, MenuId, MenuItem, PredefinedMenuItem},
+    menu::{CheckMenuItem, Menu, MenuEvent, MenuId, MenuItem, PredefinedMenuItem, Submenu},
     Icon,
 };
 
+/// Menu id prefix of the per-project "Kill All in <project>" items
+pub const KILL_PROJECT_PREFIX: &str = "kill_project:";
+
+/// Menu id prefixes of "Watch :<port>", "Unwatch" and the guard toggle of pinned ports
+pub const PIN_PREFIX: &str = "pin:";
+pub const UNPIN_PREFIX: &str = "unpin:";
+pub const PIN_GUARD_PREFIX: &str = "pin_guard:";
+
+/// Menu id of the "Start at login" toggle
+pub const LOGIN_ITEM_ID: &str = "login_item";
+
+/// Menu id of the system health header, whose text is refreshed in place
+pub const HEALTH_MENU_ID: &str = "health";
+
+/// Restart actions listed under the running processes
+#[derive(Clone, Debug, Default)]
+pub struct RestartMenuItems {
+    /// Recently killed ports that have a saved command, with a label for each
+    pub restartable: Vec<(u16, String)>,
+    /// Ports killed by the last tray action, offered as a single undo
+    pub undo_ports: Vec<u16>,
+}
+
+impl RestartMenuItems {
+    pub fn is_empty(&self) -> bool {
+        self.restartable.is_empty() && self.undo_ports.is_empty()
+    }
+}
+
 #[cfg(target_os = "macos")]
 #[derive(Clone)]
 pub struct TrayMenu {
     pub icon: Icon,
     current_processes: HashMap<u16, ProcessInfo>,
     show_pid: bool,
+    quick_actions: Vec<QuickAction>,
+    layout: TrayMenuLayout,
 }
 
 #[cfg(target_os = "macos")]
@@ -33,6 +70,8 @@ impl TrayMenu {
             icon,
             current_processes: HashMap::new(),
             show_pid: false,
+            quick_actions: Vec::new(),
+            layout: TrayMenuLayout::default(),
         })
     }
 
@@ -50,8 +89,26 @@ impl TrayMenu {
         Ok(())
     }
 
+    pub fn set_quick_actions(&mut self, quick_actions: Vec<QuickAction>) {
+        self.quick_actions = quick_actions;
+    }
+
+    pub fn set_layout(&mut self, layout: TrayMenuLayout) {
+        self.layout = layout;
+    }
+
     pub fn get_current_menu(&self) -> Result<Menu> {
-        let (menu, _) = Self::create_menu(&self.current_processes, self.show_pid)?;
+        let (menu, _) = Self::create_menu_with_verbose(
+            &self.current_processes,
+            self.show_pid,
+            false,
+            &RestartMenuItems::default(),
+            &self.quick_actions,
+            None,
+            &self.layout,
+            &[],
+            &[],
+        )?;
         Ok(menu)
     }
 
@@ -68,86 +125,405 @@ impl TrayMenu {
         processes: &HashMap<u16, ProcessInfo>,
         show_pid: bool,
     ) -> Result<(Menu, HashMap<String, u16>)> {
-        Self::create_menu_with_verbose(processes, show_pid, false)
+        Self::create_menu_with_verbose(
+            processes,
+            show_pid,
+            false,
+            &RestartMenuItems::default(),
+            &[],
+            None,
+            &TrayMenuLayout::default(),
+            &[],
+            &[],
+        )
     }
 
+    #[allow(clippy::too_many_arguments)]
     pub fn create_menu_with_verbose(
         processes: &HashMap<u16, ProcessInfo>,
         show_pid: bool,
         verbose: bool,
+        restart_items: &RestartMenuItems,
+        quick_actions: &[QuickAction],
+        health: Option<&HealthSummary>,
+        layout: &TrayMenuLayout,
+        containers: &[DockerContainer],
+        pinned: &[PinnedStatus],
     ) -> Result<(Menu, HashMap<String, u16>)> {
         let menu = Menu::new();
         let mut menu_id_to_port = HashMap::new();
+        let show_pid = layout.show_pid.unwrap_or(show_pid);
+
+        // Ports published by the listed containers are handled through their submenus,
+        // so their docker-proxy processes are not offered for killing as well
+        let processes: HashMap<u16, ProcessInfo> = processes
+            .iter()
+            .filter(|(port, _)| !containers.iter().any(|c| c.ports.contains(port)))
+            .map(|(port, process_info)| (*port, process_info.clone()))
+            .collect();
+        let processes = &processes;
+
+        // Sections in the order the [tray] config section lists them
+        for &section in &layout.sections {
+            match section {
+                MenuSection::System => {
+                    if let Some(health) = health {
+                        Self::append_health(&menu, health)?;
+                    }
+                }
+                MenuSection::Pinned => {
+                    for status in pinned {
+                        menu.append(&Self::pinned_submenu(
+                            &mut menu_id_to_port,
+                            status,
+                            show_pid,
+                        )?)?;
+                    }
+                    let mut watchable: Vec<(&u16, &ProcessInfo)> = processes
+                        .iter()
+                        .filter(|(port, _)| !pinned.iter().any(|status| status.pin.port == **port))
+                        .collect();
+                    watchable.sort_by_key(|(port, _)| **port);
+                    if !watchable.is_empty() {
+                        let watch = Submenu::new("Watch a port", true);
+                        for (port, process_info) in watchable {
+                            watch.append(&MenuItem::with_id(
+                                MenuId(format!("{}{}", PIN_PREFIX, port)),
+                                format!("Watch :{} ({})", port, process_info.name),
+                                true,
+                                None,
+                            ))?;
+                        }
+                        menu.append(&watch)?;
+                    }
+                    if !pinned.is_empty() || !processes.is_empty() {
+                        menu.append(&PredefinedMenuItem::separator())?;
+                    }
+                }
+                MenuSection::Processes => {
+                    // Add "Kill All Processes" item
+                    let kill_all_item = MenuItem::with_id(
+                        MenuId("kill_all".into()),
+                        "Kill All Processes",
+                        true,
+                        None,
+                    );
+                    menu.append(&kill_all_item)?;
+
+                    // Add separator
+                    let separator = PredefinedMenuItem::separator();
+                    menu.append(&separator)?;
+
+                    let listed = layout.section_processes(section, processes);
+                    Self::append_processes(
+                        &menu,
+                        &mut menu_id_to_port,
+                        &listed.shown,
+                        show_pid,
+                        verbose,
+                    )?;
+                    Self::append_hidden_count(&menu, listed.hidden)?;
+
+                    // Add another separator if there are processes
+                    if !listed.shown.is_empty() {
+                        let separator = PredefinedMenuItem::separator();
+                        menu.append(&separator)?;
+                    }
+                }
+                MenuSection::Docker if !containers.is_empty() => {
+                    for container in containers {
+                        menu.append(&Self::container_submenu(container)?)?;
+                    }
+                    menu.append(&PredefinedMenuItem::separator())?;
+                }
+                MenuSection::Docker => {
+                    let listed = layout.section_processes(section, processes);
+                    if listed.shown.is_empty() {
+                        continue;
+                    }
+                    menu.append(&MenuItem::new("Docker", false, None))?;
+                    for (port, process_info) in &listed.shown {
+                        let process_item = MenuItem::new(
+                            Self::process_menu_text(*port, process_info, show_pid, verbose),
+                            true,
+                            None,
+                        );
+                        menu.append(&process_item)?;
+                        menu_id_to_port.insert(process_item.id().0.clone(), *port);
+                    }
+                    Self::append_hidden_count(&menu, listed.hidden)?;
+                    menu.append(&PredefinedMenuItem::separator())?;
+                }
+                MenuSection::History => Self::append_restart_items(&menu, restart_items)?,
+                MenuSection::Actions => {
+                    // One-click shortcuts for common commands, from the [tray] config section
+                    for action in quick_actions {
+                        let action_item =
+                            MenuItem::with_id(MenuId(action.menu_id()), action.label(), true, None);
+                        menu.append(&action_item)?;
+                    }
+                    if !quick_actions.is_empty() {
+                        let separator = PredefinedMenuItem::separator();
+                        menu.append(&separator)?;
+                    }
+                }
+            }
+        }
 
-        // Add "Kill All Processes" item
-        let kill_all_item = MenuItem::with_id(MenuId("kill_all".into()), "Kill All Processes", true, None);
-        menu.append(&kill_all_item)?;
+        let login_item = CheckMenuItem::with_id(
+            MenuId(LOGIN_ITEM_ID.into()),
+            "Start at login",
+            true,
+            crate::login_item::is_installed(),
+            None,
+        );
+        menu.append(&login_item)?;
 
-        // Add separator
-        let separator = PredefinedMenuItem::separator();
-        menu.append(&separator)?;
+        // Add "Quit" item
+        let quit_item = MenuItem::with_id(MenuId("quit".into()), "Quit", true, None);
+        menu.append(&quit_item)?;
 
-        // Add individual process items
-        for (port, process_info) in processes {
-            let menu_text = if verbose {
-                // Verbose mode: show command line and working directory
-                let mut parts = vec![format!("Kill: Port {}: {}", port, process_info.name)];
+        Ok((menu, menu_id_to_port))
+    }
 
-                if let Some(ref cmd_line) = process_info.command_line {
-                    parts.push(format!("({})", cmd_line));
-                }
+    /// A pinned port with kill, guard and unwatch actions; shown even while the port is free
+    fn pinned_submenu(
+        menu_id_to_port: &mut HashMap<String, u16>,
+        status: &PinnedStatus,
+        show_pid: bool,
+    ) -> Result<Submenu> {
+        let port = status.pin.port;
+        let submenu = Submenu::new(status.label(show_pid), true);
 
-                if show_pid {
-                    parts.push(format!("(PID {})", process_info.pid));
-                }
+        let kill_item = MenuItem::new("Kill", status.holder.is_some(), None);
+        if status.holder.is_some() {
+            menu_id_to_port.insert(kill_item.id().0.clone(), port);
+        }
+        submenu.append(&kill_item)?;
+
+        // Reserving needs a process name, so a port nothing has held yet can't be guarded
+        let guard_label = match &status.guard_for {
+            Some(name) => format!("Guard for {}", name),
+            None => "Guard (waiting for a process)".to_string(),
+        };
+        submenu.append(&CheckMenuItem::with_id(
+            MenuId(format!("{}{}", PIN_GUARD_PREFIX, port)),
+            guard_label,
+            status.guarded || status.guard_for.is_some(),
+            status.guarded,
+            None,
+        ))?;
+        submenu.append(&MenuItem::new(status.pin.notify.description(), false, None))?;
+        submenu.append(&PredefinedMenuItem::separator())?;
+        submenu.append(&MenuItem::with_id(
+            MenuId(format!("{}{}", UNPIN_PREFIX, port)),
+            "Unwatch",
+            true,
+            None,
+        ))?;
+        Ok(submenu)
+    }
 
-                if let Some(ref work_dir) = process_info.working_directory {
-                    parts.push(format!("- {}", Self::short_display_path(work_dir)));
-                }
+    /// Load, memory and monitored ports at a glance, with the busiest processes below
+    fn append_health(menu: &Menu, health: &HealthSummary) -> Result<()> {
+        let health_item =
+            MenuItem::with_id(MenuId(HEALTH_MENU_ID.into()), health.header(), false, None);
+        menu.append(&health_item)?;
+        if !health.processes.is_empty() {
+            let offenders = Submenu::new("Top Offenders", true);
+            offenders.append(&MenuItem::new("By CPU", false, None))?;
+            for line in health.top_cpu() {
+                offenders.append(&MenuItem::new(line, false, None))?;
+            }
+            offenders.append(&PredefinedMenuItem::separator())?;
+            offenders.append(&MenuItem::new("By Memory", false, None))?;
+            for line in health.top_memory() {
+                offenders.append(&MenuItem::new(line, false, None))?;
+            }
+            menu.append(&offenders)?;
+        }
+        menu.append(&PredefinedMenuItem::separator())?;
+        Ok(())
+    }
 
-                if let (Some(_container_id), Some(container_name)) =
-                    (&process_info.container_id, &process_info.container_name)
-                {
-                    parts.push(format!("[Docker: {}]", container_name));
-                }
+    fn append_processes(
+        menu: &Menu,
+        menu_id_to_port: &mut HashMap<String, u16>,
+        processes: &[(u16, &ProcessInfo)],
+        show_pid: bool,
+        verbose: bool,
+    ) -> Result<()> {
+        // Group processes by project so several running stacks stay readable, with
+        // anything that has no project listed on its own below
+        let mut by_project: BTreeMap<String, Vec<(u16, &ProcessInfo)>> = BTreeMap::new();
+        let mut ungrouped = Vec::new();
+        for &(port, process_info) in processes {
+            match process_info.get_workspace_name() {
+                Some(project) => by_project
+                    .entry(project)
+                    .or_default()
+                    .push((port, process_info)),
+                None => ungrouped.push((port, process_info)),
+            }
+        }
 
-                parts.join(" ")
-            } else if let (Some(_container_id), Some(container_name)) =
-                (&process_info.container_id, &process_info.container_name)
-            {
-                format!(
-                    "Kill: Port {}: {} [Docker: {}]",
-                    port, process_info.name, container_name
-                )
-            } else if show_pid {
-                format!(
-                    "Kill: Port {}: {} (PID {})",
-                    port, process_info.name, process_info.pid
-                )
-            } else {
-                format!("Kill: Port {}: {}", port, process_info.name)
-            };
+        for (project, project_processes) in &by_project {
+            let submenu = Submenu::new(format!("{} ({})", project, project_processes.len()), true);
+            let kill_project_item = MenuItem::with_id(
+                MenuId(format!("{}{}", KILL_PROJECT_PREFIX, project)),
+                format!("Kill All in {}", project),
+                true,
+                None,
+            );
+            submenu.append(&kill_project_item)?;
+            submenu.append(&PredefinedMenuItem::separator())?;
+            for (port, process_info) in project_processes {
+                let process_item = MenuItem::new(
+                    Self::process_menu_text(*port, process_info, show_pid, verbose),
+                    true,
+                    None,
+                );
+                submenu.append(&process_item)?;
+                menu_id_to_port.insert(process_item.id().0.clone(), *port);
+            }
+            menu.append(&submenu)?;
+        }
 
+        for (port, process_info) in ungrouped {
             // Create menu item for each process
-            let process_item = MenuItem::new(&menu_text, true, None);
+            let process_item = MenuItem::new(
+                Self::process_menu_text(port, process_info, show_pid, verbose),
+                true,
+                None,
+            );
             let process_id = process_item.id();
             menu.append(&process_item)?;
 
             // Store the mapping from menu ID to port
-            menu_id_to_port.insert(process_id.0.clone(), *port);
+            menu_id_to_port.insert(process_id.0.clone(), port);
+        }
+        Ok(())
+    }
+
+    /// Image, published ports and uptime of a container, with what can be done to it
+    fn container_submenu(container: &DockerContainer) -> Result<Submenu> {
+        let submenu = Submenu::new(
+            format!("{} ({})", container.name, container.ports_label()),
+            true,
+        );
+        submenu.append(&MenuItem::new(
+            format!("Image: {}", container.image),
+            false,
+            None,
+        ))?;
+        submenu.append(&MenuItem::new(
+            format!("Ports: {}", container.ports_label()),
+            false,
+            None,
+        ))?;
+        submenu.append(&MenuItem::new(&container.status, false, None))?;
+        submenu.append(&PredefinedMenuItem::separator())?;
+        for action in ContainerAction::ALL {
+            let action_item = MenuItem::with_id(
+                MenuId(action.menu_id(&container.id)),
+                action.label(),
+                true,
+                None,
+            );
+            submenu.append(&action_item)?;
+        }
+        Ok(submenu)
+    }
+
+    /// Note the processes left out by `max_processes`
+    fn append_hidden_count(menu: &Menu, hidden: usize) -> Result<()> {
+        if hidden > 0 {
+            menu.append(&MenuItem::new(
+                format!("… and {} more", hidden),
+                false,
+                None,
+            ))?;
         }
+        Ok(())
+    }
 
-        // Add another separator if there are processes
-        if !processes.is_empty() {
+    /// Offer to undo the last kill, then any other saved commands
+    fn append_restart_items(menu: &Menu, restart_items: &RestartMenuItems) -> Result<()> {
+        if !restart_items.undo_ports.is_empty() {
+            let ports = restart_items
+                .undo_ports
+                .iter()
+                .map(|p| format!(":{}", p))
+                .collect::<Vec<_>>()
+                .join(", ");
+            let undo_item = MenuItem::with_id(
+                MenuId("undo".into()),
+                format!("Undo (restart {})", ports),
+                true,
+                None,
+            );
+            menu.append(&undo_item)?;
+        }
+        for (port, name) in &restart_items.restartable {
+            let restart_item = MenuItem::with_id(
+                MenuId(format!("restart:{}", port)),
+                format!("Restart {} on :{}", name, port),
+                true,
+                None,
+            );
+            menu.append(&restart_item)?;
+        }
+        if !restart_items.is_empty() {
             let separator = PredefinedMenuItem::separator();
             menu.append(&separator)?;
         }
+        Ok(())
+    }
 
-        // Add "Quit" item
-        let quit_item = MenuItem::with_id(MenuId("quit".into()), "Quit", true, None);
-        menu.append(&quit_item)?;
+    fn process_menu_text(
+        port: u16,
+        process_info: &ProcessInfo,
+        show_pid: bool,
+        verbose: bool,
+    ) -> String {
+        if verbose {
+            // Verbose mode: show command line and working directory
+            let mut parts = vec![format!("Kill: Port {}: {}", port, process_info.name)];
 
-        Ok((menu, menu_id_to_port))
+            if let Some(ref cmd_line) = process_info.command_line {
+                parts.push(format!("({})", cmd_line));
+            }
+
+            if show_pid {
+                parts.push(format!("(PID {})", process_info.pid));
+            }
+
+            if let Some(ref work_dir) = process_info.working_directory {
+                parts.push(format!("- {}", Self::short_display_path(work_dir)));
+            }
+
+            if let (Some(_container_id), Some(container_name)) =
+                (&process_info.container_id, &process_info.container_name)
+            {
+                parts.push(format!("[Docker: {}]", container_name));
+            }
+
+            parts.join(" ")
+        } else if let (Some(_container_id), Some(container_name)) =
+            (&process_info.container_id, &process_info.container_name)
+        {
+            format!(
+                "Kill: Port {}: {} [Docker: {}]",
+                port, process_info.name, container_name
+            )
+        } else if show_pid {
+            format!(
+                "Kill: Port {}: {} (PID {})",
+                port, process_info.name, process_info.pid
+            )
+        } else {
+            format!("Kill: Port {}: {}", port, process_info.name)
+        }
     }
 
     fn short_display_path(dir: &str) -> String {
@@ -160,55 +536,16 @@ impl TrayMenu {
     }
 
     pub fn create_icon(text: &str) -> Result<Icon> {
-        // Create a simple but visible icon for the status bar
-        let icon_data = Self::generate_visible_icon(text);
-
-        // Try different sizes for better compatibility
-        match Icon::from_rgba(icon_data.clone(), 16, 16) {
-            Ok(icon) => Ok(icon),
-            Err(_) => {
-                // Fallback to 32x32
-                Icon::from_rgba(icon_data, 32, 32)
-                    .map_err(|e| anyhow::anyhow!("Failed to create icon: {}", e))
-            }
-        }
+        // Color by process count with the default thresholds
+        let number = text.chars().filter(|c| c.is_numeric()).collect::<String>();
+        let count = number.parse::<usize>().unwrap_or(0);
+        let rules = TrayIconRules::default();
+        let level = rules.level(count, &TrayAlerts::default());
+        Self::create_level_icon(&tray_icon_image::render(level, rules.style, None))
     }
 
-    fn generate_visible_icon(text: &str) -> Vec<u8> {
-        // Create a much larger, highly visible 32x32 RGBA icon for the status bar
-        let mut icon_data = Vec::new();
-
-        for y in 0..32 {
-            for x in 0..32 {
-                // Create a very simple, highly visible icon
-                let _is_edge = x < 2 || x > 29 || y < 2 || y > 29;
-                let _is_center = x >= 14 && x <= 17 && y >= 14 && y <= 17;
-
-                // Create a number display area in the center
-                let is_number_area = x >= 12 && x <= 19 && y >= 12 && y <= 19;
-
-                let (r, g, b, a) = if is_number_area {
-                    // Parse the number from text (remove any non-numeric characters)
-                    let number = text.chars().filter(|c| c.is_numeric()).collect::<String>();
-                    let num = number.parse::<u32>().unwrap_or(0);
-
-                    if num == 0 {
-                        (0, 255, 0, 255) // Bright green when no processes
-                    } else if num <= 9 {
-                        // For 1-9 processes, use orange
-                        (255, 165, 0, 255) // Orange for 1-9 processes
-                    } else {
-                        // For 10+ processes, use red to indicate many processes
-                        (255, 0, 0, 255) // Red for 10+ processes
-                    }
-                } else {
-                    (255, 255, 255, 255) // Clean white background
-                };
-
-                icon_data.extend_from_slice(&[r, g, b, a]);
-            }
-        }
-
-        icon_data
+    pub fn create_level_icon(image: &TrayIconImage) -> Result<Icon> {
+        Icon::from_rgba(image.rgba.clone(), ICON_SIZE, ICON_SIZE)
+            .map_err(|e| anyhow::anyhow!("Failed to create icon: {}", e))
     }
 }
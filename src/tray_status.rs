@@ -0,0 +1,816 @@
+use crate::cli::Args;
+use crate::port_guard::read_reservation_file;
+use crate::system_monitor::{SystemInfo, SystemMonitor};
+use crate::types::ProcessInfo;
+use anyhow::{anyhow, Result};
+use std::collections::HashMap;
+use std::fmt;
+use std::path::Path;
+use std::str::FromStr;
+
+/// Color of the tray icon's center
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IconLevel {
+    /// Green: nothing worth attention
+    Idle,
+    /// Orange: some processes are running
+    Busy,
+    /// Red: too many processes, or something the user asked to be alerted about
+    Alert,
+}
+
+impl IconLevel {
+    pub fn rgb(self) -> (u8, u8, u8) {
+        match self {
+            IconLevel::Idle => (0, 255, 0),
+            IconLevel::Busy => (255, 165, 0),
+            IconLevel::Alert => (255, 0, 0),
+        }
+    }
+}
+
+/// Conditions that turn the icon red regardless of the process count
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AlertTrigger {
+    /// A reserved port is held by a process other than the one it is reserved for
+    Guarded,
+    /// A process listens on one of the suspicious ports (`--suspicious-ports`)
+    Suspicious,
+}
+
+impl FromStr for AlertTrigger {
+    type Err = anyhow::Error;
+
+    fn from_str(value: &str) -> Result<Self> {
+        match value.trim() {
+            "guarded" => Ok(AlertTrigger::Guarded),
+            "suspicious" => Ok(AlertTrigger::Suspicious),
+            other => Err(anyhow!(
+                "Unknown tray alert '{}' (expected guarded or suspicious)",
+                other
+            )),
+        }
+    }
+}
+
+/// What the badge drawn on the tray icon counts
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum BadgeMode {
+    #[default]
+    None,
+    /// Number of running processes
+    Count,
+    /// Number of alerts, hidden while there are none
+    Alerts,
+}
+
+impl FromStr for BadgeMode {
+    type Err = anyhow::Error;
+
+    fn from_str(value: &str) -> Result<Self> {
+        match value.trim() {
+            "none" => Ok(BadgeMode::None),
+            "count" => Ok(BadgeMode::Count),
+            "alerts" => Ok(BadgeMode::Alerts),
+            other => Err(anyhow!(
+                "Unknown tray badge '{}' (expected none, count or alerts)",
+                other
+            )),
+        }
+    }
+}
+
+/// How the tray icon is drawn
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum IconStyle {
+    /// Monochrome template that follows the light or dark menu bar, red on alerts
+    #[default]
+    Adaptive,
+    /// Always colored green, orange or red
+    Color,
+}
+
+impl FromStr for IconStyle {
+    type Err = anyhow::Error;
+
+    fn from_str(value: &str) -> Result<Self> {
+        match value.trim() {
+            "adaptive" => Ok(IconStyle::Adaptive),
+            "color" => Ok(IconStyle::Color),
+            other => Err(anyhow!(
+                "Unknown tray icon style '{}' (expected adaptive or color)",
+                other
+            )),
+        }
+    }
+}
+
+/// When the tray icon changes color and what its badge shows, from the `[tray]`
+/// config section
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TrayIconRules {
+    /// Processes needed for orange; 0 never turns orange because of the count
+    pub warn_at: usize,
+    /// Processes needed for red; 0 never turns red because of the count
+    pub alert_at: usize,
+    pub alert_on: Vec<AlertTrigger>,
+    pub badge: BadgeMode,
+    pub style: IconStyle,
+}
+
+impl Default for TrayIconRules {
+    fn default() -> Self {
+        Self {
+            warn_at: 1,
+            alert_at: 10,
+            alert_on: Vec::new(),
+            badge: BadgeMode::None,
+            style: IconStyle::Adaptive,
+        }
+    }
+}
+
+/// A part of the tray menu that `[tray] sections` can show, hide or reorder
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MenuSection {
+    /// Load, memory and the top offenders
+    System,
+    /// Pinned ports, free or not, and "Watch a port"
+    Pinned,
+    /// "Kill All" and the local processes, grouped by project
+    Processes,
+    /// Processes running in Docker containers
+    Docker,
+    /// Undo and restart items for recently killed processes
+    History,
+    /// Quick actions
+    Actions,
+}
+
+impl FromStr for MenuSection {
+    type Err = anyhow::Error;
+
+    fn from_str(value: &str) -> Result<Self> {
+        match value.trim() {
+            "system" => Ok(MenuSection::System),
+            "pinned" => Ok(MenuSection::Pinned),
+            "processes" => Ok(MenuSection::Processes),
+            "docker" => Ok(MenuSection::Docker),
+            "history" => Ok(MenuSection::History),
+            "actions" => Ok(MenuSection::Actions),
+            other => Err(anyhow!(
+                "Unknown tray menu section '{}' (expected system, pinned, processes, docker, history or actions)",
+                other
+            )),
+        }
+    }
+}
+
+impl fmt::Display for MenuSection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            MenuSection::System => "system",
+            MenuSection::Pinned => "pinned",
+            MenuSection::Processes => "processes",
+            MenuSection::Docker => "docker",
+            MenuSection::History => "history",
+            MenuSection::Actions => "actions",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// Which sections the tray menu shows and in what order, from the `[tray]` config section
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TrayMenuLayout {
+    pub sections: Vec<MenuSection>,
+    /// Process items listed per section before the rest is summarized; 0 lists them all
+    pub max_processes: usize,
+    /// Overrides `show_pid` for the menu
+    pub show_pid: Option<bool>,
+}
+
+impl Default for TrayMenuLayout {
+    fn default() -> Self {
+        Self {
+            sections: vec![
+                MenuSection::System,
+                MenuSection::Pinned,
+                MenuSection::Processes,
+                MenuSection::Docker,
+                MenuSection::History,
+                MenuSection::Actions,
+            ],
+            max_processes: 0,
+            show_pid: None,
+        }
+    }
+}
+
+/// Processes to list in one menu section, sorted by port
+#[derive(Debug, Default)]
+pub struct SectionProcesses<'a> {
+    pub shown: Vec<(u16, &'a ProcessInfo)>,
+    /// Processes left out because of `max_processes`
+    pub hidden: usize,
+}
+
+impl TrayMenuLayout {
+    pub fn shows(&self, section: MenuSection) -> bool {
+        self.sections.contains(&section)
+    }
+
+    /// The processes for `section`: containers for Docker, everything else for Processes
+    pub fn section_processes<'a>(
+        &self,
+        section: MenuSection,
+        processes: &'a HashMap<u16, ProcessInfo>,
+    ) -> SectionProcesses<'a> {
+        let docker = match section {
+            MenuSection::Processes => false,
+            MenuSection::Docker => true,
+            _ => return SectionProcesses::default(),
+        };
+        let mut shown: Vec<(u16, &ProcessInfo)> = processes
+            .iter()
+            .filter(|(_, process)| process.container_id.is_some() == docker)
+            .map(|(&port, process)| (port, process))
+            .collect();
+        shown.sort_by_key(|(port, _)| *port);
+        let mut hidden = 0;
+        if self.max_processes > 0 && shown.len() > self.max_processes {
+            hidden = shown.len() - self.max_processes;
+            shown.truncate(self.max_processes);
+        }
+        SectionProcesses { shown, hidden }
+    }
+}
+
+/// When a pinned port sends a notification
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PinNotify {
+    Taken,
+    Freed,
+    Both,
+    None,
+}
+
+impl PinNotify {
+    fn on_taken(self) -> bool {
+        matches!(self, PinNotify::Taken | PinNotify::Both)
+    }
+
+    fn on_freed(self) -> bool {
+        matches!(self, PinNotify::Freed | PinNotify::Both)
+    }
+
+    /// Shown in the pinned port's submenu
+    pub fn description(self) -> &'static str {
+        match self {
+            PinNotify::Taken => "Notifies when taken",
+            PinNotify::Freed => "Notifies when freed",
+            PinNotify::Both => "Notifies when taken or freed",
+            PinNotify::None => "No notifications",
+        }
+    }
+}
+
+impl FromStr for PinNotify {
+    type Err = anyhow::Error;
+
+    fn from_str(value: &str) -> Result<Self> {
+        match value.trim() {
+            "taken" => Ok(PinNotify::Taken),
+            "freed" => Ok(PinNotify::Freed),
+            "both" => Ok(PinNotify::Both),
+            "none" => Ok(PinNotify::None),
+            other => Err(anyhow!(
+                "Unknown pinned port notification '{}' (expected taken, freed, both or none)",
+                other
+            )),
+        }
+    }
+}
+
+impl fmt::Display for PinNotify {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            PinNotify::Taken => "taken",
+            PinNotify::Freed => "freed",
+            PinNotify::Both => "both",
+            PinNotify::None => "none",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// A port pinned in the tray through `[tray.pins]`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PinnedPort {
+    pub port: u16,
+    pub notify: PinNotify,
+}
+
+/// A pinned port as the menu shows it
+#[derive(Debug, Clone, PartialEq)]
+pub struct PinnedStatus {
+    pub pin: PinnedPort,
+    pub holder: Option<ProcessInfo>,
+    /// The port has a guard reservation
+    pub guarded: bool,
+    /// Who the guard would reserve the port for: the holder, or whoever held it last
+    pub guard_for: Option<String>,
+}
+
+impl PinnedStatus {
+    /// The pinned ports with what holds them; `last_holder` names the previous
+    /// holder of a free port
+    pub fn collect(
+        pins: &[PinnedPort],
+        processes: &HashMap<u16, ProcessInfo>,
+        reserved: &[u16],
+        last_holder: impl Fn(u16) -> Option<String>,
+    ) -> Vec<Self> {
+        let mut statuses: Vec<Self> = pins
+            .iter()
+            .map(|pin| {
+                let holder = processes.get(&pin.port).cloned();
+                let guard_for = holder
+                    .as_ref()
+                    .map(|process| process.name.clone())
+                    .or_else(|| last_holder(pin.port));
+                Self {
+                    pin: *pin,
+                    holder,
+                    guarded: reserved.contains(&pin.port),
+                    guard_for,
+                }
+            })
+            .collect();
+        statuses.sort_by_key(|status| status.pin.port);
+        statuses
+    }
+
+    /// e.g. `:3000 — node (PID 42)` or `:3000 — free`
+    pub fn label(&self, show_pid: bool) -> String {
+        match &self.holder {
+            Some(process) if show_pid => {
+                format!(
+                    ":{} — {} (PID {})",
+                    self.pin.port, process.name, process.pid
+                )
+            }
+            Some(process) => format!(":{} — {}", self.pin.port, process.name),
+            None => format!(":{} — free", self.pin.port),
+        }
+    }
+}
+
+/// Notification texts for pinned ports that were taken or freed between two scans,
+/// following each pin's `notify` rule
+pub fn pin_notifications(
+    pins: &[PinnedPort],
+    before: &HashMap<u16, ProcessInfo>,
+    after: &HashMap<u16, ProcessInfo>,
+) -> Vec<String> {
+    let mut messages = Vec::new();
+    for pin in pins {
+        match (before.get(&pin.port), after.get(&pin.port)) {
+            (previous, Some(process))
+                if pin.notify.on_taken() && previous.is_none_or(|p| p.pid != process.pid) =>
+            {
+                messages.push(format!(
+                    ":{} taken by {} (PID {})",
+                    pin.port, process.name, process.pid
+                ));
+            }
+            (Some(previous), None) if pin.notify.on_freed() => {
+                messages.push(format!(":{} is free ({} exited)", pin.port, previous.name));
+            }
+            _ => {}
+        }
+    }
+    messages
+}
+
+/// Alert conditions found in the current processes
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TrayAlerts {
+    /// Reserved ports held by another process
+    pub guarded: Vec<u16>,
+    /// Suspicious ports with a listener
+    pub suspicious: Vec<u16>,
+}
+
+impl TrayAlerts {
+    pub fn detect(processes: &HashMap<u16, ProcessInfo>, args: &Args) -> Self {
+        let reservations = read_reservation_file(Path::new(&args.get_reservation_file_path()))
+            .map(|loaded| loaded.data)
+            .unwrap_or_default();
+        let mut guarded: Vec<u16> = reservations
+            .values()
+            .filter(|reservation| {
+                processes
+                    .get(&reservation.port)
+                    .is_some_and(|process| process.name != reservation.process_name)
+            })
+            .map(|reservation| reservation.port)
+            .collect();
+        guarded.sort_unstable();
+
+        let mut suspicious: Vec<u16> = args
+            .get_suspicious_ports()
+            .into_iter()
+            .filter(|port| processes.contains_key(port))
+            .collect();
+        suspicious.sort_unstable();
+
+        Self {
+            guarded,
+            suspicious,
+        }
+    }
+
+    /// Tooltip lines for the alerts, empty when there are none
+    pub fn describe(&self) -> Vec<String> {
+        let ports = |ports: &[u16]| {
+            ports
+                .iter()
+                .map(|port| format!(":{}", port))
+                .collect::<Vec<_>>()
+                .join(", ")
+        };
+        let mut lines = Vec::new();
+        if !self.guarded.is_empty() {
+            lines.push(format!("Reserved port taken: {}", ports(&self.guarded)));
+        }
+        if !self.suspicious.is_empty() {
+            lines.push(format!("Suspicious port open: {}", ports(&self.suspicious)));
+        }
+        lines
+    }
+
+    /// Alerts for `triggers` that were not in `previous`, as (port, description)
+    pub fn new_since(
+        &self,
+        previous: &TrayAlerts,
+        triggers: &[AlertTrigger],
+    ) -> Vec<(u16, &'static str)> {
+        let mut appeared = Vec::new();
+        for trigger in triggers {
+            let (current, before, description) = match trigger {
+                AlertTrigger::Guarded => (&self.guarded, &previous.guarded, "Reserved port taken"),
+                AlertTrigger::Suspicious => (
+                    &self.suspicious,
+                    &previous.suspicious,
+                    "Suspicious port open",
+                ),
+            };
+            appeared.extend(
+                current
+                    .iter()
+                    .filter(|port| !before.contains(port))
+                    .map(|&port| (port, description)),
+            );
+        }
+        appeared
+    }
+}
+
+/// Resource usage of one monitored process
+#[derive(Debug, Clone, PartialEq)]
+pub struct ProcessUsage {
+    pub port: u16,
+    pub name: String,
+    pub cpu_percent: f64,
+    pub memory_bytes: u64,
+}
+
+/// System health shown at the top of the tray menu
+#[derive(Debug, Clone)]
+pub struct HealthSummary {
+    pub system: SystemInfo,
+    pub monitored_ports: usize,
+    pub processes: Vec<ProcessUsage>,
+}
+
+impl HealthSummary {
+    /// Number of processes listed under each heading of the offenders submenu
+    pub const TOP_COUNT: usize = 3;
+
+    /// Read the system and per-process usage; `monitor` should have been refreshed
+    pub fn collect(
+        monitor: &SystemMonitor,
+        processes: &HashMap<u16, ProcessInfo>,
+        monitored_ports: usize,
+    ) -> Self {
+        let processes = processes
+            .iter()
+            .map(|(&port, process)| ProcessUsage {
+                port,
+                name: process.name.clone(),
+                cpu_percent: monitor.get_process_cpu_percent(process.pid).unwrap_or(0.0),
+                memory_bytes: monitor
+                    .get_process_memory_usage(process.pid)
+                    .map_or(0, |(bytes, _)| bytes),
+            })
+            .collect();
+        Self {
+            system: monitor.get_system_info(),
+            monitored_ports,
+            processes,
+        }
+    }
+
+    /// One line, e.g. `Load 1.42 · Memory 68% · 12 ports monitored`
+    pub fn header(&self) -> String {
+        format!(
+            "Load {:.2} · Memory {:.0}% · {} port{} monitored",
+            self.system.load_average.one,
+            self.system.memory_percentage(),
+            self.monitored_ports,
+            if self.monitored_ports == 1 { "" } else { "s" }
+        )
+    }
+
+    /// Busiest processes by CPU, as menu lines
+    pub fn top_cpu(&self) -> Vec<String> {
+        let mut by_cpu: Vec<&ProcessUsage> = self.processes.iter().collect();
+        by_cpu.sort_by(|a, b| b.cpu_percent.total_cmp(&a.cpu_percent));
+        by_cpu
+            .into_iter()
+            .take(Self::TOP_COUNT)
+            .map(|usage| {
+                format!(
+                    ":{} {} — {:.1}% CPU",
+                    usage.port, usage.name, usage.cpu_percent
+                )
+            })
+            .collect()
+    }
+
+    /// Largest processes by memory, as menu lines
+    pub fn top_memory(&self) -> Vec<String> {
+        let mut by_memory: Vec<&ProcessUsage> = self.processes.iter().collect();
+        by_memory.sort_by_key(|usage| std::cmp::Reverse(usage.memory_bytes));
+        by_memory
+            .into_iter()
+            .take(Self::TOP_COUNT)
+            .map(|usage| {
+                format!(
+                    ":{} {} — {}",
+                    usage.port,
+                    usage.name,
+                    self.system.format_memory(usage.memory_bytes)
+                )
+            })
+            .collect()
+    }
+}
+
+impl TrayIconRules {
+    /// Alerts that count under `alert_on`
+    fn active_alerts(&self, alerts: &TrayAlerts) -> usize {
+        self.alert_on
+            .iter()
+            .map(|trigger| match trigger {
+                AlertTrigger::Guarded => alerts.guarded.len(),
+                AlertTrigger::Suspicious => alerts.suspicious.len(),
+            })
+            .sum()
+    }
+
+    pub fn level(&self, process_count: usize, alerts: &TrayAlerts) -> IconLevel {
+        let reached = |threshold: usize| threshold > 0 && process_count >= threshold;
+        if self.active_alerts(alerts) > 0 || reached(self.alert_at) {
+            IconLevel::Alert
+        } else if reached(self.warn_at) {
+            IconLevel::Busy
+        } else {
+            IconLevel::Idle
+        }
+    }
+
+    /// Number for the icon's badge, hidden while it would be zero
+    pub fn badge(&self, process_count: usize, alerts: &TrayAlerts) -> Option<usize> {
+        let count = match self.badge {
+            BadgeMode::None => 0,
+            BadgeMode::Count => process_count,
+            BadgeMode::Alerts => self.active_alerts(alerts),
+        };
+        (count > 0).then_some(count)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_icon_level_and_badge() {
+        let no_alerts = TrayAlerts::default();
+        let defaults = TrayIconRules::default();
+        assert_eq!(defaults.level(0, &no_alerts), IconLevel::Idle);
+        assert_eq!(defaults.level(3, &no_alerts), IconLevel::Busy);
+        assert_eq!(defaults.level(10, &no_alerts), IconLevel::Alert);
+        assert_eq!(defaults.badge(3, &no_alerts), None);
+
+        // Red only for a taken reservation, never for the count alone
+        let rules = TrayIconRules {
+            warn_at: 0,
+            alert_at: 0,
+            alert_on: vec![AlertTrigger::Guarded],
+            badge: BadgeMode::Alerts,
+            style: IconStyle::Color,
+        };
+        let alerts = TrayAlerts {
+            guarded: vec![3000],
+            suspicious: vec![4444],
+        };
+        assert_eq!(rules.level(25, &no_alerts), IconLevel::Idle);
+        assert_eq!(rules.badge(25, &no_alerts), None);
+        assert_eq!(rules.level(1, &alerts), IconLevel::Alert);
+        assert_eq!(rules.badge(1, &alerts), Some(1));
+
+        let counting = TrayIconRules {
+            badge: BadgeMode::Count,
+            ..TrayIconRules::default()
+        };
+        assert_eq!(counting.badge(0, &no_alerts), None);
+        assert_eq!(counting.badge(4, &alerts), Some(4));
+
+        // Only alerts that just appeared, for the configured triggers
+        assert_eq!(
+            alerts.new_since(&no_alerts, &rules.alert_on),
+            vec![(3000, "Reserved port taken")]
+        );
+        assert!(alerts.new_since(&alerts, &rules.alert_on).is_empty());
+    }
+
+    #[test]
+    fn test_menu_layout() {
+        let process = |port, container: Option<&str>| ProcessInfo {
+            pid: 100 + port as i32,
+            port,
+            command: "node".to_string(),
+            name: "node".to_string(),
+            container_id: container.map(str::to_string),
+            container_name: container.map(str::to_string),
+            command_line: None,
+            working_directory: None,
+            process_group: None,
+            project_name: None,
+            cpu_usage: None,
+            memory_usage: None,
+            memory_percentage: None,
+            protocol: crate::types::Protocol::Tcp,
+            bind_address: None,
+            address_family: None,
+        };
+        let processes: HashMap<u16, ProcessInfo> = [
+            process(3002, None),
+            process(3000, None),
+            process(3001, None),
+            process(5432, Some("db")),
+        ]
+        .into_iter()
+        .map(|process| (process.port, process))
+        .collect();
+
+        let layout = TrayMenuLayout {
+            sections: vec![MenuSection::Docker, MenuSection::Processes],
+            max_processes: 2,
+            show_pid: Some(true),
+        };
+        assert!(!layout.shows(MenuSection::System));
+        let local = layout.section_processes(MenuSection::Processes, &processes);
+        let ports: Vec<u16> = local.shown.iter().map(|(port, _)| *port).collect();
+        assert_eq!(ports, vec![3000, 3001]);
+        assert_eq!(local.hidden, 1);
+        let docker = layout.section_processes(MenuSection::Docker, &processes);
+        assert_eq!(docker.shown.len(), 1);
+        assert_eq!(docker.hidden, 0);
+        assert!(layout
+            .section_processes(MenuSection::History, &processes)
+            .shown
+            .is_empty());
+
+        assert_eq!(
+            "docker".parse::<MenuSection>().unwrap(),
+            MenuSection::Docker
+        );
+        assert!("pids".parse::<MenuSection>().is_err());
+    }
+
+    #[test]
+    fn test_pinned_ports() {
+        let process = |port, pid, name: &str| ProcessInfo {
+            pid,
+            port,
+            command: name.to_string(),
+            name: name.to_string(),
+            container_id: None,
+            container_name: None,
+            command_line: None,
+            working_directory: None,
+            process_group: None,
+            project_name: None,
+            cpu_usage: None,
+            memory_usage: None,
+            memory_percentage: None,
+            protocol: crate::types::Protocol::Tcp,
+            bind_address: None,
+            address_family: None,
+        };
+        let pins = [
+            PinnedPort {
+                port: 5173,
+                notify: PinNotify::Freed,
+            },
+            PinnedPort {
+                port: 3000,
+                notify: PinNotify::Both,
+            },
+            PinnedPort {
+                port: 8080,
+                notify: PinNotify::None,
+            },
+        ];
+        let before = HashMap::from([
+            (3000, process(3000, 1, "node")),
+            (5173, process(5173, 2, "vite")),
+        ]);
+        let after = HashMap::from([
+            (3000, process(3000, 3, "bun")),
+            (8080, process(8080, 4, "java")),
+        ]);
+
+        let statuses = PinnedStatus::collect(&pins, &after, &[5173], |port| {
+            (port == 5173).then(|| "vite".to_string())
+        });
+        let labels: Vec<String> = statuses.iter().map(|s| s.label(true)).collect();
+        assert_eq!(
+            labels,
+            vec![
+                ":3000 — bun (PID 3)",
+                ":5173 — free",
+                ":8080 — java (PID 4)"
+            ]
+        );
+        assert!(statuses[1].guarded);
+        assert_eq!(statuses[1].guard_for.as_deref(), Some("vite"));
+        assert_eq!(statuses[0].guard_for.as_deref(), Some("bun"));
+
+        // 3000 changed hands, 5173 was freed, 8080 never notifies
+        assert_eq!(
+            pin_notifications(&pins, &before, &after),
+            vec![":5173 is free (vite exited)", ":3000 taken by bun (PID 3)"]
+        );
+        assert!(pin_notifications(&pins, &after, &after).is_empty());
+        assert!("sometimes".parse::<PinNotify>().is_err());
+    }
+
+    #[test]
+    fn test_health_summary() {
+        let usage = |port, name: &str, cpu_percent, memory_mb: u64| ProcessUsage {
+            port,
+            name: name.to_string(),
+            cpu_percent,
+            memory_bytes: memory_mb * 1024 * 1024,
+        };
+        let health = HealthSummary {
+            system: SystemInfo {
+                total_memory: 16 * 1024 * 1024 * 1024,
+                used_memory: 12 * 1024 * 1024 * 1024,
+                total_swap: 0,
+                used_swap: 0,
+                cpu_count: 8,
+                load_average: sysinfo::LoadAvg {
+                    one: 1.416,
+                    five: 1.0,
+                    fifteen: 0.5,
+                },
+            },
+            monitored_ports: 12,
+            processes: vec![
+                usage(3000, "node", 12.34, 250),
+                usage(5432, "postgres", 0.5, 900),
+                usage(8000, "python3", 40.0, 80),
+                usage(6379, "redis-server", 0.1, 20),
+            ],
+        };
+        assert_eq!(
+            health.header(),
+            "Load 1.42 · Memory 75% · 12 ports monitored"
+        );
+        assert_eq!(
+            health.top_cpu(),
+            vec![
+                ":8000 python3 — 40.0% CPU",
+                ":3000 node — 12.3% CPU",
+                ":5432 postgres — 0.5% CPU"
+            ]
+        );
+        assert_eq!(health.top_memory()[0], ":5432 postgres — 900.0 MB");
+    }
+}
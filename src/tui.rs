@@ -0,0 +1,1275 @@
+//! `--tui`: a full-screen, sortable table of the monitored ports, for when the scrolling
+//! console output gets unwieldy (e.g. thousands of ports). Processes are killed,
+//! restarted and guarded with single keys while guard conflicts and kills show up in a
+//! log pane, and the selected process's CPU and memory are graphed below the table. A
+//! second tab starts, stops and restarts the services of `.port-kill.yaml`, a third
+//! browses the kill history and a fourth shows when each port was taken.
+
+use crate::cli::{Args, HistoryTime};
+use crate::config_reload::ConfigWatcher;
+use crate::daemon::DaemonClient;
+use crate::dashboard::{guard_events, reservation_snapshots, ReservationSnapshot};
+use crate::history_store::HistoryStore;
+use crate::orchestrator::{Orchestrator, ServiceState, ServiceStatus};
+use crate::port_activity::{timeline_axis, PortActivityLog, PortTimeline};
+use crate::port_guard::{
+    read_reservation_file, release_port_in_file, reserve_port_in_file, PortGuardDaemon,
+};
+use crate::process_monitor::ProcessMonitor;
+use crate::system_monitor::ResourceHistory;
+use crate::types::{HistoryFilter, PortReservation, ProcessHistory, ProcessInfo};
+use crate::user_config::UserConfig;
+use anyhow::Result;
+use chrono::{DateTime, Local, Utc};
+use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use crossterm::{cursor, execute, terminal};
+use ratatui::backend::CrosstermBackend;
+use ratatui::layout::{Constraint, Layout, Rect};
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::Line;
+use ratatui::widgets::{BarChart, Block, Borders, Paragraph, Row, Table, TableState, Tabs};
+use ratatui::{Frame, Terminal};
+use std::collections::{HashMap, VecDeque};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::mpsc::{unbounded_channel, UnboundedReceiver};
+use tokio::sync::Mutex;
+
+/// Events kept for the log pane
+const LOG_CAPACITY: usize = 200;
+/// Height of the log pane, borders included
+const LOG_HEIGHT: u16 = 10;
+/// Height of the selected process's details under the ports table, borders included
+const DETAIL_HEIGHT: u16 = 5;
+/// Height of the kills-per-hour chart of the history tab, borders included
+const HOUR_CHART_HEIGHT: u16 = 8;
+/// Kills a process needs on a port to be listed as a frequent offender
+const MIN_OFFENDER_KILLS: usize = 2;
+/// Longest window the timeline tab zooms out to
+const MAX_TIMELINE_HOURS: u32 = 24 * 30;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Tab {
+    Ports,
+    Services,
+    History,
+    Timeline,
+}
+
+impl Tab {
+    const ALL: [Tab; 4] = [Tab::Ports, Tab::Services, Tab::History, Tab::Timeline];
+
+    fn index(self) -> usize {
+        Self::ALL.iter().position(|tab| *tab == self).unwrap_or(0)
+    }
+
+    fn next(self) -> Self {
+        Self::ALL[(self.index() + 1) % Self::ALL.len()]
+    }
+
+    fn previous(self) -> Self {
+        Self::ALL[(self.index() + Self::ALL.len() - 1) % Self::ALL.len()]
+    }
+
+    fn title(self) -> &'static str {
+        match self {
+            Tab::Ports => "1 Ports",
+            Tab::Services => "2 Services",
+            Tab::History => "3 History",
+            Tab::Timeline => "4 Timeline",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum SortColumn {
+    Port,
+    Pid,
+    Name,
+    Cpu,
+    Memory,
+}
+
+impl SortColumn {
+    fn next(self) -> Self {
+        match self {
+            SortColumn::Port => SortColumn::Pid,
+            SortColumn::Pid => SortColumn::Name,
+            SortColumn::Name => SortColumn::Cpu,
+            SortColumn::Cpu => SortColumn::Memory,
+            SortColumn::Memory => SortColumn::Port,
+        }
+    }
+
+    fn title(self) -> &'static str {
+        match self {
+            SortColumn::Port => "PORT",
+            SortColumn::Pid => "PID",
+            SortColumn::Name => "PROCESS",
+            SortColumn::Cpu => "CPU",
+            SortColumn::Memory => "MEMORY",
+        }
+    }
+}
+
+/// What a key press asks for while the table has the keyboard
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum TuiCommand {
+    Up,
+    Down,
+    PageUp,
+    PageDown,
+    Top,
+    Bottom,
+    Kill,
+    Restart,
+    Guard,
+    Filter,
+    Sort,
+    Reverse,
+    Quit,
+}
+
+impl TuiCommand {
+    fn from_key(key: KeyEvent) -> Option<Self> {
+        if key.modifiers.contains(KeyModifiers::CONTROL) && key.code == KeyCode::Char('c') {
+            return Some(TuiCommand::Quit);
+        }
+        match key.code {
+            KeyCode::Up => Some(TuiCommand::Up),
+            KeyCode::Down => Some(TuiCommand::Down),
+            KeyCode::PageUp => Some(TuiCommand::PageUp),
+            KeyCode::PageDown => Some(TuiCommand::PageDown),
+            KeyCode::Home => Some(TuiCommand::Top),
+            KeyCode::End => Some(TuiCommand::Bottom),
+            KeyCode::Char('k') => Some(TuiCommand::Kill),
+            KeyCode::Char('r') => Some(TuiCommand::Restart),
+            KeyCode::Char('g') => Some(TuiCommand::Guard),
+            KeyCode::Char('/') => Some(TuiCommand::Filter),
+            KeyCode::Char('s') => Some(TuiCommand::Sort),
+            KeyCode::Char('S') => Some(TuiCommand::Reverse),
+            KeyCode::Char('q') | KeyCode::Esc => Some(TuiCommand::Quit),
+            _ => None,
+        }
+    }
+}
+
+/// What a key press asks for on the services tab
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ServiceCommand {
+    Up,
+    Down,
+    Start,
+    Stop,
+    Restart,
+    StartAll,
+    Quit,
+}
+
+impl ServiceCommand {
+    fn from_key(key: KeyEvent) -> Option<Self> {
+        if key.modifiers.contains(KeyModifiers::CONTROL) && key.code == KeyCode::Char('c') {
+            return Some(ServiceCommand::Quit);
+        }
+        match key.code {
+            KeyCode::Up | KeyCode::Char('k') => Some(ServiceCommand::Up),
+            KeyCode::Down | KeyCode::Char('j') => Some(ServiceCommand::Down),
+            KeyCode::Char('s') => Some(ServiceCommand::Start),
+            KeyCode::Char('x') => Some(ServiceCommand::Stop),
+            KeyCode::Char('r') => Some(ServiceCommand::Restart),
+            KeyCode::Char('a') => Some(ServiceCommand::StartAll),
+            KeyCode::Char('q') | KeyCode::Esc => Some(ServiceCommand::Quit),
+            _ => None,
+        }
+    }
+}
+
+/// Whether `filter` (case-insensitive) appears in the port, PID, name or project
+fn matches_filter(process: &ProcessInfo, filter: &str) -> bool {
+    if filter.is_empty() {
+        return true;
+    }
+    let filter = filter.to_lowercase();
+    process.port.to_string().contains(&filter)
+        || process.pid.to_string().contains(&filter)
+        || process.name.to_lowercase().contains(&filter)
+        || process
+            .project_name
+            .as_ref()
+            .is_some_and(|project| project.to_lowercase().contains(&filter))
+}
+
+/// The processes the table shows, in display order. Ties are broken by port.
+fn visible_processes<'a>(
+    processes: &'a HashMap<u16, ProcessInfo>,
+    filter: &str,
+    sort: SortColumn,
+    descending: bool,
+) -> Vec<&'a ProcessInfo> {
+    let mut visible: Vec<&ProcessInfo> = processes
+        .values()
+        .filter(|process| matches_filter(process, filter))
+        .collect();
+    visible.sort_by(|a, b| {
+        let order = match sort {
+            SortColumn::Port => a.port.cmp(&b.port),
+            SortColumn::Pid => a.pid.cmp(&b.pid),
+            SortColumn::Name => a.name.to_lowercase().cmp(&b.name.to_lowercase()),
+            SortColumn::Cpu => a.cpu_usage.unwrap_or(0.0).total_cmp(&b.cpu_usage.unwrap_or(0.0)),
+            SortColumn::Memory => a.memory_usage.cmp(&b.memory_usage),
+        };
+        let order = if descending { order.reverse() } else { order };
+        order.then(a.port.cmp(&b.port))
+    });
+    visible
+}
+
+/// The history tab's filter, e.g. `3000 since=7d until=2026-03-05`: a bare number is
+/// the port, and `since=`/`until=` take what `port-kill history` takes
+fn parse_history_filter(input: &str, now: DateTime<Utc>) -> Result<HistoryFilter, String> {
+    let mut filter = HistoryFilter::default();
+    for word in input.split_whitespace() {
+        if let Some(value) = word.strip_prefix("since=") {
+            filter.since = Some(HistoryTime::parse(value)?.start(now));
+        } else if let Some(value) = word.strip_prefix("until=") {
+            filter.until = Some(HistoryTime::parse(value)?.end(now));
+        } else {
+            let port = word
+                .trim_start_matches(':')
+                .parse::<u16>()
+                .ok()
+                .filter(|port| *port != 0)
+                .ok_or_else(|| format!("'{}' is not a port, since= or until=", word))?;
+            filter.port = Some(port);
+        }
+    }
+    if let (Some(since), Some(until)) = (filter.since, filter.until) {
+        if since >= until {
+            return Err("since is not before until".to_string());
+        }
+    }
+    Ok(filter)
+}
+
+fn format_memory(bytes: Option<u64>) -> String {
+    bytes.map_or_else(
+        || "-".to_string(),
+        |bytes| format!("{:.1} MB", bytes as f64 / 1024.0 / 1024.0),
+    )
+}
+
+/// Compact duration such as `45s`, `12m05s` or `3h20m`
+fn format_uptime(secs: u64) -> String {
+    match secs {
+        0..=59 => format!("{}s", secs),
+        60..=3599 => format!("{}m{:02}s", secs / 60, secs % 60),
+        _ => format!("{}h{:02}m", secs / 3600, (secs % 3600) / 60),
+    }
+}
+
+/// One line per service, with `>` marking the selected one
+fn service_rows(statuses: &[ServiceStatus], selected: usize) -> Vec<String> {
+    let name_width = statuses
+        .iter()
+        .map(|status| status.name.len())
+        .max()
+        .unwrap_or(0)
+        .max("SERVICE".len());
+    let mut rows = vec![format!(
+        "  {:<name_width$}  {:<10}  {:<6}  {:<7}  {:<7}  RESTARTS",
+        "SERVICE", "STATE", "PORT", "PID", "UPTIME"
+    )];
+    for (index, status) in statuses.iter().enumerate() {
+        let state = match (status.state, status.exit_code) {
+            (ServiceState::Running, _) => "running".to_string(),
+            (ServiceState::Stopped, _) => "stopped".to_string(),
+            (ServiceState::Exited, Some(code)) => format!("exited({})", code),
+            (ServiceState::Exited, None) => "exited".to_string(),
+        };
+        let dash = || "-".to_string();
+        rows.push(format!(
+            "{} {:<name_width$}  {:<10}  {:<6}  {:<7}  {:<7}  {}",
+            if index == selected { ">" } else { " " },
+            status.name,
+            state,
+            status.port.map_or_else(dash, |port| format!(":{}", port)),
+            status.pid.map_or_else(dash, |pid| pid.to_string()),
+            status.uptime_secs.map_or_else(dash, format_uptime),
+            status.restarts
+        ));
+    }
+    rows
+}
+
+/// The detail pane of the selected process: what it is, then its CPU and memory graphs
+fn detail_lines(process: &ProcessInfo, history: Option<&ResourceHistory>) -> Vec<String> {
+    let graph = |sparkline: Option<String>| sparkline.unwrap_or_else(|| "-".to_string());
+    vec![
+        format!(
+            "{} (PID {}) on port {}: {}",
+            process.name,
+            process.pid,
+            process.port,
+            process.command_line.as_deref().unwrap_or(&process.command)
+        ),
+        format!(
+            "CPU     {}  {}",
+            graph(history.and_then(|h| h.cpu_sparkline())),
+            process
+                .cpu_usage
+                .map_or_else(|| "-".to_string(), |cpu| format!("{:.1}%", cpu))
+        ),
+        format!(
+            "Memory  {}  {}",
+            graph(history.and_then(|h| h.memory_sparkline())),
+            format_memory(process.memory_usage)
+        ),
+    ]
+}
+
+struct TuiState {
+    tab: Tab,
+    processes: HashMap<u16, ProcessInfo>,
+    /// CPU and memory samples per PID, for the detail pane
+    resources: HashMap<i32, ResourceHistory>,
+    reservations: HashMap<u16, PortReservation>,
+    guard_snapshots: Vec<ReservationSnapshot>,
+    /// Kills up to this time are already in the log
+    kills_seen_until: DateTime<Utc>,
+    sort: SortColumn,
+    descending: bool,
+    filter: String,
+    editing_filter: bool,
+    /// Waiting for `y` before killing this one
+    confirm_kill: Option<ProcessInfo>,
+    table: TableState,
+    /// Rows that fit the table, for paging
+    page_size: usize,
+    log: VecDeque<String>,
+    services: Vec<ServiceStatus>,
+    selected_service: usize,
+    history: ProcessHistory,
+    /// What the history tab's filter was typed as
+    history_filter: String,
+    activity: PortActivityLog,
+    monitored_ports: Vec<u16>,
+    /// `--history-port`, when the timeline shows one port
+    timeline_port: Option<u16>,
+    timeline_hours: u32,
+    timelines: Vec<PortTimeline>,
+    timeline_until: DateTime<Utc>,
+}
+
+impl TuiState {
+    fn new(args: &Args, tab: Tab) -> Self {
+        Self {
+            tab,
+            processes: HashMap::new(),
+            resources: HashMap::new(),
+            reservations: HashMap::new(),
+            guard_snapshots: Vec::new(),
+            kills_seen_until: Utc::now(),
+            sort: SortColumn::Port,
+            descending: false,
+            filter: String::new(),
+            editing_filter: false,
+            confirm_kill: None,
+            table: TableState::default().with_selected(Some(0)),
+            page_size: 10,
+            log: VecDeque::new(),
+            services: Vec::new(),
+            selected_service: 0,
+            history: ProcessHistory::from_entries(Vec::new()),
+            history_filter: String::new(),
+            activity: PortActivityLog::load(),
+            monitored_ports: args.get_ports_to_monitor(),
+            timeline_port: args.history_port,
+            timeline_hours: args.timeline.unwrap_or(24),
+            timelines: Vec::new(),
+            timeline_until: Utc::now(),
+        }
+    }
+
+    fn log(&mut self, at: DateTime<Utc>, message: impl Into<String>) {
+        if self.log.len() == LOG_CAPACITY {
+            self.log.pop_front();
+        }
+        self.log.push_back(format!(
+            "{}  {}",
+            at.with_timezone(&Local).format("%H:%M:%S"),
+            message.into()
+        ));
+    }
+
+    fn visible(&self) -> Vec<&ProcessInfo> {
+        visible_processes(&self.processes, &self.filter, self.sort, self.descending)
+    }
+
+    fn selected(&self) -> Option<ProcessInfo> {
+        let index = self.table.selected()?;
+        self.visible().get(index).map(|process| (*process).clone())
+    }
+
+    /// Move the selection by `delta` rows, staying within the table
+    fn move_selection(&mut self, delta: isize) {
+        let last = self.visible().len().saturating_sub(1);
+        let current = self.table.selected().unwrap_or(0);
+        let next = current.saturating_add_signed(delta).min(last);
+        self.table.select(Some(next));
+    }
+
+    /// Pick up a new scan, the reservation file and kills made since the last refresh
+    fn update(&mut self, processes: HashMap<u16, ProcessInfo>, reservation_file: &Path) {
+        let now = Utc::now();
+        self.processes = processes;
+        self.reservations = read_reservation_file(reservation_file)
+            .map(|loaded| loaded.data)
+            .unwrap_or_default();
+
+        self.resources
+            .retain(|pid, _| self.processes.values().any(|process| process.pid == *pid));
+        for process in self.processes.values() {
+            self.resources
+                .entry(process.pid)
+                .or_default()
+                .record(process.cpu_usage, process.memory_usage);
+        }
+        if self.activity.record_scan(&self.monitored_ports, &self.processes, now) {
+            if let Err(e) = self.activity.save() {
+                self.log(now, format!("❌ Failed to save port activity: {}", e));
+            }
+        }
+
+        let snapshots = reservation_snapshots(&self.reservations, &self.processes);
+        for event in guard_events(&self.guard_snapshots, &snapshots, now) {
+            self.log(event.at, format!("🛡️  Port {}: {}", event.port, event.message));
+        }
+        self.guard_snapshots = snapshots;
+
+        // Other port-kill instances record their kills too
+        if let Ok(kills) = HistoryStore::open_default().and_then(|store| store.recent_kills(50)) {
+            let seen_until = self.kills_seen_until;
+            for entry in kills.iter().filter(|entry| entry.killed_at > seen_until) {
+                self.log(
+                    entry.killed_at,
+                    format!(
+                        "💀 {} (PID {}) on port {} killed by {}",
+                        entry.process_name, entry.pid, entry.port, entry.killed_by
+                    ),
+                );
+            }
+            if let Some(last) = kills.last() {
+                self.kills_seen_until = self.kills_seen_until.max(last.killed_at);
+            }
+        }
+        self.move_selection(0);
+        self.load_tab();
+    }
+
+    /// Reload what the current tab shows from the history store or the activity log
+    fn load_tab(&mut self) {
+        let now = Utc::now();
+        match self.tab {
+            Tab::History => match parse_history_filter(&self.history_filter, now) {
+                Ok(filter) => {
+                    match HistoryStore::open_default().and_then(|store| store.kills(&filter)) {
+                        Ok(history) => self.history = history,
+                        Err(e) => self.log(now, format!("❌ Failed to read the history: {}", e)),
+                    }
+                }
+                Err(e) => self.log(now, format!("❌ Invalid filter: {}", e)),
+            },
+            Tab::Timeline => {
+                let since = now - chrono::Duration::hours(self.timeline_hours as i64);
+                self.timelines = self.activity.timeline(self.timeline_port, since, now);
+                self.timeline_until = now;
+            }
+            Tab::Ports | Tab::Services => {}
+        }
+    }
+
+    fn show_tab(&mut self, tab: Tab) {
+        self.tab = tab;
+        self.editing_filter = false;
+        self.load_tab();
+    }
+}
+
+/// Raw mode on an alternate screen, restored when dropped
+pub(crate) struct TerminalGuard;
+
+impl TerminalGuard {
+    pub(crate) fn enter() -> Result<Self> {
+        terminal::enable_raw_mode()?;
+        execute!(
+            std::io::stdout(),
+            terminal::EnterAlternateScreen,
+            cursor::Hide
+        )?;
+        Ok(Self)
+    }
+}
+
+impl Drop for TerminalGuard {
+    fn drop(&mut self) {
+        let _ = execute!(
+            std::io::stdout(),
+            cursor::Show,
+            terminal::LeaveAlternateScreen
+        );
+        let _ = terminal::disable_raw_mode();
+    }
+}
+
+/// Key presses, read on their own thread since crossterm's reads block
+fn spawn_key_reader() -> UnboundedReceiver<KeyEvent> {
+    let (tx, keys) = unbounded_channel();
+    std::thread::spawn(move || {
+        while !tx.is_closed() {
+            if !event::poll(Duration::from_millis(200)).unwrap_or(false) {
+                continue;
+            }
+            if let Ok(Event::Key(key)) = event::read() {
+                if key.kind == KeyEventKind::Press && tx.send(key).is_err() {
+                    break;
+                }
+            }
+        }
+    });
+    keys
+}
+
+fn draw(frame: &mut Frame, state: &mut TuiState, ports: &str, services_config: &str) {
+    let [tabs_area, header, body, log_area, footer] = Layout::vertical([
+        Constraint::Length(1),
+        Constraint::Length(1),
+        Constraint::Min(5),
+        Constraint::Length(LOG_HEIGHT),
+        Constraint::Length(1),
+    ])
+    .areas(frame.size());
+
+    let bold = Style::default().add_modifier(Modifier::BOLD);
+    let tabs = Tabs::new(Tab::ALL.iter().map(|tab| tab.title()))
+        .select(state.tab.index())
+        .highlight_style(bold.add_modifier(Modifier::REVERSED));
+    frame.render_widget(tabs, tabs_area);
+
+    let (title, help) = match state.tab {
+        Tab::Ports => draw_ports(frame, state, ports, body),
+        Tab::Services => draw_services(frame, state, services_config, body),
+        Tab::History => draw_history(frame, state, body),
+        Tab::Timeline => draw_timeline(frame, state, body),
+    };
+
+    let log_lines: Vec<Line> = state
+        .log
+        .iter()
+        .skip(state.log.len().saturating_sub(LOG_HEIGHT as usize - 2))
+        .map(|line| Line::from(line.as_str()))
+        .collect();
+    let log = Paragraph::new(log_lines).block(Block::default().borders(Borders::ALL).title(" Events "));
+
+    let footer_text = if let Some(ref process) = state.confirm_kill {
+        format!(
+            "Kill {} (PID {}) on port {}? y/N",
+            process.name, process.pid, process.port
+        )
+    } else if state.editing_filter && state.tab == Tab::History {
+        format!("/{}▏  Enter apply · Esc clear", state.history_filter)
+    } else if state.editing_filter {
+        format!("/{}▏  Enter keep · Esc clear", state.filter)
+    } else {
+        format!("Tab view · {}", help)
+    };
+
+    frame.render_widget(Paragraph::new(title).style(bold), header);
+    frame.render_widget(log, log_area);
+    frame.render_widget(
+        Paragraph::new(footer_text).style(Style::default().fg(Color::Cyan)),
+        footer,
+    );
+}
+
+/// The ports table with the selected process's details below it. Returns the header
+/// and the key help.
+fn draw_ports(frame: &mut Frame, state: &mut TuiState, ports: &str, area: Rect) -> (String, &'static str) {
+    let [table_area, detail_area] =
+        Layout::vertical([Constraint::Min(5), Constraint::Length(DETAIL_HEIGHT)]).areas(area);
+
+    let visible = state.visible();
+    let mut title = format!(
+        "🚀 port-kill · {} · {} of {} in use · sorted by {} {}",
+        ports,
+        visible.len(),
+        state.processes.len(),
+        state.sort.title(),
+        if state.descending { "↓" } else { "↑" }
+    );
+    if !state.filter.is_empty() {
+        title.push_str(&format!(" · filter \"{}\"", state.filter));
+    }
+
+    let bold = Style::default().add_modifier(Modifier::BOLD);
+    let header_row = Row::new(["PORT", "PID", "PROCESS", "PROJECT", "CPU", "MEMORY", "GUARD"]).style(bold);
+    let rows: Vec<Row> = visible
+        .iter()
+        .map(|process| {
+            let guard = match state.reservations.get(&process.port) {
+                Some(reservation) if reservation.process_name != process.name => {
+                    format!("⚠ {}", reservation.project_name)
+                }
+                Some(reservation) => reservation.project_name.clone(),
+                None => String::new(),
+            };
+            Row::new([
+                process.port.to_string(),
+                process.pid.to_string(),
+                process.name.clone(),
+                process.project_name.clone().unwrap_or_default(),
+                process
+                    .cpu_usage
+                    .map_or_else(|| "-".to_string(), |cpu| format!("{:.1}%", cpu)),
+                format_memory(process.memory_usage),
+                guard,
+            ])
+        })
+        .collect();
+    let table = Table::new(
+        rows,
+        [
+            Constraint::Length(6),
+            Constraint::Length(8),
+            Constraint::Fill(2),
+            Constraint::Fill(2),
+            Constraint::Length(7),
+            Constraint::Length(10),
+            Constraint::Fill(1),
+        ],
+    )
+    .header(header_row)
+    .block(Block::default().borders(Borders::ALL).title(" Ports "))
+    .highlight_style(Style::default().add_modifier(Modifier::REVERSED))
+    .highlight_symbol("> ");
+    drop(visible);
+    state.page_size = table_area.height.saturating_sub(3).max(1) as usize;
+
+    let details: Vec<Line> = state
+        .selected()
+        .map(|process| detail_lines(&process, state.resources.get(&process.pid)))
+        .unwrap_or_default()
+        .into_iter()
+        .map(Line::from)
+        .collect();
+    let details = Paragraph::new(details).block(Block::default().borders(Borders::ALL).title(" Details "));
+
+    frame.render_stateful_widget(table, table_area, &mut state.table);
+    frame.render_widget(details, detail_area);
+    (
+        title,
+        "↑/↓ select · s sort · S reverse · / filter · k kill · r restart · g guard · q quit",
+    )
+}
+
+/// The services of the config file with their state, port, uptime and restarts
+fn draw_services(
+    frame: &mut Frame,
+    state: &TuiState,
+    services_config: &str,
+    area: Rect,
+) -> (String, &'static str) {
+    let lines: Vec<Line> = if state.services.is_empty() {
+        vec![Line::from(
+            "No services. Describe them in .port-kill.yaml (port-kill --init-config creates one).",
+        )]
+    } else {
+        service_rows(&state.services, state.selected_service)
+            .into_iter()
+            .map(Line::from)
+            .collect()
+    };
+    let running = state
+        .services
+        .iter()
+        .filter(|status| status.state == ServiceState::Running)
+        .count();
+    frame.render_widget(
+        Paragraph::new(lines).block(Block::default().borders(Borders::ALL).title(" Services ")),
+        area,
+    );
+    (
+        format!(
+            "🚀 Services from {} · {} of {} running",
+            services_config,
+            running,
+            state.services.len()
+        ),
+        "↑/↓ select · s start · x stop · r restart · a start all · q quit",
+    )
+}
+
+/// Recent kills and frequent offenders for the filter, and the kills per hour of day
+fn draw_history(frame: &mut Frame, state: &TuiState, area: Rect) -> (String, &'static str) {
+    let [tables, chart_area] =
+        Layout::vertical([Constraint::Min(5), Constraint::Length(HOUR_CHART_HEIGHT)]).areas(area);
+    let [kills_area, offenders_area] =
+        Layout::horizontal([Constraint::Fill(3), Constraint::Fill(2)]).areas(tables);
+    let bold = Style::default().add_modifier(Modifier::BOLD);
+
+    let shown = kills_area.height.saturating_sub(3) as usize;
+    let kills: Vec<Row> = state
+        .history
+        .get_recent_entries(shown)
+        .iter()
+        .rev()
+        .map(|entry| {
+            Row::new([
+                entry.killed_at.with_timezone(&Local).format("%m-%d %H:%M").to_string(),
+                entry.port.to_string(),
+                entry.process_name.clone(),
+                entry.killed_by.clone(),
+            ])
+        })
+        .collect();
+    let kills = Table::new(
+        kills,
+        [
+            Constraint::Length(11),
+            Constraint::Length(6),
+            Constraint::Fill(1),
+            Constraint::Length(8),
+        ],
+    )
+    .header(Row::new(["KILLED", "PORT", "PROCESS", "BY"]).style(bold))
+    .block(Block::default().borders(Borders::ALL).title(" Kills "));
+
+    let offenders: Vec<Row> = state
+        .history
+        .get_frequent_offenders(MIN_OFFENDER_KILLS)
+        .into_iter()
+        .map(|offender| {
+            Row::new([
+                offender.process_name,
+                offender.port.to_string(),
+                offender.kill_count.to_string(),
+            ])
+        })
+        .collect();
+    let offenders = Table::new(
+        offenders,
+        [Constraint::Fill(1), Constraint::Length(6), Constraint::Length(6)],
+    )
+    .header(Row::new(["PROCESS", "PORT", "KILLS"]).style(bold))
+    .block(Block::default().borders(Borders::ALL).title(" Frequent offenders "));
+
+    let patterns = state.history.get_time_patterns();
+    let labels: Vec<String> = (0..24).map(|hour| format!("{:02}", hour)).collect();
+    let hours: Vec<(&str, u64)> = labels
+        .iter()
+        .zip(0u32..)
+        .map(|(label, hour)| {
+            let count = patterns.hour_distribution.get(&hour).copied().unwrap_or(0);
+            (label.as_str(), count as u64)
+        })
+        .collect();
+    let chart = BarChart::default()
+        .data(hours.as_slice())
+        .bar_width(2)
+        .bar_gap(1)
+        .block(Block::default().borders(Borders::ALL).title(" Kills per hour of day "));
+
+    frame.render_widget(kills, kills_area);
+    frame.render_widget(offenders, offenders_area);
+    frame.render_widget(chart, chart_area);
+
+    let mut title = format!("📜 {} kills", state.history.len());
+    if !state.history_filter.is_empty() {
+        title.push_str(&format!(" · filter \"{}\"", state.history_filter));
+    }
+    (title, "/ filter (e.g. 3000 since=7d until=2026-03-05) · q quit")
+}
+
+/// When each port was taken and freed over the last `timeline_hours`
+fn draw_timeline(frame: &mut Frame, state: &TuiState, area: Rect) -> (String, &'static str) {
+    let hours = state.timeline_hours;
+    let mut lines: Vec<Line> = state
+        .timelines
+        .iter()
+        .map(|timeline| Line::from(timeline.render()))
+        .collect();
+    if lines.is_empty() {
+        lines.push(Line::from(format!(
+            "No port activity in the last {}h. Activity is recorded while port-kill is monitoring.",
+            hours
+        )));
+    } else {
+        let since = state.timeline_until - chrono::Duration::hours(hours as i64);
+        lines.push(Line::from(timeline_axis(since, state.timeline_until)));
+    }
+    frame.render_widget(
+        Paragraph::new(lines).block(Block::default().borders(Borders::ALL).title(" Timeline ")),
+        area,
+    );
+    (
+        format!("🕒 Port activity over the last {}h", hours),
+        "+ longer · - shorter · q quit",
+    )
+}
+
+/// Rescan, asking the daemon when one answered at startup and scanning with `monitor`
+/// once it stops answering
+async fn refresh(
+    monitor: &Mutex<ProcessMonitor>,
+    daemon: &mut Option<DaemonClient>,
+    args: &Args,
+    reservation_file: &Path,
+    state: &mut TuiState,
+) {
+    if let Some(client) = daemon.as_mut() {
+        match client.list(&state.monitored_ports, args.udp).await {
+            Ok((processes, _)) => {
+                state.update(processes, reservation_file);
+                return;
+            }
+            Err(e) => {
+                state.log(Utc::now(), format!("⚠️  Daemon stopped answering ({}), scanning directly", e));
+                *daemon = None;
+            }
+        }
+    }
+    let scanned = monitor.lock().await.scan_processes().await;
+    match scanned {
+        Ok(processes) => state.update(processes, reservation_file),
+        Err(e) => state.log(Utc::now(), format!("❌ Scan failed: {}", e)),
+    }
+}
+
+/// Tell the daemon, when there is one, that a kill or restart made here changed its ports
+async fn rescan(daemon: &mut Option<DaemonClient>, state: &mut TuiState) {
+    if let Some(client) = daemon.as_mut() {
+        if let Err(e) = client.rescan().await {
+            state.log(Utc::now(), format!("⚠️  Daemon stopped answering ({}), scanning directly", e));
+            *daemon = None;
+        }
+    }
+}
+
+/// Reserve the port of `process` for it, or drop the port's reservation when it has one
+async fn toggle_guard(
+    process: &ProcessInfo,
+    reservation_file: &Path,
+    guard: Option<&PortGuardDaemon>,
+    state: &TuiState,
+) -> Result<String> {
+    let message = if state.reservations.contains_key(&process.port) {
+        release_port_in_file(reservation_file, process.port)?;
+        format!("🔓 Released port {}", process.port)
+    } else {
+        let project = process.project_name.as_deref().unwrap_or(&process.name);
+        reserve_port_in_file(reservation_file, process.port, project, &process.name)?;
+        format!("🔒 Reserved port {} for {}", process.port, process.name)
+    };
+    if let Some(guard) = guard {
+        guard.reload_reservations().await?;
+    }
+    Ok(message)
+}
+
+/// Start, stop or restart the service `status` describes, or start them all. Returns
+/// what to tell the user; moving and quitting are left to the caller.
+async fn run_service_command(
+    orchestrator: &mut Orchestrator,
+    command: ServiceCommand,
+    status: &ServiceStatus,
+) -> Result<String> {
+    let name = &status.name;
+    let running = status.state == ServiceState::Running;
+    match command {
+        ServiceCommand::StartAll => orchestrator
+            .start_all()
+            .await
+            .map(|()| "✅ Started all services".to_string()),
+        ServiceCommand::Start if running => Ok(format!("{} is already running", name)),
+        ServiceCommand::Start => orchestrator
+            .start_service(name)
+            .await
+            .map(|()| format!("✅ Started {}", name)),
+        ServiceCommand::Stop if !running => Ok(format!("{} is not running", name)),
+        ServiceCommand::Stop => orchestrator
+            .stop_service(name)
+            .await
+            .map(|()| format!("🛑 Stopped {}", name)),
+        ServiceCommand::Restart => orchestrator
+            .restart_service(name)
+            .await
+            .map(|()| format!("🔄 Restarted {}", name)),
+        ServiceCommand::Up | ServiceCommand::Down | ServiceCommand::Quit => Ok(String::new()),
+    }
+}
+
+/// The directory service output goes to while the TUI is open
+pub fn service_log_dir() -> PathBuf {
+    UserConfig::default_path().with_file_name("logs")
+}
+
+/// The services of the config file, set up like `--up` sets them up: their output goes
+/// to log files so it does not draw over the TUI
+fn load_orchestrator(args: &Args, config_path: &Path) -> Result<Option<Orchestrator>> {
+    if !config_path.exists() {
+        return Ok(None);
+    }
+    let mut orchestrator = Orchestrator::load(config_path)?;
+    orchestrator.load_project_env(args.load_env);
+    if args.tmux {
+        orchestrator.run_in_tmux(args.tmux_session_name());
+    }
+    orchestrator.capture_output(service_log_dir());
+    Ok(Some(orchestrator))
+}
+
+async fn next_change(watcher: &mut Option<ConfigWatcher>) -> Option<Vec<PathBuf>> {
+    match watcher {
+        Some(watcher) => watcher.changed().await,
+        None => std::future::pending().await,
+    }
+}
+
+/// Run the TUI until `q`. Lists every `--scan-interval` seconds from a running
+/// `port-kill daemon`, or scans with `monitor` when there is none; a running `guard` is
+/// told about reservations made here. Kills and restarts are made here, with their
+/// history and restart information, and then the daemon is asked to rescan.
+/// `services` are the ones `--up --manage` started, and the TUI opens on their tab;
+/// otherwise those of the config file are loaded. `--timeline` opens it on the timeline
+/// tab. Services still running are stopped on the way out, and with `--watch-config`
+/// edits to the config file are applied as they are saved.
+pub async fn run_tui(
+    monitor: Arc<Mutex<ProcessMonitor>>,
+    args: &Args,
+    guard: Option<Arc<PortGuardDaemon>>,
+    services: Option<Orchestrator>,
+) -> Result<()> {
+    let reservation_file = PathBuf::from(args.get_reservation_file_path());
+    let ports = args.get_port_description();
+    let config_path = args.get_config_file_path();
+    let services_config = config_path.display().to_string();
+    let mut watcher = if args.watch_config {
+        Some(ConfigWatcher::new(vec![config_path.clone()])?)
+    } else {
+        None
+    };
+    let screen = TerminalGuard::enter()?;
+    let mut terminal = Terminal::new(CrosstermBackend::new(std::io::stdout()))?;
+    let mut keys = spawn_key_reader();
+    let mut scan = tokio::time::interval(Duration::from_secs(args.scan_interval.max(1)));
+    let tab = if services.is_some() {
+        Tab::Services
+    } else if args.timeline.is_some() {
+        Tab::Timeline
+    } else {
+        Tab::Ports
+    };
+    let mut state = TuiState::new(args, tab);
+    state.log(Utc::now(), format!("Monitoring {}", ports));
+    let mut daemon = if args.no_daemon {
+        None
+    } else {
+        DaemonClient::connect().await
+    };
+    if let Some(ref client) = daemon {
+        state.log(Utc::now(), format!("🛰️  Listing from the daemon on {}", client.socket()));
+    }
+    let mut orchestrator = match services {
+        Some(orchestrator) => Some(orchestrator),
+        None => load_orchestrator(args, &config_path).unwrap_or_else(|e| {
+            state.log(Utc::now(), format!("❌ No services: {:#}", e));
+            None
+        }),
+    };
+
+    loop {
+        if let Some(ref mut orchestrator) = orchestrator {
+            for name in orchestrator.reap_exited() {
+                state.log(Utc::now(), format!("⚠️  {} exited", name));
+            }
+            state.services = orchestrator.get_status();
+            state.selected_service = state
+                .selected_service
+                .min(state.services.len().saturating_sub(1));
+        }
+        terminal.draw(|frame| draw(frame, &mut state, &ports, &services_config))?;
+
+        tokio::select! {
+            key = keys.recv() => {
+                let Some(key) = key else {
+                    break;
+                };
+                if let Some(process) = state.confirm_kill.take() {
+                    if key.code != KeyCode::Char('y') {
+                        continue;
+                    }
+                    let killed = monitor.lock().await.kill_process(process.pid).await;
+                    if let Err(e) = killed {
+                        state.log(Utc::now(), format!("❌ Failed to kill PID {}: {}", process.pid, e));
+                    }
+                    rescan(&mut daemon, &mut state).await;
+                    refresh(&monitor, &mut daemon, args, &reservation_file, &mut state).await;
+                    continue;
+                }
+                if state.editing_filter {
+                    let filter = if state.tab == Tab::History {
+                        &mut state.history_filter
+                    } else {
+                        &mut state.filter
+                    };
+                    match key.code {
+                        KeyCode::Enter => state.editing_filter = false,
+                        KeyCode::Esc => {
+                            filter.clear();
+                            state.editing_filter = false;
+                        }
+                        KeyCode::Backspace => {
+                            filter.pop();
+                        }
+                        KeyCode::Char(c) => filter.push(c),
+                        _ => {}
+                    }
+                    if state.tab == Tab::History {
+                        // A half-typed filter does not parse, so apply it once it is done
+                        if !state.editing_filter {
+                            state.load_tab();
+                        }
+                    } else {
+                        state.table.select(Some(0));
+                    }
+                    continue;
+                }
+                match key.code {
+                    KeyCode::Tab => {
+                        state.show_tab(state.tab.next());
+                        continue;
+                    }
+                    KeyCode::BackTab => {
+                        state.show_tab(state.tab.previous());
+                        continue;
+                    }
+                    KeyCode::Char(c @ '1'..='4') => {
+                        state.show_tab(Tab::ALL[c as usize - '1' as usize]);
+                        continue;
+                    }
+                    _ => {}
+                }
+                match state.tab {
+                    Tab::Ports => {}
+                    Tab::Services => {
+                        let Some(command) = ServiceCommand::from_key(key) else {
+                            continue;
+                        };
+                        match command {
+                            ServiceCommand::Quit => break,
+                            ServiceCommand::Up => {
+                                state.selected_service = state.selected_service.saturating_sub(1);
+                            }
+                            ServiceCommand::Down => {
+                                state.selected_service = (state.selected_service + 1)
+                                    .min(state.services.len().saturating_sub(1));
+                            }
+                            _ => {
+                                let (Some(orchestrator), Some(status)) =
+                                    (orchestrator.as_mut(), state.services.get(state.selected_service))
+                                else {
+                                    continue;
+                                };
+                                let result = run_service_command(orchestrator, command, status).await;
+                                state.log(Utc::now(), result.unwrap_or_else(|e| format!("❌ {}", e)));
+                            }
+                        }
+                        continue;
+                    }
+                    Tab::History => {
+                        match key.code {
+                            KeyCode::Char('/') => state.editing_filter = true,
+                            KeyCode::Char('q') | KeyCode::Esc => break,
+                            KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => break,
+                            _ => {}
+                        }
+                        continue;
+                    }
+                    Tab::Timeline => {
+                        match key.code {
+                            KeyCode::Char('+') => {
+                                state.timeline_hours = (state.timeline_hours * 2).min(MAX_TIMELINE_HOURS);
+                            }
+                            KeyCode::Char('-') => state.timeline_hours = (state.timeline_hours / 2).max(1),
+                            KeyCode::Char('q') | KeyCode::Esc => break,
+                            KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => break,
+                            _ => {}
+                        }
+                        state.load_tab();
+                        continue;
+                    }
+                }
+                let Some(command) = TuiCommand::from_key(key) else {
+                    continue;
+                };
+                let page = state.page_size as isize;
+                match command {
+                    TuiCommand::Quit => break,
+                    TuiCommand::Up => state.move_selection(-1),
+                    TuiCommand::Down => state.move_selection(1),
+                    TuiCommand::PageUp => state.move_selection(-page),
+                    TuiCommand::PageDown => state.move_selection(page),
+                    TuiCommand::Top => state.table.select(Some(0)),
+                    TuiCommand::Bottom => state.move_selection(isize::MAX),
+                    TuiCommand::Filter => state.editing_filter = true,
+                    TuiCommand::Sort => state.sort = state.sort.next(),
+                    TuiCommand::Reverse => state.descending = !state.descending,
+                    TuiCommand::Kill => state.confirm_kill = state.selected(),
+                    TuiCommand::Restart => {
+                        let Some(process) = state.selected() else {
+                            continue;
+                        };
+                        let restarted = monitor.lock().await.restart_process_on_port(process.port).await;
+                        let message = match restarted {
+                            Ok(()) => format!("🔄 Restarted port {}", process.port),
+                            Err(e) => format!("❌ Failed to restart port {}: {}", process.port, e),
+                        };
+                        state.log(Utc::now(), message);
+                        rescan(&mut daemon, &mut state).await;
+                        refresh(&monitor, &mut daemon, args, &reservation_file, &mut state).await;
+                    }
+                    TuiCommand::Guard => {
+                        let Some(process) = state.selected() else {
+                            continue;
+                        };
+                        let toggled = toggle_guard(&process, &reservation_file, guard.as_deref(), &state).await;
+                        state.log(Utc::now(), toggled.unwrap_or_else(|e| format!("❌ {}", e)));
+                        refresh(&monitor, &mut daemon, args, &reservation_file, &mut state).await;
+                    }
+                }
+            }
+            changed = next_change(&mut watcher) => {
+                let Some(orchestrator) = orchestrator.as_mut().filter(|_| changed.is_some()) else {
+                    watcher = None;
+                    continue;
+                };
+                let message = match orchestrator.reload().await {
+                    Ok(changes) if changes.is_empty() => "🔄 Config reloaded".to_string(),
+                    Ok(changes) => format!("🔄 Applied config change: {}", changes.join(", ")),
+                    Err(e) => format!("❌ Ignoring config change: {}", e),
+                };
+                state.log(Utc::now(), message);
+            }
+            _ = scan.tick() => refresh(&monitor, &mut daemon, args, &reservation_file, &mut state).await,
+        }
+    }
+
+    drop(terminal);
+    drop(screen);
+    if let Some(mut orchestrator) = orchestrator {
+        if orchestrator
+            .get_status()
+            .iter()
+            .any(|status| status.state == ServiceState::Running)
+        {
+            println!("🛑 Stopping services...");
+            orchestrator.stop_all().await?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_visible_processes() {
+        let process = |port: u16, name: &str, cpu: Option<f64>| ProcessInfo {
+            pid: port as i32 * 10,
+            port,
+            command: name.to_string(),
+            name: name.to_string(),
+            container_id: None,
+            container_name: None,
+            command_line: None,
+            working_directory: None,
+            process_group: None,
+            project_name: (name == "node").then(|| "webshop".to_string()),
+            cpu_usage: cpu,
+            memory_usage: None,
+            memory_percentage: None,
+            protocol: crate::types::Protocol::Tcp,
+            bind_address: None,
+            address_family: None,
+        };
+        let processes: HashMap<u16, ProcessInfo> = [
+            process(3000, "node", Some(12.0)),
+            process(5432, "postgres", None),
+            process(8080, "Python", Some(40.0)),
+        ]
+        .into_iter()
+        .map(|process| (process.port, process))
+        .collect();
+        let ports = |visible: Vec<&ProcessInfo>| visible.iter().map(|p| p.port).collect::<Vec<_>>();
+
+        assert_eq!(ports(visible_processes(&processes, "", SortColumn::Port, false)), vec![3000, 5432, 8080]);
+        assert_eq!(ports(visible_processes(&processes, "", SortColumn::Cpu, true)), vec![8080, 3000, 5432]);
+        assert_eq!(ports(visible_processes(&processes, "", SortColumn::Name, false)), vec![3000, 5432, 8080]);
+        // Port, PID, name and project all match
+        assert_eq!(ports(visible_processes(&processes, "python", SortColumn::Port, false)), vec![8080]);
+        assert_eq!(ports(visible_processes(&processes, "WEBSHOP", SortColumn::Port, false)), vec![3000]);
+        assert_eq!(ports(visible_processes(&processes, "543", SortColumn::Port, false)), vec![5432]);
+        assert_eq!(SortColumn::Memory.next(), SortColumn::Port);
+        assert_eq!(Tab::Timeline.next(), Tab::Ports);
+        assert_eq!(Tab::Ports.previous(), Tab::Timeline);
+    }
+
+    #[test]
+    fn test_parse_history_filter() {
+        let now = Utc::now();
+        let filter = parse_history_filter("3000 since=2026-03-01 until=2026-03-05", now).unwrap();
+        assert_eq!(filter.port, Some(3000));
+        assert_eq!(filter.since.unwrap().to_rfc3339(), "2026-03-01T00:00:00+00:00");
+        assert_eq!(filter.until.unwrap().to_rfc3339(), "2026-03-06T00:00:00+00:00");
+        assert_eq!(
+            parse_history_filter("since=7d", now).unwrap().since,
+            Some(now - chrono::Duration::days(7))
+        );
+        assert_eq!(parse_history_filter("", now).unwrap(), HistoryFilter::default());
+        assert!(parse_history_filter("node", now).is_err());
+        assert!(parse_history_filter("since=2026-03-05 until=2026-03-01", now).is_err());
+    }
+
+    #[test]
+    fn test_service_rows() {
+        let status = |name: &str, state, pid, uptime_secs, restarts| ServiceStatus {
+            name: name.to_string(),
+            running: state == ServiceState::Running,
+            pid,
+            port: Some(3000),
+            command: "npm run dev".to_string(),
+            state,
+            exit_code: (state == ServiceState::Exited).then_some(1),
+            uptime_secs,
+            restarts,
+        };
+        let rows = service_rows(
+            &[
+                status("api", ServiceState::Exited, None, None, 2),
+                status("frontend", ServiceState::Running, Some(4242), Some(3725), 0),
+            ],
+            1,
+        );
+        assert_eq!(
+            rows,
+            vec![
+                "  SERVICE   STATE       PORT    PID      UPTIME   RESTARTS",
+                "  api       exited(1)   :3000   -        -        2",
+                "> frontend  running     :3000   4242     1h02m    0",
+            ]
+        );
+        assert_eq!(format_uptime(45), "45s");
+        assert_eq!(format_uptime(125), "2m05s");
+    }
+}
@@ -1,9 +1,16 @@
-use chrono::{DateTime, Datelike, Timelike, Utc};
+use chrono::{DateTime, Datelike, NaiveDate, Timelike, Utc};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::fs;
+use std::net::IpAddr;
 use std::path::Path;
 
+/// Runtimes whose process name says nothing about the app they run
+const GENERIC_PROCESS_NAMES: &[&str] = &[
+    "node", "nodejs", "bun", "deno", "python", "python3", "ruby", "java", "php", "dotnet",
+    "beam.smp", "npm", "npx", "yarn", "pnpm",
+];
+
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct ProcessInfo {
     pub pid: i32,
@@ -19,11 +26,108 @@ pub struct ProcessInfo {
     pub cpu_usage: Option<f64>,        // NEW: CPU usage percentage
     pub memory_usage: Option<u64>,     // NEW: Memory usage in bytes
     pub memory_percentage: Option<f64>, // NEW: Memory usage percentage
+    /// TCP listener, or a bound UDP socket found with `--udp`
+    #[serde(default)]
+    pub protocol: Protocol,
+    /// Address the socket is bound to: loopback, a single interface, or `0.0.0.0`/`::` for all
+    #[serde(default)]
+    pub bind_address: Option<IpAddr>,
+    #[serde(default)]
+    pub address_family: Option<AddressFamily>,
+}
+
+/// Transport of the socket a process holds its port with
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Protocol {
+    #[default]
+    Tcp,
+    Udp,
+}
+
+impl std::fmt::Display for Protocol {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Protocol::Tcp => write!(f, "TCP"),
+            Protocol::Udp => write!(f, "UDP"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AddressFamily {
+    Ipv4,
+    Ipv6,
+}
+
+impl AddressFamily {
+    pub fn of(address: &IpAddr) -> Self {
+        match address {
+            IpAddr::V4(_) => AddressFamily::Ipv4,
+            IpAddr::V6(_) => AddressFamily::Ipv6,
+        }
+    }
+}
+
+impl std::fmt::Display for AddressFamily {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AddressFamily::Ipv4 => write!(f, "IPv4"),
+            AddressFamily::Ipv6 => write!(f, "IPv6"),
+        }
+    }
+}
+
+/// Which bind addresses `--bind-addr` keeps
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BindFilter {
+    /// Loopback only (`127.0.0.1`, `::1`)
+    Localhost,
+    /// Reachable from other machines: all interfaces or a non-loopback address
+    Exposed,
+    Address(IpAddr),
+}
+
+impl BindFilter {
+    /// An unknown bind address never matches
+    pub fn matches(&self, bind_address: Option<IpAddr>) -> bool {
+        let Some(address) = bind_address else {
+            return false;
+        };
+        match self {
+            BindFilter::Localhost => address.is_loopback(),
+            BindFilter::Exposed => !address.is_loopback(),
+            BindFilter::Address(wanted) => address == *wanted,
+        }
+    }
+}
+
+impl std::str::FromStr for BindFilter {
+    type Err = String;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        match input.trim().to_lowercase().as_str() {
+            "localhost" | "loopback" => Ok(BindFilter::Localhost),
+            "exposed" | "public" => Ok(BindFilter::Exposed),
+            other => other
+                .trim_start_matches('[')
+                .trim_end_matches(']')
+                .parse()
+                .map(BindFilter::Address)
+                .map_err(|_| {
+                    format!(
+                        "Invalid bind address '{}' (use localhost, exposed or an IP address)",
+                        input
+                    )
+                }),
+        }
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    use super::ProcessInfo;
+    use super::{HistoryFilter, ProcessHistory, ProcessHistoryEntry, ProcessInfo};
 
     fn process_with_dir(dir: &str) -> ProcessInfo {
         ProcessInfo {
@@ -40,9 +144,31 @@ mod tests {
             cpu_usage: None,
             memory_usage: None,
             memory_percentage: None,
+            protocol: super::Protocol::Tcp,
+            bind_address: None,
+            address_family: None,
         }
     }
 
+    #[test]
+    fn test_bind_filter() {
+        use super::BindFilter;
+        let parse = |input: &str| input.parse::<BindFilter>().unwrap();
+        let loopback = Some("::1".parse().unwrap());
+        let wildcard = Some("0.0.0.0".parse().unwrap());
+        let lan = Some("192.168.1.5".parse().unwrap());
+
+        assert!(parse("localhost").matches(loopback));
+        assert!(!parse("localhost").matches(wildcard));
+        assert!(parse("exposed").matches(wildcard));
+        assert!(parse("exposed").matches(lan));
+        assert!(!parse("exposed").matches(loopback));
+        assert!(parse("[::1]").matches(loopback));
+        assert!(!parse("0.0.0.0").matches(lan));
+        assert!(!parse("exposed").matches(None));
+        assert!("nope".parse::<BindFilter>().is_err());
+    }
+
     #[cfg(not(target_os = "windows"))]
     #[test]
     fn test_extract_project_name_ignores_home_usernames() {
@@ -70,6 +196,111 @@ mod tests {
         );
     }
 
+    #[cfg(not(target_os = "windows"))]
+    #[test]
+    fn test_workspace_name_uses_repository_root() {
+        let base = std::env::temp_dir().join(format!("port-kill-workspace-{}", std::process::id()));
+        let repo = base.join("shop");
+        std::fs::create_dir_all(repo.join(".git")).unwrap();
+        std::fs::create_dir_all(repo.join("packages/api")).unwrap();
+        std::fs::create_dir_all(base.join("scratch")).unwrap();
+
+        let api = process_with_dir(repo.join("packages/api").to_str().unwrap());
+        assert_eq!(api.get_workspace_name(), Some("shop".to_string()));
+
+        let loose = process_with_dir(base.join("scratch").to_str().unwrap());
+        assert_eq!(loose.get_workspace_name(), Some("scratch".to_string()));
+
+        let _ = std::fs::remove_dir_all(&base);
+    }
+
+    #[cfg(not(target_os = "windows"))]
+    #[test]
+    fn test_friendly_name() {
+        let base = std::env::temp_dir().join(format!("port-kill-friendly-{}", std::process::id()));
+        let frontend = base.join("frontend");
+        let api = base.join("api");
+        std::fs::create_dir_all(frontend.join("src")).unwrap();
+        std::fs::create_dir_all(&api).unwrap();
+        // package.json is looked up to the repository root
+        std::fs::create_dir_all(base.join(".git")).unwrap();
+        std::fs::write(
+            frontend.join("package.json"),
+            r#"{"name": "webshop-frontend"}"#,
+        )
+        .unwrap();
+
+        let mut node = process_with_dir(frontend.join("src").to_str().unwrap());
+        node.command = "/usr/local/bin/node".to_string();
+        assert_eq!(node.friendly_name(), Some("node (webshop-frontend)".to_string()));
+
+        // Without a package.json the directory names the project
+        let mut python = process_with_dir(api.to_str().unwrap());
+        python.command = "python3".to_string();
+        assert_eq!(python.friendly_name(), Some("python3 (api)".to_string()));
+
+        // Names that already say what runs keep them
+        let mut postgres = process_with_dir(api.to_str().unwrap());
+        postgres.command = "postgres".to_string();
+        assert_eq!(postgres.friendly_name(), None);
+
+        let mut unknown = process_with_dir("/home");
+        unknown.command = "node".to_string();
+        assert_eq!(unknown.friendly_name(), None);
+
+        let _ = std::fs::remove_dir_all(&base);
+    }
+
+    #[test]
+    fn test_history_filter() {
+        let mut history = ProcessHistory::new(10);
+        for (port, killed_at) in [
+            (3000, "2026-03-01T09:00:00Z"),
+            (3000, "2026-03-05T23:30:00Z"),
+            (8080, "2026-03-05T10:00:00Z"),
+            (3000, "2026-03-06T00:10:00Z"),
+        ] {
+            let mut entry = ProcessHistoryEntry::new(&process_with_dir("/tmp"), "user".to_string());
+            entry.port = port;
+            entry.killed_at = killed_at.parse().unwrap();
+            history.add_entry(entry);
+        }
+
+        assert!(HistoryFilter::default().is_empty());
+        assert_eq!(history.filtered(&HistoryFilter::default()).len(), 4);
+
+        // Both ends of the date range are inclusive
+        let filter = HistoryFilter::days(
+            Some(3000),
+            "2026-03-02".parse().ok(),
+            "2026-03-05".parse().ok(),
+        );
+        let filtered = history.filtered(&filter);
+        assert_eq!(filtered.len(), 1);
+        assert_eq!(
+            filtered.get_recent_entries(1)[0].killed_at.to_rfc3339(),
+            "2026-03-05T23:30:00+00:00"
+        );
+        assert_eq!(filter.to_string(), "port 3000, 2026-03-02 to 2026-03-05");
+        assert_eq!(
+            HistoryFilter::days(None, "2026-03-02".parse().ok(), None).to_string(),
+            "since 2026-03-02"
+        );
+
+        let filter = HistoryFilter {
+            since: "2026-03-05T12:00:00Z".parse().ok(),
+            process: Some("TEST".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(history.filtered(&filter).len(), 2);
+        assert_eq!(filter.to_string(), "process TEST, since 2026-03-05 12:00 UTC");
+        let filter = HistoryFilter {
+            project: Some("elsewhere".to_string()),
+            ..Default::default()
+        };
+        assert!(history.filtered(&filter).is_empty());
+    }
+
     #[cfg(target_os = "windows")]
     #[test]
     fn test_extract_project_name_ignores_windows_usernames() {
@@ -98,7 +329,7 @@ impl ProcessUpdate {
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct StatusBarInfo {
     pub text: String,
     pub tooltip: String,
@@ -300,6 +531,47 @@ impl ProcessInfo {
         None
     }
 
+    /// Project the process belongs to for grouping: the git repository it runs in, so
+    /// the web, api and worker of one monorepo stay together, or its own project
+    /// directory outside a repository
+    pub fn get_workspace_name(&self) -> Option<String> {
+        let work_dir = Path::new(self.working_directory.as_ref()?);
+        let root = crate::project_dir::search_dirs(work_dir).pop()?;
+        if root.join(".git").exists() {
+            if let Some(name) = root.file_name().and_then(|name| name.to_str()) {
+                return Some(name.to_string());
+            }
+        }
+        self.project_name
+            .clone()
+            .or_else(|| self.extract_project_name())
+    }
+
+    /// `node (webshop-frontend)` for a generic runtime like `node` or `python`: the
+    /// `name` in the nearest package.json, or the project directory. `None` for other
+    /// processes and when the project is unknown.
+    pub fn friendly_name(&self) -> Option<String> {
+        let runtime = Path::new(&self.command)
+            .file_name()?
+            .to_str()?
+            .trim_end_matches(".exe")
+            .to_string();
+        if !GENERIC_PROCESS_NAMES.contains(&runtime.to_lowercase().as_str()) {
+            return None;
+        }
+
+        let package_name = self.working_directory.as_ref().and_then(|work_dir| {
+            let package_json = crate::project_dir::find_upward(Path::new(work_dir), &["package.json"])?;
+            let json: serde_json::Value =
+                serde_json::from_str(&fs::read_to_string(package_json).ok()?).ok()?;
+            json.get("name")?.as_str().map(String::from)
+        });
+        let project = package_name
+            .or_else(|| self.project_name.clone())
+            .or_else(|| self.extract_project_name())?;
+        Some(format!("{} ({})", runtime, project))
+    }
+
     /// Get the full project path context
     pub fn get_project_context(&self) -> Option<String> {
         if let Some(ref work_dir) = self.working_directory {
@@ -343,12 +615,28 @@ impl ProcessInfo {
             display_parts.push(format!("({})", group));
         }
 
-        // Add port context for clarity
-        display_parts.push(format!(":{}", self.port));
+        // Add port context for clarity; TCP is the default and goes unmarked
+        match self.protocol {
+            Protocol::Tcp => display_parts.push(format!(":{}", self.port)),
+            Protocol::Udp => display_parts.push(format!(":{}/udp", self.port)),
+        }
 
         display_parts.join(" ")
     }
 
+    /// Where the socket accepts connections from, e.g. "localhost (::1)" or
+    /// "all interfaces (0.0.0.0)"
+    pub fn bind_description(&self) -> Option<String> {
+        let address = self.bind_address?;
+        Some(if address.is_loopback() {
+            format!("localhost ({})", address)
+        } else if address.is_unspecified() {
+            format!("all interfaces ({})", address)
+        } else {
+            address.to_string()
+        })
+    }
+
     /// Get a short, clean process name for status display
     pub fn get_short_name(&self) -> String {
         // Extract just the executable name without path
@@ -374,7 +662,10 @@ impl ProcessInfo {
         let mut parts = Vec::new();
 
         // Process name and port
-        parts.push(format!("{} on port {}", self.get_short_name(), self.port));
+        match self.protocol {
+            Protocol::Tcp => parts.push(format!("{} on port {}", self.get_short_name(), self.port)),
+            Protocol::Udp => parts.push(format!("{} on UDP port {}", self.get_short_name(), self.port)),
+        }
 
         // Add command line if available and different from name
         if let Some(ref cmd_line) = self.command_line {
@@ -388,6 +679,10 @@ impl ProcessInfo {
             parts.push(format!("in {}", work_dir));
         }
 
+        if let Some(bind) = self.bind_description() {
+            parts.push(format!("[bound to {}]", bind));
+        }
+
         // Add container info
         if let (Some(_container_id), Some(container_name)) =
             (&self.container_id, &self.container_name)
@@ -562,6 +857,9 @@ pub enum RecommendationPriority {
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct RootCauseAnalysis {
+    /// Filled in from the history database and port activity by the caller
+    #[serde(default)]
+    pub anomalies: Vec<crate::anomaly::Anomaly>,
     pub conflicts: Vec<ProcessConflict>,
     pub patterns: Vec<WorkflowPattern>,
     pub recommendations: Vec<SmartRecommendation>,
@@ -706,6 +1004,96 @@ pub enum ProcessChangeType {
     UserChanged,
 }
 
+/// Narrows the kill history to a port, a process, a project and/or a time range
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct HistoryFilter {
+    pub port: Option<u16>,
+    /// Kills at or after this time
+    pub since: Option<DateTime<Utc>>,
+    /// Kills before this time
+    pub until: Option<DateTime<Utc>>,
+    /// Process name, case-insensitive
+    pub process: Option<String>,
+    pub project: Option<String>,
+}
+
+impl HistoryFilter {
+    /// A filter on a range of days (UTC, both ends inclusive)
+    pub fn days(port: Option<u16>, since: Option<NaiveDate>, until: Option<NaiveDate>) -> Self {
+        Self {
+            port,
+            since: since.map(start_of_day),
+            until: until.map(|day| start_of_day(day + chrono::Duration::days(1))),
+            ..Default::default()
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.port.is_none()
+            && self.since.is_none()
+            && self.until.is_none()
+            && self.process.is_none()
+            && self.project.is_none()
+    }
+
+    pub fn matches(&self, entry: &ProcessHistoryEntry) -> bool {
+        self.port.is_none_or(|port| entry.port == port)
+            && self.since.is_none_or(|since| entry.killed_at >= since)
+            && self.until.is_none_or(|until| entry.killed_at < until)
+            && self
+                .process
+                .as_ref()
+                .is_none_or(|process| entry.process_name.eq_ignore_ascii_case(process))
+            && self
+                .project
+                .as_ref()
+                .is_none_or(|project| entry.project_name.as_ref() == Some(project))
+    }
+}
+
+fn start_of_day(day: NaiveDate) -> DateTime<Utc> {
+    day.and_hms_opt(0, 0, 0).unwrap_or_default().and_utc()
+}
+
+/// `2026-03-05` for a midnight bound, `2026-03-05 14:30 UTC` otherwise
+fn format_bound(at: DateTime<Utc>) -> String {
+    if at.time() == chrono::NaiveTime::MIN {
+        at.format("%Y-%m-%d").to_string()
+    } else {
+        at.format("%Y-%m-%d %H:%M UTC").to_string()
+    }
+}
+
+impl std::fmt::Display for HistoryFilter {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut parts = Vec::new();
+        if let Some(port) = self.port {
+            parts.push(format!("port {}", port));
+        }
+        if let Some(process) = &self.process {
+            parts.push(format!("process {}", process));
+        }
+        if let Some(project) = &self.project {
+            parts.push(format!("project {}", project));
+        }
+        // A midnight `until` is shown as the last day it includes
+        let until = self.until.map(|until| {
+            if until.time() == chrono::NaiveTime::MIN {
+                format_bound(until - chrono::Duration::days(1))
+            } else {
+                format_bound(until)
+            }
+        });
+        match (self.since.map(format_bound), until) {
+            (Some(since), Some(until)) => parts.push(format!("{} to {}", since, until)),
+            (Some(since), None) => parts.push(format!("since {}", since)),
+            (None, Some(until)) => parts.push(format!("until {}", until)),
+            (None, None) => {}
+        }
+        write!(f, "{}", parts.join(", "))
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct ProcessHistory {
     entries: Vec<ProcessHistoryEntry>,
@@ -752,6 +1140,19 @@ impl ProcessHistory {
             .collect()
     }
 
+    /// The entries that pass `filter`, as a history of their own
+    pub fn filtered(&self, filter: &HistoryFilter) -> Self {
+        Self {
+            entries: self
+                .entries
+                .iter()
+                .filter(|entry| filter.matches(entry))
+                .cloned()
+                .collect(),
+            max_entries: self.max_entries,
+        }
+    }
+
     pub fn clear(&mut self) {
         self.entries.clear();
     }
@@ -764,12 +1165,16 @@ impl ProcessHistory {
         self.entries.is_empty()
     }
 
-    pub fn save_to_file(&self, file_path: &str) -> Result<(), Box<dyn std::error::Error>> {
-        let json = serde_json::to_string_pretty(&self.entries)?;
-        fs::write(file_path, json)?;
-        Ok(())
+    /// A history of exactly `entries`, oldest first, as read from the history database
+    pub fn from_entries(entries: Vec<ProcessHistoryEntry>) -> Self {
+        let max_entries = entries.len().max(1);
+        Self {
+            entries,
+            max_entries,
+        }
     }
 
+    /// Read the JSON file kills were kept in before the history database
     pub fn load_from_file(
         file_path: &str,
         max_entries: usize,
@@ -786,6 +1191,7 @@ impl ProcessHistory {
         }
     }
 
+    /// Path of the JSON history file, imported into the history database once
     pub fn get_history_file_path() -> String {
         let home_dir = std::env::var("HOME")
             .or_else(|_| std::env::var("USERPROFILE"))
@@ -1042,6 +1448,7 @@ impl ProcessHistory {
         let summary = self.generate_analysis_summary(&conflicts, &patterns, &recommendations);
 
         RootCauseAnalysis {
+            anomalies: Vec::new(),
             conflicts,
             patterns,
             recommendations,
@@ -1,8 +1,13 @@
+use crate::cli::UpdateChannel;
 use anyhow::Result;
 use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+use std::path::{Path, PathBuf};
 use std::time::{SystemTime, UNIX_EPOCH};
 
 const GITHUB_API_URL: &str = "https://api.github.com/repos/treadiehq/port-kill/releases/latest";
+// Every release, newest first, including pre-releases for the beta channel
+const GITHUB_RELEASES_URL: &str = "https://api.github.com/repos/treadiehq/port-kill/releases?per_page=20";
 const CHECK_INTERVAL_DAYS: u64 = 1; // Check for updates once per day
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -11,7 +16,12 @@ struct GitHubRelease {
     name: String,
     published_at: String,
     html_url: String,
-    body: String,
+    #[serde(default)]
+    body: Option<String>,
+    #[serde(default)]
+    prerelease: bool,
+    #[serde(default)]
+    draft: bool,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -22,42 +32,56 @@ pub struct UpdateInfo {
     release_url: String,
     release_notes: String,
     last_checked: u64,
+    tag_name: String,
+    prerelease: bool,
 }
 
-pub async fn check_for_updates(current_version: &str) -> Result<Option<UpdateInfo>> {
+pub async fn check_for_updates(
+    current_version: &str,
+    channel: UpdateChannel,
+) -> Result<Option<UpdateInfo>> {
     // Check if we should skip the update check (too recent)
-    if should_skip_check()? {
+    if should_skip_check(channel)? {
         return Ok(None);
     }
 
-    // Fetch latest release from GitHub
-    let latest_release = fetch_latest_release().await?;
-    let latest_version = latest_release.tag_name.trim_start_matches('v');
-
-    // Compare versions
-    let is_update_available = compare_versions(current_version, latest_version);
+    let update_info = find_update(current_version, channel).await?;
 
     // Update last check time
-    update_last_check_time()?;
+    update_last_check_time(channel)?;
 
-    if is_update_available {
-        Ok(Some(UpdateInfo {
-            current_version: current_version.to_string(),
-            latest_version: latest_version.to_string(),
-            is_update_available: true,
-            release_url: latest_release.html_url,
-            release_notes: latest_release.body,
-            last_checked: current_timestamp(),
-        }))
-    } else {
-        Ok(None)
+    Ok(update_info)
+}
+
+/// The newest release on `channel` when it is newer than `current_version`
+async fn find_update(current_version: &str, channel: UpdateChannel) -> Result<Option<UpdateInfo>> {
+    let latest_release = fetch_latest_release(channel).await?;
+    let latest_version = latest_release.tag_name.trim_start_matches('v');
+
+    if !is_newer(current_version, latest_version) {
+        return Ok(None);
     }
+
+    Ok(Some(UpdateInfo {
+        current_version: current_version.to_string(),
+        latest_version: latest_version.to_string(),
+        is_update_available: true,
+        release_url: latest_release.html_url,
+        release_notes: latest_release.body.unwrap_or_default(),
+        last_checked: current_timestamp(),
+        tag_name: latest_release.tag_name,
+        prerelease: latest_release.prerelease,
+    }))
 }
 
-async fn fetch_latest_release() -> Result<GitHubRelease> {
+async fn fetch_latest_release(channel: UpdateChannel) -> Result<GitHubRelease> {
+    let url = match channel {
+        UpdateChannel::Stable => GITHUB_API_URL,
+        UpdateChannel::Beta => GITHUB_RELEASES_URL,
+    };
     let client = reqwest::Client::new();
     let response = client
-        .get(GITHUB_API_URL)
+        .get(url)
         .header("User-Agent", "port-kill-update-checker")
         .send()
         .await?;
@@ -69,27 +93,187 @@ async fn fetch_latest_release() -> Result<GitHubRelease> {
         ));
     }
 
-    let release: GitHubRelease = response.json().await?;
-    Ok(release)
+    match channel {
+        UpdateChannel::Stable => Ok(response.json().await?),
+        UpdateChannel::Beta => {
+            let releases: Vec<GitHubRelease> = response.json().await?;
+            releases
+                .into_iter()
+                .filter(|release| !release.draft)
+                .max_by(|a, b| {
+                    compare_versions(
+                        a.tag_name.trim_start_matches('v'),
+                        b.tag_name.trim_start_matches('v'),
+                    )
+                })
+                .ok_or_else(|| anyhow::anyhow!("No releases found"))
+        }
+    }
+}
+
+/// A semantic version, e.g. `0.5.41` or `0.6.0-beta.2`
+#[derive(Debug, PartialEq, Eq)]
+struct Version {
+    core: [u64; 3],
+    pre: Vec<Identifier>,
+}
+
+/// A dot-separated part of a pre-release; numbers sort before words
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord)]
+enum Identifier {
+    Numeric(u64),
+    Alpha(String),
+}
+
+impl Version {
+    fn parse(version: &str) -> Option<Self> {
+        // Build metadata doesn't take part in precedence
+        let version = version.split('+').next()?;
+        let (core, pre) = match version.split_once('-') {
+            Some((core, pre)) => (core, Some(pre)),
+            None => (version, None),
+        };
+        let mut parts = core.split('.').map(|part| part.parse::<u64>());
+        let core = [parts.next()?.ok()?, parts.next()?.ok()?, parts.next()?.ok()?];
+        if parts.next().is_some() {
+            return None;
+        }
+        let pre = pre
+            .map(|pre| {
+                pre.split('.')
+                    .map(|part| match part.parse() {
+                        Ok(number) => Identifier::Numeric(number),
+                        Err(_) => Identifier::Alpha(part.to_string()),
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+        Some(Self { core, pre })
+    }
+}
+
+impl Ord for Version {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.core.cmp(&other.core).then_with(|| {
+            // A release is newer than its pre-releases
+            match (self.pre.is_empty(), other.pre.is_empty()) {
+                (true, true) => Ordering::Equal,
+                (true, false) => Ordering::Greater,
+                (false, true) => Ordering::Less,
+                (false, false) => self.pre.cmp(&other.pre),
+            }
+        })
+    }
+}
+
+impl PartialOrd for Version {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+fn compare_versions(a: &str, b: &str) -> Ordering {
+    match (Version::parse(a), Version::parse(b)) {
+        (Some(a), Some(b)) => a.cmp(&b),
+        (Some(_), None) => Ordering::Greater,
+        (None, Some(_)) => Ordering::Less,
+        (None, None) => a.cmp(b),
+    }
+}
+
+fn is_newer(current: &str, latest: &str) -> bool {
+    match (Version::parse(current), Version::parse(latest)) {
+        (Some(current), Some(latest)) => latest > current,
+        // Tags we can't parse are offered whenever they differ
+        _ => current != latest,
+    }
+}
+
+/// How this copy of port-kill was installed, so updates go through the package
+/// manager that owns the binary instead of overwriting it
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InstallMethod {
+    Homebrew,
+    Cargo,
+    Scoop,
+    /// Installed by the install script or by hand; updated in place
+    Standalone,
 }
 
-fn compare_versions(current: &str, latest: &str) -> bool {
-    // Simple version comparison (assumes semantic versioning)
-    // This is a basic implementation - could be enhanced with proper semver parsing
-    current != latest
+impl InstallMethod {
+    pub fn detect() -> Self {
+        let exe = match std::env::current_exe() {
+            Ok(exe) => exe.canonicalize().unwrap_or(exe),
+            Err(_) => return InstallMethod::Standalone,
+        };
+        let cargo_home = std::env::var_os("CARGO_HOME")
+            .map(PathBuf::from)
+            .or_else(|| {
+                std::env::var_os("HOME")
+                    .or_else(|| std::env::var_os("USERPROFILE"))
+                    .map(|home| PathBuf::from(home).join(".cargo"))
+            });
+        Self::from_path(&exe, cargo_home.as_deref())
+    }
+
+    fn from_path(exe: &Path, cargo_home: Option<&Path>) -> Self {
+        let path = exe.to_string_lossy().replace('\\', "/").to_lowercase();
+        if path.contains("/cellar/") || path.contains("/homebrew/") || path.contains("/linuxbrew/") {
+            InstallMethod::Homebrew
+        } else if path.contains("/scoop/apps/") || path.contains("/scoop/shims/") {
+            InstallMethod::Scoop
+        } else if cargo_home.is_some_and(|home| exe.starts_with(home.join("bin"))) {
+            InstallMethod::Cargo
+        } else {
+            InstallMethod::Standalone
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            InstallMethod::Homebrew => "Homebrew",
+            InstallMethod::Cargo => "cargo",
+            InstallMethod::Scoop => "Scoop",
+            InstallMethod::Standalone => "the install script",
+        }
+    }
+
+    /// The command that upgrades port-kill to `update_info`'s release, or `None`
+    /// when the package manager doesn't publish it (pre-releases on Homebrew and
+    /// Scoop) or port-kill updates itself.
+    fn upgrade_command(&self, update_info: &UpdateInfo) -> Option<Vec<String>> {
+        let command: &[&str] = match self {
+            InstallMethod::Homebrew if !update_info.prerelease => &["brew", "upgrade", "port-kill"],
+            InstallMethod::Scoop if !update_info.prerelease => &["scoop", "update", "port-kill"],
+            InstallMethod::Cargo => &["cargo", "install", "port-kill", "--version"],
+            _ => return None,
+        };
+        let mut command: Vec<String> = command.iter().map(|part| part.to_string()).collect();
+        if *self == InstallMethod::Cargo {
+            command.push(update_info.latest_version.clone());
+        }
+        Some(command)
+    }
 }
 
-fn should_skip_check() -> Result<bool> {
-    let last_check = get_last_check_time()?;
+fn should_skip_check(channel: UpdateChannel) -> Result<bool> {
+    let last_check = get_last_check_time(channel)?;
     let now = current_timestamp();
-    let days_since_check = (now - last_check) / (24 * 60 * 60);
+    let days_since_check = now.saturating_sub(last_check) / (24 * 60 * 60);
 
     Ok(days_since_check < CHECK_INTERVAL_DAYS)
 }
 
-fn get_last_check_time() -> Result<u64> {
+fn check_file_name(channel: UpdateChannel) -> &'static str {
+    match channel {
+        UpdateChannel::Stable => "last_update_check",
+        UpdateChannel::Beta => "last_update_check_beta",
+    }
+}
+
+fn get_last_check_time(channel: UpdateChannel) -> Result<u64> {
     let cache_dir = get_cache_dir()?;
-    let check_file = cache_dir.join("last_update_check");
+    let check_file = cache_dir.join(check_file_name(channel));
 
     if check_file.exists() {
         let content = std::fs::read_to_string(&check_file)?;
@@ -99,11 +283,11 @@ fn get_last_check_time() -> Result<u64> {
     }
 }
 
-fn update_last_check_time() -> Result<()> {
+fn update_last_check_time(channel: UpdateChannel) -> Result<()> {
     let cache_dir = get_cache_dir()?;
     std::fs::create_dir_all(&cache_dir)?;
 
-    let check_file = cache_dir.join("last_update_check");
+    let check_file = cache_dir.join(check_file_name(channel));
     std::fs::write(check_file, current_timestamp().to_string())?;
 
     Ok(())
@@ -123,15 +307,16 @@ fn current_timestamp() -> u64 {
         .as_secs()
 }
 
-pub async fn self_update() -> Result<()> {
+pub async fn self_update(channel: UpdateChannel) -> Result<()> {
     let current_version = env!("CARGO_PKG_VERSION");
 
-    // Check for updates
-    let update_info = match check_for_updates(current_version).await? {
+    // Always ask GitHub, even if the daily check ran recently
+    let update_info = match find_update(current_version, channel).await? {
         Some(info) => info,
         None => {
             println!(
-                "✅ You're already running the latest version ({})",
+                "✅ You're already running the latest {} version ({})",
+                channel.name(),
                 current_version
             );
             return Ok(());
@@ -143,13 +328,19 @@ pub async fn self_update() -> Result<()> {
         update_info.current_version, update_info.latest_version
     );
 
+    // Don't overwrite a binary a package manager owns; let it do the upgrade
+    let install_method = InstallMethod::detect();
+    if install_method != InstallMethod::Standalone {
+        return upgrade_with_package_manager(install_method, &update_info);
+    }
+
     // Get the current executable path
     let current_exe = std::env::current_exe()?;
     #[cfg(target_os = "windows")]
     let current_exe_path = current_exe.to_string_lossy().to_string();
 
     // Determine platform-specific download URL
-    let download_url = get_platform_download_url().await?;
+    let download_url = get_platform_download_url(&update_info.tag_name);
 
     // Download the new binary
     println!("📥 Downloading latest version...");
@@ -227,26 +418,48 @@ del "%~f0"
     Ok(())
 }
 
-async fn get_platform_download_url() -> Result<String> {
-    let _platform = if cfg!(target_os = "windows") {
-        "windows"
-    } else if cfg!(target_os = "macos") {
-        "macos"
-    } else {
-        "linux"
+fn upgrade_with_package_manager(install_method: InstallMethod, update_info: &UpdateInfo) -> Result<()> {
+    let Some(command) = install_method.upgrade_command(update_info) else {
+        println!(
+            "📦 port-kill was installed with {}, which only publishes stable releases.",
+            install_method.name()
+        );
+        println!("🔗 Download {} from: {}", update_info.latest_version, update_info.release_url);
+        return Ok(());
     };
+    let command_line = command.join(" ");
+    println!(
+        "📦 port-kill was installed with {}; running `{}`",
+        install_method.name(),
+        command_line
+    );
 
-    // Get the latest release info to construct the download URL
-    let client = reqwest::Client::new();
-    let response = client
-        .get(GITHUB_API_URL)
-        .header(reqwest::header::USER_AGENT, "port-kill-updater")
-        .send()
-        .await?;
-
-    let release: GitHubRelease = response.json().await?;
-    let tag_name = release.tag_name;
+    // Scoop is a PowerShell shim, so go through cmd on Windows
+    let mut upgrade = if cfg!(target_os = "windows") {
+        let mut upgrade = std::process::Command::new("cmd");
+        upgrade.arg("/C").args(&command);
+        upgrade
+    } else {
+        let mut upgrade = std::process::Command::new(&command[0]);
+        upgrade.args(&command[1..]);
+        upgrade
+    };
+    match upgrade.status() {
+        Ok(status) if status.success() => {
+            println!("✅ Update completed successfully!");
+            println!("🔗 Release notes: {}", update_info.release_url);
+            Ok(())
+        }
+        Ok(status) => Err(anyhow::anyhow!("`{}` failed with {}", command_line, status)),
+        Err(e) => {
+            println!("⚠️  Could not run {}: {}", command[0], e);
+            println!("📥 To update, run: {}", command_line);
+            Ok(())
+        }
+    }
+}
 
+fn get_platform_download_url(tag_name: &str) -> String {
     let binary_name = if cfg!(target_os = "windows") {
         "port-kill-windows.exe"
     } else if cfg!(target_os = "macos") {
@@ -255,10 +468,10 @@ async fn get_platform_download_url() -> Result<String> {
         "port-kill-linux"
     };
 
-    Ok(format!(
+    format!(
         "https://github.com/treadiehq/port-kill/releases/download/{}/{}",
         tag_name, binary_name
-    ))
+    )
 }
 
 pub fn print_update_notification(update_info: &UpdateInfo) {
@@ -269,7 +482,18 @@ pub fn print_update_notification(update_info: &UpdateInfo) {
     println!("Latest version:  {}", update_info.latest_version);
     println!();
     println!("📥 To update:");
-    println!("   curl -fsSL https://raw.githubusercontent.com/treadiehq/port-kill/main/install-release.sh | bash");
+    let install_method = InstallMethod::detect();
+    match install_method.upgrade_command(update_info) {
+        Some(command) => println!("   {}", command.join(" ")),
+        None if install_method == InstallMethod::Standalone => {
+            println!("   port-kill --self-update");
+            println!("   or: curl -fsSL https://raw.githubusercontent.com/treadiehq/port-kill/main/install-release.sh | bash");
+        }
+        None => println!(
+            "   {} only publishes stable releases; download this one from the release page",
+            install_method.name()
+        ),
+    }
     println!();
     println!("🔗 Release notes: {}", update_info.release_url);
     println!();
@@ -285,3 +509,55 @@ pub fn print_update_check_result(update_info: &UpdateInfo) {
         );
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_version_ordering() {
+        assert!(is_newer("0.5.9", "0.5.41"));
+        assert!(is_newer("0.5.41", "0.6.0-beta.1"));
+        assert!(is_newer("0.6.0-beta.1", "0.6.0-beta.2"));
+        assert!(is_newer("0.6.0-beta.2", "0.6.0"));
+        assert!(is_newer("0.6.0-alpha", "0.6.0-beta"));
+        assert!(is_newer("0.6.0-beta", "0.6.0-beta.1"));
+        assert!(!is_newer("0.5.41", "0.5.41"));
+        assert!(!is_newer("0.6.0", "0.5.41"));
+        assert!(!is_newer("0.6.0", "0.6.0-beta.3"));
+        assert!(!is_newer("0.5.41", "0.5.41+build.7"));
+    }
+
+    #[test]
+    fn test_install_method_detection() {
+        let cargo_home = Path::new("/home/dev/.cargo");
+        let detect = |path: &str| InstallMethod::from_path(Path::new(path), Some(cargo_home));
+        assert_eq!(detect("/opt/homebrew/Cellar/port-kill/0.5.41/bin/port-kill"), InstallMethod::Homebrew);
+        assert_eq!(detect("/home/linuxbrew/.linuxbrew/bin/port-kill"), InstallMethod::Homebrew);
+        assert_eq!(detect("/home/dev/.cargo/bin/port-kill"), InstallMethod::Cargo);
+        assert_eq!(detect("C:\\Users\\dev\\scoop\\apps\\port-kill\\current\\port-kill.exe"), InstallMethod::Scoop);
+        assert_eq!(detect("/usr/local/bin/port-kill"), InstallMethod::Standalone);
+        assert_eq!(detect("/home/dev/.local/bin/port-kill"), InstallMethod::Standalone);
+
+        let update = |version: &str, prerelease| UpdateInfo {
+            current_version: "0.5.41".to_string(),
+            latest_version: version.to_string(),
+            is_update_available: true,
+            release_url: String::new(),
+            release_notes: String::new(),
+            last_checked: 0,
+            tag_name: format!("v{}", version),
+            prerelease,
+        };
+        assert_eq!(
+            InstallMethod::Homebrew.upgrade_command(&update("0.5.42", false)).unwrap().join(" "),
+            "brew upgrade port-kill"
+        );
+        assert_eq!(InstallMethod::Homebrew.upgrade_command(&update("0.6.0-beta.1", true)), None);
+        assert_eq!(
+            InstallMethod::Cargo.upgrade_command(&update("0.6.0-beta.1", true)).unwrap().join(" "),
+            "cargo install port-kill --version 0.6.0-beta.1"
+        );
+        assert_eq!(InstallMethod::Standalone.upgrade_command(&update("0.5.42", false)), None);
+    }
+}
@@ -0,0 +1,830 @@
+use crate::cache::output::parse_size;
+use crate::cli::{Args, Command, LogFormat, LogLevel, UpdateChannel};
+use crate::quick_actions::{HotkeyAction, QuickAction};
+use crate::smart_filter::SmartFilterRules;
+use crate::tray_status::{AlertTrigger, BadgeMode, IconStyle, MenuSection, PinNotify, PinnedPort};
+use anyhow::{anyhow, Result};
+use clap::parser::ValueSource;
+use clap::{ArgMatches, ValueEnum};
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Persistent defaults read from `~/.port-kill/config.toml`.
+///
+/// Every setting is optional and only fills in flags that were not given on the
+/// command line or through the environment, so the precedence is
+/// CLI > environment > config file > built-in defaults.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct UserConfig {
+    /// Ports to monitor, as a list or a comma-separated string (ranges allowed)
+    pub ports: Option<ListSetting>,
+    pub start_port: Option<u16>,
+    pub end_port: Option<u16>,
+    pub ignore_ports: Option<ListSetting>,
+    pub ignore_processes: Option<ListSetting>,
+    pub ignore_patterns: Option<ListSetting>,
+    pub ignore_groups: Option<ListSetting>,
+    pub only_groups: Option<ListSetting>,
+    /// `smart_filter = true`, or a `[smart_filter]` table with extra rules
+    pub smart_filter: Option<SmartFilterSetting>,
+    pub docker: Option<bool>,
+    pub udp: Option<bool>,
+    pub show_pid: Option<bool>,
+    pub verbose: Option<bool>,
+    pub performance: Option<bool>,
+    pub show_context: Option<bool>,
+    pub friendly_names: Option<bool>,
+    /// One of info, warn, error, none
+    pub log_level: Option<String>,
+    /// Same as --log-file; `~/` is expanded
+    pub log_file: Option<String>,
+    /// text or json
+    pub log_format: Option<String>,
+    /// stable or beta
+    pub update_channel: Option<String>,
+    pub restart_timeout: Option<u64>,
+    /// Seconds between scans while ports are changing
+    pub scan_interval: Option<u64>,
+    /// Seconds between scans after backing off while nothing changes
+    pub max_scan_interval: Option<u64>,
+    /// Where monitoring results are sent (same as --monitor-endpoint and friends)
+    pub notifications: NotificationSettings,
+    pub tray: TraySettings,
+    pub cache: CacheSettings,
+}
+
+/// Notification sinks for long-running modes
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct NotificationSettings {
+    pub endpoint: Option<String>,
+    pub endpoint_auth: Option<String>,
+    pub send_interval: Option<u64>,
+    pub endpoint_retries: Option<u32>,
+    pub endpoint_timeout: Option<u64>,
+}
+
+/// Backup retention applied after every `cache --clean`, unless the flags override it
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct CacheSettings {
+    /// Keep only the N most recent backups
+    pub keep_backups: Option<usize>,
+    /// Maximum total size of all backups, e.g. "5GB"
+    pub max_backup_size: Option<String>,
+    /// Delete backups older than this many days
+    pub max_backup_age: Option<u32>,
+}
+
+/// Tray app settings
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct TraySettings {
+    /// Menu shortcuts: `reset`, `restart:<port>`, `start:<service>`, `audit`
+    pub quick_actions: Option<ListSetting>,
+    /// Process count that turns the icon orange (0 = never)
+    pub warn_at: Option<usize>,
+    /// Process count that turns the icon red (0 = never)
+    pub alert_at: Option<usize>,
+    /// Always turn red on `guarded` (reserved port taken) and/or `suspicious` ports
+    pub alert_on: Option<ListSetting>,
+    /// Number drawn on the icon: none, count or alerts
+    pub badge: Option<String>,
+    /// Icon drawing: adaptive (follows the menu bar appearance) or color
+    pub icon: Option<String>,
+    /// Menu sections in display order: system, pinned, processes, docker, history, actions
+    pub sections: Option<ListSetting>,
+    /// Processes listed per menu section before the rest is summarized (0 = all)
+    pub max_processes: Option<usize>,
+    /// Show PIDs in the menu regardless of the top-level `show_pid`
+    pub show_pid: Option<bool>,
+    /// Global shortcuts, e.g. `"alt+cmd+k" = "kill:3000"`
+    pub hotkeys: Option<BTreeMap<String, String>>,
+    /// Ports always shown in the menu, with when each notifies, e.g. `3000 = "freed"`
+    pub pins: Option<BTreeMap<String, String>>,
+}
+
+/// A table that only shows up in the file through its subtables, like `[tray.pins]`
+fn implicit_table() -> toml_edit::Item {
+    let mut table = toml_edit::Table::new();
+    table.set_implicit(true);
+    toml_edit::Item::Table(table)
+}
+
+/// Either a plain on/off switch or a `[smart_filter]` section
+#[derive(Debug, Clone, Deserialize)]
+#[serde(untagged)]
+pub enum SmartFilterSetting {
+    Enabled(bool),
+    Rules(Box<SmartFilterSection>),
+}
+
+/// Extends the built-in smart filter lists; `allow_*` entries drop built-in ignores
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct SmartFilterSection {
+    pub enabled: Option<bool>,
+    pub ignore_ports: Option<ListSetting>,
+    pub ignore_processes: Option<ListSetting>,
+    pub ignore_patterns: Option<ListSetting>,
+    pub ignore_groups: Option<ListSetting>,
+    pub allow_ports: Option<ListSetting>,
+    pub allow_processes: Option<ListSetting>,
+    pub allow_groups: Option<ListSetting>,
+}
+
+impl SmartFilterSection {
+    fn rules(&self) -> Result<SmartFilterRules> {
+        let values = |setting: &Option<ListSetting>| match setting {
+            Some(setting) => setting.values(),
+            None => Ok(Vec::new()),
+        };
+        let ports = |setting: &Option<ListSetting>| match setting {
+            Some(setting) => setting.ports(),
+            None => Ok(Vec::new()),
+        };
+        Ok(SmartFilterRules {
+            ignore_ports: ports(&self.ignore_ports)?,
+            ignore_processes: values(&self.ignore_processes)?,
+            ignore_patterns: values(&self.ignore_patterns)?,
+            ignore_groups: values(&self.ignore_groups)?,
+            allow_ports: ports(&self.allow_ports)?,
+            allow_processes: values(&self.allow_processes)?,
+            allow_groups: values(&self.allow_groups)?,
+        })
+    }
+}
+
+/// A list value written either as a TOML array or a comma-separated string
+#[derive(Debug, Clone, Deserialize)]
+#[serde(untagged)]
+pub enum ListSetting {
+    Text(String),
+    Items(Vec<toml::Value>),
+}
+
+impl ListSetting {
+    fn values(&self) -> Result<Vec<String>> {
+        match self {
+            ListSetting::Text(text) => Ok(text
+                .split(',')
+                .map(|item| item.trim().to_string())
+                .filter(|item| !item.is_empty())
+                .collect()),
+            ListSetting::Items(items) => items
+                .iter()
+                .map(|item| match item {
+                    toml::Value::String(s) => Ok(s.clone()),
+                    toml::Value::Integer(n) => Ok(n.to_string()),
+                    other => Err(anyhow!("Expected a string or number, found {}", other)),
+                })
+                .collect(),
+        }
+    }
+
+    fn ports(&self) -> Result<Vec<u16>> {
+        self.values()?
+            .iter()
+            .map(|value| {
+                value
+                    .parse::<u16>()
+                    .map_err(|_| anyhow!("Invalid port '{}'", value))
+            })
+            .collect()
+    }
+}
+
+impl UserConfig {
+    /// Default location of the config file
+    pub fn default_path() -> PathBuf {
+        let home = std::env::var("HOME")
+            .or_else(|_| std::env::var("USERPROFILE"))
+            .unwrap_or_else(|_| "/tmp".to_string());
+        PathBuf::from(home).join(".port-kill").join("config.toml")
+    }
+
+    /// Load the config file, returning an empty config when it does not exist
+    pub fn load(path: &Path) -> Result<Self> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let content = fs::read_to_string(path)?;
+        Self::parse(&content).map_err(|e| anyhow!("Invalid config {}: {}", path.display(), e))
+    }
+
+    pub fn parse(content: &str) -> Result<Self> {
+        Ok(toml::from_str(content)?)
+    }
+
+    /// Add `name` to `ignore_processes` in the config file at `path`, keeping the rest
+    /// of the file as written. Returns false when the process was already ignored.
+    pub fn add_ignored_process(path: &Path, name: &str) -> Result<bool> {
+        let mut names = match Self::load(path)?.ignore_processes {
+            Some(setting) => setting.values()?,
+            None => Vec::new(),
+        };
+        if names.iter().any(|ignored| ignored == name) {
+            return Ok(false);
+        }
+
+        let mut document = Self::load_document(path)?;
+        match document
+            .get_mut("ignore_processes")
+            .and_then(|item| item.as_array_mut())
+        {
+            Some(array) => array.push(name),
+            // Missing, or a comma-separated string: write the whole list as an array
+            None => {
+                names.push(name.to_string());
+                document["ignore_processes"] = toml_edit::value(toml_edit::Array::from_iter(names));
+            }
+        }
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(path, document.to_string())?;
+        Ok(true)
+    }
+
+    /// Pin `port` in `[tray.pins]` of the config file at `path`, notifying both when it is
+    /// taken and freed. Returns false when the port was already pinned.
+    pub fn pin_port(path: &Path, port: u16) -> Result<bool> {
+        let mut document = Self::load_document(path)?;
+        let tray = document
+            .entry("tray")
+            .or_insert(implicit_table())
+            .as_table_like_mut()
+            .ok_or_else(|| anyhow!("tray in {} is not a table", path.display()))?;
+        let pins = tray
+            .entry("pins")
+            .or_insert(toml_edit::table())
+            .as_table_like_mut()
+            .ok_or_else(|| anyhow!("tray.pins in {} is not a table", path.display()))?;
+        let key = port.to_string();
+        if pins.contains_key(&key) {
+            return Ok(false);
+        }
+        pins.insert(&key, toml_edit::value(PinNotify::Both.to_string()));
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(path, document.to_string())?;
+        Ok(true)
+    }
+
+    /// Remove `port` from `[tray.pins]`. Returns false when it was not pinned.
+    pub fn unpin_port(path: &Path, port: u16) -> Result<bool> {
+        let mut document = Self::load_document(path)?;
+        let removed = document
+            .get_mut("tray")
+            .and_then(|tray| tray.get_mut("pins"))
+            .and_then(|pins| pins.as_table_like_mut())
+            .and_then(|pins| pins.remove(&port.to_string()))
+            .is_some();
+        if removed {
+            fs::write(path, document.to_string())?;
+        }
+        Ok(removed)
+    }
+
+    /// The config file as an editable document that keeps comments and layout
+    fn load_document(path: &Path) -> Result<toml_edit::Document> {
+        let content = if path.exists() {
+            fs::read_to_string(path)?
+        } else {
+            String::new()
+        };
+        Ok(content.parse::<toml_edit::Document>()?)
+    }
+
+    /// Fill in every setting the user did not pass explicitly.
+    /// `matches` must come from parsing the same command line as `args`.
+    pub fn apply_to(&self, args: &mut Args, matches: &ArgMatches) -> Result<()> {
+        let unset = |id: &str| {
+            !matches!(
+                matches.value_source(id),
+                Some(ValueSource::CommandLine) | Some(ValueSource::EnvVariable)
+            )
+        };
+
+        // An explicit range or positional ports on the command line replace configured ports
+        let range_given = !unset("start_port") || !unset("end_port");
+        if let Some(ref ports) = self.ports {
+            if unset("ports") && !range_given && args.positional_ports.is_empty() {
+                args.ports = Some(ports.values()?);
+            }
+        }
+        if let Some(port) = self.start_port.filter(|_| unset("start_port")) {
+            args.start_port = port;
+        }
+        if let Some(port) = self.end_port.filter(|_| unset("end_port")) {
+            args.end_port = port;
+        }
+        if let Some(ref ports) = self.ignore_ports {
+            if unset("ignore_ports") {
+                args.ignore_ports = Some(ports.ports()?);
+            }
+        }
+
+        let lists = [
+            (
+                "ignore_processes",
+                &self.ignore_processes,
+                &mut args.ignore_processes,
+            ),
+            (
+                "ignore_patterns",
+                &self.ignore_patterns,
+                &mut args.ignore_patterns,
+            ),
+            (
+                "ignore_groups",
+                &self.ignore_groups,
+                &mut args.ignore_groups,
+            ),
+            ("only_groups", &self.only_groups, &mut args.only_groups),
+        ];
+        for (id, setting, target) in lists {
+            if let Some(setting) = setting {
+                if unset(id) {
+                    *target = Some(setting.values()?);
+                }
+            }
+        }
+
+        let smart_filter = match self.smart_filter {
+            Some(SmartFilterSetting::Enabled(enabled)) => Some(enabled),
+            Some(SmartFilterSetting::Rules(ref section)) => {
+                args.smart_filter_rules = section.rules()?;
+                section.enabled
+            }
+            None => None,
+        };
+        let flags = [
+            ("smart_filter", smart_filter, &mut args.smart_filter),
+            ("docker", self.docker, &mut args.docker),
+            ("udp", self.udp, &mut args.udp),
+            ("show_pid", self.show_pid, &mut args.show_pid),
+            ("verbose", self.verbose, &mut args.verbose),
+            ("performance", self.performance, &mut args.performance),
+            ("show_context", self.show_context, &mut args.show_context),
+            ("friendly_names", self.friendly_names, &mut args.friendly_names),
+        ];
+        for (id, setting, target) in flags {
+            if let Some(value) = setting.filter(|_| unset(id)) {
+                *target = value;
+            }
+        }
+
+        if let Some(ref level) = self.log_level {
+            if unset("log_level") {
+                args.log_level = LogLevel::from_str(level, true)
+                    .map_err(|_| anyhow!("Invalid log_level '{}'", level))?;
+            }
+        }
+        if let Some(path) = self.log_file.clone().filter(|_| unset("log_file")) {
+            args.log_file = Some(path);
+        }
+        if let Some(ref format) = self.log_format {
+            if unset("log_format") {
+                args.log_format = LogFormat::from_str(format, true)
+                    .map_err(|_| anyhow!("Invalid log_format '{}'", format))?;
+            }
+        }
+        if let Some(ref channel) = self.update_channel {
+            if unset("update_channel") {
+                args.update_channel = UpdateChannel::from_str(channel, true)
+                    .map_err(|_| anyhow!("Invalid update_channel '{}'", channel))?;
+            }
+        }
+        if let Some(timeout) = self.restart_timeout.filter(|_| unset("restart_timeout")) {
+            args.restart_timeout = timeout;
+        }
+        if let Some(interval) = self.scan_interval.filter(|_| unset("scan_interval")) {
+            args.scan_interval = interval;
+        }
+        if let Some(interval) = self.max_scan_interval.filter(|_| unset("max_scan_interval")) {
+            args.max_scan_interval = interval;
+        }
+
+        if let Some(Command::Op(cache)) = args.command.as_mut() {
+            if cache.keep_backups.is_none() {
+                cache.keep_backups = self.cache.keep_backups;
+            }
+            if let Some(ref size) = self.cache.max_backup_size {
+                if cache.max_backup_size.is_none() {
+                    let bytes =
+                        parse_size(size).map_err(|e| anyhow!("Invalid max_backup_size: {}", e))?;
+                    cache.max_backup_size = Some(bytes);
+                }
+            }
+            if cache.max_backup_age.is_none() {
+                cache.max_backup_age = self.cache.max_backup_age;
+            }
+        }
+
+        if let Some(ref actions) = self.tray.quick_actions {
+            let actions = actions
+                .values()?
+                .iter()
+                .map(|spec| spec.parse::<QuickAction>())
+                .collect::<Result<Vec<_>>>()?;
+            args.tray_quick_actions = Some(actions);
+        }
+        let icon_rules = &mut args.tray_icon_rules;
+        if let Some(warn_at) = self.tray.warn_at {
+            icon_rules.warn_at = warn_at;
+        }
+        if let Some(alert_at) = self.tray.alert_at {
+            icon_rules.alert_at = alert_at;
+        }
+        if let Some(ref alert_on) = self.tray.alert_on {
+            icon_rules.alert_on = alert_on
+                .values()?
+                .iter()
+                .map(|trigger| trigger.parse::<AlertTrigger>())
+                .collect::<Result<Vec<_>>>()?;
+        }
+        if let Some(ref badge) = self.tray.badge {
+            icon_rules.badge = badge.parse::<BadgeMode>()?;
+        }
+        if let Some(ref icon) = self.tray.icon {
+            icon_rules.style = icon.parse::<IconStyle>()?;
+        }
+        let layout = &mut args.tray_menu_layout;
+        if let Some(ref sections) = self.tray.sections {
+            let mut parsed = Vec::new();
+            for section in sections.values()? {
+                let section = section.parse::<MenuSection>()?;
+                if parsed.contains(&section) {
+                    return Err(anyhow!("Tray menu section '{}' is listed twice", section));
+                }
+                parsed.push(section);
+            }
+            layout.sections = parsed;
+        }
+        if let Some(max_processes) = self.tray.max_processes {
+            layout.max_processes = max_processes;
+        }
+        if self.tray.show_pid.is_some() {
+            layout.show_pid = self.tray.show_pid;
+        }
+        if let Some(ref pins) = self.tray.pins {
+            args.tray_pins = pins
+                .iter()
+                .map(|(port, notify)| {
+                    Ok(PinnedPort {
+                        port: port
+                            .parse()
+                            .map_err(|_| anyhow!("Invalid pinned port '{}'", port))?,
+                        notify: notify.parse()?,
+                    })
+                })
+                .collect::<Result<Vec<_>>>()?;
+        }
+        if let Some(ref hotkeys) = self.tray.hotkeys {
+            args.tray_hotkeys = hotkeys
+                .iter()
+                .map(|(shortcut, action)| Ok((shortcut.clone(), action.parse::<HotkeyAction>()?)))
+                .collect::<Result<Vec<_>>>()?;
+        }
+
+        let notifications = &self.notifications;
+        if let Some(ref endpoint) = notifications.endpoint {
+            if unset("monitor_endpoint") {
+                args.monitor_endpoint = Some(endpoint.clone());
+            }
+        }
+        if let Some(ref auth) = notifications.endpoint_auth {
+            if unset("endpoint_auth") {
+                args.endpoint_auth = Some(auth.clone());
+            }
+        }
+        if let Some(interval) = notifications
+            .send_interval
+            .filter(|_| unset("send_interval"))
+        {
+            args.send_interval = interval;
+        }
+        if let Some(retries) = notifications
+            .endpoint_retries
+            .filter(|_| unset("endpoint_retries"))
+        {
+            args.endpoint_retries = retries;
+        }
+        if let Some(timeout) = notifications
+            .endpoint_timeout
+            .filter(|_| unset("endpoint_timeout"))
+        {
+            args.endpoint_timeout = timeout;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::{CommandFactory, FromArgMatches, Parser};
+
+    fn layered(config: &str, argv: &[&str]) -> Args {
+        let matches = Args::command().get_matches_from(argv);
+        let mut args = Args::from_arg_matches(&matches).unwrap();
+        UserConfig::parse(config)
+            .unwrap()
+            .apply_to(&mut args, &matches)
+            .unwrap();
+        args
+    }
+
+    #[test]
+    fn test_config_fills_unset_flags() {
+        let config = r#"
+ports = [3000, "8000-8002"]
+smart_filter = true
+docker = true
+log_level = "warn"
+update_channel = "beta"
+log_format = "json"
+max_scan_interval = 60
+ignore_processes = "Chrome, rapportd"
+
+[notifications]
+endpoint = "https://example.com/hook"
+"#;
+        let args = layered(config, &["port-kill"]);
+        assert_eq!(
+            args.ports,
+            Some(vec!["3000".to_string(), "8000-8002".to_string()])
+        );
+        assert!(args.smart_filter);
+        assert!(args.docker);
+        assert_eq!(args.log_level, LogLevel::Warn);
+        assert_eq!(args.update_channel, UpdateChannel::Beta);
+        assert_eq!(args.log_format, LogFormat::Json);
+        assert_eq!(args.log_file, None);
+        assert_eq!((args.scan_interval, args.max_scan_interval), (2, 60));
+        assert_eq!(
+            args.ignore_processes,
+            Some(vec!["Chrome".to_string(), "rapportd".to_string()])
+        );
+        assert_eq!(
+            args.monitor_endpoint.as_deref(),
+            Some("https://example.com/hook")
+        );
+    }
+
+    #[test]
+    fn test_cli_wins_over_config() {
+        let config = "ports = \"3000\"\nlog_level = \"warn\"\n";
+        let args = layered(
+            config,
+            &["port-kill", "--ports", "9000", "--log-level", "error"],
+        );
+        assert_eq!(args.ports, Some(vec!["9000".to_string()]));
+        assert_eq!(args.log_level, LogLevel::Error);
+
+        let args = layered(config, &["port-kill", "--start-port", "4000"]);
+        assert_eq!(args.ports, None);
+    }
+
+    #[test]
+    fn test_cache_retention_from_config() {
+        let config = "[cache]\nkeep_backups = 5\nmax_backup_size = \"2GB\"\n";
+        let args = layered(config, &["port-kill", "cache", "--clean"]);
+        let retention = args.command.and_then(|c| c.args().cloned()).unwrap().retention_policy();
+        assert_eq!(retention.keep_last, Some(5));
+        assert_eq!(retention.max_total_bytes, Some(2 * 1024 * 1024 * 1024));
+        assert_eq!(retention.max_age_days, None);
+
+        let args = layered(config, &["port-kill", "cache", "--clean", "--keep-backups", "1"]);
+        let retention = args.command.and_then(|c| c.args().cloned()).unwrap().retention_policy();
+        assert_eq!(retention.keep_last, Some(1));
+    }
+
+    #[test]
+    fn test_smart_filter_section_extends_builtins() {
+        let config = r#"
+[smart_filter]
+enabled = true
+ignore_ports = [9229]
+ignore_processes = "Spotify"
+ignore_patterns = ["*helper*"]
+allow_ports = [5432]
+allow_processes = ["postgres"]
+allow_groups = ["Database"]
+"#;
+        let args = layered(config, &["port-kill"]);
+        assert!(args.smart_filter);
+        let (ports, processes, groups) = args.get_smart_filter_defaults();
+        assert!(ports.contains(&9229) && ports.contains(&22));
+        assert!(!ports.contains(&5432));
+        assert!(processes.contains("Spotify") && !processes.contains("postgres"));
+        assert!(!groups.contains("Database") && groups.contains("Web Server"));
+        assert_eq!(
+            args.get_ignore_patterns(),
+            Some(vec!["*helper*".to_string()])
+        );
+
+        // The plain switch still works, and --smart-filter can't be turned off by the table
+        assert!(layered("smart_filter = true\n", &["port-kill"]).smart_filter);
+        assert!(
+            layered(
+                "[smart_filter]\nignore_ports = [1]\n",
+                &["port-kill", "--smart-filter"]
+            )
+            .smart_filter
+        );
+        assert!(UserConfig::parse("[smart_filter]\nignore_port = [1]\n").is_err());
+    }
+
+    #[test]
+    fn test_tray_section() {
+        assert_eq!(
+            layered("", &["port-kill"]).get_tray_quick_actions(),
+            QuickAction::defaults()
+        );
+        let args = layered(
+            "[tray]\nquick_actions = [\"restart:3000\", \"start:web\"]\n",
+            &["port-kill"],
+        );
+        assert_eq!(
+            args.get_tray_quick_actions(),
+            vec![
+                QuickAction::Restart(3000),
+                QuickAction::StartService("web".to_string())
+            ]
+        );
+
+        let args = layered(
+            "[tray]\nwarn_at = 0\nalert_on = \"guarded, suspicious\"\nbadge = \"alerts\"\nicon = \"color\"\n",
+            &["port-kill"],
+        );
+        assert_eq!(args.tray_icon_rules.warn_at, 0);
+        assert_eq!(args.tray_icon_rules.alert_at, 10);
+        assert_eq!(
+            args.tray_icon_rules.alert_on,
+            vec![AlertTrigger::Guarded, AlertTrigger::Suspicious]
+        );
+        assert_eq!(args.tray_icon_rules.badge, BadgeMode::Alerts);
+        assert_eq!(args.tray_icon_rules.style, IconStyle::Color);
+
+        let args = layered(
+            "[tray.hotkeys]\n\"alt+cmd+k\" = \"kill:3000\"\n\"alt+cmd+l\" = \"list\"\n",
+            &["port-kill"],
+        );
+        assert_eq!(
+            args.tray_hotkeys,
+            vec![
+                ("alt+cmd+k".to_string(), HotkeyAction::Kill(3000)),
+                ("alt+cmd+l".to_string(), HotkeyAction::List)
+            ]
+        );
+
+        let args = layered(
+            "[tray]\nsections = \"processes, history\"\nmax_processes = 8\nshow_pid = true\n",
+            &["port-kill"],
+        );
+        assert_eq!(
+            args.tray_menu_layout.sections,
+            vec![MenuSection::Processes, MenuSection::History]
+        );
+        assert_eq!(args.tray_menu_layout.max_processes, 8);
+        assert_eq!(args.tray_menu_layout.show_pid, Some(true));
+        assert!(!args.show_pid);
+
+        let mut args = Args::parse_from(["port-kill"]);
+        let twice = UserConfig::parse("[tray]\nsections = [\"docker\", \"docker\"]\n").unwrap();
+        let matches = Args::command().get_matches_from(["port-kill"]);
+        assert!(twice.apply_to(&mut args, &matches).is_err());
+
+        let invalid = UserConfig::parse("[tray]\nquick_actions = \"deploy\"\n").unwrap();
+        let matches = Args::command().get_matches_from(["port-kill"]);
+        assert!(invalid.apply_to(&mut args, &matches).is_err());
+    }
+
+    #[test]
+    fn test_rejects_unknown_settings() {
+        assert!(UserConfig::parse("smart_filtr = true\n").is_err());
+        assert!(UserConfig::load(Path::new("/nonexistent/config.toml")).is_ok());
+    }
+
+    #[test]
+    fn test_add_ignored_process_keeps_the_file() {
+        let dir = std::env::temp_dir().join(format!(
+            "port-kill-config-{}",
+            std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .unwrap()
+                .as_nanos()
+        ));
+        let path = dir.join("config.toml");
+
+        // A missing file is created
+        assert!(UserConfig::add_ignored_process(&path, "rapportd").unwrap());
+        let ignored = |path: &Path| {
+            UserConfig::load(path)
+                .unwrap()
+                .ignore_processes
+                .unwrap()
+                .values()
+                .unwrap()
+        };
+        assert_eq!(ignored(&path), vec!["rapportd"]);
+
+        fs::write(
+            &path,
+            "# my settings\nignore_processes = \"Chrome, Slack\"\n\n[tray]\nbadge = \"count\"\n",
+        )
+        .unwrap();
+        assert!(UserConfig::add_ignored_process(&path, "node").unwrap());
+        assert!(!UserConfig::add_ignored_process(&path, "node").unwrap());
+        assert_eq!(ignored(&path), vec!["Chrome", "Slack", "node"]);
+        let content = fs::read_to_string(&path).unwrap();
+        assert!(content.starts_with("# my settings\n"));
+        assert!(content.contains("badge = \"count\""));
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_pin_ports() {
+        let dir = std::env::temp_dir().join(format!(
+            "port-kill-pins-{}",
+            std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .unwrap()
+                .as_nanos()
+        ));
+        let path = dir.join("config.toml");
+        let pins = |path: &Path| {
+            let matches = Args::command().get_matches_from(["port-kill"]);
+            let mut args = Args::from_arg_matches(&matches).unwrap();
+            UserConfig::load(path)
+                .unwrap()
+                .apply_to(&mut args, &matches)
+                .unwrap();
+            args.tray_pins
+        };
+
+        assert!(UserConfig::pin_port(&path, 3000).unwrap());
+        assert_eq!(
+            fs::read_to_string(&path).unwrap(),
+            "[tray.pins]\n3000 = \"both\"\n"
+        );
+
+        fs::write(
+            &path,
+            "[tray]\nbadge = \"count\"\n\n[tray.pins]\n5173 = \"freed\" # dev server\n",
+        )
+        .unwrap();
+        assert!(UserConfig::pin_port(&path, 3000).unwrap());
+        assert!(!UserConfig::pin_port(&path, 5173).unwrap());
+        assert_eq!(
+            pins(&path),
+            vec![
+                PinnedPort {
+                    port: 3000,
+                    notify: PinNotify::Both
+                },
+                PinnedPort {
+                    port: 5173,
+                    notify: PinNotify::Freed
+                },
+            ]
+        );
+        assert!(UserConfig::unpin_port(&path, 3000).unwrap());
+        assert!(!UserConfig::unpin_port(&path, 3000).unwrap());
+        assert!(fs::read_to_string(&path)
+            .unwrap()
+            .contains("5173 = \"freed\" # dev server"));
+
+        assert!(UserConfig::parse("[tray.pins]\n3000 = \"always\"\n")
+            .unwrap()
+            .apply_to(
+                &mut Args::parse_from(["port-kill"]),
+                &Args::command().get_matches_from(["port-kill"])
+            )
+            .is_err());
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_env_sits_between_cli_and_config() {
+        // Only this test reads PORT_KILL_ENDPOINT_RETRIES, so setting it is safe
+        std::env::set_var("PORT_KILL_ENDPOINT_RETRIES", "7");
+        let config = "[notifications]\nendpoint_retries = 5\n";
+        let from_env = layered(config, &["port-kill"]);
+        let from_cli = layered(config, &["port-kill", "--endpoint-retries", "1"]);
+        std::env::remove_var("PORT_KILL_ENDPOINT_RETRIES");
+        let from_config = layered(config, &["port-kill"]);
+
+        assert_eq!(from_env.endpoint_retries, 7);
+        assert_eq!(from_cli.endpoint_retries, 1);
+        assert_eq!(from_config.endpoint_retries, 5);
+    }
+}